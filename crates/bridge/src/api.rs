@@ -13,7 +13,8 @@ use std::sync::Arc;
 use tokio::net::TcpListener;
 use tracing::{debug, error, info};
 
-use crate::bridge::BridgeState;
+use crate::bridge::{BridgeState, ReplayOutcome};
+use crate::threads::ThreadRef;
 
 #[derive(Debug, Clone)]
 pub struct ApiServer {
@@ -27,6 +28,11 @@ pub struct SendRequest {
     pub content: String,
     #[serde(default = "default_kind")]
     pub kind: u16,
+    /// Event id (hex) this message replies to; adds NIP-10 thread tags
+    pub reply_to: Option<String>,
+    /// Caller's id (e.g. a ticket number), echoed on webhook payloads of
+    /// replies to this message
+    pub correlation_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -36,6 +42,22 @@ pub struct SendResponse {
     pub event_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread: Option<ThreadRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+}
+
+impl SendResponse {
+    fn failed(error: impl Into<String>, correlation_id: Option<String>) -> Self {
+        Self {
+            success: false,
+            event_id: None,
+            error: Some(error.into()),
+            thread: None,
+            correlation_id,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,6 +69,16 @@ pub struct EventsQuery {
     pub limit: i64,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ReplayRequest {
+    /// Unix seconds; cached events at or after this are re-delivered
+    pub since: i64,
+    /// Only this group's events; groups and DMs when absent
+    pub group: Option<String>,
+    #[serde(default = "default_replay_limit")]
+    pub limit: i64,
+}
+
 #[derive(Debug, Serialize)]
 pub struct EventResponse {
     pub id: String,
@@ -92,10 +124,20 @@ pub struct BridgeStatsResponse {
     pub subscribed_groups: Vec<String>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct GroupsResponse {
+    pub groups: Vec<crate::groups::GroupInfo>,
+    pub count: usize,
+}
+
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
     pub status: String,
     pub time: i64,
+    pub relays: Vec<nostr_core::RelayHealth>,
+    /// Backoff state per relay, when `adaptive_reconnect` is on
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reconnect: Vec<nostr_core::ReconnectState>,
 }
 
 fn default_kind() -> u16 {
@@ -106,6 +148,10 @@ fn default_limit() -> i64 {
     50
 }
 
+fn default_replay_limit() -> i64 {
+    1000
+}
+
 impl ApiServer {
     pub fn new(bind_address: String) -> Self {
         Self { bind_address }
@@ -116,7 +162,9 @@ impl ApiServer {
             .route("/send", post(handle_send))
             .route("/events", get(handle_events))
             .route("/events/:id", get(handle_event_by_id))
+            .route("/replay", post(handle_replay))
             .route("/stats", get(handle_stats))
+            .route("/groups", get(handle_groups))
             .route("/health", get(handle_health))
             .with_state(bridge_state);
 
@@ -139,78 +187,85 @@ async fn handle_send(
     Json(request): Json<SendRequest>,
 ) -> Result<Json<SendResponse>, StatusCode> {
     debug!("Send request: {:?}", request);
+    let correlation_id = request.correlation_id.clone();
 
     // Validate request
     if request.content.trim().is_empty() {
-        return Ok(Json(SendResponse {
-            success: false,
-            event_id: None,
-            error: Some("Content cannot be empty".to_string()),
-        }));
+        return Ok(Json(SendResponse::failed(
+            "Content cannot be empty",
+            correlation_id,
+        )));
     }
 
+    let reply_to = request.reply_to.as_deref();
     let result = if request.kind == 4 {
         // Direct message
         let recipient_str = match request.recipient {
             Some(r) => r,
             None => {
-                return Ok(Json(SendResponse {
-                    success: false,
-                    event_id: None,
-                    error: Some("Recipient required for direct messages".to_string()),
-                }));
+                return Ok(Json(SendResponse::failed(
+                    "Recipient required for direct messages",
+                    correlation_id,
+                )));
             }
         };
 
         let recipient = match PublicKey::from_hex(&recipient_str) {
             Ok(pk) => pk,
             Err(_) => {
-                return Ok(Json(SendResponse {
-                    success: false,
-                    event_id: None,
-                    error: Some("Invalid recipient public key".to_string()),
-                }));
+                return Ok(Json(SendResponse::failed(
+                    "Invalid recipient public key",
+                    correlation_id,
+                )));
             }
         };
 
         bridge
-            .send_direct_message(&recipient, &request.content)
+            .send_direct_message(
+                &recipient,
+                &request.content,
+                reply_to,
+                correlation_id.as_deref(),
+            )
             .await
     } else if request.kind == 9 {
         // Group message
         let group = match request.group {
             Some(g) => g,
             None => {
-                return Ok(Json(SendResponse {
-                    success: false,
-                    event_id: None,
-                    error: Some("Group required for group messages".to_string()),
-                }));
+                return Ok(Json(SendResponse::failed(
+                    "Group required for group messages",
+                    correlation_id,
+                )));
             }
         };
 
-        bridge.send_group_message(&group, &request.content).await
+        bridge
+            .send_group_message(
+                &group,
+                &request.content,
+                reply_to,
+                correlation_id.as_deref(),
+            )
+            .await
     } else {
-        return Ok(Json(SendResponse {
-            success: false,
-            event_id: None,
-            error: Some(format!("Unsupported message kind: {}", request.kind)),
-        }));
+        return Ok(Json(SendResponse::failed(
+            format!("Unsupported message kind: {}", request.kind),
+            correlation_id,
+        )));
     };
 
     match result {
-        Ok(event_id) => Ok(Json(SendResponse {
+        Ok((event_id, thread)) => Ok(Json(SendResponse {
             success: true,
             event_id: Some(event_id.to_hex()),
             error: None,
+            thread,
+            correlation_id,
         })),
         Err(e) => {
             error!("Failed to send message: {}", e);
-            Ok(Json(SendResponse {
-                success: false,
-                event_id: None,
-                error: Some(e.to_string()),
-            }))
+            Ok(Json(SendResponse::failed(e.to_string(), correlation_id)))
         }
     }
 }
@@ -339,6 +394,34 @@ async fn handle_event_by_id(
     }
 }
 
+/// Re-deliver cached events through the webhooks after a downstream outage.
+/// Responds 502 with the partial outcome when a delivery fails; repeat the
+/// call with `since` set to `last_created_at` once the receiver is back.
+async fn handle_replay(
+    State(bridge): State<Arc<BridgeState>>,
+    Json(request): Json<ReplayRequest>,
+) -> Result<(StatusCode, Json<ReplayOutcome>), StatusCode> {
+    debug!("Replay request: {:?}", request);
+
+    match bridge
+        .replay(request.since, request.group.as_deref(), request.limit)
+        .await
+    {
+        Ok(outcome) => {
+            let status = if outcome.error.is_some() {
+                StatusCode::BAD_GATEWAY
+            } else {
+                StatusCode::OK
+            };
+            Ok((status, Json(outcome)))
+        }
+        Err(e) => {
+            error!("Failed to replay events: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 async fn handle_stats(
     State(bridge): State<Arc<BridgeState>>,
 ) -> Result<Json<StatsResponse>, StatusCode> {
@@ -368,9 +451,27 @@ async fn handle_stats(
     }
 }
 
-async fn handle_health(State(_bridge): State<Arc<BridgeState>>) -> Json<HealthResponse> {
+async fn handle_groups(State(bridge): State<Arc<BridgeState>>) -> Json<GroupsResponse> {
+    let groups = bridge.groups.all().await;
+    Json(GroupsResponse {
+        count: groups.len(),
+        groups,
+    })
+}
+
+async fn handle_health(State(bridge): State<Arc<BridgeState>>) -> Json<HealthResponse> {
+    let relay = bridge.relay.read().await;
+    let relays = relay.health().await;
+    let reconnect = relay.reconnect_state();
+    let status = if relays.iter().any(|r| r.connected) {
+        "healthy"
+    } else {
+        "degraded"
+    };
     Json(HealthResponse {
-        status: "healthy".to_string(),
+        status: status.to_string(),
         time: chrono::Utc::now().timestamp(),
+        relays,
+        reconnect,
     })
 }
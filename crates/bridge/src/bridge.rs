@@ -1,5 +1,5 @@
 use anyhow::{Context, Result};
-use nostr_sdk::{EventId, Keys, PublicKey};
+use nostr_sdk::{Event, EventId, Keys, PublicKey};
 use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -9,22 +9,49 @@ use tracing::{debug, error, info, warn};
 
 use crate::cache::{CacheStats, EventCache};
 use crate::config::{Config, RespondMode};
-use crate::profiles::ProfileCache;
+use crate::groups::{self, GroupCache};
+use crate::profiles::{ProfileCache, ProfilePrefetcher};
 use crate::relay::{RelayClient, RelayEvent};
-use crate::webhook::WebhookDeliverer;
+use crate::threads::{CorrelationTracker, ThreadRef};
+use crate::webhook::{ModerationPayload, ModerationUser, WebhookDeliverer};
 use nostr_core::{
     detect_mentions, mentions_pubkey, sanitize_content_preview, ConversationRingBuffer,
     MessageEntry,
 };
 
+/// How often newly seen authors get their profiles fetched and subscribed.
+const PROFILE_FETCH_INTERVAL: Duration = Duration::from_secs(30);
+/// Re-fetch all tracked profiles before the 24h cache TTL expires them.
+const PROFILE_REFRESH_INTERVAL: Duration = Duration::from_secs(12 * 3600);
+/// Authors of cached messages from this many days back are prefetched.
+const RECENT_AUTHOR_DAYS: i64 = 7;
+const RECENT_AUTHOR_LIMIT: i64 = 500;
+
 pub struct BridgeState {
     pub config: Config,
     pub cache: EventCache,
     pub profiles: Arc<ProfileCache>,
+    pub prefetcher: ProfilePrefetcher,
+    pub groups: Arc<GroupCache>,
     pub relay: Arc<RwLock<RelayClient>>,
     pub webhook: WebhookDeliverer,
     pub start_time: Instant,
     pub ring_buffer: ConversationRingBuffer,
+    pub correlations: CorrelationTracker,
+}
+
+/// Outcome of [`BridgeState::replay`].
+#[derive(Debug, Default, serde::Serialize)]
+pub struct ReplayOutcome {
+    pub delivered: usize,
+    /// Filtered by respond mode, or not a deliverable event
+    pub skipped: usize,
+    /// `created_at` of the last event handled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_created_at: Option<i64>,
+    /// Delivery error that stopped the replay
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 pub struct Bridge {
@@ -40,25 +67,29 @@ impl Bridge {
 
         let profiles = Arc::new(ProfileCache::new());
 
-        let relay = RelayClient::new(&config.relay.url, keys)
-            .await
-            .with_context(|| "Failed to create relay client")?;
+        let relay = RelayClient::new(
+            &config.relay.url,
+            keys,
+            config.relay.proxy.as_deref(),
+            config.relay.adaptive_reconnect,
+        )
+        .await
+        .with_context(|| "Failed to create relay client")?;
 
-        let webhook = WebhookDeliverer::new(
-            config.webhook.url.clone(),
-            config.webhook.dm_url.clone(),
-            config.webhook.token.clone(),
-            config.webhook.preview_length,
-        );
+        let webhook = WebhookDeliverer::new(&config.webhook);
+        let relay_pubkey = Self::group_relay_pubkey(&config, &relay).await?;
 
         let state = Arc::new(BridgeState {
             config: config.clone(),
             cache,
             profiles,
+            prefetcher: ProfilePrefetcher::new(),
+            groups: Arc::new(GroupCache::new(relay_pubkey)),
             relay: Arc::new(RwLock::new(relay)),
             webhook,
             start_time: Instant::now(),
             ring_buffer: ConversationRingBuffer::new(50), // Default 50 messages per group
+            correlations: CorrelationTracker::new(),
         });
 
         let (shutdown_tx, _) = broadcast::channel(1);
@@ -66,6 +97,31 @@ impl Bridge {
         Ok(Bridge { state, shutdown_tx })
     }
 
+    /// The key group state must be signed with: `groups.relay_pubkey`, else
+    /// the relay's NIP-11 pubkey. Without either, group state and
+    /// moderation events are ignored.
+    async fn group_relay_pubkey(config: &Config, relay: &RelayClient) -> Result<Option<PublicKey>> {
+        if let Some(configured) = &config.groups.relay_pubkey {
+            let pubkey = PublicKey::parse(configured)
+                .with_context(|| format!("Invalid groups.relay_pubkey {configured}"))?;
+            return Ok(Some(pubkey));
+        }
+        if relay.is_proxied() {
+            warn!("Set groups.relay_pubkey to accept group metadata over the proxy");
+            return Ok(None);
+        }
+        match crate::relay::fetch_relay_pubkey(&config.relay.url).await {
+            Ok(pubkey) => {
+                info!("Group relay key: {}", pubkey.to_hex());
+                Ok(Some(pubkey))
+            }
+            Err(e) => {
+                warn!("{e:#}; set groups.relay_pubkey to accept group metadata");
+                Ok(None)
+            }
+        }
+    }
+
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting Nostr bridge");
 
@@ -111,6 +167,13 @@ impl Bridge {
             Self::event_processing_loop(state, event_rx, &mut shutdown_rx).await;
         });
 
+        // Start profile prefetch task
+        let state = self.state.clone();
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+        tokio::spawn(async move {
+            Self::profile_prefetch_loop(state, &mut shutdown_rx).await;
+        });
+
         // Start periodic maintenance task
         let state = self.state.clone();
         let mut shutdown_rx = self.shutdown_tx.subscribe();
@@ -186,77 +249,7 @@ impl Bridge {
                     )
                     .await?;
 
-                let author_name = state.profiles.get_display_name_hex(&author_hex).await;
-
-                // Phase 1: Content sanitization
-                let preview =
-                    sanitize_content_preview(&event.content, state.config.webhook.preview_length);
-
-                // Phase 1: Mention detection
-                let known_pubkeys = state.profiles.get_known_pubkeys().await;
-                let detected_mentions = detect_mentions(&event.content, &known_pubkeys);
-
-                // Phase 1: Check respond mode filtering
-                let respond_mode = state.config.get_group_respond_mode(&group);
-                let should_deliver_webhook = match respond_mode {
-                    RespondMode::All => true,
-                    RespondMode::None => false,
-                    RespondMode::Mentions => {
-                        // Check if our pubkey is mentioned
-                        let relay = state.relay.read().await;
-                        let our_pubkey = relay.our_pubkey().to_hex();
-                        drop(relay);
-                        mentions_pubkey(&detected_mentions, &our_pubkey)
-                    }
-                };
-
-                // Phase 1: Add to ring buffer for conversation context
-                let ring_entry = MessageEntry {
-                    author_pubkey: author_hex.clone(),
-                    author_display_name: author_name.clone(),
-                    content_preview: preview.clone(),
-                    timestamp: event.created_at.as_secs() as i64,
-                    event_id: event_id_hex.clone(),
-                };
-                state.ring_buffer.push(&group, ring_entry).await;
-
-                // Phase 1: Deliver webhook with enhanced data if appropriate
-                if should_deliver_webhook {
-                    let context = state.ring_buffer.get_context(&group, 15).await; // Last 15 messages
-                    let mentions = if detected_mentions.is_empty() {
-                        None
-                    } else {
-                        Some(detected_mentions)
-                    };
-
-                    state
-                        .webhook
-                        .deliver_group_message_enhanced(
-                            &event_id_hex,
-                            &group,
-                            &author_name,
-                            &preview,
-                            event.created_at.as_secs() as i64,
-                            Some(context),
-                            mentions,
-                        )
-                        .await?;
-
-                    info!(
-                        "#{} {} : {}",
-                        group,
-                        author_name,
-                        &preview[..preview.len().min(60)]
-                    );
-                } else {
-                    debug!(
-                        "#{} {} : {} (filtered by respond_mode: {})",
-                        group,
-                        author_name,
-                        &preview[..preview.len().min(60)],
-                        respond_mode
-                    );
-                }
+                state.forward_group_message(&event, &group, false).await?;
             }
             RelayEvent::DirectMessage { event } => {
                 let event_id_hex = event.id.to_hex();
@@ -281,38 +274,7 @@ impl Bridge {
                     )
                     .await?;
 
-                let author_name = state.profiles.get_display_name_hex(&author_hex).await;
-
-                // Phase 1: Content sanitization
-                let preview =
-                    sanitize_content_preview(&event.content, state.config.webhook.preview_length);
-
-                // Phase 1: Mention detection
-                let known_pubkeys = state.profiles.get_known_pubkeys().await;
-                let detected_mentions = detect_mentions(&event.content, &known_pubkeys);
-                let mentions = if detected_mentions.is_empty() {
-                    None
-                } else {
-                    Some(detected_mentions)
-                };
-
-                // DMs are always delivered (no respond mode filtering)
-                state
-                    .webhook
-                    .deliver_dm_enhanced(
-                        &event_id_hex,
-                        &author_name,
-                        &preview,
-                        event.created_at.as_secs() as i64,
-                        mentions,
-                    )
-                    .await?;
-
-                info!(
-                    "DM from {}: {}",
-                    author_name,
-                    &preview[..preview.len().min(60)]
-                );
+                state.forward_direct_message(&event).await?;
             }
             RelayEvent::ProfileUpdate { event } => {
                 let author_hex = event.pubkey.to_hex();
@@ -324,10 +286,133 @@ impl Bridge {
                     warn!("Failed to store profile for {}: {}", &author_hex[..8], e);
                 }
             }
+            RelayEvent::GroupMetadata { event } => {
+                if let Some(group) = state.groups.apply(&event).await {
+                    debug!("Updated #{} metadata (kind {})", group, event.kind.as_u16());
+                    if event.kind.as_u16() == groups::KIND_GROUP_MEMBERS {
+                        let members = state.groups.members(&group).await;
+                        state.prefetcher.track(members).await;
+                    }
+                }
+            }
+            RelayEvent::GroupModeration { event, group } => {
+                let event_id_hex = event.id.to_hex();
+                if state.cache.has_by_hex(&event_id_hex).await? {
+                    return Ok(());
+                }
+                if !state.groups.is_moderator(&group, &event.pubkey).await {
+                    warn!(
+                        "Ignoring kind {} in #{} from {}: not a group admin",
+                        event.kind.as_u16(),
+                        group,
+                        &event.pubkey.to_hex()[..8]
+                    );
+                    return Ok(());
+                }
+
+                state
+                    .cache
+                    .store_raw(
+                        &event_id_hex,
+                        &event.pubkey.to_hex(),
+                        event.created_at.as_secs() as i64,
+                        event.kind.as_u16() as i64,
+                        &serde_json::to_string(&event.tags)?,
+                        &event.content,
+                        &event.sig.to_string(),
+                        Some(&group),
+                    )
+                    .await?;
+
+                state.forward_moderation(&event, &group).await?;
+            }
         }
         Ok(())
     }
 
+    /// Track group members and recent authors, then keep their kind 0
+    /// profiles cached: new pubkeys are fetched and subscribed in batches,
+    /// and everything tracked is re-fetched before the cache TTL runs out.
+    async fn profile_prefetch_loop(
+        state: Arc<BridgeState>,
+        shutdown_rx: &mut broadcast::Receiver<()>,
+    ) {
+        let groups = state.config.groups.subscribe.clone();
+        let members = {
+            let relay = state.relay.read().await;
+            relay.fetch_group_members(&groups).await
+        };
+        match members {
+            Ok(events) => {
+                for event in &events {
+                    state.groups.apply(event).await;
+                }
+            }
+            Err(e) => warn!("Failed to fetch group members: {}", e),
+        }
+
+        let since = chrono::Utc::now().timestamp() - RECENT_AUTHOR_DAYS * 86_400;
+        let mut seed = Vec::new();
+        for group in &groups {
+            seed.extend(state.groups.members(group).await);
+            match state
+                .cache
+                .query(Some(group), None, Some(since), Some(RECENT_AUTHOR_LIMIT))
+                .await
+            {
+                Ok(events) => seed.extend(events.into_iter().map(|e| e.pubkey)),
+                Err(e) => warn!("Failed to read recent authors of #{}: {}", group, e),
+            }
+        }
+        let tracked = state.prefetcher.track(seed).await;
+        info!("Prefetching profiles for {} pubkeys", tracked);
+
+        let mut fetch_interval = interval(PROFILE_FETCH_INTERVAL);
+        let mut refresh_interval = interval(PROFILE_REFRESH_INTERVAL);
+        refresh_interval.tick().await;
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.recv() => break,
+                _ = fetch_interval.tick() => {
+                    let pending = state.prefetcher.take_pending().await;
+                    Self::prefetch_profiles(&state, &pending, true).await;
+                }
+                _ = refresh_interval.tick() => {
+                    let tracked = state.prefetcher.tracked().await;
+                    Self::prefetch_profiles(&state, &tracked, false).await;
+                }
+            }
+        }
+    }
+
+    async fn prefetch_profiles(state: &BridgeState, pubkeys: &[String], subscribe: bool) {
+        let keys: Vec<PublicKey> = pubkeys
+            .iter()
+            .filter_map(|hex| PublicKey::from_hex(hex).ok())
+            .collect();
+        if keys.is_empty() {
+            return;
+        }
+
+        let relay = state.relay.read().await;
+        match relay.fetch_profiles(&keys).await {
+            Ok(events) => {
+                for event in &events {
+                    if let Err(e) = state.profiles.store_profile(event).await {
+                        warn!("Failed to store profile for {}: {}", event.pubkey, e);
+                    }
+                }
+                debug!("Fetched {} of {} profiles", events.len(), keys.len());
+            }
+            Err(e) => warn!("Profile prefetch failed: {}", e),
+        }
+        if subscribe {
+            if let Err(e) = relay.subscribe_profiles(&keys).await {
+                warn!("Profile subscription failed: {}", e);
+            }
+        }
+    }
+
     async fn maintenance_loop(state: Arc<BridgeState>, shutdown_rx: &mut broadcast::Receiver<()>) {
         let mut cleanup_interval = interval(Duration::from_secs(3600));
         loop {
@@ -348,18 +433,313 @@ impl Bridge {
 }
 
 impl BridgeState {
-    pub async fn send_group_message(&self, group: &str, content: &str) -> Result<EventId> {
+    /// Send to a group, optionally as a reply to `reply_to` (event id hex).
+    /// `correlation_id` comes back on webhook payloads of replies to it.
+    pub async fn send_group_message(
+        &self,
+        group: &str,
+        content: &str,
+        reply_to: Option<&str>,
+        correlation_id: Option<&str>,
+    ) -> Result<(EventId, Option<ThreadRef>)> {
+        let thread = self.reply_thread(reply_to).await?;
         let relay = self.relay.read().await;
-        relay.send_group_message(group, content).await
+        let event_id = relay
+            .send_group_message(group, content, thread.as_ref())
+            .await?;
+        drop(relay);
+        self.record_correlation(&event_id, thread.as_ref(), correlation_id)
+            .await;
+        Ok((event_id, thread))
     }
 
+    /// As [`Self::send_group_message`], for a direct message.
     pub async fn send_direct_message(
         &self,
         recipient: &PublicKey,
         content: &str,
-    ) -> Result<EventId> {
+        reply_to: Option<&str>,
+        correlation_id: Option<&str>,
+    ) -> Result<(EventId, Option<ThreadRef>)> {
+        let thread = self.reply_thread(reply_to).await?;
         let relay = self.relay.read().await;
-        relay.send_dm(recipient, content).await
+        let event_id = relay.send_dm(recipient, content, thread.as_ref()).await?;
+        drop(relay);
+        self.record_correlation(&event_id, thread.as_ref(), correlation_id)
+            .await;
+        Ok((event_id, thread))
+    }
+
+    /// Thread for a reply to `reply_to`, continuing the parent's thread
+    /// when the parent is cached.
+    async fn reply_thread(&self, reply_to: Option<&str>) -> Result<Option<ThreadRef>> {
+        let Some(parent) = reply_to else {
+            return Ok(None);
+        };
+        let parent_id = EventId::from_hex(parent)
+            .with_context(|| format!("Invalid reply_to event id: {}", parent))?;
+        let parent_thread = match self.cache.get(&parent_id).await? {
+            Some(cached) => serde_json::from_str::<Vec<Vec<String>>>(&cached.tags)
+                .ok()
+                .and_then(|tags| ThreadRef::from_tags(tags.iter().map(Vec::as_slice))),
+            None => None,
+        };
+        Ok(Some(ThreadRef::replying_to(
+            &parent_id.to_hex(),
+            parent_thread.as_ref(),
+        )))
+    }
+
+    async fn record_correlation(
+        &self,
+        event_id: &EventId,
+        thread: Option<&ThreadRef>,
+        correlation_id: Option<&str>,
+    ) {
+        if let Some(correlation_id) = correlation_id {
+            self.correlations
+                .record(&event_id.to_hex(), thread, correlation_id)
+                .await;
+        }
+    }
+
+    /// Thread position of an incoming event and the correlation id of the
+    /// `/send` call it replies to.
+    async fn thread_of(&self, event: &Event) -> (Option<ThreadRef>, Option<String>) {
+        let thread = ThreadRef::from_tags(event.tags.iter().map(|tag| tag.as_slice()));
+        let correlation_id = match &thread {
+            Some(thread) => self.correlations.lookup(thread).await,
+            None => None,
+        };
+        (thread, correlation_id)
+    }
+
+    /// Deliver a cached group message to its webhook unless its group's
+    /// respond mode filters it. Returns whether it was delivered.
+    async fn forward_group_message(
+        &self,
+        event: &Event,
+        group: &str,
+        replay: bool,
+    ) -> Result<bool> {
+        let event_id_hex = event.id.to_hex();
+        let author_hex = event.pubkey.to_hex();
+        self.prefetcher.track([author_hex.clone()]).await;
+        let author = self.profiles.author_profile(&author_hex).await;
+        let author_name = author.display_name.clone();
+
+        // Phase 1: Content sanitization
+        let preview = sanitize_content_preview(
+            &event.content,
+            self.webhook.group_target(group).preview_length,
+        );
+
+        // Phase 1: Mention detection
+        let known_pubkeys = self.profiles.get_known_pubkeys().await;
+        let detected_mentions = detect_mentions(&event.content, &known_pubkeys);
+
+        // Phase 1: Check respond mode filtering
+        let respond_mode = self.config.get_group_respond_mode(group);
+        let should_deliver_webhook = match respond_mode {
+            RespondMode::All => true,
+            RespondMode::None => false,
+            RespondMode::Mentions => {
+                // Check if our pubkey is mentioned
+                let relay = self.relay.read().await;
+                let our_pubkey = relay.our_pubkey().to_hex();
+                drop(relay);
+                mentions_pubkey(&detected_mentions, &our_pubkey)
+            }
+        };
+
+        // Phase 1: Add to ring buffer for conversation context (a replayed
+        // event is already in it, or too old to matter)
+        if !replay {
+            let ring_entry = MessageEntry {
+                author_pubkey: author_hex.clone(),
+                author_display_name: author_name.clone(),
+                content_preview: preview.clone(),
+                timestamp: event.created_at.as_secs() as i64,
+                event_id: event_id_hex.clone(),
+            };
+            self.ring_buffer.push(group, ring_entry).await;
+        }
+
+        // Phase 1: Deliver webhook with enhanced data if appropriate
+        if should_deliver_webhook {
+            // Last 15 messages; the buffer has moved on by the time of a replay
+            let context = if replay {
+                None
+            } else {
+                Some(self.ring_buffer.get_context(group, 15).await)
+            };
+            let mentions = if detected_mentions.is_empty() {
+                None
+            } else {
+                Some(detected_mentions)
+            };
+
+            let group_name = self.groups.display_name(group).await;
+            let (thread, correlation_id) = self.thread_of(event).await;
+            self.webhook
+                .deliver_group_message_enhanced(
+                    &event_id_hex,
+                    group,
+                    group_name.as_deref(),
+                    author,
+                    &preview,
+                    event.created_at.as_secs() as i64,
+                    context,
+                    mentions,
+                    thread,
+                    correlation_id,
+                )
+                .await?;
+
+            info!(
+                "#{} {} : {}",
+                group,
+                author_name,
+                &preview[..preview.len().min(60)]
+            );
+            Ok(true)
+        } else {
+            debug!(
+                "#{} {} : {} (filtered by respond_mode: {})",
+                group,
+                author_name,
+                &preview[..preview.len().min(60)],
+                respond_mode
+            );
+            Ok(false)
+        }
+    }
+
+    /// Deliver a cached direct message to the DM webhook.
+    async fn forward_direct_message(&self, event: &Event) -> Result<bool> {
+        let event_id_hex = event.id.to_hex();
+        let author_hex = event.pubkey.to_hex();
+        self.prefetcher.track([author_hex.clone()]).await;
+        let author = self.profiles.author_profile(&author_hex).await;
+        let author_name = author.display_name.clone();
+
+        // Phase 1: Content sanitization
+        let preview =
+            sanitize_content_preview(&event.content, self.webhook.dm_target().preview_length);
+
+        // Phase 1: Mention detection
+        let known_pubkeys = self.profiles.get_known_pubkeys().await;
+        let detected_mentions = detect_mentions(&event.content, &known_pubkeys);
+        let mentions = if detected_mentions.is_empty() {
+            None
+        } else {
+            Some(detected_mentions)
+        };
+
+        // DMs are always delivered (no respond mode filtering)
+        let (thread, correlation_id) = self.thread_of(event).await;
+        self.webhook
+            .deliver_dm_enhanced(
+                &event_id_hex,
+                author,
+                &preview,
+                event.created_at.as_secs() as i64,
+                mentions,
+                thread,
+                correlation_id,
+            )
+            .await?;
+
+        info!(
+            "DM from {}: {}",
+            author_name,
+            &preview[..preview.len().min(60)]
+        );
+        Ok(true)
+    }
+
+    /// Deliver a cached NIP-29 moderation event. Returns false for events
+    /// that are not moderation actions or whose group has respond mode
+    /// `none`.
+    async fn forward_moderation(&self, event: &Event, group: &str) -> Result<bool> {
+        let event_id_hex = event.id.to_hex();
+        let Some(moderation) = groups::parse_moderation(event) else {
+            return Ok(false);
+        };
+        if self.config.get_group_respond_mode(group) == RespondMode::None {
+            debug!(
+                "#{} {} (filtered by respond_mode: none)",
+                group, moderation.action
+            );
+            return Ok(false);
+        }
+
+        let mut targets = Vec::with_capacity(moderation.targets.len());
+        for pubkey in moderation.targets {
+            let name = self.profiles.get_display_name_hex(&pubkey).await;
+            targets.push(ModerationUser { pubkey, name });
+        }
+        let actor_name = self.profiles.get_display_name_hex(&moderation.actor).await;
+        let payload = ModerationPayload {
+            r#type: "group_moderation".to_string(),
+            group: group.to_string(),
+            group_name: self.groups.display_name(group).await,
+            action: moderation.action.to_string(),
+            actor: ModerationUser {
+                pubkey: moderation.actor,
+                name: actor_name,
+            },
+            targets,
+            event_ids: moderation.event_ids,
+            reason: moderation.reason,
+            event_id: event_id_hex,
+            created_at: event.created_at.as_secs() as i64,
+        };
+        self.webhook.deliver_moderation(&payload).await?;
+        info!("#{} {} by {}", group, payload.action, payload.actor.name);
+        Ok(true)
+    }
+
+    /// Re-deliver cached events at or after `since`, oldest first, to the
+    /// webhooks — only those of `group` when given, otherwise groups and
+    /// DMs. Every payload carries its event id as `Idempotency-Key`, so
+    /// downstream can drop what it already has. Stops at the first failed
+    /// delivery, leaving no gap; resume from `last_created_at`.
+    pub async fn replay(
+        &self,
+        since: i64,
+        group: Option<&str>,
+        limit: i64,
+    ) -> Result<ReplayOutcome> {
+        let mut outcome = ReplayOutcome::default();
+        for cached in self.cache.replay_range(since, group, limit).await? {
+            let result = match cached.to_event() {
+                Ok(event) => match (cached.kind, cached.group_name.as_deref()) {
+                    (9, Some(group)) => self.forward_group_message(&event, group, true).await,
+                    (4, None) => self.forward_direct_message(&event).await,
+                    (_, Some(group)) => self.forward_moderation(&event, group).await,
+                    _ => Ok(false),
+                },
+                Err(e) => {
+                    warn!("Skipping unreadable cached event {}: {}", cached.id, e);
+                    Ok(false)
+                }
+            };
+            match result {
+                Ok(true) => outcome.delivered += 1,
+                Ok(false) => outcome.skipped += 1,
+                Err(e) => {
+                    outcome.error = Some(format!("{:#}", e));
+                    break;
+                }
+            }
+            outcome.last_created_at = Some(cached.created_at);
+        }
+        info!(
+            "Replayed {} events since {} ({} skipped)",
+            outcome.delivered, since, outcome.skipped
+        );
+        Ok(outcome)
     }
 
     pub async fn query_events(
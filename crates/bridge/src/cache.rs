@@ -21,6 +21,24 @@ pub struct CachedEvent {
     pub stored_at: String,
 }
 
+impl CachedEvent {
+    /// Rebuild the Nostr event, e.g. to replay it.
+    pub fn to_event(&self) -> Result<Event> {
+        let tags: serde_json::Value =
+            serde_json::from_str(&self.tags).context("Invalid cached tags")?;
+        let json = serde_json::json!({
+            "id": self.id,
+            "pubkey": self.pubkey,
+            "created_at": self.created_at,
+            "kind": self.kind,
+            "tags": tags,
+            "content": self.content,
+            "sig": self.sig,
+        });
+        serde_json::from_value(json).context("Invalid cached event")
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CacheStats {
     pub total_events: i64,
@@ -223,6 +241,36 @@ impl EventCache {
         Ok(events)
     }
 
+    /// Events at or after `since`, oldest first, optionally of one group.
+    pub async fn replay_range(
+        &self,
+        since: i64,
+        group: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<CachedEvent>> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, pubkey, created_at, kind, tags, content, sig, group_name, stored_at FROM events \
+             WHERE created_at >= ?1 AND (?2 IS NULL OR group_name = ?2) \
+             ORDER BY created_at ASC, id ASC LIMIT ?3",
+        )?;
+        let rows = stmt.query_map(params![since, group, limit], |row| {
+            Ok(CachedEvent {
+                id: row.get(0)?,
+                pubkey: row.get(1)?,
+                created_at: row.get(2)?,
+                kind: row.get(3)?,
+                tags: row.get(4)?,
+                content: row.get(5)?,
+                sig: row.get(6)?,
+                group_name: row.get(7)?,
+                stored_at: row.get(8)?,
+            })
+        })?;
+        rows.collect::<RusqliteResult<_>>()
+            .context("Failed to read cached events")
+    }
+
     pub async fn get(&self, event_id: &EventId) -> Result<Option<CachedEvent>> {
         self.get_event(event_id).await
     }
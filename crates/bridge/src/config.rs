@@ -21,6 +21,14 @@ pub struct Config {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct RelayConfig {
     pub url: String,
+    /// SOCKS5 proxy (`host:port`, e.g. Tor on `127.0.0.1:9050`); required
+    /// for `.onion` relay URLs
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Reconnect with exponential backoff and jitter instead of the relay
+    /// pool's own retries; backoff state is reported on `/health`
+    #[serde(default)]
+    pub adaptive_reconnect: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -34,6 +42,11 @@ pub struct GroupsConfig {
     pub subscribe: Vec<String>,
     #[serde(default)]
     pub respond_modes: std::collections::HashMap<String, String>,
+    /// Key of the group relay (hex or npub), the only accepted signer of
+    /// group state (kinds 39000-39003); read from the relay's NIP-11
+    /// document when unset
+    #[serde(default)]
+    pub relay_pubkey: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -73,6 +86,26 @@ pub struct WebhookConfig {
     pub token: Option<String>,
     #[serde(default = "default_preview_length")]
     pub preview_length: usize,
+    /// Per-group and DM endpoints; the first matching route wins, and
+    /// anything unmatched goes to `url` / `dm_url` above
+    #[serde(default)]
+    pub routes: Vec<WebhookRoute>,
+}
+
+/// `[[webhook.routes]]`: send some groups (or DMs) to another endpoint.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WebhookRoute {
+    /// Group ids delivered to this route
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Deliver direct messages to this route
+    #[serde(default)]
+    pub dm: bool,
+    pub url: String,
+    /// Bearer token for this endpoint; not inherited from `[webhook]`
+    pub token: Option<String>,
+    /// Defaults to `[webhook] preview_length`
+    pub preview_length: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -201,6 +234,18 @@ impl Config {
             }
         }
 
+        for (i, route) in self.webhook.routes.iter().enumerate() {
+            if !route.url.starts_with("http://") && !route.url.starts_with("https://") {
+                anyhow::bail!(
+                    "Webhook route {} URL must start with http:// or https://",
+                    i + 1
+                );
+            }
+            if route.groups.is_empty() && !route.dm {
+                anyhow::bail!("Webhook route {} must list groups or set dm = true", i + 1);
+            }
+        }
+
         // Validate bind address
         if self.api.bind.parse::<std::net::SocketAddr>().is_err() {
             anyhow::bail!("Invalid bind address: {}", self.api.bind);
@@ -0,0 +1,267 @@
+//! NIP-29 group metadata and moderation events.
+//!
+//! Relays publish group state as addressable events signed by the relay key:
+//! kind 39000 (name, picture, about), 39001 (admins), 39002 (members) and
+//! 39003 (roles), each with the group id in the `d` tag. `GroupCache` keeps
+//! the latest of each per group so webhook payloads can carry display names,
+//! and ignores group state signed by any other key.
+//!
+//! Moderation events (kinds 9000-9008) are parsed into `ModerationEvent`s
+//! and forwarded as `group_moderation` webhook notifications when their
+//! author is the relay or a cached kind 39001 admin. NIP-29 has a single
+//! remove-user event, so kicks and bans both arrive as `remove_user`.
+
+use nostr_sdk::{Event, PublicKey};
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+pub const KIND_GROUP_METADATA: u16 = 39000;
+pub const KIND_GROUP_ADMINS: u16 = 39001;
+pub const KIND_GROUP_MEMBERS: u16 = 39002;
+pub const KIND_GROUP_ROLES: u16 = 39003;
+
+/// Group state kinds subscribed with a `#d` filter.
+pub const METADATA_KINDS: [u16; 4] = [
+    KIND_GROUP_METADATA,
+    KIND_GROUP_ADMINS,
+    KIND_GROUP_MEMBERS,
+    KIND_GROUP_ROLES,
+];
+
+/// Moderation kinds subscribed with a `#h` filter.
+pub const MODERATION_KINDS: [u16; 6] = [9000, 9001, 9002, 9005, 9007, 9008];
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GroupInfo {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub picture: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub about: Option<String>,
+    pub public: bool,
+    pub open: bool,
+    pub admins: Vec<GroupAdmin>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member_count: Option<usize>,
+    /// Member pubkeys (hex) from kind 39002; used for profile prefetch.
+    #[serde(skip)]
+    pub members: Vec<String>,
+    pub roles: Vec<String>,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupAdmin {
+    pub pubkey: String,
+    pub roles: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct GroupCacheInner {
+    groups: HashMap<String, GroupInfo>,
+    /// Newest `created_at` applied per (group, kind), so stale replays are ignored.
+    versions: HashMap<(String, u16), i64>,
+}
+
+#[derive(Debug, Default)]
+pub struct GroupCache {
+    inner: RwLock<GroupCacheInner>,
+    /// The group relay's key; without it no group state is accepted.
+    relay_pubkey: Option<PublicKey>,
+}
+
+impl GroupCache {
+    pub fn new(relay_pubkey: Option<PublicKey>) -> Self {
+        Self {
+            inner: RwLock::default(),
+            relay_pubkey,
+        }
+    }
+
+    /// Apply a kind 39000-39003 event. Returns the group id when the cached
+    /// state changed (older, non-metadata or not relay-signed events are
+    /// ignored).
+    pub async fn apply(&self, event: &Event) -> Option<String> {
+        let kind = event.kind.as_u16();
+        if !METADATA_KINDS.contains(&kind) {
+            return None;
+        }
+        if self.relay_pubkey != Some(event.pubkey) {
+            debug!(
+                "Ignoring kind {} from {}: not signed by the group relay",
+                kind,
+                &event.pubkey.to_hex()[..8]
+            );
+            return None;
+        }
+        let group = tag_value(event, "d")?.to_string();
+        let created_at = event.created_at.as_secs() as i64;
+
+        let mut inner = self.inner.write().await;
+        let version = inner.versions.entry((group.clone(), kind)).or_insert(0);
+        if created_at < *version {
+            return None;
+        }
+        *version = created_at;
+
+        let info = inner
+            .groups
+            .entry(group.clone())
+            .or_insert_with(|| GroupInfo {
+                id: group.clone(),
+                public: true,
+                open: true,
+                ..GroupInfo::default()
+            });
+        info.updated_at = info.updated_at.max(created_at);
+
+        match kind {
+            KIND_GROUP_METADATA => {
+                info.name = tag_value(event, "name").map(str::to_string);
+                info.picture = tag_value(event, "picture").map(str::to_string);
+                info.about = tag_value(event, "about").map(str::to_string);
+                info.public = !has_tag(event, "private");
+                info.open = !has_tag(event, "closed");
+            }
+            KIND_GROUP_ADMINS => {
+                info.admins = event
+                    .tags
+                    .iter()
+                    .filter_map(|t| match t.as_slice() {
+                        [k, pubkey, roles @ ..] if k == "p" => Some(GroupAdmin {
+                            pubkey: pubkey.clone(),
+                            roles: roles.to_vec(),
+                        }),
+                        _ => None,
+                    })
+                    .collect();
+            }
+            KIND_GROUP_MEMBERS => {
+                info.members = tag_values(event, "p");
+                info.member_count = Some(info.members.len());
+            }
+            _ => {
+                info.roles = event
+                    .tags
+                    .iter()
+                    .filter_map(|t| match t.as_slice() {
+                        [k, role, ..] if k == "role" => Some(role.clone()),
+                        _ => None,
+                    })
+                    .collect();
+            }
+        }
+        Some(group)
+    }
+
+    /// Group name from kind 39000, if known and non-empty.
+    pub async fn display_name(&self, group: &str) -> Option<String> {
+        let inner = self.inner.read().await;
+        inner
+            .groups
+            .get(group)?
+            .name
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(str::to_string)
+    }
+
+    /// Member pubkeys (hex) of `group`, empty until kind 39002 is seen.
+    pub async fn members(&self, group: &str) -> Vec<String> {
+        let inner = self.inner.read().await;
+        inner
+            .groups
+            .get(group)
+            .map(|g| g.members.clone())
+            .unwrap_or_default()
+    }
+
+    /// Whether `actor` may moderate `group`: the relay itself or an admin
+    /// in the cached kind 39001 list.
+    pub async fn is_moderator(&self, group: &str, actor: &PublicKey) -> bool {
+        if self.relay_pubkey.as_ref() == Some(actor) {
+            return true;
+        }
+        let actor = actor.to_hex();
+        let inner = self.inner.read().await;
+        inner
+            .groups
+            .get(group)
+            .is_some_and(|g| g.admins.iter().any(|a| a.pubkey == actor))
+    }
+
+    /// All cached groups, sorted by id.
+    pub async fn all(&self) -> Vec<GroupInfo> {
+        let mut groups: Vec<GroupInfo> = self.inner.read().await.groups.values().cloned().collect();
+        groups.sort_by(|a, b| a.id.cmp(&b.id));
+        groups
+    }
+}
+
+/// A NIP-29 moderation event (kinds 9000-9008).
+#[derive(Debug, Clone)]
+pub struct ModerationEvent {
+    pub group: String,
+    pub action: &'static str,
+    pub actor: String,
+    /// Users the action applies to (`p` tags).
+    pub targets: Vec<String>,
+    /// Events the action applies to (`e` tags, for delete-event).
+    pub event_ids: Vec<String>,
+    pub reason: Option<String>,
+}
+
+/// Parse a moderation event, or `None` for other kinds or without an `h` tag.
+pub fn parse_moderation(event: &Event) -> Option<ModerationEvent> {
+    let action = match event.kind.as_u16() {
+        9000 => "put_user",
+        9001 => "remove_user",
+        9002 => "edit_metadata",
+        9005 => "delete_event",
+        9007 => "create_group",
+        9008 => "delete_group",
+        _ => return None,
+    };
+    let group = tag_value(event, "h")?.to_string();
+    let reason = Some(event.content.trim())
+        .filter(|c| !c.is_empty())
+        .map(str::to_string);
+    Some(ModerationEvent {
+        group,
+        action,
+        actor: event.pubkey.to_hex(),
+        targets: tag_values(event, "p"),
+        event_ids: tag_values(event, "e"),
+        reason,
+    })
+}
+
+fn tag_value<'a>(event: &'a Event, key: &str) -> Option<&'a str> {
+    event.tags.iter().find_map(|t| match t.as_slice() {
+        [k, v, ..] if k == key => Some(v.as_str()),
+        _ => None,
+    })
+}
+
+fn tag_values(event: &Event, key: &str) -> Vec<String> {
+    event
+        .tags
+        .iter()
+        .filter_map(|t| match t.as_slice() {
+            [k, v, ..] if k == key => Some(v.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn has_tag(event: &Event, key: &str) -> bool {
+    event
+        .tags
+        .iter()
+        .any(|t| t.as_slice().first().is_some_and(|k| k == key))
+}
@@ -7,8 +7,10 @@ mod api;
 mod bridge;
 mod cache;
 mod config;
+mod groups;
 mod profiles;
 mod relay;
+mod threads;
 mod webhook;
 
 use api::ApiServer;
@@ -165,12 +167,7 @@ async fn test_config(config: &Config) -> Result<()> {
     println!("✓ Database connection successful");
 
     // Test webhook connectivity
-    let webhook = webhook::WebhookDeliverer::new(
-        config.webhook.url.clone(),
-        config.webhook.dm_url.clone(),
-        config.webhook.token.clone(),
-        config.webhook.preview_length,
-    );
+    let webhook = webhook::WebhookDeliverer::new(&config.webhook);
 
     match webhook.test_webhook().await {
         Ok(()) => println!("✓ Webhook connectivity test passed"),
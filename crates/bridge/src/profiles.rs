@@ -3,9 +3,11 @@ use chrono::{DateTime, Duration, Utc};
 use lru::LruCache;
 use nostr_sdk::{Event, Kind, PublicKey};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::num::NonZeroUsize;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::webhook::AuthorProfile;
 
 const DEFAULT_CACHE_SIZE: usize = 1000;
 const PROFILE_TTL_HOURS: i64 = 24;
@@ -276,6 +278,26 @@ impl ProfileCache {
         Ok(())
     }
 
+    /// Webhook author block from the cache: display name plus nip05 and
+    /// picture when the profile is known. Never hits the relay.
+    pub async fn author_profile(&self, pubkey_hex: &str) -> AuthorProfile {
+        let display_name = self.get_display_name_hex(pubkey_hex).await;
+        let profile = {
+            let cache = self.cache.read().await;
+            cache
+                .peek(pubkey_hex)
+                .filter(|cached| Utc::now() < cached.expires_at)
+                .map(|cached| cached.profile.clone())
+        };
+        let non_empty = |v: Option<String>| v.filter(|s| !s.trim().is_empty());
+        AuthorProfile {
+            pubkey: pubkey_hex.to_string(),
+            display_name,
+            nip05: non_empty(profile.as_ref().and_then(|p| p.nip05.clone())),
+            picture: non_empty(profile.and_then(|p| p.picture)),
+        }
+    }
+
     /// Get all known pubkey -> display_name mappings for mention detection
     pub async fn get_known_pubkeys(&self) -> std::collections::HashMap<String, String> {
         let cache = self.cache.read().await;
@@ -293,3 +315,49 @@ impl ProfileCache {
             .collect()
     }
 }
+
+/// Tracks which pubkeys the bridge keeps kind 0 profiles for.
+///
+/// Group members (kind 39002) and recent authors are tracked on startup;
+/// new authors are added as they post. Newly tracked pubkeys wait in
+/// `pending` until the prefetch loop batch-fetches their profiles and adds
+/// them to the kind 0 subscription, so webhook delivery never waits on a
+/// profile lookup.
+#[derive(Debug, Default)]
+pub struct ProfilePrefetcher {
+    tracked: Mutex<HashSet<String>>,
+    pending: Mutex<HashSet<String>>,
+}
+
+impl ProfilePrefetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track pubkeys (hex). Returns how many were not tracked before.
+    pub async fn track<I>(&self, pubkeys: I) -> usize
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut tracked = self.tracked.lock().await;
+        let mut pending = self.pending.lock().await;
+        let mut added = 0;
+        for pubkey in pubkeys {
+            if tracked.insert(pubkey.clone()) {
+                pending.insert(pubkey);
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Pubkeys tracked since the last call, still to be fetched.
+    pub async fn take_pending(&self) -> Vec<String> {
+        self.pending.lock().await.drain().collect()
+    }
+
+    /// All tracked pubkeys (for periodic refresh).
+    pub async fn tracked(&self) -> Vec<String> {
+        self.tracked.lock().await.iter().cloned().collect()
+    }
+}
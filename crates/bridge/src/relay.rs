@@ -1,25 +1,44 @@
 use anyhow::{Context, Result};
+use nostr_core::{ReconnectState, Reconnector, RelayHealth, RelayProxy};
 use nostr_sdk::{
-    Alphabet, Client, Event, EventBuilder, EventId, Filter, Keys, Kind, PublicKey,
+    Alphabet, Client, Event, EventBuilder, EventId, Filter, Keys, Kind, PublicKey, RelayOptions,
     RelayPoolNotification, RelayUrl, SingleLetterTag, Tag, TagKind,
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
+use crate::groups::{KIND_GROUP_MEMBERS, METADATA_KINDS, MODERATION_KINDS};
+use crate::threads::ThreadRef;
+
+/// Authors per kind 0 fetch/subscription filter.
+const PROFILE_BATCH: usize = 250;
+const FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// How often the relay is checked for a due reconnect.
+const RECONNECT_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+const RECONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
 pub struct RelayClient {
     client: Client,
     relay_url: String,
+    proxy: RelayProxy,
+    /// Backoff and metrics when `adaptive_reconnect` is on.
+    reconnector: Option<Arc<Reconnector>>,
     our_pubkey: PublicKey,
     subscribed_groups: HashSet<String>,
 }
 
+/// Events forwarded from the relay. `GroupMetadata` carries NIP-29 group
+/// state (kinds 39000-39003), `GroupModeration` admin actions (9000-9008).
 #[derive(Debug, Clone)]
 pub enum RelayEvent {
     GroupMessage { event: Event, group: String },
     DirectMessage { event: Event },
     ProfileUpdate { event: Event },
+    GroupMetadata { event: Event },
+    GroupModeration { event: Event, group: String },
 }
 
 pub fn create_keys_from_nsec(nsec: &str) -> Result<Keys> {
@@ -28,34 +47,103 @@ pub fn create_keys_from_nsec(nsec: &str) -> Result<Keys> {
     Ok(Keys::new(secret_key))
 }
 
+/// Read the relay's own pubkey from its NIP-11 information document.
+pub async fn fetch_relay_pubkey(relay_url: &str) -> Result<PublicKey> {
+    let http_url = if let Some(rest) = relay_url.strip_prefix("wss://") {
+        format!("https://{rest}")
+    } else if let Some(rest) = relay_url.strip_prefix("ws://") {
+        format!("http://{rest}")
+    } else {
+        anyhow::bail!("Invalid relay URL: {relay_url}");
+    };
+    let document: serde_json::Value = reqwest::Client::new()
+        .get(&http_url)
+        .header("Accept", "application/nostr+json")
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch NIP-11 document of {relay_url}"))?
+        .error_for_status()?
+        .json()
+        .await
+        .with_context(|| format!("Invalid NIP-11 document from {relay_url}"))?;
+    let pubkey = document
+        .get("pubkey")
+        .and_then(|v| v.as_str())
+        .with_context(|| format!("NIP-11 document of {relay_url} has no pubkey"))?;
+    PublicKey::parse(pubkey).with_context(|| format!("Invalid relay pubkey {pubkey}"))
+}
+
 impl RelayClient {
-    pub async fn new(relay_url: &str, keys: Keys) -> Result<Self> {
+    pub async fn new(
+        relay_url: &str,
+        keys: Keys,
+        proxy: Option<&str>,
+        adaptive_reconnect: bool,
+    ) -> Result<Self> {
         let our_pubkey = keys.public_key();
         let client = Client::new(keys);
+        let proxy = RelayProxy::new(proxy, false, &HashMap::new())?;
+        proxy.check_relays(&[relay_url.to_string()])?;
 
         Ok(Self {
             client,
             relay_url: relay_url.to_string(),
+            proxy,
+            reconnector: adaptive_reconnect.then(|| Arc::new(Reconnector::default())),
             our_pubkey,
             subscribed_groups: HashSet::new(),
         })
     }
 
     pub async fn connect(&mut self) -> Result<()> {
-        let url = RelayUrl::from_str(&self.relay_url)
+        RelayUrl::from_str(&self.relay_url)
             .with_context(|| format!("Invalid relay URL: {}", self.relay_url))?;
-        self.client
-            .add_relay(url)
-            .await
-            .with_context(|| "Failed to add relay")?;
+        let opts = RelayOptions::new().reconnect(self.reconnector.is_none());
+        self.proxy
+            .add_relay_with(&self.client, &self.relay_url, opts)
+            .await?;
 
-        info!("Connecting to {}...", self.relay_url);
+        match self.proxy.for_relay(&self.relay_url) {
+            Some(addr) => info!("Connecting to {} via proxy {}...", self.relay_url, addr),
+            None => info!("Connecting to {}...", self.relay_url),
+        }
         self.client.connect().await;
         tokio::time::sleep(std::time::Duration::from_secs(3)).await;
         info!("Connected");
+
+        if let Some(reconnector) = self.reconnector.clone() {
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(RECONNECT_CHECK_INTERVAL);
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+                loop {
+                    interval.tick().await;
+                    reconnector.reconnect_due(&client, RECONNECT_TIMEOUT).await;
+                }
+            });
+        }
         Ok(())
     }
 
+    /// Whether the relay is reached through the SOCKS5 proxy.
+    pub fn is_proxied(&self) -> bool {
+        self.proxy.for_relay(&self.relay_url).is_some()
+    }
+
+    /// Connection health of the relay, including the proxy circuit.
+    pub async fn health(&self) -> Vec<RelayHealth> {
+        nostr_core::relay_health(&self.client, &self.proxy).await
+    }
+
+    /// Reconnect backoff state, when `adaptive_reconnect` is on.
+    pub fn reconnect_state(&self) -> Vec<ReconnectState> {
+        self.reconnector
+            .as_ref()
+            .map(|r| r.states(std::time::Instant::now()))
+            .unwrap_or_default()
+    }
+
     pub async fn subscribe_groups(&mut self, groups: &[String]) -> Result<()> {
         if groups.is_empty() {
             return Ok(());
@@ -76,6 +164,27 @@ impl RelayClient {
             .await
             .with_context(|| "Failed to subscribe to groups")?;
 
+        // Group state is addressable (latest per group), so no `since`.
+        let metadata = Filter::new()
+            .kinds(METADATA_KINDS.map(Kind::Custom))
+            .custom_tags(
+                SingleLetterTag::lowercase(Alphabet::D),
+                groups.iter().map(|s| s.as_str()),
+            );
+        let moderation = Filter::new()
+            .kinds(MODERATION_KINDS.map(Kind::Custom))
+            .custom_tags(
+                SingleLetterTag::lowercase(Alphabet::H),
+                groups.iter().map(|s| s.as_str()),
+            )
+            .since(since);
+        for filter in [metadata, moderation] {
+            self.client
+                .subscribe(filter, None)
+                .await
+                .with_context(|| "Failed to subscribe to group metadata")?;
+        }
+
         for g in groups {
             self.subscribed_groups.insert(g.clone());
         }
@@ -99,9 +208,66 @@ impl RelayClient {
         Ok(())
     }
 
+    /// Fetch the current member lists (kind 39002) of `groups`.
+    pub async fn fetch_group_members(&self, groups: &[String]) -> Result<Vec<Event>> {
+        if groups.is_empty() {
+            return Ok(Vec::new());
+        }
+        let filter = Filter::new()
+            .kind(Kind::Custom(KIND_GROUP_MEMBERS))
+            .custom_tags(
+                SingleLetterTag::lowercase(Alphabet::D),
+                groups.iter().map(|s| s.as_str()),
+            );
+        let events = self
+            .client
+            .fetch_events(filter, FETCH_TIMEOUT)
+            .await
+            .with_context(|| "Failed to fetch group members")?;
+        Ok(events.into_iter().collect())
+    }
+
+    /// Batch-fetch kind 0 profiles for `pubkeys`.
+    pub async fn fetch_profiles(&self, pubkeys: &[PublicKey]) -> Result<Vec<Event>> {
+        let mut profiles = Vec::new();
+        for batch in pubkeys.chunks(PROFILE_BATCH) {
+            let filter = Filter::new()
+                .kind(Kind::Metadata)
+                .authors(batch.iter().copied());
+            let events = self
+                .client
+                .fetch_events(filter, FETCH_TIMEOUT)
+                .await
+                .with_context(|| "Failed to fetch profiles")?;
+            profiles.extend(events);
+        }
+        Ok(profiles)
+    }
+
+    /// Subscribe to future kind 0 updates from `pubkeys`.
+    pub async fn subscribe_profiles(&self, pubkeys: &[PublicKey]) -> Result<()> {
+        let since = nostr_sdk::Timestamp::now();
+        for batch in pubkeys.chunks(PROFILE_BATCH) {
+            let filter = Filter::new()
+                .kind(Kind::Metadata)
+                .authors(batch.iter().copied())
+                .since(since);
+            self.client
+                .subscribe(filter, None)
+                .await
+                .with_context(|| "Failed to subscribe to profiles")?;
+        }
+        debug!(
+            "Subscribed to profile updates for {} pubkeys",
+            pubkeys.len()
+        );
+        Ok(())
+    }
+
     pub fn start_event_stream(&self, tx: mpsc::Sender<RelayEvent>) {
         let client = self.client.clone();
         let our_pubkey = self.our_pubkey;
+        let reconnector = self.reconnector.clone();
 
         tokio::spawn(async move {
             info!("Event stream listening...");
@@ -143,6 +309,27 @@ impl RelayClient {
                                     event: event.as_ref().clone(),
                                 })
                             }
+                            k if METADATA_KINDS.contains(&k) => {
+                                debug!("Event: group metadata kind {}", k);
+                                Some(RelayEvent::GroupMetadata {
+                                    event: event.as_ref().clone(),
+                                })
+                            }
+                            k if MODERATION_KINDS.contains(&k) => {
+                                let group = event
+                                    .tags
+                                    .iter()
+                                    .find(|t| t.kind() == TagKind::h())
+                                    .and_then(|t| t.content())
+                                    .map(|s| s.to_string())
+                                    .unwrap_or_else(|| "unknown".to_string());
+
+                                info!("Event: #{} moderation kind {}", group, k);
+                                Some(RelayEvent::GroupModeration {
+                                    event: event.as_ref().clone(),
+                                    group,
+                                })
+                            }
                             _ => None,
                         };
 
@@ -153,7 +340,15 @@ impl RelayClient {
                             }
                         }
                     }
-                    Ok(_) => {}
+                    Ok(notification) => {
+                        if let Some((relay, notice)) = nostr_core::reconnect::notice(&notification)
+                        {
+                            warn!("Relay {relay} notice: {notice}");
+                            if let Some(reconnector) = &reconnector {
+                                reconnector.record_notice(&relay, &notice);
+                            }
+                        }
+                    }
                     Err(e) => {
                         warn!("Notification error (lagged?): {}", e);
                         continue;
@@ -163,9 +358,15 @@ impl RelayClient {
         });
     }
 
-    pub async fn send_group_message(&self, group: &str, content: &str) -> Result<EventId> {
+    pub async fn send_group_message(
+        &self,
+        group: &str,
+        content: &str,
+        thread: Option<&ThreadRef>,
+    ) -> Result<EventId> {
         let builder = EventBuilder::new(Kind::Custom(9), content)
-            .tag(Tag::custom(TagKind::h(), vec![group.to_string()]));
+            .tag(Tag::custom(TagKind::h(), vec![group.to_string()]))
+            .tags(thread_tags(thread)?);
 
         let output = self
             .client
@@ -176,10 +377,15 @@ impl RelayClient {
         Ok(output.val)
     }
 
-    pub async fn send_dm(&self, recipient: &PublicKey, content: &str) -> Result<EventId> {
+    pub async fn send_dm(
+        &self,
+        recipient: &PublicKey,
+        content: &str,
+        thread: Option<&ThreadRef>,
+    ) -> Result<EventId> {
         let output = self
             .client
-            .send_private_msg(*recipient, content, None)
+            .send_private_msg(*recipient, content, thread_tags(thread)?)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to send DM: {}", e))?;
         Ok(output.val)
@@ -199,3 +405,13 @@ impl RelayClient {
         Ok(())
     }
 }
+
+/// NIP-10 reply tags for an outgoing message.
+fn thread_tags(thread: Option<&ThreadRef>) -> Result<Vec<Tag>> {
+    thread
+        .map(ThreadRef::to_tags)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|tag| Tag::parse(tag).context("Invalid reply tag"))
+        .collect()
+}
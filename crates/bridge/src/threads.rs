@@ -0,0 +1,134 @@
+//! Reply threading for webhook consumers.
+//!
+//! Webhook payloads carry the NIP-10 thread of each message (`root` and
+//! `reply_to` event ids) plus the `correlation_id` of the `POST /send`
+//! call it answers, if any. `/send` accepts `reply_to` and
+//! `correlation_id`, tags the outgoing event as a reply, and remembers the
+//! correlation so later replies in that thread come back with it.
+
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroUsize;
+use tokio::sync::Mutex;
+
+/// Sent events whose correlation ids are remembered.
+const CORRELATION_CAPACITY: usize = 4096;
+
+/// Position of a message in a NIP-10 reply thread.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadRef {
+    /// First event of the thread.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    /// Event this message directly replies to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
+}
+
+impl ThreadRef {
+    /// Read the thread from an event's `e` tags. Marked tags (`root`,
+    /// `reply`) win; otherwise the deprecated positional form is used
+    /// (first = root, last = reply). `None` if the event is no reply.
+    pub fn from_tags<'a>(tags: impl IntoIterator<Item = &'a [String]>) -> Option<Self> {
+        let e_tags: Vec<&[String]> = tags
+            .into_iter()
+            .filter(|tag| tag.len() >= 2 && tag[0] == "e")
+            .collect();
+        let marked = |marker: &str| {
+            e_tags
+                .iter()
+                .find(|tag| tag.get(3).map(String::as_str) == Some(marker))
+                .map(|tag| tag[1].clone())
+        };
+
+        let (root, reply_to) = match (marked("root"), marked("reply")) {
+            (None, None) => (
+                e_tags.first().map(|tag| tag[1].clone()),
+                e_tags.last().map(|tag| tag[1].clone()),
+            ),
+            // A direct reply to the root only carries the root marker.
+            (Some(root), None) => (Some(root.clone()), Some(root)),
+            (root, reply) => (root, reply),
+        };
+        if root.is_none() && reply_to.is_none() {
+            return None;
+        }
+        Some(Self { root, reply_to })
+    }
+
+    /// Thread of a reply to the event `parent`, whose own thread is
+    /// `parent_thread`.
+    pub fn replying_to(parent: &str, parent_thread: Option<&ThreadRef>) -> Self {
+        let root = parent_thread
+            .and_then(|thread| thread.root.clone())
+            .unwrap_or_else(|| parent.to_string());
+        Self {
+            root: Some(root),
+            reply_to: Some(parent.to_string()),
+        }
+    }
+
+    /// NIP-10 marked `e` tags for an outgoing reply in this thread.
+    pub fn to_tags(&self) -> Vec<Vec<String>> {
+        let e_tag = |id: &str, marker: &str| {
+            vec![
+                "e".to_string(),
+                id.to_string(),
+                String::new(),
+                marker.to_string(),
+            ]
+        };
+        match (&self.root, &self.reply_to) {
+            (Some(root), Some(reply)) if root != reply => {
+                vec![e_tag(root, "root"), e_tag(reply, "reply")]
+            }
+            (Some(id), _) | (None, Some(id)) => vec![e_tag(id, "root")],
+            (None, None) => Vec::new(),
+        }
+    }
+}
+
+/// Correlation ids of messages sent through `/send`, keyed by event id.
+/// Thread roots are recorded too, so replies further down a thread
+/// started elsewhere still resolve.
+pub struct CorrelationTracker {
+    by_event: Mutex<LruCache<String, String>>,
+}
+
+impl CorrelationTracker {
+    pub fn new() -> Self {
+        Self {
+            by_event: Mutex::new(LruCache::new(
+                NonZeroUsize::new(CORRELATION_CAPACITY).unwrap(),
+            )),
+        }
+    }
+
+    /// Remember `correlation_id` for a sent event and its thread root.
+    /// An existing root correlation is kept.
+    pub async fn record(&self, event_id: &str, thread: Option<&ThreadRef>, correlation_id: &str) {
+        let mut by_event = self.by_event.lock().await;
+        by_event.put(event_id.to_string(), correlation_id.to_string());
+        if let Some(root) = thread.and_then(|thread| thread.root.as_deref()) {
+            if !by_event.contains(root) {
+                by_event.put(root.to_string(), correlation_id.to_string());
+            }
+        }
+    }
+
+    /// Correlation id for a message in `thread`: that of the event it
+    /// replies to, else that of the thread root.
+    pub async fn lookup(&self, thread: &ThreadRef) -> Option<String> {
+        let mut by_event = self.by_event.lock().await;
+        [&thread.reply_to, &thread.root]
+            .into_iter()
+            .flatten()
+            .find_map(|id| by_event.get(id).cloned())
+    }
+}
+
+impl Default for CorrelationTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -7,17 +7,37 @@ use serde::{Deserialize, Serialize};
 use tokio::time::{sleep, Duration};
 use tracing::{debug, error, info, warn};
 
+use crate::config::WebhookConfig;
+use crate::threads::ThreadRef;
+
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 const MAX_RETRIES: u32 = 3;
 const BASE_RETRY_DELAY: Duration = Duration::from_secs(1);
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
 
 #[derive(Debug, Clone)]
 pub struct WebhookDeliverer {
     client: Client,
-    group_url: String,
-    dm_url: Option<String>,
-    token: Option<String>,
-    preview_length: usize,
+    /// `[webhook] url`: catch-all for groups without a route
+    default: WebhookTarget,
+    /// `[webhook] dm_url`, used when no route takes DMs
+    dm: Option<WebhookTarget>,
+    routes: Vec<RouteTarget>,
+}
+
+/// Endpoint one message is delivered to.
+#[derive(Debug, Clone)]
+pub struct WebhookTarget {
+    pub url: String,
+    pub token: Option<String>,
+    pub preview_length: usize,
+}
+
+#[derive(Debug, Clone)]
+struct RouteTarget {
+    groups: Vec<String>,
+    dm: bool,
+    target: WebhookTarget,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,7 +45,13 @@ pub struct WebhookPayload {
     pub r#type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub group: Option<String>,
+    /// Group display name from NIP-29 metadata (kind 39000), when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_name: Option<String>,
     pub author: String,
+    /// Cached kind 0 profile of the author (group messages and DMs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_profile: Option<AuthorProfile>,
     pub preview: String,
     pub event_id: String,
     pub created_at: i64,
@@ -34,6 +60,48 @@ pub struct WebhookPayload {
     pub context: Option<Vec<ContextMessage>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mentions: Option<Vec<MentionInfo>>,
+    /// NIP-10 thread position when the message is a reply
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread: Option<ThreadRef>,
+    /// `correlation_id` of the `/send` call this message replies to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+}
+
+/// NIP-29 moderation notification (`type = "group_moderation"`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationPayload {
+    pub r#type: String,
+    pub group: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_name: Option<String>,
+    /// put_user | remove_user | edit_metadata | delete_event | create_group | delete_group
+    pub action: String,
+    pub actor: ModerationUser,
+    pub targets: Vec<ModerationUser>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub event_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub event_id: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationUser {
+    pub pubkey: String,
+    pub name: String,
+}
+
+/// Author details from the prefetched profile cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorProfile {
+    pub pubkey: String,
+    pub display_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nip05: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub picture: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,44 +119,82 @@ pub struct MentionInfo {
 }
 
 impl WebhookDeliverer {
-    pub fn new(
-        group_url: String,
-        dm_url: Option<String>,
-        token: Option<String>,
-        preview_length: usize,
-    ) -> Self {
+    pub fn new(config: &WebhookConfig) -> Self {
         let client = Client::builder()
             .timeout(REQUEST_TIMEOUT)
             .build()
             .expect("Failed to create HTTP client");
 
+        let target = |url: &str| WebhookTarget {
+            url: url.to_string(),
+            token: config.token.clone(),
+            preview_length: config.preview_length,
+        };
+        let routes = config
+            .routes
+            .iter()
+            .map(|route| RouteTarget {
+                groups: route.groups.clone(),
+                dm: route.dm,
+                target: WebhookTarget {
+                    url: route.url.clone(),
+                    token: route.token.clone(),
+                    preview_length: route.preview_length.unwrap_or(config.preview_length),
+                },
+            })
+            .collect();
+
         Self {
             client,
-            group_url,
-            dm_url,
-            token,
-            preview_length,
+            default: target(&config.url),
+            dm: config.dm_url.as_deref().map(target),
+            routes,
         }
     }
 
+    /// Endpoint for messages in `group`: the first route listing it, else
+    /// the default.
+    pub fn group_target(&self, group: &str) -> &WebhookTarget {
+        self.routes
+            .iter()
+            .find(|route| route.groups.iter().any(|g| g == group))
+            .map_or(&self.default, |route| &route.target)
+    }
+
+    /// Endpoint for direct messages: the first route with `dm = true`, else
+    /// `dm_url`, else the default.
+    pub fn dm_target(&self) -> &WebhookTarget {
+        self.routes
+            .iter()
+            .find(|route| route.dm)
+            .map(|route| &route.target)
+            .or(self.dm.as_ref())
+            .unwrap_or(&self.default)
+    }
+
     pub async fn deliver_group_message(
         &self,
         event: &Event,
         group: &str,
         author_name: &str,
     ) -> Result<()> {
+        let target = self.group_target(group);
         let payload = WebhookPayload {
             r#type: "group_message".to_string(),
             group: Some(group.to_string()),
+            group_name: None,
             author: author_name.to_string(),
-            preview: self.create_preview(&event.content),
+            author_profile: None,
+            preview: create_preview(&event.content, target.preview_length),
             event_id: event.id.to_hex(),
             created_at: event.created_at.as_secs() as i64,
             context: None,
             mentions: None,
+            thread: None,
+            correlation_id: None,
         };
 
-        self.deliver_payload(&self.group_url, &payload)
+        self.deliver_payload(target, &payload, Some(&payload.event_id))
             .await
             .with_context(|| format!("Failed to deliver group message for event {}", event.id))
     }
@@ -99,13 +205,7 @@ impl WebhookDeliverer {
         author_name: &str,
         decrypted_content: Option<&str>,
     ) -> Result<()> {
-        let dm_url = match &self.dm_url {
-            Some(url) => url,
-            None => {
-                debug!("No DM webhook URL configured, using group URL");
-                &self.group_url
-            }
-        };
+        let target = self.dm_target();
 
         // Use decrypted content for preview if available, otherwise use raw content
         let content_for_preview = decrypted_content.unwrap_or(&event.content);
@@ -113,15 +213,19 @@ impl WebhookDeliverer {
         let payload = WebhookPayload {
             r#type: "direct_message".to_string(),
             group: None,
+            group_name: None,
             author: author_name.to_string(),
-            preview: self.create_preview(content_for_preview),
+            author_profile: None,
+            preview: create_preview(content_for_preview, target.preview_length),
             event_id: event.id.to_hex(),
             created_at: event.created_at.as_secs() as i64,
             context: None,
             mentions: None,
+            thread: None,
+            correlation_id: None,
         };
 
-        self.deliver_payload(dm_url, &payload)
+        self.deliver_payload(target, &payload, Some(&payload.event_id))
             .await
             .with_context(|| format!("Failed to deliver direct message for event {}", event.id))
     }
@@ -130,35 +234,62 @@ impl WebhookDeliverer {
         let test_payload = WebhookPayload {
             r#type: "test".to_string(),
             group: Some("test".to_string()),
+            group_name: None,
             author: "bridge".to_string(),
+            author_profile: None,
             preview: "Test webhook connectivity".to_string(),
             event_id: "test".to_string(),
             created_at: chrono::Utc::now().timestamp(),
             context: None,
             mentions: None,
+            thread: None,
+            correlation_id: None,
         };
 
-        info!("Testing group webhook: {}", self.group_url);
-        self.deliver_payload(&self.group_url, &test_payload)
+        let mut dm_test_payload = test_payload.clone();
+        dm_test_payload.r#type = "test_dm".to_string();
+        dm_test_payload.group = None;
+
+        info!("Testing group webhook: {}", self.default.url);
+        self.deliver_payload(&self.default, &test_payload, None)
             .await
             .with_context(|| "Group webhook test failed")?;
 
-        if let Some(dm_url) = &self.dm_url {
-            info!("Testing DM webhook: {}", dm_url);
-            let mut dm_test_payload = test_payload.clone();
-            dm_test_payload.r#type = "test_dm".to_string();
-            dm_test_payload.group = None;
-
-            self.deliver_payload(dm_url, &dm_test_payload)
+        if let Some(dm) = &self.dm {
+            info!("Testing DM webhook: {}", dm.url);
+            self.deliver_payload(dm, &dm_test_payload, None)
                 .await
                 .with_context(|| "DM webhook test failed")?;
         }
 
+        for (i, route) in self.routes.iter().enumerate() {
+            info!("Testing webhook route {}: {}", i + 1, route.target.url);
+            let payload = match route.groups.first() {
+                Some(group) => WebhookPayload {
+                    group: Some(group.clone()),
+                    ..test_payload.clone()
+                },
+                None => dm_test_payload.clone(),
+            };
+            self.deliver_payload(&route.target, &payload, None)
+                .await
+                .with_context(|| format!("Webhook route {} test failed", i + 1))?;
+        }
+
         info!("All webhook tests passed");
         Ok(())
     }
 
-    async fn deliver_payload(&self, url: &str, payload: &WebhookPayload) -> Result<()> {
+    /// POST `payload` to `target`, retrying server errors. The event id goes
+    /// out as the `Idempotency-Key` header so a receiver can drop repeats,
+    /// e.g. from `/replay`.
+    async fn deliver_payload<T: Serialize>(
+        &self,
+        target: &WebhookTarget,
+        payload: &T,
+        idempotency_key: Option<&str>,
+    ) -> Result<()> {
+        let url = target.url.as_str();
         let mut attempt = 0;
 
         loop {
@@ -167,9 +298,12 @@ impl WebhookDeliverer {
 
             let mut request = self.client.post(url).json(payload);
 
-            if let Some(token) = &self.token {
+            if let Some(token) = &target.token {
                 request = request.bearer_auth(token);
             }
+            if let Some(key) = idempotency_key {
+                request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+            }
 
             match request.send().await {
                 Ok(response) => {
@@ -231,37 +365,16 @@ impl WebhookDeliverer {
         }
     }
 
-    fn create_preview(&self, content: &str) -> String {
-        let trimmed = content.trim();
-
-        if trimmed.len() <= self.preview_length {
-            trimmed.to_string()
-        } else {
-            // Try to break at word boundary near the limit
-            let mut end = self.preview_length;
-
-            // Look for a space within the last 20 characters
-            if let Some(space_pos) = trimmed[..self.preview_length]
-                .rfind(' ')
-                .filter(|&pos| pos > self.preview_length.saturating_sub(20))
-            {
-                end = space_pos;
-            }
-
-            format!("{}...", &trimmed[..end])
-        }
-    }
-
     pub fn set_preview_length(&mut self, length: usize) {
-        self.preview_length = length;
+        self.default.preview_length = length;
     }
 
     pub fn preview_length(&self) -> usize {
-        self.preview_length
+        self.default.preview_length
     }
 
     pub fn has_dm_url(&self) -> bool {
-        self.dm_url.is_some()
+        self.dm.is_some() || self.routes.iter().any(|route| route.dm)
     }
 
     /// Deliver group message from pre-extracted fields (no nostr_sdk types needed)
@@ -276,14 +389,19 @@ impl WebhookDeliverer {
         let payload = WebhookPayload {
             r#type: "group_message".to_string(),
             group: Some(group.to_string()),
+            group_name: None,
             author: author.to_string(),
+            author_profile: None,
             preview: preview.to_string(),
             event_id: event_id.to_string(),
             created_at,
             context: None,
             mentions: None,
+            thread: None,
+            correlation_id: None,
         };
-        self.deliver_payload(&self.group_url, &payload).await
+        self.deliver_payload(self.group_target(group), &payload, Some(&payload.event_id))
+            .await
     }
 
     /// Deliver DM from pre-extracted fields
@@ -294,30 +412,38 @@ impl WebhookDeliverer {
         preview: &str,
         created_at: i64,
     ) -> Result<()> {
-        let url = self.dm_url.as_deref().unwrap_or(&self.group_url);
         let payload = WebhookPayload {
             r#type: "direct_message".to_string(),
             group: None,
+            group_name: None,
             author: author.to_string(),
+            author_profile: None,
             preview: preview.to_string(),
             event_id: event_id.to_string(),
             created_at,
             context: None,
             mentions: None,
+            thread: None,
+            correlation_id: None,
         };
-        self.deliver_payload(url, &payload).await
+        self.deliver_payload(self.dm_target(), &payload, Some(&payload.event_id))
+            .await
     }
 
-    /// Deliver group message with enhanced context and mentions
+    /// Deliver group message with enhanced context, mentions and threading
+    #[allow(clippy::too_many_arguments)]
     pub async fn deliver_group_message_enhanced(
         &self,
         event_id: &str,
         group: &str,
-        author: &str,
+        group_name: Option<&str>,
+        author: AuthorProfile,
         preview: &str,
         created_at: i64,
         context: Option<Vec<MessageEntry>>,
         mentions: Option<Vec<Mention>>,
+        thread: Option<ThreadRef>,
+        correlation_id: Option<String>,
     ) -> Result<()> {
         let webhook_context = context.map(|ctx| {
             ctx.into_iter()
@@ -349,28 +475,44 @@ impl WebhookDeliverer {
         let payload = WebhookPayload {
             r#type: "group_message".to_string(),
             group: Some(group.to_string()),
-            author: author.to_string(),
+            group_name: group_name.map(str::to_string),
+            author: author.display_name.clone(),
+            author_profile: Some(author),
             preview: preview.to_string(),
             event_id: event_id.to_string(),
             created_at,
             context: webhook_context,
             mentions: webhook_mentions,
+            thread,
+            correlation_id,
         };
 
-        self.deliver_payload(&self.group_url, &payload).await
+        self.deliver_payload(self.group_target(group), &payload, Some(&payload.event_id))
+            .await
+    }
+
+    /// Deliver a NIP-29 moderation notification to the group's webhook
+    pub async fn deliver_moderation(&self, payload: &ModerationPayload) -> Result<()> {
+        self.deliver_payload(
+            self.group_target(&payload.group),
+            payload,
+            Some(&payload.event_id),
+        )
+        .await
     }
 
-    /// Deliver DM with enhanced context and mentions
+    /// Deliver DM with enhanced context, mentions and threading
+    #[allow(clippy::too_many_arguments)]
     pub async fn deliver_dm_enhanced(
         &self,
         event_id: &str,
-        author: &str,
+        author: AuthorProfile,
         preview: &str,
         created_at: i64,
         mentions: Option<Vec<Mention>>,
+        thread: Option<ThreadRef>,
+        correlation_id: Option<String>,
     ) -> Result<()> {
-        let url = self.dm_url.as_deref().unwrap_or(&self.group_url);
-
         let webhook_mentions = mentions.map(|mentions| {
             mentions
                 .into_iter()
@@ -391,15 +533,41 @@ impl WebhookDeliverer {
         let payload = WebhookPayload {
             r#type: "direct_message".to_string(),
             group: None,
-            author: author.to_string(),
+            group_name: None,
+            author: author.display_name.clone(),
+            author_profile: Some(author),
             preview: preview.to_string(),
             event_id: event_id.to_string(),
             created_at,
             context: None, // DMs don't have group context
             mentions: webhook_mentions,
+            thread,
+            correlation_id,
         };
 
-        self.deliver_payload(url, &payload).await
+        self.deliver_payload(self.dm_target(), &payload, Some(&payload.event_id))
+            .await
+    }
+}
+
+fn create_preview(content: &str, preview_length: usize) -> String {
+    let trimmed = content.trim();
+
+    if trimmed.len() <= preview_length {
+        trimmed.to_string()
+    } else {
+        // Try to break at word boundary near the limit
+        let mut end = preview_length;
+
+        // Look for a space within the last 20 characters
+        if let Some(space_pos) = trimmed[..preview_length]
+            .rfind(' ')
+            .filter(|&pos| pos > preview_length.saturating_sub(20))
+        {
+            end = space_pos;
+        }
+
+        format!("{}...", &trimmed[..end])
     }
 }
 
@@ -0,0 +1,165 @@
+//! Typed relay and remote signer errors.
+//!
+//! [`RelayError::is_transient`] tells callers whether a failure is worth
+//! retrying (network, relay or timeout) or needs a configuration change
+//! (bad proxy address, onion relay without a proxy, unusable relay URL).
+//! [`Nip46Error::is_transient`] does the same for a NIP-46 bunker.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Boxed nostr-sdk error kept as the `source` of a [`RelayError`].
+pub type SdkError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Failure configuring, connecting to, or talking to Nostr relays.
+#[derive(Debug, Error)]
+pub enum RelayError {
+    /// Proxy address is not `host:port`.
+    #[error("invalid proxy address `{addr}` (expected host:port): {source}")]
+    InvalidProxyAddress {
+        addr: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Proxy address parsed but resolved to nothing.
+    #[error("proxy address `{0}` did not resolve")]
+    UnresolvedProxyAddress(String),
+
+    /// A `.onion` relay would be dialed without a SOCKS5 proxy.
+    #[error("relay {0} is an onion service but has no SOCKS5 proxy configured")]
+    OnionWithoutProxy(String),
+
+    /// The relay could not be added to the pool (usually a malformed URL).
+    #[error("failed to add relay {url}: {source}")]
+    AddRelay {
+        url: String,
+        #[source]
+        source: SdkError,
+    },
+
+    /// As [`Self::AddRelay`], for a relay routed through a SOCKS5 proxy.
+    #[error("failed to add relay {url} via proxy {proxy}: {source}")]
+    AddProxiedRelay {
+        url: String,
+        proxy: SocketAddr,
+        #[source]
+        source: SdkError,
+    },
+
+    /// Opening a subscription failed.
+    #[error("failed to subscribe: {0}")]
+    Subscribe(#[source] SdkError),
+
+    /// Publishing an event failed on every relay.
+    #[error("failed to send event: {0}")]
+    Send(#[source] SdkError),
+
+    /// Fetching stored events failed.
+    #[error("failed to fetch events: {0}")]
+    Fetch(#[source] SdkError),
+
+    /// An ephemeral send or subscription was given a stored kind.
+    #[error("kind {0} is not ephemeral (expected 20000-29999)")]
+    NotEphemeral(u16),
+
+    /// The relays did not answer in time.
+    #[error("{operation} timed out after {}s", after.as_secs())]
+    Timeout {
+        operation: &'static str,
+        after: Duration,
+    },
+}
+
+impl RelayError {
+    /// Whether retrying later may succeed without a config change.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::Subscribe(_) | Self::Send(_) | Self::Fetch(_) | Self::Timeout { .. }
+        )
+    }
+}
+
+/// Failure talking to a NIP-46 remote signer.
+#[derive(Debug, Error)]
+pub enum Nip46Error {
+    /// The bunker URL is malformed.
+    #[error("invalid bunker URL ({0})")]
+    InvalidUrl(String),
+
+    /// Too many requests are waiting for the signer.
+    #[error("remote signer queue is full ({0} requests waiting)")]
+    QueueFull(usize),
+
+    /// The session ended.
+    #[error("remote signer session closed")]
+    Closed,
+
+    /// The signer did not answer in time.
+    #[error("remote signer did not answer {method} within {}s", after.as_secs())]
+    Timeout { method: String, after: Duration },
+
+    /// The signer answered with an error, e.g. a denied permission.
+    #[error("remote signer rejected {method}: {message}")]
+    Rejected { method: String, message: String },
+
+    /// The answer could not be used.
+    #[error("invalid remote signer response to {method}: {reason}")]
+    InvalidResponse { method: String, reason: String },
+
+    /// Encrypting or signing a request failed.
+    #[error("failed to build remote signer request: {0}")]
+    Crypto(#[source] SdkError),
+
+    #[error(transparent)]
+    Relay(#[from] RelayError),
+}
+
+impl Nip46Error {
+    /// Whether retrying later may succeed without a config change.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::QueueFull(_) | Self::Timeout { .. } => true,
+            Self::Relay(e) => e.is_transient(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_errors_are_not_transient() {
+        assert!(!RelayError::OnionWithoutProxy("ws://x.onion".into()).is_transient());
+        assert!(!RelayError::UnresolvedProxyAddress("tor:9050".into()).is_transient());
+        assert!(!RelayError::NotEphemeral(1).is_transient());
+        let timeout = RelayError::Timeout {
+            operation: "fetching events",
+            after: Duration::from_secs(5),
+        };
+        assert!(timeout.is_transient());
+        assert_eq!(timeout.to_string(), "fetching events timed out after 5s");
+    }
+
+    #[test]
+    fn add_relay_keeps_source() {
+        let err = RelayError::AddProxiedRelay {
+            url: "wss://relay.example".into(),
+            proxy: "127.0.0.1:9050".parse().unwrap(),
+            source: "bad url".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "failed to add relay wss://relay.example via proxy 127.0.0.1:9050: bad url"
+        );
+        assert_eq!(
+            std::error::Error::source(&err).unwrap().to_string(),
+            "bad url"
+        );
+        assert!(!err.is_transient());
+    }
+}
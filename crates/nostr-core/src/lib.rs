@@ -6,12 +6,16 @@
 
 pub mod actions;
 pub mod context;
+pub mod error;
 pub mod key_filter;
 pub mod memory;
 pub mod mention;
+pub mod proxy;
+pub mod reconnect;
 pub mod relay;
 pub mod respond;
 pub mod ring_buffer;
+pub mod signer;
 pub mod tasks;
 
 // Re-export commonly used types
@@ -20,18 +24,27 @@ pub use context::{
     compact_group_header, compact_task_content, format_history_context, push_history,
     truncate_npub, HistoryMessage,
 };
-pub use key_filter::{log_flags, KeyFilter, KeyFilterMetrics, SecurityFlag, SecurityFlagKind};
+pub use error::{Nip46Error, RelayError};
+pub use key_filter::{
+    builtin_patterns, log_flags, should_alert_owner, should_block, KeyFilter, KeyFilterMetrics,
+    PatternAction, SecretPattern, SecurityFlag, SecurityFlagKind,
+};
 pub use memory::{GroupMemory, NostrMemory, NostrMemoryStore, NpubMemory, ProfileMetadata};
 pub use mention::{
     detect_mentions, extract_mentioned_pubkeys, is_mentioned, mentions_pubkey,
     sanitize_content_preview, Mention, MentionType,
 };
-pub use relay::RelayClient;
+pub use proxy::{
+    circuit_status, health_score, is_onion, normalize_url, relay_health, RelayHealth, RelayProxy,
+};
+pub use reconnect::{jitter_sample, BackoffPolicy, ReconnectState, Reconnector};
+pub use relay::{ephemeral_filter, send_ephemeral, PublishReport, RelayClient};
 pub use respond::{
     apply_config_entry, parse_config_event, respond_mode_for_group, DynamicConfig, GroupConfig,
     RespondMode,
 };
 pub use ring_buffer::{ConversationRingBuffer, GroupRingBuffer, MessageEntry};
+pub use signer::{load_or_create_client_keys, BunkerUrl, RemoteSigner, Signer};
 pub use tasks::{build_task_metadata, is_task_status_kind, status_name_for_kind};
 
 // Re-export nostr-sdk for convenience
@@ -0,0 +1,334 @@
+//! SOCKS5/Tor proxy routing and health scoring for relay connections.
+//!
+//! A [`RelayProxy`] decides per relay whether to connect directly or through
+//! a SOCKS5 proxy (typically a local Tor daemon on `127.0.0.1:9050`).
+//! [`relay_health`] scores each relay from its connection stats so a broken
+//! Tor circuit shows up as low scores on the proxied relays.
+
+use crate::error::RelayError;
+use nostr_sdk::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+
+/// Per-relay proxy routing.
+#[derive(Debug, Clone, Default)]
+pub struct RelayProxy {
+    socks5: Option<SocketAddr>,
+    onion_only: bool,
+    /// Relay URL → proxy address, or `None` to force a direct connection.
+    overrides: HashMap<String, Option<SocketAddr>>,
+}
+
+impl RelayProxy {
+    /// Build routing rules.
+    ///
+    /// `socks5` is the default proxy (`host:port`); with `onion_only` it is
+    /// only used for `.onion` relays. `overrides` maps relay URLs to their
+    /// own proxy address or `"direct"`.
+    pub fn new(
+        socks5: Option<&str>,
+        onion_only: bool,
+        overrides: &HashMap<String, String>,
+    ) -> Result<Self, RelayError> {
+        let socks5 = socks5
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(resolve_addr)
+            .transpose()?;
+        let overrides = overrides
+            .iter()
+            .map(|(url, target)| {
+                let target = target.trim();
+                let addr = if target.eq_ignore_ascii_case("direct") {
+                    None
+                } else {
+                    Some(resolve_addr(target)?)
+                };
+                Ok((normalize_url(url), addr))
+            })
+            .collect::<Result<_, RelayError>>()?;
+        Ok(Self {
+            socks5,
+            onion_only,
+            overrides,
+        })
+    }
+
+    /// Whether any relay can go through a proxy.
+    pub fn is_enabled(&self) -> bool {
+        self.socks5.is_some() || self.overrides.values().any(Option::is_some)
+    }
+
+    /// Proxy address for a relay, or `None` for a direct connection.
+    pub fn for_relay(&self, url: &str) -> Option<SocketAddr> {
+        if let Some(target) = self.overrides.get(&normalize_url(url)) {
+            return *target;
+        }
+        match self.socks5 {
+            Some(addr) if !self.onion_only || is_onion(url) => Some(addr),
+            _ => None,
+        }
+    }
+
+    /// Reject `.onion` relays that would be dialed directly.
+    pub fn check_relays(&self, urls: &[String]) -> Result<(), RelayError> {
+        for url in urls {
+            if is_onion(url) && self.for_relay(url).is_none() {
+                return Err(RelayError::OnionWithoutProxy(url.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// A client signing with `signer`, with `relays` added through the proxy
+    /// where one applies. The caller connects it.
+    pub async fn client(
+        &self,
+        signer: impl IntoNostrSigner,
+        relays: &[String],
+    ) -> Result<Client, RelayError> {
+        self.check_relays(relays)?;
+        let client = Client::new(signer);
+        for url in relays {
+            self.add_relay(&client, url).await?;
+        }
+        Ok(client)
+    }
+
+    /// Add a relay to the client, through the proxy when one applies.
+    pub async fn add_relay(&self, client: &Client, url: &str) -> Result<(), RelayError> {
+        self.add_relay_with(client, url, RelayOptions::new()).await
+    }
+
+    /// As [`Self::add_relay`], with `opts` (e.g. `reconnect(false)` when a
+    /// [`Reconnector`](crate::Reconnector) handles reconnects).
+    pub async fn add_relay_with(
+        &self,
+        client: &Client,
+        url: &str,
+        opts: RelayOptions,
+    ) -> Result<(), RelayError> {
+        match self.for_relay(url) {
+            Some(addr) => {
+                let opts = opts.connection_mode(ConnectionMode::proxy(addr));
+                client.pool().add_relay(url, opts).await.map_err(|e| {
+                    RelayError::AddProxiedRelay {
+                        url: url.to_string(),
+                        proxy: addr,
+                        source: e.into(),
+                    }
+                })?;
+            }
+            None => {
+                client
+                    .pool()
+                    .add_relay(url, opts)
+                    .await
+                    .map_err(|e| RelayError::AddRelay {
+                        url: url.to_string(),
+                        source: e.into(),
+                    })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether a relay URL points at a Tor onion service.
+pub fn is_onion(url: &str) -> bool {
+    let host = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['/', ':'])
+        .next()
+        .unwrap_or_default();
+    host.to_ascii_lowercase().ends_with(".onion")
+}
+
+/// Relay URL in the form used to compare relays: trimmed, lowercase and
+/// without a trailing slash.
+pub fn normalize_url(url: &str) -> String {
+    url.trim().trim_end_matches('/').to_ascii_lowercase()
+}
+
+fn resolve_addr(addr: &str) -> Result<SocketAddr, RelayError> {
+    addr.to_socket_addrs()
+        .map_err(|source| RelayError::InvalidProxyAddress {
+            addr: addr.to_string(),
+            source,
+        })?
+        .next()
+        .ok_or_else(|| RelayError::UnresolvedProxyAddress(addr.to_string()))
+}
+
+/// Connection health of one relay.
+#[derive(Debug, Clone, Serialize)]
+pub struct RelayHealth {
+    pub url: String,
+    /// Connected through a proxy (SOCKS5/Tor circuit).
+    pub proxied: bool,
+    pub status: String,
+    pub connected: bool,
+    pub attempts: usize,
+    pub success_rate: f64,
+    pub latency_ms: Option<u64>,
+    /// 0–100; see [`health_score`].
+    pub score: u8,
+}
+
+/// Score a relay from 0 to 100: up to 40 for being connected, up to 40
+/// for the connection success rate and up to 20 for latency. Proxied relays
+/// get more latency headroom, since Tor circuits add several hops.
+pub fn health_score(
+    connected: bool,
+    attempts: usize,
+    success_rate: f64,
+    latency: Option<Duration>,
+    proxied: bool,
+) -> u8 {
+    let mut score = if connected { 40.0 } else { 0.0 };
+    score += if attempts == 0 {
+        20.0
+    } else {
+        40.0 * success_rate.clamp(0.0, 1.0)
+    };
+    let (fast, slow) = if proxied { (1500, 5000) } else { (500, 2000) };
+    score += match latency.map(|l| l.as_millis()) {
+        Some(ms) if ms <= fast => 20.0,
+        Some(ms) if ms <= slow => 10.0,
+        Some(_) => 0.0,
+        None if connected => 10.0,
+        None => 0.0,
+    };
+    score.round().clamp(0.0, 100.0) as u8
+}
+
+/// Health of every relay in the client's pool.
+pub async fn relay_health(client: &Client, proxy: &RelayProxy) -> Vec<RelayHealth> {
+    let mut reports = Vec::new();
+    for (url, relay) in client.relays().await {
+        let url = url.to_string();
+        let stats = relay.stats();
+        let status = relay.status();
+        let connected = status == RelayStatus::Connected;
+        let proxied = proxy.for_relay(&url).is_some();
+        let latency = stats.latency();
+        reports.push(RelayHealth {
+            score: health_score(
+                connected,
+                stats.attempts(),
+                stats.success_rate(),
+                latency,
+                proxied,
+            ),
+            proxied,
+            status: status.to_string(),
+            connected,
+            attempts: stats.attempts(),
+            success_rate: stats.success_rate(),
+            latency_ms: latency.map(|l| u64::try_from(l.as_millis()).unwrap_or(u64::MAX)),
+            url,
+        });
+    }
+    reports.sort_by(|a, b| a.url.cmp(&b.url));
+    reports
+}
+
+/// Proxied relays connected / total, or `None` when nothing is proxied.
+pub fn circuit_status(reports: &[RelayHealth]) -> Option<(usize, usize)> {
+    let proxied: Vec<_> = reports.iter().filter(|r| r.proxied).collect();
+    if proxied.is_empty() {
+        return None;
+    }
+    Some((
+        proxied.iter().filter(|r| r.connected).count(),
+        proxied.len(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn routes_relays_through_proxy() {
+        let proxy = RelayProxy::new(
+            Some("127.0.0.1:9050"),
+            false,
+            &overrides(&[
+                ("wss://local.example/", "direct"),
+                ("wss://other.example", "127.0.0.1:9150"),
+            ]),
+        )
+        .unwrap();
+        assert!(proxy.is_enabled());
+        assert_eq!(
+            proxy.for_relay("wss://relay.damus.io"),
+            Some("127.0.0.1:9050".parse().unwrap())
+        );
+        assert_eq!(proxy.for_relay("wss://local.example"), None);
+        assert_eq!(
+            proxy.for_relay("wss://OTHER.example/"),
+            Some("127.0.0.1:9150".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn onion_only_keeps_clearnet_direct() {
+        let proxy = RelayProxy::new(Some("127.0.0.1:9050"), true, &HashMap::new()).unwrap();
+        assert_eq!(proxy.for_relay("wss://relay.damus.io"), None);
+        assert!(proxy
+            .for_relay("ws://abcdefghijklmnop.onion:8080/nostr")
+            .is_some());
+    }
+
+    #[test]
+    fn onion_relays_need_a_proxy() {
+        let relays = vec!["ws://abcdefghijklmnop.onion".to_string()];
+        assert!(RelayProxy::default().check_relays(&relays).is_err());
+        let proxy = RelayProxy::new(Some("127.0.0.1:9050"), true, &HashMap::new()).unwrap();
+        assert!(proxy.check_relays(&relays).is_ok());
+        assert!(RelayProxy::new(Some("not an address"), false, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn scores_reflect_connection_and_latency() {
+        let healthy = health_score(true, 10, 1.0, Some(Duration::from_millis(200)), false);
+        assert_eq!(healthy, 100);
+        // A slow Tor circuit still scores well; the same latency direct doesn't.
+        let tor = health_score(true, 10, 1.0, Some(Duration::from_millis(1200)), true);
+        let direct = health_score(true, 10, 1.0, Some(Duration::from_millis(1200)), false);
+        assert!(tor > direct);
+        // A dead circuit: disconnected with failing attempts.
+        assert_eq!(health_score(false, 5, 0.0, None, true), 0);
+        assert_eq!(health_score(false, 0, 0.0, None, false), 20);
+    }
+
+    #[test]
+    fn circuit_status_counts_proxied_relays() {
+        let report = |proxied, connected| RelayHealth {
+            url: String::new(),
+            proxied,
+            status: String::new(),
+            connected,
+            attempts: 0,
+            success_rate: 0.0,
+            latency_ms: None,
+            score: 0,
+        };
+        assert_eq!(circuit_status(&[report(false, true)]), None);
+        assert_eq!(
+            circuit_status(&[report(true, false), report(true, true), report(false, true)]),
+            Some((1, 2))
+        );
+    }
+}
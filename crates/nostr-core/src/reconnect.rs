@@ -0,0 +1,370 @@
+//! Adaptive relay reconnection.
+//!
+//! When a relay flaps, every client retrying it on a fixed schedule
+//! reconnects at the same moment. A [`Reconnector`] takes over from the
+//! relay pool's own retries (relays are added with
+//! [`RelayProxy::add_relay_with`](crate::RelayProxy::add_relay_with) and
+//! `reconnect(false)`): each relay backs off exponentially with jitter after
+//! a failed attempt, up to a cap, and the first retry after a drop is
+//! jittered too. Attempts, failures and relay `NOTICE`s are counted per
+//! relay for health reports.
+
+use nostr_sdk::prelude::*;
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Longest relay notice kept for health reports.
+const MAX_NOTICE_CHARS: usize = 200;
+
+/// Backoff schedule for one relay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackoffPolicy {
+    /// Delay after the first failure, doubled per further failure
+    pub base: Duration,
+    /// Upper bound on the delay
+    pub max: Duration,
+    /// Fraction (0–1) of each delay that is randomized away
+    pub jitter: f64,
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_secs(2),
+            max: Duration::from_secs(300),
+            jitter: 0.5,
+        }
+    }
+}
+
+impl BackoffPolicy {
+    /// Delay after `failures` consecutive failures, before jitter.
+    pub fn delay(&self, failures: u32) -> Duration {
+        self.base
+            .saturating_mul(1 << failures.saturating_sub(1).min(16))
+            .min(self.max)
+    }
+
+    /// [`Self::delay`] shortened by up to `jitter` of itself; `sample` is
+    /// uniform in `[0, 1)`.
+    pub fn jittered(&self, failures: u32, sample: f64) -> Duration {
+        let cut = self.jitter.clamp(0.0, 1.0) * sample.clamp(0.0, 1.0);
+        self.delay(failures).mul_f64(1.0 - cut)
+    }
+}
+
+/// A uniform sample in `[0, 1)` for jitter, from the std hasher's random
+/// keys. The default jitter source of a [`Reconnector`].
+pub fn jitter_sample() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// The relay URL and text of a relay `NOTICE` notification.
+pub fn notice(notification: &RelayPoolNotification) -> Option<(String, String)> {
+    match notification {
+        RelayPoolNotification::Message {
+            relay_url,
+            message: RelayMessage::Notice(notice),
+        } => Some((relay_url.to_string(), notice.to_string())),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default)]
+struct RelayState {
+    connected: bool,
+    attempts: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    backoff: Duration,
+    next_attempt: Option<Instant>,
+    last_error: Option<String>,
+    notices: u64,
+    last_notice: Option<String>,
+}
+
+/// Reconnect state and counters of one relay, for health reports.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReconnectState {
+    pub url: String,
+    pub connected: bool,
+    /// Reconnect attempts made
+    pub attempts: u64,
+    /// Reconnect attempts that failed
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    /// Current backoff (jitter applied)
+    pub backoff_secs: u64,
+    /// Seconds until the next attempt, when one is scheduled
+    pub retry_in_secs: Option<u64>,
+    pub last_error: Option<String>,
+    pub notices: u64,
+    pub last_notice: Option<String>,
+}
+
+/// Per-relay backoff and connection metrics.
+#[derive(Debug)]
+pub struct Reconnector {
+    policy: BackoffPolicy,
+    jitter_source: fn() -> f64,
+    relays: Mutex<HashMap<String, RelayState>>,
+}
+
+impl Reconnector {
+    pub fn new(policy: BackoffPolicy) -> Self {
+        Self {
+            policy,
+            jitter_source: jitter_sample,
+            relays: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Draw jitter samples from `source` (uniform in `[0, 1)`) instead of
+    /// [`jitter_sample`], e.g. a seeded stream for reproducible runs.
+    #[must_use]
+    pub fn with_jitter_source(mut self, source: fn() -> f64) -> Self {
+        self.jitter_source = source;
+        self
+    }
+
+    fn with_relay<T>(&self, url: &str, f: impl FnOnce(&mut RelayState) -> T) -> T {
+        let mut relays = self.relays.lock().unwrap_or_else(PoisonError::into_inner);
+        f(relays.entry(url.to_string()).or_default())
+    }
+
+    /// Record a relay seen connected: its backoff resets.
+    pub fn observe_connected(&self, url: &str) {
+        self.with_relay(url, |state| {
+            state.connected = true;
+            state.consecutive_failures = 0;
+            state.backoff = Duration::ZERO;
+            state.next_attempt = None;
+        });
+    }
+
+    /// Record a relay seen disconnected. A relay that just dropped gets a
+    /// jittered first retry, so clients don't all return at once.
+    pub fn observe_disconnected(&self, url: &str, now: Instant, sample: f64) {
+        let policy = self.policy;
+        self.with_relay(url, |state| {
+            if state.connected {
+                state.connected = false;
+                state.backoff = policy.jittered(1, sample);
+                state.next_attempt = Some(now + state.backoff);
+            }
+        });
+    }
+
+    /// Whether a disconnected relay's backoff has elapsed.
+    pub fn is_due(&self, url: &str, now: Instant) -> bool {
+        self.with_relay(url, |state| state.next_attempt.is_none_or(|at| now >= at))
+    }
+
+    /// Record a reconnect attempt. A failure schedules the next attempt and
+    /// returns its delay.
+    pub fn record_attempt(
+        &self,
+        url: &str,
+        result: Result<(), String>,
+        now: Instant,
+        sample: f64,
+    ) -> Option<Duration> {
+        let policy = self.policy;
+        self.with_relay(url, |state| {
+            state.attempts += 1;
+            match result {
+                Ok(()) => {
+                    state.connected = true;
+                    state.consecutive_failures = 0;
+                    state.backoff = Duration::ZERO;
+                    state.next_attempt = None;
+                    None
+                }
+                Err(error) => {
+                    state.connected = false;
+                    state.failures += 1;
+                    state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+                    state.backoff = policy.jittered(state.consecutive_failures, sample);
+                    state.next_attempt = Some(now + state.backoff);
+                    state.last_error = Some(error);
+                    Some(state.backoff)
+                }
+            }
+        })
+    }
+
+    /// Count a relay `NOTICE`; see [`notice`].
+    pub fn record_notice(&self, url: &str, notice: &str) {
+        self.with_relay(url, |state| {
+            state.notices += 1;
+            state.last_notice = Some(notice.chars().take(MAX_NOTICE_CHARS).collect());
+        });
+    }
+
+    /// Every relay's state, sorted by URL.
+    pub fn states(&self, now: Instant) -> Vec<ReconnectState> {
+        let relays = self.relays.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut states: Vec<ReconnectState> = relays
+            .iter()
+            .map(|(url, state)| ReconnectState {
+                url: url.clone(),
+                connected: state.connected,
+                attempts: state.attempts,
+                failures: state.failures,
+                consecutive_failures: state.consecutive_failures,
+                backoff_secs: state.backoff.as_secs(),
+                retry_in_secs: state
+                    .next_attempt
+                    .filter(|_| !state.connected)
+                    .map(|at| at.saturating_duration_since(now).as_secs()),
+                last_error: state.last_error.clone(),
+                notices: state.notices,
+                last_notice: state.last_notice.clone(),
+            })
+            .collect();
+        states.sort_by(|a, b| a.url.cmp(&b.url));
+        states
+    }
+
+    /// Check every relay in the pool and try to connect the disconnected
+    /// ones whose backoff has elapsed, waiting up to `timeout` for each.
+    pub async fn reconnect_due(&self, client: &Client, timeout: Duration) {
+        for (url, relay) in client.relays().await {
+            let url = url.to_string();
+            match relay.status() {
+                RelayStatus::Connected => {
+                    self.observe_connected(&url);
+                    continue;
+                }
+                RelayStatus::Pending | RelayStatus::Connecting => continue,
+                _ => self.observe_disconnected(&url, Instant::now(), (self.jitter_source)()),
+            }
+            if !self.is_due(&url, Instant::now()) {
+                continue;
+            }
+            let result = relay.try_connect(timeout).await.map_err(|e| e.to_string());
+            let failed = result.as_ref().err().cloned();
+            match self.record_attempt(&url, result, Instant::now(), (self.jitter_source)()) {
+                None => info!("Reconnected to relay {url}"),
+                Some(delay) => warn!(
+                    "Reconnect to relay {url} failed: {}; retrying in {}s",
+                    failed.unwrap_or_default(),
+                    delay.as_secs()
+                ),
+            }
+        }
+    }
+}
+
+impl Default for Reconnector {
+    fn default() -> Self {
+        Self::new(BackoffPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> BackoffPolicy {
+        BackoffPolicy {
+            base: Duration::from_secs(2),
+            max: Duration::from_secs(60),
+            jitter: 0.5,
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let p = policy();
+        assert_eq!(p.delay(1), Duration::from_secs(2));
+        assert_eq!(p.delay(2), Duration::from_secs(4));
+        assert_eq!(p.delay(5), Duration::from_secs(32));
+        assert_eq!(p.delay(6), Duration::from_secs(60));
+        assert_eq!(p.delay(u32::MAX), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn jitter_only_shortens_the_delay() {
+        let p = policy();
+        assert_eq!(p.jittered(3, 0.0), Duration::from_secs(8));
+        assert_eq!(p.jittered(3, 0.5), Duration::from_secs(6));
+        assert!(p.jittered(10, 0.999) >= Duration::from_secs(30));
+        for _ in 0..100 {
+            let sample = jitter_sample();
+            assert!((0.0..1.0).contains(&sample));
+        }
+    }
+
+    #[test]
+    fn failures_back_off_and_success_resets() {
+        let r = Reconnector::new(policy());
+        let url = "wss://relay.example";
+        let now = Instant::now();
+        assert!(r.is_due(url, now));
+
+        let err = || Err("refused".to_string());
+        assert_eq!(
+            r.record_attempt(url, err(), now, 0.0),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            r.record_attempt(url, err(), now, 0.0),
+            Some(Duration::from_secs(4))
+        );
+        assert!(!r.is_due(url, now + Duration::from_secs(3)));
+        assert!(r.is_due(url, now + Duration::from_secs(4)));
+
+        let state = &r.states(now)[0];
+        assert_eq!((state.attempts, state.failures), (2, 2));
+        assert_eq!(state.consecutive_failures, 2);
+        assert_eq!(state.retry_in_secs, Some(4));
+        assert_eq!(state.last_error.as_deref(), Some("refused"));
+
+        assert_eq!(r.record_attempt(url, Ok(()), now, 0.0), None);
+        let state = &r.states(now)[0];
+        assert!(state.connected);
+        assert_eq!((state.attempts, state.failures), (3, 2));
+        assert_eq!(state.consecutive_failures, 0);
+        assert_eq!(state.retry_in_secs, None);
+    }
+
+    #[test]
+    fn a_dropped_relay_waits_a_jittered_base_delay() {
+        let r = Reconnector::new(policy());
+        let url = "wss://relay.example";
+        let now = Instant::now();
+        r.observe_connected(url);
+        r.observe_disconnected(url, now, 0.5);
+        assert!(!r.is_due(url, now));
+        assert!(r.is_due(url, now + Duration::from_millis(1500)));
+        // Only the drop itself schedules a retry.
+        r.observe_disconnected(url, now + Duration::from_secs(10), 0.0);
+        assert!(r.is_due(url, now + Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn notices_are_counted_and_truncated() {
+        let r = Reconnector::default();
+        r.record_notice("wss://a.example", "rate limited");
+        r.record_notice("wss://a.example", &"x".repeat(500));
+        let states = r.states(Instant::now());
+        assert_eq!(states[0].notices, 2);
+        assert_eq!(
+            states[0].last_notice.as_ref().map(String::len),
+            Some(MAX_NOTICE_CHARS)
+        );
+    }
+}
@@ -1,29 +1,106 @@
 //! Basic Nostr relay client wrapper functionality.
+//!
+//! Ephemeral kinds (20000-29999) are relayed to current subscribers but
+//! never stored, which suits transient signals between agents (presence,
+//! heartbeats, typing). [`send_ephemeral`] and [`ephemeral_filter`] refuse
+//! stored kinds so a signal can't end up persisted by mistake.
 
-use anyhow::{Context, Result};
+use crate::error::RelayError;
+use crate::proxy::{self, RelayHealth, RelayProxy};
 use nostr_sdk::prelude::*;
 use std::time::Duration;
 use tracing::{info, warn};
 
+/// Per-relay result of a publish, from the relays' NIP-20 `OK` replies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishReport {
+    pub event_id: EventId,
+    /// Relays that stored the event.
+    pub accepted: Vec<String>,
+    /// Relays that rejected the event or never replied, with the reason.
+    pub rejected: Vec<(String, String)>,
+}
+
+impl PublishReport {
+    /// True when at least one relay stored the event.
+    pub fn is_accepted(&self) -> bool {
+        !self.accepted.is_empty()
+    }
+}
+
+impl From<Output<EventId>> for PublishReport {
+    fn from(output: Output<EventId>) -> Self {
+        let mut accepted: Vec<String> = output.success.iter().map(|url| url.to_string()).collect();
+        let mut rejected: Vec<(String, String)> = output
+            .failed
+            .iter()
+            .map(|(url, reason)| (url.to_string(), reason.clone()))
+            .collect();
+        accepted.sort();
+        rejected.sort();
+        Self {
+            event_id: output.val,
+            accepted,
+            rejected,
+        }
+    }
+}
+
+/// Sign and send an ephemeral event. Relays forward it to live
+/// subscriptions only, so there are no retries and no receipts to keep.
+pub async fn send_ephemeral(client: &Client, builder: EventBuilder) -> Result<EventId, RelayError> {
+    let event = client
+        .sign_event_builder(builder)
+        .await
+        .map_err(|e| RelayError::Send(e.into()))?;
+    if !event.kind.is_ephemeral() {
+        return Err(RelayError::NotEphemeral(event.kind.as_u16()));
+    }
+    let output = client
+        .send_event(&event)
+        .await
+        .map_err(|e| RelayError::Send(e.into()))?;
+    Ok(output.val)
+}
+
+/// Filter for live ephemeral events of `kinds`. Relays hold no history for
+/// them, so it starts now.
+pub fn ephemeral_filter(kinds: &[Kind]) -> Result<Filter, RelayError> {
+    if let Some(kind) = kinds.iter().find(|k| !k.is_ephemeral()) {
+        return Err(RelayError::NotEphemeral(kind.as_u16()));
+    }
+    Ok(Filter::new()
+        .kinds(kinds.iter().copied())
+        .since(Timestamp::now()))
+}
+
 /// A simplified Nostr relay client for shared use.
 #[derive(Clone)]
 pub struct RelayClient {
     client: Client,
     keys: Keys,
     relays: Vec<String>,
+    proxy: RelayProxy,
 }
 
 impl RelayClient {
     /// Create a new relay client with the given keys and relay URLs.
-    pub async fn new(keys: Keys, relay_urls: Vec<String>) -> Result<Self> {
+    pub async fn new(keys: Keys, relay_urls: Vec<String>) -> Result<Self, RelayError> {
+        Self::new_with_proxy(keys, relay_urls, RelayProxy::default()).await
+    }
+
+    /// Create a relay client that routes relays through `proxy` (SOCKS5/Tor).
+    pub async fn new_with_proxy(
+        keys: Keys,
+        relay_urls: Vec<String>,
+        proxy: RelayProxy,
+    ) -> Result<Self, RelayError> {
+        proxy.check_relays(&relay_urls)?;
         let client = Client::new(keys.clone());
 
         // Add relays
         for relay_url in &relay_urls {
-            client
-                .add_relay(relay_url.as_str())
-                .await
-                .with_context(|| format!("Failed to add relay: {}", relay_url))?;
+            proxy.add_relay(&client, relay_url).await?;
         }
 
         // Connect to all relays
@@ -34,6 +111,7 @@ impl RelayClient {
             client,
             keys,
             relays: relay_urls,
+            proxy,
         })
     }
 
@@ -43,36 +121,85 @@ impl RelayClient {
     }
 
     /// Subscribe to events matching the given filters.
-    pub async fn subscribe(&self, filters: Vec<Filter>) -> Result<()> {
+    pub async fn subscribe(&self, filters: Vec<Filter>) -> Result<(), RelayError> {
         for filter in filters {
-            self.client.subscribe(filter, None).await?;
+            self.client
+                .subscribe(filter, None)
+                .await
+                .map_err(|e| RelayError::Subscribe(e.into()))?;
         }
         Ok(())
     }
 
+    /// Subscribe to live ephemeral events of `kinds`.
+    pub async fn subscribe_ephemeral(&self, kinds: &[Kind]) -> Result<(), RelayError> {
+        self.subscribe(vec![ephemeral_filter(kinds)?]).await
+    }
+
     /// Send an event to relays.
-    pub async fn send_event(&self, event: Event) -> Result<EventId> {
-        let output = self.client.send_event(&event).await?;
+    pub async fn send_event(&self, event: Event) -> Result<EventId, RelayError> {
+        let output = self
+            .client
+            .send_event(&event)
+            .await
+            .map_err(|e| RelayError::Send(e.into()))?;
         Ok(output.val)
     }
 
+    /// Send an event and report which relays accepted it.
+    pub async fn publish(&self, event: Event) -> Result<PublishReport, RelayError> {
+        let output = self
+            .client
+            .send_event(&event)
+            .await
+            .map_err(|e| RelayError::Send(e.into()))?;
+        let report = PublishReport::from(output);
+        for (relay, reason) in &report.rejected {
+            warn!(
+                "Relay {relay} did not accept event {}: {reason}",
+                report.event_id
+            );
+        }
+        Ok(report)
+    }
+
+    /// Send an ephemeral event (kind 20000-29999); see [`send_ephemeral`].
+    pub async fn send_ephemeral(&self, builder: EventBuilder) -> Result<EventId, RelayError> {
+        send_ephemeral(&self.client, builder).await
+    }
+
     /// Send an event builder to relays.
-    pub async fn send_event_builder(&self, builder: EventBuilder) -> Result<EventId> {
-        let output = self.client.send_event_builder(builder).await?;
+    pub async fn send_event_builder(&self, builder: EventBuilder) -> Result<EventId, RelayError> {
+        let output = self
+            .client
+            .send_event_builder(builder)
+            .await
+            .map_err(|e| RelayError::Send(e.into()))?;
         Ok(output.val)
     }
 
     /// Fetch events matching the given filter with a timeout.
-    pub async fn fetch_events(&self, filter: Filter, timeout: Duration) -> Result<Vec<Event>> {
+    pub async fn fetch_events(
+        &self,
+        filter: Filter,
+        timeout: Duration,
+    ) -> Result<Vec<Event>, RelayError> {
         let events = tokio::time::timeout(timeout, self.client.fetch_events(filter, timeout))
             .await
-            .context("Timeout fetching events")?
-            .context("Failed to fetch events")?;
+            .map_err(|_| RelayError::Timeout {
+                operation: "fetching events",
+                after: timeout,
+            })?
+            .map_err(|e| RelayError::Fetch(e.into()))?;
         Ok(events.into_iter().collect())
     }
 
     /// Send a group message (kind 9) to a NIP-29 group.
-    pub async fn send_group_message(&self, group_id: &str, content: &str) -> Result<EventId> {
+    pub async fn send_group_message(
+        &self,
+        group_id: &str,
+        content: &str,
+    ) -> Result<EventId, RelayError> {
         let builder = EventBuilder::new(Kind::Custom(9), content).tag(Tag::custom(
             TagKind::custom("h"),
             vec![group_id.to_string()],
@@ -81,11 +208,16 @@ impl RelayClient {
     }
 
     /// Send a direct message using NIP-17 (gift wrap).
-    pub async fn send_dm(&self, recipient: &PublicKey, content: &str) -> Result<EventId> {
+    pub async fn send_dm(
+        &self,
+        recipient: &PublicKey,
+        content: &str,
+    ) -> Result<EventId, RelayError> {
         let output = self
             .client
             .send_private_msg(*recipient, content, None)
-            .await?;
+            .await
+            .map_err(|e| RelayError::Send(e.into()))?;
         Ok(output.val)
     }
 
@@ -112,6 +244,11 @@ impl RelayClient {
         &self.relays
     }
 
+    /// Per-relay connection health, including proxy circuit state.
+    pub async fn relay_health(&self) -> Vec<RelayHealth> {
+        proxy::relay_health(&self.client, &self.proxy).await
+    }
+
     /// Health check - verify we can connect to at least one relay.
     pub async fn health_check(&self) -> bool {
         // Try to send a simple filter to test connectivity
@@ -165,6 +302,19 @@ mod tests {
         assert_eq!(group, None);
     }
 
+    #[test]
+    fn ephemeral_filter_rejects_stored_kinds() {
+        assert!(ephemeral_filter(&[Kind::Custom(21121), Kind::Custom(20001)]).is_ok());
+        assert!(matches!(
+            ephemeral_filter(&[Kind::Custom(21121), Kind::TextNote]),
+            Err(RelayError::NotEphemeral(1))
+        ));
+        assert!(matches!(
+            ephemeral_filter(&[Kind::Custom(30000)]),
+            Err(RelayError::NotEphemeral(30000))
+        ));
+    }
+
     #[test]
     fn is_own_event_check() {
         let keys = Keys::generate();
@@ -0,0 +1,612 @@
+//! Event signing: local keys or a NIP-46 remote signer ("bunker").
+//!
+//! [`Signer`] implements nostr-sdk's `NostrSigner`, so a client built with
+//! it signs, encrypts and decrypts through whichever backend holds the key.
+//! With [`Signer::Remote`] the private key never enters the process: every
+//! signature and NIP-04/NIP-44 operation is a kind 24133 request to the
+//! bunker named by a [`BunkerUrl`].
+//!
+//! A [`RemoteSigner`] session starts with `connect` (passing the bunker
+//! secret, if any) and `get_public_key`. Requests then go through a bounded
+//! queue and are sent one at a time, each waiting up to the configured
+//! timeout for its response; a full queue fails new requests right away
+//! instead of letting them pile up behind a bunker that stopped answering.
+//! The session talks to the bunker as a separate client key, which should
+//! be kept across restarts ([`load_or_create_client_keys`]) so the bunker's
+//! authorization survives. Bunker relays are dialed through the same
+//! [`RelayProxy`] as the agent's own relays.
+
+use crate::error::{Nip46Error, RelayError};
+use crate::proxy::RelayProxy;
+use nostr_sdk::nips::{nip04, nip44};
+use nostr_sdk::prelude::*;
+use nostr_sdk::util::BoxedFuture;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tracing::{debug, warn};
+
+/// NIP-46 request and response kind.
+pub const KIND_NOSTR_CONNECT: u16 = 24133;
+
+/// The agent's signer.
+#[derive(Debug, Clone)]
+pub enum Signer {
+    /// Private key held in-process.
+    Local(Keys),
+    /// NIP-46 remote signer.
+    Remote(RemoteSigner),
+}
+
+impl Signer {
+    /// The agent's public key, known without a round trip.
+    pub fn public_key(&self) -> PublicKey {
+        match self {
+            Self::Local(keys) => keys.public_key(),
+            Self::Remote(remote) => remote.public_key(),
+        }
+    }
+
+    pub fn is_remote(&self) -> bool {
+        matches!(self, Self::Remote(_))
+    }
+}
+
+impl From<Keys> for Signer {
+    fn from(keys: Keys) -> Self {
+        Self::Local(keys)
+    }
+}
+
+impl NostrSigner for Signer {
+    fn backend(&self) -> SignerBackend {
+        match self {
+            Self::Local(keys) => NostrSigner::backend(keys),
+            Self::Remote(remote) => remote.backend(),
+        }
+    }
+
+    fn get_public_key(&self) -> BoxedFuture<'_, Result<PublicKey, SignerError>> {
+        match self {
+            Self::Local(keys) => NostrSigner::get_public_key(keys),
+            Self::Remote(remote) => remote.get_public_key(),
+        }
+    }
+
+    fn sign_event(&self, unsigned: UnsignedEvent) -> BoxedFuture<'_, Result<Event, SignerError>> {
+        match self {
+            Self::Local(keys) => NostrSigner::sign_event(keys, unsigned),
+            Self::Remote(remote) => remote.sign_event(unsigned),
+        }
+    }
+
+    fn nip04_encrypt<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        content: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        match self {
+            Self::Local(keys) => NostrSigner::nip04_encrypt(keys, public_key, content),
+            Self::Remote(remote) => remote.nip04_encrypt(public_key, content),
+        }
+    }
+
+    fn nip04_decrypt<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        encrypted_content: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        match self {
+            Self::Local(keys) => NostrSigner::nip04_decrypt(keys, public_key, encrypted_content),
+            Self::Remote(remote) => remote.nip04_decrypt(public_key, encrypted_content),
+        }
+    }
+
+    fn nip44_encrypt<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        content: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        match self {
+            Self::Local(keys) => NostrSigner::nip44_encrypt(keys, public_key, content),
+            Self::Remote(remote) => remote.nip44_encrypt(public_key, content),
+        }
+    }
+
+    fn nip44_decrypt<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        payload: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        match self {
+            Self::Local(keys) => NostrSigner::nip44_decrypt(keys, public_key, payload),
+            Self::Remote(remote) => remote.nip44_decrypt(public_key, payload),
+        }
+    }
+}
+
+/// A parsed `bunker://<signer-pubkey>?relay=<url>&secret=<secret>` URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BunkerUrl {
+    /// Pubkey the bunker answers as.
+    pub signer: PublicKey,
+    pub relays: Vec<RelayUrl>,
+    /// One-time connection secret.
+    pub secret: Option<String>,
+}
+
+impl BunkerUrl {
+    pub fn parse(url: &str) -> Result<Self, Nip46Error> {
+        let invalid = |reason: &str| Nip46Error::InvalidUrl(format!("{reason}: {url}"));
+        let rest = url
+            .trim()
+            .strip_prefix("bunker://")
+            .ok_or_else(|| invalid("expected bunker://"))?;
+        let (pubkey, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let signer = PublicKey::parse(pubkey).map_err(|_| invalid("bad signer pubkey"))?;
+        let mut relays = Vec::new();
+        let mut secret = None;
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(value).ok_or_else(|| invalid("bad escape"))?;
+            match key {
+                "relay" => relays.push(
+                    RelayUrl::parse(&value).map_err(|_| invalid(&format!("bad relay {value}")))?,
+                ),
+                "secret" if !value.is_empty() => secret = Some(value),
+                _ => {}
+            }
+        }
+        if relays.is_empty() {
+            return Err(invalid("no relay"));
+        }
+        Ok(Self {
+            signer,
+            relays,
+            secret,
+        })
+    }
+}
+
+/// Decode `%XX` escapes.
+fn percent_decode(raw: &str) -> Option<String> {
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = raw.get(i + 1..i + 3)?;
+                out.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Client key for bunker sessions stored at `path` (hex secret), created on
+/// first use.
+pub fn load_or_create_client_keys(path: &Path) -> std::io::Result<Keys> {
+    if let Ok(raw) = std::fs::read_to_string(path) {
+        return Keys::parse(raw.trim())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()));
+    }
+    let keys = Keys::generate();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, keys.secret_key().to_secret_hex())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(keys)
+}
+
+/// A NIP-46 JSON-RPC request.
+#[derive(Debug, Serialize)]
+struct Request<'a> {
+    id: &'a str,
+    method: &'a str,
+    params: &'a [String],
+}
+
+/// A NIP-46 JSON-RPC response.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Response {
+    pub id: String,
+    #[serde(default)]
+    pub result: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Kind 24133 request from `client` to `signer`, NIP-44 encrypted.
+pub fn request_event(
+    client: &Keys,
+    signer: &PublicKey,
+    id: &str,
+    method: &str,
+    params: &[String],
+) -> Result<Event, Nip46Error> {
+    let payload = serde_json::to_string(&Request { id, method, params })
+        .map_err(|e| Nip46Error::Crypto(e.into()))?;
+    let content = nip44::encrypt(client.secret_key(), signer, payload, nip44::Version::V2)
+        .map_err(|e| Nip46Error::Crypto(e.into()))?;
+    EventBuilder::new(Kind::Custom(KIND_NOSTR_CONNECT), content)
+        .tag(Tag::public_key(*signer))
+        .sign_with_keys(client)
+        .map_err(|e| Nip46Error::Crypto(e.into()))
+}
+
+/// Decrypt a response addressed to `client`. Older bunkers still answer
+/// with NIP-04, so that is tried when NIP-44 fails.
+pub fn parse_response(client: &Keys, event: &Event) -> Option<Response> {
+    let plaintext = nip44::decrypt(client.secret_key(), &event.pubkey, &event.content)
+        .or_else(|_| nip04::decrypt(client.secret_key(), &event.pubkey, &event.content))
+        .ok()?;
+    serde_json::from_str(&plaintext).ok()
+}
+
+/// A queued request and where its result goes.
+struct Pending {
+    method: String,
+    params: Vec<String>,
+    reply: oneshot::Sender<Result<String, Nip46Error>>,
+}
+
+/// An established NIP-46 session.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    /// The agent's pubkey, as reported by `get_public_key`.
+    user: PublicKey,
+    requests: mpsc::Sender<Pending>,
+    queue_size: usize,
+}
+
+impl RemoteSigner {
+    /// Connect to the bunker as `client`, through `proxy` where it applies,
+    /// and establish a session. Each request, including `connect`, waits up
+    /// to `timeout`.
+    pub async fn connect(
+        url: &BunkerUrl,
+        client: Keys,
+        timeout: Duration,
+        queue_size: usize,
+        proxy: &RelayProxy,
+    ) -> Result<Self, Nip46Error> {
+        let relays: Vec<String> = url.relays.iter().map(ToString::to_string).collect();
+        let relay_client = proxy.client(client.clone(), &relays).await?;
+        relay_client.connect().await;
+        let filter = Filter::new()
+            .kind(Kind::Custom(KIND_NOSTR_CONNECT))
+            .pubkey(client.public_key())
+            .since(Timestamp::now());
+        relay_client
+            .subscribe(filter, None)
+            .await
+            .map_err(|e| RelayError::Subscribe(e.into()))?;
+
+        let queue_size = queue_size.max(1);
+        let (tx, rx) = mpsc::channel(queue_size);
+        let notifications = relay_client.notifications();
+        tokio::spawn(run_session(
+            relay_client,
+            client,
+            url.signer,
+            timeout,
+            rx,
+            notifications,
+        ));
+
+        let mut session = Self {
+            user: url.signer,
+            requests: tx,
+            queue_size,
+        };
+        let mut params = vec![url.signer.to_hex()];
+        params.extend(url.secret.clone());
+        session.request("connect", params).await?;
+        let user = session.request("get_public_key", Vec::new()).await?;
+        session.user = PublicKey::parse(user.trim()).map_err(|e| Nip46Error::InvalidResponse {
+            method: "get_public_key".into(),
+            reason: e.to_string(),
+        })?;
+        Ok(session)
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.user
+    }
+
+    /// Queue a request and wait for its result.
+    pub async fn request(&self, method: &str, params: Vec<String>) -> Result<String, Nip46Error> {
+        let (reply, result) = oneshot::channel();
+        self.requests
+            .try_send(Pending {
+                method: method.to_string(),
+                params,
+                reply,
+            })
+            .map_err(|e| match e {
+                mpsc::error::TrySendError::Full(_) => Nip46Error::QueueFull(self.queue_size),
+                mpsc::error::TrySendError::Closed(_) => Nip46Error::Closed,
+            })?;
+        result.await.map_err(|_| Nip46Error::Closed)?
+    }
+
+    /// Have the bunker sign `unsigned` and check the signature is the
+    /// agent's and covers the requested event.
+    pub async fn sign(&self, unsigned: UnsignedEvent) -> Result<Event, Nip46Error> {
+        let expected = unsigned.id.unwrap_or_else(|| {
+            EventId::new(
+                &unsigned.pubkey,
+                &unsigned.created_at,
+                &unsigned.kind,
+                unsigned.tags.as_slice(),
+                &unsigned.content,
+            )
+        });
+        let signed = self.request("sign_event", vec![unsigned.as_json()]).await?;
+        let invalid = |reason: String| Nip46Error::InvalidResponse {
+            method: "sign_event".into(),
+            reason,
+        };
+        let event = Event::from_json(&signed).map_err(|e| invalid(e.to_string()))?;
+        event.verify().map_err(|e| invalid(e.to_string()))?;
+        if event.pubkey != self.user {
+            return Err(invalid(format!("signed by {}", event.pubkey)));
+        }
+        if event.id != expected {
+            return Err(invalid(format!(
+                "signed event {} instead of {expected}",
+                event.id
+            )));
+        }
+        Ok(event)
+    }
+
+    async fn crypt(
+        &self,
+        method: &str,
+        public_key: &PublicKey,
+        content: &str,
+    ) -> Result<String, SignerError> {
+        self.request(method, vec![public_key.to_hex(), content.to_string()])
+            .await
+            .map_err(SignerError::backend)
+    }
+}
+
+impl NostrSigner for RemoteSigner {
+    fn backend(&self) -> SignerBackend {
+        SignerBackend::NostrConnect
+    }
+
+    fn get_public_key(&self) -> BoxedFuture<'_, Result<PublicKey, SignerError>> {
+        Box::pin(async move { Ok(self.user) })
+    }
+
+    fn sign_event(&self, unsigned: UnsignedEvent) -> BoxedFuture<'_, Result<Event, SignerError>> {
+        Box::pin(async move { self.sign(unsigned).await.map_err(SignerError::backend) })
+    }
+
+    fn nip04_encrypt<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        content: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        Box::pin(self.crypt("nip04_encrypt", public_key, content))
+    }
+
+    fn nip04_decrypt<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        encrypted_content: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        Box::pin(self.crypt("nip04_decrypt", public_key, encrypted_content))
+    }
+
+    fn nip44_encrypt<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        content: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        Box::pin(self.crypt("nip44_encrypt", public_key, content))
+    }
+
+    fn nip44_decrypt<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        payload: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        Box::pin(self.crypt("nip44_decrypt", public_key, payload))
+    }
+}
+
+/// Send queued requests one at a time until every [`RemoteSigner`] clone
+/// is dropped.
+async fn run_session(
+    relay_client: Client,
+    client: Keys,
+    signer: PublicKey,
+    timeout: Duration,
+    mut requests: mpsc::Receiver<Pending>,
+    mut notifications: broadcast::Receiver<RelayPoolNotification>,
+) {
+    let session = Timestamp::now().as_secs();
+    let mut next_id = 0u64;
+    while let Some(pending) = requests.recv().await {
+        next_id += 1;
+        let id = format!("{session}-{next_id}");
+        let result = async {
+            let event = request_event(&client, &signer, &id, &pending.method, &pending.params)?;
+            relay_client
+                .send_event(&event)
+                .await
+                .map_err(|e| RelayError::Send(e.into()))?;
+            tokio::time::timeout(
+                timeout,
+                await_response(&mut notifications, &client, &signer, &id),
+            )
+            .await
+            .map_err(|_| Nip46Error::Timeout {
+                method: pending.method.clone(),
+                after: timeout,
+            })?
+            .and_then(|response| response_result(&pending.method, response))
+        }
+        .await;
+        debug!("nip46 {} → {}", pending.method, result.is_ok());
+        let _ = pending.reply.send(result);
+    }
+    relay_client.shutdown().await;
+}
+
+/// Wait for the response to request `id`.
+async fn await_response(
+    notifications: &mut broadcast::Receiver<RelayPoolNotification>,
+    client: &Keys,
+    signer: &PublicKey,
+    id: &str,
+) -> Result<Response, Nip46Error> {
+    loop {
+        let event = match notifications.recv().await {
+            Ok(RelayPoolNotification::Event { event, .. }) => event,
+            Ok(_) | Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return Err(Nip46Error::Closed),
+        };
+        if event.kind != Kind::Custom(KIND_NOSTR_CONNECT) || event.pubkey != *signer {
+            continue;
+        }
+        let Some(response) = parse_response(client, &event) else {
+            continue;
+        };
+        if response.id != id {
+            continue;
+        }
+        if response.result.as_deref() == Some("auth_url") {
+            warn!(
+                "Remote signer asks for authorization at {}",
+                response.error.as_deref().unwrap_or("?")
+            );
+            continue;
+        }
+        return Ok(response);
+    }
+}
+
+/// The result of a response, or the bunker's error.
+fn response_result(method: &str, response: Response) -> Result<String, Nip46Error> {
+    match (response.result, response.error) {
+        (_, Some(message)) if !message.is_empty() => Err(Nip46Error::Rejected {
+            method: method.to_string(),
+            message,
+        }),
+        (Some(result), _) => Ok(result),
+        _ => Err(Nip46Error::InvalidResponse {
+            method: method.to_string(),
+            reason: "no result".into(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bunker_urls_parse() {
+        let signer = Keys::generate().public_key();
+        let url = BunkerUrl::parse(&format!(
+            "bunker://{}?relay=wss%3A%2F%2Frelay.example&relay=wss://two.example&secret=s3",
+            signer.to_hex()
+        ))
+        .unwrap();
+        assert_eq!(url.signer, signer);
+        assert_eq!(url.relays.len(), 2);
+        assert_eq!(
+            url.relays[0].as_str().trim_end_matches('/'),
+            "wss://relay.example"
+        );
+        assert_eq!(url.secret.as_deref(), Some("s3"));
+
+        assert!(BunkerUrl::parse("nostrconnect://abc").is_err());
+        assert!(BunkerUrl::parse(&format!("bunker://{}", signer.to_hex())).is_err());
+        assert!(BunkerUrl::parse("bunker://nope?relay=wss://relay.example").is_err());
+    }
+
+    #[test]
+    fn requests_round_trip_between_client_and_bunker() {
+        let client = Keys::generate();
+        let bunker = Keys::generate();
+        let event = request_event(
+            &client,
+            &bunker.public_key(),
+            "7",
+            "sign_event",
+            &["{}".to_string()],
+        )
+        .unwrap();
+        assert_eq!(event.kind, Kind::Custom(KIND_NOSTR_CONNECT));
+        assert_eq!(event.tags.public_keys().next(), Some(&bunker.public_key()));
+
+        // The bunker decrypts the request and answers
+        let request =
+            nip44::decrypt(bunker.secret_key(), &client.public_key(), &event.content).unwrap();
+        assert!(request.contains("\"method\":\"sign_event\""));
+        let answer = nip44::encrypt(
+            bunker.secret_key(),
+            &client.public_key(),
+            r#"{"id":"7","result":"ack"}"#,
+            nip44::Version::V2,
+        )
+        .unwrap();
+        let reply = EventBuilder::new(Kind::Custom(KIND_NOSTR_CONNECT), answer)
+            .tag(Tag::public_key(client.public_key()))
+            .sign_with_keys(&bunker)
+            .unwrap();
+        let response = parse_response(&client, &reply).unwrap();
+        assert_eq!(response.id, "7");
+        assert_eq!(response_result("connect", response).unwrap(), "ack");
+    }
+
+    #[test]
+    fn bunker_errors_are_rejections() {
+        let response = Response {
+            id: "1".into(),
+            result: None,
+            error: Some("denied".into()),
+        };
+        let err = response_result("sign_event", response).unwrap_err();
+        assert_eq!(err.to_string(), "remote signer rejected sign_event: denied");
+        assert!(!err.is_transient());
+    }
+
+    #[test]
+    fn client_keys_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nip46_client.key");
+        let first = load_or_create_client_keys(&path).unwrap();
+        let second = load_or_create_client_keys(&path).unwrap();
+        assert_eq!(first.public_key(), second.public_key());
+    }
+
+    #[tokio::test]
+    async fn local_signer_signs_in_process() {
+        let keys = Keys::generate();
+        let signer = Signer::from(keys.clone());
+        assert!(!signer.is_remote());
+        assert_eq!(signer.public_key(), keys.public_key());
+        let event = EventBuilder::text_note("hi").sign(&signer).await.unwrap();
+        assert_eq!(event.pubkey, keys.public_key());
+    }
+}
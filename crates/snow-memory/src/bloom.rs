@@ -0,0 +1,197 @@
+//! Rotating bloom filter for bounded-memory event deduplication.
+//!
+//! Two generations of the same size are kept. Inserts go to the current
+//! generation; lookups check both. Once the current generation holds
+//! `capacity` ids it becomes the previous one and the old previous one is
+//! dropped, so memory stays fixed and every id is remembered for at least
+//! `capacity` further inserts. Each generation is sized for half the
+//! target false-positive rate, since a lookup can hit in either.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(Debug, Clone)]
+struct Generation {
+    bits: Vec<u64>,
+    ones: usize,
+    items: usize,
+}
+
+impl Generation {
+    fn new(words: usize) -> Self {
+        Self {
+            bits: vec![0; words],
+            ones: 0,
+            items: 0,
+        }
+    }
+
+    fn contains(&self, indexes: &[usize]) -> bool {
+        indexes
+            .iter()
+            .all(|&i| self.bits[i / 64] & (1 << (i % 64)) != 0)
+    }
+
+    fn insert(&mut self, indexes: &[usize]) {
+        for &i in indexes {
+            let word = &mut self.bits[i / 64];
+            let mask = 1 << (i % 64);
+            if *word & mask == 0 {
+                *word |= mask;
+                self.ones += 1;
+            }
+        }
+        self.items += 1;
+    }
+
+    /// Chance that an id never inserted matches: (set bits / bits)^k.
+    fn false_positive_rate(&self, hashes: u32) -> f64 {
+        let fill = self.ones as f64 / (self.bits.len() * 64) as f64;
+        fill.powi(hashes as i32)
+    }
+}
+
+/// Bloom filter that forgets old ids in generations of `capacity`.
+#[derive(Debug, Clone)]
+pub struct RotatingBloom {
+    current: Generation,
+    previous: Generation,
+    capacity: usize,
+    hashes: u32,
+    bits: usize,
+}
+
+impl RotatingBloom {
+    /// Filter remembering at least `capacity` ids with a false-positive
+    /// rate of about `fp_rate` (clamped to `1e-9..=0.5`).
+    pub fn new(capacity: usize, fp_rate: f64) -> Self {
+        let capacity = capacity.max(1);
+        let per_generation = (fp_rate / 2.0).clamp(1e-9, 0.25);
+        let ln2 = std::f64::consts::LN_2;
+        // Optimal size m = -n·ln(p) / ln(2)^2, hash count k = m/n·ln(2).
+        let bits = (-(capacity as f64) * per_generation.ln() / (ln2 * ln2)).ceil() as usize;
+        let words = bits.div_ceil(64).max(1);
+        let bits = words * 64;
+        let hashes = ((bits as f64 / capacity as f64) * ln2).round().max(1.0) as u32;
+        Self {
+            current: Generation::new(words),
+            previous: Generation::new(words),
+            capacity,
+            hashes,
+            bits,
+        }
+    }
+
+    /// Whether `id` was (probably) seen, without recording it.
+    pub fn contains(&self, id: &str) -> bool {
+        let indexes = self.indexes(id);
+        self.current.contains(&indexes) || self.previous.contains(&indexes)
+    }
+
+    /// Returns true if `id` was not (probably) seen before, and records it.
+    pub fn check_and_insert(&mut self, id: &str) -> bool {
+        let indexes = self.indexes(id);
+        if self.current.contains(&indexes) || self.previous.contains(&indexes) {
+            return false;
+        }
+        if self.current.items >= self.capacity {
+            let words = self.current.bits.len();
+            self.previous = std::mem::replace(&mut self.current, Generation::new(words));
+        }
+        self.current.insert(&indexes);
+        true
+    }
+
+    /// Ids held in the two generations.
+    pub fn len(&self) -> usize {
+        self.current.items + self.previous.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bytes used by the bit arrays (fixed at construction).
+    pub fn memory_bytes(&self) -> usize {
+        2 * self.bits / 8
+    }
+
+    /// Current false-positive rate estimated from how full both
+    /// generations are.
+    pub fn false_positive_rate(&self) -> f64 {
+        let miss_current = 1.0 - self.current.false_positive_rate(self.hashes);
+        let miss_previous = 1.0 - self.previous.false_positive_rate(self.hashes);
+        1.0 - miss_current * miss_previous
+    }
+
+    /// Bit positions for `id` by double hashing (h1 + i·h2).
+    fn indexes(&self, id: &str) -> Vec<usize> {
+        let h1 = hash_with(id, 0x736e_6f77);
+        let h2 = hash_with(id, 0x636c_6177) | 1;
+        (0..u64::from(self.hashes))
+            .map(|i| (h1.wrapping_add(i.wrapping_mul(h2)) % self.bits as u64) as usize)
+            .collect()
+    }
+}
+
+fn hash_with(id: &str, seed: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    id.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remembers_recent_ids() {
+        let mut bloom = RotatingBloom::new(1000, 1e-6);
+        for i in 0..1000 {
+            assert!(bloom.check_and_insert(&format!("id-{i}")));
+        }
+        // No false negatives: every inserted id is reported as seen.
+        for i in 0..1000 {
+            assert!(!bloom.check_and_insert(&format!("id-{i}")));
+        }
+        assert_eq!(bloom.len(), 1000);
+    }
+
+    #[test]
+    fn memory_stays_bounded_and_old_ids_rotate_out() {
+        let mut bloom = RotatingBloom::new(1000, 0.01);
+        let bytes = bloom.memory_bytes();
+        for i in 0..10_000 {
+            bloom.check_and_insert(&format!("id-{i}"));
+        }
+        assert_eq!(bloom.memory_bytes(), bytes);
+        assert!(bloom.len() <= 2000);
+        // The first ids were two generations ago.
+        let forgotten = (0..100)
+            .filter(|i| bloom.check_and_insert(&format!("id-{i}")))
+            .count();
+        assert!(forgotten > 90, "only {forgotten} of 100 old ids forgotten");
+    }
+
+    #[test]
+    fn measured_false_positive_rate_matches_target() {
+        let target = 0.01;
+        let mut bloom = RotatingBloom::new(5000, target);
+        // Fill both generations completely.
+        for i in 0..10_000 {
+            bloom.check_and_insert(&format!("seen-{i}"));
+        }
+        let trials = 20_000;
+        let false_positives = (0..trials)
+            .filter(|i| bloom.contains(&format!("new-{i}")))
+            .count();
+        let measured = false_positives as f64 / trials as f64;
+        assert!(measured < target * 1.5, "measured {measured}");
+        let estimated = bloom.false_positive_rate();
+        assert!(
+            (estimated - measured).abs() < target,
+            "estimated {estimated}"
+        );
+    }
+}
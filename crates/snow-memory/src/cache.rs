@@ -1,62 +1,196 @@
-//! TTL cache layer for remote relay memories.
+//! TTL + LRU cache layer for remote relay memories.
 //!
-//! Wraps SqliteMemoryIndex with TTL-based invalidation and
-//! deduplication via supersedes chains.
+//! Wraps SqliteMemoryIndex with TTL-based invalidation,
+//! deduplication via supersedes chains, an optional capacity bound with
+//! least-recently-used eviction, and a JSONL snapshot of the hot set
+//! ([`MemoryCache::save_snapshot`], [`MemoryCache::load_snapshot`]) so a
+//! restarted agent can warm-start without waiting for relay sync.
+//! [`MemoryCache::open_with_config`] sizes the cache from [`CacheConfig`],
+//! loads its snapshot on open and writes it back when the cache is dropped.
 
+use crate::config::CacheConfig;
+use crate::error::{MemoryStoreError, StoreResult};
 use crate::search::SqliteMemoryIndex;
 use crate::types::Memory;
-use rusqlite::Result as SqlResult;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
 
-/// Memory cache with TTL eviction.
+/// Memory cache with TTL and LRU eviction.
 pub struct MemoryCache {
     index: SqliteMemoryIndex,
     /// Default TTL in seconds for cached memories.
     pub ttl_secs: u64,
+    /// Maximum number of cached memories (0 = unbounded).
+    pub capacity: usize,
+    /// Snapshot written on drop, from [`CacheConfig::snapshot_path`].
+    snapshot_path: Option<PathBuf>,
+    recency: RefCell<Recency>,
+}
+
+/// Access order of cached memory IDs.
+#[derive(Default)]
+struct Recency {
+    tick: u64,
+    by_id: HashMap<String, u64>,
+    by_tick: BTreeMap<u64, String>,
+}
+
+impl Recency {
+    fn touch(&mut self, id: &str) {
+        self.tick += 1;
+        if let Some(old) = self.by_id.insert(id.to_string(), self.tick) {
+            self.by_tick.remove(&old);
+        }
+        self.by_tick.insert(self.tick, id.to_string());
+    }
+
+    fn remove(&mut self, id: &str) {
+        if let Some(tick) = self.by_id.remove(id) {
+            self.by_tick.remove(&tick);
+        }
+    }
+
+    fn pop_lru(&mut self) -> Option<String> {
+        let (_, id) = self.by_tick.pop_first()?;
+        self.by_id.remove(&id);
+        Some(id)
+    }
+
+    fn len(&self) -> usize {
+        self.by_id.len()
+    }
+
+    /// IDs from least to most recently used.
+    fn ordered(&self) -> Vec<String> {
+        self.by_tick.values().cloned().collect()
+    }
+}
+
+/// One line of the warm-start snapshot.
+#[derive(Serialize, Deserialize)]
+struct SnapshotEntry {
+    cached_at: u64,
+    memory: Memory,
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
 }
 
 impl MemoryCache {
     /// Open a cache backed by a SQLite file.
-    pub fn open(path: &Path, ttl_secs: u64) -> SqlResult<Self> {
+    pub fn open(path: &Path, ttl_secs: u64) -> StoreResult<Self> {
         let index = SqliteMemoryIndex::open(path)?;
-        Ok(Self { index, ttl_secs })
+        Self::from_index(index, ttl_secs)
     }
 
     /// Open an in-memory cache (for testing).
-    pub fn open_in_memory(ttl_secs: u64) -> SqlResult<Self> {
+    pub fn open_in_memory(ttl_secs: u64) -> StoreResult<Self> {
         let index = SqliteMemoryIndex::open_in_memory()?;
-        Ok(Self { index, ttl_secs })
+        Self::from_index(index, ttl_secs)
+    }
+
+    /// Open a cache sized by `config`: SQLite file at `path` (in-memory if
+    /// `None`), warm-started from `config.snapshot_path` when that file
+    /// exists. The snapshot is written back when the cache is dropped.
+    ///
+    /// An unreadable snapshot is logged and skipped; the cache then fills
+    /// from relay sync as usual.
+    pub fn open_with_config(path: Option<&Path>, config: &CacheConfig) -> StoreResult<Self> {
+        let index = match path {
+            Some(p) => SqliteMemoryIndex::open(p)?,
+            None => SqliteMemoryIndex::open_in_memory()?,
+        };
+        let mut cache = Self::from_index(index, config.ttl_secs)?.with_capacity(config.capacity)?;
+        if let Some(snapshot) = config.snapshot_path.as_deref().filter(|p| p.exists()) {
+            match cache.load_snapshot(snapshot) {
+                Ok(n) => log::info!("Warm-started memory cache with {n} memories"),
+                Err(e) => log::warn!("Skipping memory cache snapshot {}: {e}", snapshot.display()),
+            }
+        }
+        cache.snapshot_path = config.snapshot_path.clone();
+        Ok(cache)
+    }
+
+    /// Bound the cache to `capacity` memories (0 = unbounded), evicting the
+    /// least recently used ones beyond it.
+    pub fn with_capacity(mut self, capacity: usize) -> StoreResult<Self> {
+        self.capacity = capacity;
+        self.enforce_capacity()?;
+        Ok(self)
+    }
+
+    fn from_index(index: SqliteMemoryIndex, ttl_secs: u64) -> StoreResult<Self> {
+        // Rows already in a file-backed index count as used in cache order.
+        let mut recency = Recency::default();
+        for (id, _) in index.cache_times()? {
+            recency.touch(&id);
+        }
+        Ok(Self {
+            index,
+            ttl_secs,
+            capacity: 0,
+            snapshot_path: None,
+            recency: RefCell::new(recency),
+        })
     }
 
     /// Cache a memory from a relay event.
     /// If this memory supersedes an existing one, the old one is kept
     /// but the new one takes priority in search results.
-    pub fn cache_memory(&self, memory: &Memory, event_json: Option<&str>) -> SqlResult<()> {
-        self.index.upsert(memory, event_json)
+    pub fn cache_memory(&self, memory: &Memory, event_json: Option<&str>) -> StoreResult<()> {
+        self.index.upsert(memory, event_json)?;
+        self.recency.borrow_mut().touch(&memory.id);
+        self.enforce_capacity()
     }
 
     /// Get a cached memory by ID.
-    pub fn get(&self, id: &str) -> SqlResult<Option<Memory>> {
-        self.index.get(id)
+    pub fn get(&self, id: &str) -> StoreResult<Option<Memory>> {
+        let memory = self.index.get(id)?;
+        if memory.is_some() {
+            self.recency.borrow_mut().touch(id);
+        }
+        Ok(memory)
     }
 
-    /// Search cached memories.
+    /// Search cached memories. Hits count as uses for LRU eviction.
     pub fn search(
         &self,
         query: &str,
         tier_filter: Option<&str>,
         limit: usize,
-    ) -> SqlResult<Vec<(Memory, f64)>> {
-        self.index.search(query, tier_filter, limit)
+    ) -> StoreResult<Vec<(Memory, f64)>> {
+        let results = self.index.search(query, tier_filter, limit)?;
+        let mut recency = self.recency.borrow_mut();
+        for (memory, _) in &results {
+            recency.touch(&memory.id);
+        }
+        Ok(results)
     }
 
     /// Evict memories older than the configured TTL.
-    pub fn evict_stale(&self) -> SqlResult<usize> {
-        self.index.evict_stale(self.ttl_secs)
+    pub fn evict_stale(&self) -> StoreResult<usize> {
+        let evicted = self.index.evict_stale(self.ttl_secs)?;
+        if evicted > 0 {
+            let live: HashMap<String, u64> = self.index.cache_times()?.into_iter().collect();
+            let mut recency = self.recency.borrow_mut();
+            for id in recency.ordered() {
+                if !live.contains_key(&id) {
+                    recency.remove(&id);
+                }
+            }
+        }
+        Ok(evicted)
     }
 
     /// Get total cached memory count.
-    pub fn count(&self) -> SqlResult<usize> {
+    pub fn count(&self) -> StoreResult<usize> {
         self.index.count()
     }
 
@@ -64,6 +198,90 @@ impl MemoryCache {
     pub fn index(&self) -> &SqliteMemoryIndex {
         &self.index
     }
+
+    /// Write cached memories as JSONL, least recently used first, so a
+    /// replay restores the same LRU order. Returns the number written.
+    pub fn save_snapshot(&self, path: &Path) -> StoreResult<usize> {
+        let io_err = |e| MemoryStoreError::snapshot(path, e);
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(io_err)?;
+        }
+        let cached_at: HashMap<String, u64> = self.index.cache_times()?.into_iter().collect();
+
+        let tmp = path.with_extension("tmp");
+        let mut out = BufWriter::new(std::fs::File::create(&tmp).map_err(io_err)?);
+        let mut written = 0;
+        for id in self.recency.borrow().ordered() {
+            let Some(memory) = self.index.get(&id)? else {
+                continue;
+            };
+            let entry = SnapshotEntry {
+                cached_at: cached_at.get(&id).copied().unwrap_or_else(now_secs),
+                memory,
+            };
+            serde_json::to_writer(&mut out, &entry)?;
+            out.write_all(b"\n").map_err(io_err)?;
+            written += 1;
+        }
+        out.flush().map_err(io_err)?;
+        drop(out);
+        std::fs::rename(&tmp, path).map_err(io_err)?;
+        Ok(written)
+    }
+
+    /// Load a snapshot written by [`Self::save_snapshot`], keeping each
+    /// entry's original cache time so the TTL still counts from when it was
+    /// fetched. Entries past the TTL and malformed lines are skipped.
+    /// Returns the number loaded.
+    pub fn load_snapshot(&self, path: &Path) -> StoreResult<usize> {
+        let io_err = |e| MemoryStoreError::snapshot(path, e);
+        let reader = BufReader::new(std::fs::File::open(path).map_err(io_err)?);
+        let cutoff = now_secs().saturating_sub(self.ttl_secs);
+        let mut loaded = 0;
+        for line in reader.lines() {
+            let line = line.map_err(io_err)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Ok(entry) = serde_json::from_str::<SnapshotEntry>(&line) else {
+                log::warn!("Skipping malformed memory cache snapshot line");
+                continue;
+            };
+            if entry.cached_at < cutoff {
+                continue;
+            }
+            self.cache_memory(&entry.memory, None)?;
+            self.index
+                .set_cached_at(&entry.memory.id, entry.cached_at)?;
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Drop least recently used memories until within capacity.
+    fn enforce_capacity(&self) -> StoreResult<()> {
+        if self.capacity == 0 {
+            return Ok(());
+        }
+        let mut recency = self.recency.borrow_mut();
+        while recency.len() > self.capacity {
+            let Some(id) = recency.pop_lru() else { break };
+            self.index.delete(&id)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MemoryCache {
+    fn drop(&mut self) {
+        let Some(path) = self.snapshot_path.take() else {
+            return;
+        };
+        match self.save_snapshot(&path) {
+            Ok(n) => log::info!("Saved {n} cached memories to {}", path.display()),
+            Err(e) => log::warn!("Failed to save memory cache snapshot: {e}"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -115,4 +333,130 @@ mod tests {
         let v2 = cache.get("v2").unwrap().unwrap();
         assert_eq!(v2.supersedes, Some("v1".to_string()));
     }
+
+    fn bounded(capacity: usize) -> MemoryCache {
+        MemoryCache::open_in_memory(3600)
+            .unwrap()
+            .with_capacity(capacity)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = bounded(2);
+        cache
+            .cache_memory(&make_memory("a", "t/a", "alpha"), None)
+            .unwrap();
+        cache
+            .cache_memory(&make_memory("b", "t/b", "bravo"), None)
+            .unwrap();
+        // Touch "a" so "b" becomes least recently used.
+        assert!(cache.get("a").unwrap().is_some());
+        cache
+            .cache_memory(&make_memory("c", "t/c", "charlie"), None)
+            .unwrap();
+
+        assert_eq!(cache.count().unwrap(), 2);
+        assert!(cache.get("b").unwrap().is_none());
+        assert!(cache.get("a").unwrap().is_some());
+        assert!(cache.get("c").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_snapshot_warm_start() {
+        let dir = std::env::temp_dir().join(format!("snow-memory-cache-{}", std::process::id()));
+        let snapshot = dir.join("hot.jsonl");
+        let _ = std::fs::remove_file(&snapshot);
+
+        let cache = bounded(2);
+        cache
+            .cache_memory(&make_memory("a", "t/a", "alpha"), None)
+            .unwrap();
+        cache
+            .cache_memory(&make_memory("b", "t/b", "bravo"), None)
+            .unwrap();
+        cache.index().set_cached_at("b", 1_000).unwrap();
+        cache.get("a").unwrap();
+        assert_eq!(cache.save_snapshot(&snapshot).unwrap(), 2);
+
+        // A fresh cache comes up with the hot set, its LRU order and the
+        // original cache times.
+        let warm = MemoryCache::open_in_memory(u64::MAX)
+            .unwrap()
+            .with_capacity(2)
+            .unwrap();
+        assert_eq!(warm.load_snapshot(&snapshot).unwrap(), 2);
+        let times: HashMap<String, u64> = warm.index().cache_times().unwrap().into_iter().collect();
+        assert_eq!(times["b"], 1_000);
+        warm.cache_memory(&make_memory("c", "t/c", "charlie"), None)
+            .unwrap();
+        assert!(warm.index().get("b").unwrap().is_none());
+        assert!(warm.index().get("a").unwrap().is_some());
+
+        // A smaller capacity keeps only the most recently used entries.
+        let small = MemoryCache::open_in_memory(u64::MAX)
+            .unwrap()
+            .with_capacity(1)
+            .unwrap();
+        small.load_snapshot(&snapshot).unwrap();
+        assert_eq!(small.count().unwrap(), 1);
+        assert!(small.index().get("a").unwrap().is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_config_cache_saves_on_drop_and_warm_starts() {
+        let dir = std::env::temp_dir().join(format!("snow-memory-config-{}", std::process::id()));
+        let config = CacheConfig {
+            ttl_secs: 3600,
+            capacity: 2,
+            snapshot_path: Some(dir.join("hot.jsonl")),
+        };
+
+        let cache = MemoryCache::open_with_config(None, &config).unwrap();
+        for (id, summary) in [("a", "alpha"), ("b", "bravo"), ("c", "charlie")] {
+            cache
+                .cache_memory(&make_memory(id, id, summary), None)
+                .unwrap();
+        }
+        drop(cache);
+
+        let warm = MemoryCache::open_with_config(None, &config).unwrap();
+        assert_eq!(warm.capacity, 2);
+        assert_eq!(warm.count().unwrap(), 2);
+        assert!(warm.index().get("a").unwrap().is_none());
+        assert!(warm.index().get("c").unwrap().is_some());
+        drop(warm);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_snapshot_skips_expired_and_malformed() {
+        let dir = std::env::temp_dir().join(format!("snow-memory-stale-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let snapshot = dir.join("hot.jsonl");
+        let fresh = SnapshotEntry {
+            cached_at: now_secs(),
+            memory: make_memory("fresh", "t/f", "fresh"),
+        };
+        let stale = SnapshotEntry {
+            cached_at: 1,
+            memory: make_memory("stale", "t/s", "stale"),
+        };
+        let body = format!(
+            "{}\nnot json\n{}\n",
+            serde_json::to_string(&stale).unwrap(),
+            serde_json::to_string(&fresh).unwrap()
+        );
+        std::fs::write(&snapshot, body).unwrap();
+
+        let cache = MemoryCache::open_in_memory(3600).unwrap();
+        assert_eq!(cache.load_snapshot(&snapshot).unwrap(), 1);
+        assert!(cache.get("fresh").unwrap().is_some());
+        assert!(cache.get("stale").unwrap().is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -1,7 +1,8 @@
 //! Configuration for the collective memory system.
 
-use crate::types::SourcePreference;
+use crate::types::{MemoryTier, SourcePreference};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Top-level memory configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -29,6 +30,37 @@ pub struct MemoryConfig {
     /// Relay URLs for group tier memories.
     #[serde(default)]
     pub relays_group: Vec<String>,
+    /// Relay URLs for private tier memories.
+    #[serde(default)]
+    pub relays_private: Vec<String>,
+
+    /// Largest boost a frequently and recently used memory gets: its score
+    /// is multiplied by up to `1 + usage_weight`. `0.0` ignores usage.
+    #[serde(default = "default_usage_weight")]
+    pub usage_weight: f64,
+    /// Days after which the usage boost of an unused memory halves. `0`
+    /// disables the decay.
+    #[serde(default = "default_usage_half_life_days")]
+    pub usage_half_life_days: u64,
+}
+
+fn default_usage_weight() -> f64 {
+    0.25
+}
+
+fn default_usage_half_life_days() -> u64 {
+    30
+}
+
+impl MemoryConfig {
+    /// Relay URLs that memories of `tier` are published to.
+    pub fn relays_for(&self, tier: &MemoryTier) -> &[String] {
+        match tier {
+            MemoryTier::Public => &self.relays_public,
+            MemoryTier::Group(_) => &self.relays_group,
+            MemoryTier::Private(_) => &self.relays_private,
+        }
+    }
 }
 
 impl Default for MemoryConfig {
@@ -58,6 +90,83 @@ impl Default for MemoryConfig {
             ],
             relays_public: vec![],
             relays_group: vec![],
+            relays_private: vec![],
+            usage_weight: default_usage_weight(),
+            usage_half_life_days: default_usage_half_life_days(),
+        }
+    }
+}
+
+/// Sizing of the relay memory cache ([`crate::MemoryCache`]).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CacheConfig {
+    /// Seconds a cached memory stays valid.
+    #[serde(default = "default_cache_ttl")]
+    pub ttl_secs: u64,
+    /// Maximum number of cached memories; least recently used ones are
+    /// evicted beyond this. `0` means unbounded.
+    #[serde(default)]
+    pub capacity: usize,
+    /// JSONL snapshot of the hot set, loaded when the cache opens and
+    /// written when it is dropped. No snapshot when unset.
+    #[serde(default)]
+    pub snapshot_path: Option<PathBuf>,
+}
+
+fn default_cache_ttl() -> u64 {
+    7 * 24 * 3600
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: default_cache_ttl(),
+            capacity: 0,
+            snapshot_path: None,
+        }
+    }
+}
+
+/// How [`crate::EventDedup`] remembers seen event ids.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupMode {
+    /// Exact id set; no false positives, memory grows with `capacity`.
+    #[default]
+    Exact,
+    /// Rotating bloom filter; fixed memory, rare false positives (a new
+    /// event dropped as a duplicate). For firehose subscriptions.
+    Bloom,
+}
+
+/// Event deduplication for relay subscriptions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DedupConfig {
+    #[serde(default)]
+    pub mode: DedupMode,
+    /// Ids remembered: the exact set's size cap, or the bloom filter's
+    /// generation size.
+    #[serde(default = "default_dedup_capacity")]
+    pub capacity: usize,
+    /// Target false-positive rate in `bloom` mode.
+    #[serde(default = "default_dedup_fp_rate")]
+    pub false_positive_rate: f64,
+}
+
+fn default_dedup_capacity() -> usize {
+    10_000
+}
+
+fn default_dedup_fp_rate() -> f64 {
+    0.001
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self {
+            mode: DedupMode::default(),
+            capacity: default_dedup_capacity(),
+            false_positive_rate: default_dedup_fp_rate(),
         }
     }
 }
@@ -109,5 +218,43 @@ relays_public = ["wss://relay.damus.io"]
         assert_eq!(config.tier1, vec!["anthropic/claude-opus-4-6"]);
         assert_eq!(config.relays_public, vec!["wss://relay.damus.io"]);
         assert!(config.sources.is_empty());
+        assert_eq!(config.usage_weight, 0.25);
+        assert_eq!(config.usage_half_life_days, 30);
+    }
+
+    #[test]
+    fn relays_follow_memory_tier() {
+        let config = MemoryConfig {
+            relays_public: vec!["wss://public.example.com".to_string()],
+            relays_private: vec!["wss://private.example.com".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.relays_for(&MemoryTier::Public),
+            ["wss://public.example.com"]
+        );
+        assert!(config
+            .relays_for(&MemoryTier::Group("dev".into()))
+            .is_empty());
+        assert_eq!(
+            config.relays_for(&MemoryTier::Private("self".into())),
+            ["wss://private.example.com"]
+        );
+    }
+
+    #[test]
+    fn cache_config_defaults() {
+        let config: CacheConfig = toml::from_str("capacity = 500").unwrap();
+        assert_eq!(config.capacity, 500);
+        assert_eq!(config.ttl_secs, CacheConfig::default().ttl_secs);
+        assert_eq!(config.snapshot_path, None);
+    }
+
+    #[test]
+    fn dedup_config_selects_bloom() {
+        let config: DedupConfig = toml::from_str("mode = \"bloom\"").unwrap();
+        assert_eq!(config.mode, DedupMode::Bloom);
+        assert_eq!(config.capacity, 10_000);
+        assert_eq!(DedupConfig::default().mode, DedupMode::Exact);
     }
 }
@@ -0,0 +1,152 @@
+//! Typed errors for event conversion, signing, storage, ranking, and config
+//! loading.
+//!
+//! Callers can match on these instead of parsing strings:
+//! [`MemoryStoreError::is_transient`] separates a busy/locked database
+//! (retry later) from I/O, serialization, and corruption failures.
+
+use crate::event::{D_TAG_PREFIX, KIND_APP_SPECIFIC};
+use crate::loader::ConfigSource;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// A Nostr event that does not match the snow-memory event schema.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum EventSchemaError {
+    #[error("missing required tag: {0}")]
+    MissingTag(String),
+    #[error("invalid tag '{tag}': {reason}")]
+    InvalidTag { tag: String, reason: String },
+    #[error("invalid content: {0}")]
+    InvalidContent(String),
+    #[error("wrong event kind: {0}, expected {KIND_APP_SPECIFIC}")]
+    WrongKind(u64),
+    #[error("invalid d-tag: '{0}', expected prefix '{D_TAG_PREFIX}'")]
+    InvalidDTag(String),
+}
+
+/// Failure reading or writing the SQLite index or the cache snapshot.
+#[derive(Debug, Error)]
+pub enum MemoryStoreError {
+    /// SQLite failure; see [`Self::is_transient`] for busy/locked.
+    #[error("memory index: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    /// Reading or writing the JSONL snapshot file.
+    #[error("memory cache snapshot {}: {source}", path.display())]
+    Snapshot {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// An archived memory or snapshot entry could not be serialized.
+    #[error("memory serialization: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Result of a [`crate::SqliteMemoryIndex`] or [`crate::MemoryCache`] call.
+pub type StoreResult<T> = Result<T, MemoryStoreError>;
+
+impl MemoryStoreError {
+    pub(crate) fn snapshot(path: &std::path::Path, source: std::io::Error) -> Self {
+        Self::Snapshot {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    /// Whether retrying later may succeed: the database was busy or
+    /// locked by another connection, or snapshot I/O was interrupted.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Sqlite(rusqlite::Error::SqliteFailure(e, _)) => matches!(
+                e.code,
+                rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked
+            ),
+            Self::Snapshot { source, .. } => matches!(
+                source.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::WouldBlock
+            ),
+            _ => false,
+        }
+    }
+}
+
+/// Ranking configuration that cannot produce meaningful scores.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum RankingError {
+    #[error("source '{source_id}' has trust {trust}, expected 0.0..=1.0")]
+    InvalidTrust { source_id: String, trust: f64 },
+    #[error("tier {tier} model pattern '{pattern}': '*' is only allowed as a suffix")]
+    InvalidTierPattern { tier: u8, pattern: String },
+    #[error("memory '{id}' has relevance {relevance}, expected a finite value >= 0")]
+    InvalidRelevance { id: String, relevance: f64 },
+    #[error("usage_weight is {0}, expected a finite value >= 0")]
+    InvalidUsageWeight(f64),
+}
+
+/// An externally made signature that cannot be attached to an
+/// [`crate::UnsignedEvent`].
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SignatureError {
+    #[error("invalid pubkey '{0}', expected 64 hex characters")]
+    InvalidPubkey(String),
+    #[error("invalid signature '{0}', expected 128 hex characters")]
+    InvalidSignature(String),
+    #[error("event id {actual} does not match the computed id {expected}")]
+    IdMismatch { expected: String, actual: String },
+}
+
+/// A config layer that cannot be loaded by [`crate::ConfigLoader`].
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ConfigError {
+    #[error("{0} config is not a table")]
+    NotAnObject(ConfigSource),
+    #[error("{origin} config: {message}")]
+    Parse {
+        origin: ConfigSource,
+        message: String,
+    },
+    #[error("{var}: {reason}")]
+    Env { var: String, reason: String },
+    #[error("{field} ({origin}): {reason}")]
+    OutOfRange {
+        field: String,
+        origin: ConfigSource,
+        reason: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn busy_database_is_transient() {
+        let busy = MemoryStoreError::Sqlite(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            None,
+        ));
+        assert!(busy.is_transient());
+        let corrupt = MemoryStoreError::Sqlite(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CORRUPT),
+            None,
+        ));
+        assert!(!corrupt.is_transient());
+    }
+
+    #[test]
+    fn sources_are_preserved() {
+        let err = MemoryStoreError::snapshot(
+            std::path::Path::new("/tmp/cache.jsonl"),
+            std::io::Error::new(std::io::ErrorKind::NotFound, "gone"),
+        );
+        assert_eq!(
+            err.to_string(),
+            "memory cache snapshot /tmp/cache.jsonl: gone"
+        );
+        let source = std::error::Error::source(&err).unwrap();
+        assert_eq!(source.to_string(), "gone");
+    }
+}
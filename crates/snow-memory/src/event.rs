@@ -4,8 +4,12 @@
 //! depending on nostr-sdk directly, keeping the crate lightweight.
 //! Integrators convert to/from their concrete Nostr event types.
 
-use crate::types::{AgentProfile, Memory, MemoryTier};
+use crate::error::EventSchemaError;
+use crate::types::{
+    AgentDirectoryEntry, AgentProfile, AgentStatus, AgentStatusEntry, Memory, MemoryTier,
+};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 
 /// NIP-78 event kind for application-specific data.
 pub const KIND_APP_SPECIFIC: u64 = 30078;
@@ -21,6 +25,39 @@ pub const TAG_CONFIDENCE: &str = "snow:confidence";
 pub const TAG_SOURCE: &str = "snow:source";
 pub const TAG_VERSION: &str = "snow:version";
 pub const TAG_SUPERSEDES: &str = "snow:supersedes";
+/// Group namespace of a `group` tier memory.
+pub const TAG_GROUP: &str = "snow:group";
+/// Event schema version, distinct from a memory's own `snow:version`.
+pub const TAG_SCHEMA: &str = "snow:schema";
+
+/// Schema version written by [`memory_to_event`].
+///
+/// - 1: events without a `snow:schema` tag; group memories may lack
+///   `snow:group`.
+/// - 2: adds `snow:schema`; group memories always carry `snow:group`.
+///
+/// Older events are upgraded one version at a time before parsing. Newer
+/// events are parsed with the current rules, so later versions may only add
+/// tags and content fields that older agents can ignore.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Upgrade steps; entry `n` rewrites a version `n + 1` event into version
+/// `n + 2`.
+const UPGRADES: &[fn(&mut MemoryEvent)] = &[upgrade_v1];
+
+/// `t` tag value marking a kind 0 event as a Snowclaw agent profile, so
+/// discovery can filter for agents.
+pub const AGENT_PROFILE_TAG: &str = "snow-agent";
+
+/// d-tag of the replaceable agent status event.
+pub const STATUS_D_TAG: &str = "snow:status";
+/// `t` tag value marking an agent status event.
+pub const AGENT_STATUS_TAG: &str = "snow-status";
+
+/// Tag prefix for conflict notice d-tags.
+pub const CONFLICT_D_TAG_PREFIX: &str = "snow:conflict:";
+/// Tag naming one memory involved in a conflict notice.
+pub const TAG_CONFLICT_MEMORY: &str = "snow:memory";
 
 /// A lightweight representation of a Nostr event for conversion purposes.
 /// Integrators map this to/from their concrete event types (e.g. nostr_sdk::Event).
@@ -44,40 +81,6 @@ pub struct MemoryContent {
     pub context: Option<String>,
 }
 
-/// Errors from event conversion.
-#[derive(Debug, Clone, PartialEq)]
-pub enum ConversionError {
-    MissingTag(String),
-    InvalidTag { tag: String, reason: String },
-    InvalidContent(String),
-    WrongKind(u64),
-    InvalidDTag(String),
-}
-
-impl std::fmt::Display for ConversionError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ConversionError::MissingTag(t) => write!(f, "missing required tag: {}", t),
-            ConversionError::InvalidTag { tag, reason } => {
-                write!(f, "invalid tag '{}': {}", tag, reason)
-            }
-            ConversionError::InvalidContent(e) => write!(f, "invalid content: {}", e),
-            ConversionError::WrongKind(k) => {
-                write!(f, "wrong event kind: {}, expected {}", k, KIND_APP_SPECIFIC)
-            }
-            ConversionError::InvalidDTag(d) => {
-                write!(
-                    f,
-                    "invalid d-tag: '{}', expected prefix '{}'",
-                    d, D_TAG_PREFIX
-                )
-            }
-        }
-    }
-}
-
-impl std::error::Error for ConversionError {}
-
 impl MemoryEvent {
     fn get_tag(&self, key: &str) -> Option<&str> {
         self.tags
@@ -86,9 +89,9 @@ impl MemoryEvent {
             .map(|(_, v)| v.as_str())
     }
 
-    fn require_tag(&self, key: &str) -> Result<&str, ConversionError> {
+    fn require_tag(&self, key: &str) -> Result<&str, EventSchemaError> {
         self.get_tag(key)
-            .ok_or_else(|| ConversionError::MissingTag(key.to_string()))
+            .ok_or_else(|| EventSchemaError::MissingTag(key.to_string()))
     }
 }
 
@@ -111,8 +114,13 @@ pub fn memory_to_event(memory: &Memory) -> MemoryEvent {
         ),
         (TAG_SOURCE.to_string(), memory.source.clone()),
         (TAG_VERSION.to_string(), memory.version.to_string()),
+        (TAG_SCHEMA.to_string(), SCHEMA_VERSION.to_string()),
     ];
 
+    if let MemoryTier::Group(ref group) = memory.tier {
+        tags.push((TAG_GROUP.to_string(), group.clone()));
+    }
+
     if let Some(ref sup) = memory.supersedes {
         tags.push((TAG_SUPERSEDES.to_string(), sup.clone()));
     }
@@ -132,59 +140,20 @@ pub fn memory_to_event(memory: &Memory) -> MemoryEvent {
 }
 
 /// Convert a MemoryEvent back to a Memory. Validates required tags.
-pub fn memory_from_event(event: &MemoryEvent) -> Result<Memory, ConversionError> {
-    if event.kind != KIND_APP_SPECIFIC {
-        return Err(ConversionError::WrongKind(event.kind));
-    }
-
-    let d_tag = event.require_tag("d")?;
-    let topic = d_tag
-        .strip_prefix(D_TAG_PREFIX)
-        .ok_or_else(|| ConversionError::InvalidDTag(d_tag.to_string()))?
-        .to_string();
-
-    let tier_str = event.require_tag(TAG_TIER)?;
-    let tier = match tier_str {
-        "public" => MemoryTier::Public,
-        "group" => MemoryTier::Group(String::new()),
-        "private" => MemoryTier::Private(String::new()),
-        other => {
-            return Err(ConversionError::InvalidTag {
-                tag: TAG_TIER.to_string(),
-                reason: format!("unknown tier: {}", other),
-            })
-        }
-    };
-
+///
+/// Events of an older schema are upgraded first; see [`SCHEMA_VERSION`].
+pub fn memory_from_event(event: &MemoryEvent) -> Result<Memory, EventSchemaError> {
+    check_kind(event)?;
+    let event = upgrade_event(event)?;
+    let event = event.as_ref();
+    let topic = parse_topic(event)?;
+    let tier = parse_tier(event)?;
     let model = event.require_tag(TAG_MODEL)?.to_string();
-    let confidence_str = event.require_tag(TAG_CONFIDENCE)?;
-    let confidence: f64 = confidence_str
-        .parse()
-        .map_err(|_| ConversionError::InvalidTag {
-            tag: TAG_CONFIDENCE.to_string(),
-            reason: format!("not a valid f64: {}", confidence_str),
-        })?;
-
-    if !(0.0..=1.0).contains(&confidence) {
-        return Err(ConversionError::InvalidTag {
-            tag: TAG_CONFIDENCE.to_string(),
-            reason: format!("out of range [0.0, 1.0]: {}", confidence),
-        });
-    }
-
+    let confidence = parse_confidence(event)?;
     let source = event.require_tag(TAG_SOURCE)?.to_string();
-    let version_str = event.require_tag(TAG_VERSION)?;
-    let version: u32 = version_str
-        .parse()
-        .map_err(|_| ConversionError::InvalidTag {
-            tag: TAG_VERSION.to_string(),
-            reason: format!("not a valid u32: {}", version_str),
-        })?;
-
+    let version = parse_version(event)?;
     let supersedes = event.get_tag(TAG_SUPERSEDES).map(|s| s.to_string());
-
-    let content: MemoryContent = serde_json::from_str(&event.content)
-        .map_err(|e| ConversionError::InvalidContent(e.to_string()))?;
+    let content = parse_content(event)?;
 
     let tags: Vec<String> = event
         .tags
@@ -210,6 +179,159 @@ pub fn memory_from_event(event: &MemoryEvent) -> Result<Memory, ConversionError>
     })
 }
 
+/// Schema version of `event`. Events without a `snow:schema` tag are
+/// version 1.
+pub fn schema_version(event: &MemoryEvent) -> Result<u32, EventSchemaError> {
+    let Some(value) = event.get_tag(TAG_SCHEMA) else {
+        return Ok(1);
+    };
+    match value.parse() {
+        Ok(version) if version >= 1 => Ok(version),
+        _ => Err(EventSchemaError::InvalidTag {
+            tag: TAG_SCHEMA.to_string(),
+            reason: format!("not a schema version: {}", value),
+        }),
+    }
+}
+
+/// Rewrite an event of an older schema into the current one. Events of the
+/// current or a newer schema are returned as they are.
+pub fn upgrade_event(event: &MemoryEvent) -> Result<Cow<'_, MemoryEvent>, EventSchemaError> {
+    let version = schema_version(event)?;
+    if version >= SCHEMA_VERSION {
+        return Ok(Cow::Borrowed(event));
+    }
+    let mut upgraded = event.clone();
+    for upgrade in &UPGRADES[(version - 1) as usize..] {
+        upgrade(&mut upgraded);
+    }
+    upgraded.tags.retain(|(k, _)| k != TAG_SCHEMA);
+    upgraded
+        .tags
+        .push((TAG_SCHEMA.to_string(), SCHEMA_VERSION.to_string()));
+    Ok(Cow::Owned(upgraded))
+}
+
+/// Version 1 group memories may predate the group namespace; they get an
+/// empty one.
+fn upgrade_v1(event: &mut MemoryEvent) {
+    if event.get_tag(TAG_TIER) == Some("group") && event.get_tag(TAG_GROUP).is_none() {
+        event.tags.push((TAG_GROUP.to_string(), String::new()));
+    }
+}
+
+/// A single problem found by [`validate_memory_event`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ValidationIssue {
+    /// Tag name, or `kind` / `content` for the event fields.
+    pub field: String,
+    pub message: String,
+}
+
+impl From<EventSchemaError> for ValidationIssue {
+    fn from(err: EventSchemaError) -> Self {
+        let field = match &err {
+            EventSchemaError::MissingTag(tag) => tag.clone(),
+            EventSchemaError::InvalidTag { tag, .. } => tag.clone(),
+            EventSchemaError::InvalidContent(_) => "content".to_string(),
+            EventSchemaError::WrongKind(_) => "kind".to_string(),
+            EventSchemaError::InvalidDTag(_) => "d".to_string(),
+        };
+        ValidationIssue {
+            field,
+            message: err.to_string(),
+        }
+    }
+}
+
+/// Check a memory event against the same rules as [`memory_from_event`],
+/// collecting every problem instead of stopping at the first.
+///
+/// An empty result means the event will be accepted by other agents.
+pub fn validate_memory_event(event: &MemoryEvent) -> Vec<ValidationIssue> {
+    let (event, schema) = match upgrade_event(event) {
+        Ok(upgraded) => (upgraded, None),
+        Err(err) => (Cow::Borrowed(event), Some(err)),
+    };
+    let event = event.as_ref();
+    let checks = [
+        check_kind(event),
+        parse_topic(event).map(drop),
+        parse_tier(event).map(drop),
+        event.require_tag(TAG_MODEL).map(drop),
+        parse_confidence(event).map(drop),
+        event.require_tag(TAG_SOURCE).map(drop),
+        parse_version(event).map(drop),
+        parse_content(event).map(drop),
+    ];
+    schema
+        .into_iter()
+        .chain(checks.into_iter().filter_map(Result::err))
+        .map(ValidationIssue::from)
+        .collect()
+}
+
+fn check_kind(event: &MemoryEvent) -> Result<(), EventSchemaError> {
+    if event.kind == KIND_APP_SPECIFIC {
+        Ok(())
+    } else {
+        Err(EventSchemaError::WrongKind(event.kind))
+    }
+}
+
+fn parse_topic(event: &MemoryEvent) -> Result<String, EventSchemaError> {
+    let d_tag = event.require_tag("d")?;
+    d_tag
+        .strip_prefix(D_TAG_PREFIX)
+        .map(|t| t.to_string())
+        .ok_or_else(|| EventSchemaError::InvalidDTag(d_tag.to_string()))
+}
+
+fn parse_tier(event: &MemoryEvent) -> Result<MemoryTier, EventSchemaError> {
+    match event.require_tag(TAG_TIER)? {
+        "public" => Ok(MemoryTier::Public),
+        "group" => Ok(MemoryTier::Group(event.require_tag(TAG_GROUP)?.to_string())),
+        "private" => Ok(MemoryTier::Private(String::new())),
+        other => Err(EventSchemaError::InvalidTag {
+            tag: TAG_TIER.to_string(),
+            reason: format!("unknown tier: {}", other),
+        }),
+    }
+}
+
+fn parse_confidence(event: &MemoryEvent) -> Result<f64, EventSchemaError> {
+    let confidence_str = event.require_tag(TAG_CONFIDENCE)?;
+    let confidence: f64 = confidence_str
+        .parse()
+        .map_err(|_| EventSchemaError::InvalidTag {
+            tag: TAG_CONFIDENCE.to_string(),
+            reason: format!("not a valid f64: {}", confidence_str),
+        })?;
+
+    if !(0.0..=1.0).contains(&confidence) {
+        return Err(EventSchemaError::InvalidTag {
+            tag: TAG_CONFIDENCE.to_string(),
+            reason: format!("out of range [0.0, 1.0]: {}", confidence),
+        });
+    }
+    Ok(confidence)
+}
+
+fn parse_version(event: &MemoryEvent) -> Result<u32, EventSchemaError> {
+    let version_str = event.require_tag(TAG_VERSION)?;
+    version_str
+        .parse()
+        .map_err(|_| EventSchemaError::InvalidTag {
+            tag: TAG_VERSION.to_string(),
+            reason: format!("not a valid u32: {}", version_str),
+        })
+}
+
+fn parse_content(event: &MemoryEvent) -> Result<MemoryContent, EventSchemaError> {
+    serde_json::from_str(&event.content)
+        .map_err(|e| EventSchemaError::InvalidContent(e.to_string()))
+}
+
 /// Convert an AgentProfile to a kind-0-style metadata JSON string.
 pub fn profile_to_metadata(profile: &AgentProfile) -> String {
     let mut map = serde_json::Map::new();
@@ -245,13 +367,28 @@ pub fn profile_to_metadata(profile: &AgentProfile) -> String {
             serde_json::Value::String(op.clone()),
         );
     }
+    if let Some(tier) = profile.model_tier {
+        map.insert("snow:model_tier".to_string(), serde_json::Value::from(tier));
+    }
+    if !profile.actions.is_empty() {
+        map.insert(
+            "snow:actions".to_string(),
+            serde_json::Value::Array(
+                profile
+                    .actions
+                    .iter()
+                    .map(|a| serde_json::Value::String(a.clone()))
+                    .collect(),
+            ),
+        );
+    }
     serde_json::to_string(&map).expect("profile metadata is always serializable")
 }
 
 /// Parse an AgentProfile from a kind-0 metadata JSON string.
-pub fn profile_from_metadata(json: &str) -> Result<AgentProfile, ConversionError> {
+pub fn profile_from_metadata(json: &str) -> Result<AgentProfile, EventSchemaError> {
     let map: serde_json::Map<String, serde_json::Value> =
-        serde_json::from_str(json).map_err(|e| ConversionError::InvalidContent(e.to_string()))?;
+        serde_json::from_str(json).map_err(|e| EventSchemaError::InvalidContent(e.to_string()))?;
 
     let name = map
         .get("name")
@@ -266,26 +403,34 @@ pub fn profile_from_metadata(json: &str) -> Result<AgentProfile, ConversionError
     let model = map
         .get("snow:model")
         .and_then(|v| v.as_str())
-        .ok_or_else(|| ConversionError::MissingTag("snow:model".to_string()))?
+        .ok_or_else(|| EventSchemaError::MissingTag("snow:model".to_string()))?
         .to_string();
     let version = map
         .get("snow:version")
         .and_then(|v| v.as_str())
         .unwrap_or("0.0.0")
         .to_string();
-    let capabilities = map
-        .get("snow:capabilities")
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                .collect()
-        })
-        .unwrap_or_default();
+    let string_list = |key: &str| -> Vec<String> {
+        map.get(key)
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let capabilities = string_list("snow:capabilities");
+    let actions = string_list("snow:actions");
     let operator = map
         .get("snow:operator")
         .and_then(|v| v.as_str())
         .map(|s| s.to_string());
+    let model_tier = map
+        .get("snow:model_tier")
+        .and_then(|v| v.as_u64())
+        .and_then(|tier| u8::try_from(tier).ok())
+        .filter(|tier| (1..=4).contains(tier));
 
     Ok(AgentProfile {
         name,
@@ -294,6 +439,25 @@ pub fn profile_from_metadata(json: &str) -> Result<AgentProfile, ConversionError
         version,
         capabilities,
         operator,
+        model_tier,
+        actions,
+    })
+}
+
+/// Parse a raw JSON kind 0 event (as serde_json::Value) into an agent
+/// directory entry. Returns None for other kinds and for profiles without
+/// agent metadata.
+pub fn event_json_to_agent(event: &serde_json::Value) -> Option<AgentDirectoryEntry> {
+    if event.get("kind")?.as_u64()? != KIND_METADATA {
+        return None;
+    }
+    let pubkey = event.get("pubkey")?.as_str()?.to_string();
+    let updated_at = event.get("created_at")?.as_u64()?;
+    let profile = profile_from_metadata(event.get("content")?.as_str()?).ok()?;
+    Some(AgentDirectoryEntry {
+        pubkey,
+        profile,
+        updated_at,
     })
 }
 
@@ -336,22 +500,161 @@ pub fn event_json_to_memory(event: &serde_json::Value) -> Option<Memory> {
 
 /// Convert an AgentProfile to a MemoryEvent (kind 0).
 pub fn profile_to_event(profile: &AgentProfile, pubkey: &str) -> MemoryEvent {
-    let content = profile_to_metadata(profile);
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs();
+    profile_to_event_at(profile, pubkey, now)
+}
 
+/// Like [`profile_to_event`] with an explicit timestamp, for callers
+/// without a system clock (e.g. WASM in the browser).
+pub fn profile_to_event_at(profile: &AgentProfile, pubkey: &str, created_at: u64) -> MemoryEvent {
     MemoryEvent {
         id: String::new(), // computed by caller after signing
         kind: KIND_METADATA,
         pubkey: pubkey.to_string(),
-        created_at: now,
-        tags: vec![],
+        created_at,
+        tags: vec![("t".to_string(), AGENT_PROFILE_TAG.to_string())],
+        content: profile_to_metadata(profile),
+    }
+}
+
+/// Convert an AgentStatus to a replaceable NIP-78 event (d-tag
+/// [`STATUS_D_TAG`]); each publish replaces the agent's previous status.
+pub fn status_to_event_at(status: &AgentStatus, pubkey: &str, created_at: u64) -> MemoryEvent {
+    MemoryEvent {
+        id: String::new(), // computed by caller after signing
+        kind: KIND_APP_SPECIFIC,
+        pubkey: pubkey.to_string(),
+        created_at,
+        tags: vec![
+            ("d".to_string(), STATUS_D_TAG.to_string()),
+            ("t".to_string(), AGENT_STATUS_TAG.to_string()),
+        ],
+        content: serde_json::to_string(status).expect("agent status is always serializable"),
+    }
+}
+
+/// Convert a MemoryEvent back to an AgentStatus.
+pub fn status_from_event(event: &MemoryEvent) -> Result<AgentStatus, EventSchemaError> {
+    if event.kind != KIND_APP_SPECIFIC {
+        return Err(EventSchemaError::WrongKind(event.kind));
+    }
+    let d_tag = event.require_tag("d")?;
+    if d_tag != STATUS_D_TAG {
+        return Err(EventSchemaError::InvalidTag {
+            tag: "d".to_string(),
+            reason: format!("expected '{STATUS_D_TAG}', got '{d_tag}'"),
+        });
+    }
+    serde_json::from_str(&event.content)
+        .map_err(|e| EventSchemaError::InvalidContent(e.to_string()))
+}
+
+/// Parse a raw JSON status event (as serde_json::Value) into a status
+/// entry. Returns None for other events.
+pub fn event_json_to_status(event: &serde_json::Value) -> Option<AgentStatusEntry> {
+    let mem_event = MemoryEvent {
+        id: event
+            .get("id")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        kind: event.get("kind")?.as_u64()?,
+        pubkey: event.get("pubkey")?.as_str()?.to_string(),
+        created_at: event.get("created_at")?.as_u64()?,
+        tags: event
+            .get("tags")?
+            .as_array()?
+            .iter()
+            .filter_map(|t| {
+                let arr = t.as_array()?;
+                Some((
+                    arr.first()?.as_str()?.to_string(),
+                    arr.get(1)?.as_str()?.to_string(),
+                ))
+            })
+            .collect(),
+        content: event.get("content")?.as_str()?.to_string(),
+    };
+    let status = status_from_event(&mem_event).ok()?;
+    Some(AgentStatusEntry {
+        pubkey: mem_event.pubkey,
+        healthy: status.healthy(),
+        status,
+        updated_at: mem_event.created_at,
+    })
+}
+
+/// Notice that memories on one topic from different sources disagree.
+///
+/// Published as a NIP-78 event with d-tag `snow:conflict:<topic>` so other
+/// agents can react; the content is the JSON array of sides.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConflictNotice {
+    pub topic: String,
+    pub detected_at: u64,
+    pub sides: Vec<ConflictSide>,
+}
+
+/// One memory in a [`ConflictNotice`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConflictSide {
+    pub memory_id: String,
+    pub source: String,
+    pub summary: String,
+    pub confidence: f64,
+    pub version: u32,
+    pub created_at: u64,
+}
+
+/// Convert a ConflictNotice to a MemoryEvent.
+pub fn conflict_notice_to_event(notice: &ConflictNotice, pubkey: &str) -> MemoryEvent {
+    let content =
+        serde_json::to_string(&notice.sides).expect("conflict sides are always serializable");
+
+    let mut tags = vec![(
+        "d".to_string(),
+        format!("{}{}", CONFLICT_D_TAG_PREFIX, notice.topic),
+    )];
+    for side in &notice.sides {
+        tags.push((TAG_CONFLICT_MEMORY.to_string(), side.memory_id.clone()));
+    }
+    tags.push(("t".to_string(), "conflict".to_string()));
+
+    MemoryEvent {
+        id: String::new(), // computed by caller after signing
+        kind: KIND_APP_SPECIFIC,
+        pubkey: pubkey.to_string(),
+        created_at: notice.detected_at,
+        tags,
         content,
     }
 }
 
+/// Convert a MemoryEvent back to a ConflictNotice.
+pub fn conflict_notice_from_event(event: &MemoryEvent) -> Result<ConflictNotice, EventSchemaError> {
+    if event.kind != KIND_APP_SPECIFIC {
+        return Err(EventSchemaError::WrongKind(event.kind));
+    }
+
+    let d_tag = event.require_tag("d")?;
+    let topic = d_tag
+        .strip_prefix(CONFLICT_D_TAG_PREFIX)
+        .ok_or_else(|| EventSchemaError::InvalidDTag(d_tag.to_string()))?
+        .to_string();
+
+    let sides: Vec<ConflictSide> = serde_json::from_str(&event.content)
+        .map_err(|e| EventSchemaError::InvalidContent(e.to_string()))?;
+
+    Ok(ConflictNotice {
+        topic,
+        detected_at: event.created_at,
+        sides,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -407,6 +710,64 @@ mod tests {
         assert_eq!(recovered.version, 2);
     }
 
+    #[test]
+    fn roundtrip_group_namespace() {
+        let mut mem = sample_memory();
+        mem.tier = MemoryTier::Group("techteam".to_string());
+
+        let event = memory_to_event(&mem);
+        assert!(event
+            .tags
+            .contains(&(TAG_GROUP.to_string(), "techteam".to_string())));
+        let recovered = memory_from_event(&event).unwrap();
+        assert_eq!(recovered.tier, MemoryTier::Group("techteam".to_string()));
+
+        // Version 1 events published before the group tag existed keep an
+        // empty namespace; current events must carry it.
+        let mut legacy = event.clone();
+        legacy
+            .tags
+            .retain(|(k, _)| k != TAG_GROUP && k != TAG_SCHEMA);
+        let recovered = memory_from_event(&legacy).unwrap();
+        assert_eq!(recovered.tier, MemoryTier::Group(String::new()));
+
+        let mut missing = event.clone();
+        missing.tags.retain(|(k, _)| k != TAG_GROUP);
+        assert_eq!(
+            memory_from_event(&missing),
+            Err(EventSchemaError::MissingTag(TAG_GROUP.to_string()))
+        );
+    }
+
+    #[test]
+    fn upgrade_steps_cover_every_older_schema() {
+        assert_eq!(UPGRADES.len() as u32, SCHEMA_VERSION - 1);
+
+        let event = memory_to_event(&sample_memory());
+        assert_eq!(schema_version(&event), Ok(SCHEMA_VERSION));
+        assert!(matches!(upgrade_event(&event), Ok(Cow::Borrowed(_))));
+
+        let mut legacy = event.clone();
+        legacy.tags.retain(|(k, _)| k != TAG_SCHEMA);
+        assert_eq!(schema_version(&legacy), Ok(1));
+        assert_eq!(
+            upgrade_event(&legacy).unwrap().get_tag(TAG_SCHEMA),
+            Some("2")
+        );
+
+        let mut invalid = event;
+        for tag in &mut invalid.tags {
+            if tag.0 == TAG_SCHEMA {
+                tag.1 = "0".to_string();
+            }
+        }
+        let fields: Vec<String> = validate_memory_event(&invalid)
+            .into_iter()
+            .map(|i| i.field)
+            .collect();
+        assert_eq!(fields, vec![TAG_SCHEMA]);
+    }
+
     #[test]
     fn reject_wrong_kind() {
         let event = MemoryEvent {
@@ -419,7 +780,7 @@ mod tests {
         };
         assert!(matches!(
             memory_from_event(&event),
-            Err(ConversionError::WrongKind(1))
+            Err(EventSchemaError::WrongKind(1))
         ));
     }
 
@@ -435,7 +796,7 @@ mod tests {
         };
         assert!(matches!(
             memory_from_event(&event),
-            Err(ConversionError::MissingTag(_))
+            Err(EventSchemaError::MissingTag(_))
         ));
     }
 
@@ -452,10 +813,32 @@ mod tests {
         }
         assert!(matches!(
             memory_from_event(&event),
-            Err(ConversionError::InvalidTag { .. })
+            Err(EventSchemaError::InvalidTag { .. })
         ));
     }
 
+    #[test]
+    fn validate_collects_all_issues() {
+        assert!(validate_memory_event(&memory_to_event(&sample_memory())).is_empty());
+
+        let mut event = memory_to_event(&sample_memory());
+        event.tags.retain(|(k, _)| k != TAG_MODEL);
+        for tag in &mut event.tags {
+            match tag.0.as_str() {
+                TAG_CONFIDENCE => tag.1 = "high".to_string(),
+                TAG_TIER => tag.1 = "secret".to_string(),
+                _ => {}
+            }
+        }
+        event.content = "not json".to_string();
+
+        let fields: Vec<String> = validate_memory_event(&event)
+            .into_iter()
+            .map(|i| i.field)
+            .collect();
+        assert_eq!(fields, vec![TAG_TIER, TAG_MODEL, TAG_CONFIDENCE, "content"]);
+    }
+
     #[test]
     fn roundtrip_agent_profile() {
         let profile = AgentProfile {
@@ -465,14 +848,36 @@ mod tests {
             version: "0.1.0".to_string(),
             capabilities: vec!["memory".to_string(), "code".to_string()],
             operator: Some("operator_npub".to_string()),
+            model_tier: Some(1),
+            actions: vec!["memory.".to_string(), "control.".to_string()],
         };
 
         let json = profile_to_metadata(&profile);
         let recovered = profile_from_metadata(&json).unwrap();
-        assert_eq!(recovered.name, profile.name);
-        assert_eq!(recovered.model, profile.model);
-        assert_eq!(recovered.capabilities, profile.capabilities);
-        assert_eq!(recovered.operator, profile.operator);
+        assert_eq!(recovered, profile);
+
+        let event = profile_to_event_at(&profile, "cafe", 1700000000);
+        assert!(event
+            .tags
+            .contains(&("t".to_string(), AGENT_PROFILE_TAG.to_string())));
+        let entry = event_json_to_agent(&serde_json::json!({
+            "kind": 0,
+            "pubkey": "cafe",
+            "created_at": 1700000000,
+            "content": event.content,
+        }))
+        .unwrap();
+        assert_eq!(entry.profile, profile);
+        assert_eq!(entry.updated_at, 1700000000);
+
+        // Plain user metadata has no snow:model and is not an agent.
+        let user = serde_json::json!({
+            "kind": 0,
+            "pubkey": "beef",
+            "created_at": 1,
+            "content": r#"{"name":"alice"}"#,
+        });
+        assert!(event_json_to_agent(&user).is_none());
     }
 
     #[test]
@@ -480,7 +885,88 @@ mod tests {
         let json = r#"{"name": "test"}"#;
         assert!(matches!(
             profile_from_metadata(json),
-            Err(ConversionError::MissingTag(_))
+            Err(EventSchemaError::MissingTag(_))
+        ));
+    }
+
+    #[test]
+    fn roundtrip_conflict_notice() {
+        let mem = sample_memory();
+        let side = |id: &str, source: &str, summary: &str| ConflictSide {
+            memory_id: id.to_string(),
+            source: source.to_string(),
+            summary: summary.to_string(),
+            confidence: mem.confidence,
+            version: 1,
+            created_at: mem.created_at,
+        };
+        let notice = ConflictNotice {
+            topic: mem.topic.clone(),
+            detected_at: 1700000100,
+            sides: vec![
+                side("a", "alice", "Use anyhow"),
+                side("b", "bob", "Use thiserror"),
+            ],
+        };
+
+        let event = conflict_notice_to_event(&notice, "cafe");
+        assert_eq!(
+            event.get_tag("d"),
+            Some("snow:conflict:rust/error-handling")
+        );
+        assert_eq!(
+            event
+                .tags
+                .iter()
+                .filter(|(k, _)| k == TAG_CONFLICT_MEMORY)
+                .count(),
+            2
+        );
+        // Not mistaken for a memory by syncing agents.
+        assert!(matches!(
+            memory_from_event(&event),
+            Err(EventSchemaError::InvalidDTag(_))
         ));
+        assert_eq!(conflict_notice_from_event(&event).unwrap(), notice);
+    }
+
+    #[test]
+    fn roundtrip_agent_status() {
+        let status = AgentStatus {
+            version: "0.4.0".to_string(),
+            uptime_seconds: 3600,
+            groups_served: 2,
+            messages_indexed: 1500,
+            messages_24h: 40,
+            relays_connected: 2,
+            relays_total: 3,
+            components: [("channels".to_string(), "ok".to_string())].into(),
+        };
+        let event = status_to_event_at(&status, "cafe", 1700000200);
+        assert_eq!(event.get_tag("d"), Some(STATUS_D_TAG));
+        assert!(memory_from_event(&event).is_err());
+        assert_eq!(status_from_event(&event).unwrap(), status);
+
+        let json = serde_json::json!({
+            "id": "00",
+            "kind": event.kind,
+            "pubkey": event.pubkey,
+            "created_at": event.created_at,
+            "tags": [["d", STATUS_D_TAG], ["t", AGENT_STATUS_TAG]],
+            "content": event.content,
+        });
+        let entry = event_json_to_status(&json).unwrap();
+        assert_eq!(entry.pubkey, "cafe");
+        assert_eq!(entry.updated_at, 1700000200);
+        assert!(entry.healthy);
+
+        let memory_json = serde_json::json!({
+            "kind": KIND_APP_SPECIFIC,
+            "pubkey": "cafe",
+            "created_at": 1,
+            "tags": [["d", "snow:memory:x"]],
+            "content": "{}",
+        });
+        assert!(event_json_to_status(&memory_json).is_none());
     }
 }
@@ -4,19 +4,40 @@
 //! collective memory system. Memories are published as NIP-78 Nostr events
 //! and ranked by source trust, model tier, and recency.
 
+pub mod bloom;
 pub mod cache;
 pub mod config;
+pub mod error;
 pub mod event;
+pub mod loader;
 pub mod publish;
 pub mod ranking;
 pub mod search;
 pub mod subscribe;
 pub mod types;
 
+pub use bloom::RotatingBloom;
 pub use cache::MemoryCache;
-pub use config::MemoryConfig;
-pub use publish::{build_memory_event, build_profile_event, UnsignedEvent};
-pub use ranking::{detect_conflicts, rank_memories, resolve_conflict, Conflict};
+pub use config::{CacheConfig, DedupConfig, DedupMode, MemoryConfig};
+pub use error::{
+    ConfigError, EventSchemaError, MemoryStoreError, RankingError, SignatureError, StoreResult,
+};
+pub use loader::{ConfigLoader, ConfigSource, Layered, Validate};
+pub use publish::{
+    build_memory_event, build_profile_event, build_status_event_at, ProfilePublisher, SignedEvent,
+    UnsignedEvent,
+};
+pub use ranking::{
+    detect_conflicts, rank_memories, rank_memories_with_usage, resolve_conflict, try_rank_memories,
+    validate_config, Conflict,
+};
 pub use search::SqliteMemoryIndex;
-pub use subscribe::{parse_relay_message, EventDedup, RelayMessage};
-pub use types::{AgentProfile, Memory, MemoryTier, SearchResult, SourcePreference};
+pub use subscribe::{
+    parse_relay_message, EventDedup, PublishOutcome, PublishReceipt, PublishTracker, RelayAck,
+    RelayMessage,
+};
+pub use types::{
+    AgentDirectoryEntry, AgentProfile, AgentStatus, AgentStatusEntry, Memory, MemoryTier,
+    MemoryUsage, ReputationChange, ReputationReason, SearchResult, SourcePreference,
+    SourceReputation,
+};
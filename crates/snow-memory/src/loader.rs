@@ -0,0 +1,486 @@
+//! Layered configuration: defaults ← file ← env ← NIP-78 dynamic config.
+//!
+//! [`ConfigLoader`] merges the layers field by field, so a later layer only
+//! overrides the top-level fields it sets. The result is validated
+//! ([`Validate`]) and comes with the layer each field was taken from
+//! ([`Layered::provenance`]), which [`Layered::report`] renders.
+//!
+//! - **file**: a TOML table, or the fields of an already parsed config
+//!   that differ from the defaults ([`ConfigLoader::file_config`]).
+//! - **env**: `<PREFIX><FIELD>` variables, e.g. `SNOW_MEMORY_USAGE_WEIGHT`.
+//!   Values are typed after the field's default: numbers and booleans are
+//!   parsed, lists are JSON arrays or comma-separated.
+//! - **nip78**: the JSON object content of the agent's kind 30078 event
+//!   with d-tag [`DYNAMIC_CONFIG_D_TAG`].
+//!
+//! Unknown keys are skipped with a warning instead of failing, so an older
+//! agent keeps running on a dynamic config written for a newer one.
+
+use crate::config::{CacheConfig, DedupConfig, MemoryConfig};
+use crate::error::{ConfigError, RankingError};
+use crate::ranking::validate_config;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::marker::PhantomData;
+
+/// d-tag of the NIP-78 event holding the dynamic memory config.
+pub const DYNAMIC_CONFIG_D_TAG: &str = "snow:config:memory";
+
+/// Env var prefix for [`MemoryConfig`] overrides.
+pub const ENV_PREFIX: &str = "SNOW_MEMORY_";
+
+/// Layer a config field was taken from, lowest precedence first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSource {
+    Default,
+    File,
+    Env,
+    #[serde(rename = "nip78")]
+    Dynamic,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Default => "default",
+            Self::File => "file",
+            Self::Env => "env",
+            Self::Dynamic => "nip78",
+        })
+    }
+}
+
+/// Range checks beyond what deserialization enforces.
+pub trait Validate {
+    /// The first field out of range, and why.
+    fn invalid_field(&self) -> Option<(&'static str, String)>;
+}
+
+impl Validate for MemoryConfig {
+    fn invalid_field(&self) -> Option<(&'static str, String)> {
+        let err = validate_config(self).err()?;
+        let field = match &err {
+            RankingError::InvalidUsageWeight(_) => "usage_weight",
+            RankingError::InvalidTrust { .. } => "sources",
+            RankingError::InvalidTierPattern { tier: 1, .. } => "tier1",
+            RankingError::InvalidTierPattern { tier: 2, .. } => "tier2",
+            RankingError::InvalidTierPattern { tier: 3, .. } => "tier3",
+            RankingError::InvalidTierPattern { .. } => "tier4",
+            RankingError::InvalidRelevance { .. } => "sources",
+        };
+        Some((field, err.to_string()))
+    }
+}
+
+impl Validate for CacheConfig {
+    fn invalid_field(&self) -> Option<(&'static str, String)> {
+        (self.ttl_secs == 0).then(|| ("ttl_secs", "expected at least 1 second".to_string()))
+    }
+}
+
+impl Validate for DedupConfig {
+    fn invalid_field(&self) -> Option<(&'static str, String)> {
+        if self.capacity == 0 {
+            return Some(("capacity", "expected at least 1".to_string()));
+        }
+        let rate = self.false_positive_rate;
+        (!(rate > 0.0 && rate < 1.0)).then(|| {
+            (
+                "false_positive_rate",
+                format!("is {rate}, expected a value between 0.0 and 1.0"),
+            )
+        })
+    }
+}
+
+/// A loaded config with the layer each top-level field came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layered<T> {
+    pub config: T,
+    pub provenance: BTreeMap<String, ConfigSource>,
+    /// Skipped keys and env vars.
+    pub warnings: Vec<String>,
+}
+
+impl<T: Serialize> Layered<T> {
+    /// Layer `field` was taken from.
+    pub fn source(&self, field: &str) -> ConfigSource {
+        self.provenance
+            .get(field)
+            .copied()
+            .unwrap_or(ConfigSource::Default)
+    }
+
+    /// One `field = value  (source)` line per field, then the warnings.
+    pub fn report(&self) -> String {
+        let fields = match serde_json::to_value(&self.config) {
+            Ok(Value::Object(fields)) => fields,
+            _ => Map::new(),
+        };
+        let width = fields.keys().map(String::len).max().unwrap_or(0);
+        let mut out = String::new();
+        for (field, value) in &fields {
+            let _ = writeln!(out, "{field:<width$} = {value}  ({})", self.source(field));
+        }
+        for warning in &self.warnings {
+            let _ = writeln!(out, "warning: {warning}");
+        }
+        out
+    }
+}
+
+/// Builds a [`Layered`] config from defaults and override layers.
+pub struct ConfigLoader<T> {
+    defaults: Map<String, Value>,
+    layers: Vec<(ConfigSource, Map<String, Value>)>,
+    fixed: Vec<String>,
+    warnings: Vec<String>,
+    _config: PhantomData<T>,
+}
+
+impl<T> ConfigLoader<T>
+where
+    T: Serialize + DeserializeOwned + Default + Validate,
+{
+    pub fn new() -> Self {
+        let defaults = match serde_json::to_value(T::default()) {
+            Ok(Value::Object(fields)) => fields,
+            _ => Map::new(),
+        };
+        Self {
+            defaults,
+            layers: Vec::new(),
+            fixed: Vec::new(),
+            warnings: Vec::new(),
+            _config: PhantomData,
+        }
+    }
+
+    /// Fields only the file may set; env and dynamic values for them are
+    /// skipped with a warning.
+    pub fn fixed(mut self, fields: &[&str]) -> Self {
+        self.fixed = fields.iter().map(|f| (*f).to_string()).collect();
+        self
+    }
+
+    /// File layer from a TOML table.
+    pub fn file_toml(self, raw: &str) -> Result<Self, ConfigError> {
+        let value: Value = toml::from_str(raw).map_err(|e| ConfigError::Parse {
+            origin: ConfigSource::File,
+            message: e.to_string(),
+        })?;
+        self.layer(ConfigSource::File, value)
+    }
+
+    /// File layer from a parsed config: the fields that differ from the
+    /// defaults. A field set to its default value reports as `default`.
+    pub fn file_config(self, config: &T) -> Result<Self, ConfigError> {
+        let value = serde_json::to_value(config).map_err(|e| ConfigError::Parse {
+            origin: ConfigSource::File,
+            message: e.to_string(),
+        })?;
+        let Value::Object(fields) = value else {
+            return Err(ConfigError::NotAnObject(ConfigSource::File));
+        };
+        let changed: Map<String, Value> = fields
+            .into_iter()
+            .filter(|(field, value)| self.defaults.get(field) != Some(value))
+            .collect();
+        self.layer(ConfigSource::File, Value::Object(changed))
+    }
+
+    /// Env layer from `vars` named `<prefix><FIELD>`; other vars are
+    /// ignored.
+    pub fn env<I>(mut self, prefix: &str, vars: I) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut fields = Map::new();
+        for (var, raw) in vars {
+            let Some(field) = var.strip_prefix(prefix) else {
+                continue;
+            };
+            let field = field.to_ascii_lowercase();
+            let Some(default) = self.defaults.get(&field) else {
+                self.warnings.push(format!("unknown env var {var}"));
+                continue;
+            };
+            let value = env_value(default, &raw).ok_or_else(|| ConfigError::Env {
+                var: var.clone(),
+                reason: format!("cannot parse '{raw}' as {}", type_name(default)),
+            })?;
+            fields.insert(field, value);
+        }
+        self.layer(ConfigSource::Env, Value::Object(fields))
+    }
+
+    /// Dynamic layer from the content of a [`DYNAMIC_CONFIG_D_TAG`] event.
+    pub fn dynamic(self, content: &str) -> Result<Self, ConfigError> {
+        let value: Value = serde_json::from_str(content).map_err(|e| ConfigError::Parse {
+            origin: ConfigSource::Dynamic,
+            message: e.to_string(),
+        })?;
+        self.layer(ConfigSource::Dynamic, value)
+    }
+
+    fn layer(mut self, source: ConfigSource, value: Value) -> Result<Self, ConfigError> {
+        let Value::Object(fields) = value else {
+            return Err(ConfigError::NotAnObject(source));
+        };
+        let mut kept = Map::new();
+        for (field, value) in fields {
+            if !self.defaults.contains_key(&field) {
+                self.warnings
+                    .push(format!("unknown key '{field}' in {source} config"));
+            } else if source > ConfigSource::File && self.fixed.contains(&field) {
+                self.warnings.push(format!(
+                    "'{field}' can only be set in the file, {source} value skipped"
+                ));
+            } else {
+                kept.insert(field, value);
+            }
+        }
+        self.layers.push((source, kept));
+        Ok(self)
+    }
+
+    /// Merge the layers and validate the result.
+    pub fn load(self) -> Result<Layered<T>, ConfigError> {
+        let mut merged = self.defaults;
+        let mut provenance: BTreeMap<String, ConfigSource> = merged
+            .keys()
+            .map(|field| (field.clone(), ConfigSource::Default))
+            .collect();
+        for (source, fields) in self.layers {
+            for (field, value) in fields {
+                merged.insert(field.clone(), value);
+                provenance.insert(field, source);
+            }
+            // Parse after each layer so a bad value is blamed on its layer.
+            serde_json::from_value::<T>(Value::Object(merged.clone())).map_err(|e| {
+                ConfigError::Parse {
+                    origin: source,
+                    message: e.to_string(),
+                }
+            })?;
+        }
+        let config: T =
+            serde_json::from_value(Value::Object(merged)).map_err(|e| ConfigError::Parse {
+                origin: ConfigSource::Default,
+                message: e.to_string(),
+            })?;
+        if let Some((field, reason)) = config.invalid_field() {
+            return Err(ConfigError::OutOfRange {
+                field: field.to_string(),
+                origin: provenance
+                    .get(field)
+                    .copied()
+                    .unwrap_or(ConfigSource::Default),
+                reason,
+            });
+        }
+        Ok(Layered {
+            config,
+            provenance,
+            warnings: self.warnings,
+        })
+    }
+}
+
+impl<T> Default for ConfigLoader<T>
+where
+    T: Serialize + DeserializeOwned + Default + Validate,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse an env var after the type of the field's default.
+fn env_value(default: &Value, raw: &str) -> Option<Value> {
+    let raw = raw.trim();
+    match default {
+        Value::String(_) | Value::Null => Some(Value::String(raw.to_string())),
+        Value::Array(_) if !raw.starts_with('[') => Some(Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        )),
+        _ => serde_json::from_str(raw).ok(),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::Array(_) => "a list",
+        Value::Object(_) => "an object",
+        Value::String(_) | Value::Null => "a string",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let layered = ConfigLoader::<MemoryConfig>::new()
+            .file_toml("usage_weight = 0.5\nusage_half_life_days = 14")
+            .unwrap()
+            .env(ENV_PREFIX, vars(&[("SNOW_MEMORY_USAGE_WEIGHT", "0.75")]))
+            .unwrap()
+            .dynamic(r#"{"usage_half_life_days": 7}"#)
+            .unwrap()
+            .load()
+            .unwrap();
+        assert_eq!(layered.config.usage_weight, 0.75);
+        assert_eq!(layered.config.usage_half_life_days, 7);
+        assert_eq!(layered.source("usage_weight"), ConfigSource::Env);
+        assert_eq!(
+            layered.source("usage_half_life_days"),
+            ConfigSource::Dynamic
+        );
+        assert_eq!(layered.source("tier1"), ConfigSource::Default);
+        assert_eq!(layered.config.tier1, MemoryConfig::default().tier1);
+    }
+
+    #[test]
+    fn env_values_follow_field_types() {
+        let layered = ConfigLoader::<MemoryConfig>::new()
+            .env(
+                ENV_PREFIX,
+                vars(&[
+                    ("SNOW_MEMORY_TIER4", "local/*, mistral/*"),
+                    ("SNOW_MEMORY_RELAYS_PUBLIC", r#"["wss://a.example"]"#),
+                    ("SNOW_MEMORY_NOPE", "1"),
+                    ("HOME", "/root"),
+                ]),
+            )
+            .unwrap()
+            .load()
+            .unwrap();
+        assert_eq!(layered.config.tier4, vec!["local/*", "mistral/*"]);
+        assert_eq!(layered.config.relays_public, vec!["wss://a.example"]);
+        assert_eq!(layered.warnings, vec!["unknown env var SNOW_MEMORY_NOPE"]);
+
+        let err = ConfigLoader::<MemoryConfig>::new()
+            .env(ENV_PREFIX, vars(&[("SNOW_MEMORY_USAGE_WEIGHT", "high")]))
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, ConfigError::Env { ref var, .. } if var == "SNOW_MEMORY_USAGE_WEIGHT")
+        );
+    }
+
+    #[test]
+    fn file_config_keeps_changed_fields_only() {
+        let file = MemoryConfig {
+            usage_weight: 1.0,
+            ..MemoryConfig::default()
+        };
+        let layered = ConfigLoader::new()
+            .file_config(&file)
+            .unwrap()
+            .load()
+            .unwrap();
+        assert_eq!(layered.config, file);
+        assert_eq!(layered.source("usage_weight"), ConfigSource::File);
+        assert_eq!(layered.source("tier2"), ConfigSource::Default);
+    }
+
+    #[test]
+    fn out_of_range_values_name_their_layer() {
+        let err = ConfigLoader::<MemoryConfig>::new()
+            .dynamic(r#"{"usage_weight": -1.0}"#)
+            .unwrap()
+            .load()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "usage_weight (nip78): usage_weight is -1, expected a finite value >= 0"
+        );
+
+        let err = ConfigLoader::<CacheConfig>::new()
+            .file_toml("ttl_secs = 0")
+            .unwrap()
+            .load()
+            .unwrap_err();
+        assert!(
+            matches!(err, ConfigError::OutOfRange { ref field, origin: ConfigSource::File, .. } if field == "ttl_secs")
+        );
+
+        let err = ConfigLoader::<DedupConfig>::new()
+            .dynamic(r#"{"false_positive_rate": 1.5}"#)
+            .unwrap()
+            .load()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("false_positive_rate (nip78)"));
+    }
+
+    #[test]
+    fn type_errors_are_blamed_on_their_layer() {
+        let err = ConfigLoader::<MemoryConfig>::new()
+            .file_toml("usage_weight = 0.5")
+            .unwrap()
+            .dynamic(r#"{"usage_half_life_days": "soon"}"#)
+            .unwrap()
+            .load()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Parse {
+                origin: ConfigSource::Dynamic,
+                ..
+            }
+        ));
+
+        let err = ConfigLoader::<MemoryConfig>::new()
+            .dynamic("[1, 2]")
+            .err()
+            .unwrap();
+        assert_eq!(err, ConfigError::NotAnObject(ConfigSource::Dynamic));
+    }
+
+    #[test]
+    fn fixed_fields_ignore_overrides() {
+        let layered = ConfigLoader::<MemoryConfig>::new()
+            .fixed(&["relays_public"])
+            .file_toml(r#"relays_public = ["wss://file.example"]"#)
+            .unwrap()
+            .dynamic(r#"{"relays_public": ["wss://dyn.example"], "colour": "blue"}"#)
+            .unwrap()
+            .load()
+            .unwrap();
+        assert_eq!(layered.config.relays_public, vec!["wss://file.example"]);
+        assert_eq!(layered.source("relays_public"), ConfigSource::File);
+        assert_eq!(layered.warnings.len(), 2);
+    }
+
+    #[test]
+    fn report_lists_every_field_with_its_source() {
+        let layered = ConfigLoader::<DedupConfig>::new()
+            .env("SNOW_DEDUP_", vars(&[("SNOW_DEDUP_MODE", "bloom")]))
+            .unwrap()
+            .load()
+            .unwrap();
+        let report = layered.report();
+        assert!(report.contains("capacity            = 10000  (default)"));
+        assert!(report.contains("mode                = \"bloom\"  (env)"));
+        assert_eq!(report.lines().count(), 3);
+    }
+}
@@ -2,13 +2,19 @@
 //!
 //! This module handles serialization and signing of memory events.
 //! Actual relay transport is handled by the caller (agent runtime or CLI).
+//!
+//! Signing itself happens outside the crate: the caller hashes
+//! [`UnsignedEvent::id_preimage`] (or takes [`UnsignedEvent::compute_id`]),
+//! has the key holder sign the id, e.g. a NIP-07 browser extension, and
+//! attaches the signature with [`UnsignedEvent::into_signed`].
 
+use crate::error::SignatureError;
 use crate::event;
-use crate::types::{AgentProfile, Memory};
+use crate::types::{AgentProfile, AgentStatus, Memory};
 use sha2::{Digest, Sha256};
 
 /// A signed Nostr event ready to be sent to relays.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct SignedEvent {
     pub id: String,
     pub pubkey: String,
@@ -20,7 +26,7 @@ pub struct SignedEvent {
 }
 
 /// An unsigned event that needs signing before publishing.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct UnsignedEvent {
     pub pubkey: String,
     pub created_at: u64,
@@ -30,8 +36,9 @@ pub struct UnsignedEvent {
 }
 
 impl UnsignedEvent {
-    /// Compute the event ID (SHA-256 of the canonical serialization).
-    pub fn compute_id(&self) -> String {
+    /// NIP-01 canonical serialization the event ID is hashed from:
+    /// `[0,<pubkey>,<created_at>,<kind>,<tags>,<content>]`.
+    pub fn id_preimage(&self) -> String {
         let canonical = serde_json::json!([
             0,
             self.pubkey,
@@ -40,33 +47,117 @@ impl UnsignedEvent {
             self.tags,
             self.content,
         ]);
-        let serialized = serde_json::to_string(&canonical).unwrap_or_default();
-        let hash = Sha256::digest(serialized.as_bytes());
-        hex::encode(hash)
+        serde_json::to_string(&canonical).unwrap_or_default()
+    }
+
+    /// Compute the event ID (SHA-256 of the canonical serialization).
+    pub fn compute_id(&self) -> String {
+        hex::encode(Sha256::digest(self.id_preimage().as_bytes()))
+    }
+
+    /// Attach a Schnorr signature over [`Self::compute_id`].
+    ///
+    /// Only the shape of the pubkey and signature is checked; the crate has
+    /// no secp256k1, so relays verify the signature itself.
+    pub fn into_signed(self, sig: &str) -> Result<SignedEvent, SignatureError> {
+        if !is_hex(&self.pubkey, 64) {
+            return Err(SignatureError::InvalidPubkey(self.pubkey));
+        }
+        if !is_hex(sig, 128) {
+            return Err(SignatureError::InvalidSignature(sig.to_string()));
+        }
+        Ok(SignedEvent {
+            id: self.compute_id(),
+            pubkey: self.pubkey,
+            created_at: self.created_at,
+            kind: self.kind,
+            tags: self.tags,
+            content: self.content,
+            sig: sig.to_ascii_lowercase(),
+        })
+    }
+}
+
+impl SignedEvent {
+    /// Serialize as event JSON, the form relays and `["EVENT", …]` expect.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
     }
 }
 
+fn is_hex(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
 /// Build an unsigned NIP-78 memory event.
 pub fn build_memory_event(memory: &Memory, pubkey: &str) -> UnsignedEvent {
-    let nostr_event = event::memory_to_event(memory);
-
-    UnsignedEvent {
-        pubkey: pubkey.to_string(),
-        created_at: nostr_event.created_at,
-        kind: nostr_event.kind as u32,
-        tags: nostr_event
-            .tags
-            .into_iter()
-            .map(|(k, v)| vec![k, v])
-            .collect(),
-        content: nostr_event.content,
-    }
+    unsigned(event::memory_to_event(memory), pubkey)
 }
 
 /// Build an unsigned kind 0 agent profile event.
 pub fn build_profile_event(profile: &AgentProfile, pubkey: &str) -> UnsignedEvent {
-    let nostr_event = event::profile_to_event(profile, pubkey);
+    unsigned(event::profile_to_event(profile, pubkey), pubkey)
+}
+
+/// Build an unsigned kind 0 agent profile event with an explicit timestamp.
+pub fn build_profile_event_at(
+    profile: &AgentProfile,
+    pubkey: &str,
+    created_at: u64,
+) -> UnsignedEvent {
+    unsigned(
+        event::profile_to_event_at(profile, pubkey, created_at),
+        pubkey,
+    )
+}
+
+/// Build an unsigned replaceable agent status event.
+pub fn build_status_event_at(status: &AgentStatus, pubkey: &str, created_at: u64) -> UnsignedEvent {
+    unsigned(
+        event::status_to_event_at(status, pubkey, created_at),
+        pubkey,
+    )
+}
+
+/// Decides when an agent's profile is (re)published: the first time, when
+/// it changes, and when the published copy is `refresh_secs` old, so
+/// relays that prune old metadata keep listing the agent.
+#[derive(Debug, Clone)]
+pub struct ProfilePublisher {
+    refresh_secs: u64,
+    /// Metadata content and `created_at` of the last published event.
+    last: Option<(String, u64)>,
+}
+
+impl ProfilePublisher {
+    pub fn new(refresh_secs: u64) -> Self {
+        Self {
+            refresh_secs,
+            last: None,
+        }
+    }
+
+    /// Profile event to publish at `now`, or `None` if the published one is
+    /// current. Call [`Self::published`] once relays accepted it.
+    pub fn due(&self, profile: &AgentProfile, pubkey: &str, now: u64) -> Option<UnsignedEvent> {
+        let event = build_profile_event_at(profile, pubkey, now);
+        match &self.last {
+            Some((content, at))
+                if *content == event.content && now.saturating_sub(*at) < self.refresh_secs =>
+            {
+                None
+            }
+            _ => Some(event),
+        }
+    }
+
+    /// Record that `event` was published.
+    pub fn published(&mut self, event: &UnsignedEvent) {
+        self.last = Some((event.content.clone(), event.created_at));
+    }
+}
 
+fn unsigned(nostr_event: event::MemoryEvent, pubkey: &str) -> UnsignedEvent {
     UnsignedEvent {
         pubkey: pubkey.to_string(),
         created_at: nostr_event.created_at,
@@ -99,6 +190,36 @@ pub fn build_memory_subscription(sub_id: &str, since: Option<u64>) -> String {
     serde_json::json!(["REQ", sub_id, filter]).to_string()
 }
 
+/// Build a ["REQ", ...] message discovering Snowclaw agents: kind 0 events
+/// tagged as agent profiles, optionally only those updated after `since`.
+pub fn build_agent_discovery_subscription(sub_id: &str, since: Option<u64>) -> String {
+    let mut filter = serde_json::json!({
+        "kinds": [0],
+        "#t": [event::AGENT_PROFILE_TAG],
+    });
+
+    if let Some(since) = since {
+        filter["since"] = serde_json::json!(since);
+    }
+
+    serde_json::json!(["REQ", sub_id, filter]).to_string()
+}
+
+/// Build a ["REQ", ...] message for agent status events, optionally only
+/// those of `pubkeys`.
+pub fn build_status_subscription(sub_id: &str, pubkeys: Option<&[&str]>) -> String {
+    let mut filter = serde_json::json!({
+        "kinds": [event::KIND_APP_SPECIFIC],
+        "#d": [event::STATUS_D_TAG],
+    });
+
+    if let Some(pks) = pubkeys {
+        filter["authors"] = serde_json::json!(pks);
+    }
+
+    serde_json::json!(["REQ", sub_id, filter]).to_string()
+}
+
 /// Build a filter for agent profile events with snow: metadata.
 pub fn build_profile_subscription(sub_id: &str, pubkeys: Option<&[&str]>) -> String {
     let mut filter = serde_json::json!({
@@ -150,10 +271,75 @@ mod tests {
             version: "0.1.0".to_string(),
             capabilities: vec!["memory".to_string()],
             operator: None,
+            model_tier: None,
+            actions: vec![],
         };
 
         let event = build_profile_event(&profile, "aabbccdd");
         assert_eq!(event.kind, 0);
+
+        let event = build_profile_event_at(&profile, "aabbccdd", 1_700_000_000);
+        assert_eq!(event.created_at, 1_700_000_000);
+    }
+
+    #[test]
+    fn profile_publisher_republishes_on_change_and_refresh() {
+        let mut profile = AgentProfile {
+            name: "snow-test".to_string(),
+            about: String::new(),
+            model: "test/model".to_string(),
+            version: "0.1.0".to_string(),
+            capabilities: vec![],
+            operator: None,
+            model_tier: Some(2),
+            actions: vec!["memory.".to_string()],
+        };
+        let mut publisher = ProfilePublisher::new(3600);
+
+        let first = publisher.due(&profile, "aabb", 1000).unwrap();
+        publisher.published(&first);
+        assert!(publisher.due(&profile, "aabb", 2000).is_none());
+        assert!(publisher.due(&profile, "aabb", 4600).is_some());
+
+        profile.actions.push("control.".to_string());
+        let changed = publisher.due(&profile, "aabb", 2000).unwrap();
+        assert!(changed.content.contains("control."));
+    }
+
+    #[test]
+    fn signatures_attach_to_the_canonical_id() {
+        let pubkey = "a".repeat(64);
+        let event = UnsignedEvent {
+            pubkey: pubkey.clone(),
+            created_at: 1_700_000_000,
+            kind: 30078,
+            tags: vec![vec!["d".to_string(), "snow:memory:x".to_string()]],
+            content: "line \"one\"\n".to_string(),
+        };
+        assert_eq!(
+            event.id_preimage(),
+            format!(r#"[0,"{pubkey}",1700000000,30078,[["d","snow:memory:x"]],"line \"one\"\n"]"#)
+        );
+
+        let sig = "B".repeat(128);
+        let signed = event.clone().into_signed(&sig).unwrap();
+        assert_eq!(signed.id, event.compute_id());
+        assert_eq!(signed.sig, "b".repeat(128));
+        let json: serde_json::Value = serde_json::from_str(&signed.to_json()).unwrap();
+        assert_eq!(json["content"], "line \"one\"\n");
+
+        assert_eq!(
+            event.clone().into_signed("abcd"),
+            Err(SignatureError::InvalidSignature("abcd".to_string()))
+        );
+        let unknown_signer = UnsignedEvent {
+            pubkey: "npub1xyz".to_string(),
+            ..event
+        };
+        assert!(matches!(
+            unknown_signer.into_signed(&sig),
+            Err(SignatureError::InvalidPubkey(_))
+        ));
     }
 
     #[test]
@@ -164,5 +350,8 @@ mod tests {
 
         let prof_sub = build_profile_subscription("sub2", Some(&["aabb"]));
         assert!(prof_sub.contains("aabb"));
+
+        let discovery = build_agent_discovery_subscription("sub3", None);
+        assert!(discovery.contains(r##""#t":["snow-agent"]"##));
     }
 }
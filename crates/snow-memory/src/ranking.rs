@@ -1,11 +1,17 @@
 //! Memory ranking, conflict detection, and resolution.
 
 use crate::config::MemoryConfig;
-use crate::types::{Memory, SearchResult, SourcePreference};
+use crate::error::RankingError;
+use crate::types::{Memory, MemoryUsage, SearchResult, SourcePreference};
 
 /// Weight multiplier per model tier (tier 1 = best).
 const TIER_WEIGHTS: [f64; 5] = [0.0, 1.0, 0.8, 0.6, 0.4];
 
+/// Uses at which the frequency part of the usage score reaches one half.
+const USAGE_HALF_COUNT: f64 = 4.0;
+
+const SECS_PER_DAY: f64 = 86_400.0;
+
 /// Get the trust weight for a source from the preference list.
 /// Returns 0.0 (untrusted) if not in the list.
 fn source_trust(source: &str, preferences: &[SourcePreference]) -> f64 {
@@ -18,7 +24,7 @@ fn source_trust(source: &str, preferences: &[SourcePreference]) -> f64 {
 
 /// Determine the model tier (1-4) for a given model string.
 /// Returns 4 (lowest) if not found in any tier.
-fn model_tier(model: &str, config: &MemoryConfig) -> u8 {
+pub fn model_tier(model: &str, config: &MemoryConfig) -> u8 {
     for (tier_num, tier_models) in [
         (1u8, &config.tier1),
         (2, &config.tier2),
@@ -39,25 +45,84 @@ fn model_tier(model: &str, config: &MemoryConfig) -> u8 {
     4
 }
 
+/// Usefulness of a memory from its recorded use: the frequency part
+/// `n / (n + 4)` for `n` uses, halved for every `half_life_days` since the
+/// last use. `0.0` for a memory never used.
+pub fn usage_score(usage: &MemoryUsage, half_life_days: u64, now: u64) -> f64 {
+    if usage.access_count == 0 {
+        return 0.0;
+    }
+    let count = usage.access_count as f64;
+    let frequency = count / (count + USAGE_HALF_COUNT);
+    let freshness = match (usage.last_used_at, half_life_days) {
+        (_, 0) => 1.0,
+        (Some(last), days) => {
+            let idle_days = now.saturating_sub(last) as f64 / SECS_PER_DAY;
+            0.5f64.powf(idle_days / days as f64)
+        }
+        (None, _) => 0.0,
+    };
+    frequency * freshness
+}
+
 /// Rank memories by: source preference -> model tier -> recency.
 ///
 /// Each memory gets an effective score = relevance * source_trust * tier_weight.
 /// Results are sorted by effective_score descending, then by created_at descending.
+/// Every result carries its component scores and an explanation.
 pub fn rank_memories(memories: Vec<(Memory, f64)>, config: &MemoryConfig) -> Vec<SearchResult> {
+    let memories = memories
+        .into_iter()
+        .map(|(memory, relevance)| (memory, relevance, MemoryUsage::default()))
+        .collect();
+    rank_memories_with_usage(memories, config, 0)
+}
+
+/// [`rank_memories`] with a usage component: the score of a memory that was
+/// recalled before is multiplied by `1 + usage_weight * usage_score`, see
+/// [`usage_score`]. `now` is the Unix time the last use is measured from.
+pub fn rank_memories_with_usage(
+    memories: Vec<(Memory, f64, MemoryUsage)>,
+    config: &MemoryConfig,
+    now: u64,
+) -> Vec<SearchResult> {
+    let oldest = memories
+        .iter()
+        .map(|(m, _, _)| m.created_at)
+        .min()
+        .unwrap_or(0);
+    let newest = memories
+        .iter()
+        .map(|(m, _, _)| m.created_at)
+        .max()
+        .unwrap_or(0);
+
     let mut results: Vec<SearchResult> = memories
         .into_iter()
-        .map(|(memory, relevance)| {
+        .map(|(memory, relevance, usage)| {
             let trust = source_trust(&memory.source, &config.sources);
             let tier = model_tier(&memory.model, config);
             let tier_weight = TIER_WEIGHTS.get(tier as usize).copied().unwrap_or(0.4);
-            let effective_score = relevance * trust * tier_weight;
+            let usage_score = usage_score(&usage, config.usage_half_life_days, now);
+            let effective_score =
+                relevance * trust * tier_weight * (1.0 + config.usage_weight * usage_score);
+            let recency = if newest > oldest {
+                (memory.created_at - oldest) as f64 / (newest - oldest) as f64
+            } else {
+                1.0
+            };
 
             SearchResult {
                 memory,
                 relevance,
                 source_trust: trust,
                 model_tier: tier,
+                tier_weight,
+                recency,
+                usage,
+                usage_score,
                 effective_score,
+                explanation: String::new(),
             }
         })
         .collect();
@@ -69,9 +134,104 @@ pub fn rank_memories(memories: Vec<(Memory, f64)>, config: &MemoryConfig) -> Vec
             .then_with(|| b.memory.created_at.cmp(&a.memory.created_at))
     });
 
+    for i in 0..results.len() {
+        let tied = results
+            .iter()
+            .enumerate()
+            .any(|(j, other)| j != i && other.effective_score == results[i].effective_score);
+        results[i].explanation = explain(&results[i], config.usage_weight, tied);
+    }
+
     results
 }
 
+/// Explain a ranked result, e.g. `relevance 0.80 × trust 0.90 (source
+/// alice) × tier 2 weight 0.80 (anthropic/claude-sonnet-4) = 0.576`.
+/// A usage boost shows as `× usage 1.10 (3 uses)` before the total.
+/// `tied` notes that recency decided its place among equal scores.
+fn explain(result: &SearchResult, usage_weight: f64, tied: bool) -> String {
+    let source = if result.source_trust > 0.0 {
+        format!("source {}", result.memory.source)
+    } else {
+        format!("untrusted source {}", result.memory.source)
+    };
+    let mut text = format!(
+        "relevance {:.2} × trust {:.2} ({source}) × tier {} weight {:.2} ({})",
+        result.relevance,
+        result.source_trust,
+        result.model_tier,
+        result.tier_weight,
+        result.memory.model,
+    );
+    let boost = usage_weight * result.usage_score;
+    if boost > 0.0 {
+        let uses = result.usage.access_count;
+        let noun = if uses == 1 { "use" } else { "uses" };
+        text.push_str(&format!(" × usage {:.2} ({uses} {noun})", 1.0 + boost));
+    }
+    text.push_str(&format!(" = {:.3}", result.effective_score));
+    if tied {
+        text.push_str(&format!(
+            "; tied score ordered by recency {:.2} (newest first)",
+            result.recency
+        ));
+    }
+    text
+}
+
+/// Check that source trusts are within `0.0..=1.0`, that tier model
+/// patterns use `*` only as a trailing wildcard and that `usage_weight` is
+/// not negative.
+pub fn validate_config(config: &MemoryConfig) -> Result<(), RankingError> {
+    if !config.usage_weight.is_finite() || config.usage_weight < 0.0 {
+        return Err(RankingError::InvalidUsageWeight(config.usage_weight));
+    }
+    for pref in &config.sources {
+        if !(0.0..=1.0).contains(&pref.trust) {
+            return Err(RankingError::InvalidTrust {
+                source_id: pref
+                    .npub
+                    .clone()
+                    .or_else(|| pref.group.clone())
+                    .unwrap_or_default(),
+                trust: pref.trust,
+            });
+        }
+    }
+    for (tier, patterns) in [
+        (1u8, &config.tier1),
+        (2, &config.tier2),
+        (3, &config.tier3),
+        (4, &config.tier4),
+    ] {
+        for pattern in patterns {
+            if pattern.trim_end_matches('*').contains('*') {
+                return Err(RankingError::InvalidTierPattern {
+                    tier,
+                    pattern: pattern.clone(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// [`rank_memories`] after validating `config` and rejecting non-finite
+/// or negative relevance scores, which would make the ordering arbitrary.
+pub fn try_rank_memories(
+    memories: Vec<(Memory, f64)>,
+    config: &MemoryConfig,
+) -> Result<Vec<SearchResult>, RankingError> {
+    validate_config(config)?;
+    if let Some((memory, relevance)) = memories.iter().find(|(_, r)| !r.is_finite() || *r < 0.0) {
+        return Err(RankingError::InvalidRelevance {
+            id: memory.id.clone(),
+            relevance: *relevance,
+        });
+    }
+    Ok(rank_memories(memories, config))
+}
+
 /// A pair of conflicting memories on the same topic from different sources.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Conflict {
@@ -136,7 +296,7 @@ pub fn resolve_conflict(conflict: &Conflict, config: &MemoryConfig) -> Option<us
 mod tests {
     use super::*;
     use crate::config::MemoryConfig;
-    use crate::types::{Memory, MemoryTier, SourcePreference};
+    use crate::types::{Memory, MemoryTier, MemoryUsage, SourcePreference};
 
     fn test_config() -> MemoryConfig {
         MemoryConfig {
@@ -151,6 +311,9 @@ mod tests {
             tier4: vec!["meta/llama-*".to_string(), "local/*".to_string()],
             relays_public: vec![],
             relays_group: vec![],
+            relays_private: vec![],
+            usage_weight: 0.25,
+            usage_half_life_days: 30,
         }
     }
 
@@ -239,6 +402,95 @@ mod tests {
         assert_eq!(ranked[0].effective_score, 0.0);
     }
 
+    #[test]
+    fn ranked_results_explain_their_scores() {
+        let config = test_config();
+        let memories = vec![
+            (
+                make_memory("a", "trusted_agent", "anthropic/claude-sonnet-4", 100),
+                0.5,
+            ),
+            (
+                make_memory("b", "unknown_agent", "anthropic/claude-opus-4-6", 300),
+                1.0,
+            ),
+            (
+                make_memory("c", "unknown_agent", "meta/llama-70b", 200),
+                1.0,
+            ),
+        ];
+
+        let ranked = rank_memories(memories, &config);
+        assert_eq!(ranked[0].memory.id, "a");
+        assert_eq!(ranked[0].tier_weight, 0.8);
+        assert_eq!(ranked[0].recency, 0.0);
+        assert_eq!(
+            ranked[0].explanation,
+            "relevance 0.50 × trust 0.90 (source trusted_agent) × tier 2 weight 0.80 \
+             (anthropic/claude-sonnet-4) = 0.360"
+        );
+
+        // b and c both score 0; the newer one ranks first.
+        assert_eq!(ranked[1].memory.id, "b");
+        assert_eq!(ranked[1].recency, 1.0);
+        assert!(ranked[1]
+            .explanation
+            .contains("untrusted source unknown_agent"));
+        assert!(ranked[1]
+            .explanation
+            .ends_with("tied score ordered by recency 1.00 (newest first)"));
+        assert_eq!(ranked[2].recency, 0.5);
+    }
+
+    #[test]
+    fn used_memories_rank_higher_until_their_use_fades() {
+        let config = test_config();
+        let day = 86_400;
+        let now = 100 * day;
+        let used = |access_count, idle_days| MemoryUsage {
+            access_count,
+            last_used_at: Some(now - idle_days * day),
+        };
+        let memories = |usage| {
+            vec![
+                (
+                    make_memory("a", "trusted_agent", "anthropic/claude-opus-4-6", 200),
+                    0.8,
+                    MemoryUsage::default(),
+                ),
+                (
+                    make_memory("b", "trusted_agent", "anthropic/claude-opus-4-6", 100),
+                    0.75,
+                    usage,
+                ),
+            ]
+        };
+
+        // 4 uses today: score × (1 + 0.25 × 0.5)
+        let ranked = rank_memories_with_usage(memories(used(4, 0)), &config, now);
+        assert_eq!(ranked[0].memory.id, "b");
+        assert_eq!(ranked[0].usage_score, 0.5);
+        assert!((ranked[0].effective_score - 0.759375).abs() < 1e-9);
+        assert!(ranked[0]
+            .explanation
+            .ends_with("× usage 1.12 (4 uses) = 0.759"));
+        assert_eq!(ranked[1].usage_score, 0.0);
+        assert!(!ranked[1].explanation.contains("usage"));
+
+        // The same uses 60 days ago (two half-lives) no longer make up the gap.
+        let ranked = rank_memories_with_usage(memories(used(4, 60)), &config, now);
+        assert_eq!(ranked[0].memory.id, "a");
+        assert_eq!(ranked[1].usage_score, 0.125);
+
+        let ignored = MemoryConfig {
+            usage_weight: 0.0,
+            ..test_config()
+        };
+        let ranked = rank_memories_with_usage(memories(used(40, 0)), &ignored, now);
+        assert_eq!(ranked[0].memory.id, "a");
+        assert!(!ranked[1].explanation.contains("usage"));
+    }
+
     #[test]
     fn wildcard_model_matching() {
         let config = test_config();
@@ -284,4 +536,43 @@ mod tests {
         let winner = resolve_conflict(&conflict, &config);
         assert_eq!(winner, Some(1)); // trusted_agent + opus wins
     }
+
+    #[test]
+    fn validate_rejects_bad_trust_and_patterns() {
+        let mut config = test_config();
+        assert!(validate_config(&config).is_ok());
+        config.tier4.push("meta/*-instruct".to_string());
+        assert_eq!(
+            validate_config(&config),
+            Err(RankingError::InvalidTierPattern {
+                tier: 4,
+                pattern: "meta/*-instruct".to_string(),
+            })
+        );
+        config.tier4.pop();
+        config.usage_weight = -0.5;
+        assert_eq!(
+            validate_config(&config),
+            Err(RankingError::InvalidUsageWeight(-0.5))
+        );
+        config.usage_weight = 0.25;
+        config.sources.push(SourcePreference::for_group("ops", 1.5));
+        assert!(matches!(
+            validate_config(&config),
+            Err(RankingError::InvalidTrust { source_id, .. }) if source_id == "ops"
+        ));
+    }
+
+    #[test]
+    fn try_rank_rejects_nan_relevance() {
+        let config = test_config();
+        let memories = vec![(
+            make_memory("a", "trusted_agent", "anthropic/claude-opus-4-6", 100),
+            f64::NAN,
+        )];
+        assert!(matches!(
+            try_rank_memories(memories, &config),
+            Err(RankingError::InvalidRelevance { id, .. }) if id == "a"
+        ));
+    }
 }
@@ -1,9 +1,13 @@
 //! Layered search over locally cached memories using SQLite FTS5.
 
 use crate::config::MemoryConfig;
-use crate::ranking::rank_memories;
-use crate::types::{Memory, MemoryTier, SearchResult};
-use rusqlite::{params, Connection, Result as SqlResult};
+use crate::error::StoreResult;
+use crate::ranking::rank_memories_with_usage;
+use crate::types::{
+    AgentDirectoryEntry, Memory, MemoryTier, MemoryUsage, ReputationChange, ReputationReason,
+    SearchResult, SourceReputation,
+};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
 use std::path::Path;
 
 /// SQLite-backed memory index with FTS5 full-text search.
@@ -13,7 +17,7 @@ pub struct SqliteMemoryIndex {
 
 impl SqliteMemoryIndex {
     /// Open or create a memory index database.
-    pub fn open(path: &Path) -> SqlResult<Self> {
+    pub fn open(path: &Path) -> StoreResult<Self> {
         let conn = Connection::open(path)?;
         conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA busy_timeout=5000;")?;
 
@@ -57,19 +61,83 @@ impl SqliteMemoryIndex {
                 VALUES ('delete', old.rowid, old.summary, old.detail, old.tags);
                 INSERT INTO memories_fts(rowid, summary, detail, tags)
                 VALUES (new.rowid, new.summary, new.detail, new.tags);
-            END;",
+            END;
+
+            -- Archive tier: memories nobody has used in a long time, kept
+            -- as serialized rows outside the FTS index.
+            CREATE TABLE IF NOT EXISTS memories_archive (
+                id TEXT PRIMARY KEY,
+                topic TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                archived_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_archive_topic ON memories_archive(topic);
+
+            -- Conflicts found by the background scanner. `memory_ids` is the
+            -- sorted, comma-joined set of conflicting memories.
+            CREATE TABLE IF NOT EXISTS memory_conflicts (
+                topic TEXT NOT NULL,
+                memory_ids TEXT NOT NULL,
+                detected_at INTEGER NOT NULL,
+                resolved_at INTEGER,
+                PRIMARY KEY (topic, memory_ids)
+            );
+
+            -- Agent directory: the newest profile seen for each agent.
+            -- `profile` is the serialized AgentProfile.
+            CREATE TABLE IF NOT EXISTS agent_profiles (
+                pubkey TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                model_tier INTEGER,
+                profile TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+
+            -- Learned source reputation and the changes that led to it.
+            CREATE TABLE IF NOT EXISTS source_reputation (
+                source TEXT PRIMARY KEY,
+                factor REAL NOT NULL,
+                conflicts_lost INTEGER NOT NULL DEFAULT 0,
+                conflicts_won INTEGER NOT NULL DEFAULT 0,
+                corrections INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reputation_changes (
+                source TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                topic TEXT NOT NULL,
+                factor_before REAL NOT NULL,
+                factor_after REAL NOT NULL,
+                at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_reputation_changes_at ON reputation_changes(at);",
         )?;
 
+        // Migrations: track when a memory was last returned by a search, and
+        // how often.
+        let memories_sql: String = conn
+            .prepare("SELECT sql FROM sqlite_master WHERE type='table' AND name='memories'")?
+            .query_row([], |row| row.get(0))?;
+        if !memories_sql.contains("last_used_at") {
+            conn.execute_batch("ALTER TABLE memories ADD COLUMN last_used_at INTEGER;")?;
+        }
+        if !memories_sql.contains("access_count") {
+            conn.execute_batch(
+                "ALTER TABLE memories ADD COLUMN access_count INTEGER NOT NULL DEFAULT 0;",
+            )?;
+        }
+
         Ok(Self { conn })
     }
 
     /// Open an in-memory database (for testing).
-    pub fn open_in_memory() -> SqlResult<Self> {
+    pub fn open_in_memory() -> StoreResult<Self> {
         Self::open(Path::new(":memory:"))
     }
 
     /// Insert or update a memory.
-    pub fn upsert(&self, memory: &Memory, event_json: Option<&str>) -> SqlResult<()> {
+    pub fn upsert(&self, memory: &Memory, event_json: Option<&str>) -> StoreResult<()> {
         let tier_str = memory.tier.to_string();
         let tags_str = memory.tags.join(",");
 
@@ -96,7 +164,7 @@ impl SqliteMemoryIndex {
         query: &str,
         tier_filter: Option<&str>,
         limit: usize,
-    ) -> SqlResult<Vec<(Memory, f64)>> {
+    ) -> StoreResult<Vec<(Memory, f64)>> {
         // Sanitize query for FTS5: quote each word to avoid special char issues (e.g. hyphens)
         let fts_query: String = query
             .split_whitespace()
@@ -150,31 +218,34 @@ impl SqliteMemoryIndex {
         Ok(results)
     }
 
-    /// Search and apply trust ranking using the full MemoryConfig.
+    /// Search and apply trust and usage ranking using the full MemoryConfig.
     pub fn ranked_search(
         &self,
         query: &str,
         tier_filter: Option<&str>,
         config: &MemoryConfig,
         limit: usize,
-    ) -> SqlResult<Vec<SearchResult>> {
+    ) -> StoreResult<Vec<SearchResult>> {
         let raw = self.search(query, tier_filter, limit * 3)?;
 
-        let pairs: Vec<(Memory, f64)> = raw
-            .into_iter()
-            .map(|(memory, bm25_score)| {
-                let relevance = (-bm25_score).max(0.0).min(1.0);
-                (memory, relevance)
-            })
-            .collect();
+        let mut scored = Vec::with_capacity(raw.len());
+        for (memory, bm25_score) in raw {
+            let relevance = (-bm25_score).max(0.0).min(1.0);
+            let usage = self.usage(&memory.id)?;
+            scored.push((memory, relevance, usage));
+        }
 
-        let mut ranked = rank_memories(pairs, config);
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut ranked = rank_memories_with_usage(scored, config, now);
         ranked.truncate(limit);
         Ok(ranked)
     }
 
     /// Get a memory by ID.
-    pub fn get(&self, id: &str) -> SqlResult<Option<Memory>> {
+    pub fn get(&self, id: &str) -> StoreResult<Option<Memory>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, tier, topic, summary, detail, context, source, model,
                     confidence, supersedes, version, tags, created_at
@@ -190,7 +261,7 @@ impl SqliteMemoryIndex {
     }
 
     /// Look up a memory by topic (key).
-    pub fn get_by_topic(&self, topic: &str) -> SqlResult<Option<Memory>> {
+    pub fn get_by_topic(&self, topic: &str) -> StoreResult<Option<Memory>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, tier, topic, summary, detail, context, source, model,
                     confidence, supersedes, version, tags, created_at
@@ -206,7 +277,7 @@ impl SqliteMemoryIndex {
     }
 
     /// Delete memories older than `max_age_secs`.
-    pub fn evict_stale(&self, max_age_secs: u64) -> SqlResult<usize> {
+    pub fn evict_stale(&self, max_age_secs: u64) -> StoreResult<usize> {
         let cutoff = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
@@ -221,15 +292,48 @@ impl SqliteMemoryIndex {
     }
 
     /// Count total memories.
-    pub fn count(&self) -> SqlResult<usize> {
-        self.conn
+    pub fn count(&self) -> StoreResult<usize> {
+        let count = self
+            .conn
             .query_row("SELECT COUNT(*) FROM memories", [], |row| {
                 row.get::<_, usize>(0)
-            })
+            })?;
+        Ok(count)
+    }
+
+    /// Delete a memory by ID. Returns true if a row was deleted.
+    pub fn delete(&self, id: &str) -> StoreResult<bool> {
+        let count = self
+            .conn
+            .execute("DELETE FROM memories WHERE id = ?1", params![id])?;
+        Ok(count > 0)
+    }
+
+    /// Set the `cached_at` of memory `id` (e.g. restored from a snapshot).
+    pub fn set_cached_at(&self, id: &str, cached_at: u64) -> StoreResult<()> {
+        self.conn.execute(
+            "UPDATE memories SET cached_at = ?1 WHERE id = ?2",
+            params![cached_at as i64, id],
+        )?;
+        Ok(())
+    }
+
+    /// IDs and `cached_at` of all memories, least recently cached first.
+    pub fn cache_times(&self) -> StoreResult<Vec<(String, u64)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, cached_at FROM memories ORDER BY cached_at ASC, rowid ASC")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?.max(0) as u64,
+            ))
+        })?;
+        Ok(rows.collect::<SqlResult<_>>()?)
     }
 
     /// Delete a memory by topic. Returns true if a row was deleted.
-    pub fn delete_by_topic(&self, topic: &str) -> SqlResult<bool> {
+    pub fn delete_by_topic(&self, topic: &str) -> StoreResult<bool> {
         let count = self
             .conn
             .execute("DELETE FROM memories WHERE topic = ?1", params![topic])?;
@@ -237,7 +341,7 @@ impl SqliteMemoryIndex {
     }
 
     /// List all memories, optionally filtered by tier prefix, up to `limit`.
-    pub fn list_all(&self, tier_filter: Option<&str>, limit: usize) -> SqlResult<Vec<Memory>> {
+    pub fn list_all(&self, tier_filter: Option<&str>, limit: usize) -> StoreResult<Vec<Memory>> {
         let (sql, use_tier) = if tier_filter.is_some() {
             (
                 "SELECT id, tier, topic, summary, detail, context, source, model,
@@ -281,14 +385,424 @@ impl SqliteMemoryIndex {
         Ok(results)
     }
 
+    /// Record that memories were just recalled into a prompt: their access
+    /// count goes up and `now` becomes their last use.
+    pub fn touch(&self, ids: &[&str], now: u64) -> StoreResult<()> {
+        let mut stmt = self.conn.prepare(
+            "UPDATE memories SET last_used_at = ?1, access_count = access_count + 1
+             WHERE id = ?2",
+        )?;
+        for id in ids {
+            stmt.execute(params![now as i64, id])?;
+        }
+        Ok(())
+    }
+
+    /// Recorded use of a memory; never used (or unknown) is the default.
+    pub fn usage(&self, id: &str) -> StoreResult<MemoryUsage> {
+        let usage = self
+            .conn
+            .query_row(
+                "SELECT access_count, last_used_at FROM memories WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok(MemoryUsage {
+                        access_count: row.get::<_, i64>(0)?.max(0) as u64,
+                        last_used_at: row.get::<_, Option<i64>>(1)?.map(|t| t.max(0) as u64),
+                    })
+                },
+            )
+            .optional()?;
+        Ok(usage.unwrap_or_default())
+    }
+
+    /// Move memories neither created nor used since `cutoff` into the archive.
+    /// Returns the number of memories archived.
+    pub fn archive_untouched(&self, cutoff: u64, now: u64) -> StoreResult<usize> {
+        let ids: Vec<String> = {
+            let mut stmt = self.conn.prepare(
+                "SELECT id FROM memories WHERE MAX(created_at, COALESCE(last_used_at, 0)) < ?1",
+            )?;
+            let rows = stmt.query_map(params![cutoff as i64], |row| row.get(0))?;
+            rows.collect::<SqlResult<_>>()?
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut archived = 0;
+        for id in &ids {
+            let Some(memory) = self.get(id)? else {
+                continue;
+            };
+            let payload = serde_json::to_string(&memory)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO memories_archive (id, topic, summary, payload, archived_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![memory.id, memory.topic, memory.summary, payload, now as i64],
+            )?;
+            tx.execute("DELETE FROM memories WHERE id = ?1", params![id])?;
+            archived += 1;
+        }
+        tx.commit()?;
+        Ok(archived)
+    }
+
+    /// Search archived memories by the words of their topic and summary,
+    /// most matching words first.
+    pub fn search_archive(&self, query: &str, limit: usize) -> StoreResult<Vec<Memory>> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if words.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT topic, summary, payload FROM memories_archive")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+
+        let mut scored = Vec::new();
+        for row in rows {
+            let (topic, summary, payload) = row?;
+            let haystack = format!("{topic} {summary}").to_lowercase();
+            let hits = words
+                .iter()
+                .filter(|w| haystack.contains(w.as_str()))
+                .count();
+            if hits == 0 {
+                continue;
+            }
+            if let Ok(memory) = serde_json::from_str::<Memory>(&payload) {
+                scored.push((hits, memory));
+            }
+        }
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.created_at.cmp(&a.1.created_at)));
+        Ok(scored.into_iter().take(limit).map(|(_, m)| m).collect())
+    }
+
+    /// Move an archived memory back into the live index, marking it used.
+    pub fn rehydrate(&self, id: &str, now: u64) -> StoreResult<Option<Memory>> {
+        let payload: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT payload FROM memories_archive WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(memory) = payload.and_then(|p| serde_json::from_str::<Memory>(&p).ok()) else {
+            return Ok(None);
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.upsert(&memory, None)?;
+        tx.execute(
+            "UPDATE memories SET last_used_at = ?1 WHERE id = ?2",
+            params![now as i64, id],
+        )?;
+        tx.execute("DELETE FROM memories_archive WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(Some(memory))
+    }
+
+    /// Rehydrate every archived memory with the given topic.
+    pub fn rehydrate_topic(&self, topic: &str, now: u64) -> StoreResult<Vec<Memory>> {
+        let ids: Vec<String> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT id FROM memories_archive WHERE topic = ?1")?;
+            let rows = stmt.query_map(params![topic], |row| row.get(0))?;
+            rows.collect::<SqlResult<_>>()?
+        };
+        let mut restored = Vec::new();
+        for id in ids {
+            restored.extend(self.rehydrate(&id, now)?);
+        }
+        Ok(restored)
+    }
+
+    /// Count archived memories.
+    pub fn archive_count(&self) -> StoreResult<usize> {
+        let count = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM memories_archive", [], |row| {
+                row.get::<_, usize>(0)
+            })?;
+        Ok(count)
+    }
+
+    /// All memories on a topic, newest first.
+    pub fn list_by_topic(&self, topic: &str) -> StoreResult<Vec<Memory>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, tier, topic, summary, detail, context, source, model,
+                    confidence, supersedes, version, tags, created_at
+             FROM memories WHERE topic = ?1
+             ORDER BY created_at DESC",
+        )?;
+        let rows = stmt.query_map(params![topic], Self::row_to_memory)?;
+        Ok(rows.collect::<SqlResult<_>>()?)
+    }
+
+    /// Distinct topics with a memory created in `[since, until]`.
+    pub fn topics_created_between(&self, since: u64, until: u64) -> StoreResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT topic FROM memories
+             WHERE created_at >= ?1 AND created_at <= ?2
+             ORDER BY topic",
+        )?;
+        let rows = stmt.query_map(params![since as i64, until as i64], |row| row.get(0))?;
+        Ok(rows.collect::<SqlResult<_>>()?)
+    }
+
+    /// Record an unresolved conflict between `ids` on `topic`. Earlier
+    /// unresolved records for the topic with a different set of memories are
+    /// marked resolved. Returns `true` when this set was not recorded before.
+    pub fn record_conflict(&self, topic: &str, ids: &[&str], now: u64) -> StoreResult<bool> {
+        let mut sorted = ids.to_vec();
+        sorted.sort_unstable();
+        let key = sorted.join(",");
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE memory_conflicts SET resolved_at = ?1
+             WHERE topic = ?2 AND memory_ids != ?3 AND resolved_at IS NULL",
+            params![now as i64, topic, key],
+        )?;
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO memory_conflicts (topic, memory_ids, detected_at)
+             VALUES (?1, ?2, ?3)",
+            params![topic, key, now as i64],
+        )?;
+        tx.commit()?;
+        Ok(inserted > 0)
+    }
+
+    /// Mark every unresolved conflict on `topic` resolved. Returns how many were.
+    pub fn resolve_conflicts(&self, topic: &str, now: u64) -> StoreResult<usize> {
+        let resolved = self.conn.execute(
+            "UPDATE memory_conflicts SET resolved_at = ?1
+             WHERE topic = ?2 AND resolved_at IS NULL",
+            params![now as i64, topic],
+        )?;
+        Ok(resolved)
+    }
+
+    /// Unresolved conflicts as `(topic, memory_ids, detected_at)`, oldest first.
+    pub fn unresolved_conflicts(&self) -> StoreResult<Vec<(String, Vec<String>, u64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT topic, memory_ids, detected_at FROM memory_conflicts
+             WHERE resolved_at IS NULL
+             ORDER BY detected_at, topic",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let ids: String = row.get(1)?;
+            Ok((
+                row.get(0)?,
+                ids.split(',').map(str::to_string).collect(),
+                row.get::<_, i64>(2)? as u64,
+            ))
+        })?;
+        Ok(rows.collect::<SqlResult<_>>()?)
+    }
+
+    /// Store `change`: the source's factor becomes `factor_after` and the
+    /// counter for its reason goes up. The change is logged for reports.
+    pub fn record_reputation_change(&self, change: &ReputationChange) -> StoreResult<()> {
+        let (lost, won, corrected) = match change.reason {
+            ReputationReason::ConflictLost => (1, 0, 0),
+            ReputationReason::ConflictWon => (0, 1, 0),
+            ReputationReason::OwnerCorrection => (0, 0, 1),
+        };
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO source_reputation
+                (source, factor, conflicts_lost, conflicts_won, corrections, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(source) DO UPDATE SET
+                factor = excluded.factor,
+                conflicts_lost = conflicts_lost + excluded.conflicts_lost,
+                conflicts_won = conflicts_won + excluded.conflicts_won,
+                corrections = corrections + excluded.corrections,
+                updated_at = excluded.updated_at",
+            params![
+                change.source,
+                change.factor_after,
+                lost,
+                won,
+                corrected,
+                change.at as i64
+            ],
+        )?;
+        tx.execute(
+            "INSERT INTO reputation_changes
+                (source, reason, topic, factor_before, factor_after, at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                change.source,
+                change.reason.as_str(),
+                change.topic,
+                change.factor_before,
+                change.factor_after,
+                change.at as i64
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Learned reputation of `source`, if any outcome was recorded for it.
+    pub fn get_reputation(&self, source: &str) -> StoreResult<Option<SourceReputation>> {
+        let mut reputations = self.query_reputations("WHERE source = ?1", params![source])?;
+        Ok(reputations.pop())
+    }
+
+    /// Every source with a recorded outcome, least trusted first.
+    pub fn list_reputations(&self) -> StoreResult<Vec<SourceReputation>> {
+        self.query_reputations("", params![])
+    }
+
+    fn query_reputations(
+        &self,
+        filter: &str,
+        args: &[&dyn rusqlite::ToSql],
+    ) -> StoreResult<Vec<SourceReputation>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT source, factor, conflicts_lost, conflicts_won, corrections, updated_at
+             FROM source_reputation {filter}
+             ORDER BY factor, source"
+        ))?;
+        let rows = stmt.query_map(args, |row| {
+            Ok(SourceReputation {
+                source: row.get(0)?,
+                factor: row.get(1)?,
+                conflicts_lost: row.get(2)?,
+                conflicts_won: row.get(3)?,
+                corrections: row.get(4)?,
+                updated_at: row.get::<_, i64>(5)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<SqlResult<_>>()?)
+    }
+
+    /// Reputation changes at or after `since`, newest first.
+    pub fn reputation_changes_since(&self, since: u64) -> StoreResult<Vec<ReputationChange>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT source, reason, topic, factor_before, factor_after, at
+             FROM reputation_changes WHERE at >= ?1
+             ORDER BY at DESC, rowid DESC",
+        )?;
+        let rows = stmt
+            .query_map(params![since as i64], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, f64>(3)?,
+                    row.get::<_, f64>(4)?,
+                    row.get::<_, i64>(5)?,
+                ))
+            })?
+            .collect::<SqlResult<Vec<_>>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(source, reason, topic, factor_before, factor_after, at)| {
+                Some(ReputationChange {
+                    source,
+                    reason: ReputationReason::parse(&reason)?,
+                    topic,
+                    factor_before,
+                    factor_after,
+                    at: at as u64,
+                })
+            })
+            .collect())
+    }
+
+    /// Index a discovered agent profile. Older profiles than the one stored
+    /// for the agent are ignored; returns `true` when the entry was stored.
+    pub fn upsert_profile(&self, entry: &AgentDirectoryEntry) -> StoreResult<bool> {
+        let profile = serde_json::to_string(&entry.profile)?;
+        let changed = self.conn.execute(
+            "INSERT INTO agent_profiles (pubkey, name, model_tier, profile, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(pubkey) DO UPDATE SET
+                name = excluded.name,
+                model_tier = excluded.model_tier,
+                profile = excluded.profile,
+                updated_at = excluded.updated_at
+             WHERE excluded.updated_at > agent_profiles.updated_at",
+            params![
+                entry.pubkey,
+                entry.profile.name,
+                entry.profile.model_tier,
+                profile,
+                entry.updated_at as i64
+            ],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Indexed profile of the agent with `pubkey`.
+    pub fn get_profile(&self, pubkey: &str) -> StoreResult<Option<AgentDirectoryEntry>> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT pubkey, profile, updated_at FROM agent_profiles WHERE pubkey = ?1",
+                params![pubkey],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, i64>(2)?)),
+            )
+            .optional()?;
+        row.map(Self::profile_entry).transpose()
+    }
+
+    /// Indexed agents, best model tier first, then by name. With
+    /// `capability`, only agents advertising it.
+    pub fn list_profiles(&self, capability: Option<&str>) -> StoreResult<Vec<AgentDirectoryEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT pubkey, profile, updated_at FROM agent_profiles
+             ORDER BY model_tier IS NULL, model_tier, name, pubkey",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get::<_, i64>(2)?))
+            })?
+            .collect::<SqlResult<Vec<_>>>()?;
+        let mut entries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let entry = Self::profile_entry(row)?;
+            if capability.is_none_or(|c| entry.profile.capabilities.iter().any(|have| have == c)) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    fn profile_entry(
+        (pubkey, profile, updated_at): (String, String, i64),
+    ) -> StoreResult<AgentDirectoryEntry> {
+        Ok(AgentDirectoryEntry {
+            pubkey,
+            profile: serde_json::from_str(&profile)?,
+            updated_at: updated_at as u64,
+        })
+    }
+
     /// Execute a raw SQL statement (for schema extensions like metadata tables).
-    pub fn execute_raw(&self, sql: &str) -> SqlResult<()> {
-        self.conn.execute_batch(sql)
+    pub fn execute_raw(&self, sql: &str) -> StoreResult<()> {
+        self.conn.execute_batch(sql)?;
+        Ok(())
     }
 
     /// Query a single text value from a raw SQL statement.
-    pub fn query_raw(&self, sql: &str) -> SqlResult<String> {
-        self.conn.query_row(sql, [], |row| row.get::<_, String>(0))
+    pub fn query_raw(&self, sql: &str) -> StoreResult<String> {
+        let value = self
+            .conn
+            .query_row(sql, [], |row| row.get::<_, String>(0))?;
+        Ok(value)
     }
 
     fn row_to_memory(row: &rusqlite::Row<'_>) -> rusqlite::Result<Memory> {
@@ -427,4 +941,218 @@ mod tests {
             .unwrap();
         assert_eq!(idx.count().unwrap(), 2);
     }
+
+    #[test]
+    fn test_usage_counts_feed_ranking() {
+        let idx = SqliteMemoryIndex::open_in_memory().unwrap();
+        idx.upsert(&make_memory("1", "t1", "rust error handling", "aaa"), None)
+            .unwrap();
+        idx.upsert(&make_memory("2", "t2", "rust error handling", "aaa"), None)
+            .unwrap();
+        assert_eq!(idx.usage("2").unwrap(), MemoryUsage::default());
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        idx.touch(&["2"], now - 10).unwrap();
+        idx.touch(&["2"], now).unwrap();
+        assert_eq!(
+            idx.usage("2").unwrap(),
+            MemoryUsage {
+                access_count: 2,
+                last_used_at: Some(now),
+            }
+        );
+
+        let config = MemoryConfig {
+            sources: vec![crate::types::SourcePreference::for_npub("aaa", 1.0)],
+            ..MemoryConfig::default()
+        };
+        let ranked = idx.ranked_search("rust", None, &config, 2).unwrap();
+        assert_eq!(ranked[0].memory.id, "2");
+        assert_eq!(ranked[0].usage.access_count, 2);
+        assert!(ranked[0].effective_score > ranked[1].effective_score);
+    }
+
+    #[test]
+    fn test_archive_and_rehydrate() {
+        let idx = SqliteMemoryIndex::open_in_memory().unwrap();
+        let mut old = make_memory("1", "relay/staging", "Staging relay is relay1", "a");
+        old.created_at = 1_000;
+        let mut used = make_memory("2", "relay/prod", "Production relay is relay9", "a");
+        used.created_at = 1_000;
+        idx.upsert(&old, None).unwrap();
+        idx.upsert(&used, None).unwrap();
+        idx.touch(&["2"], 5_000).unwrap();
+
+        assert_eq!(idx.archive_untouched(4_000, 6_000).unwrap(), 1);
+        assert_eq!(idx.count().unwrap(), 1);
+        assert_eq!(idx.archive_count().unwrap(), 1);
+        assert!(idx.search("staging", None, 10).unwrap().is_empty());
+
+        let found = idx.search_archive("staging relay", 5).unwrap();
+        assert_eq!(found, vec![old.clone()]);
+
+        let restored = idx.rehydrate_topic("relay/staging", 7_000).unwrap();
+        assert_eq!(restored, vec![old]);
+        assert_eq!(idx.archive_count().unwrap(), 0);
+        assert_eq!(idx.search("staging", None, 10).unwrap().len(), 1);
+        // Rehydration counts as a use, so the memory is not archived again.
+        assert_eq!(idx.archive_untouched(5_000, 8_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_conflict_records() {
+        let idx = SqliteMemoryIndex::open_in_memory().unwrap();
+        let mut a = make_memory("a", "relay/staging", "relay1", "alice");
+        a.created_at = 1_000;
+        let mut b = make_memory("b", "relay/staging", "relay2", "bob");
+        b.created_at = 2_000;
+        idx.upsert(&a, None).unwrap();
+        idx.upsert(&b, None).unwrap();
+
+        assert_eq!(
+            idx.topics_created_between(1_500, 3_000).unwrap(),
+            vec!["relay/staging"]
+        );
+        assert_eq!(idx.list_by_topic("relay/staging").unwrap(), vec![b, a]);
+
+        assert!(idx
+            .record_conflict("relay/staging", &["b", "a"], 3_000)
+            .unwrap());
+        assert!(!idx
+            .record_conflict("relay/staging", &["a", "b"], 3_100)
+            .unwrap());
+        assert_eq!(
+            idx.unresolved_conflicts().unwrap(),
+            vec![(
+                "relay/staging".to_string(),
+                vec!["a".to_string(), "b".to_string()],
+                3_000
+            )]
+        );
+
+        // A new set of memories replaces the old record.
+        assert!(idx
+            .record_conflict("relay/staging", &["a", "c"], 4_000)
+            .unwrap());
+        assert_eq!(idx.unresolved_conflicts().unwrap().len(), 1);
+        assert_eq!(idx.resolve_conflicts("relay/staging", 5_000).unwrap(), 1);
+        assert!(idx.unresolved_conflicts().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reputation_changes() {
+        let idx = SqliteMemoryIndex::open_in_memory().unwrap();
+        assert_eq!(idx.get_reputation("alice").unwrap(), None);
+
+        let change = |source: &str, reason, before, after, at| ReputationChange {
+            source: source.to_string(),
+            reason,
+            topic: "relay/staging".to_string(),
+            factor_before: before,
+            factor_after: after,
+            at,
+        };
+        idx.record_reputation_change(&change(
+            "alice",
+            ReputationReason::ConflictLost,
+            1.0,
+            0.8,
+            1_000,
+        ))
+        .unwrap();
+        idx.record_reputation_change(&change(
+            "bob",
+            ReputationReason::ConflictWon,
+            1.0,
+            1.0,
+            1_000,
+        ))
+        .unwrap();
+        idx.record_reputation_change(&change(
+            "alice",
+            ReputationReason::OwnerCorrection,
+            0.8,
+            0.64,
+            2_000,
+        ))
+        .unwrap();
+
+        let alice = idx.get_reputation("alice").unwrap().unwrap();
+        assert_eq!(
+            (
+                alice.factor,
+                alice.conflicts_lost,
+                alice.corrections,
+                alice.updated_at
+            ),
+            (0.64, 1, 1, 2_000)
+        );
+        let sources: Vec<String> = idx
+            .list_reputations()
+            .unwrap()
+            .into_iter()
+            .map(|r| r.source)
+            .collect();
+        assert_eq!(sources, vec!["alice", "bob"]);
+
+        let recent = idx.reputation_changes_since(1_500).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].reason, ReputationReason::OwnerCorrection);
+        assert_eq!(idx.reputation_changes_since(0).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_agent_directory() {
+        use crate::types::AgentProfile;
+
+        let idx = SqliteMemoryIndex::open_in_memory().unwrap();
+        let entry =
+            |pubkey: &str, name: &str, tier: Option<u8>, updated_at: u64| AgentDirectoryEntry {
+                pubkey: pubkey.to_string(),
+                profile: AgentProfile {
+                    name: name.to_string(),
+                    about: String::new(),
+                    model: "test/model".to_string(),
+                    version: "0.1.0".to_string(),
+                    capabilities: vec!["memory".to_string()],
+                    operator: None,
+                    model_tier: tier,
+                    actions: vec!["memory.".to_string()],
+                },
+                updated_at,
+            };
+
+        assert!(idx
+            .upsert_profile(&entry("aa", "zed", Some(1), 100))
+            .unwrap());
+        assert!(idx
+            .upsert_profile(&entry("bb", "amy", Some(3), 100))
+            .unwrap());
+        assert!(idx.upsert_profile(&entry("cc", "bob", None, 100)).unwrap());
+        // A stale copy from another relay does not replace the newer one.
+        assert!(!idx
+            .upsert_profile(&entry("aa", "old", Some(1), 50))
+            .unwrap());
+        assert!(idx
+            .upsert_profile(&entry("bb", "amy", Some(2), 200))
+            .unwrap());
+
+        let names: Vec<String> = idx
+            .list_profiles(None)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.profile.name)
+            .collect();
+        assert_eq!(names, vec!["zed", "amy", "bob"]);
+        assert_eq!(
+            idx.get_profile("bb").unwrap(),
+            Some(entry("bb", "amy", Some(2), 200))
+        );
+        assert!(idx.list_profiles(Some("code")).unwrap().is_empty());
+        assert_eq!(idx.list_profiles(Some("memory")).unwrap().len(), 3);
+        assert!(idx.get_profile("dd").unwrap().is_none());
+    }
 }
@@ -1,51 +1,101 @@
 //! Subscribe to memory events from Nostr relays.
 //!
-//! Handles parsing incoming relay messages into Memory structs.
-//! Actual WebSocket transport is handled by the caller.
+//! Handles parsing incoming relay messages into Memory structs and
+//! correlating `OK` / `CLOSED` / `NOTICE` replies with published events
+//! ([`PublishTracker`]). Actual WebSocket transport is handled by the caller.
 
+use crate::bloom::RotatingBloom;
+use crate::config::{DedupConfig, DedupMode};
 use crate::event;
 use crate::types::Memory;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
 
 /// Tracks seen event IDs for deduplication.
+///
+/// The exact mode keeps up to `max_size` ids in a set. The bloom mode
+/// ([`DedupConfig`] `mode = "bloom"`) uses a [`RotatingBloom`] with fixed
+/// memory for high-volume subscriptions, at the cost of occasionally
+/// treating a new event as a duplicate.
 pub struct EventDedup {
-    seen: HashSet<String>,
-    max_size: usize,
+    inner: DedupInner,
+}
+
+enum DedupInner {
+    Exact {
+        seen: HashSet<String>,
+        max_size: usize,
+    },
+    Bloom(RotatingBloom),
 }
 
 impl EventDedup {
     pub fn new(max_size: usize) -> Self {
         Self {
-            seen: HashSet::new(),
-            max_size,
+            inner: DedupInner::Exact {
+                seen: HashSet::new(),
+                max_size,
+            },
+        }
+    }
+
+    /// Bloom-filter dedup remembering at least `capacity` ids.
+    pub fn bloom(capacity: usize, false_positive_rate: f64) -> Self {
+        Self {
+            inner: DedupInner::Bloom(RotatingBloom::new(capacity, false_positive_rate)),
+        }
+    }
+
+    pub fn from_config(config: &DedupConfig) -> Self {
+        match config.mode {
+            DedupMode::Exact => Self::new(config.capacity),
+            DedupMode::Bloom => Self::bloom(config.capacity, config.false_positive_rate),
         }
     }
 
     /// Returns true if the event is new (not seen before).
     pub fn check_and_insert(&mut self, event_id: &str) -> bool {
-        if self.seen.contains(event_id) {
+        let (seen, max_size) = match &mut self.inner {
+            DedupInner::Exact { seen, max_size } => (seen, *max_size),
+            DedupInner::Bloom(bloom) => return bloom.check_and_insert(event_id),
+        };
+        if seen.contains(event_id) {
             return false;
         }
 
         // Evict oldest if at capacity (simple: just clear half)
-        if self.seen.len() >= self.max_size {
-            let to_remove: Vec<String> =
-                self.seen.iter().take(self.max_size / 2).cloned().collect();
+        if seen.len() >= max_size {
+            let to_remove: Vec<String> = seen.iter().take(max_size / 2).cloned().collect();
             for id in to_remove {
-                self.seen.remove(&id);
+                seen.remove(&id);
             }
         }
 
-        self.seen.insert(event_id.to_string());
+        seen.insert(event_id.to_string());
         true
     }
 
     pub fn len(&self) -> usize {
-        self.seen.len()
+        match &self.inner {
+            DedupInner::Exact { seen, .. } => seen.len(),
+            DedupInner::Bloom(bloom) => bloom.len(),
+        }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.seen.is_empty()
+        self.len() == 0
+    }
+
+    /// Estimated chance that a new event is reported as a duplicate
+    /// (always 0 in exact mode).
+    pub fn false_positive_rate(&self) -> f64 {
+        match &self.inner {
+            DedupInner::Exact { .. } => 0.0,
+            DedupInner::Bloom(bloom) => bloom.false_positive_rate(),
+        }
     }
 }
 
@@ -55,6 +105,8 @@ impl EventDedup {
 /// - `["EVENT", <sub_id>, <event>]`
 /// - `["EOSE", <sub_id>]`
 /// - `["NOTICE", <message>]`
+/// - `["OK", <event_id>, <accepted>, <message>]`
+/// - `["CLOSED", <sub_id>, <message>]`
 pub fn parse_relay_message(msg: &str) -> RelayMessage {
     let parsed: Result<serde_json::Value, _> = serde_json::from_str(msg);
     let parsed = match parsed {
@@ -132,6 +184,19 @@ pub fn parse_relay_message(msg: &str) -> RelayMessage {
                 message,
             }
         }
+        Some("CLOSED") => {
+            let sub_id = arr
+                .get(1)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+            let message = arr
+                .get(2)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+            RelayMessage::Closed { sub_id, message }
+        }
         _ => RelayMessage::Unknown(msg.to_string()),
     }
 }
@@ -155,10 +220,273 @@ pub enum RelayMessage {
         accepted: bool,
         message: String,
     },
+    /// The relay ended a subscription.
+    Closed { sub_id: String, message: String },
     /// Unparseable message.
     Unknown(String),
 }
 
+/// Machine-readable prefix of an `OK` / `CLOSED` message (`"blocked"` for
+/// `"blocked: spam"`), per NIP-01.
+pub fn reason_prefix(message: &str) -> Option<&str> {
+    let (prefix, _) = message.split_once(':')?;
+    (!prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_lowercase() || c == '-'))
+        .then_some(prefix)
+}
+
+/// What one relay did with a published event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayAck {
+    /// No reply yet.
+    Pending,
+    /// `OK true`. A `duplicate:` message also counts as stored.
+    Accepted { message: String },
+    /// `OK false`.
+    Rejected { message: String },
+    /// No `OK` before the connection dropped or the receipt expired. Carries
+    /// the relay's last `NOTICE`, if any.
+    NoReply { notice: Option<String> },
+}
+
+impl RelayAck {
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::Pending)
+    }
+}
+
+/// Final per-relay result of a publish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishOutcome {
+    pub event_id: String,
+    /// Relay URL -> acknowledgement.
+    pub relays: BTreeMap<String, RelayAck>,
+}
+
+impl PublishOutcome {
+    /// Relays that stored the event.
+    pub fn accepted_by(&self) -> Vec<&str> {
+        self.relays
+            .iter()
+            .filter(|(_, ack)| matches!(ack, RelayAck::Accepted { .. }))
+            .map(|(relay, _)| relay.as_str())
+            .collect()
+    }
+
+    /// True when at least one relay stored the event.
+    pub fn is_accepted(&self) -> bool {
+        !self.accepted_by().is_empty()
+    }
+}
+
+struct PendingPublish {
+    relays: BTreeMap<String, RelayAck>,
+    waker: Option<Waker>,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    pending: HashMap<String, PendingPublish>,
+    /// Last `NOTICE` per relay.
+    notices: HashMap<String, String>,
+    /// Subscriptions the relays ended: (relay, sub_id, message).
+    closed: Vec<(String, String, String)>,
+}
+
+/// Correlates relay replies with published events.
+///
+/// Call [`PublishTracker::track`] when sending an event, feed every relay
+/// message to [`PublishTracker::handle`], and await the returned
+/// [`PublishReceipt`]. It resolves once every relay has answered, dropped
+/// its connection ([`PublishTracker::relay_disconnected`]) or the caller
+/// gives up ([`PublishTracker::expire`]).
+#[derive(Clone, Default)]
+pub struct PublishTracker {
+    state: Arc<Mutex<TrackerState>>,
+}
+
+impl PublishTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TrackerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start tracking `event_id` sent to `relays`.
+    pub fn track<I, S>(&self, event_id: &str, relays: I) -> PublishReceipt
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let relays = relays
+            .into_iter()
+            .map(|relay| (relay.into(), RelayAck::Pending))
+            .collect();
+        self.lock().pending.insert(
+            event_id.to_string(),
+            PendingPublish {
+                relays,
+                waker: None,
+            },
+        );
+        PublishReceipt {
+            tracker: self.clone(),
+            event_id: event_id.to_string(),
+        }
+    }
+
+    /// Apply a message received from `relay`.
+    pub fn handle(&self, relay: &str, message: &RelayMessage) {
+        let mut state = self.lock();
+        match message {
+            RelayMessage::Ok {
+                event_id,
+                accepted,
+                message,
+            } => {
+                let ack = if *accepted || reason_prefix(message) == Some("duplicate") {
+                    RelayAck::Accepted {
+                        message: message.clone(),
+                    }
+                } else {
+                    RelayAck::Rejected {
+                        message: message.clone(),
+                    }
+                };
+                if let Some(publish) = state.pending.get_mut(event_id) {
+                    settle(publish, relay, ack);
+                }
+            }
+            RelayMessage::Notice { message } => {
+                state.notices.insert(relay.to_string(), message.clone());
+            }
+            RelayMessage::Closed { sub_id, message } => {
+                state
+                    .closed
+                    .push((relay.to_string(), sub_id.clone(), message.clone()));
+            }
+            _ => {}
+        }
+    }
+
+    /// `relay` dropped its connection: its unanswered publishes get no reply.
+    pub fn relay_disconnected(&self, relay: &str) {
+        let mut state = self.lock();
+        let notice = state.notices.remove(relay);
+        for publish in state.pending.values_mut() {
+            let ack = RelayAck::NoReply {
+                notice: notice.clone(),
+            };
+            settle(publish, relay, ack);
+        }
+    }
+
+    /// Stop waiting for `event_id`: relays that have not answered get no
+    /// reply and the receipt resolves.
+    pub fn expire(&self, event_id: &str) {
+        let mut state = self.lock();
+        let TrackerState {
+            pending, notices, ..
+        } = &mut *state;
+        if let Some(publish) = pending.get_mut(event_id) {
+            let silent: Vec<String> = publish
+                .relays
+                .iter()
+                .filter(|(_, ack)| ack.is_pending())
+                .map(|(relay, _)| relay.clone())
+                .collect();
+            for relay in silent {
+                let notice = notices.get(&relay).cloned();
+                settle(publish, &relay, RelayAck::NoReply { notice });
+            }
+        }
+    }
+
+    /// Subscriptions ended by relays since the last call, as
+    /// `(relay, sub_id, message)`.
+    pub fn take_closed(&self) -> Vec<(String, String, String)> {
+        std::mem::take(&mut self.lock().closed)
+    }
+
+    /// Number of publishes still waiting for relays.
+    pub fn pending_len(&self) -> usize {
+        self.lock().pending.len()
+    }
+}
+
+/// Record `ack` for `relay` unless it already answered, waking the receipt
+/// once no relay is pending.
+fn settle(publish: &mut PendingPublish, relay: &str, ack: RelayAck) {
+    let Some(slot) = publish.relays.get_mut(relay) else {
+        return;
+    };
+    if !slot.is_pending() {
+        return;
+    }
+    *slot = ack;
+    if publish.relays.values().all(|ack| !ack.is_pending()) {
+        if let Some(waker) = publish.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Future resolving to the [`PublishOutcome`] of a tracked publish.
+pub struct PublishReceipt {
+    tracker: PublishTracker,
+    event_id: String,
+}
+
+impl PublishReceipt {
+    pub fn event_id(&self) -> &str {
+        &self.event_id
+    }
+
+    /// Acknowledgements received so far, without waiting.
+    pub fn snapshot(&self) -> Option<PublishOutcome> {
+        let state = self.tracker.lock();
+        let publish = state.pending.get(&self.event_id)?;
+        Some(PublishOutcome {
+            event_id: self.event_id.clone(),
+            relays: publish.relays.clone(),
+        })
+    }
+}
+
+impl Future for PublishReceipt {
+    type Output = PublishOutcome;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<PublishOutcome> {
+        let mut state = self.tracker.lock();
+        let Some(publish) = state.pending.get_mut(&self.event_id) else {
+            // Tracked again under the same id and already resolved there.
+            return Poll::Ready(PublishOutcome {
+                event_id: self.event_id.clone(),
+                relays: BTreeMap::new(),
+            });
+        };
+        if publish.relays.values().any(RelayAck::is_pending) {
+            publish.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        let publish = state
+            .pending
+            .remove(&self.event_id)
+            .expect("publish was just found");
+        Poll::Ready(PublishOutcome {
+            event_id: self.event_id.clone(),
+            relays: publish.relays,
+        })
+    }
+}
+
+impl Drop for PublishReceipt {
+    fn drop(&mut self) {
+        self.tracker.lock().pending.remove(&self.event_id);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,6 +500,19 @@ mod tests {
         assert_eq!(dedup.len(), 2);
     }
 
+    #[test]
+    fn test_dedup_bloom_from_config() {
+        let mut dedup = EventDedup::from_config(&DedupConfig {
+            mode: DedupMode::Bloom,
+            capacity: 100,
+            false_positive_rate: 0.01,
+        });
+        assert!(dedup.check_and_insert("aaa"));
+        assert!(!dedup.check_and_insert("aaa"));
+        assert_eq!(dedup.len(), 1);
+        assert!(dedup.false_positive_rate() < 0.01);
+    }
+
     #[test]
     fn test_parse_eose() {
         let msg = r#"["EOSE","sub1"]"#;
@@ -203,4 +544,91 @@ mod tests {
             other => panic!("Expected Ok, got {:?}", other),
         }
     }
+
+    #[test]
+    fn test_parse_closed() {
+        let msg = r#"["CLOSED","sub1","auth-required: sign in first"]"#;
+        match parse_relay_message(msg) {
+            RelayMessage::Closed { sub_id, message } => {
+                assert_eq!(sub_id, "sub1");
+                assert_eq!(reason_prefix(&message), Some("auth-required"));
+            }
+            other => panic!("Expected Closed, got {:?}", other),
+        }
+        assert_eq!(reason_prefix("no prefix here"), None);
+    }
+
+    fn poll_once(receipt: &mut PublishReceipt) -> Poll<PublishOutcome> {
+        let mut cx = Context::from_waker(Waker::noop());
+        Pin::new(receipt).poll(&mut cx)
+    }
+
+    #[test]
+    fn receipt_resolves_when_every_relay_answers() {
+        let tracker = PublishTracker::new();
+        let mut receipt = tracker.track("ev1", ["wss://a", "wss://b", "wss://c"]);
+        assert!(poll_once(&mut receipt).is_pending());
+
+        tracker.handle("wss://a", &parse_relay_message(r#"["OK","ev1",true,""]"#));
+        tracker.handle(
+            "wss://b",
+            &parse_relay_message(r#"["OK","ev1",false,"blocked: not on allowlist"]"#),
+        );
+        // A reply for another event changes nothing.
+        tracker.handle("wss://c", &parse_relay_message(r#"["OK","ev2",true,""]"#));
+        assert!(poll_once(&mut receipt).is_pending());
+
+        tracker.handle("wss://c", &parse_relay_message(r#"["NOTICE","slow down"]"#));
+        tracker.relay_disconnected("wss://c");
+
+        let Poll::Ready(outcome) = poll_once(&mut receipt) else {
+            panic!("receipt should resolve");
+        };
+        assert_eq!(outcome.accepted_by(), vec!["wss://a"]);
+        assert_eq!(
+            outcome.relays["wss://b"],
+            RelayAck::Rejected {
+                message: "blocked: not on allowlist".into()
+            }
+        );
+        assert_eq!(
+            outcome.relays["wss://c"],
+            RelayAck::NoReply {
+                notice: Some("slow down".into())
+            }
+        );
+        assert_eq!(tracker.pending_len(), 0);
+    }
+
+    #[test]
+    fn expire_resolves_silent_relays_and_duplicates_count_as_stored() {
+        let tracker = PublishTracker::new();
+        let mut receipt = tracker.track("ev1", ["wss://a", "wss://b"]);
+        tracker.handle(
+            "wss://a",
+            &parse_relay_message(r#"["OK","ev1",false,"duplicate: already have it"]"#),
+        );
+        tracker.handle(
+            "wss://b",
+            &parse_relay_message(r#"["CLOSED","sub1","error: shutting down"]"#),
+        );
+        tracker.expire("ev1");
+
+        let Poll::Ready(outcome) = poll_once(&mut receipt) else {
+            panic!("expired receipt should resolve");
+        };
+        assert!(outcome.is_accepted());
+        assert_eq!(
+            outcome.relays["wss://b"],
+            RelayAck::NoReply { notice: None }
+        );
+        assert_eq!(
+            tracker.take_closed(),
+            vec![(
+                "wss://b".to_string(),
+                "sub1".to_string(),
+                "error: shutting down".to_string()
+            )]
+        );
+    }
 }
@@ -112,6 +112,15 @@ impl SourcePreference {
     }
 }
 
+/// How often a memory has been injected into a prompt, and when last.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Times the memory was recalled into a prompt.
+    pub access_count: u64,
+    /// Unix time of the last recall, if any.
+    pub last_used_at: Option<u64>,
+}
+
 /// A ranked search result combining a memory with its scoring breakdown.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SearchResult {
@@ -123,8 +132,26 @@ pub struct SearchResult {
     pub source_trust: f64,
     /// Model tier (1 = best, 4 = lowest).
     pub model_tier: u8,
-    /// Final effective score: relevance * source_trust * tier_weight.
+    /// Weight of `model_tier` in the score (tier 1 = 1.0 … tier 4 = 0.4).
+    #[serde(default)]
+    pub tier_weight: f64,
+    /// Recency within the ranked set (1.0 = newest, 0.0 = oldest). Only
+    /// breaks ties between equal effective scores.
+    #[serde(default)]
+    pub recency: f64,
+    /// Recorded use of the memory.
+    #[serde(default)]
+    pub usage: MemoryUsage,
+    /// Usefulness from `usage` (0.0 = never used, 1.0 = used often and
+    /// recently); scaled by `MemoryConfig::usage_weight` in the score.
+    #[serde(default)]
+    pub usage_score: f64,
+    /// Final effective score: relevance * source_trust * tier_weight *
+    /// (1 + usage_weight * usage_score).
     pub effective_score: f64,
+    /// Human-readable account of how the score and rank came about.
+    #[serde(default)]
+    pub explanation: String,
 }
 
 /// Agent profile metadata published as kind 0.
@@ -145,4 +172,124 @@ pub struct AgentProfile {
     /// Npub (hex pubkey) of the human operator.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub operator: Option<String>,
+    /// Tier (1 = best, 4 = lowest) of `model` in the publisher's ranking
+    /// config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_tier: Option<u8>,
+    /// Action prefixes the agent handles (e.g. `"memory."`).
+    #[serde(default)]
+    pub actions: Vec<String>,
+}
+
+/// An agent discovered on relays, as listed in the agent directory.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentDirectoryEntry {
+    /// Hex pubkey of the agent.
+    pub pubkey: String,
+    #[serde(flatten)]
+    pub profile: AgentProfile,
+    /// `created_at` of the profile event.
+    pub updated_at: u64,
+}
+
+/// Public health summary of an agent, for status pages.
+///
+/// Only counts and component states: no message content, group ids,
+/// pubkeys or error text.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AgentStatus {
+    /// Snowclaw version.
+    pub version: String,
+    /// Seconds since the agent process started.
+    pub uptime_seconds: u64,
+    /// Groups the agent is configured to serve.
+    pub groups_served: u64,
+    /// Messages in the agent's message index.
+    pub messages_indexed: u64,
+    /// Indexed messages from the last 24 hours.
+    pub messages_24h: u64,
+    /// Connected relays.
+    pub relays_connected: u64,
+    /// Configured relays.
+    pub relays_total: u64,
+    /// Daemon components by name, each `ok`, `error` or `starting`.
+    #[serde(default)]
+    pub components: std::collections::BTreeMap<String, String>,
+}
+
+impl AgentStatus {
+    /// Every component reports `ok`.
+    pub fn healthy(&self) -> bool {
+        self.components.values().all(|status| status == "ok")
+    }
+}
+
+/// An agent status read from a relay.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AgentStatusEntry {
+    /// Hex pubkey of the agent.
+    pub pubkey: String,
+    #[serde(flatten)]
+    pub status: AgentStatus,
+    /// Whether every component reported `ok`.
+    pub healthy: bool,
+    /// `created_at` of the status event.
+    pub updated_at: u64,
+}
+
+/// Why a source's reputation changed.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReputationReason {
+    /// A conflict resolved and the source's memory was not the one kept.
+    ConflictLost,
+    /// A conflict resolved in favor of the source's memory.
+    ConflictWon,
+    /// The owner stored a different answer on a topic the source covers.
+    OwnerCorrection,
+}
+
+impl ReputationReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ConflictLost => "conflict_lost",
+            Self::ConflictWon => "conflict_won",
+            Self::OwnerCorrection => "owner_correction",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "conflict_lost" => Some(Self::ConflictLost),
+            "conflict_won" => Some(Self::ConflictWon),
+            "owner_correction" => Some(Self::OwnerCorrection),
+            _ => None,
+        }
+    }
+}
+
+/// Learned reputation of a memory source. `factor` scales the trust the
+/// ranking config gives the source (1.0 = unchanged).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SourceReputation {
+    /// Hex pubkey of the source.
+    pub source: String,
+    pub factor: f64,
+    pub conflicts_lost: u32,
+    pub conflicts_won: u32,
+    pub corrections: u32,
+    /// Unix time of the last change.
+    pub updated_at: u64,
+}
+
+/// One reputation adjustment, kept for the owner's report.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReputationChange {
+    pub source: String,
+    pub reason: ReputationReason,
+    /// Memory topic the outcome was about.
+    pub topic: String,
+    pub factor_before: f64,
+    pub factor_after: f64,
+    pub at: u64,
 }
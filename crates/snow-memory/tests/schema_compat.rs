@@ -0,0 +1,88 @@
+//! Compatibility corpus for the memory event schema.
+//!
+//! `fixtures/memory-events.json` holds events as published by every schema
+//! version. They must keep parsing the same way, and re-encoding them must
+//! produce a current-schema event with the same memory.
+
+use snow_memory::event::{
+    memory_from_event, memory_to_event, schema_version, validate_memory_event, MemoryEvent,
+    SCHEMA_VERSION,
+};
+use snow_memory::{EventSchemaError, Memory};
+
+#[derive(serde::Deserialize)]
+struct Corpus {
+    events: Vec<Case>,
+}
+
+#[derive(serde::Deserialize)]
+struct Case {
+    name: String,
+    event: serde_json::Value,
+    #[serde(default)]
+    expect: Option<Memory>,
+    /// Tag the event is rejected on.
+    #[serde(default)]
+    error: Option<String>,
+}
+
+fn memory_event(json: &serde_json::Value) -> MemoryEvent {
+    let tags = json["tags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tag| {
+            (
+                tag[0].as_str().unwrap().to_string(),
+                tag[1].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    MemoryEvent {
+        id: json["id"].as_str().unwrap().to_string(),
+        kind: json["kind"].as_u64().unwrap(),
+        pubkey: json["pubkey"].as_str().unwrap().to_string(),
+        created_at: json["created_at"].as_u64().unwrap(),
+        tags,
+        content: json["content"].as_str().unwrap().to_string(),
+    }
+}
+
+fn rejected_tag(err: &EventSchemaError) -> Option<&str> {
+    match err {
+        EventSchemaError::MissingTag(tag) | EventSchemaError::InvalidTag { tag, .. } => Some(tag),
+        _ => None,
+    }
+}
+
+#[test]
+fn corpus_events_parse_across_schema_versions() {
+    let corpus: Corpus = serde_json::from_str(include_str!("fixtures/memory-events.json")).unwrap();
+    assert!(!corpus.events.is_empty());
+
+    for case in corpus.events {
+        let event = memory_event(&case.event);
+        let parsed = memory_from_event(&event);
+
+        if let Some(tag) = case.error {
+            let err = parsed.expect_err(&case.name);
+            assert_eq!(rejected_tag(&err), Some(tag.as_str()), "{}", case.name);
+            let issues = validate_memory_event(&event);
+            assert!(issues.iter().any(|i| i.field == tag), "{}", case.name);
+            continue;
+        }
+
+        let memory = parsed.unwrap_or_else(|e| panic!("{}: {e}", case.name));
+        assert_eq!(Some(&memory), case.expect.as_ref(), "{}", case.name);
+        assert!(validate_memory_event(&event).is_empty(), "{}", case.name);
+
+        let reencoded = memory_to_event(&memory);
+        assert_eq!(schema_version(&reencoded), Ok(SCHEMA_VERSION));
+        assert_eq!(
+            memory_from_event(&reencoded).unwrap(),
+            memory,
+            "{}",
+            case.name
+        );
+    }
+}
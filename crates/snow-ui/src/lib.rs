@@ -1,14 +1,24 @@
 //! WASM bindings for Snow UI.
 //!
 //! Exposes snow-memory functions to JavaScript via wasm-bindgen.
-//! The UI calls these to rank memories, detect conflicts, and parse
-//! Nostr events — using the exact same logic as the agent runtime.
+//! The UI calls these to rank memories, detect conflicts, parse and
+//! build Nostr events — using the exact same logic as the agent runtime.
+//! Built events are unsigned; the browser signs them via NIP-07. The
+//! `prepare_*` functions also return the event id and its NIP-01 preimage,
+//! and [`finalize_event_js`] assembles the signed event JSON from a
+//! signature, so JavaScript never serializes events itself.
+//! Relay messages are parsed and deduplicated here too ([`DedupHandle`]),
+//! so the web client drops the same repeats as the agent. Agent status
+//! events ([`parse_status_event`]) let a public status page show agent
+//! health straight from relays.
 
 use wasm_bindgen::prelude::*;
 
-use snow_memory::event::{memory_from_event, MemoryEvent};
+use snow_memory::event::{memory_from_event, MemoryEvent, ValidationIssue};
+use snow_memory::publish::{self, UnsignedEvent};
 use snow_memory::ranking::{self, Conflict};
-use snow_memory::types::{Memory, SourcePreference};
+use snow_memory::subscribe::{parse_relay_message, EventDedup, RelayMessage};
+use snow_memory::types::{AgentDirectoryEntry, AgentProfile, Memory, SourcePreference};
 
 /// Parse a Nostr event JSON string into a Memory.
 ///
@@ -33,7 +43,10 @@ struct MemoryWithRelevance {
 /// Rank a list of memories by source trust, model tier, and relevance.
 ///
 /// Input: JSON array of {memory, relevance} objects + JSON array of source preferences.
-/// Returns: sorted SearchResult array as JsValue.
+/// Returns: sorted SearchResult array as JsValue. Each result carries its
+/// score components (`relevance`, `source_trust`, `model_tier`,
+/// `tier_weight`, `recency`) and an `explanation` string for score
+/// breakdown tooltips.
 #[wasm_bindgen]
 pub fn rank_memories(results_json: &str, prefs_json: &str) -> Result<JsValue, JsError> {
     let items: Vec<MemoryWithRelevance> = serde_json::from_str(results_json)
@@ -80,3 +93,410 @@ pub fn resolve_conflict(conflict_json: &str, prefs_json: &str) -> Result<JsValue
     let winner = ranking::resolve_conflict(&conflict, &config);
     serde_wasm_bindgen::to_value(&winner).map_err(|e| JsError::new(&e.to_string()))
 }
+
+/// Build an unsigned NIP-78 memory event.
+///
+/// Input: JSON for a Memory object + hex pubkey of the signer.
+/// Returns: `{pubkey, created_at, kind, tags, content}` ready for
+/// `window.nostr.signEvent()`.
+#[wasm_bindgen]
+pub fn build_memory_event_js(memory_json: &str, pubkey: &str) -> Result<JsValue, JsError> {
+    let memory: Memory = serde_json::from_str(memory_json)
+        .map_err(|e| JsError::new(&format!("invalid memory JSON: {e}")))?;
+    let event = publish::build_memory_event(&memory, pubkey);
+    serde_wasm_bindgen::to_value(&event).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Build an unsigned kind 0 agent profile event.
+///
+/// Input: JSON for an AgentProfile, hex pubkey of the signer, and the
+/// creation time in unix seconds (WASM has no system clock).
+/// Returns: unsigned event ready for `window.nostr.signEvent()`.
+#[wasm_bindgen]
+pub fn build_profile_event_js(
+    profile_json: &str,
+    pubkey: &str,
+    created_at: u32,
+) -> Result<JsValue, JsError> {
+    let profile: AgentProfile = serde_json::from_str(profile_json)
+        .map_err(|e| JsError::new(&format!("invalid profile JSON: {e}")))?;
+    let event = publish::build_profile_event_at(&profile, pubkey, u64::from(created_at));
+    serde_wasm_bindgen::to_value(&event).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Event in the form NIP-07 `signEvent` takes, with its precomputed id.
+#[derive(serde::Serialize)]
+struct EventToSign {
+    id: String,
+    pubkey: String,
+    created_at: u64,
+    kind: u32,
+    tags: Vec<Vec<String>>,
+    content: String,
+}
+
+/// Result of the `prepare_*` functions.
+#[derive(serde::Serialize)]
+struct PreparedEvent {
+    event: EventToSign,
+    /// NIP-01 serialization whose SHA-256 is `event.id`
+    preimage: String,
+}
+
+impl From<UnsignedEvent> for PreparedEvent {
+    fn from(event: UnsignedEvent) -> Self {
+        Self {
+            preimage: event.id_preimage(),
+            event: EventToSign {
+                id: event.compute_id(),
+                pubkey: event.pubkey,
+                created_at: event.created_at,
+                kind: event.kind,
+                tags: event.tags,
+                content: event.content,
+            },
+        }
+    }
+}
+
+/// Prepare a NIP-78 memory event for signing.
+///
+/// Input: JSON for a Memory object + hex pubkey of the signer (from
+/// `window.nostr.getPublicKey()`).
+/// Returns: `{event: {id, pubkey, created_at, kind, tags, content},
+/// preimage}`; pass `event` to `window.nostr.signEvent()` and the
+/// signature to `finalize_event_js`.
+#[wasm_bindgen]
+pub fn prepare_memory_event_js(memory_json: &str, pubkey: &str) -> Result<JsValue, JsError> {
+    let memory: Memory = serde_json::from_str(memory_json)
+        .map_err(|e| JsError::new(&format!("invalid memory JSON: {e}")))?;
+    let prepared = PreparedEvent::from(publish::build_memory_event(&memory, pubkey));
+    serde_wasm_bindgen::to_value(&prepared).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Prepare a kind 0 agent profile event for signing.
+///
+/// Input: JSON for an AgentProfile, hex pubkey of the signer, and the
+/// creation time in unix seconds.
+/// Returns: `{event, preimage}` as for `prepare_memory_event_js`.
+#[wasm_bindgen]
+pub fn prepare_profile_event_js(
+    profile_json: &str,
+    pubkey: &str,
+    created_at: u32,
+) -> Result<JsValue, JsError> {
+    let profile: AgentProfile = serde_json::from_str(profile_json)
+        .map_err(|e| JsError::new(&format!("invalid profile JSON: {e}")))?;
+    let event = publish::build_profile_event_at(&profile, pubkey, u64::from(created_at));
+    serde_wasm_bindgen::to_value(&PreparedEvent::from(event))
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Event handed back for finalizing; `id` is checked when present.
+#[derive(serde::Deserialize)]
+struct EventToFinalize {
+    #[serde(default)]
+    id: String,
+    pubkey: String,
+    created_at: u64,
+    kind: u32,
+    #[serde(default)]
+    tags: Vec<Vec<String>>,
+    #[serde(default)]
+    content: String,
+}
+
+/// Assemble the signed event from a prepared event and its signature.
+///
+/// Input: JSON for the prepared `event` (or the event returned by
+/// `window.nostr.signEvent()`, in case the extension changed a field) and
+/// the hex Schnorr signature.
+/// Returns: the signed event JSON, ready for `["EVENT", …]`. Throws if the
+/// pubkey or signature is malformed, or if the event carries an `id` that
+/// does not match its fields.
+#[wasm_bindgen]
+pub fn finalize_event_js(event_json: &str, sig: &str) -> Result<String, JsError> {
+    let input: EventToFinalize = serde_json::from_str(event_json)
+        .map_err(|e| JsError::new(&format!("invalid event JSON: {e}")))?;
+    let signed = UnsignedEvent {
+        pubkey: input.pubkey,
+        created_at: input.created_at,
+        kind: input.kind,
+        tags: input.tags,
+        content: input.content,
+    }
+    .into_signed(sig)
+    .map_err(|e| JsError::new(&e.to_string()))?;
+    if !input.id.is_empty() && !input.id.eq_ignore_ascii_case(&signed.id) {
+        let mismatch = snow_memory::SignatureError::IdMismatch {
+            expected: signed.id,
+            actual: input.id,
+        };
+        return Err(JsError::new(&mismatch.to_string()));
+    }
+    Ok(signed.to_json())
+}
+
+/// Build the agent directory from kind 0 events fetched from relays.
+///
+/// Input: JSON array of Nostr events, e.g. the results of the
+/// `build_agent_discovery_subscription` filter.
+/// Returns: one entry per agent (its newest profile), best model tier first,
+/// then by name. Events without agent metadata are skipped.
+#[wasm_bindgen]
+pub fn agent_directory(events_json: &str) -> Result<JsValue, JsError> {
+    let events: Vec<serde_json::Value> = serde_json::from_str(events_json)
+        .map_err(|e| JsError::new(&format!("invalid events JSON: {e}")))?;
+    let directory = directory_from_events(&events);
+    serde_wasm_bindgen::to_value(&directory).map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn directory_from_events(events: &[serde_json::Value]) -> Vec<AgentDirectoryEntry> {
+    let mut newest: std::collections::HashMap<String, AgentDirectoryEntry> =
+        std::collections::HashMap::new();
+    for entry in events
+        .iter()
+        .filter_map(snow_memory::event::event_json_to_agent)
+    {
+        match newest.get(&entry.pubkey) {
+            Some(known) if known.updated_at >= entry.updated_at => {}
+            _ => {
+                newest.insert(entry.pubkey.clone(), entry);
+            }
+        }
+    }
+    let mut directory: Vec<AgentDirectoryEntry> = newest.into_values().collect();
+    directory.sort_by(|a, b| {
+        let tier = |e: &AgentDirectoryEntry| e.profile.model_tier.unwrap_or(u8::MAX);
+        tier(a)
+            .cmp(&tier(b))
+            .then_with(|| a.profile.name.cmp(&b.profile.name))
+            .then_with(|| a.pubkey.cmp(&b.pubkey))
+    });
+    directory
+}
+
+/// Parse an agent status event for a public status page.
+///
+/// Input: JSON for a signed kind 30078 event with d-tag `snow:status`, as
+/// published by the agent's status feed.
+/// Returns: `{pubkey, version, uptime_seconds, groups_served,
+/// messages_indexed, messages_24h, relays_connected, relays_total,
+/// components, healthy, updated_at}`. Throws if the event is not an agent
+/// status.
+#[wasm_bindgen]
+pub fn parse_status_event(json: &str) -> Result<JsValue, JsError> {
+    let event: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| JsError::new(&format!("invalid JSON: {e}")))?;
+    let entry = snow_memory::event::event_json_to_status(&event)
+        .ok_or_else(|| JsError::new("not an agent status event"))?;
+    serde_wasm_bindgen::to_value(&entry).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Build the `["REQ", …]` message subscribing to agent status events.
+///
+/// Input: subscription id and an optional JSON array of hex pubkeys to
+/// follow (all agents when empty).
+#[wasm_bindgen]
+pub fn build_status_subscription_js(sub_id: &str, pubkeys_json: &str) -> Result<String, JsError> {
+    let pubkeys: Vec<String> = if pubkeys_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(pubkeys_json)
+            .map_err(|e| JsError::new(&format!("invalid pubkeys JSON: {e}")))?
+    };
+    let pubkeys: Vec<&str> = pubkeys.iter().map(String::as_str).collect();
+    Ok(publish::build_status_subscription(
+        sub_id,
+        (!pubkeys.is_empty()).then_some(pubkeys.as_slice()),
+    ))
+}
+
+/// Nostr event as produced by the builders or a NIP-07 signer.
+/// `id` and `pubkey` are optional so unsigned events can be checked.
+#[derive(serde::Deserialize)]
+struct EventInput {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    pubkey: String,
+    kind: u64,
+    #[serde(default)]
+    created_at: u64,
+    #[serde(default)]
+    tags: Vec<Vec<String>>,
+    #[serde(default)]
+    content: String,
+}
+
+/// Result of validate_memory_event.
+#[derive(serde::Serialize)]
+struct ValidationReport {
+    valid: bool,
+    errors: Vec<ValidationIssue>,
+}
+
+/// Validate a memory event before signing, for form feedback.
+///
+/// Input: JSON for a signed or unsigned Nostr event.
+/// Returns: `{valid, errors: [{field, message}]}` listing every problem;
+/// `field` is the tag name, or `kind` / `content`. Throws only if the
+/// input is not an event at all.
+#[wasm_bindgen]
+pub fn validate_memory_event(json: &str) -> Result<JsValue, JsError> {
+    let input: EventInput = serde_json::from_str(json)
+        .map_err(|e| JsError::new(&format!("invalid event JSON: {e}")))?;
+    let event = MemoryEvent {
+        id: input.id,
+        kind: input.kind,
+        pubkey: input.pubkey,
+        created_at: input.created_at,
+        tags: input
+            .tags
+            .into_iter()
+            .filter_map(|t| {
+                let mut t = t.into_iter();
+                Some((t.next()?, t.next()?))
+            })
+            .collect(),
+        content: input.content,
+    };
+    let errors = snow_memory::event::validate_memory_event(&event);
+    let report = ValidationReport {
+        valid: errors.is_empty(),
+        errors,
+    };
+    serde_wasm_bindgen::to_value(&report).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// A parsed relay message, tagged by `type` for JavaScript.
+#[derive(serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RelayMessageView {
+    Memory {
+        sub_id: String,
+        memory: Box<Memory>,
+    },
+    Profile {
+        sub_id: String,
+        event_json: String,
+    },
+    Other {
+        sub_id: String,
+        kind: u32,
+    },
+    Eose {
+        sub_id: String,
+    },
+    Notice {
+        message: String,
+    },
+    Ok {
+        event_id: String,
+        accepted: bool,
+        message: String,
+    },
+    Closed {
+        sub_id: String,
+        message: String,
+    },
+    Unknown {
+        raw: String,
+    },
+}
+
+impl From<RelayMessage> for RelayMessageView {
+    fn from(msg: RelayMessage) -> Self {
+        match msg {
+            RelayMessage::MemoryEvent { sub_id, memory } => Self::Memory {
+                sub_id,
+                memory: Box::new(memory),
+            },
+            RelayMessage::ProfileEvent { sub_id, event_json } => {
+                Self::Profile { sub_id, event_json }
+            }
+            RelayMessage::OtherEvent { sub_id, kind } => Self::Other { sub_id, kind },
+            RelayMessage::EndOfStoredEvents { sub_id } => Self::Eose { sub_id },
+            RelayMessage::Notice { message } => Self::Notice { message },
+            RelayMessage::Ok {
+                event_id,
+                accepted,
+                message,
+            } => Self::Ok {
+                event_id,
+                accepted,
+                message,
+            },
+            RelayMessage::Closed { sub_id, message } => Self::Closed { sub_id, message },
+            RelayMessage::Unknown(raw) => Self::Unknown { raw },
+        }
+    }
+}
+
+/// Parse a raw relay message (a WebSocket text frame).
+///
+/// Returns: an object tagged by `type`: `memory` ({sub_id, memory}),
+/// `profile` ({sub_id, event_json}), `other` ({sub_id, kind}), `eose`
+/// ({sub_id}), `notice` ({message}), `ok` ({event_id, accepted, message}),
+/// `closed` ({sub_id, message}) or `unknown` ({raw}). Never throws on bad
+/// input; unparseable frames come back as `unknown`.
+#[wasm_bindgen]
+pub fn parse_relay_message_js(msg: &str) -> Result<JsValue, JsError> {
+    let view = RelayMessageView::from(parse_relay_message(msg));
+    serde_wasm_bindgen::to_value(&view).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Seen-event filter shared with the agent runtime ([`EventDedup`]).
+///
+/// Keep one handle per subscription set and call `check_and_insert` with
+/// each event id; events it returns `false` for are repeats.
+#[wasm_bindgen]
+pub struct DedupHandle {
+    inner: EventDedup,
+}
+
+#[wasm_bindgen]
+impl DedupHandle {
+    /// Exact dedup remembering up to `max_size` ids.
+    #[wasm_bindgen(constructor)]
+    pub fn new(max_size: usize) -> DedupHandle {
+        Self {
+            inner: EventDedup::new(max_size),
+        }
+    }
+
+    /// Bloom-filter dedup with fixed memory; rarely drops a new event.
+    pub fn bloom(capacity: usize, false_positive_rate: f64) -> DedupHandle {
+        Self {
+            inner: EventDedup::bloom(capacity, false_positive_rate),
+        }
+    }
+
+    /// Build from a `DedupConfig` JSON object (`{mode, capacity,
+    /// false_positive_rate}`); missing fields take their defaults.
+    pub fn from_config(config_json: &str) -> Result<DedupHandle, JsError> {
+        let config: snow_memory::DedupConfig = serde_json::from_str(config_json)
+            .map_err(|e| JsError::new(&format!("invalid dedup config JSON: {e}")))?;
+        Ok(Self {
+            inner: EventDedup::from_config(&config),
+        })
+    }
+
+    /// Returns true if the event id has not been seen before.
+    pub fn check_and_insert(&mut self, event_id: &str) -> bool {
+        self.inner.check_and_insert(event_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Estimated chance that a new event is reported as a duplicate.
+    pub fn false_positive_rate(&self) -> f64 {
+        self.inner.false_positive_rate()
+    }
+}
@@ -14,6 +14,7 @@ use crate::runtime;
 use crate::security::SecurityPolicy;
 use crate::tools::{self, Tool, ToolSpec};
 use anyhow::Result;
+use futures_util::StreamExt;
 use std::collections::HashMap;
 use std::io::Write as IoWrite;
 use std::sync::Arc;
@@ -499,11 +500,34 @@ impl Agent {
             return results;
         }
 
-        let futs: Vec<_> = calls
-            .iter()
-            .map(|call| self.execute_tool_call(call))
-            .collect();
-        futures_util::future::join_all(futs).await
+        // Tools that are not parallel-safe act as barriers between batches.
+        let mut results = Vec::with_capacity(calls.len());
+        let mut start = 0;
+        for (idx, call) in calls.iter().enumerate() {
+            let parallel_safe = self
+                .tools
+                .iter()
+                .find(|t| t.name() == call.name)
+                .is_none_or(|t| t.is_parallel_safe());
+            if parallel_safe {
+                continue;
+            }
+            results.extend(self.execute_tools_concurrently(&calls[start..idx]).await);
+            results.push(self.execute_tool_call(call).await);
+            start = idx + 1;
+        }
+        results.extend(self.execute_tools_concurrently(&calls[start..]).await);
+        results
+    }
+
+    async fn execute_tools_concurrently(
+        &self,
+        calls: &[ParsedToolCall],
+    ) -> Vec<ToolExecutionResult> {
+        futures_util::stream::iter(calls.iter().map(|call| self.execute_tool_call(call)))
+            .buffered(self.config.max_parallel_tools.max(1))
+            .collect()
+            .await
     }
 
     fn classify_model(&self, user_message: &str) -> String {
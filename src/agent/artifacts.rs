@@ -0,0 +1,262 @@
+//! Per-conversation artifact workspaces.
+//!
+//! Each conversation gets an isolated scratch directory under
+//! `<workspace>/artifacts/<conversation>/` where tools write intermediate
+//! files instead of littering the main workspace. The directory for the
+//! active conversation is scoped to the tool loop task via [`scope`] and
+//! exposed to tools through [`current_dir`]: shell commands get the
+//! [`ARTIFACT_DIR_ENV`] variable, and the file tools resolve paths starting
+//! with `$ZEROCLAW_ARTIFACT_DIR` through [`expand_path`]. Directories
+//! untouched for longer than `agent.artifact_ttl_hours` are removed by
+//! [`ArtifactStore::collect_garbage`].
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Environment variable pointing shell commands at the conversation's artifact dir.
+pub const ARTIFACT_DIR_ENV: &str = "ZEROCLAW_ARTIFACT_DIR";
+
+/// Subdirectory of the workspace holding all artifact workspaces.
+const ARTIFACTS_DIR_NAME: &str = "artifacts";
+
+/// Marker file whose mtime records the last time a conversation used its dir.
+const TOUCH_MARKER: &str = ".last_used";
+
+/// Maximum length of a sanitized conversation directory name.
+const MAX_DIR_NAME_LEN: usize = 96;
+
+tokio::task_local! {
+    static CURRENT_ARTIFACT_DIR: Option<PathBuf>;
+}
+
+/// Run `fut` with `dir` as the active conversation artifact directory.
+pub async fn scope<F: Future>(dir: Option<PathBuf>, fut: F) -> F::Output {
+    CURRENT_ARTIFACT_DIR.scope(dir, fut).await
+}
+
+/// Artifact directory of the conversation driving the current task, if any.
+pub fn current_dir() -> Option<PathBuf> {
+    CURRENT_ARTIFACT_DIR.try_with(Clone::clone).ok().flatten()
+}
+
+/// Resolve a tool path starting with `$ZEROCLAW_ARTIFACT_DIR` (or
+/// `${ZEROCLAW_ARTIFACT_DIR}`) to the current conversation's artifact dir,
+/// relative to `workspace_dir` when inside it. Other paths are unchanged.
+pub fn expand_path(path: &str, workspace_dir: &Path) -> Result<String> {
+    expand_path_in(path, workspace_dir, current_dir().as_deref())
+}
+
+fn expand_path_in(path: &str, workspace_dir: &Path, artifact_dir: Option<&Path>) -> Result<String> {
+    let braced = format!("${{{ARTIFACT_DIR_ENV}}}");
+    let plain = format!("${ARTIFACT_DIR_ENV}");
+    let Some(rest) = path
+        .strip_prefix(&braced)
+        .or_else(|| path.strip_prefix(&plain))
+    else {
+        return Ok(path.to_string());
+    };
+    // `$ZEROCLAW_ARTIFACT_DIR_OTHER` is a different variable.
+    if !(rest.is_empty() || rest.starts_with('/')) {
+        return Ok(path.to_string());
+    }
+    let Some(dir) = artifact_dir else {
+        bail!("{ARTIFACT_DIR_ENV} is not set: this conversation has no artifact dir");
+    };
+    let dir = dir.strip_prefix(workspace_dir).unwrap_or(dir);
+    let rest = rest.trim_start_matches('/');
+    let resolved = if rest.is_empty() {
+        dir.to_path_buf()
+    } else {
+        dir.join(rest)
+    };
+    Ok(resolved.to_string_lossy().into_owned())
+}
+
+/// Manages artifact directories below `<workspace>/artifacts/`.
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn new(workspace_dir: &Path) -> Self {
+        Self {
+            root: workspace_dir.join(ARTIFACTS_DIR_NAME),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Return (creating if needed) the artifact dir for a conversation and
+    /// mark it as recently used.
+    pub fn dir_for(&self, conversation_key: &str) -> Result<PathBuf> {
+        let dir = self.root.join(sanitize_key(conversation_key));
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create artifact dir {}", dir.display()))?;
+        fs::write(dir.join(TOUCH_MARKER), b"")
+            .with_context(|| format!("Failed to touch artifact dir {}", dir.display()))?;
+        Ok(dir)
+    }
+
+    /// Remove artifact dirs not used within `ttl`. Returns the number removed.
+    pub fn collect_garbage(&self, ttl: Duration) -> Result<usize> {
+        let entries = match fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+
+        let now = SystemTime::now();
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let last_used = fs::metadata(path.join(TOUCH_MARKER))
+                .or_else(|_| fs::metadata(&path))
+                .and_then(|m| m.modified())
+                .unwrap_or(now);
+            let idle = now.duration_since(last_used).unwrap_or_default();
+            if idle > ttl {
+                match fs::remove_dir_all(&path) {
+                    Ok(()) => removed += 1,
+                    Err(e) => tracing::warn!(
+                        "Failed to remove expired artifact dir {}: {e}",
+                        path.display()
+                    ),
+                }
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// List files inside an artifact dir (relative paths, sorted), for trace records.
+pub fn list_files(dir: &Path, limit: usize) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else if let Ok(rel) = path.strip_prefix(dir) {
+                if rel != Path::new(TOUCH_MARKER) {
+                    files.push(rel.to_string_lossy().into_owned());
+                }
+            }
+        }
+    }
+    files.sort();
+    files.truncate(limit);
+    files
+}
+
+/// Map a conversation key to a safe single-component directory name.
+fn sanitize_key(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_DIR_NAME_LEN)
+        .collect();
+    if name.is_empty() {
+        name.push('_');
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_key_strips_path_separators() {
+        assert_eq!(sanitize_key("nostr_../etc_alice"), "nostr____etc_alice");
+        assert_eq!(sanitize_key(""), "_");
+        assert_eq!(sanitize_key(&"x".repeat(200)).len(), MAX_DIR_NAME_LEN);
+    }
+
+    #[test]
+    fn dir_for_creates_isolated_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::new(tmp.path());
+        let a = store.dir_for("telegram_alice").unwrap();
+        let b = store.dir_for("telegram_bob").unwrap();
+        assert_ne!(a, b);
+        assert!(a.starts_with(store.root()));
+        assert!(a.join(TOUCH_MARKER).exists());
+    }
+
+    #[test]
+    fn collect_garbage_removes_only_expired_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::new(tmp.path());
+        let dir = store.dir_for("conv").unwrap();
+        fs::write(dir.join("out.txt"), "data").unwrap();
+
+        assert_eq!(store.collect_garbage(Duration::from_secs(3600)).unwrap(), 0);
+        assert!(dir.exists());
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(store.collect_garbage(Duration::from_millis(1)).unwrap(), 1);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn list_files_skips_marker() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::new(tmp.path());
+        let dir = store.dir_for("conv").unwrap();
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("b.txt"), "").unwrap();
+        fs::write(dir.join("sub/a.txt"), "").unwrap();
+
+        let files = list_files(&dir, 10);
+        assert_eq!(files, vec!["b.txt".to_string(), "sub/a.txt".to_string()]);
+    }
+
+    #[test]
+    fn expand_path_maps_the_variable_into_the_artifact_dir() {
+        let workspace = Path::new("/ws");
+        let dir = Path::new("/ws/artifacts/conv");
+        let expand = |path| expand_path_in(path, workspace, Some(dir)).unwrap();
+        assert_eq!(
+            expand("$ZEROCLAW_ARTIFACT_DIR/out.txt"),
+            "artifacts/conv/out.txt"
+        );
+        assert_eq!(
+            expand("${ZEROCLAW_ARTIFACT_DIR}/a/b.md"),
+            "artifacts/conv/a/b.md"
+        );
+        assert_eq!(expand("$ZEROCLAW_ARTIFACT_DIR"), "artifacts/conv");
+        assert_eq!(expand("notes/todo.md"), "notes/todo.md");
+        assert_eq!(
+            expand("$ZEROCLAW_ARTIFACT_DIRX/a"),
+            "$ZEROCLAW_ARTIFACT_DIRX/a"
+        );
+        assert!(expand_path_in("$ZEROCLAW_ARTIFACT_DIR/a", workspace, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn scope_exposes_current_dir() {
+        assert!(current_dir().is_none());
+        let dir = PathBuf::from("/tmp/artifacts/conv");
+        let seen = scope(Some(dir.clone()), async { current_dir() }).await;
+        assert_eq!(seen, Some(dir));
+    }
+}
@@ -0,0 +1,308 @@
+//! Latency budget for channel replies (`[channels_config.latency]`).
+//!
+//! A reply passes through four stages: context assembly, LLM calls, tool
+//! calls and publishing. [`run_stage_with`] cuts a stage off at its timeout
+//! and records how long it took. Channel message handlers run the tool loop
+//! under [`scope`], so the loop's LLM and tool calls pick the budget up with
+//! [`run_stage`] without threading it through every call.
+//!
+//! Stage latency is kept process-wide and reported as the `reply_latency`
+//! health component (count, timeouts, degraded replies, average, p95 and
+//! max per stage). A slow recall also pauses memory context for
+//! `recall_cooldown_secs` ([`recall_paused`]), so the following replies do
+//! not each wait out the context timeout.
+
+use crate::config::LatencyBudgetConfig;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Health component carrying the per-stage report.
+const HEALTH_COMPONENT: &str = "reply_latency";
+
+/// Samples per stage kept for the p95.
+const RECENT_SAMPLES: usize = 200;
+
+/// A step of the reply pipeline with its own timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    /// Memory recall for the prompt context.
+    Context,
+    /// One provider call.
+    Llm,
+    /// One tool call.
+    Tool,
+    /// Sending the reply on the channel.
+    Publish,
+}
+
+impl Stage {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Context => "context",
+            Self::Llm => "llm",
+            Self::Tool => "tool",
+            Self::Publish => "publish",
+        }
+    }
+
+    /// Timeout of this stage under `config`; `None` when unlimited.
+    pub fn timeout(self, config: &LatencyBudgetConfig) -> Option<Duration> {
+        let secs = match self {
+            Self::Context => config.context_secs,
+            Self::Llm => config.llm_secs,
+            Self::Tool => config.tool_secs,
+            Self::Publish => config.publish_secs,
+        };
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+}
+
+/// A stage ran past its timeout. The message avoids the words the tool
+/// loop's transient-error check retries on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StageTimeout {
+    pub stage: Stage,
+    pub limit: Duration,
+}
+
+impl fmt::Display for StageTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "exceeded the {}s {} latency budget",
+            self.limit.as_secs(),
+            self.stage.name()
+        )
+    }
+}
+
+impl std::error::Error for StageTimeout {}
+
+tokio::task_local! {
+    static CURRENT_BUDGET: LatencyBudgetConfig;
+}
+
+/// Run `fut` with `config` applied to the stages it runs via [`run_stage`].
+pub async fn scope<F: Future>(config: LatencyBudgetConfig, fut: F) -> F::Output {
+    CURRENT_BUDGET.scope(config, fut).await
+}
+
+/// Run `fut` as `stage` of the current task's budget. Without an enabled
+/// budget in [`scope`] it runs unlimited and unrecorded.
+pub async fn run_stage<F: Future>(stage: Stage, fut: F) -> Result<F::Output, StageTimeout> {
+    match CURRENT_BUDGET.try_with(Clone::clone) {
+        Ok(config) => run_stage_with(&config, stage, fut).await,
+        Err(_) => Ok(fut.await),
+    }
+}
+
+/// Run `fut` as `stage`, cut off at the stage timeout of `config` and
+/// recorded in the stage stats. A disabled budget runs it unlimited and
+/// unrecorded.
+pub async fn run_stage_with<F: Future>(
+    config: &LatencyBudgetConfig,
+    stage: Stage,
+    fut: F,
+) -> Result<F::Output, StageTimeout> {
+    if !config.enabled {
+        return Ok(fut.await);
+    }
+    let started = Instant::now();
+    let result = match stage.timeout(config) {
+        Some(limit) => tokio::time::timeout(limit, fut)
+            .await
+            .map_err(|_| StageTimeout { stage, limit }),
+        None => Ok(fut.await),
+    };
+    stats().record(stage, started.elapsed(), result.is_err());
+    report_health();
+    result
+}
+
+/// Count a reply that went out without `stage`'s contribution.
+pub fn record_degraded(stage: Stage) {
+    stats().record_degraded(stage);
+    report_health();
+}
+
+/// Whether memory recall is paused after a slow recall.
+pub fn recall_paused() -> bool {
+    stats().recall_paused(Instant::now())
+}
+
+/// Skip memory recall for `cooldown`.
+pub fn pause_recall(cooldown: Duration) {
+    stats().pause_recall(Instant::now() + cooldown);
+}
+
+/// Latency of one stage since startup.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StageReport {
+    pub count: u64,
+    pub timeouts: u64,
+    /// Replies sent without this stage's contribution.
+    pub degraded: u64,
+    pub avg_ms: u64,
+    /// Over the most recent samples.
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+/// Per-stage report by stage name.
+pub fn report() -> BTreeMap<&'static str, StageReport> {
+    stats().report()
+}
+
+fn report_health() {
+    crate::health::mark_component_ok(HEALTH_COMPONENT);
+    crate::health::set_component_detail(
+        HEALTH_COMPONENT,
+        serde_json::json!({ "stages": report() }),
+    );
+}
+
+fn stats() -> &'static LatencyStats {
+    static STATS: OnceLock<LatencyStats> = OnceLock::new();
+    STATS.get_or_init(LatencyStats::default)
+}
+
+#[derive(Debug, Default)]
+struct StageStats {
+    count: u64,
+    timeouts: u64,
+    degraded: u64,
+    total_ms: u64,
+    max_ms: u64,
+    recent: VecDeque<u64>,
+}
+
+impl StageStats {
+    fn report(&self) -> StageReport {
+        let mut recent: Vec<u64> = self.recent.iter().copied().collect();
+        recent.sort_unstable();
+        let p95_ms = recent
+            .len()
+            .checked_sub(1)
+            .map_or(0, |last| recent[last * 95 / 100]);
+        StageReport {
+            count: self.count,
+            timeouts: self.timeouts,
+            degraded: self.degraded,
+            avg_ms: self.total_ms.checked_div(self.count).unwrap_or(0),
+            p95_ms,
+            max_ms: self.max_ms,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LatencyStats {
+    stages: Mutex<BTreeMap<Stage, StageStats>>,
+    recall_paused_until: Mutex<Option<Instant>>,
+}
+
+impl LatencyStats {
+    fn record(&self, stage: Stage, elapsed: Duration, timed_out: bool) {
+        let ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        let mut stages = self.stages.lock();
+        let stats = stages.entry(stage).or_default();
+        stats.count += 1;
+        stats.timeouts += u64::from(timed_out);
+        stats.total_ms = stats.total_ms.saturating_add(ms);
+        stats.max_ms = stats.max_ms.max(ms);
+        if stats.recent.len() == RECENT_SAMPLES {
+            stats.recent.pop_front();
+        }
+        stats.recent.push_back(ms);
+    }
+
+    fn record_degraded(&self, stage: Stage) {
+        self.stages.lock().entry(stage).or_default().degraded += 1;
+    }
+
+    fn recall_paused(&self, now: Instant) -> bool {
+        self.recall_paused_until
+            .lock()
+            .is_some_and(|until| now < until)
+    }
+
+    fn pause_recall(&self, until: Instant) {
+        *self.recall_paused_until.lock() = Some(until);
+    }
+
+    fn report(&self) -> BTreeMap<&'static str, StageReport> {
+        self.stages
+            .lock()
+            .iter()
+            .map(|(stage, stats)| (stage.name(), stats.report()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget() -> LatencyBudgetConfig {
+        LatencyBudgetConfig {
+            enabled: true,
+            ..LatencyBudgetConfig::default()
+        }
+    }
+
+    #[test]
+    fn reports_percentiles_timeouts_and_degraded_replies() {
+        let stats = LatencyStats::default();
+        for ms in 1..=100 {
+            stats.record(Stage::Llm, Duration::from_millis(ms), false);
+        }
+        stats.record(Stage::Context, Duration::from_secs(5), true);
+        stats.record_degraded(Stage::Context);
+
+        let report = stats.report();
+        let llm = &report["llm"];
+        assert_eq!((llm.count, llm.timeouts), (100, 0));
+        assert_eq!((llm.avg_ms, llm.p95_ms, llm.max_ms), (50, 95, 100));
+        let context = &report["context"];
+        assert_eq!((context.timeouts, context.degraded), (1, 1));
+        assert!(!report.contains_key("tool"));
+    }
+
+    #[test]
+    fn recall_pause_expires() {
+        let stats = LatencyStats::default();
+        let now = Instant::now();
+        assert!(!stats.recall_paused(now));
+        stats.pause_recall(now + Duration::from_secs(30));
+        assert!(stats.recall_paused(now));
+        assert!(!stats.recall_paused(now + Duration::from_secs(31)));
+    }
+
+    #[tokio::test]
+    async fn cuts_off_slow_stages_only_inside_an_enabled_budget() {
+        let budget = LatencyBudgetConfig {
+            llm_secs: 1,
+            ..budget()
+        };
+        // The scoped budget reaches stages run deep in the tool loop
+        let cut = scope(budget, run_stage(Stage::Llm, std::future::pending::<()>())).await;
+        let timeout = StageTimeout {
+            stage: Stage::Llm,
+            limit: Duration::from_secs(1),
+        };
+        assert_eq!(cut, Err(timeout));
+        assert_eq!(timeout.to_string(), "exceeded the 1s llm latency budget");
+
+        assert_eq!(run_stage(Stage::Llm, async { 7 }).await, Ok(7));
+        let disabled = LatencyBudgetConfig::default();
+        assert_eq!(
+            run_stage_with(&disabled, Stage::Tool, async { 7 }).await,
+            Ok(7)
+        );
+    }
+}
@@ -1,6 +1,8 @@
+use crate::agent::latency::{self, Stage};
 use crate::approval::{ApprovalManager, ApprovalRequest, ApprovalResponse};
-use crate::config::schema::{CostEnforcementMode, ModelPricing};
+use crate::config::schema::CostEnforcementMode;
 use crate::config::{Config, ProgressMode};
+use crate::cost::pricing::Pricing;
 use crate::cost::{BudgetCheck, CostTracker, UsagePeriod};
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::multimodal;
@@ -22,7 +24,7 @@ use rustyline::hint::Hinter;
 use rustyline::validate::Validator;
 use rustyline::{CompletionType, Config as RlConfig, Context, Editor, Helper};
 use std::borrow::Cow;
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeSet, HashSet};
 use std::fmt::Write;
 use std::future::Future;
 use std::io::Write as _;
@@ -359,6 +361,9 @@ pub(crate) fn scrub_credentials(input: &str) -> String {
 /// used when callers omit the parameter.
 const DEFAULT_MAX_HISTORY_MESSAGES: usize = 50;
 
+/// Maximum artifact file names recorded per tool result in the runtime trace.
+const TRACE_ARTIFACT_LIST_LIMIT: usize = 50;
+
 /// Minimum interval between progress sends to avoid flooding the draft channel.
 pub(crate) const PROGRESS_MIN_INTERVAL_MS: u64 = 500;
 
@@ -417,6 +422,7 @@ tokio::task_local! {
     static SAFETY_HEARTBEAT_CONFIG: Option<SafetyHeartbeatConfig>;
     static TOOL_LOOP_PROGRESS_MODE: ProgressMode;
     static TOOL_LOOP_COST_ENFORCEMENT_CONTEXT: Option<CostEnforcementContext>;
+    static TOOL_LOOP_MAX_PARALLEL_TOOLS: usize;
 }
 
 /// Configuration for periodic safety-constraint re-injection (heartbeat).
@@ -431,7 +437,7 @@ pub(crate) struct SafetyHeartbeatConfig {
 #[derive(Clone)]
 pub(crate) struct CostEnforcementContext {
     tracker: Arc<CostTracker>,
-    prices: HashMap<String, ModelPricing>,
+    pricing: Arc<Pricing>,
     mode: CostEnforcementMode,
     route_down_model: Option<String>,
     reserve_percent: u8,
@@ -459,7 +465,7 @@ pub(crate) fn create_cost_enforcement_context(
         .filter(|value| !value.is_empty());
     Some(CostEnforcementContext {
         tracker,
-        prices: cost_config.prices.clone(),
+        pricing: Arc::new(Pricing::from_config(cost_config)),
         mode: cost_config.enforcement.mode,
         route_down_model,
         reserve_percent: cost_config.enforcement.reserve_percent.min(100),
@@ -478,6 +484,14 @@ where
         .await
 }
 
+/// Run `future` with at most `limit` tool calls of one turn executing at once.
+pub(crate) async fn scope_max_parallel_tools<F>(limit: usize, future: F) -> F::Output
+where
+    F: Future,
+{
+    TOOL_LOOP_MAX_PARALLEL_TOOLS.scope(limit, future).await
+}
+
 fn should_inject_safety_heartbeat(counter: usize, interval: usize) -> bool {
     interval > 0 && counter > 0 && counter % interval == 0
 }
@@ -517,33 +531,6 @@ fn estimate_prompt_tokens(
     char_estimate.saturating_add(framing_overhead)
 }
 
-fn lookup_model_pricing(
-    prices: &HashMap<String, ModelPricing>,
-    provider: &str,
-    model: &str,
-) -> (f64, f64) {
-    let full_name = format!("{provider}/{model}");
-    if let Some(pricing) = prices.get(&full_name) {
-        return (pricing.input, pricing.output);
-    }
-    if let Some(pricing) = prices.get(model) {
-        return (pricing.input, pricing.output);
-    }
-    for (key, pricing) in prices {
-        let key_model = key.split('/').next_back().unwrap_or(key);
-        if model.starts_with(key_model) || key_model.starts_with(model) {
-            return (pricing.input, pricing.output);
-        }
-        let normalized_model = model.replace('-', ".");
-        let normalized_key = key_model.replace('-', ".");
-        if normalized_model.contains(&normalized_key) || normalized_key.contains(&normalized_model)
-        {
-            return (pricing.input, pricing.output);
-        }
-    }
-    (3.0, 15.0)
-}
-
 fn estimate_request_cost_usd(
     context: &CostEnforcementContext,
     provider: &str,
@@ -557,9 +544,9 @@ fn estimate_request_cost_usd(
     let input_tokens = ((input_tokens as f64) * reserve_multiplier).ceil() as u64;
     let output_tokens = ((output_tokens as f64) * reserve_multiplier).ceil() as u64;
 
-    let (input_price, output_price) = lookup_model_pricing(&context.prices, provider, model);
-    let input_cost = (input_tokens as f64 / 1_000_000.0) * input_price.max(0.0);
-    let output_cost = (output_tokens as f64 / 1_000_000.0) * output_price.max(0.0);
+    let rates = context.pricing.rates(provider, model);
+    let input_cost = (input_tokens as f64 / 1_000_000.0) * rates.input.max(0.0);
+    let output_cost = (output_tokens as f64 / 1_000_000.0) * rates.output.max(0.0);
     input_cost + output_cost
 }
 
@@ -1276,6 +1263,7 @@ pub async fn run_tool_call_loop(
         .ok()
         .flatten();
     let mut progress_tracker = ProgressTracker::default();
+    let artifact_dir = crate::agent::artifacts::current_dir();
     let mut active_model = model.to_string();
     let canary_guard = CanaryGuard::new(
         TOOL_LOOP_CANARY_TOKENS_ENABLED
@@ -1567,13 +1555,16 @@ pub async fn run_tool_call_loop(
             hooks.fire_llm_input(history, active_model.as_str()).await;
         }
 
-        let chat_future = provider.chat(
-            ChatRequest {
-                messages: &request_messages,
-                tools: request_tools,
-            },
-            active_model.as_str(),
-            temperature,
+        let chat_future = latency::run_stage(
+            Stage::Llm,
+            provider.chat(
+                ChatRequest {
+                    messages: &request_messages,
+                    tools: request_tools,
+                },
+                active_model.as_str(),
+                temperature,
+            ),
         );
 
         let chat_result = if let Some(token) = cancellation_token.as_ref() {
@@ -1583,7 +1574,8 @@ pub async fn run_tool_call_loop(
             }
         } else {
             chat_future.await
-        };
+        }
+        .unwrap_or_else(|timeout| Err(timeout.into()));
 
         let (
             response_text,
@@ -1655,13 +1647,16 @@ pub async fn run_tool_call_loop(
                         MAX_TOKENS_CONTINUATION_PROMPT.to_string(),
                     ));
 
-                    let continuation_future = provider.chat(
-                        ChatRequest {
-                            messages: &continuation_messages,
-                            tools: request_tools,
-                        },
-                        active_model.as_str(),
-                        temperature,
+                    let continuation_future = latency::run_stage(
+                        Stage::Llm,
+                        provider.chat(
+                            ChatRequest {
+                                messages: &continuation_messages,
+                                tools: request_tools,
+                            },
+                            active_model.as_str(),
+                            temperature,
+                        ),
                     );
                     let continuation_result = if let Some(token) = cancellation_token.as_ref() {
                         tokio::select! {
@@ -1670,7 +1665,8 @@ pub async fn run_tool_call_loop(
                         }
                     } else {
                         continuation_future.await
-                    };
+                    }
+                    .unwrap_or_else(|timeout| Err(timeout.into()));
 
                     let continuation_resp = match continuation_result {
                         Ok(response) => response,
@@ -2389,6 +2385,7 @@ Root error: {root}"
                     "iteration": iteration + 1,
                     "tool": tool_name.clone(),
                     "arguments": scrub_credentials(&tool_args.to_string()),
+                    "artifact_dir": artifact_dir.as_ref().map(|d| d.display().to_string()),
                 }),
             );
 
@@ -2414,11 +2411,15 @@ Root error: {root}"
         }
 
         let executed_outcomes = if allow_parallel_execution && executable_calls.len() > 1 {
+            let max_parallel = TOOL_LOOP_MAX_PARALLEL_TOOLS
+                .try_with(|limit| *limit)
+                .unwrap_or_else(|_| crate::config::schema::default_agent_max_parallel_tools());
             execute_tools_parallel(
                 &executable_calls,
                 tools_registry,
                 observer,
                 cancellation_token.as_ref(),
+                max_parallel,
             )
             .await?
         } else {
@@ -2437,6 +2438,7 @@ Root error: {root}"
             .zip(executed_outcomes.into_iter())
             .zip(progress_indices.iter())
         {
+            crate::cost::attribution::record_tool_latency(&call.name, outcome.duration);
             runtime_trace::record_event(
                 "tool_call_result",
                 Some(channel_name),
@@ -2450,6 +2452,9 @@ Root error: {root}"
                     "tool": call.name.clone(),
                     "duration_ms": outcome.duration.as_millis(),
                     "output": scrub_credentials(&outcome.output),
+                    "artifacts": artifact_dir
+                        .as_deref()
+                        .map(|d| crate::agent::artifacts::list_files(d, TRACE_ARTIFACT_LIST_LIMIT)),
                 }),
             );
 
@@ -3117,29 +3122,32 @@ pub async fn run(
         };
         let response = scope_cost_enforcement_context(
             cost_enforcement_context.clone(),
-            SAFETY_HEARTBEAT_CONFIG.scope(
-                hb_cfg,
-                LOOP_DETECTION_CONFIG.scope(
-                    ld_cfg,
-                    TOOL_LOOP_CANARY_TOKENS_ENABLED.scope(
-                        config.security.canary_tokens,
-                        run_tool_call_loop(
-                            provider.as_ref(),
-                            &mut history,
-                            &tools_registry,
-                            observer.as_ref(),
-                            provider_name,
-                            &model_name,
-                            temperature,
-                            false,
-                            approval_manager.as_ref(),
-                            channel_name,
-                            &config.multimodal,
-                            config.agent.max_tool_iterations,
-                            None,
-                            None,
-                            effective_hooks,
-                            &[],
+            scope_max_parallel_tools(
+                config.agent.max_parallel_tools,
+                SAFETY_HEARTBEAT_CONFIG.scope(
+                    hb_cfg,
+                    LOOP_DETECTION_CONFIG.scope(
+                        ld_cfg,
+                        TOOL_LOOP_CANARY_TOKENS_ENABLED.scope(
+                            config.security.canary_tokens,
+                            run_tool_call_loop(
+                                provider.as_ref(),
+                                &mut history,
+                                &tools_registry,
+                                observer.as_ref(),
+                                provider_name,
+                                &model_name,
+                                temperature,
+                                false,
+                                approval_manager.as_ref(),
+                                channel_name,
+                                &config.multimodal,
+                                config.agent.max_tool_iterations,
+                                None,
+                                None,
+                                effective_hooks,
+                                &[],
+                            ),
                         ),
                     ),
                 ),
@@ -3346,29 +3354,32 @@ pub async fn run(
             };
             let response = match scope_cost_enforcement_context(
                 cost_enforcement_context.clone(),
-                SAFETY_HEARTBEAT_CONFIG.scope(
-                    hb_cfg,
-                    LOOP_DETECTION_CONFIG.scope(
-                        ld_cfg,
-                        TOOL_LOOP_CANARY_TOKENS_ENABLED.scope(
-                            config.security.canary_tokens,
-                            run_tool_call_loop(
-                                provider.as_ref(),
-                                &mut history,
-                                &tools_registry,
-                                observer.as_ref(),
-                                provider_name,
-                                &model_name,
-                                temperature,
-                                false,
-                                approval_manager.as_ref(),
-                                channel_name,
-                                &config.multimodal,
-                                config.agent.max_tool_iterations,
-                                None,
-                                None,
-                                effective_hooks,
-                                &[],
+                scope_max_parallel_tools(
+                    config.agent.max_parallel_tools,
+                    SAFETY_HEARTBEAT_CONFIG.scope(
+                        hb_cfg,
+                        LOOP_DETECTION_CONFIG.scope(
+                            ld_cfg,
+                            TOOL_LOOP_CANARY_TOKENS_ENABLED.scope(
+                                config.security.canary_tokens,
+                                run_tool_call_loop(
+                                    provider.as_ref(),
+                                    &mut history,
+                                    &tools_registry,
+                                    observer.as_ref(),
+                                    provider_name,
+                                    &model_name,
+                                    temperature,
+                                    false,
+                                    approval_manager.as_ref(),
+                                    channel_name,
+                                    &config.multimodal,
+                                    config.agent.max_tool_iterations,
+                                    None,
+                                    None,
+                                    effective_hooks,
+                                    &[],
+                                ),
                             ),
                         ),
                     ),
@@ -3716,19 +3727,22 @@ pub async fn process_message_with_session(
     };
     let response = scope_cost_enforcement_context(
         cost_enforcement_context,
-        SAFETY_HEARTBEAT_CONFIG.scope(
-            hb_cfg,
-            agent_turn(
-                provider.as_ref(),
-                &mut history,
-                &tools_registry,
-                observer.as_ref(),
-                provider_name,
-                &model_name,
-                config.default_temperature,
-                true,
-                &config.multimodal,
-                config.agent.max_tool_iterations,
+        scope_max_parallel_tools(
+            config.agent.max_parallel_tools,
+            SAFETY_HEARTBEAT_CONFIG.scope(
+                hb_cfg,
+                agent_turn(
+                    provider.as_ref(),
+                    &mut history,
+                    &tools_registry,
+                    observer.as_ref(),
+                    provider_name,
+                    &model_name,
+                    config.default_temperature,
+                    true,
+                    &config.multimodal,
+                    config.agent.max_tool_iterations,
+                ),
             ),
         ),
     )
@@ -1,9 +1,14 @@
 use super::parsing::ParsedToolCall;
 use super::{scrub_credentials, ToolLoopCancelled};
+use crate::agent::latency::{self, Stage};
 use crate::approval::ApprovalManager;
 use crate::observability::{Observer, ObserverEvent};
-use crate::tools::Tool;
+use crate::security::execution_policy::{self, ExecutionPolicy};
+use crate::tools::{Tool, ToolResult};
 use anyhow::Result;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use std::future::Future;
+use std::ops::Range;
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
 use tokio_util::sync::CancellationToken;
@@ -23,6 +28,26 @@ async fn execute_one_tool(
     });
     let start = Instant::now();
 
+    let policy = execution_policy::current();
+    if let Some(policy) = policy.as_deref() {
+        if let Err(reason) = policy.check(call_name, &call_arguments) {
+            policy.record_violation(call_name, &reason);
+            let reason = format!("Blocked by execution policy `{}`: {reason}", policy.name());
+            let duration = start.elapsed();
+            observer.record_event(&ObserverEvent::ToolCall {
+                tool: call_name.to_string(),
+                duration,
+                success: false,
+            });
+            return Ok(ToolExecutionOutcome {
+                output: reason.clone(),
+                success: false,
+                error_reason: Some(scrub_credentials(&reason)),
+                duration,
+            });
+        }
+    }
+
     let Some(tool) = find_tool(tools_registry, call_name) else {
         let reason = format!("Unknown tool: {call_name}");
         let duration = start.elapsed();
@@ -43,7 +68,11 @@ async fn execute_one_tool(
     let mut first_error: Option<String> = None;
 
     loop {
-        let tool_future = tool.execute(call_arguments.clone());
+        let tool_future = with_latency_budget(with_policy_limit(
+            tool.execute(call_arguments.clone()),
+            call_name,
+            policy.as_deref(),
+        ));
         let tool_result = if let Some(token) = cancellation_token {
             tokio::select! {
                 () = token.cancelled() => return Err(ToolLoopCancelled.into()),
@@ -120,6 +149,39 @@ async fn execute_one_tool(
     }
 }
 
+/// Cut `tool_future` off at the execution policy's timeout. The error avoids
+/// the words the transient-error check retries on.
+async fn with_policy_limit(
+    tool_future: impl Future<Output = Result<ToolResult>>,
+    call_name: &str,
+    policy: Option<&ExecutionPolicy>,
+) -> Result<ToolResult> {
+    let Some((policy, limit)) = policy.and_then(|p| Some((p, p.timeout()?))) else {
+        return tool_future.await;
+    };
+    match tokio::time::timeout(limit, tool_future).await {
+        Ok(result) => result,
+        Err(_) => {
+            let reason = format!(
+                "exceeded the {}s limit of execution policy `{}`",
+                limit.as_secs(),
+                policy.name()
+            );
+            policy.record_violation(call_name, &reason);
+            Err(anyhow::anyhow!(reason))
+        }
+    }
+}
+
+/// Cut `tool_future` off at the tool stage of the reply latency budget.
+async fn with_latency_budget(
+    tool_future: impl Future<Output = Result<ToolResult>>,
+) -> Result<ToolResult> {
+    latency::run_stage(Stage::Tool, tool_future)
+        .await
+        .unwrap_or_else(|timeout| Err(timeout.into()))
+}
+
 pub(super) struct ToolExecutionOutcome {
     pub(super) output: String,
     pub(super) success: bool,
@@ -361,6 +423,198 @@ mod tests {
         assert!(outcome.output.contains("root error"));
         assert_eq!(invocations.load(Ordering::SeqCst), 3);
     }
+
+    /// Records how many calls overlap and the order in which calls start.
+    struct GaugeTool {
+        name: &'static str,
+        parallel_safe: bool,
+        running: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+        started: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Tool for GaugeTool {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn description(&self) -> &str {
+            "concurrency gauge for parallel execution tests"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        fn is_parallel_safe(&self) -> bool {
+            self.parallel_safe
+        }
+
+        async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+            let label = args["label"].as_str().unwrap_or_default().to_string();
+            self.started
+                .lock()
+                .expect("started lock")
+                .push(label.clone());
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            let sleep_ms = args["sleep_ms"].as_u64().unwrap_or(20);
+            tokio::time::sleep(Duration::from_millis(sleep_ms)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(ToolResult {
+                success: true,
+                output: label,
+                error: None,
+            })
+        }
+    }
+
+    struct Gauges {
+        registry: Vec<Box<dyn Tool>>,
+        peak: Arc<AtomicUsize>,
+        started: Arc<Mutex<Vec<String>>>,
+    }
+
+    fn gauges() -> Gauges {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let tool = |name, parallel_safe| -> Box<dyn Tool> {
+            Box::new(GaugeTool {
+                name,
+                parallel_safe,
+                running: Arc::clone(&running),
+                peak: Arc::clone(&peak),
+                started: Arc::clone(&started),
+            })
+        };
+        Gauges {
+            registry: vec![tool("read", true), tool("write", false)],
+            peak,
+            started,
+        }
+    }
+
+    fn call(name: &str, label: &str) -> ParsedToolCall {
+        ParsedToolCall {
+            name: name.to_string(),
+            arguments: serde_json::json!({ "label": label }),
+            tool_call_id: None,
+        }
+    }
+
+    #[test]
+    fn dependency_segments_isolate_stateful_tools() {
+        let registry = gauges().registry;
+        let calls = vec![
+            call("read", "a"),
+            call("unknown", "b"),
+            call("write", "c"),
+            call("write", "d"),
+            call("read", "e"),
+        ];
+        assert_eq!(
+            dependency_segments(&calls, &registry),
+            vec![0..2, 2..3, 3..4, 4..5]
+        );
+        assert!(dependency_segments(&[], &registry).is_empty());
+    }
+
+    #[tokio::test]
+    async fn execute_tools_parallel_respects_the_limit() {
+        let Gauges { registry, peak, .. } = gauges();
+        let calls: Vec<_> = (0..6).map(|i| call("read", &i.to_string())).collect();
+
+        let outcomes = execute_tools_parallel(&calls, &registry, &NoopObserver, None, 2)
+            .await
+            .expect("parallel execution");
+
+        let outputs: Vec<_> = outcomes.iter().map(|o| o.output.as_str()).collect();
+        assert_eq!(outputs, ["0", "1", "2", "3", "4", "5"]);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn execute_tools_parallel_orders_stateful_tools() {
+        let Gauges {
+            registry, started, ..
+        } = gauges();
+        let calls = vec![
+            call("read", "a"),
+            call("read", "b"),
+            call("write", "c"),
+            call("read", "d"),
+        ];
+
+        let outcomes = execute_tools_parallel(&calls, &registry, &NoopObserver, None, 4)
+            .await
+            .expect("parallel execution");
+
+        assert_eq!(outcomes.len(), 4);
+        let started = started.lock().expect("started lock").clone();
+        assert_eq!(started.len(), 4);
+        assert_eq!(
+            started[2], "c",
+            "write must start after both reads: {started:?}"
+        );
+        assert_eq!(started[3], "d");
+    }
+
+    #[tokio::test]
+    async fn execution_policy_blocks_and_limits_calls() {
+        use crate::config::ExecutionPolicyConfig;
+        use std::sync::Arc;
+
+        let registry = gauges().registry;
+        let policy = ExecutionPolicy::new(
+            "public",
+            &ExecutionPolicyConfig {
+                allowed_tools: vec!["read".into()],
+                timeout_secs: Some(1),
+                ..ExecutionPolicyConfig::default()
+            },
+            std::path::Path::new("/tmp"),
+        );
+
+        let (blocked, slow) = execution_policy::scope(Some(Arc::new(policy)), async {
+            let blocked = execute_one_tool(
+                "write",
+                serde_json::json!({"label": "w"}),
+                &registry,
+                &NoopObserver,
+                None,
+            )
+            .await
+            .unwrap();
+            let slow = execute_one_tool(
+                "read",
+                serde_json::json!({"label": "r", "sleep_ms": 5_000}),
+                &registry,
+                &NoopObserver,
+                None,
+            )
+            .await
+            .unwrap();
+            (blocked, slow)
+        })
+        .await;
+
+        assert!(!blocked.success);
+        assert!(blocked
+            .output
+            .contains("Blocked by execution policy `public`"));
+        assert!(!slow.success);
+        assert!(
+            slow.output.contains("exceeded the 1s limit"),
+            "{}",
+            slow.output
+        );
+        assert!(
+            slow.duration < Duration::from_secs(3),
+            "timeout must not be retried"
+        );
+    }
 }
 
 pub(super) fn should_execute_tools_in_parallel(
@@ -385,27 +639,63 @@ pub(super) fn should_execute_tools_in_parallel(
     true
 }
 
+/// Split `tool_calls` into segments that run one after another. Calls to
+/// parallel-safe tools share a segment with their neighbours; a call to a
+/// tool that is not parallel-safe is a segment of its own, so it starts
+/// after every earlier call finished and before any later call starts.
+fn dependency_segments(
+    tool_calls: &[ParsedToolCall],
+    tools_registry: &[Box<dyn Tool>],
+) -> Vec<Range<usize>> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    for (idx, call) in tool_calls.iter().enumerate() {
+        let exclusive =
+            find_tool(tools_registry, &call.name).is_some_and(|tool| !tool.is_parallel_safe());
+        if exclusive {
+            if start < idx {
+                segments.push(start..idx);
+            }
+            segments.push(idx..idx + 1);
+            start = idx + 1;
+        }
+    }
+    if start < tool_calls.len() {
+        segments.push(start..tool_calls.len());
+    }
+    segments
+}
+
+/// Run independent calls concurrently, at most `max_parallel` at a time,
+/// keeping the order between calls that are not parallel-safe and the rest.
+/// Outcomes come back in call order.
 pub(super) async fn execute_tools_parallel(
     tool_calls: &[ParsedToolCall],
     tools_registry: &[Box<dyn Tool>],
     observer: &dyn Observer,
     cancellation_token: Option<&CancellationToken>,
+    max_parallel: usize,
 ) -> Result<Vec<ToolExecutionOutcome>> {
-    let futures: Vec<_> = tool_calls
-        .iter()
-        .map(|call| {
-            execute_one_tool(
-                &call.name,
-                call.arguments.clone(),
-                tools_registry,
-                observer,
-                cancellation_token,
-            )
-        })
-        .collect();
+    let mut outcomes = Vec::with_capacity(tool_calls.len());
 
-    let results = futures_util::future::join_all(futures).await;
-    results.into_iter().collect()
+    for segment in dependency_segments(tool_calls, tools_registry) {
+        let segment_outcomes: Vec<ToolExecutionOutcome> =
+            stream::iter(tool_calls[segment].iter().map(|call| {
+                execute_one_tool(
+                    &call.name,
+                    call.arguments.clone(),
+                    tools_registry,
+                    observer,
+                    cancellation_token,
+                )
+            }))
+            .buffered(max_parallel.max(1))
+            .try_collect()
+            .await?;
+        outcomes.extend(segment_outcomes);
+    }
+
+    Ok(outcomes)
 }
 
 pub(super) async fn execute_tools_sequential(
@@ -1,9 +1,12 @@
 #[allow(clippy::module_inception)]
 pub mod agent;
+pub mod artifacts;
 pub mod classifier;
 pub mod dispatcher;
+pub mod latency;
 pub mod loop_;
 pub mod memory_loader;
+pub mod model_router;
 pub mod prompt;
 pub mod quota_aware;
 pub mod research;
@@ -0,0 +1,238 @@
+//! Complexity and budget based model routing.
+//!
+//! Messages that no `[query_classification]` rule claims are sorted into a
+//! complexity tier and mapped to a `[[model_routes]]` hint through
+//! `[model_router.routes]`. When the remaining `[cost]` budget is low,
+//! everything except acks is sent to the `economy` route.
+
+use crate::config::{CostConfig, ModelRouterConfig};
+use crate::cost::CostSummary;
+
+/// Complexity tier of an incoming message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Complexity {
+    /// Short acknowledgement ("thanks", "ok 👍").
+    Ack,
+    Standard,
+    /// Long or multi-part question, or one carrying code.
+    Complex,
+    /// Request that will likely need tools.
+    Tool,
+}
+
+impl Complexity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ack => "ack",
+            Self::Standard => "standard",
+            Self::Complex => "complex",
+            Self::Tool => "tool",
+        }
+    }
+}
+
+/// Routing outcome for one message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteDecision {
+    pub tier: Complexity,
+    /// `[[model_routes]]` hint to use; `None` keeps the default route.
+    pub hint: Option<String>,
+    /// The economy route replaced the tier's route because budget is low.
+    pub downgraded: bool,
+}
+
+impl RouteDecision {
+    /// Label recorded on cost records and shown in `stats` "By Route".
+    pub fn label(&self) -> String {
+        let hint = self.hint.as_deref().unwrap_or("default");
+        if self.downgraded {
+            format!("{} -> {hint} (low budget)", self.tier.name())
+        } else {
+            format!("{} -> {hint}", self.tier.name())
+        }
+    }
+}
+
+/// Sort a message into a complexity tier.
+pub fn classify_complexity(config: &ModelRouterConfig, message: &str) -> Complexity {
+    let text = message.trim();
+    let lower = text.to_lowercase();
+
+    if config
+        .tool_keywords
+        .iter()
+        .any(|kw| contains_word(&lower, &kw.to_lowercase()))
+    {
+        return Complexity::Tool;
+    }
+
+    let chars = text.chars().count();
+    let questions = text.matches('?').count();
+    if chars <= config.ack_max_chars && questions == 0 {
+        return Complexity::Ack;
+    }
+    if chars >= config.complex_min_chars || questions >= 2 || text.contains("```") {
+        return Complexity::Complex;
+    }
+    Complexity::Standard
+}
+
+/// Route a message. `budget_remaining` is the remaining budget fraction
+/// (`0.0..=1.0`) from [`remaining_budget_fraction`], when known.
+pub fn route(
+    config: &ModelRouterConfig,
+    message: &str,
+    budget_remaining: Option<f64>,
+) -> RouteDecision {
+    let tier = classify_complexity(config, message);
+    let low_budget = config.low_budget_percent > 0
+        && budget_remaining.is_some_and(|left| left * 100.0 < f64::from(config.low_budget_percent));
+
+    if low_budget && tier != Complexity::Ack {
+        if let Some(hint) = config.routes.get("economy") {
+            return RouteDecision {
+                tier,
+                hint: Some(hint.clone()),
+                downgraded: true,
+            };
+        }
+    }
+
+    RouteDecision {
+        tier,
+        hint: config.routes.get(tier.name()).cloned(),
+        downgraded: false,
+    }
+}
+
+/// Smallest remaining fraction of the daily and monthly limits, or `None`
+/// when cost tracking is off or no limit is set.
+pub fn remaining_budget_fraction(config: &CostConfig, summary: &CostSummary) -> Option<f64> {
+    if !config.enabled {
+        return None;
+    }
+    [
+        (config.daily_limit_usd, summary.daily_cost_usd),
+        (config.monthly_limit_usd, summary.monthly_cost_usd),
+    ]
+    .into_iter()
+    .filter(|(limit, _)| *limit > 0.0)
+    .map(|(limit, spent)| (1.0 - spent / limit).clamp(0.0, 1.0))
+    .reduce(f64::min)
+}
+
+/// Whole-word (or whole-phrase) match, so "run" doesn't hit "running late".
+fn contains_word(haystack: &str, needle: &str) -> bool {
+    if needle.is_empty() {
+        return false;
+    }
+    haystack.match_indices(needle).any(|(start, _)| {
+        let end = start + needle.len();
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[end..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ModelRouterConfig {
+        let mut config = ModelRouterConfig {
+            enabled: true,
+            ..ModelRouterConfig::default()
+        };
+        for (tier, hint) in [
+            ("ack", "fast"),
+            ("complex", "reasoning"),
+            ("tool", "agentic"),
+            ("economy", "cheap"),
+        ] {
+            config.routes.insert(tier.into(), hint.into());
+        }
+        config
+    }
+
+    #[test]
+    fn classifies_tiers() {
+        let config = config();
+        assert_eq!(classify_complexity(&config, "thanks!"), Complexity::Ack);
+        assert_eq!(
+            classify_complexity(&config, "what is a relay?"),
+            Complexity::Standard
+        );
+        assert_eq!(
+            classify_complexity(&config, "why does this fail? and how do I fix it?"),
+            Complexity::Complex
+        );
+        assert_eq!(
+            classify_complexity(&config, &"explain ".repeat(60)),
+            Complexity::Complex
+        );
+        assert_eq!(
+            classify_complexity(&config, "please search the docs for NIP-29"),
+            Complexity::Tool
+        );
+        assert_eq!(
+            classify_complexity(&config, "I'm running late, sorry"),
+            Complexity::Ack
+        );
+    }
+
+    #[test]
+    fn routes_by_tier_and_budget() {
+        let config = config();
+        let decision = route(
+            &config,
+            "compare these two designs? which scales better?",
+            Some(0.9),
+        );
+        assert_eq!(decision.hint.as_deref(), Some("reasoning"));
+        assert_eq!(decision.label(), "complex -> reasoning");
+
+        let decision = route(
+            &config,
+            "compare these two designs? which scales better?",
+            Some(0.1),
+        );
+        assert_eq!(decision.hint.as_deref(), Some("cheap"));
+        assert!(decision.downgraded);
+        assert_eq!(decision.label(), "complex -> cheap (low budget)");
+
+        // Acks stay on their route and unmapped tiers keep the default.
+        assert_eq!(
+            route(&config, "ok", Some(0.0)).hint.as_deref(),
+            Some("fast")
+        );
+        let decision = route(&config, "what is a relay?", None);
+        assert_eq!(decision.hint, None);
+        assert_eq!(decision.label(), "standard -> default");
+    }
+
+    #[test]
+    fn remaining_budget_uses_tightest_limit() {
+        let cost = CostConfig {
+            enabled: true,
+            daily_limit_usd: 10.0,
+            monthly_limit_usd: 100.0,
+            ..CostConfig::default()
+        };
+        let summary = CostSummary {
+            session_cost_usd: 0.0,
+            daily_cost_usd: 2.0,
+            monthly_cost_usd: 95.0,
+            total_tokens: 0,
+            request_count: 0,
+            by_model: std::collections::HashMap::new(),
+        };
+        let left = remaining_budget_fraction(&cost, &summary).unwrap();
+        assert!((left - 0.05).abs() < 1e-9);
+
+        let disabled = CostConfig {
+            enabled: false,
+            ..cost
+        };
+        assert!(remaining_budget_fraction(&disabled, &summary).is_none());
+    }
+}
@@ -0,0 +1,69 @@
+//! CLI subcommands for multi-agent deployments.
+//!
+//! Provides `snowclaw agents status [--history]`, reading the kind 31121
+//! agent state history that the Nostr channel records in `social.db`.
+
+use anyhow::Result;
+use clap::Subcommand;
+use std::collections::HashMap;
+
+use crate::channels::nostr_fleet;
+use crate::config::Config;
+use crate::memory::social;
+
+#[derive(Subcommand, Debug)]
+pub enum AgentsCommands {
+    /// Show the latest state of our own and peer agents
+    Status {
+        /// Add per-day uptime, restarts and version changes
+        #[arg(long)]
+        history: bool,
+        /// Days of history to include
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+        /// Output as JSON (fleet dashboard data)
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn handle_command(cmd: AgentsCommands, config: &Config) -> Result<()> {
+    match cmd {
+        AgentsCommands::Status {
+            history,
+            days,
+            json,
+        } => {
+            let db_path = config
+                .config_path
+                .parent()
+                .unwrap_or(std::path::Path::new("."))
+                .join("social.db");
+            let now = chrono::Utc::now().timestamp();
+
+            let view = if db_path.exists() {
+                let conn = crate::security::db_encryption::open(&db_path)?;
+                social::create_social_tables(&conn)?;
+                let latest = social::latest_agent_states(&conn)?;
+                let states = social::list_agent_states(&conn, now - i64::from(days) * 86_400)?;
+                let names: HashMap<String, String> = latest
+                    .iter()
+                    .filter_map(|s| {
+                        let npub = social::get_npub(&conn, &s.hex_pubkey).ok().flatten()?;
+                        Some((s.hex_pubkey.clone(), npub.display_name))
+                    })
+                    .collect();
+                nostr_fleet::fleet_view(&latest, &states, &names, now, days)
+            } else {
+                nostr_fleet::fleet_view(&[], &[], &HashMap::new(), now, days)
+            };
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&view)?);
+            } else {
+                print!("{}", nostr_fleet::render(&view, history));
+            }
+            Ok(())
+        }
+    }
+}
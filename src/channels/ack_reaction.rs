@@ -34,20 +34,6 @@ pub struct AckReactionSelection {
     pub source: Option<AckReactionSelectionSource>,
 }
 
-#[allow(clippy::cast_possible_truncation)]
-fn pick_uniform_index(len: usize) -> usize {
-    debug_assert!(len > 0);
-    let upper = len as u64;
-    let reject_threshold = (u64::MAX / upper) * upper;
-
-    loop {
-        let value = rand::random::<u64>();
-        if value < reject_threshold {
-            return (value % upper) as usize;
-        }
-    }
-}
-
 fn normalize_entries(entries: &[String]) -> Vec<String> {
     entries
         .iter()
@@ -239,7 +225,9 @@ fn pick_from_pool(pool: &[String], strategy: AckReactionStrategy) -> Option<Stri
         return None;
     }
     match strategy {
-        AckReactionStrategy::Random => Some(pool[pick_uniform_index(pool.len())].clone()),
+        AckReactionStrategy::Random => {
+            Some(pool[crate::determinism::pick_index("ack_reaction", pool.len())].clone())
+        }
         AckReactionStrategy::First => pool.first().cloned(),
     }
 }
@@ -269,7 +257,7 @@ fn passes_sample_rate(rate: f64) -> bool {
     if rate >= 1.0 {
         return true;
     }
-    rand::random::<f64>() < rate
+    crate::determinism::random_f64("ack_reaction_sample") < rate
 }
 
 pub fn select_ack_reaction(
@@ -649,22 +649,9 @@ fn split_message_for_discord(message: &str) -> Vec<String> {
     chunks
 }
 
-#[allow(clippy::cast_possible_truncation)]
-fn pick_uniform_index(len: usize) -> usize {
-    debug_assert!(len > 0);
-    let upper = len as u64;
-    let reject_threshold = (u64::MAX / upper) * upper;
-
-    loop {
-        let value = rand::random::<u64>();
-        if value < reject_threshold {
-            return (value % upper) as usize;
-        }
-    }
-}
-
 fn random_discord_ack_reaction() -> &'static str {
-    DISCORD_ACK_REACTIONS[pick_uniform_index(DISCORD_ACK_REACTIONS.len())]
+    let index = crate::determinism::pick_index("discord_ack", DISCORD_ACK_REACTIONS.len());
+    DISCORD_ACK_REACTIONS[index]
 }
 
 /// URL-encode a Unicode emoji for use in Discord reaction API paths.
@@ -2081,22 +2081,8 @@ impl LarkChannel {
 // WS helper functions
 // ─────────────────────────────────────────────────────────────────────────────
 
-#[allow(clippy::cast_possible_truncation)]
-fn pick_uniform_index(len: usize) -> usize {
-    debug_assert!(len > 0);
-    let upper = len as u64;
-    let reject_threshold = (u64::MAX / upper) * upper;
-
-    loop {
-        let value = rand::random::<u64>();
-        if value < reject_threshold {
-            return (value % upper) as usize;
-        }
-    }
-}
-
 fn random_from_pool(pool: &'static [&'static str]) -> &'static str {
-    pool[pick_uniform_index(pool.len())]
+    pool[crate::determinism::pick_index("lark_ack", pool.len())]
 }
 
 fn lark_ack_pool(locale: LarkAckLocale) -> &'static [&'static str] {
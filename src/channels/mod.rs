@@ -31,15 +31,45 @@ pub mod linq;
 #[cfg(feature = "channel-matrix")]
 pub mod matrix;
 pub mod mattermost;
+pub mod model_profile;
 pub mod napcat;
 pub mod nextcloud_talk;
 pub mod nostr;
+pub mod nostr_answer_cache;
+pub mod nostr_audience;
+pub mod nostr_backfill;
+pub mod nostr_banner;
+pub mod nostr_catchup;
+pub mod nostr_commands;
+pub mod nostr_context;
+pub mod nostr_cost_alert;
+pub mod nostr_deletion;
+pub mod nostr_digest;
+pub mod nostr_export;
+pub mod nostr_fleet;
+pub mod nostr_links;
+pub mod nostr_live;
 pub mod nostr_memory;
+pub mod nostr_moderation;
+pub mod nostr_nip05;
+pub mod nostr_onboarding;
+pub mod nostr_outbox;
+pub mod nostr_pipeline;
+pub mod nostr_proactive;
+pub mod nostr_publish;
+pub mod nostr_quota;
+pub mod nostr_rebroadcast;
+pub mod nostr_relay_trust;
+pub mod nostr_roles;
+pub mod nostr_split;
+pub mod nostr_status_feed;
+pub mod nostr_subscriptions;
+pub mod persona;
 pub mod qq;
 pub mod seen_events;
 pub mod signal;
 pub mod slack;
-mod snowclaw_channels;
+pub(crate) mod snowclaw_channels;
 pub mod telegram;
 pub mod traits;
 pub mod transcription;
@@ -79,6 +109,7 @@ pub use whatsapp::WhatsAppChannel;
 #[cfg(feature = "whatsapp-web")]
 pub use whatsapp_web::WhatsAppWebChannel;
 
+use crate::agent::latency::{self, Stage};
 use crate::agent::loop_::{
     build_shell_policy_instructions, build_tool_instructions_from_specs,
     run_tool_call_loop_with_non_cli_approval_context, scrub_credentials, NonCliApprovalContext,
@@ -132,6 +163,11 @@ const CHANNEL_MIN_IN_FLIGHT_MESSAGES: usize = 8;
 const CHANNEL_MAX_IN_FLIGHT_MESSAGES: usize = 64;
 const CHANNEL_TYPING_REFRESH_INTERVAL_SECS: u64 = 4;
 const CHANNEL_HEALTH_HEARTBEAT_SECS: u64 = 30;
+/// How often expired per-conversation artifact workspaces are garbage-collected.
+const ARTIFACT_GC_INTERVAL: Duration = Duration::from_secs(3600);
+/// How long the model router reuses a cost summary before re-reading the
+/// cost log.
+const BUDGET_SUMMARY_TTL: Duration = Duration::from_secs(60);
 const MODEL_CACHE_FILE: &str = "models_cache.json";
 const MODEL_CACHE_PREVIEW_LIMIT: usize = 10;
 const MEMORY_CONTEXT_MAX_ENTRIES: usize = 4;
@@ -147,6 +183,7 @@ const CHANNEL_HOOK_MAX_OUTBOUND_CHARS: usize = 20_000;
 
 type ProviderCacheMap = Arc<Mutex<HashMap<String, Arc<dyn Provider>>>>;
 type RouteSelectionMap = Arc<Mutex<HashMap<String, ChannelRouteSelection>>>;
+type BudgetSummaryCache = Arc<Mutex<Option<(Instant, crate::cost::CostSummary)>>>;
 
 fn live_channels_registry() -> &'static Mutex<HashMap<String, Arc<dyn Channel>>> {
     static REGISTRY: OnceLock<Mutex<HashMap<String, Arc<dyn Channel>>>> = OnceLock::new();
@@ -256,12 +293,19 @@ struct ChannelRuntimeDefaults {
     cost: crate::config::CostConfig,
     auto_save_memory: bool,
     max_tool_iterations: usize,
+    max_parallel_tools: usize,
     min_relevance_score: f64,
     message_timeout_secs: u64,
     interrupt_on_new_message: bool,
     multimodal: crate::config::MultimodalConfig,
     query_classification: crate::config::QueryClassificationConfig,
     model_routes: Vec<crate::config::ModelRouteConfig>,
+    personas: crate::config::PersonasConfig,
+    execution_policies: crate::config::ExecutionPoliciesConfig,
+    audit: crate::config::AuditConfig,
+    model_profiles: crate::config::ModelProfilesConfig,
+    model_router: crate::config::ModelRouterConfig,
+    latency: crate::config::LatencyBudgetConfig,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -360,6 +404,13 @@ struct ChannelRuntimeContext {
     approval_manager: Arc<ApprovalManager>,
     safety_heartbeat: Option<SafetyHeartbeatConfig>,
     startup_perplexity_filter: crate::config::PerplexityFilterConfig,
+    execution_policies: crate::config::ExecutionPoliciesConfig,
+    audit: crate::config::AuditConfig,
+    personas: crate::config::PersonasConfig,
+    model_profiles: crate::config::ModelProfilesConfig,
+    model_router: crate::config::ModelRouterConfig,
+    latency: crate::config::LatencyBudgetConfig,
+    budget_summary: BudgetSummaryCache,
 }
 
 #[derive(Clone)]
@@ -1152,12 +1203,19 @@ fn runtime_defaults_from_config(config: &Config) -> ChannelRuntimeDefaults {
         cost: config.cost.clone(),
         auto_save_memory: config.memory.auto_save,
         max_tool_iterations: config.agent.max_tool_iterations,
+        max_parallel_tools: config.agent.max_parallel_tools,
         min_relevance_score: config.memory.min_relevance_score,
         message_timeout_secs,
         interrupt_on_new_message,
         multimodal: config.multimodal.clone(),
         query_classification: config.query_classification.clone(),
         model_routes: config.model_routes.clone(),
+        personas: config.personas.clone(),
+        execution_policies: config.execution_policies.clone(),
+        audit: config.security.audit.clone(),
+        model_profiles: config.model_profiles.clone(),
+        model_router: config.model_router.clone(),
+        latency: config.channels_config.latency.clone(),
     }
 }
 
@@ -1218,12 +1276,19 @@ fn runtime_defaults_snapshot(ctx: &ChannelRuntimeContext) -> ChannelRuntimeDefau
         cost: crate::config::CostConfig::default(),
         auto_save_memory: ctx.auto_save_memory,
         max_tool_iterations: ctx.max_tool_iterations,
+        max_parallel_tools: crate::config::schema::default_agent_max_parallel_tools(),
         min_relevance_score: ctx.min_relevance_score,
         message_timeout_secs: ctx.message_timeout_secs,
         interrupt_on_new_message: ctx.interrupt_on_new_message,
         multimodal: ctx.multimodal.clone(),
         query_classification: ctx.query_classification.clone(),
         model_routes: ctx.model_routes.clone(),
+        personas: ctx.personas.clone(),
+        execution_policies: ctx.execution_policies.clone(),
+        audit: ctx.audit.clone(),
+        model_profiles: ctx.model_profiles.clone(),
+        model_router: ctx.model_router.clone(),
+        latency: ctx.latency.clone(),
     }
 }
 
@@ -1341,6 +1406,35 @@ fn snapshot_non_cli_excluded_tools(ctx: &ChannelRuntimeContext) -> Vec<String> {
         .clone()
 }
 
+/// Execution policy for `msg`, with violations attributed to its sender and
+/// written to the audit log when one is configured.
+fn resolve_execution_policy(
+    ctx: &ChannelRuntimeContext,
+    defaults: &ChannelRuntimeDefaults,
+    msg: &traits::ChannelMessage,
+    persona: Option<&str>,
+) -> Option<Arc<crate::security::execution_policy::ExecutionPolicy>> {
+    let (name, config) = crate::security::execution_policy::select(
+        &defaults.execution_policies,
+        &msg.channel,
+        &msg.reply_target,
+        persona,
+    )?;
+    let audit = ctx
+        .provider_runtime_options
+        .zeroclaw_dir
+        .as_ref()
+        .and_then(|dir| {
+            crate::security::AuditLogger::new(defaults.audit.clone(), dir.clone())
+                .map_err(|err| tracing::warn!("Audit log unavailable: {err}"))
+                .ok()
+        });
+    let policy =
+        crate::security::execution_policy::ExecutionPolicy::new(name, config, &ctx.workspace_dir)
+            .with_audit(&msg.channel, &msg.reply_target, &msg.sender, audit);
+    Some(Arc::new(policy))
+}
+
 fn filtered_tool_specs_for_runtime(
     tools_registry: &[Box<dyn Tool>],
     excluded_tools: &[String],
@@ -1924,6 +2018,29 @@ fn default_route_selection(ctx: &ChannelRuntimeContext) -> ChannelRouteSelection
     }
 }
 
+/// Route for a message handled by a persona: an explicit `/models` choice for
+/// the sender wins, then the persona's provider/model, then the default.
+fn get_persona_route_selection(
+    ctx: &ChannelRuntimeContext,
+    sender_key: &str,
+    persona: &persona::ResolvedPersona,
+) -> ChannelRouteSelection {
+    if let Some(selected) = ctx
+        .route_overrides
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(sender_key)
+        .cloned()
+    {
+        return selected;
+    }
+    let default = default_route_selection(ctx);
+    ChannelRouteSelection {
+        provider: persona.config.provider.clone().unwrap_or(default.provider),
+        model: persona.config.model.clone().unwrap_or(default.model),
+    }
+}
+
 fn get_route_selection(ctx: &ChannelRuntimeContext, sender_key: &str) -> ChannelRouteSelection {
     ctx.route_overrides
         .lock()
@@ -1960,6 +2077,88 @@ fn classify_message_route(
     })
 }
 
+/// Route a message that no classification rule matched through
+/// `[model_router]`, factoring in the remaining cost budget.
+fn route_by_complexity(
+    ctx: &ChannelRuntimeContext,
+    defaults: &ChannelRuntimeDefaults,
+    message: &str,
+) -> Option<crate::agent::model_router::RouteDecision> {
+    let router = &defaults.model_router;
+    if !router.enabled {
+        return None;
+    }
+    let budget_remaining = if defaults.cost.enabled && router.low_budget_percent > 0 {
+        cached_budget_summary(ctx, &defaults.cost)
+            .map_err(|err| tracing::warn!("Model router budget check failed: {err}"))
+            .ok()
+            .and_then(|summary| {
+                crate::agent::model_router::remaining_budget_fraction(&defaults.cost, &summary)
+            })
+    } else {
+        None
+    };
+    let decision = crate::agent::model_router::route(router, message, budget_remaining);
+    tracing::info!(
+        target: "model_router",
+        tier = decision.tier.name(),
+        hint = decision.hint.as_deref().unwrap_or("default"),
+        downgraded = decision.downgraded,
+        budget_remaining = budget_remaining.unwrap_or(1.0),
+        message_length = message.len(),
+        "Routed message by complexity"
+    );
+    Some(decision)
+}
+
+/// Cost summary for the router's budget check. The cost log is re-read at
+/// most every [`BUDGET_SUMMARY_TTL`] rather than on every message.
+fn cached_budget_summary(
+    ctx: &ChannelRuntimeContext,
+    cost: &crate::config::CostConfig,
+) -> anyhow::Result<crate::cost::CostSummary> {
+    let mut cached = ctx.budget_summary.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((read_at, summary)) = cached.as_ref() {
+        if read_at.elapsed() < BUDGET_SUMMARY_TTL {
+            return Ok(summary.clone());
+        }
+    }
+    let summary = crate::cost::CostTracker::new(cost.clone(), &ctx.workspace_dir)?.get_summary()?;
+    *cached = Some((Instant::now(), summary.clone()));
+    Ok(summary)
+}
+
+/// Pick the model profile for a message: routing hint, then the channel's
+/// dynamic selection, then the group mapping, then the default.
+async fn resolve_model_profile(
+    defaults: &ChannelRuntimeDefaults,
+    msg: &traits::ChannelMessage,
+    hint: Option<&str>,
+    channel: Option<&Arc<dyn Channel>>,
+) -> Option<model_profile::ResolvedModelProfile> {
+    if defaults.model_profiles.profiles.is_empty() {
+        return None;
+    }
+    let dynamic = match channel {
+        Some(channel) => channel.model_profile(&msg.reply_target).await,
+        None => None,
+    };
+    let profile = model_profile::resolve(
+        &defaults.model_profiles,
+        hint,
+        dynamic.as_deref(),
+        &msg.reply_target,
+    )?;
+    tracing::info!(
+        target: "model_profiles",
+        profile = %profile.name,
+        source = profile.source,
+        reply_target = %msg.reply_target,
+        "Selected model profile"
+    );
+    Some(profile)
+}
+
 fn set_route_selection(ctx: &ChannelRuntimeContext, sender_key: &str, next: ChannelRouteSelection) {
     let default_route = default_route_selection(ctx);
     let mut routes = ctx
@@ -2189,6 +2388,55 @@ async fn get_or_create_provider(
     Ok(Arc::clone(cached))
 }
 
+/// Provider for a route with a model profile applied. Profiles that only
+/// change temperature reuse the route's provider; max_tokens or reasoning
+/// overrides get their own cached instance.
+async fn get_or_create_profiled_provider(
+    ctx: &ChannelRuntimeContext,
+    provider_name: &str,
+    profile: Option<&model_profile::ResolvedModelProfile>,
+) -> anyhow::Result<Arc<dyn Provider>> {
+    let Some((profile, options)) = profile.and_then(|p| {
+        p.provider_options(&ctx.provider_runtime_options)
+            .map(|options| (p, options))
+    }) else {
+        return get_or_create_provider(ctx, provider_name).await;
+    };
+
+    let cache_key = profile.provider_cache_key(provider_name);
+    if let Some(existing) = ctx
+        .provider_cache
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&cache_key)
+        .cloned()
+    {
+        return Ok(existing);
+    }
+
+    let defaults = runtime_defaults_snapshot(ctx);
+    let api_url = if provider_name == defaults.default_provider.as_str() {
+        defaults.api_url.clone()
+    } else {
+        None
+    };
+    let provider = create_resilient_provider_nonblocking(
+        provider_name,
+        defaults.api_key.clone(),
+        api_url,
+        defaults.reliability.clone(),
+        options,
+    )
+    .await?;
+    let provider: Arc<dyn Provider> = Arc::from(provider);
+
+    let mut cache = ctx.provider_cache.lock().unwrap_or_else(|e| e.into_inner());
+    let cached = cache
+        .entry(cache_key)
+        .or_insert_with(|| Arc::clone(&provider));
+    Ok(Arc::clone(cached))
+}
+
 async fn create_resilient_provider_nonblocking(
     provider_name: &str,
     api_key: Option<String>,
@@ -3062,6 +3310,16 @@ async fn handle_runtime_command_if_needed(
     true
 }
 
+/// Memory scope of a message. Nostr messages are scoped by audience:
+/// their group, the owner's DM session or another DM. Other channels are
+/// unscoped.
+fn memory_recall_context(msg: &traits::ChannelMessage) -> Option<memory::RecallContext> {
+    if msg.channel != "nostr" {
+        return None;
+    }
+    Some(nostr_audience::audience(&msg.reply_target).recall_context(&msg.channel))
+}
+
 async fn build_memory_context(
     mem: &dyn Memory,
     user_msg: &str,
@@ -3115,6 +3373,35 @@ async fn build_memory_context(
     context
 }
 
+/// [`build_memory_context`] within the context stage of the latency budget.
+/// A slow recall is abandoned so the reply goes out without memory context,
+/// and recall is skipped for `recall_cooldown_secs` after that.
+async fn build_budgeted_memory_context(
+    mem: &dyn Memory,
+    user_msg: &str,
+    min_relevance_score: f64,
+    session_id: Option<&str>,
+    budget: &crate::config::LatencyBudgetConfig,
+) -> String {
+    if budget.enabled && latency::recall_paused() {
+        latency::record_degraded(Stage::Context);
+        return String::new();
+    }
+    let recall = build_memory_context(mem, user_msg, min_relevance_score, session_id);
+    match latency::run_stage_with(budget, Stage::Context, recall).await {
+        Ok(context) => context,
+        Err(timeout) => {
+            tracing::warn!(
+                "Memory recall {timeout}; replying without memory context for {}s",
+                budget.recall_cooldown_secs
+            );
+            latency::pause_recall(Duration::from_secs(budget.recall_cooldown_secs));
+            latency::record_degraded(Stage::Context);
+            String::new()
+        }
+    }
+}
+
 /// Extract a compact summary of tool interactions from history messages added
 /// during `run_tool_call_loop`. Scans assistant messages for `<tool_call>` tags
 /// or native tool-call JSON to collect tool names used.
@@ -3791,14 +4078,61 @@ If this input is legitimate, rephrase the request and avoid instruction-override
             }
         }
     }
-    // Try classification first, fall back to sender/default route.
+    let persona = persona::resolve(&runtime_defaults.personas, &msg);
+    let memory_session = persona
+        .as_ref()
+        .map_or_else(|| history_key.clone(), |p| p.memory_session(&history_key));
+    // Try classification rules first, then the complexity router, then fall
+    // back to sender/persona/default route.
+    let rule_hint =
+        crate::agent::classifier::classify(&runtime_defaults.query_classification, &msg.content);
+    let router_decision = if rule_hint.is_none() {
+        route_by_complexity(ctx.as_ref(), &runtime_defaults, &msg.content)
+    } else {
+        None
+    };
+    let routing_hint = rule_hint
+        .clone()
+        .or_else(|| router_decision.as_ref().and_then(|d| d.hint.clone()));
     let route = classify_message_route(
         &runtime_defaults.query_classification,
         &runtime_defaults.model_routes,
         &msg.content,
     )
-    .unwrap_or_else(|| get_route_selection(ctx.as_ref(), &history_key));
-    let active_provider = match get_or_create_provider(ctx.as_ref(), &route.provider).await {
+    .or_else(|| {
+        let hint = router_decision.as_ref()?.hint.as_deref()?;
+        let route = runtime_defaults
+            .model_routes
+            .iter()
+            .find(|r| r.hint == hint)?;
+        Some(ChannelRouteSelection {
+            provider: route.provider.clone(),
+            model: route.model.clone(),
+        })
+    })
+    .unwrap_or_else(|| match persona.as_ref() {
+        Some(persona) => get_persona_route_selection(ctx.as_ref(), &history_key, persona),
+        None => get_route_selection(ctx.as_ref(), &history_key),
+    });
+    let model_profile = resolve_model_profile(
+        &runtime_defaults,
+        &msg,
+        routing_hint.as_deref(),
+        target_channel.as_ref(),
+    )
+    .await;
+    let temperature = model_profile
+        .as_ref()
+        .map_or(runtime_defaults.temperature, |p| {
+            p.temperature(runtime_defaults.temperature)
+        });
+    let active_provider = match get_or_create_profiled_provider(
+        ctx.as_ref(),
+        &route.provider,
+        model_profile.as_ref(),
+    )
+    .await
+    {
         Ok(provider) => provider,
         Err(err) => {
             let safe_err = providers::sanitize_api_error(&err.to_string());
@@ -3827,7 +4161,7 @@ If this input is legitimate, rephrase the request and avoid instruction-override
                 &autosave_key,
                 &msg.content,
                 crate::memory::MemoryCategory::Conversation,
-                Some(&history_key),
+                Some(&memory_session),
             )
             .await;
     }
@@ -3880,11 +4214,12 @@ If this input is legitimate, rephrase the request and avoid instruction-override
             // conversation history. Follow-up turns already include context
             // from previous messages.
             if !had_prior_history {
-                let memory_context = build_memory_context(
+                let memory_context = build_budgeted_memory_context(
                     ctx.memory.as_ref(),
                     &msg.content,
                     runtime_defaults.min_relevance_score,
-                    Some(&history_key),
+                    Some(&memory_session),
+                    &runtime_defaults.latency,
                 )
                 .await;
                 if !memory_context.is_empty() {
@@ -3898,13 +4233,39 @@ If this input is legitimate, rephrase the request and avoid instruction-override
         msg.channel == "cli" || should_expose_internal_tool_details(&msg.content);
     let progress_mode =
         effective_progress_mode_for_message(msg.channel.as_str(), expose_internal_tool_details);
-    let excluded_tools_snapshot = if msg.channel == "cli" {
+    let mut excluded_tools_snapshot = if msg.channel == "cli" {
         Vec::new()
     } else {
         snapshot_non_cli_excluded_tools(ctx.as_ref())
     };
+    let base_system_prompt = match persona.as_ref() {
+        Some(persona) => {
+            persona.restrict_tools(
+                ctx.tools_registry.iter().map(|tool| tool.name()),
+                &mut excluded_tools_snapshot,
+            );
+            persona.apply_system_prompt(ctx.system_prompt.as_str())
+        }
+        None => ctx.system_prompt.as_str().to_string(),
+    };
+    let execution_policy = if msg.channel == "cli" {
+        None
+    } else {
+        resolve_execution_policy(
+            ctx.as_ref(),
+            &runtime_defaults,
+            &msg,
+            persona.as_ref().map(|p| p.name.as_str()),
+        )
+    };
+    if let Some(policy) = execution_policy.as_deref() {
+        policy.restrict_tools(
+            ctx.tools_registry.iter().map(|tool| tool.name()),
+            &mut excluded_tools_snapshot,
+        );
+    }
     let mut system_prompt = build_channel_system_prompt(
-        ctx.system_prompt.as_str(),
+        &base_system_prompt,
         &msg.channel,
         &msg.reply_target,
         expose_internal_tool_details,
@@ -4086,33 +4447,67 @@ If this input is legitimate, rephrase the request and avoid instruction-override
     } else {
         None
     };
+    let artifact_dir = match crate::agent::artifacts::ArtifactStore::new(&ctx.workspace_dir)
+        .dir_for(&history_key)
+    {
+        Ok(dir) => Some(dir),
+        Err(err) => {
+            tracing::warn!("Artifact workspace unavailable for {history_key}: {err}");
+            None
+        }
+    };
+    let cost_attribution = crate::cost::attribution::CostAttribution {
+        channel: Some(msg.channel.clone()),
+        room: Some(msg.reply_target.clone()),
+        persona: persona.as_ref().map(|p| p.name.clone()),
+        route: match (&rule_hint, &router_decision) {
+            (Some(hint), _) => Some(format!("rule -> {hint}")),
+            (None, Some(decision)) => Some(decision.label()),
+            (None, None) => None,
+        },
+    };
     let llm_result = tokio::select! {
         () = cancellation_token.cancelled() => LlmExecutionResult::Cancelled,
         result = tokio::time::timeout(
             Duration::from_secs(timeout_budget_secs),
-            crate::agent::loop_::scope_cost_enforcement_context(
-                cost_enforcement_context,
-                run_tool_call_loop_with_non_cli_approval_context(
-                    active_provider.as_ref(),
-                    &mut history,
-                    ctx.tools_registry.as_ref(),
-                    ctx.observer.as_ref(),
-                    route.provider.as_str(),
-                    route.model.as_str(),
-                    runtime_defaults.temperature,
-                    true,
-                    Some(ctx.approval_manager.as_ref()),
-                    msg.channel.as_str(),
-                    non_cli_approval_context,
-                    &runtime_defaults.multimodal,
-                    runtime_defaults.max_tool_iterations,
-                    Some(cancellation_token.clone()),
-                    delta_tx,
-                    ctx.hooks.as_deref(),
-                    &excluded_tools_snapshot,
-                    progress_mode,
-                    ctx.safety_heartbeat.clone(),
-                    runtime_canary_tokens_snapshot(ctx.as_ref()),
+            latency::scope(
+                runtime_defaults.latency.clone(),
+                crate::agent::loop_::scope_cost_enforcement_context(
+                    cost_enforcement_context,
+                    crate::agent::artifacts::scope(
+                        artifact_dir,
+                        crate::cost::attribution::scope(
+                            cost_attribution,
+                            crate::agent::loop_::scope_max_parallel_tools(
+                                runtime_defaults.max_parallel_tools,
+                                crate::security::execution_policy::scope(
+                                    execution_policy,
+                                    run_tool_call_loop_with_non_cli_approval_context(
+                                        active_provider.as_ref(),
+                                        &mut history,
+                                        ctx.tools_registry.as_ref(),
+                                        ctx.observer.as_ref(),
+                                        route.provider.as_str(),
+                                        route.model.as_str(),
+                                        temperature,
+                                        true,
+                                        Some(ctx.approval_manager.as_ref()),
+                                        msg.channel.as_str(),
+                                        non_cli_approval_context,
+                                        &runtime_defaults.multimodal,
+                                        runtime_defaults.max_tool_iterations,
+                                        Some(cancellation_token.clone()),
+                                        delta_tx,
+                                        ctx.hooks.as_deref(),
+                                        &excluded_tools_snapshot,
+                                        progress_mode,
+                                        ctx.safety_heartbeat.clone(),
+                                        runtime_canary_tokens_snapshot(ctx.as_ref()),
+                                    ),
+                                ),
+                            ),
+                        ),
+                    ),
                 ),
             ),
         ) => LlmExecutionResult::Completed(result),
@@ -4295,7 +4690,7 @@ If this input is legitimate, rephrase the request and avoid instruction-override
                         &assistant_key,
                         &delivered_response,
                         crate::memory::MemoryCategory::Conversation,
-                        None,
+                        persona.is_some().then_some(memory_session.as_str()),
                     )
                     .await;
             }
@@ -4305,27 +4700,35 @@ If this input is legitimate, rephrase the request and avoid instruction-override
                 truncate_with_ellipsis(&delivered_response, 80)
             );
             if let Some(channel) = target_channel.as_ref() {
-                if let Some(ref draft_id) = draft_message_id {
-                    if let Err(e) = channel
-                        .finalize_draft(&msg.reply_target, draft_id, &delivered_response)
+                let publish = async {
+                    if let Some(ref draft_id) = draft_message_id {
+                        if let Err(e) = channel
+                            .finalize_draft(&msg.reply_target, draft_id, &delivered_response)
+                            .await
+                        {
+                            tracing::warn!("Failed to finalize draft: {e}; sending as new message");
+                            let _ = channel
+                                .send(
+                                    &SendMessage::new(&delivered_response, &msg.reply_target)
+                                        .in_thread(msg.thread_ts.clone()),
+                                )
+                                .await;
+                        }
+                    } else if let Err(e) = channel
+                        .send(
+                            &SendMessage::new(delivered_response, &msg.reply_target)
+                                .in_thread(msg.thread_ts.clone()),
+                        )
                         .await
                     {
-                        tracing::warn!("Failed to finalize draft: {e}; sending as new message");
-                        let _ = channel
-                            .send(
-                                &SendMessage::new(&delivered_response, &msg.reply_target)
-                                    .in_thread(msg.thread_ts.clone()),
-                            )
-                            .await;
+                        eprintln!("  ❌ Failed to reply on {}: {e}", channel.name());
                     }
-                } else if let Err(e) = channel
-                    .send(
-                        &SendMessage::new(delivered_response, &msg.reply_target)
-                            .in_thread(msg.thread_ts.clone()),
-                    )
-                    .await
+                };
+                if let Err(timeout) =
+                    latency::run_stage_with(&runtime_defaults.latency, Stage::Publish, publish)
+                        .await
                 {
-                    eprintln!("  ❌ Failed to reply on {}: {e}", channel.name());
+                    tracing::warn!("Abandoned reply on {}: {timeout}", channel.name());
                 }
             }
         }
@@ -4567,6 +4970,14 @@ async fn run_message_dispatch_loop(
     let task_sequence = Arc::new(AtomicU64::new(1));
 
     while let Some(msg) = rx.recv().await {
+        if crate::daemon::shutdown::is_draining() {
+            tracing::debug!(
+                channel = %msg.channel,
+                sender = %msg.sender,
+                "Dropping channel message received during shutdown"
+            );
+            continue;
+        }
         let permit = match Arc::clone(&semaphore).acquire_owned().await {
             Ok(permit) => permit,
             Err(_) => break,
@@ -4575,8 +4986,10 @@ async fn run_message_dispatch_loop(
         let worker_ctx = Arc::clone(&ctx);
         let in_flight = Arc::clone(&in_flight_by_sender);
         let task_sequence = Arc::clone(&task_sequence);
+        let draining_guard = crate::daemon::shutdown::InFlight::start();
         workers.spawn(async move {
             let _permit = permit;
+            let _draining_guard = draining_guard;
             let runtime_defaults = runtime_defaults_snapshot(worker_ctx.as_ref());
             let interrupt_enabled =
                 runtime_defaults.interrupt_on_new_message && msg.channel == "telegram";
@@ -4609,7 +5022,12 @@ async fn run_message_dispatch_loop(
                 }
             }
 
-            Box::pin(process_channel_message(worker_ctx, msg, cancellation_token)).await;
+            let recall_context = memory_recall_context(&msg);
+            memory::snowclaw_ext::scope(
+                recall_context,
+                Box::pin(process_channel_message(worker_ctx, msg, cancellation_token)),
+            )
+            .await;
 
             if interrupt_enabled {
                 let mut active = in_flight.lock().await;
@@ -5215,6 +5633,25 @@ pub(crate) async fn handle_command(command: crate::ChannelCommands, config: &Con
         crate::ChannelCommands::BindTelegram { identity } => {
             bind_telegram_identity(config, &identity).await
         }
+        crate::ChannelCommands::Export {
+            target,
+            format,
+            limit,
+            from,
+            to,
+            output,
+        } => {
+            nostr_export::export_cli(
+                config,
+                &target,
+                &format,
+                limit,
+                from.as_deref(),
+                to.as_deref(),
+                output.as_deref(),
+            )
+            .await
+        }
     }
 }
 
@@ -5612,8 +6049,9 @@ async fn append_nostr_channel_if_available(
     config: &Config,
     channels: &mut Vec<ConfiguredChannel>,
     startup_context: &str,
+    memory: Option<Arc<dyn Memory>>,
 ) -> Option<String> {
-    snowclaw_channels::append_nostr_channel(config, channels, startup_context).await
+    snowclaw_channels::append_nostr_channel(config, channels, startup_context, memory).await
 }
 
 /// Run health checks for configured channels.
@@ -5622,7 +6060,7 @@ pub async fn doctor_channels(config: Config) -> Result<()> {
     let mut init_failures = Vec::new();
 
     if let Some(reason) =
-        append_nostr_channel_if_available(&config, &mut channels, "health check").await
+        append_nostr_channel_if_available(&config, &mut channels, "health check", None).await
     {
         init_failures.push(reason);
     }
@@ -5953,9 +6391,13 @@ pub async fn start_channels(config: Config) -> Result<()> {
     // Collect active channels from a shared builder to keep startup and doctor parity.
     let mut configured_channels = collect_configured_channels(&config, "runtime startup");
     let mut init_failures = Vec::new();
-    if let Some(reason) =
-        append_nostr_channel_if_available(&config, &mut configured_channels, "runtime startup")
-            .await
+    if let Some(reason) = append_nostr_channel_if_available(
+        &config,
+        &mut configured_channels,
+        "runtime startup",
+        Some(Arc::clone(&mem)),
+    )
+    .await
     {
         init_failures.push(reason);
     }
@@ -6100,6 +6542,16 @@ pub async fn start_channels(config: Config) -> Result<()> {
         // Preserve startup perplexity filter config to ensure policy is not weakened
         // when runtime store lookup misses.
         startup_perplexity_filter: config.security.perplexity_filter.clone(),
+        // Same for execution policies and their audit log: a store miss must
+        // not run tools unrestricted. Personas, profiles, routing and latency
+        // budgets are kept too so a miss doesn't drop back to the defaults.
+        execution_policies: config.execution_policies.clone(),
+        audit: config.security.audit.clone(),
+        personas: config.personas.clone(),
+        model_profiles: config.model_profiles.clone(),
+        model_router: config.model_router.clone(),
+        latency: config.channels_config.latency.clone(),
+        budget_summary: Arc::new(Mutex::new(None)),
         approval_manager: {
             // Keep approval policy provenance-bound to static config. Do not
             // auto-approve tool names from untrusted manifest files.
@@ -6115,6 +6567,22 @@ pub async fn start_channels(config: Config) -> Result<()> {
         },
     });
 
+    if config.agent.artifact_ttl_hours > 0 {
+        let store = crate::agent::artifacts::ArtifactStore::new(&config.workspace_dir);
+        let ttl = Duration::from_secs(config.agent.artifact_ttl_hours * 3600);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(ARTIFACT_GC_INTERVAL);
+            loop {
+                interval.tick().await;
+                match store.collect_garbage(ttl) {
+                    Ok(0) => {}
+                    Ok(n) => tracing::info!("Removed {n} expired artifact workspace(s)"),
+                    Err(e) => tracing::warn!("Artifact workspace cleanup failed: {e}"),
+                }
+            }
+        });
+    }
+
     run_message_dispatch_loop(rx, runtime_ctx, max_in_flight_messages).await;
 
     // Wait for all channel tasks
@@ -6458,6 +6926,13 @@ mod tests {
             approval_manager: mock_price_approved_manager(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         };
 
         assert!(compact_sender_history(&ctx, &sender));
@@ -6515,6 +6990,13 @@ mod tests {
             approval_manager: mock_price_approved_manager(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         };
 
         append_sender_turn(&ctx, &sender, ChatMessage::user("hello"));
@@ -6575,6 +7057,13 @@ mod tests {
             approval_manager: mock_price_approved_manager(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         };
 
         assert!(rollback_orphan_user_turn(&ctx, &sender, "pending"));
@@ -7258,6 +7747,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: mock_price_approved_manager(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -7345,6 +7841,13 @@ BTC is currently around $65,000 based on latest tool output."#
             hooks: None,
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -7419,6 +7922,13 @@ BTC is currently around $65,000 based on latest tool output."#
             hooks: None,
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -7507,6 +8017,13 @@ BTC is currently around $65,000 based on latest tool output."#
             model_routes: Vec::new(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -7594,6 +8111,13 @@ BTC is currently around $65,000 based on latest tool output."#
             model_routes: Vec::new(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -7666,6 +8190,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: mock_price_approved_manager(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -7740,6 +8271,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: mock_price_approved_manager(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -7816,6 +8354,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: mock_price_approved_manager(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -7923,6 +8468,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
         assert_eq!(
             runtime_ctx
@@ -8061,6 +8613,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::clone(&approval_manager),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -8150,6 +8709,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::clone(&approval_manager),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -8228,6 +8794,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         let runtime_ctx_for_first_turn = runtime_ctx.clone();
@@ -8392,6 +8965,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
         assert_eq!(
             runtime_ctx
@@ -8507,6 +9087,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager,
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -8617,6 +9204,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager,
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -8709,6 +9303,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::clone(&approval_manager),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -8811,6 +9412,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::clone(&approval_manager),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -8914,6 +9522,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -8998,6 +9613,83 @@ BTC is currently around $65,000 based on latest tool output."#
             .all(|tool| tool != "mock_price"));
     }
 
+    #[test]
+    fn runtime_defaults_snapshot_keeps_startup_personas_and_routing_on_store_miss() {
+        let provider: Arc<dyn Provider> = Arc::new(ModelCaptureProvider::default());
+        let mut personas = crate::config::PersonasConfig::default();
+        personas
+            .groups
+            .insert("dev-group".to_string(), "reviewer".to_string());
+        let mut model_profiles = crate::config::ModelProfilesConfig::default();
+        model_profiles.default = Some("precise".to_string());
+        let model_router = crate::config::ModelRouterConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let latency = crate::config::LatencyBudgetConfig {
+            enabled: true,
+            ..Default::default()
+        };
+
+        let runtime_ctx = ChannelRuntimeContext {
+            channels_by_name: Arc::new(HashMap::new()),
+            provider: Arc::clone(&provider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("test-system-prompt".to_string()),
+            model: Arc::new("default-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 5,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            conversation_locks: Default::default(),
+            session_config: crate::config::AgentSessionConfig::default(),
+            session_manager: None,
+            provider_cache: Arc::new(Mutex::new(HashMap::new())),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(std::env::temp_dir()),
+            message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            interrupt_on_new_message: false,
+            multimodal: crate::config::MultimodalConfig::default(),
+            hooks: None,
+            non_cli_excluded_tools: Arc::new(Mutex::new(Vec::new())),
+            query_classification: crate::config::QueryClassificationConfig::default(),
+            model_routes: Vec::new(),
+            approval_manager: Arc::new(ApprovalManager::from_config(
+                &crate::config::AutonomyConfig::default(),
+            )),
+            safety_heartbeat: None,
+            startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas,
+            model_profiles,
+            model_router,
+            latency,
+            budget_summary: Arc::new(Mutex::new(None)),
+        };
+
+        let defaults = runtime_defaults_snapshot(&runtime_ctx);
+        assert_eq!(
+            defaults
+                .personas
+                .groups
+                .get("dev-group")
+                .map(String::as_str),
+            Some("reviewer")
+        );
+        assert_eq!(defaults.model_profiles.default.as_deref(), Some("precise"));
+        assert!(defaults.model_router.enabled);
+        assert!(defaults.latency.enabled);
+    }
+
     #[tokio::test]
     async fn process_channel_message_blocks_gcg_like_suffix_when_perplexity_filter_enabled() {
         let channel_impl = Arc::new(TelegramRecordingChannel::default());
@@ -9065,6 +9757,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: mock_price_approved_manager(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
         maybe_apply_runtime_config_update(runtime_ctx.as_ref())
             .await
@@ -9162,6 +9861,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -9312,6 +10018,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -9432,6 +10145,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -9532,6 +10252,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -9651,6 +10378,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: Arc::new(ApprovalManager::from_config(&autonomy_cfg)),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -9771,6 +10505,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: mock_price_approved_manager(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -9850,6 +10591,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: mock_price_approved_manager(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -9904,12 +10652,19 @@ BTC is currently around $65,000 based on latest tool output."#
                         cost: crate::config::CostConfig::default(),
                         auto_save_memory: false,
                         max_tool_iterations: 5,
+                        max_parallel_tools: 4,
                         min_relevance_score: 0.0,
                         message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
                         interrupt_on_new_message: false,
                         multimodal: crate::config::MultimodalConfig::default(),
                         query_classification: crate::config::QueryClassificationConfig::default(),
                         model_routes: Vec::new(),
+                        personas: crate::config::PersonasConfig::default(),
+                        execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+                        audit: crate::config::AuditConfig::default(),
+                        model_profiles: crate::config::ModelProfilesConfig::default(),
+                        model_router: crate::config::ModelRouterConfig::default(),
+                        latency: crate::config::LatencyBudgetConfig::default(),
                     },
                     perplexity_filter: crate::config::PerplexityFilterConfig::default(),
                     outbound_leak_guard: crate::config::OutboundLeakGuardConfig::default(),
@@ -9960,6 +10715,13 @@ BTC is currently around $65,000 based on latest tool output."#
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -10158,6 +10920,13 @@ BTC is currently around $65,000 based on latest tool output."#
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         maybe_apply_runtime_config_update(runtime_ctx.as_ref())
@@ -10363,6 +11132,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: mock_price_approved_manager(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -10431,6 +11207,13 @@ BTC is currently around $65,000 based on latest tool output."#
             approval_manager: mock_price_approved_manager(),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -10613,6 +11396,13 @@ BTC is currently around $65,000 based on latest tool output."#
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         let (tx, rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(4);
@@ -10703,6 +11493,13 @@ BTC is currently around $65,000 based on latest tool output."#
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         let (tx, rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(8);
@@ -10805,6 +11602,13 @@ BTC is currently around $65,000 based on latest tool output."#
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         let (tx, rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(8);
@@ -10889,6 +11693,13 @@ BTC is currently around $65,000 based on latest tool output."#
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -10958,6 +11769,13 @@ BTC is currently around $65,000 based on latest tool output."#
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -11591,6 +12409,28 @@ BTC is currently around $65,000 based on latest tool output."#
         assert!(!session_a_context.contains("age 31"));
     }
 
+    #[test]
+    fn memory_recall_context_scopes_nostr_by_audience() {
+        let message = |channel: &str, reply_target: &str| traits::ChannelMessage {
+            id: "m1".into(),
+            sender: "alice".into(),
+            reply_target: reply_target.into(),
+            content: "hi".into(),
+            channel: channel.into(),
+            timestamp: 0,
+            thread_ts: None,
+        };
+
+        let group = memory_recall_context(&message("nostr", "#infra")).unwrap();
+        assert_eq!(group.group_id.as_deref(), Some("infra"));
+        assert!(!group.is_main_session);
+        // A DM from anyone but the registered owner never sees private memories.
+        let dm = memory_recall_context(&message("nostr", "ab12cd")).unwrap();
+        assert!(dm.group_id.is_none());
+        assert!(!dm.is_main_session);
+        assert!(memory_recall_context(&message("slack", "#general")).is_none());
+    }
+
     #[tokio::test]
     async fn process_channel_message_restores_per_sender_history_on_follow_ups() {
         let channel_impl = Arc::new(RecordingChannel::default());
@@ -11637,6 +12477,13 @@ BTC is currently around $65,000 based on latest tool output."#
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -11733,6 +12580,13 @@ BTC is currently around $65,000 based on latest tool output."#
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -11828,6 +12682,13 @@ BTC is currently around $65,000 based on latest tool output."#
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -11927,6 +12788,13 @@ BTC is currently around $65,000 based on latest tool output."#
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -12755,6 +13623,13 @@ BTC is currently around $65,000 based on latest tool output."#;
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         // Simulate a photo attachment message with [IMAGE:] marker.
@@ -12831,6 +13706,13 @@ BTC is currently around $65,000 based on latest tool output."#;
             )),
             safety_heartbeat: None,
             startup_perplexity_filter: crate::config::PerplexityFilterConfig::default(),
+            execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            personas: crate::config::PersonasConfig::default(),
+            model_profiles: crate::config::ModelProfilesConfig::default(),
+            model_router: crate::config::ModelRouterConfig::default(),
+            latency: crate::config::LatencyBudgetConfig::default(),
+            budget_summary: Arc::new(Mutex::new(None)),
         });
 
         process_channel_message(
@@ -0,0 +1,185 @@
+//! Per-message generation parameter profiles.
+//!
+//! `[model_profiles]` defines named temperature / max_tokens / reasoning
+//! effort bundles. The router picks one per message: the query
+//! classification hint first, then the channel's dynamic selection (NIP-78
+//! `model_profile` for Nostr rooms), then `[model_profiles.groups]`, then
+//! `default`. Unset profile values keep the global settings.
+
+use crate::config::{ModelProfileConfig, ModelProfilesConfig};
+use crate::providers::ProviderRuntimeOptions;
+
+/// A profile selected for an incoming message.
+#[derive(Debug, Clone)]
+pub struct ResolvedModelProfile {
+    pub name: String,
+    /// Which mapping selected it: "hint", "dynamic", "group" or "default".
+    pub source: &'static str,
+    pub config: ModelProfileConfig,
+}
+
+impl ResolvedModelProfile {
+    pub fn temperature(&self, default: f64) -> f64 {
+        self.config.temperature.unwrap_or(default)
+    }
+
+    /// Provider options with this profile's overrides, or `None` when the
+    /// profile doesn't touch provider construction.
+    pub fn provider_options(
+        &self,
+        base: &ProviderRuntimeOptions,
+    ) -> Option<ProviderRuntimeOptions> {
+        if self.config.max_tokens.is_none() && self.config.reasoning_level.is_none() {
+            return None;
+        }
+        let mut options = base.clone();
+        if let Some(max_tokens) = self.config.max_tokens {
+            options.max_tokens_override = Some(max_tokens);
+        }
+        if let Some(level) = &self.config.reasoning_level {
+            options.reasoning_level = Some(level.clone());
+        }
+        Some(options)
+    }
+
+    /// Provider cache key for this profile's options, so providers built
+    /// with different overrides are kept apart.
+    pub fn provider_cache_key(&self, provider_name: &str) -> String {
+        format!(
+            "{provider_name}#max_tokens={:?};reasoning={:?}",
+            self.config.max_tokens, self.config.reasoning_level
+        )
+    }
+}
+
+/// Select the profile for a message.
+///
+/// `hint` is the query classification hint, `dynamic` the profile named by
+/// the channel's runtime config, `reply_target` the conversation the reply
+/// goes to (`#<group>` for Nostr groups).
+pub fn resolve(
+    config: &ModelProfilesConfig,
+    hint: Option<&str>,
+    dynamic: Option<&str>,
+    reply_target: &str,
+) -> Option<ResolvedModelProfile> {
+    if config.profiles.is_empty() {
+        return None;
+    }
+
+    let group = reply_target.strip_prefix('#').unwrap_or(reply_target);
+    let candidates = [
+        (
+            "hint",
+            hint.and_then(|h| config.hints.get(h)).map(String::as_str),
+        ),
+        ("dynamic", dynamic),
+        (
+            "group",
+            config
+                .groups
+                .get(group)
+                .or_else(|| config.groups.get(reply_target))
+                .map(String::as_str),
+        ),
+        ("default", config.default.as_deref()),
+    ];
+
+    candidates.into_iter().find_map(|(source, name)| {
+        let name = name?;
+        match config.profiles.get(name) {
+            Some(profile) => Some(ResolvedModelProfile {
+                name: name.to_string(),
+                source,
+                config: profile.clone(),
+            }),
+            None => {
+                tracing::warn!(
+                    "Model profile `{name}` ({source}) is not defined in model_profiles.profiles"
+                );
+                None
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ModelProfilesConfig {
+        let mut config = ModelProfilesConfig::default();
+        config.profiles.insert(
+            "precise".into(),
+            ModelProfileConfig {
+                temperature: Some(0.1),
+                max_tokens: Some(1024),
+                reasoning_level: Some("high".into()),
+            },
+        );
+        config.profiles.insert(
+            "chatty".into(),
+            ModelProfileConfig {
+                temperature: Some(1.0),
+                ..ModelProfileConfig::default()
+            },
+        );
+        config.hints.insert("reasoning".into(), "precise".into());
+        config.groups.insert("lounge".into(), "chatty".into());
+        config
+    }
+
+    #[test]
+    fn hint_wins_over_dynamic_and_group() {
+        let config = config();
+        let p = resolve(&config, Some("reasoning"), Some("chatty"), "#lounge").unwrap();
+        assert_eq!((p.name.as_str(), p.source), ("precise", "hint"));
+
+        let p = resolve(&config, Some("fast"), Some("precise"), "#lounge").unwrap();
+        assert_eq!((p.name.as_str(), p.source), ("precise", "dynamic"));
+
+        let p = resolve(&config, None, None, "#lounge").unwrap();
+        assert_eq!((p.name.as_str(), p.source), ("chatty", "group"));
+        assert!(resolve(&config, None, None, "#dev").is_none());
+    }
+
+    #[test]
+    fn unknown_dynamic_profile_falls_through_to_default() {
+        let mut config = config();
+        config.default = Some("chatty".into());
+        let p = resolve(&config, None, Some("missing"), "#dev").unwrap();
+        assert_eq!((p.name.as_str(), p.source), ("chatty", "default"));
+    }
+
+    #[test]
+    fn provider_options_only_when_needed() {
+        let config = config();
+        let base = ProviderRuntimeOptions::default();
+
+        let chatty = resolve(&config, None, None, "#lounge").unwrap();
+        assert!(chatty.provider_options(&base).is_none());
+        assert!((chatty.temperature(0.7) - 1.0).abs() < f64::EPSILON);
+
+        let precise = resolve(&config, Some("reasoning"), None, "#dev").unwrap();
+        let options = precise.provider_options(&base).unwrap();
+        assert_eq!(options.max_tokens_override, Some(1024));
+        assert_eq!(options.reasoning_level.as_deref(), Some("high"));
+        assert_ne!(
+            precise.provider_cache_key("openrouter"),
+            chatty.provider_cache_key("openrouter")
+        );
+    }
+
+    #[test]
+    fn validate_rejects_unknown_mappings_and_bad_values() {
+        let mut config = config();
+        assert!(config.validate().is_ok());
+
+        config.groups.insert("dev".into(), "missing".into());
+        assert!(config.validate().is_err());
+
+        let mut config = self::config();
+        config.profiles.get_mut("chatty").unwrap().temperature = Some(3.0);
+        assert!(config.validate().is_err());
+    }
+}
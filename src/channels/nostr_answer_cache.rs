@@ -0,0 +1,301 @@
+//! Answer cache for repeat questions in Nostr groups.
+//!
+//! When a question reaches the agent its normalized-text hash (and, for
+//! semantic matching, its embedding) is marked pending for the group; the
+//! next reply the agent sends to that group is stored as the answer. A
+//! repeat of the same question within the TTL — or a paraphrase close enough
+//! in embedding space — is answered from the cache with an annotation
+//! instead of a new LLM call. Replies are only cached when exactly one
+//! question is pending in the group, so concurrent questions never get each
+//! other's answers.
+
+use crate::memory::vector::cosine_similarity;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A reply stored for a question.
+#[derive(Debug, Clone)]
+struct CachedAnswer {
+    answer: String,
+    answered_at: Instant,
+    /// Embedding of the normalized question, when semantic matching is on.
+    embedding: Option<Vec<f32>>,
+}
+
+/// A question handed to the agent and not answered yet.
+#[derive(Debug)]
+struct PendingQuestion {
+    key: String,
+    embedding: Option<Vec<f32>>,
+    asked_at: Instant,
+}
+
+/// Per-group question → answer cache with a short TTL.
+#[derive(Debug)]
+pub struct AnswerCache {
+    ttl: Duration,
+    max_entries: usize,
+    /// (group, question key) → answer.
+    entries: HashMap<(String, String), CachedAnswer>,
+    /// Questions handed to the agent and not answered yet, per group.
+    pending: HashMap<String, Vec<PendingQuestion>>,
+}
+
+impl AnswerCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries: max_entries.max(1),
+            entries: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Cached answer for `key` in `group` and its age, if still fresh.
+    pub fn lookup(&mut self, group: &str, key: &str, now: Instant) -> Option<(String, Duration)> {
+        let entry_key = (group.to_string(), key.to_string());
+        let entry = self.entries.get(&entry_key)?;
+        let age = now.saturating_duration_since(entry.answered_at);
+        if age > self.ttl {
+            self.entries.remove(&entry_key);
+            return None;
+        }
+        Some((entry.answer.clone(), age))
+    }
+
+    /// Closest fresh answer in `group` whose question embedding has at
+    /// least `threshold` cosine similarity to `embedding`, with its age and
+    /// the similarity.
+    pub fn lookup_similar(
+        &self,
+        group: &str,
+        embedding: &[f32],
+        threshold: f64,
+        now: Instant,
+    ) -> Option<(String, Duration, f32)> {
+        self.entries
+            .iter()
+            .filter(|((g, _), e)| {
+                g == group && now.saturating_duration_since(e.answered_at) <= self.ttl
+            })
+            .filter_map(|(_, e)| {
+                let similarity = cosine_similarity(e.embedding.as_deref()?, embedding);
+                (f64::from(similarity) >= threshold).then_some((e, similarity))
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(e, similarity)| {
+                (
+                    e.answer.clone(),
+                    now.saturating_duration_since(e.answered_at),
+                    similarity,
+                )
+            })
+    }
+
+    /// Mark a question as handed to the agent.
+    pub fn begin(&mut self, group: &str, key: &str, embedding: Option<Vec<f32>>, now: Instant) {
+        let ttl = self.ttl;
+        let pending = self.pending.entry(group.to_string()).or_default();
+        pending.retain(|p| now.saturating_duration_since(p.asked_at) <= ttl);
+        pending.push(PendingQuestion {
+            key: key.to_string(),
+            embedding,
+            asked_at: now,
+        });
+    }
+
+    /// Store the agent's reply to the group's pending question. Returns
+    /// false (and forgets the pending questions) when it can't be matched
+    /// to exactly one question.
+    pub fn complete(&mut self, group: &str, answer: &str, now: Instant) -> bool {
+        let Some(pending) = self.pending.remove(group) else {
+            return false;
+        };
+        let mut fresh = pending
+            .into_iter()
+            .filter(|p| now.saturating_duration_since(p.asked_at) <= self.ttl);
+        let (Some(question), None) = (fresh.next(), fresh.next()) else {
+            return false;
+        };
+        self.entries.insert(
+            (group.to_string(), question.key),
+            CachedAnswer {
+                answer: answer.to_string(),
+                answered_at: now,
+                embedding: question.embedding,
+            },
+        );
+        self.evict(now);
+        true
+    }
+
+    /// Forget pending questions for a group (the agent stayed silent).
+    pub fn abandon(&mut self, group: &str) {
+        self.pending.remove(group);
+    }
+
+    /// Drop expired answers, then the oldest ones beyond `max_entries`.
+    fn evict(&mut self, now: Instant) {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, e| now.saturating_duration_since(e.answered_at) <= ttl);
+        while self.entries.len() > self.max_entries {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.answered_at)
+                .map(|(k, _)| k.clone())
+            else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+/// Normalize a question: drop mentions, lowercase, collapse whitespace and
+/// strip trailing punctuation.
+pub fn normalize_question(text: &str) -> String {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .filter(|w| !(w.starts_with("nostr:") || w.starts_with("npub1") || w.starts_with('@')))
+        .map(str::to_lowercase)
+        .collect();
+    words
+        .join(" ")
+        .trim_end_matches(|c: char| c.is_ascii_punctuation() || c.is_whitespace())
+        .to_string()
+}
+
+/// Cache key for a question: SHA-256 of its normalized text, or `None` when
+/// nothing is left after normalizing.
+pub fn question_key(text: &str) -> Option<String> {
+    let normalized = normalize_question(text);
+    if normalized.is_empty() {
+        return None;
+    }
+    Some(hex::encode(Sha256::digest(normalized.as_bytes())))
+}
+
+/// Compact age for annotations ("42s", "3m").
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    if secs < 60 {
+        format!("{secs}s")
+    } else {
+        format!("{}m", secs / 60)
+    }
+}
+
+/// Cached reply with the annotation line on top; `{age}` is substituted.
+pub fn render_cached(annotation: &str, age: Duration, answer: &str) -> String {
+    format!(
+        "{}\n{}",
+        annotation.replace("{age}", &format_age(age)),
+        answer
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalization_ignores_case_mentions_and_punctuation() {
+        let a = question_key("nostr:npub1abc  What is the   relay URL??").unwrap();
+        let b = question_key("what is the relay url").unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, question_key("what is the relay port").unwrap());
+        assert!(question_key("@bot ?!").is_none());
+    }
+
+    #[test]
+    fn repeat_within_ttl_hits_cache() {
+        let mut cache = AnswerCache::new(Duration::from_secs(300), 16);
+        let t0 = Instant::now();
+        let key = question_key("when is the meetup?").unwrap();
+
+        assert!(cache.lookup("dev", &key, t0).is_none());
+        cache.begin("dev", &key, None, t0);
+        assert!(cache.complete("dev", "Friday at 18:00", t0));
+
+        let (answer, age) = cache
+            .lookup("dev", &key, t0 + Duration::from_secs(90))
+            .unwrap();
+        assert_eq!(answer, "Friday at 18:00");
+        assert_eq!(format_age(age), "1m");
+
+        // Per group, and gone after the TTL.
+        assert!(cache.lookup("ops", &key, t0).is_none());
+        assert!(cache
+            .lookup("dev", &key, t0 + Duration::from_secs(301))
+            .is_none());
+    }
+
+    #[test]
+    fn ambiguous_or_silent_replies_are_not_cached() {
+        let mut cache = AnswerCache::new(Duration::from_secs(300), 16);
+        let t0 = Instant::now();
+        let q1 = question_key("first question").unwrap();
+        let q2 = question_key("second question").unwrap();
+
+        cache.begin("dev", &q1, None, t0);
+        cache.begin("dev", &q2, None, t0);
+        assert!(!cache.complete("dev", "which one?", t0));
+        assert!(cache.lookup("dev", &q1, t0).is_none());
+
+        cache.begin("dev", &q1, None, t0);
+        cache.abandon("dev");
+        assert!(!cache.complete("dev", "unrelated announcement", t0));
+    }
+
+    #[test]
+    fn oldest_answers_are_evicted_beyond_capacity() {
+        let mut cache = AnswerCache::new(Duration::from_secs(300), 1);
+        let t0 = Instant::now();
+        let q1 = question_key("one").unwrap();
+        let q2 = question_key("two").unwrap();
+        cache.begin("dev", &q1, None, t0);
+        cache.complete("dev", "1", t0);
+        cache.begin("dev", &q2, None, t0 + Duration::from_secs(1));
+        cache.complete("dev", "2", t0 + Duration::from_secs(1));
+
+        assert!(cache.lookup("dev", &q1, t0).is_none());
+        assert!(cache.lookup("dev", &q2, t0).is_some());
+    }
+
+    #[test]
+    fn paraphrase_hits_by_embedding_similarity() {
+        let mut cache = AnswerCache::new(Duration::from_secs(300), 16);
+        let t0 = Instant::now();
+        let key = question_key("when is the meetup?").unwrap();
+        cache.begin("dev", &key, Some(vec![1.0, 0.0, 0.2]), t0);
+        assert!(cache.complete("dev", "Friday at 18:00", t0));
+
+        let (answer, _, similarity) = cache
+            .lookup_similar("dev", &[0.98, 0.05, 0.2], 0.9, t0)
+            .unwrap();
+        assert_eq!(answer, "Friday at 18:00");
+        assert!(similarity > 0.99);
+
+        // Unrelated questions, other groups and expired answers miss.
+        assert!(cache
+            .lookup_similar("dev", &[0.0, 1.0, 0.0], 0.9, t0)
+            .is_none());
+        assert!(cache
+            .lookup_similar("ops", &[1.0, 0.0, 0.2], 0.9, t0)
+            .is_none());
+        assert!(cache
+            .lookup_similar("dev", &[1.0, 0.0, 0.2], 0.9, t0 + Duration::from_secs(301))
+            .is_none());
+    }
+
+    #[test]
+    fn annotation_is_prepended() {
+        assert_eq!(
+            render_cached("♻️ answered {age} ago:", Duration::from_secs(42), "42"),
+            "♻️ answered 42s ago:\n42"
+        );
+    }
+}
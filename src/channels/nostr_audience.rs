@@ -0,0 +1,57 @@
+//! Audience of a Nostr conversation, for the context policy.
+//!
+//! Owner notes and private memories only go into the context of the
+//! owner's DMs; group replies get group and public context, other DMs
+//! public context only (see [`Audience`]). The channel registers its owners
+//! here so memory recall in the channel runtime, which only sees a reply
+//! target, can tell owner DMs apart.
+
+use parking_lot::RwLock;
+
+use crate::memory::Audience;
+
+static OWNERS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Register the hex pubkeys of the channel's owners (every `Role::Owner`).
+pub fn set_owners(owners_hex: Vec<String>) {
+    *OWNERS.write() = owners_hex;
+}
+
+/// Audience of a reply to `reply_target` (`#group` or a DM peer's hex
+/// pubkey), judged against the registered owners.
+pub fn audience(reply_target: &str) -> Audience {
+    audience_for(reply_target, &OWNERS.read())
+}
+
+/// Audience of a reply to `reply_target` when the owners are `owners_hex`.
+pub fn audience_for(reply_target: &str, owners_hex: &[String]) -> Audience {
+    if let Some(group) = reply_target.strip_prefix('#') {
+        return Audience::Group(group.to_string());
+    }
+    if owners_hex
+        .iter()
+        .any(|owner| owner.eq_ignore_ascii_case(reply_target))
+    {
+        Audience::OwnerDm
+    } else {
+        Audience::Dm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reply_targets_map_to_audiences() {
+        let owners = ["ab12cd".to_string(), "9f9f9f".to_string()];
+        assert_eq!(
+            audience_for("#infra", &owners),
+            Audience::Group("infra".into())
+        );
+        assert_eq!(audience_for("AB12CD", &owners), Audience::OwnerDm);
+        assert_eq!(audience_for("9f9f9f", &owners), Audience::OwnerDm);
+        assert_eq!(audience_for("ef34ab", &owners), Audience::Dm);
+        assert_eq!(audience_for("ab12cd", &[]), Audience::Dm);
+    }
+}
@@ -0,0 +1,249 @@
+//! Deep history backfill for newly joined groups.
+//!
+//! The startup ring-buffer backfill only covers the last few messages. With
+//! `[channels_config.nostr.backfill]` enabled, every configured group that is
+//! not yet recorded in `group_backfill.json` (under the channel persist dir)
+//! gets its last `days` of messages fetched page by page, newest first. The
+//! listener runs them through social memory and the message index without
+//! replying, then asks the agent for a summary of recent topics
+//! ([`summary_request`]); the reply comes back with the reply target
+//! `backfill:<group>` ([`summary_target`]) and is stored as a group note.
+//!
+//! A group is recorded once its messages are processed, so a backfill
+//! interrupted by a restart starts over for that group.
+
+use super::nostr_export::ExportMessage;
+use anyhow::{Context, Result};
+use chrono::{TimeZone, Utc};
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// State file name under the channel persist dir.
+const STATE_FILE: &str = "group_backfill.json";
+
+/// Reply target prefix that routes an agent reply into a group note.
+pub const SUMMARY_TARGET_PREFIX: &str = "backfill:";
+
+/// A finished group backfill.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupBackfill {
+    /// Unix seconds
+    pub completed_at: u64,
+    /// Messages fetched
+    pub messages: usize,
+}
+
+/// Persisted backfill state, keyed by group id.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackfillState {
+    #[serde(default)]
+    pub groups: BTreeMap<String, GroupBackfill>,
+}
+
+impl BackfillState {
+    pub fn path(persist_dir: &Path) -> PathBuf {
+        persist_dir.join(STATE_FILE)
+    }
+
+    /// Load state; empty when no state file exists yet.
+    pub fn load(persist_dir: &Path) -> Result<Self> {
+        let path = Self::path(persist_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&raw).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, persist_dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(persist_dir)?;
+        let path = Self::path(persist_dir);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Configured groups not backfilled yet, in config order.
+    pub fn pending(&self, groups: &[String]) -> Vec<String> {
+        let mut pending: Vec<String> = Vec::new();
+        for group in groups {
+            if !self.groups.contains_key(group) && !pending.contains(group) {
+                pending.push(group.clone());
+            }
+        }
+        pending
+    }
+
+    pub fn record(&mut self, group: &str, completed_at: u64, messages: usize) {
+        self.groups.insert(
+            group.to_string(),
+            GroupBackfill {
+                completed_at,
+                messages,
+            },
+        );
+    }
+}
+
+/// One page of group messages in `since..=until`, newest first.
+pub fn page_filter(group: &str, since: u64, until: u64, limit: usize) -> Filter {
+    Filter::new()
+        .kinds(vec![Kind::Custom(9), Kind::Custom(11), Kind::Custom(12)])
+        .custom_tag(SingleLetterTag::lowercase(Alphabet::H), group)
+        .since(Timestamp::from(since))
+        .until(Timestamp::from(until))
+        .limit(limit)
+}
+
+/// Events of a page not seen on earlier pages, and the `until` for the next
+/// page. Pages overlap by one second, since events sharing the oldest
+/// timestamp may be split across pages; `None` means the history is
+/// exhausted.
+pub fn next_page(events: Vec<Event>, seen: &mut HashSet<EventId>) -> (Vec<Event>, Option<u64>) {
+    let fresh: Vec<Event> = events.into_iter().filter(|e| seen.insert(e.id)).collect();
+    let until = fresh.iter().map(|e| e.created_at.as_secs()).min();
+    (fresh, until)
+}
+
+/// Reply target for the summary of `group`.
+pub fn summary_target(group: &str) -> String {
+    format!("{SUMMARY_TARGET_PREFIX}{group}")
+}
+
+/// Group id of a `backfill:<group>` reply target.
+pub fn parse_summary_target(target: &str) -> Option<&str> {
+    target
+        .strip_prefix(SUMMARY_TARGET_PREFIX)
+        .filter(|g| !g.is_empty())
+}
+
+/// Message asking the agent to summarize a backfilled group. `messages`
+/// are oldest first.
+pub fn summary_request(group: &str, days: u64, messages: &[ExportMessage]) -> String {
+    let mut out = format!(
+        "[nostr:backfill group=#{group} messages={}]\n\
+         You just joined #{group}. Below are its messages from the last {days} \
+         day(s). Write a short note for your own memory: the group's main \
+         topics, ongoing projects, decisions, open questions and who is \
+         active on what. Use short bullet points. Reply with the note only, \
+         or NO_REPLY if there is nothing worth keeping.\n\n",
+        messages.len()
+    );
+    for m in messages {
+        #[allow(clippy::cast_possible_wrap)]
+        let time = Utc
+            .timestamp_opt(m.timestamp as i64, 0)
+            .single()
+            .map(|t| t.format("%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let who = if m.from_agent {
+            "you"
+        } else {
+            m.sender.as_str()
+        };
+        let _ = writeln!(out, "[{time}] {who}: {}", m.content.trim());
+    }
+    out
+}
+
+/// Group note holding the agent's summary.
+pub fn group_note(summary: &str, date: &str) -> String {
+    format!(
+        "Recent topics (history backfill {date}):\n{}",
+        summary.trim()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(keys: &Keys, content: &str, at: u64) -> Event {
+        EventBuilder::new(Kind::Custom(9), content)
+            .tag(Tag::custom(TagKind::custom("h"), vec!["dev".to_string()]))
+            .custom_created_at(Timestamp::from(at))
+            .sign_with_keys(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn pending_skips_recorded_groups() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = BackfillState::load(dir.path()).unwrap();
+        let groups = vec!["dev".to_string(), "ops".to_string(), "dev".to_string()];
+        assert_eq!(state.pending(&groups), vec!["dev", "ops"]);
+
+        state.record("dev", 1_700_000_000, 42);
+        state.save(dir.path()).unwrap();
+        let state = BackfillState::load(dir.path()).unwrap();
+        assert_eq!(state.groups["dev"].messages, 42);
+        assert_eq!(state.pending(&groups), vec!["ops"]);
+    }
+
+    #[test]
+    fn pages_drop_repeated_events() {
+        let keys = Keys::generate();
+        let a = event(&keys, "a", 300);
+        let b = event(&keys, "b", 200);
+        let c = event(&keys, "c", 200);
+        let mut seen = HashSet::new();
+
+        let (fresh, until) = next_page(vec![a.clone(), b.clone()], &mut seen);
+        assert_eq!(fresh.len(), 2);
+        assert_eq!(until, Some(200));
+
+        // The next page repeats `b` at the boundary second
+        let (fresh, until) = next_page(vec![b, c.clone()], &mut seen);
+        assert_eq!(fresh, vec![c]);
+        assert_eq!(until, Some(200));
+
+        let (fresh, until) = next_page(vec![a], &mut seen);
+        assert!(fresh.is_empty());
+        assert_eq!(until, None);
+    }
+
+    #[test]
+    fn summary_targets_round_trip() {
+        assert_eq!(parse_summary_target(&summary_target("dev")), Some("dev"));
+        assert_eq!(parse_summary_target("backfill:"), None);
+        assert_eq!(parse_summary_target("#dev"), None);
+    }
+
+    #[test]
+    fn summary_request_lists_messages() {
+        let messages = vec![
+            ExportMessage {
+                timestamp: 1_772_000_000,
+                sender: "alice".into(),
+                npub: String::new(),
+                event_id: "a".into(),
+                from_agent: false,
+                content: "Release is Friday".into(),
+            },
+            ExportMessage {
+                timestamp: 1_772_000_060,
+                sender: "snowclaw".into(),
+                npub: String::new(),
+                event_id: "b".into(),
+                from_agent: true,
+                content: "Noted".into(),
+            },
+        ];
+        let request = summary_request("dev", 30, &messages);
+        assert!(request.starts_with("[nostr:backfill group=#dev messages=2]"));
+        assert!(request.contains("last 30 day(s)"));
+        assert!(request.contains("alice: Release is Friday"));
+        assert!(request.contains("you: Noted"));
+
+        assert_eq!(
+            group_note("  - release Friday\n", "2026-03-02"),
+            "Recent topics (history backfill 2026-03-02):\n- release Friday"
+        );
+    }
+}
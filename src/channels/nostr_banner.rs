@@ -0,0 +1,259 @@
+//! Startup/shutdown status DM to the owner (`[channels_config.nostr.banner]`).
+//!
+//! So operators notice unexpected restarts without watching logs, the Nostr
+//! channel DMs the owner once it is connected, and the daemon sends a short
+//! notice when it stops on SIGINT/SIGTERM. Both carry the same status lines:
+//! version, groups, queued outbox events, unpublished lessons and remaining
+//! cost budget.
+
+use super::{nostr_digest, nostr_outbox};
+use crate::config::{Config, CostConfig};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use nostr_sdk::prelude::*;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+/// Upper bound for connecting and sending the shutdown DM.
+const SHUTDOWN_SEND_TIMEOUT: Duration = Duration::from_secs(4);
+
+/// Which banner to send.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BannerKind<'a> {
+    Startup,
+    Shutdown { reason: &'a str },
+}
+
+/// Remaining cost budget (when `[cost]` is enabled).
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BudgetRemaining {
+    pub daily: f64,
+    pub daily_limit: f64,
+    pub monthly: f64,
+    pub monthly_limit: f64,
+}
+
+impl BudgetRemaining {
+    /// `Budget: …` status line text for the limits that are set, `None`
+    /// when neither is.
+    pub fn summary(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if self.daily_limit > 0.0 {
+            parts.push(format!(
+                "${:.2} of ${:.2} left today",
+                self.daily, self.daily_limit
+            ));
+        }
+        if self.monthly_limit > 0.0 {
+            parts.push(format!(
+                "${:.2} of ${:.2} this month",
+                self.monthly, self.monthly_limit
+            ));
+        }
+        (!parts.is_empty()).then(|| format!("Budget: {}", parts.join(", ")))
+    }
+}
+
+/// Status lines included in the banner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BannerStatus {
+    pub version: &'static str,
+    pub groups: Vec<String>,
+    /// Connected and configured relays (startup only).
+    pub relays: Option<(usize, usize)>,
+    /// Events queued in the channel's `nostr_outbox` spool.
+    pub pending_events: usize,
+    /// Agent lessons still waiting to be published as kind 4129.
+    pub pending_lessons: usize,
+    pub budget: Option<BudgetRemaining>,
+}
+
+/// Remaining daily and monthly budget, or `None` when cost tracking is off.
+pub fn budget_remaining(
+    cost: &CostConfig,
+    workspace_dir: &Path,
+    now: DateTime<Utc>,
+) -> Option<BudgetRemaining> {
+    if !cost.enabled {
+        return None;
+    }
+    let day_start = Utc.from_utc_datetime(&now.date_naive().and_hms_opt(0, 0, 0)?);
+    let month_start = Utc.from_utc_datetime(&now.date_naive().with_day(1)?.and_hms_opt(0, 0, 0)?);
+    let spent_today = nostr_digest::cost_stats(workspace_dir, day_start, now).total_cost;
+    let spent_month = nostr_digest::cost_stats(workspace_dir, month_start, now).total_cost;
+    Some(BudgetRemaining {
+        daily: (cost.daily_limit_usd - spent_today).max(0.0),
+        daily_limit: cost.daily_limit_usd,
+        monthly: (cost.monthly_limit_usd - spent_month).max(0.0),
+        monthly_limit: cost.monthly_limit_usd,
+    })
+}
+
+/// Number of unpublished agent lessons in `social.db`.
+pub fn pending_lessons(conn: &rusqlite::Connection) -> usize {
+    crate::tools::agent_lesson::fetch_unpublished(conn).map_or(0, |l| l.len())
+}
+
+/// Render the banner DM body.
+pub fn render(kind: BannerKind<'_>, status: &BannerStatus) -> String {
+    let mut out = String::new();
+    match kind {
+        BannerKind::Startup => {
+            let _ = writeln!(out, "🟢 Snowclaw {} started", status.version);
+        }
+        BannerKind::Shutdown { reason } => {
+            let _ = writeln!(out, "🔴 Snowclaw {} stopping: {reason}", status.version);
+        }
+    }
+    if status.groups.is_empty() {
+        out.push_str("Groups: none\n");
+    } else {
+        let groups: Vec<String> = status.groups.iter().map(|g| format!("#{g}")).collect();
+        let _ = writeln!(out, "Groups: {}", groups.join(", "));
+    }
+    if let Some((connected, total)) = status.relays {
+        let _ = writeln!(out, "Relays: {connected}/{total} connected");
+    }
+    let _ = writeln!(
+        out,
+        "Outbox: {} queued event(s), {} pending lesson(s)",
+        status.pending_events, status.pending_lessons
+    );
+    if let Some(budget) = status.budget.as_ref().and_then(BudgetRemaining::summary) {
+        let _ = writeln!(out, "{budget}");
+    }
+    out
+}
+
+/// DM the shutdown banner to the owner from a short-lived client.
+///
+/// Called by the daemon after a shutdown signal, before channel tasks are
+/// aborted. Does nothing unless `banner.shutdown` is set and an owner is
+/// configured.
+pub async fn send_shutdown(config: &Config, reason: &str) -> Result<()> {
+    let Some(ns) = config.channels_config.nostr.as_ref() else {
+        return Ok(());
+    };
+    if !ns.banner.shutdown {
+        return Ok(());
+    }
+    let Some(owner) = ns.owner.as_deref().and_then(|s| PublicKey::parse(s).ok()) else {
+        return Ok(());
+    };
+    let persist_dir = config.config_path.parent().unwrap_or(Path::new("."));
+    let Some(signer) = super::snowclaw_channels::nostr_signer(ns, persist_dir).await? else {
+        return Ok(());
+    };
+
+    let db_path = persist_dir.join("social.db");
+    let pending = if db_path.exists() {
+        crate::security::db_encryption::open(&db_path)
+            .map(|conn| pending_lessons(&conn))
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    let queued = nostr_outbox::read_status(&persist_dir.join(nostr_outbox::OUTBOX_FILE))
+        .ok()
+        .flatten()
+        .map_or(0, |outbox| outbox.pending);
+    let status = BannerStatus {
+        version: env!("CARGO_PKG_VERSION"),
+        groups: ns.groups.clone(),
+        relays: None,
+        pending_events: queued,
+        pending_lessons: pending,
+        budget: budget_remaining(&config.cost, &config.workspace_dir, Utc::now()),
+    };
+    let text = render(BannerKind::Shutdown { reason }, &status);
+
+    let client = super::snowclaw_channels::nostr_client(signer, &ns.relays).await?;
+    client.connect().await;
+    let sent = tokio::time::timeout(
+        SHUTDOWN_SEND_TIMEOUT,
+        client.send_private_msg(owner, text, Vec::<Tag>::new()),
+    )
+    .await;
+    client.disconnect().await;
+    sent.context("Timed out sending shutdown banner")?
+        .context("Failed to send shutdown banner")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> BannerStatus {
+        BannerStatus {
+            version: "0.5.0",
+            groups: vec!["dev".into(), "ops".into()],
+            relays: Some((2, 3)),
+            pending_events: 4,
+            pending_lessons: 1,
+            budget: Some(BudgetRemaining {
+                daily: 4.2,
+                daily_limit: 10.0,
+                monthly: 80.0,
+                monthly_limit: 100.0,
+            }),
+        }
+    }
+
+    #[test]
+    fn startup_lists_status_lines() {
+        let text = render(BannerKind::Startup, &status());
+        assert_eq!(
+            text,
+            "🟢 Snowclaw 0.5.0 started\n\
+             Groups: #dev, #ops\n\
+             Relays: 2/3 connected\n\
+             Outbox: 4 queued event(s), 1 pending lesson(s)\n\
+             Budget: $4.20 of $10.00 left today, $80.00 of $100.00 this month\n"
+        );
+    }
+
+    #[test]
+    fn shutdown_includes_reason_and_skips_unknowns() {
+        let mut status = status();
+        status.groups.clear();
+        status.relays = None;
+        status.budget = None;
+        let text = render(
+            BannerKind::Shutdown {
+                reason: "shutdown requested (SIGTERM)",
+            },
+            &status,
+        );
+        assert!(text.starts_with("🔴 Snowclaw 0.5.0 stopping: shutdown requested (SIGTERM)\n"));
+        assert!(text.contains("Groups: none\n"));
+        assert!(!text.contains("Relays"));
+        assert!(!text.contains("Budget"));
+    }
+
+    #[test]
+    fn budget_line_lists_only_set_limits() {
+        let mut budget = status().budget.unwrap();
+        budget.daily_limit = 0.0;
+        assert_eq!(
+            budget.summary().as_deref(),
+            Some("Budget: $80.00 of $100.00 this month")
+        );
+        budget.monthly_limit = 0.0;
+        assert_eq!(budget.summary(), None);
+
+        let mut status = status();
+        status.budget = Some(budget);
+        assert!(!render(BannerKind::Startup, &status).contains("Budget"));
+    }
+
+    #[test]
+    fn budget_is_none_when_cost_tracking_disabled() {
+        let cost = CostConfig {
+            enabled: false,
+            ..CostConfig::default()
+        };
+        assert!(budget_remaining(&cost, Path::new("/nonexistent"), Utc::now()).is_none());
+    }
+}
@@ -0,0 +1,136 @@
+//! Per-group read markers and "catch me up" summaries.
+//!
+//! The Nostr listener keeps the last group message read by the owner (their
+//! own posts, delivered catch-ups) and by the agent (messages handed to it)
+//! in `social_read_markers`, optionally mirrored as NIP-78 events. A
+//! catch-up — the owner typing `catch me up` in a group or the owner-only
+//! `conversation.catchup` action — collects everything after the owner's
+//! marker, asks the agent to summarize it for the owner by DM and moves the
+//! marker to the newest message included.
+
+use super::nostr_export::ExportMessage;
+use crate::memory::social::ReadMarker;
+use chrono::{TimeZone, Utc};
+use std::fmt::Write as _;
+
+/// Reader whose marker moves when the owner posts or gets a catch-up.
+pub const READER_OWNER: &str = "owner";
+/// Reader whose marker moves when a group message is handed to the agent.
+pub const READER_AGENT: &str = "agent";
+
+/// Whether an owner group message asks for a catch-up.
+pub fn is_catchup_command(content: &str) -> bool {
+    let cmd = content
+        .trim()
+        .trim_end_matches(['!', '.', '?'])
+        .to_lowercase();
+    matches!(cmd.as_str(), "catch me up" | "catchup" | "catch up")
+}
+
+/// Messages after `marker`, oldest first. Without a marker everything is
+/// unread. Messages from the marker's own second are kept unless they are
+/// the marker event itself.
+pub fn unread(messages: Vec<ExportMessage>, marker: Option<&ReadMarker>) -> Vec<ExportMessage> {
+    let Some(marker) = marker else {
+        return messages;
+    };
+    messages
+        .into_iter()
+        .filter(|m| {
+            #[allow(clippy::cast_possible_wrap)]
+            let at = m.timestamp as i64;
+            at > marker.read_at || (at == marker.read_at && m.event_id != marker.event_id)
+        })
+        .collect()
+}
+
+/// Agent request to summarize `messages` from `#group` for the owner.
+/// `since` is the previous marker time, if any.
+pub fn summary_request(group: &str, messages: &[ExportMessage], since: Option<i64>) -> String {
+    let since = since
+        .and_then(|ts| Utc.timestamp_opt(ts, 0).single())
+        .map_or_else(
+            || "the start of the retained history".to_string(),
+            |t| t.format("%Y-%m-%d %H:%M UTC").to_string(),
+        );
+    let mut out = format!(
+        "[nostr:catchup group=#{group} messages={}]\n\
+         The owner asked to be caught up on #{group}. Summarize the messages \
+         below, sent since {since}: main topics, decisions, open questions, \
+         and anything addressed to the owner or to you. Be concise; use short \
+         bullet points and name who said what when it matters. Reply with the \
+         summary only.\n\n",
+        messages.len()
+    );
+    for m in messages {
+        #[allow(clippy::cast_possible_wrap)]
+        let time = Utc
+            .timestamp_opt(m.timestamp as i64, 0)
+            .single()
+            .map(|t| t.format("%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let who = if m.from_agent {
+            "you"
+        } else {
+            m.sender.as_str()
+        };
+        let _ = writeln!(out, "[{time}] {who}: {}", m.content.trim());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg(id: &str, sender: &str, ts: u64) -> ExportMessage {
+        ExportMessage {
+            timestamp: ts,
+            sender: sender.to_string(),
+            npub: String::new(),
+            event_id: id.to_string(),
+            from_agent: sender == "snowclaw",
+            content: format!("message {id}"),
+        }
+    }
+
+    #[test]
+    fn recognizes_catchup_commands() {
+        assert!(is_catchup_command("Catch me up!"));
+        assert!(is_catchup_command("  catchup "));
+        assert!(!is_catchup_command("can you catch me up on the release?"));
+    }
+
+    #[test]
+    fn unread_starts_after_marker() {
+        let messages = vec![
+            msg("a", "alice", 100),
+            msg("b", "bob", 200),
+            msg("c", "carol", 200),
+            msg("d", "snowclaw", 300),
+        ];
+        let marker = ReadMarker {
+            reader: READER_OWNER.to_string(),
+            group_id: "dev".to_string(),
+            event_id: "b".to_string(),
+            read_at: 200,
+            updated_at: 0,
+        };
+        let ids: Vec<String> = unread(messages.clone(), Some(&marker))
+            .into_iter()
+            .map(|m| m.event_id)
+            .collect();
+        assert_eq!(ids, vec!["c", "d"]);
+        assert_eq!(unread(messages, None).len(), 4);
+    }
+
+    #[test]
+    fn summary_request_lists_messages() {
+        let messages = vec![msg("a", "alice", 0), msg("d", "snowclaw", 60)];
+        let text = summary_request("dev", &messages, None);
+        assert!(text.starts_with("[nostr:catchup group=#dev messages=2]"));
+        assert!(text.contains("since the start of the retained history"));
+        assert!(text.contains("[01-01 00:00] alice: message a\n"));
+        assert!(text.contains("[01-01 00:01] you: message d\n"));
+    }
+}
@@ -0,0 +1,1178 @@
+//! Owner command palette: slash commands the owner sends to the agent by DM.
+//!
+//! Lets the owner manage the agent from any Nostr client with commands such
+//! as `/status`, `/config set #dev respond_mode=all`, `/memory note @npub1…
+//! prefers short answers`, `/costs today` or `/mute #dev 2h`. Commands are
+//! parsed and validated here. Most map onto the kind 1121 action handlers of
+//! the Nostr channel; help, status and costs are answered directly.
+
+use crate::stats::StatsResult;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use nostr_sdk::prelude::*;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use super::nostr_banner::BudgetRemaining;
+use super::nostr_deletion;
+
+/// Mute length when `/mute` has no duration.
+pub const DEFAULT_MUTE: Duration = Duration::from_secs(3600);
+
+/// Longest accepted mute.
+pub const MAX_MUTE: Duration = Duration::from_secs(30 * 86_400);
+
+/// Longest string value shown per field in a rendered reply.
+const MAX_VALUE_CHARS: usize = 200;
+
+/// Rows shown per breakdown in `/costs`.
+const MAX_COST_ROWS: usize = 5;
+
+/// Period summarised by `/costs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostPeriod {
+    /// Since 00:00 UTC.
+    Today,
+    /// The last 7 days.
+    Week,
+    /// Since the first of the month, 00:00 UTC.
+    Month,
+}
+
+impl CostPeriod {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "" | "today" | "day" => Some(Self::Today),
+            "week" | "7d" => Some(Self::Week),
+            "month" | "mtd" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Today => "today",
+            Self::Week => "last 7 days",
+            Self::Month => "this month",
+        }
+    }
+
+    /// Start of the period ending at `now`.
+    pub fn since(self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let midnight = |date: chrono::NaiveDate| {
+            date.and_hms_opt(0, 0, 0)
+                .map_or(now, |dt| Utc.from_utc_datetime(&dt))
+        };
+        match self {
+            Self::Today => midnight(now.date_naive()),
+            Self::Week => now - chrono::Duration::days(7),
+            Self::Month => now.date_naive().with_day(1).map_or(now, midnight),
+        }
+    }
+}
+
+/// A parsed owner command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OwnerCommand {
+    /// Command list, or usage of one command.
+    Help(Option<String>),
+    Status,
+    Costs(CostPeriod),
+    /// Timed mute of a group, or of all groups when `group` is `None`.
+    Mute {
+        group: Option<String>,
+        duration: Duration,
+    },
+    Unmute {
+        group: Option<String>,
+    },
+    /// Run a kind 1121 action handler with these params.
+    Action {
+        action: &'static str,
+        params: Vec<(String, String)>,
+        group: Option<String>,
+    },
+}
+
+struct CommandSpec {
+    name: &'static str,
+    usage: &'static str,
+    summary: &'static str,
+}
+
+const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "help",
+        usage: "/help [command]",
+        summary: "List commands or show how to use one",
+    },
+    CommandSpec {
+        name: "status",
+        usage: "/status",
+        summary: "Version, groups, relays, mutes, outbox and budget",
+    },
+    CommandSpec {
+        name: "config",
+        usage: "/config get [#group]\n/config set [#group] key=value …",
+        summary: "Show or change respond_mode, context_history or model_profile \
+                  (global without #group)",
+    },
+    CommandSpec {
+        name: "stop",
+        usage: "/stop [#group]",
+        summary: "Stop replying in a group, or in all groups",
+    },
+    CommandSpec {
+        name: "resume",
+        usage: "/resume [#group] [all|mention|owner]",
+        summary: "Resume replying (default: mention)",
+    },
+    CommandSpec {
+        name: "mute",
+        usage: "/mute [#group] [duration]",
+        summary: "Silence a group, or all groups, for a while, then restore the respond mode \
+                  (default 1h; e.g. 30m, 2h, 1d)",
+    },
+    CommandSpec {
+        name: "unmute",
+        usage: "/unmute [#group]",
+        summary: "End a mute early",
+    },
+    CommandSpec {
+        name: "memory",
+        usage: "/memory note @npub|#group <text>\n/memory search <query>\n/memory forget <key>\n\
+                /memory pending\n/memory approve|reject <id>",
+        summary: "Add an owner note, search memory, forget an entry or decide on writes \
+                  waiting for approval",
+    },
+    CommandSpec {
+        name: "costs",
+        usage: "/costs [today|week|month]",
+        summary: "LLM spend and requests for the period (UTC)",
+    },
+    CommandSpec {
+        name: "catchup",
+        usage: "/catchup #group",
+        summary: "Summary of what you missed in a group",
+    },
+    CommandSpec {
+        name: "export",
+        usage: "/export #group|@npub [markdown|jsonl]",
+        summary: "DM a conversation transcript",
+    },
+    CommandSpec {
+        name: "delete",
+        usage: "/delete [#group] <note1…|nevent1…|hex> …",
+        summary: "Ask relays to delete the agent's own events (NIP-09) and purge local copies",
+    },
+    CommandSpec {
+        name: "quota",
+        usage: "/quota @npub reset|unlimited|requests=N|cost=USD",
+        summary: "Override a contact's daily quota until 00:00 UTC",
+    },
+    CommandSpec {
+        name: "drafts",
+        usage: "/drafts",
+        summary: "Proactive posts waiting for approval or publishing",
+    },
+    CommandSpec {
+        name: "approve",
+        usage: "/approve <id> [edited text]",
+        summary: "Approve a proactive post draft, optionally replacing its text",
+    },
+    CommandSpec {
+        name: "reject",
+        usage: "/reject <id>",
+        summary: "Discard a proactive post draft",
+    },
+];
+
+/// Parse a DM as an owner command. `None` when it is not a command (no
+/// leading `/` followed by a word), so it goes to the agent as usual.
+pub fn parse(text: &str) -> Option<Result<OwnerCommand, String>> {
+    let body = text.trim().strip_prefix('/')?;
+    let (name, rest) = split_word(body);
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some(parse_command(&name.to_ascii_lowercase(), rest))
+}
+
+fn parse_command(name: &str, rest: &str) -> Result<OwnerCommand, String> {
+    let name = canonical_name(name);
+    let usage = || usage_error(name);
+    match name {
+        "help" => {
+            let (topic, extra) = split_word(rest);
+            if !extra.is_empty() {
+                return Err(usage());
+            }
+            Ok(OwnerCommand::Help((!topic.is_empty()).then(|| {
+                topic.trim_start_matches('/').to_ascii_lowercase()
+            })))
+        }
+        "status" => {
+            if !rest.is_empty() {
+                return Err(usage());
+            }
+            Ok(OwnerCommand::Status)
+        }
+        "config" => parse_config(rest).map_err(|e| with_usage(name, e)),
+        "stop" => {
+            let (group, extra) = optional_group(rest).map_err(|e| with_usage(name, e))?;
+            if !extra.is_empty() {
+                return Err(usage());
+            }
+            Ok(action("control.stop", vec![], group))
+        }
+        "resume" => {
+            let (group, rest) = optional_group(rest).map_err(|e| with_usage(name, e))?;
+            let (mode, extra) = split_word(rest);
+            if !extra.is_empty() {
+                return Err(usage());
+            }
+            let mut params = Vec::new();
+            if !mode.is_empty() {
+                let mode = mode.to_ascii_lowercase();
+                if !matches!(mode.as_str(), "all" | "mention" | "owner") {
+                    return Err(with_usage(
+                        name,
+                        format!("unknown mode '{mode}' (expected all, mention or owner)"),
+                    ));
+                }
+                params.push(("mode".to_string(), mode));
+            }
+            Ok(action("control.resume", params, group))
+        }
+        "mute" => {
+            // `#group`, a bare group name, or no group (all groups)
+            let (first, after) = split_word(rest);
+            let (group, rest) = if first.starts_with('#') {
+                (
+                    Some(parse_group(first).map_err(|e| with_usage(name, e))?),
+                    after,
+                )
+            } else if first.is_empty() || parse_duration(rest).is_some() {
+                (None, rest)
+            } else {
+                (Some(first.to_string()), after)
+            };
+            let duration = if rest.is_empty() {
+                DEFAULT_MUTE
+            } else {
+                parse_duration(rest).ok_or_else(|| {
+                    with_usage(
+                        name,
+                        format!("invalid duration '{rest}' (e.g. 30m, 2h, 1d)"),
+                    )
+                })?
+            };
+            if duration.is_zero() || duration > MAX_MUTE {
+                return Err(with_usage(
+                    name,
+                    "duration must be between 1s and 30d".to_string(),
+                ));
+            }
+            Ok(OwnerCommand::Mute { group, duration })
+        }
+        "unmute" => {
+            let (group, extra) = split_word(rest);
+            if !extra.is_empty() {
+                return Err(usage());
+            }
+            let group = (!group.is_empty())
+                .then(|| parse_group(group))
+                .transpose()
+                .map_err(|e| with_usage(name, e))?;
+            Ok(OwnerCommand::Unmute { group })
+        }
+        "memory" => parse_memory(rest).map_err(|e| with_usage(name, e)),
+        "costs" => {
+            let period = rest.to_ascii_lowercase();
+            CostPeriod::parse(&period)
+                .map(OwnerCommand::Costs)
+                .ok_or_else(usage)
+        }
+        "catchup" => {
+            let (group, extra) = split_word(rest);
+            if !extra.is_empty() {
+                return Err(usage());
+            }
+            let group = parse_group(group).map_err(|e| with_usage(name, e))?;
+            Ok(action(
+                "conversation.catchup",
+                vec![("group".to_string(), group)],
+                None,
+            ))
+        }
+        "export" => {
+            let (target, rest) = split_word(rest);
+            let (format, extra) = split_word(rest);
+            if target.is_empty() || !extra.is_empty() {
+                return Err(usage());
+            }
+            let target = if target.starts_with('#') {
+                format!("#{}", parse_group(target).map_err(|e| with_usage(name, e))?)
+            } else {
+                parse_npub(target).map_err(|e| with_usage(name, e))?
+            };
+            let mut params = vec![("target".to_string(), target)];
+            if !format.is_empty() {
+                let format = format.to_ascii_lowercase();
+                if !matches!(format.as_str(), "markdown" | "md" | "jsonl" | "ndjson") {
+                    return Err(with_usage(
+                        name,
+                        format!("unknown format '{format}' (expected markdown or jsonl)"),
+                    ));
+                }
+                params.push(("format".to_string(), format));
+            }
+            Ok(action("conversation.export", params, None))
+        }
+        "delete" => {
+            let (group, rest) = optional_group(rest).map_err(|e| with_usage(name, e))?;
+            let ids = nostr_deletion::parse_event_ids(rest).map_err(|e| with_usage(name, e))?;
+            let ids: Vec<String> = ids.iter().map(|id| id.to_hex()).collect();
+            Ok(action(
+                "events.delete",
+                vec![("ids".to_string(), ids.join(","))],
+                group,
+            ))
+        }
+        "quota" => parse_quota(rest).map_err(|e| with_usage(name, e)),
+        "drafts" => {
+            if !rest.is_empty() {
+                return Err(usage());
+            }
+            Ok(action("proactive.list", vec![], None))
+        }
+        "approve" | "reject" => {
+            let (id, text) = split_word(rest);
+            if id.is_empty() || (name == "reject" && !text.is_empty()) {
+                return Err(usage());
+            }
+            let id = id.trim_start_matches('#');
+            id.parse::<i64>()
+                .map_err(|_| with_usage(name, format!("'{id}' is not a draft id")))?;
+            let mut params = vec![("id".to_string(), id.to_string())];
+            if !text.is_empty() {
+                params.push(("content".to_string(), text.to_string()));
+            }
+            let action_name = if name == "approve" {
+                "proactive.approve"
+            } else {
+                "proactive.reject"
+            };
+            Ok(action(action_name, params, None))
+        }
+        other => Err(format!(
+            "Unknown command /{other}. Send /help for the list."
+        )),
+    }
+}
+
+/// Map aliases to the command name used in [`COMMANDS`].
+fn canonical_name(name: &str) -> &str {
+    match name {
+        "h" | "commands" => "help",
+        "halt" => "stop",
+        "mem" => "memory",
+        "cost" | "spend" => "costs",
+        other => other,
+    }
+}
+
+fn action(
+    action: &'static str,
+    params: Vec<(String, String)>,
+    group: Option<String>,
+) -> OwnerCommand {
+    OwnerCommand::Action {
+        action,
+        params,
+        group,
+    }
+}
+
+fn parse_config(rest: &str) -> Result<OwnerCommand, String> {
+    let (sub, rest) = split_word(rest);
+    match sub.to_ascii_lowercase().as_str() {
+        "get" | "show" | "" => {
+            let (group, extra) = optional_group(rest)?;
+            if !extra.is_empty() {
+                return Err(format!("unexpected '{extra}'"));
+            }
+            Ok(action("config.get", vec![], group))
+        }
+        "set" => {
+            let (group, rest) = optional_group(rest)?;
+            let mut params: Vec<(String, String)> = Vec::new();
+            let mut words = rest.split_whitespace();
+            while let Some(word) = words.next() {
+                let (key, value) = match word.split_once('=') {
+                    Some((key, value)) => (key.to_string(), value.to_string()),
+                    None => {
+                        let value = words
+                            .next()
+                            .ok_or_else(|| format!("missing value for '{word}'"))?;
+                        (word.to_string(), value.to_string())
+                    }
+                };
+                let (key, value) = config_value(&key, &value)?;
+                params.retain(|(k, _)| *k != key);
+                params.push((key, value));
+            }
+            if params.is_empty() {
+                return Err("nothing to set".to_string());
+            }
+            Ok(action("config.set", params, group))
+        }
+        other => Err(format!("unknown subcommand '{other}'")),
+    }
+}
+
+/// Validate one `/config set` pair and return it under its canonical key.
+fn config_value(key: &str, value: &str) -> Result<(String, String), String> {
+    let key = key.to_ascii_lowercase();
+    match key.as_str() {
+        "respond_mode" | "mode" => {
+            let value = value.to_ascii_lowercase();
+            if !matches!(
+                value.as_str(),
+                "all" | "mention" | "owner" | "none" | "silent" | "listen"
+            ) {
+                return Err(format!(
+                    "invalid respond_mode '{value}' (expected all, mention, owner or none)"
+                ));
+            }
+            Ok(("respond_mode".to_string(), value))
+        }
+        "context_history" | "history" => {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("context_history must be a number, got '{value}'"))?;
+            Ok(("context_history".to_string(), value.to_string()))
+        }
+        "model_profile" | "profile" => Ok(("model_profile".to_string(), value.to_string())),
+        _ => Err(format!(
+            "unknown key '{key}' (expected respond_mode, context_history or model_profile)"
+        )),
+    }
+}
+
+fn parse_memory(rest: &str) -> Result<OwnerCommand, String> {
+    let (sub, rest) = split_word(rest);
+    match sub.to_ascii_lowercase().as_str() {
+        "note" => {
+            let (target, note) = split_word(rest);
+            if target.is_empty() || note.is_empty() {
+                return Err("a target and a note are required".to_string());
+            }
+            let params = vec![("note".to_string(), note.to_string())];
+            if target.starts_with('#') {
+                Ok(action("memory.note", params, Some(parse_group(target)?)))
+            } else {
+                let mut params = params;
+                params.push(("npub".to_string(), parse_npub(target)?));
+                Ok(action("memory.note", params, None))
+            }
+        }
+        "search" | "find" => {
+            if rest.is_empty() {
+                return Err("a query is required".to_string());
+            }
+            Ok(action(
+                "memory.search",
+                vec![("query".to_string(), rest.to_string())],
+                None,
+            ))
+        }
+        "forget" => {
+            let (key, extra) = split_word(rest);
+            if key.is_empty() || !extra.is_empty() {
+                return Err("exactly one key is required".to_string());
+            }
+            Ok(action(
+                "memory.forget",
+                vec![("key".to_string(), key.to_string())],
+                None,
+            ))
+        }
+        "pending" => {
+            if !rest.is_empty() {
+                return Err("pending takes no arguments".to_string());
+            }
+            Ok(action("memory.pending", vec![], None))
+        }
+        sub @ ("approve" | "reject") => {
+            let (id, extra) = split_word(rest);
+            let id = id.trim_start_matches('#');
+            if id.is_empty() || !extra.is_empty() {
+                return Err("exactly one id is required".to_string());
+            }
+            id.parse::<u64>()
+                .map_err(|_| format!("'{id}' is not a pending write id"))?;
+            Ok(action(
+                if sub == "approve" {
+                    "memory.approve"
+                } else {
+                    "memory.reject"
+                },
+                vec![("id".to_string(), id.to_string())],
+                None,
+            ))
+        }
+        "" => Err("a subcommand is required".to_string()),
+        other => Err(format!("unknown subcommand '{other}'")),
+    }
+}
+
+fn parse_quota(rest: &str) -> Result<OwnerCommand, String> {
+    let (target, rest) = split_word(rest);
+    if target.is_empty() || rest.is_empty() {
+        return Err("a contact and a change are required".to_string());
+    }
+    let mut params = vec![("npub".to_string(), parse_npub(target)?)];
+    for word in rest.split_whitespace() {
+        let word = word.to_ascii_lowercase();
+        match word.split_once('=') {
+            None if word == "reset" || word == "unlimited" => {
+                params.push((word, "true".to_string()));
+            }
+            Some(("requests", value)) => {
+                value
+                    .parse::<u32>()
+                    .map_err(|_| format!("requests must be a whole number, got '{value}'"))?;
+                params.push(("requests".to_string(), value.to_string()));
+            }
+            Some(("cost" | "cost_usd", value)) => {
+                let usd = value
+                    .trim_start_matches('$')
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|usd| usd.is_finite() && *usd >= 0.0)
+                    .ok_or_else(|| format!("cost must be a non-negative amount, got '{value}'"))?;
+                params.push(("cost_usd".to_string(), usd.to_string()));
+            }
+            _ => return Err(format!("unknown change '{word}'")),
+        }
+    }
+    Ok(action("quota.override", params, None))
+}
+
+/// `#group` (or a bare group name) → group id.
+fn parse_group(word: &str) -> Result<String, String> {
+    let group = word.trim_start_matches('#');
+    if group.is_empty() {
+        return Err("a #group is required".to_string());
+    }
+    Ok(group.to_string())
+}
+
+/// Leading `#group`, if the first word is one.
+fn optional_group(rest: &str) -> Result<(Option<String>, &str), String> {
+    let (word, after) = split_word(rest);
+    if word.starts_with('#') {
+        Ok((Some(parse_group(word)?), after))
+    } else {
+        Ok((None, rest))
+    }
+}
+
+/// `@npub1…`, `nostr:npub1…`, `npub1…` or hex → hex pubkey.
+fn parse_npub(word: &str) -> Result<String, String> {
+    let raw = word.trim_start_matches('@');
+    let raw = raw.strip_prefix("nostr:").unwrap_or(raw);
+    PublicKey::parse(raw)
+        .map(|pk| pk.to_hex())
+        .map_err(|_| format!("'{word}' is not an npub or hex pubkey"))
+}
+
+/// First whitespace-separated word and the trimmed remainder.
+fn split_word(s: &str) -> (&str, &str) {
+    let s = s.trim();
+    match s.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (s, ""),
+    }
+}
+
+/// Parse `2h`, `30m`, `1h30m`, `2 hours` or `1d` into a duration.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let compact: String = s
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if compact.is_empty() {
+        return None;
+    }
+    let mut total: u64 = 0;
+    let mut rest = compact.as_str();
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return None;
+        }
+        let value: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let secs = match &rest[..unit_len] {
+            "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3600,
+            "d" | "day" | "days" => 86_400,
+            _ => return None,
+        };
+        rest = &rest[unit_len..];
+        total = total.checked_add(value.checked_mul(secs)?)?;
+    }
+    Some(Duration::from_secs(total))
+}
+
+/// Compact duration for replies: `45s`, `30m`, `1h30m`, `2d3h`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, mins) = (secs / 86_400, (secs % 86_400) / 3600, (secs % 3600) / 60);
+    let mut out = String::new();
+    if days > 0 {
+        let _ = write!(out, "{days}d");
+    }
+    if hours > 0 {
+        let _ = write!(out, "{hours}h");
+    }
+    if mins > 0 && days == 0 {
+        let _ = write!(out, "{mins}m");
+    }
+    if out.is_empty() {
+        let _ = write!(out, "{secs}s");
+    }
+    out
+}
+
+fn usage_error(name: &str) -> String {
+    match COMMANDS.iter().find(|c| c.name == name) {
+        Some(spec) => format!("Usage:\n{}", spec.usage),
+        None => format!("Unknown command /{name}. Send /help for the list."),
+    }
+}
+
+fn with_usage(name: &str, error: String) -> String {
+    format!("/{name}: {error}\n{}", usage_error(name))
+}
+
+/// `/help` reply: the command list, or usage of `topic`.
+pub fn help(topic: Option<&str>) -> String {
+    if let Some(topic) = topic {
+        let name = canonical_name(topic);
+        return match COMMANDS.iter().find(|c| c.name == name) {
+            Some(spec) => format!("{}\n{}", spec.usage, spec.summary),
+            None => format!("Unknown command /{topic}. Send /help for the list."),
+        };
+    }
+    let mut out = String::from("Owner commands:\n");
+    for spec in COMMANDS {
+        let _ = writeln!(out, "/{} — {}", spec.name, spec.summary);
+    }
+    out.push_str("Send /help <command> for usage.");
+    out
+}
+
+/// One configured group in `/status`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupStatus {
+    pub name: String,
+    /// Effective respond mode.
+    pub mode: String,
+    /// Time left on a `/mute`.
+    pub muted_for: Option<Duration>,
+}
+
+/// One owner, admin or operator in `/status`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoleStatus {
+    pub name: String,
+    pub role: &'static str,
+    /// NIP-AE ownership confirmed by a kind 14199 claim.
+    pub verified: bool,
+}
+
+/// Lines shown by `/status`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnerStatus {
+    pub version: &'static str,
+    pub groups: Vec<GroupStatus>,
+    /// Connected and configured relays.
+    pub relays: (usize, usize),
+    /// Events waiting in the outbox.
+    pub outbox_pending: usize,
+    /// Long-running actions in progress.
+    pub running_actions: usize,
+    pub budget: Option<BudgetRemaining>,
+    pub owners: Vec<RoleStatus>,
+}
+
+/// Render the `/status` reply.
+pub fn render_status(status: &OwnerStatus) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Snowclaw {}", status.version);
+    let _ = writeln!(
+        out,
+        "Relays: {}/{} connected",
+        status.relays.0, status.relays.1
+    );
+    if status.groups.is_empty() {
+        out.push_str("Groups: none\n");
+    } else {
+        out.push_str("Groups:\n");
+        for group in &status.groups {
+            let _ = write!(out, "- #{}: {}", group.name, group.mode);
+            if let Some(left) = group.muted_for {
+                let _ = write!(out, " (muted, {} left)", format_duration(left));
+            }
+            out.push('\n');
+        }
+    }
+    if !status.owners.is_empty() {
+        let owners: Vec<String> = status
+            .owners
+            .iter()
+            .map(|o| {
+                let verified = if o.verified { ", NIP-AE ✓" } else { "" };
+                format!("{} ({}{verified})", o.name, o.role)
+            })
+            .collect();
+        let _ = writeln!(out, "Owners: {}", owners.join(", "));
+    }
+    let _ = writeln!(out, "Outbox: {} pending event(s)", status.outbox_pending);
+    if status.running_actions > 0 {
+        let _ = writeln!(out, "Running actions: {}", status.running_actions);
+    }
+    if let Some(budget) = status.budget.as_ref().and_then(BudgetRemaining::summary) {
+        let _ = writeln!(out, "{budget}");
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+/// Render the `/costs` reply.
+pub fn render_costs(period: CostPeriod, costs: &StatsResult) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Costs {} (UTC): ${:.4} over {} request(s)",
+        period.label(),
+        costs.total_cost,
+        costs.request_count
+    );
+    let _ = writeln!(
+        out,
+        "Tokens: {} in / {} out",
+        costs.total_input, costs.total_output
+    );
+    for (title, rows) in [
+        ("By room", &costs.by_channel_room),
+        ("By model", &costs.by_model),
+    ] {
+        if rows.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "{title}:");
+        for row in rows.iter().take(MAX_COST_ROWS) {
+            let _ = writeln!(
+                out,
+                "- {}: ${:.4} ({} req)",
+                row.label, row.cost, row.requests
+            );
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+/// Render an action handler's JSON response as a DM reply.
+pub fn render_reply(action: &str, status: &str, content: &str) -> String {
+    let value: Option<serde_json::Value> = serde_json::from_str(content).ok();
+    if status != "ok" {
+        let error = value
+            .as_ref()
+            .and_then(|v| v.get("error"))
+            .and_then(|e| e.as_str())
+            .map_or_else(|| content.to_string(), str::to_string);
+        return if error.is_empty() {
+            format!("⚠️ {action}: {status}")
+        } else {
+            format!("⚠️ {action}: {error}")
+        };
+    }
+
+    let mut out = format!("✅ {action}");
+    let Some(serde_json::Value::Object(fields)) = value else {
+        if !content.is_empty() {
+            let _ = write!(out, "\n{}", truncate(content));
+        }
+        return out;
+    };
+    for (key, value) in &fields {
+        match value {
+            serde_json::Value::Null => {}
+            serde_json::Value::Array(items) if items.iter().any(|i| i.is_object()) => {
+                let _ = write!(out, "\n{key}:");
+                for item in items {
+                    let _ = write!(out, "\n- {}", inline_value(item));
+                }
+            }
+            other => {
+                let _ = write!(out, "\n{key}: {}", inline_value(other));
+            }
+        }
+    }
+    out
+}
+
+/// One-line rendering of a JSON value.
+fn inline_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => truncate(s),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(inline_value)
+            .collect::<Vec<_>>()
+            .join(", "),
+        serde_json::Value::Object(fields) => fields
+            .iter()
+            .filter(|(_, v)| !v.is_null())
+            .map(|(k, v)| format!("{k}={}", inline_value(v)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+fn truncate(s: &str) -> String {
+    if s.chars().count() <= MAX_VALUE_CHARS {
+        return s.to_string();
+    }
+    let cut: String = s.chars().take(MAX_VALUE_CHARS).collect();
+    format!("{cut}…")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NPUB: &str = "npub1sg6plzptd64u62a878hep2kev88swjh3tw00gjsfl8f237lmu63q0uf63m";
+    const HEX: &str = "82341f882b6eabcd2ba7f1ef90aad961cf074af15b9ef44a09f9d2a8fbfbe6a2";
+
+    fn parsed(text: &str) -> OwnerCommand {
+        parse(text).expect("is a command").expect("parses")
+    }
+
+    fn rejected(text: &str) -> String {
+        parse(text).expect("is a command").unwrap_err()
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn plain_messages_are_not_commands() {
+        assert!(parse("hello there").is_none());
+        assert!(parse("/").is_none());
+        assert!(parse("/usr/bin is full").is_none());
+        assert!(parse("  status").is_none());
+    }
+
+    #[test]
+    fn unknown_commands_point_to_help() {
+        assert!(rejected("/frobnicate").contains("/help"));
+    }
+
+    #[test]
+    fn status_help_and_aliases() {
+        assert_eq!(parsed("/status"), OwnerCommand::Status);
+        assert_eq!(parsed("/STATUS"), OwnerCommand::Status);
+        assert_eq!(parsed("/help"), OwnerCommand::Help(None));
+        assert_eq!(
+            parsed("/help /mute"),
+            OwnerCommand::Help(Some("mute".into()))
+        );
+        assert_eq!(
+            parsed("/halt"),
+            OwnerCommand::Action {
+                action: "control.stop",
+                params: vec![],
+                group: None
+            }
+        );
+        assert!(rejected("/status now").starts_with("Usage:"));
+    }
+
+    #[test]
+    fn config_set_validates_and_normalises_keys() {
+        assert_eq!(
+            parsed("/config set #dev mode=ALL history 20"),
+            OwnerCommand::Action {
+                action: "config.set",
+                params: params(&[("respond_mode", "all"), ("context_history", "20")]),
+                group: Some("dev".into()),
+            }
+        );
+        assert_eq!(
+            parsed("/config get"),
+            OwnerCommand::Action {
+                action: "config.get",
+                params: vec![],
+                group: None
+            }
+        );
+        assert!(rejected("/config set respond_mode=loud").contains("invalid respond_mode"));
+        assert!(rejected("/config set context_history=lots").contains("must be a number"));
+        assert!(rejected("/config set colour=blue").contains("unknown key"));
+        assert!(rejected("/config set #dev").contains("nothing to set"));
+    }
+
+    #[test]
+    fn resume_accepts_group_and_mode() {
+        assert_eq!(
+            parsed("/resume #dev owner"),
+            OwnerCommand::Action {
+                action: "control.resume",
+                params: params(&[("mode", "owner")]),
+                group: Some("dev".into()),
+            }
+        );
+        assert!(rejected("/resume #dev none").contains("unknown mode"));
+    }
+
+    #[test]
+    fn mute_parses_durations() {
+        assert_eq!(
+            parsed("/mute #dev 2h"),
+            OwnerCommand::Mute {
+                group: Some("dev".into()),
+                duration: Duration::from_secs(7200)
+            }
+        );
+        assert_eq!(
+            parsed("/mute dev 1 hour 30 min"),
+            OwnerCommand::Mute {
+                group: Some("dev".into()),
+                duration: Duration::from_secs(5400)
+            }
+        );
+        assert_eq!(
+            parsed("/mute #dev"),
+            OwnerCommand::Mute {
+                group: Some("dev".into()),
+                duration: DEFAULT_MUTE
+            }
+        );
+        assert_eq!(
+            parsed("/mute 2h"),
+            OwnerCommand::Mute {
+                group: None,
+                duration: Duration::from_secs(7200)
+            }
+        );
+        assert_eq!(
+            parsed("/mute"),
+            OwnerCommand::Mute {
+                group: None,
+                duration: DEFAULT_MUTE
+            }
+        );
+        assert_eq!(parsed("/unmute"), OwnerCommand::Unmute { group: None });
+        assert!(rejected("/mute #dev soon").contains("invalid duration"));
+        assert!(rejected("/mute #dev 90d").contains("between"));
+    }
+
+    #[test]
+    fn memory_note_targets_npub_or_group() {
+        assert_eq!(
+            parsed(&format!("/memory note @{NPUB} prefers  short answers")),
+            OwnerCommand::Action {
+                action: "memory.note",
+                params: params(&[("note", "prefers  short answers"), ("npub", HEX)]),
+                group: None,
+            }
+        );
+        assert_eq!(
+            parsed("/mem note #dev ships on Fridays"),
+            OwnerCommand::Action {
+                action: "memory.note",
+                params: params(&[("note", "ships on Fridays")]),
+                group: Some("dev".into()),
+            }
+        );
+        assert!(rejected("/memory note @alice hi").contains("not an npub"));
+        assert!(rejected(&format!("/memory note {NPUB}")).contains("required"));
+
+        assert_eq!(
+            parsed("/memory pending"),
+            OwnerCommand::Action {
+                action: "memory.pending",
+                params: vec![],
+                group: None,
+            }
+        );
+        assert_eq!(
+            parsed("/memory approve #3"),
+            OwnerCommand::Action {
+                action: "memory.approve",
+                params: params(&[("id", "3")]),
+                group: None,
+            }
+        );
+        assert_eq!(
+            parsed("/memory reject 3"),
+            OwnerCommand::Action {
+                action: "memory.reject",
+                params: params(&[("id", "3")]),
+                group: None,
+            }
+        );
+        assert!(rejected("/memory approve three").contains("not a pending write id"));
+        assert!(rejected("/memory reject").contains("exactly one id"));
+    }
+
+    #[test]
+    fn costs_export_and_quota() {
+        assert_eq!(parsed("/costs"), OwnerCommand::Costs(CostPeriod::Today));
+        assert_eq!(parsed("/cost week"), OwnerCommand::Costs(CostPeriod::Week));
+        assert!(rejected("/costs forever").starts_with("Usage:"));
+
+        assert_eq!(
+            parsed("/export #dev jsonl"),
+            OwnerCommand::Action {
+                action: "conversation.export",
+                params: params(&[("target", "#dev"), ("format", "jsonl")]),
+                group: None,
+            }
+        );
+        assert_eq!(
+            parsed(&format!("/quota nostr:{NPUB} requests=5 cost=$0.50")),
+            OwnerCommand::Action {
+                action: "quota.override",
+                params: params(&[("npub", HEX), ("requests", "5"), ("cost_usd", "0.5")]),
+                group: None,
+            }
+        );
+        assert!(rejected(&format!("/quota {NPUB} requests=-1")).contains("whole number"));
+    }
+
+    #[test]
+    fn proactive_draft_commands() {
+        assert_eq!(
+            parsed("/drafts"),
+            OwnerCommand::Action {
+                action: "proactive.list",
+                params: vec![],
+                group: None,
+            }
+        );
+        assert_eq!(
+            parsed("/approve 7"),
+            OwnerCommand::Action {
+                action: "proactive.approve",
+                params: params(&[("id", "7")]),
+                group: None,
+            }
+        );
+        assert_eq!(
+            parsed("/approve #7 Standup in 10 minutes!"),
+            OwnerCommand::Action {
+                action: "proactive.approve",
+                params: params(&[("id", "7"), ("content", "Standup in 10 minutes!")]),
+                group: None,
+            }
+        );
+        assert_eq!(
+            parsed("/reject 7"),
+            OwnerCommand::Action {
+                action: "proactive.reject",
+                params: params(&[("id", "7")]),
+                group: None,
+            }
+        );
+        assert!(rejected("/approve").starts_with("Usage:"));
+        assert!(rejected("/approve seven").contains("not a draft id"));
+        assert!(rejected("/reject 7 because").starts_with("Usage:"));
+    }
+
+    #[test]
+    fn delete_takes_event_references() {
+        let id = "ab".repeat(32);
+        assert_eq!(
+            parsed(&format!("/delete #dev {id}")),
+            OwnerCommand::Action {
+                action: "events.delete",
+                params: params(&[("ids", &id)]),
+                group: Some("dev".into()),
+            }
+        );
+        assert!(rejected("/delete").contains("no event IDs"));
+        assert!(rejected("/delete #dev oops").contains("not an event ID"));
+    }
+
+    #[test]
+    fn cost_periods_start_where_expected() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 14, 15, 9, 26).unwrap();
+        assert_eq!(
+            CostPeriod::Today.since(now),
+            Utc.with_ymd_and_hms(2026, 3, 14, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            CostPeriod::Week.since(now),
+            Utc.with_ymd_and_hms(2026, 3, 7, 15, 9, 26).unwrap()
+        );
+        assert_eq!(
+            CostPeriod::Month.since(now),
+            Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn durations_format_compactly() {
+        assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+        assert_eq!(format_duration(Duration::from_secs(5400)), "1h30m");
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 86_400 + 3 * 3600)),
+            "2d3h"
+        );
+    }
+
+    #[test]
+    fn help_lists_every_command() {
+        let text = help(None);
+        for spec in COMMANDS {
+            assert!(text.contains(&format!("/{} —", spec.name)));
+        }
+        assert!(help(Some("halt")).starts_with("/stop"));
+    }
+
+    #[test]
+    fn replies_render_json_fields() {
+        let reply = render_reply(
+            "memory.search",
+            "ok",
+            r#"{"query":"rust","count":1,"results":[{"key":"lang","content":"Prefers Rust","score":null}]}"#,
+        );
+        assert!(reply.starts_with("✅ memory.search"));
+        assert!(reply.contains("count: 1"));
+        assert!(reply.contains("- content=Prefers Rust, key=lang"));
+
+        assert_eq!(
+            render_reply("quota.override", "error", "quotas are not enabled"),
+            "⚠️ quota.override: quotas are not enabled"
+        );
+        assert_eq!(render_reply("control.stop", "ok", ""), "✅ control.stop");
+    }
+}
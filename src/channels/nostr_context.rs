@@ -0,0 +1,202 @@
+//! Prompt context assembly for Nostr group messages and DMs.
+//!
+//! A message reaches the agent as one block of text: owner line, mode
+//! guidance, social memory, recent conversation, a compact header and the
+//! message itself. The layout is a minijinja template — the built-in one
+//! below, or a file named by `[channels_config.nostr] context_template`.
+//! The template is compiled and test-rendered when the channel starts, so a
+//! broken template fails startup rather than the first message.
+
+use anyhow::{Context, Result};
+use minijinja::{AutoEscape, Environment, UndefinedBehavior};
+use serde::Serialize;
+use std::path::Path;
+use tracing::warn;
+
+/// Built-in layout, matching the context format used before templates.
+pub const DEFAULT_TEMPLATE: &str = "{{ owner_line }}{{ mode_guidance }}{{ memory }}{{ history }}\
+{{ reply_chain }}{{ header }}\n{{ content }}";
+
+const TEMPLATE_NAME: &str = "context";
+
+/// Variables available to a context template. Block variables (`owner_line`,
+/// `mode_guidance`, `memory`, `history`, `reply_chain`) are empty or end
+/// with a newline.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContextVars<'a> {
+    /// `[Owner: name (npub)]` line, empty without an owner
+    pub owner_line: &'a str,
+    /// Guidance for `respond_mode = "all"` groups, empty otherwise
+    pub mode_guidance: &'a str,
+    /// Social memory about the sender and group
+    pub memory: &'a str,
+    /// Recent group messages or DM history
+    pub history: &'a str,
+    /// Our message the group message replies to and the messages before
+    /// it, empty unless `is_reply`
+    pub reply_chain: &'a str,
+    /// Compact `[nostr:...]` header of the message
+    pub header: &'a str,
+    /// Message text, after secret filtering
+    pub content: &'a str,
+    /// Sender display name
+    pub sender: &'a str,
+    /// Sender npub
+    pub npub: &'a str,
+    /// Group ID, unset for DMs
+    pub group: Option<&'a str>,
+    pub is_dm: bool,
+    pub is_owner: bool,
+    /// The group message replies to one of ours (rather than mentioning us)
+    pub is_reply: bool,
+}
+
+/// A compiled context template.
+pub struct ContextTemplate {
+    env: Environment<'static>,
+}
+
+impl std::fmt::Debug for ContextTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextTemplate").finish_non_exhaustive()
+    }
+}
+
+impl Default for ContextTemplate {
+    fn default() -> Self {
+        Self::from_source(DEFAULT_TEMPLATE.to_string()).expect("built-in context template")
+    }
+}
+
+impl ContextTemplate {
+    /// Compile a template and check it renders with sample variables.
+    /// Unknown variables are errors, so typos surface here.
+    pub fn from_source(source: String) -> Result<Self> {
+        let mut env = Environment::new();
+        env.set_auto_escape_callback(|_| AutoEscape::None);
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        env.add_template_owned(TEMPLATE_NAME, source)
+            .context("context template has a syntax error")?;
+        let template = Self { env };
+
+        let sample = ContextVars {
+            header: "[nostr:group=#test from=alice npub=npub1test kind=9 id=00000000]",
+            content: "hello",
+            sender: "alice",
+            npub: "npub1test",
+            group: Some("test"),
+            ..ContextVars::default()
+        };
+        for vars in [
+            sample.clone(),
+            ContextVars {
+                group: None,
+                is_dm: true,
+                ..sample
+            },
+        ] {
+            template
+                .try_render(&vars)
+                .context("context template failed to render")?;
+        }
+        Ok(template)
+    }
+
+    /// Load the template at `path`, or the built-in one when unset.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read context template {}", path.display()))?;
+        Self::from_source(source)
+            .with_context(|| format!("invalid context template {}", path.display()))
+    }
+
+    fn try_render(&self, vars: &ContextVars<'_>) -> Result<String, minijinja::Error> {
+        self.env.get_template(TEMPLATE_NAME)?.render(vars)
+    }
+
+    /// Render the context for one message. A render error (only possible
+    /// for input the startup check did not cover) falls back to the
+    /// built-in layout.
+    pub fn render(&self, vars: &ContextVars<'_>) -> String {
+        self.try_render(vars).unwrap_or_else(|e| {
+            warn!("Context template failed, using the built-in layout: {e:#}");
+            format!(
+                "{}{}{}{}{}{}\n{}",
+                vars.owner_line,
+                vars.mode_guidance,
+                vars.memory,
+                vars.history,
+                vars.reply_chain,
+                vars.header,
+                vars.content
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> ContextVars<'static> {
+        ContextVars {
+            owner_line: "[Owner: k0 (npub1owner)]\n",
+            memory: "[Known about alice: notes: likes Rust]\n",
+            history: "[Recent conversation context]\n<bob npub=npub1bob>  hi\n\n",
+            header: "[nostr:group=#dev from=alice npub=npub1alice kind=9 id=abcdef12]",
+            content: "<b>what's new?</b>",
+            sender: "alice",
+            npub: "npub1alice",
+            group: Some("dev"),
+            ..ContextVars::default()
+        }
+    }
+
+    #[test]
+    fn default_template_matches_legacy_format() {
+        let v = vars();
+        let legacy = format!(
+            "{}{}{}{}{}\n{}",
+            v.owner_line, v.mode_guidance, v.memory, v.history, v.header, v.content
+        );
+        assert_eq!(ContextTemplate::default().render(&v), legacy);
+    }
+
+    #[test]
+    fn reply_chain_follows_history() {
+        let v = ContextVars {
+            reply_chain: "[Reply chain: ...]\n<snowclaw npub=npub1snow>  it's in v2\n\n",
+            is_reply: true,
+            ..vars()
+        };
+        let rendered = ContextTemplate::default().render(&v);
+        assert!(rendered.contains(
+            "hi\n\n[Reply chain: ...]\n<snowclaw npub=npub1snow>  it's in v2\n\n[nostr:group=#dev"
+        ));
+
+        let template =
+            ContextTemplate::from_source("{% if is_reply %}reply{% endif %}".to_string()).unwrap();
+        assert_eq!(template.render(&v), "reply");
+    }
+
+    #[test]
+    fn custom_template_uses_variables_without_escaping() {
+        let template = ContextTemplate::from_source(
+            "{% if is_dm %}DM{% else %}#{{ group }}{% endif %} {{ sender }}: {{ content }}\n"
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(template.render(&vars()), "#dev alice: <b>what's new?</b>");
+    }
+
+    #[test]
+    fn invalid_templates_are_rejected() {
+        assert!(ContextTemplate::from_source("{{ content ".to_string()).is_err());
+        // Unknown variables fail the startup check.
+        assert!(ContextTemplate::from_source("{{ sendr }}".to_string()).is_err());
+        assert!(ContextTemplate::load(Some(Path::new("/nonexistent/context.j2"))).is_err());
+    }
+}
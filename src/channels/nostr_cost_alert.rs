@@ -0,0 +1,495 @@
+//! Cost anomaly alerts: owner DMs when spending looks wrong.
+//!
+//! With `[channels_config.nostr.cost_alerts]` enabled, the Nostr channel
+//! polls a [`CostAlerter`] every few minutes. It tail-reads
+//! `state/costs.jsonl`, keeping only the records of its window, and looks
+//! for an hourly spend far above the trailing average, a session calling
+//! tools in a runaway loop, and single requests over a cost threshold. Each
+//! anomaly is reported once per cooldown.
+
+use crate::config::NostrCostAlertConfig;
+use crate::cost::types::{latest_records, CostRecord};
+use crate::stats;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Something in the cost records worth telling the owner about.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Anomaly {
+    /// Spend of the last hour is `spike_factor` times the trailing average.
+    HourlySpike {
+        spend: f64,
+        /// Average hourly spend over the trailing window.
+        average: f64,
+        /// Channel/room and model that spent most in the hour.
+        room: String,
+        model: String,
+    },
+    /// A session made `calls` tool calls within `tool_loop_minutes`.
+    ToolLoop {
+        session: String,
+        room: String,
+        model: String,
+        calls: u32,
+        /// Most called tool.
+        tool: String,
+        cost: f64,
+    },
+    /// One request cost more than `request_usd`.
+    CostlyRequest {
+        id: String,
+        room: String,
+        model: String,
+        cost: f64,
+        input_tokens: u64,
+        output_tokens: u64,
+    },
+}
+
+impl Anomaly {
+    /// Anomalies with the same key share a cooldown.
+    fn cooldown_key(&self) -> String {
+        match self {
+            Self::HourlySpike { .. } => "spike".to_string(),
+            Self::ToolLoop { session, .. } => format!("loop:{session}"),
+            Self::CostlyRequest { room, .. } => format!("request:{room}"),
+        }
+    }
+
+    /// Owner DM text.
+    pub fn message(&self) -> String {
+        match self {
+            Self::HourlySpike {
+                spend,
+                average,
+                room,
+                model,
+            } => format!(
+                "⚠️ Cost spike: ${spend:.2} in the last hour, {}× the trailing average of \
+                 ${average:.2}/h. Most spent in {room} on {model}.",
+                ratio_label(*spend, *average)
+            ),
+            Self::ToolLoop {
+                session,
+                room,
+                model,
+                calls,
+                tool,
+                cost,
+            } => format!(
+                "⚠️ Possible tool loop: {calls} tool calls (mostly `{tool}`) in session \
+                 {session} in {room} on {model}, ${cost:.2} so far."
+            ),
+            Self::CostlyRequest {
+                id,
+                room,
+                model,
+                cost,
+                input_tokens,
+                output_tokens,
+            } => format!(
+                "⚠️ Costly request: ${cost:.2} for {input_tokens} input / {output_tokens} \
+                 output tokens in {room} on {model} (request {id})."
+            ),
+        }
+    }
+}
+
+fn ratio_label(spend: f64, average: f64) -> String {
+    if average > 0.0 {
+        format!("{:.1}", spend / average)
+    } else {
+        "∞".to_string()
+    }
+}
+
+/// `channel/room` of a record, as in `snowclaw stats`.
+fn room_label(record: &CostRecord) -> String {
+    format!(
+        "{}/{}",
+        record.channel.as_deref().unwrap_or("unknown"),
+        record.room.as_deref().unwrap_or("default")
+    )
+}
+
+/// Key with the highest total `weight` among `items`.
+fn heaviest(items: impl Iterator<Item = (String, f64)>) -> String {
+    let mut totals: HashMap<String, f64> = HashMap::new();
+    for (key, weight) in items {
+        *totals.entry(key).or_default() += weight;
+    }
+    totals
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .map(|(key, _)| key)
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Cost records of the alert window, tail-read from `costs.jsonl` so a
+/// poll only parses what was appended since the previous one.
+#[derive(Debug)]
+struct CostLog {
+    path: PathBuf,
+    offset: u64,
+    records: Vec<CostRecord>,
+}
+
+impl CostLog {
+    /// Read the records appended since the last call, keep the latest
+    /// version of each and drop those recorded at or before `since`.
+    fn refresh(&mut self, since: DateTime<Utc>) -> Result<()> {
+        let Ok(mut file) = std::fs::File::open(&self.path) else {
+            return Ok(());
+        };
+        if file.metadata()?.len() < self.offset {
+            // Truncated or rotated: start over.
+            self.offset = 0;
+            self.records.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 || !line.ends_with('\n') {
+                // EOF or a partially written line: pick it up next time.
+                break;
+            }
+            self.offset += read as u64;
+            if let Ok(record) = serde_json::from_str::<CostRecord>(line.trim()) {
+                self.records.push(record);
+            }
+        }
+        self.records = latest_records(std::mem::take(&mut self.records));
+        self.records.retain(|r| r.usage.timestamp > since);
+        Ok(())
+    }
+}
+
+/// Finds anomalies in cost records and applies the alert cooldown.
+pub struct CostAlerter {
+    config: NostrCostAlertConfig,
+    /// Requests up to this time were already checked against `request_usd`.
+    last_checked: DateTime<Utc>,
+    last_alert: HashMap<String, DateTime<Utc>>,
+    log: CostLog,
+}
+
+impl CostAlerter {
+    /// Alerter over `<workspace_dir>/state/costs.jsonl`. Requests recorded
+    /// before `now` are never reported as costly.
+    pub fn new(config: NostrCostAlertConfig, workspace_dir: &Path, now: DateTime<Utc>) -> Self {
+        Self {
+            config,
+            last_checked: now,
+            last_alert: HashMap::new(),
+            log: CostLog {
+                path: stats::costs_jsonl_path(workspace_dir),
+                offset: 0,
+                records: Vec::new(),
+            },
+        }
+    }
+
+    /// Oldest record time any check as of `now` looks at.
+    fn window_start(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let spike = now - Duration::hours(i64::from(self.config.trailing_hours) + 1);
+        let tool_loop = now - Duration::minutes(i64::from(self.config.tool_loop_minutes.max(1)));
+        spike.min(tool_loop).min(self.last_checked)
+    }
+
+    /// Read the records appended to `costs.jsonl` since the last poll and
+    /// check the window as of `now`. Blocking file I/O.
+    pub fn poll(&mut self, now: DateTime<Utc>) -> Result<Vec<Anomaly>> {
+        self.log.refresh(self.window_start(now))?;
+        let records = std::mem::take(&mut self.log.records);
+        let found = self.check(&records, now);
+        self.log.records = records;
+        Ok(found)
+    }
+
+    /// Anomalies in `records` as of `now` that are not cooling down.
+    pub fn check(&mut self, records: &[CostRecord], now: DateTime<Utc>) -> Vec<Anomaly> {
+        let mut found = Vec::new();
+        found.extend(self.hourly_spike(records, now));
+        found.extend(self.tool_loops(records, now));
+        found.extend(self.costly_requests(records, now));
+        self.last_checked = now;
+
+        let cooldown = Duration::minutes(i64::try_from(self.config.cooldown_minutes).unwrap_or(0));
+        found.retain(|anomaly| {
+            let key = anomaly.cooldown_key();
+            if self
+                .last_alert
+                .get(&key)
+                .is_some_and(|last| now - *last < cooldown)
+            {
+                return false;
+            }
+            self.last_alert.insert(key, now);
+            true
+        });
+        found
+    }
+
+    fn hourly_spike(&self, records: &[CostRecord], now: DateTime<Utc>) -> Option<Anomaly> {
+        let cfg = &self.config;
+        if cfg.spike_factor <= 0.0 || cfg.trailing_hours == 0 {
+            return None;
+        }
+        let hour_ago = now - Duration::hours(1);
+        let trailing_start = hour_ago - Duration::hours(i64::from(cfg.trailing_hours));
+        let last_hour: Vec<&CostRecord> = records
+            .iter()
+            .filter(|r| r.usage.timestamp > hour_ago && r.usage.timestamp <= now)
+            .collect();
+        let spend: f64 = last_hour.iter().map(|r| r.usage.cost_usd).sum();
+        let trailing: f64 = records
+            .iter()
+            .filter(|r| r.usage.timestamp > trailing_start && r.usage.timestamp <= hour_ago)
+            .map(|r| r.usage.cost_usd)
+            .sum();
+        let average = trailing / f64::from(cfg.trailing_hours);
+        if spend < cfg.min_hourly_usd || spend <= cfg.spike_factor * average {
+            return None;
+        }
+        Some(Anomaly::HourlySpike {
+            spend,
+            average,
+            room: heaviest(last_hour.iter().map(|r| (room_label(r), r.usage.cost_usd))),
+            model: heaviest(
+                last_hour
+                    .iter()
+                    .map(|r| (r.usage.model.clone(), r.usage.cost_usd)),
+            ),
+        })
+    }
+
+    fn tool_loops(&self, records: &[CostRecord], now: DateTime<Utc>) -> Vec<Anomaly> {
+        let cfg = &self.config;
+        if cfg.tool_loop_calls == 0 {
+            return Vec::new();
+        }
+        let since = now - Duration::minutes(i64::from(cfg.tool_loop_minutes.max(1)));
+        let mut sessions: HashMap<&str, Vec<&CostRecord>> = HashMap::new();
+        for r in records
+            .iter()
+            .filter(|r| r.usage.timestamp > since && r.usage.timestamp <= now)
+        {
+            sessions.entry(r.session_id.as_str()).or_default().push(r);
+        }
+
+        let mut loops: Vec<Anomaly> = sessions
+            .into_iter()
+            .filter_map(|(session, recs)| {
+                let calls: u32 = recs
+                    .iter()
+                    .flat_map(|r| r.tool_latency.values())
+                    .map(|latency| latency.calls)
+                    .sum();
+                if calls < cfg.tool_loop_calls {
+                    return None;
+                }
+                let latest = recs.iter().max_by_key(|r| r.usage.timestamp)?;
+                Some(Anomaly::ToolLoop {
+                    session: session.to_string(),
+                    room: room_label(latest),
+                    model: latest.usage.model.clone(),
+                    calls,
+                    tool: heaviest(recs.iter().flat_map(|r| {
+                        r.tool_latency
+                            .iter()
+                            .map(|(tool, latency)| (tool.clone(), f64::from(latency.calls)))
+                    })),
+                    cost: recs.iter().map(|r| r.usage.cost_usd).sum(),
+                })
+            })
+            .collect();
+        loops.sort_by(|a, b| a.cooldown_key().cmp(&b.cooldown_key()));
+        loops
+    }
+
+    fn costly_requests(&self, records: &[CostRecord], now: DateTime<Utc>) -> Vec<Anomaly> {
+        let threshold = self.config.request_usd;
+        if threshold <= 0.0 {
+            return Vec::new();
+        }
+        let mut costly: Vec<&CostRecord> = records
+            .iter()
+            .filter(|r| r.usage.timestamp > self.last_checked && r.usage.timestamp <= now)
+            .filter(|r| !r.partial && r.usage.cost_usd > threshold)
+            .collect();
+        costly.sort_by(|a, b| b.usage.cost_usd.total_cmp(&a.usage.cost_usd));
+        costly
+            .into_iter()
+            .map(|r| Anomaly::CostlyRequest {
+                id: r.id.clone(),
+                room: room_label(r),
+                model: r.usage.model.clone(),
+                cost: r.usage.cost_usd,
+                input_tokens: r.usage.input_tokens,
+                output_tokens: r.usage.output_tokens,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::types::TokenUsage;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 12, 0, 0).unwrap()
+    }
+
+    fn record(minutes_ago: i64, room: &str, model: &str, cost: f64) -> CostRecord {
+        let mut usage = TokenUsage::new(model, 1000, 200, 0.0, 0.0);
+        usage.cost_usd = cost;
+        usage.timestamp = now() - Duration::minutes(minutes_ago);
+        CostRecord::with_context("s", usage, Some("nostr".into()), Some(room.into()), None)
+    }
+
+    fn config() -> NostrCostAlertConfig {
+        NostrCostAlertConfig {
+            enabled: true,
+            request_usd: 0.0,
+            tool_loop_calls: 0,
+            ..NostrCostAlertConfig::default()
+        }
+    }
+
+    #[test]
+    fn hourly_spike_against_trailing_average() {
+        // $0.10 per hour for the trailing day, then $1.50 in the last hour.
+        let mut records: Vec<CostRecord> = (2..=24)
+            .map(|h| record(h * 60, "#dev", "m/cheap", 0.1))
+            .collect();
+        records.push(record(10, "#ops", "m/big", 1.0));
+        records.push(record(20, "#dev", "m/cheap", 0.5));
+
+        let mut alerter = CostAlerter::new(config(), Path::new(""), now() - Duration::hours(48));
+        let found = alerter.check(&records, now());
+        let [Anomaly::HourlySpike {
+            spend,
+            average,
+            room,
+            model,
+        }] = found.as_slice()
+        else {
+            panic!("expected one spike, got {found:?}");
+        };
+        assert!((spend - 1.5).abs() < 1e-9);
+        assert!((average - 2.3 / 24.0).abs() < 1e-9);
+        assert_eq!((room.as_str(), model.as_str()), ("nostr/#ops", "m/big"));
+        assert!(found[0]
+            .message()
+            .contains("Most spent in nostr/#ops on m/big"));
+
+        // Below min_hourly_usd nothing is reported, whatever the ratio.
+        let mut alerter = CostAlerter::new(config(), Path::new(""), now());
+        assert!(alerter
+            .check(&[record(5, "#dev", "m", 0.2)], now())
+            .is_empty());
+    }
+
+    #[test]
+    fn tool_loops_and_costly_requests() {
+        let mut looping = record(3, "#dev", "m/a", 0.05);
+        let web = looping.tool_latency.entry("web_fetch".into()).or_default();
+        for _ in 0..40 {
+            web.record(std::time::Duration::from_millis(5));
+        }
+        looping.session_id = "loop-session".into();
+        let old_costly = record(30, "#dev", "m/b", 3.0);
+        let new_costly = record(1, "#ops", "m/b", 2.5);
+
+        let cfg = NostrCostAlertConfig {
+            enabled: true,
+            spike_factor: 0.0,
+            ..NostrCostAlertConfig::default()
+        };
+        let mut alerter = CostAlerter::new(cfg, Path::new(""), now() - Duration::minutes(5));
+        let found = alerter.check(&[looping, old_costly, new_costly], now());
+        assert_eq!(found.len(), 2, "{found:?}");
+        assert!(matches!(
+            &found[0],
+            Anomaly::ToolLoop { calls: 40, tool, session, .. }
+                if tool == "web_fetch" && session == "loop-session"
+        ));
+        assert!(matches!(
+            &found[1],
+            Anomaly::CostlyRequest { room, .. } if room == "nostr/#ops"
+        ));
+    }
+
+    #[test]
+    fn poll_tail_reads_the_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = stats::costs_jsonl_path(dir.path());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let line = |r: &CostRecord| format!("{}\n", serde_json::to_string(r).unwrap());
+        let old = record(48 * 60, "#dev", "m", 5.0);
+        let costly = record(1, "#ops", "m", 2.0);
+        std::fs::write(&path, line(&old) + &line(&costly)).unwrap();
+
+        let cfg = NostrCostAlertConfig {
+            enabled: true,
+            spike_factor: 0.0,
+            tool_loop_calls: 0,
+            ..NostrCostAlertConfig::default()
+        };
+        let mut alerter = CostAlerter::new(cfg, dir.path(), now() - Duration::minutes(5));
+        assert_eq!(alerter.poll(now()).unwrap().len(), 1);
+        // Records outside the window are dropped after parsing.
+        assert_eq!(alerter.log.records.len(), 1);
+
+        // Only the appended line is read; a partial line waits.
+        let later = now() + Duration::minutes(5);
+        let mut appended = record(0, "#web", "m", 3.0);
+        appended.usage.timestamp = later;
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        std::io::Write::write_all(&mut file, (line(&appended) + "{\"partial").as_bytes()).unwrap();
+        let found = alerter.poll(later).unwrap();
+        assert!(
+            matches!(&found[..], [Anomaly::CostlyRequest { room, .. }] if room == "nostr/#web"),
+            "{found:?}"
+        );
+        assert_eq!(
+            alerter.log.offset,
+            (line(&old) + &line(&costly) + &line(&appended)).len() as u64
+        );
+    }
+
+    #[test]
+    fn cooldown_suppresses_repeats() {
+        let cfg = NostrCostAlertConfig {
+            enabled: true,
+            spike_factor: 0.0,
+            tool_loop_calls: 0,
+            cooldown_minutes: 60,
+            ..NostrCostAlertConfig::default()
+        };
+        let mut alerter = CostAlerter::new(cfg, Path::new(""), now() - Duration::minutes(10));
+        let first = [record(5, "#dev", "m", 2.0)];
+        assert_eq!(alerter.check(&first, now()).len(), 1);
+
+        // A second costly request in the same room within the hour is held back.
+        let later = now() + Duration::minutes(5);
+        let mut second = record(0, "#dev", "m", 2.0);
+        second.usage.timestamp = later;
+        assert!(alerter.check(&[second.clone()], later).is_empty());
+
+        let much_later = now() + Duration::minutes(65);
+        second.usage.timestamp = much_later;
+        assert_eq!(alerter.check(&[second], much_later).len(), 1);
+    }
+}
@@ -0,0 +1,152 @@
+//! NIP-09 deletion requests (kind 5).
+//!
+//! A kind 5 event lists the IDs of events its author wants deleted. The
+//! Nostr listener honours one only for events written by the same author
+//! and purges them from everything derived from them: the message index,
+//! pages indexed from links they shared, DM history, the group ring buffer,
+//! the raw event cache and auto-saved conversation memories. The owner can
+//! ask for the agent's own events to be deleted with the `events.delete`
+//! action, the `/delete` DM command or `snowclaw nostr delete`; the agent
+//! then publishes the kind 5 and purges its local copies the same way.
+
+use nostr_sdk::prelude::*;
+use serde::Serialize;
+
+/// Most events one deletion request may list.
+pub const MAX_DELETE_IDS: usize = 50;
+
+/// IDs of the events a kind 5 request asks to delete (its `e` tags).
+/// `a` tags (replaceable events) are not tracked locally and are ignored.
+pub fn deleted_ids(event: &Event) -> Vec<String> {
+    let mut ids: Vec<String> = event
+        .tags
+        .iter()
+        .filter_map(|tag| {
+            let s = tag.as_slice();
+            (s.first().map(String::as_str) == Some("e"))
+                .then(|| s.get(1))
+                .flatten()
+                .and_then(|id| EventId::from_hex(id).ok())
+                .map(|id| id.to_hex())
+        })
+        .collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Parse an event reference: hex, `note1…`, `nevent1…`, optionally with a
+/// `nostr:` prefix.
+pub fn parse_event_id(raw: &str) -> Option<EventId> {
+    let raw = raw.trim();
+    let raw = raw.strip_prefix("nostr:").unwrap_or(raw);
+    EventId::parse(raw)
+        .ok()
+        .or_else(|| Nip19Event::from_bech32(raw).ok().map(|e| e.event_id))
+}
+
+/// Parse a comma- or whitespace-separated list of event references.
+pub fn parse_event_ids(list: &str) -> Result<Vec<EventId>, String> {
+    let mut ids = Vec::new();
+    for raw in list
+        .split([',', ' ', '\n'])
+        .filter(|s| !s.trim().is_empty())
+    {
+        let id = parse_event_id(raw).ok_or_else(|| format!("'{raw}' is not an event ID"))?;
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    if ids.is_empty() {
+        return Err("no event IDs given".to_string());
+    }
+    if ids.len() > MAX_DELETE_IDS {
+        return Err(format!("at most {MAX_DELETE_IDS} events per request"));
+    }
+    Ok(ids)
+}
+
+/// What a deletion removed locally.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PurgeReport {
+    /// Rows in the message index.
+    pub indexed_messages: usize,
+    /// Shared links, with their indexed pages.
+    pub links: usize,
+    pub dm_messages: usize,
+    /// Messages in group ring buffers.
+    pub history: usize,
+    /// Raw events in the event cache.
+    pub cached_events: usize,
+    /// Auto-saved conversation memories.
+    pub memories: usize,
+}
+
+impl PurgeReport {
+    pub fn total(&self) -> usize {
+        self.indexed_messages
+            + self.links
+            + self.dm_messages
+            + self.history
+            + self.cached_events
+            + self.memories
+    }
+}
+
+/// Whether an auto-saved conversation memory key belongs to one of
+/// `event_ids` (keys end with `_<event id>`).
+pub fn is_memory_key_for(key: &str, event_ids: &[String]) -> bool {
+    key.rsplit_once('_')
+        .is_some_and(|(_, id)| event_ids.iter().any(|e| e == id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "b3e392b11f5d4f28321cedd09303a748acfd0487aea5a7450b3481c60b6e4f87";
+
+    #[test]
+    fn deleted_ids_reads_valid_e_tags() {
+        let keys = Keys::generate();
+        let tag =
+            |name: &str, value: &str| Tag::custom(TagKind::custom(name), vec![value.to_string()]);
+        let event = EventBuilder::new(Kind::EventDeletion, "spam")
+            .tags([
+                tag("e", ID),
+                tag("e", ID),
+                tag("e", "not-an-id"),
+                tag("a", "30023:abcd:post"),
+                tag("k", "9"),
+            ])
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(deleted_ids(&event), vec![ID.to_string()]);
+    }
+
+    #[test]
+    fn event_references_parse_in_all_forms() {
+        let id = EventId::from_hex(ID).unwrap();
+        let note = id.to_bech32().unwrap();
+        assert_eq!(parse_event_id(ID), Some(id));
+        assert_eq!(parse_event_id(&note), Some(id));
+        assert_eq!(parse_event_id(&format!("nostr:{note}")), Some(id));
+        assert_eq!(parse_event_id("hello"), None);
+
+        assert_eq!(parse_event_ids(&format!("{ID}, {note}")).unwrap(), vec![id]);
+        assert!(parse_event_ids("").is_err());
+        assert!(parse_event_ids("nope").unwrap_err().contains("'nope'"));
+    }
+
+    #[test]
+    fn memory_keys_match_on_the_event_id_suffix() {
+        let ids = vec![ID.to_string()];
+        assert!(is_memory_key_for(&format!("nostr_Alice_{ID}"), &ids));
+        assert!(is_memory_key_for(
+            &format!("assistant_resp_nostr_Alice_{ID}"),
+            &ids
+        ));
+        assert!(!is_memory_key_for("nostr_Alice_other", &ids));
+        assert!(!is_memory_key_for(ID, &ids));
+    }
+}
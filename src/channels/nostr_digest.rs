@@ -0,0 +1,343 @@
+//! Owner digest: a scheduled activity and cost summary sent as a DM.
+//!
+//! The Nostr listener feeds a [`DigestTracker`] with messages seen and
+//! answered per group, new contacts and key-filter security flags. When
+//! `[channels_config.nostr.digest]` is due, the tracker is drained, combined
+//! with cost records via [`crate::stats::aggregate`] and rendered into a
+//! plain-text report for the owner.
+
+use crate::config::NostrDigestConfig;
+use crate::cron::{next_run_for_schedule, Schedule};
+use crate::stats::{self, StatsFilter, StatsResult};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use nostr_core::key_filter::{PatternAction, SecurityFlag};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Max list entries (contacts, flags, rooms) shown in a detailed digest.
+const MAX_DETAIL_LINES: usize = 10;
+
+/// How much detail the digest includes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestVerbosity {
+    /// Totals only.
+    Summary,
+    /// Totals plus per-group, per-contact and per-flag lines.
+    Detailed,
+}
+
+impl DigestVerbosity {
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "detailed" | "full" | "verbose" => Self::Detailed,
+            _ => Self::Summary,
+        }
+    }
+}
+
+/// Message counters for one group (or for DMs).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoomActivity {
+    pub seen: u64,
+    pub answered: u64,
+}
+
+/// Activity collected since the previous digest.
+#[derive(Debug, Clone, Default)]
+pub struct DigestActivity {
+    pub groups: BTreeMap<String, RoomActivity>,
+    pub dms: RoomActivity,
+    pub new_contacts: Vec<String>,
+    pub security_flags: Vec<String>,
+}
+
+/// Shared, cheaply clonable collector for digest activity.
+#[derive(Debug, Clone, Default)]
+pub struct DigestTracker {
+    inner: Arc<Mutex<DigestActivity>>,
+}
+
+impl DigestTracker {
+    /// Count an incoming message. `group` is `None` for DMs.
+    pub fn record_seen(&self, group: Option<&str>) {
+        self.with_room(group, |room| room.seen += 1);
+    }
+
+    /// Count a reply sent by the agent. `group` is `None` for DMs.
+    pub fn record_answered(&self, group: Option<&str>) {
+        self.with_room(group, |room| room.answered += 1);
+    }
+
+    pub fn record_new_contact(&self, label: impl Into<String>) {
+        self.lock().new_contacts.push(label.into());
+    }
+
+    pub fn record_security_flag(&self, description: impl Into<String>) {
+        self.lock().security_flags.push(description.into());
+    }
+
+    /// Drain the collected activity, resetting the tracker.
+    pub fn take(&self) -> DigestActivity {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn with_room(&self, group: Option<&str>, update: impl FnOnce(&mut RoomActivity)) {
+        let mut activity = self.lock();
+        let room = match group {
+            Some(group) => activity.groups.entry(group.to_string()).or_default(),
+            None => &mut activity.dms,
+        };
+        update(room);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DigestActivity> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Short label for a key-filter flag in the digest, e.g. `aws_access_key blocked`.
+pub fn security_flag_label(flag: &SecurityFlag) -> String {
+    let outcome = match (flag.action, flag.redacted) {
+        (PatternAction::Block, _) => "blocked",
+        (_, true) => "redacted",
+        (_, false) => "flagged",
+    };
+    format!("{} {outcome}", flag.pattern)
+}
+
+/// Next delivery time strictly after `after`, per the configured cron schedule.
+pub fn next_delivery(config: &NostrDigestConfig, after: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let schedule = Schedule::Cron {
+        expr: config.schedule.clone(),
+        tz: config.timezone.clone(),
+    };
+    next_run_for_schedule(&schedule, after)
+}
+
+/// Aggregate cost records in `[since, until]` with the regular stats pipeline.
+pub fn cost_stats(workspace_dir: &Path, since: DateTime<Utc>, until: DateTime<Utc>) -> StatsResult {
+    let mut records =
+        stats::read_records(&stats::costs_jsonl_path(workspace_dir)).unwrap_or_else(|e| {
+            tracing::warn!("Digest: failed to read cost records: {e}");
+            Vec::new()
+        });
+    records.retain(|r| r.usage.timestamp >= since && r.usage.timestamp <= until);
+    let filter = StatsFilter {
+        start_date: since.date_naive(),
+        end_date: until.date_naive(),
+        room: None,
+        tz: chrono_tz::Tz::UTC,
+    };
+    stats::aggregate(&records, &filter)
+}
+
+/// Render the digest DM body.
+pub fn render(
+    activity: &DigestActivity,
+    costs: &StatsResult,
+    verbosity: DigestVerbosity,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> String {
+    let seen: u64 = activity.groups.values().map(|g| g.seen).sum::<u64>() + activity.dms.seen;
+    let answered: u64 =
+        activity.groups.values().map(|g| g.answered).sum::<u64>() + activity.dms.answered;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "📊 Daily digest ({} → {} UTC)",
+        since.format("%Y-%m-%d %H:%M"),
+        until.format("%Y-%m-%d %H:%M")
+    );
+    let _ = writeln!(
+        out,
+        "Messages: {seen} seen, {answered} answered across {} group(s) + DMs",
+        activity.groups.len()
+    );
+    let _ = writeln!(
+        out,
+        "Cost: ${:.4} over {} request(s) ({} in / {} out tokens)",
+        costs.total_cost, costs.request_count, costs.total_input, costs.total_output
+    );
+    let _ = writeln!(out, "New contacts: {}", activity.new_contacts.len());
+    let _ = writeln!(out, "Security flags: {}", activity.security_flags.len());
+
+    if verbosity == DigestVerbosity::Summary {
+        return out.trim_end().to_string();
+    }
+
+    if !activity.groups.is_empty() || activity.dms != RoomActivity::default() {
+        out.push_str("\nRooms:\n");
+        for (group, room) in &activity.groups {
+            let _ = writeln!(
+                out,
+                "  #{group}: {} seen, {} answered",
+                room.seen, room.answered
+            );
+        }
+        if activity.dms != RoomActivity::default() {
+            let _ = writeln!(
+                out,
+                "  DMs: {} seen, {} answered",
+                activity.dms.seen, activity.dms.answered
+            );
+        }
+    }
+    if !costs.by_channel_room.is_empty() {
+        out.push_str("\nCost by room:\n");
+        for row in costs.by_channel_room.iter().take(MAX_DETAIL_LINES) {
+            let _ = writeln!(
+                out,
+                "  {}: ${:.4} ({} req)",
+                row.label, row.cost, row.requests
+            );
+        }
+    }
+    push_list(&mut out, "New contacts", &activity.new_contacts);
+    push_list(&mut out, "Security flags", &activity.security_flags);
+
+    out.trim_end().to_string()
+}
+
+fn push_list(out: &mut String, title: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "\n{title}:");
+    for item in items.iter().take(MAX_DETAIL_LINES) {
+        let _ = writeln!(out, "  - {item}");
+    }
+    if items.len() > MAX_DETAIL_LINES {
+        let _ = writeln!(out, "  … and {} more", items.len() - MAX_DETAIL_LINES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn empty_costs() -> StatsResult {
+        let day = Utc
+            .with_ymd_and_hms(2026, 3, 1, 0, 0, 0)
+            .unwrap()
+            .date_naive();
+        stats::aggregate(
+            &[],
+            &StatsFilter {
+                start_date: day,
+                end_date: day,
+                room: None,
+                tz: chrono_tz::Tz::UTC,
+            },
+        )
+    }
+
+    fn window() -> (DateTime<Utc>, DateTime<Utc>) {
+        (
+            Utc.with_ymd_and_hms(2026, 3, 1, 8, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2026, 3, 2, 8, 0, 0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn tracker_counts_per_group_and_resets_on_take() {
+        let tracker = DigestTracker::default();
+        tracker.record_seen(Some("dev"));
+        tracker.record_seen(Some("dev"));
+        tracker.record_answered(Some("dev"));
+        tracker.record_seen(None);
+        tracker.record_new_contact("alice");
+        tracker.record_security_flag("nsec redacted in #dev");
+
+        let activity = tracker.take();
+        assert_eq!(
+            activity.groups["dev"],
+            RoomActivity {
+                seen: 2,
+                answered: 1
+            }
+        );
+        assert_eq!(activity.dms.seen, 1);
+        assert_eq!(activity.new_contacts, vec!["alice".to_string()]);
+        assert_eq!(activity.security_flags.len(), 1);
+
+        let drained = tracker.take();
+        assert!(drained.groups.is_empty());
+        assert!(drained.new_contacts.is_empty());
+    }
+
+    #[test]
+    fn summary_omits_detail_sections() {
+        let tracker = DigestTracker::default();
+        tracker.record_seen(Some("dev"));
+        tracker.record_new_contact("alice");
+        let (since, until) = window();
+
+        let text = render(
+            &tracker.take(),
+            &empty_costs(),
+            DigestVerbosity::Summary,
+            since,
+            until,
+        );
+        assert!(text.contains("Messages: 1 seen, 0 answered across 1 group(s)"));
+        assert!(text.contains("New contacts: 1"));
+        assert!(!text.contains("#dev"));
+        assert!(!text.contains("alice"));
+    }
+
+    #[test]
+    fn detailed_lists_rooms_contacts_and_flags() {
+        let tracker = DigestTracker::default();
+        tracker.record_seen(Some("dev"));
+        tracker.record_answered(Some("dev"));
+        tracker.record_seen(None);
+        tracker.record_new_contact("alice (npub1abc)");
+        tracker.record_security_flag("nsec redacted in #dev");
+        let (since, until) = window();
+
+        let text = render(
+            &tracker.take(),
+            &empty_costs(),
+            DigestVerbosity::Detailed,
+            since,
+            until,
+        );
+        assert!(text.contains("#dev: 1 seen, 1 answered"));
+        assert!(text.contains("DMs: 1 seen, 0 answered"));
+        assert!(text.contains("- alice (npub1abc)"));
+        assert!(text.contains("- nsec redacted in #dev"));
+    }
+
+    #[test]
+    fn next_delivery_follows_schedule_and_timezone() {
+        let config = NostrDigestConfig::default();
+        let (since, _) = window();
+        let next = next_delivery(&config, since).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2026, 3, 2, 8, 0, 0).unwrap());
+
+        let config = NostrDigestConfig {
+            timezone: Some("Europe/Helsinki".into()),
+            ..NostrDigestConfig::default()
+        };
+        let next = next_delivery(&config, since).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2026, 3, 2, 6, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn verbosity_parses_leniently() {
+        assert_eq!(
+            DigestVerbosity::from_str("Detailed"),
+            DigestVerbosity::Detailed
+        );
+        assert_eq!(
+            DigestVerbosity::from_str("anything"),
+            DigestVerbosity::Summary
+        );
+    }
+}
@@ -0,0 +1,670 @@
+//! Conversation export for documentation or escalation.
+//!
+//! Renders a group thread or a DM conversation, the agent's own replies
+//! included, as markdown, JSONL or JSON, optionally limited to a date
+//! range. The owner-only `conversation.export` action builds it from the
+//! live ring buffers, event cache and message index; the
+//! `zeroclaw channel export` CLI reads persisted DM history from
+//! `seen_events.db`, and group threads from the message index in
+//! `social.db` merged with the configured relays.
+
+use super::nostr::HistoryMessage;
+use super::seen_events::{DmHistoryMessage, SeenEventsStore};
+use crate::config::Config;
+use crate::memory::message_index::{self, IndexableMessage};
+use crate::memory::social;
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use nostr_core::Signer;
+use nostr_sdk::prelude::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+/// Default number of messages exported when no limit is given.
+pub const DEFAULT_EXPORT_LIMIT: usize = 200;
+/// Timeout for relay fetches made by the CLI export.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Output format of an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Jsonl,
+    /// One JSON document with the conversation metadata and its messages
+    Json,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            "json" => Ok(Self::Json),
+            other => {
+                anyhow::bail!("unknown export format '{other}' (expected markdown, jsonl or json)")
+            }
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Jsonl => "jsonl",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Time range of an export in Unix seconds, both ends inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportRange {
+    pub since: u64,
+    pub until: u64,
+}
+
+impl Default for ExportRange {
+    fn default() -> Self {
+        Self {
+            since: 0,
+            until: u64::MAX,
+        }
+    }
+}
+
+impl ExportRange {
+    /// From optional `YYYY-MM-DD` dates (UTC); `to` covers its whole day.
+    pub fn parse(from: Option<&str>, to: Option<&str>) -> Result<Self> {
+        let date = |s: &str| {
+            NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+                .with_context(|| format!("invalid date '{s}' (expected YYYY-MM-DD)"))
+        };
+        let mut range = Self::default();
+        if let Some(from) = from {
+            let start = date(from)?.and_hms_opt(0, 0, 0).unwrap_or_default();
+            range.since = start.and_utc().timestamp().max(0).unsigned_abs();
+        }
+        if let Some(to) = to {
+            let end = date(to)?.and_hms_opt(23, 59, 59).unwrap_or_default();
+            range.until = end.and_utc().timestamp().max(0).unsigned_abs();
+        }
+        anyhow::ensure!(range.since <= range.until, "--from must not be after --to");
+        Ok(range)
+    }
+
+    pub fn contains(&self, timestamp: u64) -> bool {
+        (self.since..=self.until).contains(&timestamp)
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// `since` as a date, or `None` when the range is open at the start.
+    fn from_date(&self) -> Option<String> {
+        (self.since > 0).then(|| format_date(self.since))
+    }
+
+    /// `until` as a date, or `None` when the range is open at the end.
+    fn to_date(&self) -> Option<String> {
+        (self.until < u64::MAX).then(|| format_date(self.until))
+    }
+}
+
+fn format_date(timestamp: u64) -> String {
+    i64::try_from(timestamp)
+        .ok()
+        .and_then(|ts| Utc.timestamp_opt(ts, 0).single())
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+/// Conversation to export: `#group` or a DM peer (npub or hex).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportTarget {
+    Group(String),
+    Dm(PublicKey),
+}
+
+impl ExportTarget {
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some(group) = s.strip_prefix('#') {
+            anyhow::ensure!(!group.is_empty(), "empty group name");
+            return Ok(Self::Group(group.to_string()));
+        }
+        let pk = PublicKey::parse(s)
+            .with_context(|| format!("invalid export target '{s}' (expected #group or npub)"))?;
+        Ok(Self::Dm(pk))
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Self::Group(group) => format!("#{group}"),
+            Self::Dm(pk) => format!("DM with {}", pk.to_bech32().unwrap_or_else(|_| pk.to_hex())),
+        }
+    }
+}
+
+/// One exported message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportMessage {
+    pub timestamp: u64,
+    pub sender: String,
+    pub npub: String,
+    pub event_id: String,
+    pub from_agent: bool,
+    pub content: String,
+}
+
+impl ExportMessage {
+    /// From a group ring-buffer entry; `agent_npub` marks the agent's replies.
+    pub fn from_group(msg: &HistoryMessage, agent_npub: &str) -> Self {
+        Self {
+            timestamp: msg.timestamp,
+            sender: msg.sender.clone(),
+            npub: msg.npub.clone(),
+            event_id: msg.event_id.clone(),
+            from_agent: msg.npub == agent_npub,
+            content: msg.content.clone(),
+        }
+    }
+
+    /// From a DM history row. Outgoing rows are the agent's replies.
+    pub fn from_dm(msg: &DmHistoryMessage, agent_npub: &str) -> Self {
+        let npub = if msg.is_outgoing {
+            agent_npub.to_string()
+        } else {
+            PublicKey::from_hex(&msg.sender_hex)
+                .ok()
+                .and_then(|pk| pk.to_bech32().ok())
+                .unwrap_or_else(|| msg.sender_hex.clone())
+        };
+        Self {
+            timestamp: msg.timestamp,
+            sender: msg.sender_name.clone(),
+            npub,
+            // Outgoing DMs are stored with a placeholder ID (`out_<peer>`).
+            event_id: if msg.is_outgoing {
+                String::new()
+            } else {
+                msg.event_id.clone()
+            },
+            from_agent: msg.is_outgoing,
+            content: msg.content.clone(),
+        }
+    }
+
+    /// From a message index row; `agent_hex` marks the agent's replies.
+    pub fn from_indexed(msg: &IndexableMessage, sender: &str, agent_hex: &str) -> Self {
+        Self {
+            timestamp: msg.created_at.max(0).unsigned_abs(),
+            sender: sender.to_string(),
+            npub: PublicKey::from_hex(&msg.sender_hex)
+                .ok()
+                .and_then(|pk| pk.to_bech32().ok())
+                .unwrap_or_else(|| msg.sender_hex.clone()),
+            event_id: msg.event_id.clone(),
+            from_agent: msg.sender_hex == agent_hex,
+            content: msg.content.clone(),
+        }
+    }
+
+    /// From a raw group event (relay fetch or event cache).
+    pub fn from_event(event: &Event, sender: &str, agent: &PublicKey) -> Self {
+        Self {
+            timestamp: event.created_at.as_secs(),
+            sender: sender.to_string(),
+            npub: event
+                .pubkey
+                .to_bech32()
+                .unwrap_or_else(|_| event.pubkey.to_hex()),
+            event_id: event.id.to_hex(),
+            from_agent: event.pubkey == *agent,
+            content: event.content.clone(),
+        }
+    }
+}
+
+/// Merge group messages from several sources: drop those outside `range`,
+/// dedup by event ID, sort by time and keep the newest `limit`.
+pub fn merge_group(
+    messages: impl IntoIterator<Item = ExportMessage>,
+    range: ExportRange,
+    limit: usize,
+) -> Vec<ExportMessage> {
+    let mut seen = HashSet::new();
+    let mut merged: Vec<ExportMessage> = messages
+        .into_iter()
+        .filter(|m| range.contains(m.timestamp))
+        .filter(|m| m.event_id.is_empty() || seen.insert(m.event_id.clone()))
+        .collect();
+    merged.sort_by_key(|m| m.timestamp);
+    let skip = merged.len().saturating_sub(limit);
+    merged.drain(..skip);
+    merged
+}
+
+/// Render an export in the requested format.
+pub fn render(
+    target: &ExportTarget,
+    messages: &[ExportMessage],
+    format: ExportFormat,
+    range: ExportRange,
+    exported_at: DateTime<Utc>,
+) -> String {
+    match format {
+        ExportFormat::Jsonl => messages
+            .iter()
+            .filter_map(|m| serde_json::to_string(m).ok())
+            .map(|line| line + "\n")
+            .collect(),
+        ExportFormat::Json => {
+            let doc = serde_json::json!({
+                "target": target.label(),
+                "from": range.from_date(),
+                "to": range.to_date(),
+                "exported_at": exported_at.to_rfc3339(),
+                "messages": messages,
+            });
+            serde_json::to_string_pretty(&doc).unwrap_or_default() + "\n"
+        }
+        ExportFormat::Markdown => render_markdown(target, messages, range, exported_at),
+    }
+}
+
+fn render_markdown(
+    target: &ExportTarget,
+    messages: &[ExportMessage],
+    range: ExportRange,
+    exported_at: DateTime<Utc>,
+) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Conversation export: {}", target.label());
+    let _ = writeln!(
+        out,
+        "\nExported {} UTC, {} message(s).",
+        exported_at.format("%Y-%m-%d %H:%M"),
+        messages.len()
+    );
+    if range.is_bounded() {
+        let _ = writeln!(
+            out,
+            "Range: {} to {} (UTC).",
+            range.from_date().as_deref().unwrap_or("start"),
+            range.to_date().as_deref().unwrap_or("now")
+        );
+    }
+    for msg in messages {
+        let when = Utc
+            .timestamp_opt(msg.timestamp as i64, 0)
+            .single()
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_else(|| msg.timestamp.to_string());
+        let role = if msg.from_agent { " (agent)" } else { "" };
+        let _ = writeln!(out, "\n**{}**{role} · {when}", msg.sender);
+        for line in msg.content.lines() {
+            let _ = writeln!(out, "> {line}");
+        }
+    }
+    out
+}
+
+/// CLI entry point for `zeroclaw channel export`.
+pub async fn export_cli(
+    config: &Config,
+    target: &str,
+    format: &str,
+    limit: Option<usize>,
+    from: Option<&str>,
+    to: Option<&str>,
+    output: Option<&Path>,
+) -> Result<()> {
+    let target = ExportTarget::parse(target)?;
+    let format = ExportFormat::parse(format)?;
+    let range = ExportRange::parse(from, to)?;
+    let limit = limit.unwrap_or(DEFAULT_EXPORT_LIMIT).max(1);
+    let ns = config
+        .channels_config
+        .nostr
+        .as_ref()
+        .context("Nostr channel is not configured")?;
+    let persist_dir = config.config_path.parent().unwrap_or(Path::new("."));
+    let signer = super::snowclaw_channels::nostr_signer(ns, persist_dir)
+        .await?
+        .context("No Nostr identity configured (set nsec, SNOWCLAW_NSEC or a remote signer)")?;
+    let agent_npub = signer.public_key().to_bech32()?;
+
+    let messages = match &target {
+        ExportTarget::Dm(peer) => {
+            let store = SeenEventsStore::new(persist_dir, None)?;
+            store
+                .dm_conversation_between(&peer.to_hex(), range.since, range.until, limit)?
+                .iter()
+                .map(|m| ExportMessage::from_dm(m, &agent_npub))
+                .collect()
+        }
+        ExportTarget::Group(group) => {
+            export_group(&signer, &ns.relays, persist_dir, group, range, limit).await?
+        }
+    };
+
+    let text = render(&target, &messages, format, range, Utc::now());
+    match output {
+        Some(path) => {
+            std::fs::write(path, &text)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!(
+                "Exported {} message(s) from {} to {}",
+                messages.len(),
+                target.label(),
+                path.display()
+            );
+        }
+        None => print!("{text}"),
+    }
+    Ok(())
+}
+
+/// The newest `limit` messages of a NIP-29 group within `range`: the
+/// message index in `social.db` merged with the configured relays, which
+/// also hold the agent's own replies. Sender names come from social memory,
+/// then from kind 0 profiles.
+async fn export_group(
+    signer: &Signer,
+    relays: &[String],
+    persist_dir: &Path,
+    group: &str,
+    range: ExportRange,
+    limit: usize,
+) -> Result<Vec<ExportMessage>> {
+    let db_path = persist_dir.join("social.db");
+    let social_conn = if db_path.exists() {
+        let conn = crate::security::db_encryption::open(&db_path)?;
+        social::create_social_tables(&conn)?;
+        message_index::create_message_tables(&conn)?;
+        Some(conn)
+    } else {
+        None
+    };
+    let indexed = match &social_conn {
+        Some(conn) => message_index::group_messages(
+            conn,
+            group,
+            i64::try_from(range.since).unwrap_or(i64::MAX),
+            i64::try_from(range.until).unwrap_or(i64::MAX),
+            limit,
+        )?,
+        None => Vec::new(),
+    };
+
+    let client = super::snowclaw_channels::nostr_client(signer.clone(), relays).await?;
+    client.connect().await;
+
+    let mut filter = Filter::new()
+        .kinds(vec![Kind::Custom(9), Kind::Custom(11), Kind::Custom(12)])
+        .custom_tag(SingleLetterTag::lowercase(Alphabet::H), group)
+        .limit(limit);
+    if range.since > 0 {
+        filter = filter.since(Timestamp::from(range.since));
+    }
+    if range.until < u64::MAX {
+        filter = filter.until(Timestamp::from(range.until));
+    }
+    // Relays are only needed for what the index lacks, so a failed fetch
+    // still exports the indexed messages.
+    let events: Vec<Event> = match client.fetch_events(filter, FETCH_TIMEOUT).await {
+        Ok(events) => events.into_iter().collect(),
+        Err(e) if !indexed.is_empty() => {
+            tracing::warn!("Export: failed to fetch group messages from relays: {e}");
+            Vec::new()
+        }
+        Err(e) => {
+            client.disconnect().await;
+            return Err(e).context("Failed to fetch group messages");
+        }
+    };
+
+    let authors: HashSet<String> = events
+        .iter()
+        .map(|e| e.pubkey.to_hex())
+        .chain(indexed.iter().map(|m| m.sender_hex.clone()))
+        .collect();
+    let mut names: HashMap<String, String> = HashMap::new();
+    if let Some(conn) = &social_conn {
+        for hex in &authors {
+            if let Ok(Some(npub)) = social::get_npub(conn, hex) {
+                if !npub.display_name.is_empty() {
+                    names.insert(hex.clone(), npub.display_name);
+                }
+            }
+        }
+    }
+    let unnamed: Vec<PublicKey> = authors
+        .iter()
+        .filter(|hex| !names.contains_key(*hex))
+        .filter_map(|hex| PublicKey::from_hex(hex).ok())
+        .collect();
+    if !unnamed.is_empty() {
+        let filter = Filter::new().authors(unnamed).kind(Kind::Metadata);
+        if let Ok(profiles) = client.fetch_events(filter, FETCH_TIMEOUT).await {
+            for profile in profiles {
+                if let Ok(meta) = Metadata::from_json(&profile.content) {
+                    if let Some(name) = meta.display_name.or(meta.name) {
+                        names.insert(profile.pubkey.to_hex(), name);
+                    }
+                }
+            }
+        }
+    }
+    client.disconnect().await;
+
+    let agent = signer.public_key();
+    let agent_hex = agent.to_hex();
+    let name_of = |hex: &str| {
+        names
+            .get(hex)
+            .cloned()
+            .unwrap_or_else(|| hex.chars().take(8).collect())
+    };
+    let messages = events
+        .iter()
+        .map(|event| ExportMessage::from_event(event, &name_of(&event.pubkey.to_hex()), &agent))
+        .chain(
+            indexed
+                .iter()
+                .map(|m| ExportMessage::from_indexed(m, &name_of(&m.sender_hex), &agent_hex)),
+        );
+    Ok(merge_group(messages, range, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg(ts: u64, id: &str, from_agent: bool) -> ExportMessage {
+        ExportMessage {
+            timestamp: ts,
+            sender: if from_agent { "snowclaw" } else { "alice" }.into(),
+            npub: "npub1x".into(),
+            event_id: id.into(),
+            from_agent,
+            content: format!("message {id}"),
+        }
+    }
+
+    #[test]
+    fn target_parses_groups_and_npubs() {
+        assert_eq!(
+            ExportTarget::parse("#dev").unwrap(),
+            ExportTarget::Group("dev".into())
+        );
+        let pk = Keys::generate().public_key();
+        assert_eq!(
+            ExportTarget::parse(&pk.to_bech32().unwrap()).unwrap(),
+            ExportTarget::Dm(pk)
+        );
+        assert_eq!(
+            ExportTarget::parse(&pk.to_hex()).unwrap(),
+            ExportTarget::Dm(pk)
+        );
+        assert!(ExportTarget::parse("#").is_err());
+        assert!(ExportTarget::parse("dev").is_err());
+    }
+
+    #[test]
+    fn format_rejects_unknown_values() {
+        assert_eq!(ExportFormat::parse("MD").unwrap(), ExportFormat::Markdown);
+        assert_eq!(ExportFormat::parse("jsonl").unwrap(), ExportFormat::Jsonl);
+        assert_eq!(ExportFormat::parse("json").unwrap(), ExportFormat::Json);
+        assert!(ExportFormat::parse("pdf").is_err());
+    }
+
+    #[test]
+    fn merge_dedups_sorts_and_limits() {
+        let merged = merge_group(
+            vec![
+                msg(30, "c", false),
+                msg(10, "a", false),
+                msg(20, "b", true),
+                msg(10, "a", false),
+            ],
+            ExportRange::default(),
+            2,
+        );
+        let ids: Vec<&str> = merged.iter().map(|m| m.event_id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "c"]);
+
+        let range = ExportRange {
+            since: 10,
+            until: 25,
+        };
+        let merged = merge_group(
+            vec![msg(30, "c", false), msg(10, "a", false), msg(20, "b", true)],
+            range,
+            10,
+        );
+        let ids: Vec<&str> = merged.iter().map(|m| m.event_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn range_covers_whole_days() {
+        let range = ExportRange::parse(Some("2026-03-01"), Some("2026-03-02")).unwrap();
+        let start = Utc
+            .with_ymd_and_hms(2026, 3, 1, 0, 0, 0)
+            .unwrap()
+            .timestamp();
+        let end = Utc
+            .with_ymd_and_hms(2026, 3, 2, 23, 59, 59)
+            .unwrap()
+            .timestamp();
+        assert_eq!(range.since, start.unsigned_abs());
+        assert_eq!(range.until, end.unsigned_abs());
+        assert!(range.contains(range.until));
+        assert!(!range.contains(range.until + 1));
+        assert_eq!(range.from_date().as_deref(), Some("2026-03-01"));
+        assert_eq!(range.to_date().as_deref(), Some("2026-03-02"));
+
+        let open = ExportRange::parse(None, None).unwrap();
+        assert!(!open.is_bounded());
+        assert_eq!(open.to_date(), None);
+        assert!(ExportRange::parse(Some("2026-03-02"), Some("2026-03-01")).is_err());
+        assert!(ExportRange::parse(Some("03/01/2026"), None).is_err());
+    }
+
+    #[test]
+    fn indexed_rows_mark_agent_messages() {
+        let agent = Keys::generate().public_key();
+        let row = IndexableMessage {
+            event_id: "ev1".into(),
+            sender_hex: agent.to_hex(),
+            group_id: Some("dev".into()),
+            content: "indexed reply".into(),
+            created_at: 42,
+            kind: 9,
+        };
+        let msg = ExportMessage::from_indexed(&row, "snowclaw", &agent.to_hex());
+        assert!(msg.from_agent);
+        assert_eq!(msg.timestamp, 42);
+        assert_eq!(msg.npub, agent.to_bech32().unwrap());
+        let other = ExportMessage::from_indexed(&row, "snowclaw", "ff00");
+        assert!(!other.from_agent);
+    }
+
+    #[test]
+    fn dm_rows_mark_outgoing_as_agent() {
+        let peer = Keys::generate().public_key();
+        let incoming = DmHistoryMessage {
+            sender_hex: peer.to_hex(),
+            sender_name: "alice".into(),
+            content: "hi".into(),
+            timestamp: 1,
+            event_id: "ev1".into(),
+            is_outgoing: false,
+        };
+        let outgoing = DmHistoryMessage {
+            sender_name: "snowclaw".into(),
+            event_id: format!("out_{}", peer.to_hex()),
+            is_outgoing: true,
+            ..incoming.clone()
+        };
+
+        let a = ExportMessage::from_dm(&incoming, "npub1agent");
+        assert!(!a.from_agent);
+        assert_eq!(a.npub, peer.to_bech32().unwrap());
+        let b = ExportMessage::from_dm(&outgoing, "npub1agent");
+        assert!(b.from_agent);
+        assert_eq!(b.npub, "npub1agent");
+        assert!(b.event_id.is_empty());
+    }
+
+    #[test]
+    fn renders_markdown_and_jsonl() {
+        let target = ExportTarget::Group("dev".into());
+        let mut reply = msg(20, "b", true);
+        reply.content = "line one\nline two".into();
+        let messages = vec![msg(10, "a", false), reply];
+        let at = Utc.with_ymd_and_hms(2026, 3, 1, 8, 0, 0).unwrap();
+
+        let md = render(
+            &target,
+            &messages,
+            ExportFormat::Markdown,
+            ExportRange::default(),
+            at,
+        );
+        assert!(md.starts_with("# Conversation export: #dev"));
+        assert!(md.contains("2 message(s)"));
+        assert!(!md.contains("Range:"));
+        assert!(md.contains("**snowclaw** (agent) · 1970-01-01 00:00:20 UTC"));
+        assert!(md.contains("> line one\n> line two"));
+
+        let jsonl = render(
+            &target,
+            &messages,
+            ExportFormat::Jsonl,
+            ExportRange::default(),
+            at,
+        );
+        let lines: Vec<&str> = jsonl.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let second: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second["from_agent"], true);
+        assert_eq!(second["event_id"], "b");
+
+        let range = ExportRange::parse(Some("2026-03-01"), None).unwrap();
+        let md = render(&target, &messages, ExportFormat::Markdown, range, at);
+        assert!(md.contains("Range: 2026-03-01 to now (UTC)."));
+
+        let json = render(&target, &messages, ExportFormat::Json, range, at);
+        let doc: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(doc["target"], "#dev");
+        assert_eq!(doc["from"], "2026-03-01");
+        assert_eq!(doc["to"], serde_json::Value::Null);
+        assert_eq!(doc["messages"].as_array().unwrap().len(), 2);
+        assert_eq!(doc["messages"][1]["from_agent"], true);
+    }
+}
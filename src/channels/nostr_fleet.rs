@@ -0,0 +1,390 @@
+//! Fleet view over kind 31121 agent state history.
+//!
+//! The Nostr listener appends our own and peer agents' state events to
+//! `social_agent_states` (see [`crate::memory::social::record_agent_state`]).
+//! [`fleet_view`] turns that history into one row per agent with its latest
+//! state, per-day uptime, restarts and version changes; `snowclaw agents
+//! status` renders it as a table or JSON for dashboards.
+//!
+//! Agents republish `online` state every heartbeat and `offline` on a
+//! graceful shutdown. One still `online` after [`STALE_AFTER_HEARTBEATS`]
+//! missed heartbeats died without saying so and is shown as `stale`.
+//!
+//! Running agents also send an ephemeral [`HEARTBEAT_KIND`] event every
+//! minute. Relays don't store it and the listener keeps only the latest one
+//! per peer in memory, which lets it notice a dead peer within minutes
+//! instead of waiting for the state event to age out.
+
+use crate::memory::social::AgentStateRecord;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write as _;
+
+const DAY_SECS: i64 = 86_400;
+
+/// Heartbeat assumed for agents whose state has no `heartbeat_secs`.
+pub const DEFAULT_HEARTBEAT_SECS: u64 = 15 * 60;
+/// Missed heartbeats after which an `online` agent counts as stale.
+pub const STALE_AFTER_HEARTBEATS: u64 = 3;
+/// Ephemeral liveness signal (the ephemeral counterpart of kind 31121).
+pub const HEARTBEAT_KIND: u16 = 21121;
+
+/// Latest ephemeral heartbeat seen from a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Heartbeat {
+    pub at: i64,
+    pub interval_secs: u64,
+}
+
+impl Heartbeat {
+    /// Heartbeat sent at `at` with the event's `content`.
+    pub fn parse(content: &str, at: i64) -> Self {
+        Self {
+            at,
+            interval_secs: heartbeat_secs(content).unwrap_or(DEFAULT_HEARTBEAT_SECS),
+        }
+    }
+
+    /// Whether [`STALE_AFTER_HEARTBEATS`] heartbeats were missed at `now`.
+    pub fn is_missed(&self, now: i64) -> bool {
+        #[allow(clippy::cast_possible_wrap)]
+        let max_age = (self.interval_secs * STALE_AFTER_HEARTBEATS) as i64;
+        now - self.at > max_age
+    }
+}
+
+/// One agent in the fleet view.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FleetAgent {
+    pub hex_pubkey: String,
+    pub name: String,
+    /// Latest status, or `stale` for an `online` agent that stopped
+    /// sending heartbeats.
+    pub status: String,
+    pub version: Option<String>,
+    pub last_seen: i64,
+    /// Latest version differs from the most common version in the fleet.
+    pub version_drift: bool,
+    /// Versions reported in the window, in order, without repeats in a row.
+    pub versions: Vec<String>,
+    /// Distinct process starts (`uptime_start` values) in the window.
+    pub starts: usize,
+    /// Hours with at least one state event, per day, oldest day first.
+    pub hours_online: Vec<u8>,
+}
+
+/// Fleet summary for `snowclaw agents status`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FleetView {
+    pub generated_at: i64,
+    pub days: u32,
+    /// Most common latest version across the fleet.
+    pub main_version: Option<String>,
+    pub agents: Vec<FleetAgent>,
+}
+
+/// Build the fleet view from each agent's latest state and the state
+/// history of the last `days` days before `now`.
+pub fn fleet_view(
+    latest: &[AgentStateRecord],
+    history: &[AgentStateRecord],
+    names: &HashMap<String, String>,
+    now: i64,
+    days: u32,
+) -> FleetView {
+    let mut version_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for state in latest {
+        if let Some(version) = state.version.as_deref() {
+            *version_counts.entry(version).or_default() += 1;
+        }
+    }
+    // Most agents wins; ties go to the later version string.
+    let main_version = version_counts
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)))
+        .map(|(v, _)| (*v).to_string());
+
+    let first_day = now - i64::from(days) * DAY_SECS;
+    let agents = latest
+        .iter()
+        .map(|state| {
+            let own: Vec<&AgentStateRecord> = history
+                .iter()
+                .filter(|s| s.hex_pubkey == state.hex_pubkey && s.created_at >= first_day)
+                .collect();
+
+            let mut versions: Vec<String> = Vec::new();
+            for version in own.iter().filter_map(|s| s.version.as_deref()) {
+                if versions.last().map(String::as_str) != Some(version) {
+                    versions.push(version.to_string());
+                }
+            }
+
+            let starts = own
+                .iter()
+                .filter_map(|s| uptime_start(&s.content))
+                .collect::<BTreeSet<_>>()
+                .len();
+
+            let mut hours: Vec<BTreeSet<i64>> = vec![BTreeSet::new(); days as usize];
+            for s in &own {
+                let day = ((s.created_at - first_day) / DAY_SECS) as usize;
+                if let Some(set) = hours.get_mut(day) {
+                    set.insert((s.created_at - first_day) / 3600);
+                }
+            }
+
+            FleetAgent {
+                hex_pubkey: state.hex_pubkey.clone(),
+                name: names
+                    .get(&state.hex_pubkey)
+                    .cloned()
+                    .unwrap_or_else(|| short_key(&state.hex_pubkey)),
+                status: if is_stale(state, now) {
+                    "stale".to_string()
+                } else {
+                    state.status.clone()
+                },
+                version: state.version.clone(),
+                last_seen: state.created_at,
+                version_drift: state.version.is_some() && state.version != main_version,
+                versions,
+                starts,
+                hours_online: hours.iter().map(|h| h.len() as u8).collect(),
+            }
+        })
+        .collect();
+
+    FleetView {
+        generated_at: now,
+        days,
+        main_version,
+        agents,
+    }
+}
+
+/// Plain-text fleet table. `history` adds uptime, starts and versions.
+pub fn render(view: &FleetView, history: bool) -> String {
+    let mut out = String::new();
+    if view.agents.is_empty() {
+        out.push_str("No agent states recorded yet.\n");
+        return out;
+    }
+
+    let _ = writeln!(
+        out,
+        "{} agent(s), main version {}",
+        view.agents.len(),
+        view.main_version.as_deref().unwrap_or("unknown")
+    );
+    let _ = write!(
+        out,
+        "\n{:<20} {:<8} {:<10} {:<10}",
+        "AGENT", "STATUS", "VERSION", "LAST SEEN"
+    );
+    if history {
+        let _ = write!(
+            out,
+            " {:<width$} {:<6} VERSIONS",
+            format!("UPTIME {}D", view.days),
+            "STARTS",
+            width = (view.days as usize).max(9)
+        );
+    }
+    out.push('\n');
+
+    for agent in &view.agents {
+        let version = match (&agent.version, agent.version_drift) {
+            (Some(v), true) => format!("{v}*"),
+            (Some(v), false) => v.clone(),
+            (None, _) => "-".to_string(),
+        };
+        let _ = write!(
+            out,
+            "{:<20} {:<8} {:<10} {:<10}",
+            truncate(&agent.name, 20),
+            agent.status,
+            version,
+            ago(view.generated_at - agent.last_seen)
+        );
+        if history {
+            let bar: String = agent.hours_online.iter().map(|h| uptime_cell(*h)).collect();
+            let _ = write!(
+                out,
+                " {:<width$} {:<6} {}",
+                bar,
+                agent.starts,
+                agent.versions.join(" → "),
+                width = (view.days as usize).max(9)
+            );
+        }
+        out.push('\n');
+    }
+
+    if view.agents.iter().any(|a| a.version_drift) {
+        out.push_str("\n* differs from the main version\n");
+    }
+    if history {
+        out.push_str("Uptime per day: █ 20h+  ▄ some  · none\n");
+    }
+    out
+}
+
+/// Whether `state` claims `online` but is older than
+/// [`STALE_AFTER_HEARTBEATS`] of the agent's heartbeats at `now`.
+pub fn is_stale(state: &AgentStateRecord, now: i64) -> bool {
+    let heartbeat = heartbeat_secs(&state.content).unwrap_or(DEFAULT_HEARTBEAT_SECS);
+    #[allow(clippy::cast_possible_wrap)]
+    let max_age = (heartbeat * STALE_AFTER_HEARTBEATS) as i64;
+    state.status == "online" && now - state.created_at > max_age
+}
+
+/// [`is_stale`], judged by the ephemeral `heartbeat` when it is at least as
+/// recent as the state event.
+pub fn is_stale_with(state: &AgentStateRecord, heartbeat: Option<&Heartbeat>, now: i64) -> bool {
+    match heartbeat {
+        Some(hb) if hb.at >= state.created_at => state.status == "online" && hb.is_missed(now),
+        _ => is_stale(state, now),
+    }
+}
+
+fn heartbeat_secs(content: &str) -> Option<u64> {
+    serde_json::from_str::<serde_json::Value>(content)
+        .ok()?
+        .get("heartbeat_secs")?
+        .as_u64()
+        .filter(|secs| *secs > 0)
+}
+
+fn uptime_start(content: &str) -> Option<u64> {
+    serde_json::from_str::<serde_json::Value>(content)
+        .ok()?
+        .get("uptime_start")?
+        .as_u64()
+}
+
+fn uptime_cell(hours: u8) -> char {
+    match hours {
+        0 => '·',
+        1..=19 => '▄',
+        _ => '█',
+    }
+}
+
+fn ago(secs: i64) -> String {
+    match secs.max(0) {
+        s if s < 60 => "now".to_string(),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < DAY_SECS => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / DAY_SECS),
+    }
+}
+
+fn short_key(hex: &str) -> String {
+    format!("{}…", hex.chars().take(12).collect::<String>())
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        format!("{}…", s.chars().take(max - 1).collect::<String>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn state(hex: &str, version: &str, start: u64, at: i64) -> AgentStateRecord {
+        AgentStateRecord {
+            hex_pubkey: hex.to_string(),
+            d_tag: "snowclaw:status".to_string(),
+            status: "online".to_string(),
+            version: Some(version.to_string()),
+            content: format!(r#"{{"uptime_start":{start}}}"#),
+            created_at: at,
+        }
+    }
+
+    #[test]
+    fn builds_uptime_starts_and_drift() {
+        let history = vec![
+            state("aa", "0.4.0", 1, NOW - 2 * DAY_SECS + 60),
+            state("aa", "0.5.0", 2, NOW - DAY_SECS + 60),
+            state("aa", "0.5.0", 2, NOW - DAY_SECS + 3_700),
+            state("bb", "0.5.0", 5, NOW - 600),
+            state("cc", "0.4.0", 7, NOW - 300),
+        ];
+        let latest = vec![history[2].clone(), history[3].clone(), history[4].clone()];
+        let names = HashMap::from([("bb".to_string(), "snow-studio".to_string())]);
+
+        let view = fleet_view(&latest, &history, &names, NOW, 3);
+        assert_eq!(view.main_version.as_deref(), Some("0.5.0"));
+
+        let aa = &view.agents[0];
+        assert_eq!(aa.name, "aa…");
+        assert_eq!(aa.versions, vec!["0.4.0", "0.5.0"]);
+        assert_eq!(aa.starts, 2);
+        assert_eq!(aa.hours_online, vec![0, 1, 2]);
+        assert!(!aa.version_drift);
+
+        assert_eq!(view.agents[1].name, "snow-studio");
+        assert!(view.agents[2].version_drift);
+
+        let text = render(&view, true);
+        assert!(text.contains("main version 0.5.0"));
+        assert!(text.contains("0.4.0*"));
+        assert!(text.contains("0.4.0 → 0.5.0"));
+        assert!(text.contains("·▄▄"));
+    }
+
+    #[test]
+    fn online_agent_without_heartbeat_goes_stale() {
+        let mut fresh = state("aa", "0.5.0", 1, NOW - 600);
+        fresh.content = r#"{"uptime_start":1,"heartbeat_secs":300}"#.to_string();
+        assert!(!is_stale(&fresh, NOW));
+        assert!(is_stale(&fresh, NOW + 600));
+
+        // Default heartbeat when the state does not advertise one.
+        let old = state("bb", "0.5.0", 1, NOW - 2 * 3600);
+        assert!(is_stale(&old, NOW));
+        let mut offline = old.clone();
+        offline.status = "offline".to_string();
+        assert!(!is_stale(&offline, NOW));
+
+        let view = fleet_view(&[fresh, old, offline], &[], &HashMap::new(), NOW, 1);
+        let statuses: Vec<&str> = view.agents.iter().map(|a| a.status.as_str()).collect();
+        assert_eq!(statuses, vec!["online", "stale", "offline"]);
+    }
+
+    #[test]
+    fn ephemeral_heartbeats_judge_newer_liveness() {
+        let stated = state("aa", "0.5.0", 1, NOW - 2 * 3600);
+        assert!(is_stale(&stated, NOW));
+
+        let beating = Heartbeat::parse(r#"{"heartbeat_secs":60}"#, NOW - 60);
+        assert!(!is_stale_with(&stated, Some(&beating), NOW));
+        // Three missed heartbeats mark it stale long before the state ages out.
+        let fresh = state("aa", "0.5.0", 1, NOW - 600);
+        let silent = Heartbeat::parse(r#"{"heartbeat_secs":60}"#, NOW - 300);
+        assert!(!is_stale(&fresh, NOW));
+        assert!(is_stale_with(&fresh, Some(&silent), NOW));
+
+        // A state event newer than the last heartbeat (e.g. a restart) wins.
+        let restarted = state("aa", "0.5.0", 2, NOW - 120);
+        assert!(!is_stale_with(&restarted, Some(&silent), NOW));
+        let mut offline = fresh.clone();
+        offline.status = "offline".to_string();
+        assert!(!is_stale_with(&offline, Some(&silent), NOW));
+    }
+
+    #[test]
+    fn empty_fleet() {
+        let view = fleet_view(&[], &[], &HashMap::new(), NOW, 7);
+        assert!(view.main_version.is_none());
+        assert_eq!(render(&view, false), "No agent states recorded yet.\n");
+    }
+}
@@ -0,0 +1,414 @@
+//! Fetching of links shared in Nostr groups into the document index.
+//!
+//! When `[channels_config.nostr.link_index]` is enabled, URLs in indexed
+//! group messages are queued for a background worker. It checks each URL
+//! against the domain allowlist and `[security.url_access]`, fetches it
+//! with size and time limits, strips page chrome the same way `web_fetch`
+//! does and indexes the text with attribution to the sharing event (see
+//! `memory::link_index`). A URL is fetched once, whether or not it worked.
+
+use anyhow::{bail, Context, Result};
+use parking_lot::Mutex as ParkingMutex;
+use regex::Regex;
+use rusqlite::Connection;
+use std::collections::VecDeque;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::config::{NostrLinkIndexConfig, UrlAccessConfig};
+use crate::memory::link_index::{self, SharedLink};
+use crate::tools::url_validation::{
+    normalize_allowed_domains, validate_url, DomainPolicy, UrlSchemePolicy,
+};
+use crate::tools::web_fetch::WebFetchTool;
+
+/// Links waiting for the worker; further links are dropped while full.
+pub const LINK_QUEUE_CAPACITY: usize = 64;
+/// URLs taken from one message at most.
+const MAX_LINKS_PER_MESSAGE: usize = 5;
+/// Redirects followed (each target is re-validated).
+const MAX_REDIRECTS: usize = 3;
+/// Window for `max_per_hour`.
+const RATE_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Extensions of media files, which have no text worth indexing.
+const MEDIA_EXTENSIONS: &[&str] = &[
+    ".png", ".jpg", ".jpeg", ".gif", ".webp", ".svg", ".mp4", ".webm", ".mov", ".mp3", ".ogg",
+    ".wav", ".zip", ".gz",
+];
+
+/// Readable text of a fetched page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page {
+    pub title: Option<String>,
+    pub text: String,
+}
+
+/// Extract up to five distinct http(s) URLs from message content, without
+/// fragments, trailing punctuation or links to media files.
+pub fn extract_urls(content: &str) -> Vec<String> {
+    static URL_RE: OnceLock<Regex> = OnceLock::new();
+    let re = URL_RE.get_or_init(|| Regex::new(r#"https?://[^\s<>"'`]+"#).expect("URL regex"));
+
+    let mut urls = Vec::new();
+    for m in re.find_iter(content) {
+        let mut raw = m.as_str();
+        loop {
+            raw = raw.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '"']);
+            // Drop a closing bracket that belongs to the surrounding text.
+            let (open, close) = match raw.chars().last() {
+                Some(')') => ('(', ')'),
+                Some(']') => ('[', ']'),
+                _ => break,
+            };
+            if raw.matches(open).count() >= raw.matches(close).count() {
+                break;
+            }
+            raw = &raw[..raw.len() - 1];
+        }
+        let Ok(mut url) = reqwest::Url::parse(raw) else {
+            continue;
+        };
+        url.set_fragment(None);
+        let path = url.path().to_ascii_lowercase();
+        if MEDIA_EXTENSIONS.iter().any(|ext| path.ends_with(ext)) {
+            continue;
+        }
+        let url = url.to_string();
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+        if urls.len() == MAX_LINKS_PER_MESSAGE {
+            break;
+        }
+    }
+    urls
+}
+
+/// Turn a response body into readable text, keeping at most `max_chars`.
+pub fn extract_page(content_type: &str, body: &str, max_chars: usize) -> Result<Page> {
+    let (title, text) = if content_type.contains("text/html")
+        || content_type.contains("application/xhtml")
+        || content_type.is_empty()
+    {
+        static TITLE_RE: OnceLock<Regex> = OnceLock::new();
+        let title_re = TITLE_RE
+            .get_or_init(|| Regex::new(r"(?si)<title[^>]*>(.*?)</title>").expect("title regex"));
+        let title = title_re
+            .captures(body)
+            .map(|c| collapse_whitespace(&nanohtml2text::html2text(&c[1])))
+            .filter(|t| !t.is_empty());
+        let cleaned = WebFetchTool::strip_noise_elements(body)?;
+        (title, nanohtml2text::html2text(&cleaned))
+    } else if content_type.contains("text/plain") || content_type.contains("text/markdown") {
+        (None, body.to_string())
+    } else {
+        bail!("unsupported content type {content_type}");
+    };
+
+    let mut text = squeeze_blank_lines(&text);
+    if text.is_empty() {
+        bail!("no readable text");
+    }
+    if let Some((cut, _)) = text.char_indices().nth(max_chars) {
+        text.truncate(cut);
+    }
+    Ok(Page { title, text })
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Trim lines and keep at most one blank line between paragraphs.
+fn squeeze_blank_lines(text: &str) -> String {
+    let mut out = String::new();
+    let mut blank = false;
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            blank = !out.is_empty();
+            continue;
+        }
+        if blank {
+            out.push('\n');
+            blank = false;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+/// Background worker that fetches and indexes queued links.
+pub struct LinkFetcher {
+    config: NostrLinkIndexConfig,
+    allowed_domains: Vec<String>,
+    blocked_domains: Vec<String>,
+    url_access: UrlAccessConfig,
+    conn: Arc<ParkingMutex<Connection>>,
+    client: reqwest::Client,
+    /// Start times of fetches in the last hour
+    recent: VecDeque<Instant>,
+}
+
+impl LinkFetcher {
+    pub fn new(
+        config: NostrLinkIndexConfig,
+        url_access: UrlAccessConfig,
+        conn: Arc<ParkingMutex<Connection>>,
+    ) -> Result<Self> {
+        let builder = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+            .connect_timeout(Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(concat!(
+                "snowclaw/",
+                env!("CARGO_PKG_VERSION"),
+                " link-index"
+            ));
+        let builder = crate::config::apply_runtime_proxy_to_builder(builder, "tool.web_fetch");
+        let client = builder
+            .build()
+            .context("failed to build link fetch client")?;
+        Ok(Self {
+            allowed_domains: normalize_allowed_domains(config.allowed_domains.clone()),
+            blocked_domains: normalize_allowed_domains(config.blocked_domains.clone()),
+            config,
+            url_access,
+            conn,
+            client,
+            recent: VecDeque::new(),
+        })
+    }
+
+    /// Process queued links until every sender is dropped.
+    pub async fn run(mut self, mut rx: mpsc::Receiver<SharedLink>) {
+        while let Some(link) = rx.recv().await {
+            self.process(link).await;
+        }
+        debug!("Link index queue closed");
+    }
+
+    async fn process(&mut self, link: SharedLink) {
+        match link_index::is_known(&self.conn.lock(), &link.url) {
+            Ok(false) => {}
+            Ok(true) => return,
+            Err(e) => {
+                warn!("Link index lookup failed: {e}");
+                return;
+            }
+        }
+        if let Err(e) = self.validate(&link.url) {
+            debug!(url = %link.url, "Not indexing link: {e}");
+            return;
+        }
+        if !self.take_slot(Instant::now()) {
+            debug!(url = %link.url, "Link index hourly limit reached, skipping");
+            return;
+        }
+
+        let result = self.fetch(&link.url).await;
+        let db = self.conn.lock();
+        match result {
+            Ok(page) => match link_index::index_link(&db, &link, page.title.as_deref(), &page.text)
+            {
+                Ok(chunks) => info!(url = %link.url, chunks, "Indexed shared link"),
+                Err(e) => warn!(url = %link.url, "Failed to index shared link: {e}"),
+            },
+            Err(e) => {
+                debug!(url = %link.url, "Failed to fetch shared link: {e:#}");
+                if let Err(e) = link_index::record_failure(&db, &link, &format!("{e:#}")) {
+                    warn!("Failed to record link fetch failure: {e}");
+                }
+            }
+        }
+    }
+
+    fn validate(&self, url: &str) -> Result<String> {
+        validate_url(
+            url,
+            &DomainPolicy {
+                allowed_domains: &self.allowed_domains,
+                blocked_domains: &self.blocked_domains,
+                allowed_field_name: "link_index.allowed_domains",
+                blocked_field_name: Some("link_index.blocked_domains"),
+                empty_allowed_message: "link_index.allowed_domains is empty",
+                scheme_policy: UrlSchemePolicy::HttpOrHttps,
+                ipv6_error_context: "link_index",
+                url_access: Some(&self.url_access),
+            },
+        )
+    }
+
+    /// Reserve a fetch under `max_per_hour`.
+    fn take_slot(&mut self, now: Instant) -> bool {
+        while self
+            .recent
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        if self.recent.len() >= self.config.max_per_hour as usize {
+            return false;
+        }
+        self.recent.push_back(now);
+        true
+    }
+
+    async fn fetch(&self, url: &str) -> Result<Page> {
+        let mut url = url.to_string();
+        for _ in 0..=MAX_REDIRECTS {
+            let mut response = self.client.get(&url).send().await?;
+
+            if response.status().is_redirection() {
+                let location = response
+                    .headers()
+                    .get(reqwest::header::LOCATION)
+                    .and_then(|v| v.to_str().ok())
+                    .context("redirect without Location header")?;
+                url = reqwest::Url::parse(&url)
+                    .and_then(|base| base.join(location))
+                    .context("invalid redirect Location header")?
+                    .to_string();
+                self.validate(&url)?;
+                continue;
+            }
+
+            let status = response.status();
+            if !status.is_success() {
+                bail!(
+                    "HTTP {} {}",
+                    status.as_u16(),
+                    status.canonical_reason().unwrap_or("Unknown")
+                );
+            }
+            let max_bytes = self.config.max_bytes;
+            if response
+                .content_length()
+                .is_some_and(|len| len > max_bytes as u64)
+            {
+                bail!("response larger than {max_bytes} bytes");
+            }
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_lowercase();
+
+            let mut body = Vec::new();
+            while let Some(chunk) = response.chunk().await? {
+                if body.len() + chunk.len() > max_bytes {
+                    bail!("response larger than {max_bytes} bytes");
+                }
+                body.extend_from_slice(&chunk);
+            }
+            return extract_page(
+                &content_type,
+                &String::from_utf8_lossy(&body),
+                self.config.max_chars,
+            );
+        }
+        bail!("more than {MAX_REDIRECTS} redirects")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_urls_trims_punctuation_and_dedupes() {
+        let urls = extract_urls(
+            "see https://example.com/a. and (https://docs.rs/regex/latest/regex/), \
+             https://example.com/a#intro, https://en.wikipedia.org/wiki/Rust_(programming_language)! \
+             plus https://example.com/cat.PNG and ftp://example.com/file",
+        );
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/a",
+                "https://docs.rs/regex/latest/regex/",
+                "https://en.wikipedia.org/wiki/Rust_(programming_language)",
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_urls_caps_links_per_message() {
+        let content = (0..10)
+            .map(|i| format!("https://example.com/{i}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(extract_urls(&content).len(), MAX_LINKS_PER_MESSAGE);
+    }
+
+    #[test]
+    fn extract_page_reads_html_without_chrome() {
+        let html = "<html><head><title>  Widget &amp; Co\n release </title>\
+                    <script>track()</script></head><body><nav>Home | About</nav>\
+                    <h1>Widgets 2.0</h1><p>Now with   gears.</p>\n\n\n<p>Second.</p>\
+                    <footer>© 2024</footer></body></html>";
+        let page = extract_page("text/html; charset=utf-8", html, 10_000).unwrap();
+        assert_eq!(page.title.as_deref(), Some("Widget & Co release"));
+        assert!(page.text.contains("Widgets 2.0"));
+        assert!(page.text.contains("Now with"));
+        assert!(!page.text.contains("track()"));
+        assert!(!page.text.contains("About"));
+        assert!(!page.text.contains("2024"));
+        assert!(!page.text.contains("\n\n\n"));
+    }
+
+    #[test]
+    fn extract_page_limits_text_and_rejects_binary() {
+        let page = extract_page("text/plain", "héllo wörld", 5).unwrap();
+        assert_eq!(page.text, "héllo");
+        assert!(page.title.is_none());
+
+        assert!(extract_page("application/pdf", "%PDF-1.7", 100).is_err());
+        assert!(extract_page("text/html", "<html><script>x()</script></html>", 100).is_err());
+    }
+
+    #[test]
+    fn hourly_limit_frees_slots_after_an_hour() {
+        let conn = Connection::open_in_memory().unwrap();
+        let mut fetcher = LinkFetcher::new(
+            NostrLinkIndexConfig {
+                max_per_hour: 2,
+                ..NostrLinkIndexConfig::default()
+            },
+            UrlAccessConfig::default(),
+            Arc::new(ParkingMutex::new(conn)),
+        )
+        .unwrap();
+
+        let start = Instant::now();
+        assert!(fetcher.take_slot(start));
+        assert!(fetcher.take_slot(start + Duration::from_secs(60)));
+        assert!(!fetcher.take_slot(start + Duration::from_secs(120)));
+        assert!(fetcher.take_slot(start + RATE_WINDOW));
+    }
+
+    #[test]
+    fn validation_requires_allowlisted_public_hosts() {
+        let conn = Connection::open_in_memory().unwrap();
+        let fetcher = LinkFetcher::new(
+            NostrLinkIndexConfig {
+                enabled: true,
+                allowed_domains: vec!["example.com".into(), "127.0.0.1".into()],
+                blocked_domains: vec!["ads.example.com".into()],
+                ..NostrLinkIndexConfig::default()
+            },
+            UrlAccessConfig::default(),
+            Arc::new(ParkingMutex::new(conn)),
+        )
+        .unwrap();
+
+        assert!(fetcher.validate("https://other.org/page").is_err());
+        assert!(fetcher.validate("https://ads.example.com/x").is_err());
+        assert!(fetcher.validate("http://127.0.0.1/admin").is_err());
+    }
+}
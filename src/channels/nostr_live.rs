@@ -0,0 +1,346 @@
+//! Live view of the running Nostr channel, served by the local API.
+//!
+//! The channel records recent group messages, replies it still owes,
+//! security flags and a periodic status snapshot here. The gateway serves
+//! the view at `GET /api/nostr/live` for `snowclaw top`, and hands owner
+//! commands posted to `POST /api/nostr/command` to the channel, which runs
+//! them like the owner's DM commands.
+
+use nostr_sdk::prelude::*;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::OnceLock;
+use tokio::sync::{mpsc, oneshot};
+
+use super::nostr_banner::BudgetRemaining;
+use super::nostr_commands::{self, OwnerCommand, OwnerStatus};
+
+/// Messages kept per group.
+pub const MAX_MESSAGES_PER_GROUP: usize = 20;
+
+/// Security flags kept, newest last.
+pub const MAX_SECURITY_FLAGS: usize = 50;
+
+/// Owner commands waiting for the channel.
+const COMMAND_QUEUE: usize = 8;
+
+/// Everything `snowclaw top` shows.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LiveView {
+    /// `None` until the channel reports its first status.
+    pub status: Option<LiveStatus>,
+    /// Recent messages per group, oldest first.
+    pub messages: BTreeMap<String, Vec<LiveMessage>>,
+    /// Messages handed to the agent that have no reply yet, oldest first.
+    pub pending_replies: Vec<PendingReply>,
+    pub security_flags: Vec<SecurityFlag>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiveStatus {
+    pub version: String,
+    /// Unix time of this snapshot.
+    pub updated_at: u64,
+    pub relays: Vec<RelayState>,
+    pub groups: Vec<GroupState>,
+    pub outbox_pending: usize,
+    pub running_actions: usize,
+    pub budget: Option<BudgetRemaining>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayState {
+    pub url: String,
+    /// Lowercase relay status, e.g. `connected`.
+    pub status: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupState {
+    pub name: String,
+    /// Effective respond mode.
+    pub mode: String,
+    /// Seconds left on a timed mute.
+    pub muted_for_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiveMessage {
+    pub sender: String,
+    pub content: String,
+    pub created_at: u64,
+    pub is_owner: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingReply {
+    /// `#group`, or the DM peer's hex pubkey.
+    pub target: String,
+    pub sender: String,
+    /// Unix time of the message waiting for a reply.
+    pub since: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityFlag {
+    pub at: u64,
+    /// E.g. "nsec in #dev from npub1abc…".
+    pub description: String,
+}
+
+impl LiveStatus {
+    pub fn new(status: &OwnerStatus, relays: Vec<RelayState>, updated_at: u64) -> Self {
+        Self {
+            version: status.version.to_string(),
+            updated_at,
+            relays,
+            groups: status
+                .groups
+                .iter()
+                .map(|g| GroupState {
+                    name: g.name.clone(),
+                    mode: g.mode.clone(),
+                    muted_for_secs: g.muted_for.map(|d| d.as_secs()),
+                })
+                .collect(),
+            outbox_pending: status.outbox_pending,
+            running_actions: status.running_actions,
+            budget: status.budget,
+        }
+    }
+}
+
+/// An owner command from the local API, answered with the reply text.
+#[derive(Debug)]
+pub struct LiveCommand {
+    /// Command text as typed, e.g. `/mute #dev 1h`.
+    pub text: String,
+    pub command: OwnerCommand,
+    pub reply: oneshot::Sender<String>,
+}
+
+/// Why an owner command could not be run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    /// No Nostr channel is listening in this process.
+    NotRunning,
+    /// Not a command, or malformed; carries the usage text.
+    Invalid(String),
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotRunning => f.write_str("Nostr channel is not running"),
+            Self::Invalid(usage) => f.write_str(usage),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// Live state of one channel. The process-wide instance is behind the free
+/// functions below.
+#[derive(Default)]
+pub struct LiveRegistry {
+    status: Mutex<Option<LiveStatus>>,
+    messages: Mutex<BTreeMap<String, VecDeque<LiveMessage>>>,
+    pending: Mutex<BTreeMap<String, PendingReply>>,
+    flags: Mutex<VecDeque<SecurityFlag>>,
+    commands: Mutex<Option<mpsc::Sender<LiveCommand>>>,
+}
+
+impl LiveRegistry {
+    pub fn set_status(&self, status: LiveStatus) {
+        *self.status.lock() = Some(status);
+    }
+
+    pub fn record_message(&self, group: &str, message: LiveMessage) {
+        let mut messages = self.messages.lock();
+        let buf = messages.entry(group.to_string()).or_default();
+        buf.push_back(message);
+        while buf.len() > MAX_MESSAGES_PER_GROUP {
+            buf.pop_front();
+        }
+    }
+
+    /// A message for `target` went to the agent. An earlier pending one for
+    /// the same target is kept, so the age shows the longest wait.
+    pub fn reply_pending(&self, target: &str, sender: &str, since: u64) {
+        let target = target_key(target);
+        self.pending
+            .lock()
+            .entry(target.clone())
+            .or_insert_with(|| PendingReply {
+                target,
+                sender: sender.to_string(),
+                since,
+            });
+    }
+
+    /// The agent replied to `target` (or chose to stay silent).
+    pub fn reply_sent(&self, target: &str) {
+        self.pending.lock().remove(&target_key(target));
+    }
+
+    pub fn record_security_flag(&self, at: u64, description: String) {
+        let mut flags = self.flags.lock();
+        flags.push_back(SecurityFlag { at, description });
+        while flags.len() > MAX_SECURITY_FLAGS {
+            flags.pop_front();
+        }
+    }
+
+    pub fn snapshot(&self) -> LiveView {
+        let mut pending_replies: Vec<PendingReply> =
+            self.pending.lock().values().cloned().collect();
+        pending_replies.sort_by_key(|p| p.since);
+        LiveView {
+            status: self.status.lock().clone(),
+            messages: self
+                .messages
+                .lock()
+                .iter()
+                .map(|(group, buf)| (group.clone(), buf.iter().cloned().collect()))
+                .collect(),
+            pending_replies,
+            security_flags: self.flags.lock().iter().cloned().collect(),
+        }
+    }
+
+    /// Route owner commands to the caller, replacing an earlier listener.
+    pub fn register_commands(&self) -> mpsc::Receiver<LiveCommand> {
+        let (tx, rx) = mpsc::channel(COMMAND_QUEUE);
+        *self.commands.lock() = Some(tx);
+        rx
+    }
+
+    /// Parse `text` as an owner command and wait for the channel's reply.
+    pub async fn run_command(&self, text: &str) -> Result<String, CommandError> {
+        let command = match nostr_commands::parse(text) {
+            Some(Ok(command)) => command,
+            Some(Err(usage)) => return Err(CommandError::Invalid(usage)),
+            None => {
+                return Err(CommandError::Invalid(
+                    "Not a command; commands start with `/` (try /help)".to_string(),
+                ))
+            }
+        };
+        let sender = self
+            .commands
+            .lock()
+            .clone()
+            .ok_or(CommandError::NotRunning)?;
+        let (reply, rx) = oneshot::channel();
+        sender
+            .send(LiveCommand {
+                text: text.to_string(),
+                command,
+                reply,
+            })
+            .await
+            .map_err(|_| CommandError::NotRunning)?;
+        rx.await.map_err(|_| CommandError::NotRunning)
+    }
+}
+
+/// Group names as-is; DM peers as lowercase hex whether given as npub or hex.
+fn target_key(target: &str) -> String {
+    if target.starts_with('#') {
+        return target.to_string();
+    }
+    PublicKey::parse(target).map_or_else(|_| target.to_lowercase(), |pk| pk.to_hex())
+}
+
+static REGISTRY: OnceLock<LiveRegistry> = OnceLock::new();
+
+/// The process-wide registry.
+pub fn registry() -> &'static LiveRegistry {
+    REGISTRY.get_or_init(LiveRegistry::default)
+}
+
+/// The live view, or `None` when no channel has reported a status.
+pub fn snapshot() -> Option<LiveView> {
+    let view = registry().snapshot();
+    view.status.is_some().then_some(view)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(n: u64) -> LiveMessage {
+        LiveMessage {
+            sender: "alice".into(),
+            content: format!("message {n}"),
+            created_at: n,
+            is_owner: false,
+        }
+    }
+
+    #[test]
+    fn buffers_are_bounded_and_pending_clears_by_either_key() {
+        let live = LiveRegistry::default();
+        for n in 0..MAX_MESSAGES_PER_GROUP as u64 + 5 {
+            live.record_message("dev", message(n));
+        }
+        for n in 0..MAX_SECURITY_FLAGS as u64 + 1 {
+            live.record_security_flag(n, format!("flag {n}"));
+        }
+
+        let keys = Keys::generate();
+        let npub = keys.public_key().to_bech32().unwrap();
+        live.reply_pending("#dev", "alice", 20);
+        live.reply_pending("#dev", "bob", 30);
+        live.reply_pending(&keys.public_key().to_hex(), "carol", 10);
+
+        let view = live.snapshot();
+        let dev = &view.messages["dev"];
+        assert_eq!(dev.len(), MAX_MESSAGES_PER_GROUP);
+        assert_eq!(dev[0].created_at, 5);
+        assert_eq!(view.security_flags.len(), MAX_SECURITY_FLAGS);
+        assert_eq!(view.security_flags[0].at, 1);
+        let pending: Vec<(&str, u64)> = view
+            .pending_replies
+            .iter()
+            .map(|p| (p.sender.as_str(), p.since))
+            .collect();
+        assert_eq!(pending, vec![("carol", 10), ("alice", 20)]);
+
+        live.reply_sent(&npub);
+        live.reply_sent("#dev");
+        assert!(live.snapshot().pending_replies.is_empty());
+    }
+
+    #[tokio::test]
+    async fn commands_reach_the_registered_channel() {
+        let live = LiveRegistry::default();
+        assert_eq!(
+            live.run_command("/status").await,
+            Err(CommandError::NotRunning)
+        );
+        assert!(matches!(
+            live.run_command("hello").await,
+            Err(CommandError::Invalid(_))
+        ));
+
+        let mut rx = live.register_commands();
+        let channel = tokio::spawn(async move {
+            let cmd = rx.recv().await.unwrap();
+            assert_eq!(cmd.text, "/unmute #dev");
+            assert_eq!(
+                cmd.command,
+                OwnerCommand::Unmute {
+                    group: Some("dev".into())
+                }
+            );
+            cmd.reply.send("🔊 #dev unmuted.".into()).unwrap();
+        });
+        assert_eq!(
+            live.run_command("/unmute #dev").await.unwrap(),
+            "🔊 #dev unmuted."
+        );
+        channel.await.unwrap();
+    }
+}
@@ -0,0 +1,379 @@
+//! Moderation of outgoing group replies.
+//!
+//! When `[channels_config.nostr.reply_moderation]` is enabled, every group
+//! reply passes a [`ReplyModerator`] before it is published. The moderator
+//! runs its [`ReplyCheck`]s in order — banned-topic patterns, length, link
+//! policy and, when configured, an LLM self-check — and stops at the first
+//! violation. The channel drops a blocked reply, writes the violation to the
+//! audit log and tells the owner.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use regex::{Regex, RegexBuilder};
+use std::sync::{Arc, OnceLock};
+use tracing::warn;
+
+use crate::config::NostrReplyModerationConfig;
+use crate::providers::Provider;
+use crate::tools::url_validation::{
+    extract_host, host_matches_allowlist, normalize_allowed_domains, UrlSchemePolicy,
+};
+
+const SELF_CHECK_SYSTEM_PROMPT: &str = "You review replies an AI agent is about to post in a \
+public group chat. Block a reply only if it leaks private or secret information, is abusive, \
+harassing or hateful, gives dangerous instructions, or would embarrass the agent's owner. \
+Ordinary answers, jokes and disagreement are fine.\n\
+Reply with a single JSON object: {\"allow\": true | false, \"reason\": \"...\"}";
+
+/// A check on an outgoing group reply.
+#[async_trait]
+pub trait ReplyCheck: Send + Sync {
+    /// Short name used in logs, the audit log and owner alerts.
+    fn name(&self) -> &'static str;
+
+    /// Why the reply must not be published, or `None` when it may.
+    async fn check(&self, group: &str, content: &str) -> Option<String>;
+}
+
+/// A reply blocked by a check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub check: &'static str,
+    pub reason: String,
+}
+
+/// Runs the configured checks on group replies.
+pub struct ReplyModerator {
+    groups: Vec<String>,
+    checks: Vec<Box<dyn ReplyCheck>>,
+}
+
+impl ReplyModerator {
+    /// Build from `[channels_config.nostr.reply_moderation]`; `None` when
+    /// moderation is disabled. Fails on an invalid pattern or link mode.
+    /// The LLM self-check needs a provider and is added with
+    /// [`Self::add_check`].
+    pub fn from_config(config: &NostrReplyModerationConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let mut moderator = Self {
+            groups: config.groups.clone(),
+            checks: Vec::new(),
+        };
+        if !config.banned_patterns.is_empty() {
+            moderator.add_check(Box::new(BannedPatterns::new(&config.banned_patterns)?));
+        }
+        if config.max_length > 0 {
+            moderator.add_check(Box::new(MaxLength(config.max_length)));
+        }
+        match config.links.trim().to_ascii_lowercase().as_str() {
+            "allow" => {}
+            "deny" => moderator.add_check(Box::new(LinkPolicy::Deny)),
+            "allowlist" => moderator.add_check(Box::new(LinkPolicy::Allowlist(
+                normalize_allowed_domains(config.allowed_domains.clone()),
+            ))),
+            other => bail!(
+                "Unknown reply_moderation.links `{other}` (expected allow, deny or allowlist)"
+            ),
+        }
+        Ok(Some(moderator))
+    }
+
+    /// Append a check; it runs after the existing ones.
+    pub fn add_check(&mut self, check: Box<dyn ReplyCheck>) {
+        self.checks.push(check);
+    }
+
+    /// Whether replies in `group` are checked.
+    pub fn applies_to(&self, group: &str) -> bool {
+        self.groups.is_empty() || self.groups.iter().any(|g| g == group)
+    }
+
+    /// First violation of the reply, or `None` when it may be published.
+    pub async fn review(&self, group: &str, content: &str) -> Option<Violation> {
+        if !self.applies_to(group) {
+            return None;
+        }
+        for check in &self.checks {
+            if let Some(reason) = check.check(group, content).await {
+                return Some(Violation {
+                    check: check.name(),
+                    reason,
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Rejects replies matching any banned-topic regex (case-insensitive).
+struct BannedPatterns(Vec<Regex>);
+
+impl BannedPatterns {
+    fn new(patterns: &[String]) -> Result<Self> {
+        let regexes = patterns
+            .iter()
+            .map(|p| {
+                RegexBuilder::new(p)
+                    .case_insensitive(true)
+                    .build()
+                    .with_context(|| {
+                        format!("Invalid reply_moderation.banned_patterns regex `{p}`")
+                    })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self(regexes))
+    }
+}
+
+#[async_trait]
+impl ReplyCheck for BannedPatterns {
+    fn name(&self) -> &'static str {
+        "banned_pattern"
+    }
+
+    async fn check(&self, _group: &str, content: &str) -> Option<String> {
+        self.0
+            .iter()
+            .find(|re| re.is_match(content))
+            .map(|re| format!("matches banned pattern `{}`", re.as_str()))
+    }
+}
+
+/// Rejects replies longer than the limit, in characters.
+struct MaxLength(usize);
+
+#[async_trait]
+impl ReplyCheck for MaxLength {
+    fn name(&self) -> &'static str {
+        "max_length"
+    }
+
+    async fn check(&self, _group: &str, content: &str) -> Option<String> {
+        let len = content.chars().count();
+        (len > self.0).then(|| format!("{len} characters (limit {})", self.0))
+    }
+}
+
+/// Rejects links, or links to hosts outside the allowlist.
+enum LinkPolicy {
+    Deny,
+    Allowlist(Vec<String>),
+}
+
+/// Every http(s) URL in the text.
+fn find_urls(content: &str) -> impl Iterator<Item = &str> {
+    static URL_RE: OnceLock<Regex> = OnceLock::new();
+    let re = URL_RE.get_or_init(|| Regex::new(r#"(?i)https?://[^\s<>"'`]+"#).expect("URL regex"));
+    re.find_iter(content).map(|m| m.as_str())
+}
+
+#[async_trait]
+impl ReplyCheck for LinkPolicy {
+    fn name(&self) -> &'static str {
+        "links"
+    }
+
+    async fn check(&self, _group: &str, content: &str) -> Option<String> {
+        for url in find_urls(content) {
+            let allowed = match self {
+                Self::Deny => false,
+                Self::Allowlist(domains) => extract_host(
+                    &url.to_ascii_lowercase(),
+                    UrlSchemePolicy::HttpOrHttps,
+                    "replies",
+                )
+                .is_ok_and(|host| host_matches_allowlist(&host, domains)),
+            };
+            if !allowed {
+                return Some(format!("links to {url}"));
+            }
+        }
+        None
+    }
+}
+
+/// Asks the LLM whether the reply is fit to post. Fails open: a provider
+/// error or an unreadable answer lets the reply through with a warning.
+pub struct SelfCheck {
+    provider: Arc<dyn Provider>,
+    model: String,
+}
+
+impl SelfCheck {
+    pub fn new(provider: Arc<dyn Provider>, model: String) -> Self {
+        Self { provider, model }
+    }
+}
+
+#[async_trait]
+impl ReplyCheck for SelfCheck {
+    fn name(&self) -> &'static str {
+        "self_check"
+    }
+
+    async fn check(&self, group: &str, content: &str) -> Option<String> {
+        let prompt = format!("Group: #{group}\n\nReply:\n{content}");
+        let answer = match self
+            .provider
+            .chat_with_system(Some(SELF_CHECK_SYSTEM_PROMPT), &prompt, &self.model, 0.0)
+            .await
+        {
+            Ok(answer) => answer,
+            Err(e) => {
+                warn!("Reply self-check failed, publishing unchecked: {e}");
+                return None;
+            }
+        };
+        match parse_verdict(&answer) {
+            Some((true, _)) => None,
+            Some((false, reason)) if reason.is_empty() => Some("rejected by self-check".into()),
+            Some((false, reason)) => Some(reason),
+            None => {
+                warn!("Unreadable reply self-check answer, publishing unchecked: {answer}");
+                None
+            }
+        }
+    }
+}
+
+/// `(allow, reason)` from the self-check's JSON answer.
+fn parse_verdict(answer: &str) -> Option<(bool, String)> {
+    let start = answer.find('{')?;
+    let end = answer.rfind('}')?;
+    let value: serde_json::Value = serde_json::from_str(answer.get(start..=end)?).ok()?;
+    let allow = value["allow"].as_bool()?;
+    let reason = value["reason"]
+        .as_str()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    Some((allow, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> NostrReplyModerationConfig {
+        NostrReplyModerationConfig {
+            enabled: true,
+            ..NostrReplyModerationConfig::default()
+        }
+    }
+
+    struct ScriptedProvider(&'static str);
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn static_checks_block_in_order() {
+        let moderator = ReplyModerator::from_config(&NostrReplyModerationConfig {
+            groups: vec!["public".into()],
+            banned_patterns: vec![r"\bcrypto\s+tips?\b".into()],
+            max_length: 40,
+            links: "allowlist".into(),
+            allowed_domains: vec!["docs.rs".into()],
+            ..config()
+        })
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            moderator
+                .review("public", "see https://docs.rs/regex")
+                .await,
+            None
+        );
+        assert_eq!(moderator.review("team", "CRYPTO TIP: buy").await, None);
+
+        let v = moderator
+            .review("public", "Crypto tips: buy now")
+            .await
+            .unwrap();
+        assert_eq!(v.check, "banned_pattern");
+        let v = moderator.review("public", &"a".repeat(41)).await.unwrap();
+        assert_eq!(
+            (v.check, v.reason.as_str()),
+            ("max_length", "41 characters (limit 40)")
+        );
+        let v = moderator
+            .review("public", "try https://evil.example/x")
+            .await
+            .unwrap();
+        assert_eq!(
+            (v.check, v.reason.as_str()),
+            ("links", "links to https://evil.example/x")
+        );
+        assert!(moderator
+            .review("public", "https://docs.rs.evil.example")
+            .await
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn config_switch_and_errors() {
+        let disabled = NostrReplyModerationConfig::default();
+        assert!(ReplyModerator::from_config(&disabled).unwrap().is_none());
+
+        let deny = ReplyModerator::from_config(&NostrReplyModerationConfig {
+            links: "deny".into(),
+            ..config()
+        })
+        .unwrap()
+        .unwrap();
+        assert!(deny.review("g", "HTTP://example.com").await.is_some());
+        assert_eq!(deny.review("g", "no links here").await, None);
+
+        let err = ReplyModerator::from_config(&NostrReplyModerationConfig {
+            links: "some".into(),
+            ..config()
+        })
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("allow, deny or allowlist"));
+        assert!(ReplyModerator::from_config(&NostrReplyModerationConfig {
+            banned_patterns: vec!["(".into()],
+            ..config()
+        })
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn self_check_blocks_and_fails_open() {
+        let check = |answer: &'static str| {
+            let mut moderator = ReplyModerator::from_config(&config()).unwrap().unwrap();
+            moderator.add_check(Box::new(SelfCheck::new(
+                Arc::new(ScriptedProvider(answer)),
+                "m".into(),
+            )));
+            moderator
+        };
+
+        let v = check("```json\n{\"allow\": false, \"reason\": \"leaks a private address\"}\n```")
+            .review("g", "hi")
+            .await
+            .unwrap();
+        assert_eq!(
+            v,
+            Violation {
+                check: "self_check",
+                reason: "leaks a private address".into()
+            }
+        );
+        assert_eq!(check("{\"allow\": true}").review("g", "hi").await, None);
+        assert_eq!(check("looks fine to me").review("g", "hi").await, None);
+    }
+}
@@ -0,0 +1,301 @@
+//! NIP-05 verification of message senders.
+//!
+//! Display names are free text and trivially spoofed. With
+//! `[channels_config.nostr.nip05]` enabled, the `nip05` identifier from a
+//! sender's kind 0 profile is checked against
+//! `https://<domain>/.well-known/nostr.json?name=<name>`. Results are cached
+//! for `recheck_hours` and kept in the sender's social memory profile;
+//! verified senders get a `nip05=<id>✓` field in the compact group header.
+//! Members of `trusted_domains` may run `trusted_actions` and, with
+//! `trust_hex`, share 64-char hex without it being flagged.
+
+use anyhow::{bail, Context, Result};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use crate::config::{NostrNip05Config, UrlAccessConfig};
+use crate::tools::url_validation::{validate_url, DomainPolicy, UrlSchemePolicy};
+
+/// Timeout for one `nostr.json` fetch.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Largest `nostr.json` accepted.
+const MAX_DOCUMENT_BYTES: usize = 256 * 1024;
+
+/// A parsed NIP-05 identifier, lowercased.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nip05Id {
+    pub name: String,
+    pub domain: String,
+}
+
+impl Nip05Id {
+    /// `name@domain`, or a bare `domain` for the root name `_`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_lowercase();
+        let (name, domain) = match s.split_once('@') {
+            Some((name, domain)) => (name.to_string(), domain.to_string()),
+            None => ("_".to_string(), s),
+        };
+        let name_ok = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        let domain_ok = domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && domain
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.'));
+        (name_ok && domain_ok).then_some(Self { name, domain })
+    }
+
+    /// Where the identifier is resolved.
+    pub fn url(&self) -> String {
+        format!(
+            "https://{}/.well-known/nostr.json?name={}",
+            self.domain, self.name
+        )
+    }
+
+    /// Whether the domain is one of `domains` or a subdomain of one.
+    pub fn on_domain(&self, domains: &[String]) -> bool {
+        domains.iter().any(|d| {
+            let d = d.trim().trim_start_matches("*.").to_lowercase();
+            !d.is_empty() && (self.domain == d || self.domain.ends_with(&format!(".{d}")))
+        })
+    }
+}
+
+impl fmt::Display for Nip05Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.name == "_" {
+            write!(f, "{}", self.domain)
+        } else {
+            write!(f, "{}@{}", self.name, self.domain)
+        }
+    }
+}
+
+/// Whether a `nostr.json` document maps `name` to `pubkey_hex`.
+pub fn document_matches(doc: &serde_json::Value, name: &str, pubkey_hex: &str) -> bool {
+    doc.get("names")
+        .and_then(|names| names.get(name))
+        .and_then(|v| v.as_str())
+        .is_some_and(|hex| hex.eq_ignore_ascii_case(pubkey_hex))
+}
+
+/// Outcome of checking one sender's identifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nip05Status {
+    /// The identifier as given in the profile
+    pub identifier: String,
+    pub verified: bool,
+    /// Unix seconds
+    pub checked_at: u64,
+}
+
+/// Resolves identifiers and caches the results per pubkey.
+pub struct Nip05Verifier {
+    config: NostrNip05Config,
+    url_access: UrlAccessConfig,
+    client: reqwest::Client,
+    cache: RwLock<HashMap<String, Nip05Status>>,
+}
+
+impl Nip05Verifier {
+    pub fn new(config: NostrNip05Config, url_access: UrlAccessConfig) -> Result<Self> {
+        // NIP-05: fetchers must ignore redirects.
+        let builder = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .connect_timeout(FETCH_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(concat!("snowclaw/", env!("CARGO_PKG_VERSION"), " nip05"));
+        let builder = crate::config::apply_runtime_proxy_to_builder(builder, "tool.web_fetch");
+        let client = builder.build().context("failed to build NIP-05 client")?;
+        Ok(Self {
+            config,
+            url_access,
+            client,
+            cache: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// The cached status of `pubkey_hex`, if it is for `identifier` and no
+    /// older than `recheck_hours`.
+    pub fn cached(&self, pubkey_hex: &str, identifier: &str, now: u64) -> Option<Nip05Status> {
+        let max_age = self.config.recheck_hours.saturating_mul(3600);
+        self.cache
+            .read()
+            .get(pubkey_hex)
+            .filter(|s| s.identifier == identifier && now.saturating_sub(s.checked_at) < max_age)
+            .cloned()
+    }
+
+    /// Cache a status, e.g. one restored from social memory.
+    pub fn remember(&self, pubkey_hex: &str, status: Nip05Status) {
+        self.cache.write().insert(pubkey_hex.to_string(), status);
+    }
+
+    /// Resolve `identifier` and cache whether it maps to `pubkey_hex`.
+    /// Invalid identifiers and failed fetches count as unverified.
+    pub async fn check(&self, pubkey_hex: &str, identifier: &str, now: u64) -> Nip05Status {
+        let verified = match self.resolve(identifier).await {
+            Ok((id, doc)) => document_matches(&doc, &id.name, pubkey_hex),
+            Err(e) => {
+                tracing::debug!("NIP-05 check of {identifier} failed: {e:#}");
+                false
+            }
+        };
+        let status = Nip05Status {
+            identifier: identifier.to_string(),
+            verified,
+            checked_at: now,
+        };
+        self.remember(pubkey_hex, status.clone());
+        status
+    }
+
+    /// Whether a verified `identifier` belongs to a trusted domain.
+    pub fn is_trusted(&self, identifier: &str) -> bool {
+        Nip05Id::parse(identifier).is_some_and(|id| id.on_domain(&self.config.trusted_domains))
+    }
+
+    /// Whether members of trusted domains may run `action`.
+    pub fn allows_action(&self, action: &str) -> bool {
+        self.config
+            .trusted_actions
+            .iter()
+            .any(|a| a == "*" || a == action)
+    }
+
+    /// Whether members of trusted domains skip the key filter's hex check.
+    pub fn trusts_hex(&self) -> bool {
+        self.config.trust_hex
+    }
+
+    async fn resolve(&self, identifier: &str) -> Result<(Nip05Id, serde_json::Value)> {
+        let Some(id) = Nip05Id::parse(identifier) else {
+            bail!("invalid identifier");
+        };
+        let url = validate_url(
+            &id.url(),
+            &DomainPolicy {
+                allowed_domains: &["*".to_string()],
+                blocked_domains: &[],
+                allowed_field_name: "nip05",
+                blocked_field_name: None,
+                empty_allowed_message: "nip05 has no allowed domains",
+                scheme_policy: UrlSchemePolicy::HttpsOnly,
+                ipv6_error_context: "nip05",
+                url_access: Some(&self.url_access),
+            },
+        )?;
+        let mut response = self.client.get(&url).send().await?;
+        if !response.status().is_success() {
+            bail!("HTTP {}", response.status());
+        }
+        if response
+            .content_length()
+            .is_some_and(|len| len > MAX_DOCUMENT_BYTES as u64)
+        {
+            bail!("nostr.json larger than {MAX_DOCUMENT_BYTES} bytes");
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > MAX_DOCUMENT_BYTES {
+                bail!("nostr.json larger than {MAX_DOCUMENT_BYTES} bytes");
+            }
+            body.extend_from_slice(&chunk);
+        }
+        let doc = serde_json::from_slice(&body).context("invalid nostr.json")?;
+        Ok((id, doc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verifier(trusted: &[&str], actions: &[&str]) -> Nip05Verifier {
+        let config = NostrNip05Config {
+            enabled: true,
+            trusted_domains: trusted.iter().map(|d| d.to_string()).collect(),
+            trusted_actions: actions.iter().map(|a| a.to_string()).collect(),
+            ..NostrNip05Config::default()
+        };
+        Nip05Verifier::new(config, UrlAccessConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn identifiers_parse_and_display() {
+        let id = Nip05Id::parse("Alice@Example.com").unwrap();
+        assert_eq!(id.name, "alice");
+        assert_eq!(id.domain, "example.com");
+        assert_eq!(
+            id.url(),
+            "https://example.com/.well-known/nostr.json?name=alice"
+        );
+        assert_eq!(id.to_string(), "alice@example.com");
+
+        let root = Nip05Id::parse("example.com").unwrap();
+        assert_eq!(root.name, "_");
+        assert_eq!(root.to_string(), "example.com");
+
+        for bad in [
+            "",
+            "alice@",
+            "@example.com",
+            "alice@localhost",
+            "a b@x.org",
+            "a@x.org/p",
+        ] {
+            assert!(Nip05Id::parse(bad).is_none(), "{bad} should not parse");
+        }
+    }
+
+    #[test]
+    fn documents_must_map_the_name_to_the_pubkey() {
+        let doc = serde_json::json!({"names": {"alice": "AABB", "bob": "ccdd"}});
+        assert!(document_matches(&doc, "alice", "aabb"));
+        assert!(!document_matches(&doc, "alice", "ccdd"));
+        assert!(!document_matches(&doc, "carol", "aabb"));
+        assert!(!document_matches(&serde_json::json!({}), "alice", "aabb"));
+    }
+
+    #[test]
+    fn trust_covers_subdomains_and_listed_actions() {
+        let v = verifier(&["example.com"], &["status"]);
+        assert!(v.is_trusted("alice@example.com"));
+        assert!(v.is_trusted("bob@team.example.com"));
+        assert!(!v.is_trusted("eve@notexample.com"));
+        assert!(!v.is_trusted("not an identifier"));
+        assert!(v.allows_action("status"));
+        assert!(!v.allows_action("config.get"));
+        assert!(verifier(&[], &["*"]).allows_action("anything"));
+    }
+
+    #[test]
+    fn cache_expires_and_tracks_the_identifier() {
+        let v = verifier(&[], &[]);
+        let status = Nip05Status {
+            identifier: "alice@example.com".into(),
+            verified: true,
+            checked_at: 1_000,
+        };
+        v.remember("aabb", status.clone());
+        assert_eq!(
+            v.cached("aabb", "alice@example.com", 1_000 + 3_600),
+            Some(status)
+        );
+        // A changed profile identifier needs a new check.
+        assert_eq!(v.cached("aabb", "alice@other.org", 1_000), None);
+        // recheck_hours defaults to 24.
+        assert_eq!(
+            v.cached("aabb", "alice@example.com", 1_000 + 24 * 3600),
+            None
+        );
+        assert_eq!(v.cached("ccdd", "alice@example.com", 1_000), None);
+    }
+}
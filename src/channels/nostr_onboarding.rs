@@ -0,0 +1,127 @@
+//! Group onboarding: welcome members first seen in a configured group.
+//!
+//! The listener calls into this when social memory records a member for a
+//! group for the first time. Whether and how the member is welcomed follows
+//! `[channels_config.nostr.onboarding]`; the outcome is stored in the
+//! `social_onboarding` table, which also deduplicates welcomes.
+
+use crate::config::NostrOnboardingConfig;
+
+/// How a new member is onboarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnboardingMode {
+    /// NIP-17 welcome DM to the member.
+    Dm,
+    /// Group message tagging the member.
+    Mention,
+    /// Record the onboarding status without sending anything.
+    Record,
+}
+
+impl OnboardingMode {
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "mention" | "group" => Self::Mention,
+            "record" | "none" | "silent" => Self::Record,
+            _ => Self::Dm,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dm => "dm",
+            Self::Mention => "mention",
+            Self::Record => "record",
+        }
+    }
+}
+
+/// Whether a member just recorded in `group` should be onboarded.
+///
+/// `configured_groups` is the channel's group list, used when the
+/// onboarding config doesn't name groups itself.
+pub fn should_onboard(
+    config: &NostrOnboardingConfig,
+    configured_groups: &[String],
+    group: &str,
+    is_new_contact: bool,
+) -> bool {
+    if !config.enabled || (config.new_contacts_only && !is_new_contact) {
+        return false;
+    }
+    let groups = if config.groups.is_empty() {
+        configured_groups
+    } else {
+        &config.groups
+    };
+    groups.iter().any(|g| g == group)
+}
+
+/// Fill the welcome template.
+pub fn render_welcome(template: &str, name: &str, group: &str, npub: &str) -> String {
+    template
+        .replace("{name}", name)
+        .replace("{group}", group)
+        .replace("{npub}", npub)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled() -> NostrOnboardingConfig {
+        NostrOnboardingConfig {
+            enabled: true,
+            ..NostrOnboardingConfig::default()
+        }
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let groups = vec!["dev".to_string()];
+        assert!(!should_onboard(
+            &NostrOnboardingConfig::default(),
+            &groups,
+            "dev",
+            true
+        ));
+        assert!(should_onboard(&enabled(), &groups, "dev", true));
+    }
+
+    #[test]
+    fn respects_group_list_and_new_contact_rule() {
+        let groups = vec!["dev".to_string(), "ops".to_string()];
+        assert!(!should_onboard(&enabled(), &groups, "dev", false));
+        assert!(!should_onboard(&enabled(), &groups, "random", true));
+
+        let config = NostrOnboardingConfig {
+            groups: vec!["ops".into()],
+            new_contacts_only: false,
+            ..enabled()
+        };
+        assert!(should_onboard(&config, &groups, "ops", false));
+        assert!(!should_onboard(&config, &groups, "dev", true));
+    }
+
+    #[test]
+    fn template_placeholders_are_filled() {
+        let text = render_welcome(
+            &NostrOnboardingConfig::default().template,
+            "alice",
+            "dev",
+            "npub1x",
+        );
+        assert!(text.starts_with("Welcome to #dev, alice!"));
+        assert_eq!(
+            render_welcome("hi {name} ({npub}) in {group}", "bob", "ops", "npub1y"),
+            "hi bob (npub1y) in ops"
+        );
+    }
+
+    #[test]
+    fn mode_parses_leniently() {
+        assert_eq!(OnboardingMode::from_str("Mention"), OnboardingMode::Mention);
+        assert_eq!(OnboardingMode::from_str("record"), OnboardingMode::Record);
+        assert_eq!(OnboardingMode::from_str("whatever"), OnboardingMode::Dm);
+    }
+}
@@ -0,0 +1,646 @@
+//! Durable outbound spool for Nostr events.
+//!
+//! An event that no relay accepts (all relays unreachable, or every relay
+//! rejecting it) is signed once and kept in an `outbox` SQLite table. A
+//! background task re-sends queued events, oldest first, once a relay is
+//! connected, backing off after each failure. While anything is queued, new
+//! events are queued behind it, so replies reach relays in publish order.
+//!
+//! A replaceable or addressable event replaces any older queued event for the
+//! same coordinate, and is dropped if a newer one is already queued, so an
+//! outage never publishes stale state over fresh state. Ephemeral events
+//! (kinds 20000–29999) are never queued.
+
+use anyhow::{Context, Result};
+use nostr_sdk::prelude::*;
+use parking_lot::Mutex as ParkingMutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+/// Spool file name, next to `social.db` (channel) or `brain.db` (memory).
+pub const OUTBOX_FILE: &str = "outbox.db";
+/// Queued events kept at most; the oldest are dropped beyond this.
+const MAX_PENDING: usize = 1000;
+/// Send attempts while a relay is connected before an event is dropped.
+const MAX_ATTEMPTS: u32 = 8;
+/// First retry delay, doubled per failed attempt up to [`RETRY_MAX`].
+const RETRY_BASE: Duration = Duration::from_secs(5);
+const RETRY_MAX: Duration = Duration::from_secs(300);
+/// How often the sender checks the queue when not woken.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Events sent per flush.
+const FLUSH_BATCH: usize = 50;
+
+// ── Schema and queue operations ──────────────────────────────────
+
+/// A queued event.
+#[derive(Debug, Clone)]
+pub struct QueuedEvent {
+    pub event_id: String,
+    pub kind: u16,
+    /// `kind:pubkey[:d]` of replaceable and addressable events
+    pub coordinate: Option<String>,
+    pub created_at: i64,
+    pub queued_at: i64,
+    pub attempts: u32,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub event_json: String,
+}
+
+/// Queue summary for `snowclaw outbox status` and health reporting.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OutboxStatus {
+    pub pending: usize,
+    /// Unix time the oldest queued event was queued
+    pub oldest_queued_at: Option<i64>,
+    /// Unix time of the next send attempt
+    pub next_attempt_at: Option<i64>,
+    pub last_error: Option<String>,
+    /// Queued events per kind, most common first
+    pub kinds: Vec<(u16, usize)>,
+}
+
+/// Create the outbox table in the given connection.
+pub fn create_outbox_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS outbox (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,
+            event_id TEXT NOT NULL UNIQUE,
+            kind INTEGER NOT NULL,
+            coordinate TEXT,
+            created_at INTEGER NOT NULL,
+            queued_at INTEGER NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            next_attempt_at INTEGER NOT NULL,
+            last_error TEXT,
+            event_json TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_outbox_coordinate ON outbox(coordinate);",
+    )
+    .context("failed to create outbox table")?;
+    Ok(())
+}
+
+/// Queue an event. Returns `false` when a newer event for the same
+/// coordinate is already queued and this one was dropped.
+pub fn enqueue(conn: &Connection, entry: &QueuedEvent) -> Result<bool> {
+    if let Some(ref coordinate) = entry.coordinate {
+        let newer: Option<i64> = conn
+            .query_row(
+                "SELECT 1 FROM outbox WHERE coordinate = ?1 AND created_at > ?2 LIMIT 1",
+                params![coordinate, entry.created_at],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if newer.is_some() {
+            return Ok(false);
+        }
+        supersede(conn, coordinate, entry.created_at)?;
+    }
+    conn.execute(
+        "INSERT OR IGNORE INTO outbox
+             (event_id, kind, coordinate, created_at, queued_at, attempts, next_attempt_at,
+              last_error, event_json)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            entry.event_id,
+            entry.kind,
+            entry.coordinate,
+            entry.created_at,
+            entry.queued_at,
+            entry.attempts,
+            entry.next_attempt_at,
+            entry.last_error,
+            entry.event_json
+        ],
+    )?;
+    trim(conn, MAX_PENDING)?;
+    Ok(true)
+}
+
+/// Drop queued events for `coordinate` created at or before `created_at`.
+pub fn supersede(conn: &Connection, coordinate: &str, created_at: i64) -> Result<usize> {
+    let removed = conn.execute(
+        "DELETE FROM outbox WHERE coordinate = ?1 AND created_at <= ?2",
+        params![coordinate, created_at],
+    )?;
+    Ok(removed)
+}
+
+/// Remove a sent (or abandoned) event.
+pub fn remove(conn: &Connection, event_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM outbox WHERE event_id = ?1", params![event_id])?;
+    Ok(())
+}
+
+/// Record a failed send: back off, or drop the event after
+/// [`MAX_ATTEMPTS`]. Returns `true` when the event was dropped.
+pub fn record_failure(
+    conn: &Connection,
+    event: &QueuedEvent,
+    error: &str,
+    now: i64,
+) -> Result<bool> {
+    let attempts = event.attempts + 1;
+    if attempts >= MAX_ATTEMPTS {
+        remove(conn, &event.event_id)?;
+        return Ok(true);
+    }
+    conn.execute(
+        "UPDATE outbox SET attempts = ?1, next_attempt_at = ?2, last_error = ?3
+         WHERE event_id = ?4",
+        params![attempts, now + retry_delay(attempts), error, event.event_id],
+    )?;
+    Ok(false)
+}
+
+/// Queued events in send order.
+pub fn queued(conn: &Connection, limit: usize) -> Result<Vec<QueuedEvent>> {
+    let mut stmt = conn.prepare(
+        "SELECT event_id, kind, coordinate, created_at, queued_at, attempts, next_attempt_at,
+                last_error, event_json
+         FROM outbox ORDER BY seq LIMIT ?1",
+    )?;
+    #[allow(clippy::cast_possible_wrap)]
+    let rows = stmt.query_map(params![limit as i64], |row| {
+        Ok(QueuedEvent {
+            event_id: row.get(0)?,
+            kind: row.get(1)?,
+            coordinate: row.get(2)?,
+            created_at: row.get(3)?,
+            queued_at: row.get(4)?,
+            attempts: row.get(5)?,
+            next_attempt_at: row.get(6)?,
+            last_error: row.get(7)?,
+            event_json: row.get(8)?,
+        })
+    })?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to read outbox")
+}
+
+/// Number of queued events.
+pub fn pending_count(conn: &Connection) -> Result<usize> {
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM outbox", [], |row| row.get(0))?;
+    Ok(usize::try_from(count).unwrap_or(0))
+}
+
+/// Summarize the queue.
+pub fn status(conn: &Connection) -> Result<OutboxStatus> {
+    let (pending, oldest_queued_at): (i64, Option<i64>) =
+        conn.query_row("SELECT COUNT(*), MIN(queued_at) FROM outbox", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+    let head = conn
+        .query_row(
+            "SELECT next_attempt_at, last_error FROM outbox ORDER BY seq LIMIT 1",
+            [],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?)),
+        )
+        .optional()?;
+    let mut stmt = conn
+        .prepare("SELECT kind, COUNT(*) FROM outbox GROUP BY kind ORDER BY COUNT(*) DESC, kind")?;
+    let kinds = stmt
+        .query_map([], |row| {
+            let count: i64 = row.get(1)?;
+            Ok((row.get(0)?, usize::try_from(count).unwrap_or(0)))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(OutboxStatus {
+        pending: usize::try_from(pending).unwrap_or(0),
+        oldest_queued_at,
+        next_attempt_at: head.as_ref().map(|(next, _)| *next),
+        last_error: head.and_then(|(_, error)| error),
+        kinds,
+    })
+}
+
+/// Read the status of the spool at `path`, `None` when it does not exist.
+pub fn read_status(path: &Path) -> Result<Option<OutboxStatus>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let conn = crate::security::db_encryption::open(path)?;
+    create_outbox_tables(&conn)?;
+    status(&conn).map(Some)
+}
+
+/// Drop the oldest events beyond `max`.
+fn trim(conn: &Connection, max: usize) -> Result<usize> {
+    #[allow(clippy::cast_possible_wrap)]
+    let removed = conn.execute(
+        "DELETE FROM outbox WHERE seq NOT IN (SELECT seq FROM outbox ORDER BY seq DESC LIMIT ?1)",
+        params![max as i64],
+    )?;
+    if removed > 0 {
+        warn!("Outbox full, dropped {removed} oldest queued event(s)");
+    }
+    Ok(removed)
+}
+
+/// Delay in seconds before attempt number `attempts + 1`.
+fn retry_delay(attempts: u32) -> i64 {
+    let delay = RETRY_BASE
+        .saturating_mul(1 << attempts.saturating_sub(1).min(16))
+        .min(RETRY_MAX);
+    i64::try_from(delay.as_secs()).unwrap_or(i64::MAX)
+}
+
+// ── Event helpers ────────────────────────────────────────────────
+
+fn is_ephemeral(kind: u16) -> bool {
+    (20_000..30_000).contains(&kind)
+}
+
+/// `kind:pubkey` for replaceable and `kind:pubkey:d` for addressable events.
+fn coordinate(event: &Event) -> Option<String> {
+    let kind = event.kind.as_u16();
+    let pubkey = event.pubkey.to_hex();
+    if kind == 0 || kind == 3 || (10_000..20_000).contains(&kind) {
+        return Some(format!("{kind}:{pubkey}"));
+    }
+    if (30_000..40_000).contains(&kind) {
+        let d = event
+            .tags
+            .iter()
+            .find(|t| t.as_slice().first().map(String::as_str) == Some("d"))
+            .and_then(|t| t.as_slice().get(1).cloned())
+            .unwrap_or_default();
+        return Some(format!("{kind}:{pubkey}:{d}"));
+    }
+    None
+}
+
+fn queued_event(event: &Event, error: &str, now: i64) -> QueuedEvent {
+    #[allow(clippy::cast_possible_wrap)]
+    let created_at = event.created_at.as_secs() as i64;
+    QueuedEvent {
+        event_id: event.id.to_hex(),
+        kind: event.kind.as_u16(),
+        coordinate: coordinate(event),
+        created_at,
+        queued_at: now,
+        attempts: 0,
+        next_attempt_at: now + retry_delay(1),
+        last_error: Some(error.to_string()),
+        event_json: event.as_json(),
+    }
+}
+
+/// Send `event`; `None` when a relay accepted it, otherwise why none did.
+async fn try_send(client: &Client, event: &Event) -> Option<String> {
+    match client.send_event(event).await {
+        Ok(output) if !output.success.is_empty() => None,
+        Ok(output) => {
+            let reasons: Vec<String> = output
+                .failed
+                .iter()
+                .map(|(relay, reason)| format!("{relay}: {reason}"))
+                .collect();
+            Some(if reasons.is_empty() {
+                "no relay accepted the event".to_string()
+            } else {
+                reasons.join("; ")
+            })
+        }
+        Err(e) => Some(e.to_string()),
+    }
+}
+
+async fn any_relay_connected(client: &Client) -> bool {
+    client
+        .relays()
+        .await
+        .values()
+        .any(|relay| relay.status() == RelayStatus::Connected)
+}
+
+// ── Outbox ───────────────────────────────────────────────────────
+
+/// Handle to one spool. Clones share the database and the sender task.
+#[derive(Clone)]
+pub struct Outbox {
+    conn: Arc<ParkingMutex<Connection>>,
+    wake: Arc<Notify>,
+    /// Health registry component reporting the queue depth
+    component: &'static str,
+}
+
+impl Outbox {
+    /// Open (or create) the spool at `path`.
+    pub fn open(path: &Path, component: &'static str) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = crate::security::db_encryption::open(path)?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous = NORMAL;
+             PRAGMA busy_timeout = 5000;",
+        )?;
+        create_outbox_tables(&conn)?;
+        let outbox = Self {
+            conn: Arc::new(ParkingMutex::new(conn)),
+            wake: Arc::new(Notify::new()),
+            component,
+        };
+        let pending = outbox.pending();
+        if pending > 0 {
+            info!("Outbox {} has {pending} queued event(s)", path.display());
+        }
+        outbox.report_health();
+        Ok(outbox)
+    }
+
+    /// Number of queued events.
+    pub fn pending(&self) -> usize {
+        pending_count(&self.conn.lock()).unwrap_or(0)
+    }
+
+    /// Sign `builder` with the client's signer and [`send`](Self::send) it.
+    pub async fn send_builder(&self, client: &Client, builder: EventBuilder) -> Result<EventId> {
+        let event = client
+            .sign_event_builder(builder)
+            .await
+            .context("Failed to sign event")?;
+        self.send(client, event).await
+    }
+
+    /// Publish `event`, or queue it when no relay accepts it. Returns the
+    /// event ID once the event is sent or safely queued; errors only when
+    /// it could be neither (or, for ephemeral events, was not sent).
+    pub async fn send(&self, client: &Client, event: Event) -> Result<EventId> {
+        let event_id = event.id;
+        let kind = event.kind.as_u16();
+        if is_ephemeral(kind) {
+            return match try_send(client, &event).await {
+                None => Ok(event_id),
+                Some(error) => anyhow::bail!("Failed to publish kind {kind} event: {error}"),
+            };
+        }
+
+        // Publish directly only when nothing is waiting, to keep order.
+        let error = if self.pending() == 0 {
+            match try_send(client, &event).await {
+                None => {
+                    if let Some(coordinate) = coordinate(&event) {
+                        #[allow(clippy::cast_possible_wrap)]
+                        let created_at = event.created_at.as_secs() as i64;
+                        let _ = supersede(&self.conn.lock(), &coordinate, created_at);
+                    }
+                    return Ok(event_id);
+                }
+                Some(error) => error,
+            }
+        } else {
+            "queued behind earlier events".to_string()
+        };
+
+        let entry = queued_event(&event, &error, chrono::Utc::now().timestamp());
+        let queued = enqueue(&self.conn.lock(), &entry)
+            .with_context(|| format!("Failed to publish or queue event {event_id}: {error}"))?;
+        if queued {
+            info!("Queued kind {kind} event {event_id} in outbox ({error})");
+        } else {
+            debug!("Dropped kind {kind} event {event_id}: a newer one is already queued");
+        }
+        self.report_health();
+        self.wake.notify_one();
+        Ok(event_id)
+    }
+
+    /// Re-send queued events until the process exits.
+    pub async fn run(self, client: Client) {
+        loop {
+            tokio::select! {
+                () = self.wake.notified() => {}
+                () = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+            self.flush(&client).await;
+        }
+    }
+
+    /// Send due events in order, stopping at the first failure.
+    async fn flush(&self, client: &Client) {
+        let now = chrono::Utc::now().timestamp();
+        let batch = match queued(&self.conn.lock(), FLUSH_BATCH) {
+            Ok(batch) => batch,
+            Err(e) => {
+                warn!("Failed to read outbox: {e:#}");
+                return;
+            }
+        };
+        let Some(head) = batch.first() else {
+            return;
+        };
+        if head.next_attempt_at > now || !any_relay_connected(client).await {
+            return;
+        }
+
+        let mut sent = 0usize;
+        for entry in &batch {
+            let event = match Event::from_json(&entry.event_json) {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("Dropping unreadable outbox event {}: {e}", entry.event_id);
+                    let _ = remove(&self.conn.lock(), &entry.event_id);
+                    continue;
+                }
+            };
+            let failure = try_send(client, &event).await;
+            let db = self.conn.lock();
+            match failure {
+                None => {
+                    let _ = remove(&db, &entry.event_id);
+                    sent += 1;
+                }
+                Some(error) => {
+                    match record_failure(&db, entry, &error, now) {
+                        Ok(true) => warn!(
+                            "Dropping outbox event {} (kind {}) after {MAX_ATTEMPTS} attempts: {error}",
+                            entry.event_id, entry.kind
+                        ),
+                        Ok(false) => debug!("Outbox send of {} failed: {error}", entry.event_id),
+                        Err(e) => warn!("Failed to update outbox: {e:#}"),
+                    }
+                    break;
+                }
+            }
+        }
+        if sent > 0 {
+            info!("Sent {sent} queued event(s) from outbox");
+        }
+        self.report_health();
+        if sent == batch.len() && batch.len() == FLUSH_BATCH {
+            self.wake.notify_one();
+        }
+    }
+
+    fn report_health(&self) {
+        let status = match status(&self.conn.lock()) {
+            Ok(status) => status,
+            Err(e) => {
+                crate::health::mark_component_error(self.component, format!("{e:#}"));
+                return;
+            }
+        };
+        crate::health::mark_component_ok(self.component);
+        crate::health::set_component_detail(
+            self.component,
+            serde_json::json!({
+                "pending": status.pending,
+                "oldest_queued_at": status.oldest_queued_at,
+                "next_attempt_at": status.next_attempt_at,
+                "last_error": status.last_error,
+            }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        create_outbox_tables(&conn).unwrap();
+        conn
+    }
+
+    fn entry(id: &str, kind: u16, coordinate: Option<&str>, created_at: i64) -> QueuedEvent {
+        QueuedEvent {
+            event_id: id.to_string(),
+            kind,
+            coordinate: coordinate.map(String::from),
+            created_at,
+            queued_at: 1_000,
+            attempts: 0,
+            next_attempt_at: 1_000,
+            last_error: Some("relay unreachable".to_string()),
+            event_json: "{}".to_string(),
+        }
+    }
+
+    fn ids(conn: &Connection) -> Vec<String> {
+        queued(conn, 100)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.event_id)
+            .collect()
+    }
+
+    #[test]
+    fn events_are_sent_in_queue_order() {
+        let conn = test_conn();
+        enqueue(&conn, &entry("b", 9, None, 20)).unwrap();
+        enqueue(&conn, &entry("a", 9, None, 10)).unwrap();
+        enqueue(&conn, &entry("c", 1059, None, 5)).unwrap();
+        assert_eq!(ids(&conn), vec!["b", "a", "c"]);
+
+        remove(&conn, "b").unwrap();
+        assert_eq!(ids(&conn), vec!["a", "c"]);
+        assert_eq!(pending_count(&conn).unwrap(), 2);
+    }
+
+    #[test]
+    fn newer_replaceable_event_supersedes_queued_one() {
+        let conn = test_conn();
+        let coord = "30078:ab:snowclaw:memory:group:dev";
+        enqueue(&conn, &entry("old", 30078, Some(coord), 100)).unwrap();
+        enqueue(&conn, &entry("reply", 9, None, 101)).unwrap();
+        assert!(enqueue(&conn, &entry("new", 30078, Some(coord), 200)).unwrap());
+        assert_eq!(ids(&conn), vec!["reply", "new"]);
+
+        // An older state queued late is dropped instead of overwriting.
+        assert!(!enqueue(&conn, &entry("stale", 30078, Some(coord), 150)).unwrap());
+        assert_eq!(ids(&conn), vec!["reply", "new"]);
+
+        // A direct publish of a newer state clears the queued one.
+        assert_eq!(supersede(&conn, coord, 300).unwrap(), 1);
+        assert_eq!(ids(&conn), vec!["reply"]);
+    }
+
+    #[test]
+    fn failures_back_off_then_drop_the_event() {
+        let conn = test_conn();
+        enqueue(&conn, &entry("a", 9, None, 10)).unwrap();
+
+        for attempt in 1..MAX_ATTEMPTS {
+            let head = queued(&conn, 1).unwrap().remove(0);
+            assert!(!record_failure(&conn, &head, "rejected", 1_000).unwrap());
+            let head = queued(&conn, 1).unwrap().remove(0);
+            assert_eq!(head.attempts, attempt);
+            assert_eq!(head.next_attempt_at, 1_000 + retry_delay(attempt));
+        }
+        let head = queued(&conn, 1).unwrap().remove(0);
+        assert!(record_failure(&conn, &head, "rejected", 1_000).unwrap());
+        assert_eq!(pending_count(&conn).unwrap(), 0);
+
+        assert_eq!(retry_delay(1), 5);
+        assert_eq!(retry_delay(2), 10);
+        assert_eq!(retry_delay(10), 300);
+    }
+
+    #[test]
+    fn status_summarizes_queue() {
+        let conn = test_conn();
+        assert_eq!(status(&conn).unwrap().pending, 0);
+
+        let mut first = entry("a", 9, None, 10);
+        first.queued_at = 900;
+        enqueue(&conn, &first).unwrap();
+        enqueue(&conn, &entry("b", 9, None, 11)).unwrap();
+        enqueue(&conn, &entry("c", 1059, None, 12)).unwrap();
+
+        let status = status(&conn).unwrap();
+        assert_eq!(status.pending, 3);
+        assert_eq!(status.oldest_queued_at, Some(900));
+        assert_eq!(status.next_attempt_at, Some(1_000));
+        assert_eq!(status.last_error.as_deref(), Some("relay unreachable"));
+        assert_eq!(status.kinds, vec![(9, 2), (1059, 1)]);
+    }
+
+    #[test]
+    fn queue_is_capped() {
+        let conn = test_conn();
+        for i in 0..5 {
+            enqueue(&conn, &entry(&i.to_string(), 9, None, i)).unwrap();
+        }
+        assert_eq!(trim(&conn, 3).unwrap(), 2);
+        assert_eq!(ids(&conn), vec!["2", "3", "4"]);
+    }
+
+    #[test]
+    fn coordinates_follow_nip01_kind_ranges() {
+        let keys = Keys::generate();
+        let pubkey = keys.public_key().to_hex();
+        let sign = |builder: EventBuilder| builder.sign_with_keys(&keys).unwrap();
+
+        let profile = sign(EventBuilder::new(Kind::Metadata, "{}"));
+        assert_eq!(coordinate(&profile), Some(format!("0:{pubkey}")));
+
+        let relays = sign(EventBuilder::new(Kind::Custom(10002), ""));
+        assert_eq!(coordinate(&relays), Some(format!("10002:{pubkey}")));
+
+        let memory = sign(
+            EventBuilder::new(Kind::Custom(30078), "{}")
+                .tags([Tag::custom(TagKind::custom("d"), ["snowclaw:x"])]),
+        );
+        assert_eq!(
+            coordinate(&memory),
+            Some(format!("30078:{pubkey}:snowclaw:x"))
+        );
+
+        let message = sign(EventBuilder::new(Kind::Custom(9), "hi"));
+        assert_eq!(coordinate(&message), None);
+        assert!(is_ephemeral(21_122));
+        assert!(!is_ephemeral(9));
+    }
+}
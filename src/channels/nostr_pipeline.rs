@@ -0,0 +1,389 @@
+//! Staged subscribe pipeline for the Nostr listener.
+//!
+//! Relay notifications flow receive → filter → enrich → dispatch. The
+//! receive stage only does cheap filtering (own events, allow-list, dedup,
+//! configured groups) and sorts accepted events into bounded per-lane
+//! queues; each lane has its own worker that enriches (names, memory,
+//! context) and dispatches to the agent. A flood in one group fills only
+//! the group lane, while DMs, action requests and owner events keep moving
+//! through the priority lane.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// Queue an accepted event is routed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lane {
+    /// DMs, action requests and events from owners and role holders.
+    /// Never drops.
+    Priority,
+    /// NIP-29 group messages.
+    Group,
+    /// Task status, other agents' state and extra kinds.
+    Background,
+}
+
+impl Lane {
+    pub const ALL: [Lane; 3] = [Lane::Priority, Lane::Group, Lane::Background];
+
+    pub fn for_kind(kind: u16) -> Self {
+        match kind {
+            4 | 1059 | 1121 | 14199 | 30078 => Self::Priority,
+            9 | 11 | 12 => Self::Group,
+            _ => Self::Background,
+        }
+    }
+
+    /// Lane for an event of `kind`. Owners and other role holders always
+    /// get the priority lane, so a group halt or stop is never evicted by
+    /// the flood it is meant to stop.
+    pub fn route(kind: u16, from_role_holder: bool) -> Self {
+        if from_role_holder {
+            Self::Priority
+        } else {
+            Self::for_kind(kind)
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Priority => "priority",
+            Self::Group => "group",
+            Self::Background => "background",
+        }
+    }
+}
+
+/// What a full lane does with a new event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Wait for room, stalling the receive stage.
+    Block,
+    /// Evict the oldest queued event (the lane falls behind, newest wins).
+    DropOldest,
+    /// Discard the incoming event.
+    DropNewest,
+}
+
+impl Overflow {
+    /// Parse a config value; unknown values fall back to `default`.
+    pub fn parse(raw: &str, default: Self) -> Self {
+        match raw {
+            "block" => Self::Block,
+            "drop_oldest" => Self::DropOldest,
+            "drop_newest" => Self::DropNewest,
+            other => {
+                tracing::warn!("Unknown pipeline overflow policy {other:?}, using {default:?}");
+                default
+            }
+        }
+    }
+}
+
+/// Outcome of [`LaneQueue::push`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pushed {
+    Queued,
+    /// Queued after evicting the oldest event.
+    DroppedOldest,
+    /// The incoming event was discarded.
+    DroppedNewest,
+}
+
+/// Counters for one lane.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LaneStats {
+    pub depth: usize,
+    pub capacity: usize,
+    pub queued: u64,
+    pub dropped: u64,
+    pub handled: u64,
+    /// Longest time an event waited in the queue, in milliseconds.
+    pub max_lag_ms: u64,
+}
+
+/// Bounded single-producer, single-consumer queue with an overflow policy.
+pub struct LaneQueue<T> {
+    capacity: usize,
+    overflow: Overflow,
+    items: Mutex<VecDeque<(T, Instant)>>,
+    ready: Notify,
+    space: Notify,
+    queued: AtomicU64,
+    dropped: AtomicU64,
+    handled: AtomicU64,
+    max_lag_ms: AtomicU64,
+    window_dropped: AtomicU64,
+    window_lag_ms: AtomicU64,
+}
+
+impl<T> LaneQueue<T> {
+    pub fn new(capacity: usize, overflow: Overflow) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            overflow,
+            items: Mutex::new(VecDeque::new()),
+            ready: Notify::new(),
+            space: Notify::new(),
+            queued: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            handled: AtomicU64::new(0),
+            max_lag_ms: AtomicU64::new(0),
+            window_dropped: AtomicU64::new(0),
+            window_lag_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Enqueue an item, applying the overflow policy when the lane is full.
+    pub async fn push(&self, item: T) -> Pushed {
+        let mut item = Some(item);
+        loop {
+            {
+                let mut items = self.items.lock();
+                if items.len() < self.capacity {
+                    items.push_back((item.take().expect("item pushed once"), Instant::now()));
+                    drop(items);
+                    self.queued.fetch_add(1, Ordering::Relaxed);
+                    self.ready.notify_one();
+                    return Pushed::Queued;
+                }
+                match self.overflow {
+                    Overflow::Block => {}
+                    Overflow::DropOldest => {
+                        items.pop_front();
+                        items.push_back((item.take().expect("item pushed once"), Instant::now()));
+                        drop(items);
+                        self.queued.fetch_add(1, Ordering::Relaxed);
+                        self.record_drop();
+                        self.ready.notify_one();
+                        return Pushed::DroppedOldest;
+                    }
+                    Overflow::DropNewest => {
+                        drop(items);
+                        self.record_drop();
+                        return Pushed::DroppedNewest;
+                    }
+                }
+            }
+            self.space.notified().await;
+        }
+    }
+
+    /// Wait for the next item; returns it with the time it spent queued.
+    pub async fn pop(&self) -> (T, Duration) {
+        loop {
+            let next = self.items.lock().pop_front();
+            if let Some((item, queued_at)) = next {
+                self.space.notify_one();
+                let lag = queued_at.elapsed();
+                let lag_ms = u64::try_from(lag.as_millis()).unwrap_or(u64::MAX);
+                self.handled.fetch_add(1, Ordering::Relaxed);
+                self.max_lag_ms.fetch_max(lag_ms, Ordering::Relaxed);
+                self.window_lag_ms.fetch_max(lag_ms, Ordering::Relaxed);
+                return (item, lag);
+            }
+            self.ready.notified().await;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> LaneStats {
+        LaneStats {
+            depth: self.len(),
+            capacity: self.capacity,
+            queued: self.queued.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            handled: self.handled.load(Ordering::Relaxed),
+            max_lag_ms: self.max_lag_ms.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Drops and peak lag (ms) since the previous call.
+    pub fn take_window(&self) -> (u64, u64) {
+        (
+            self.window_dropped.swap(0, Ordering::Relaxed),
+            self.window_lag_ms.swap(0, Ordering::Relaxed),
+        )
+    }
+
+    fn record_drop(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        self.window_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// The three lanes plus receive-stage counters.
+pub struct Pipeline<T> {
+    priority: LaneQueue<T>,
+    group: LaneQueue<T>,
+    background: LaneQueue<T>,
+    lag_warn: Duration,
+    /// Notifications the relay pool discarded because the receive stage lagged.
+    missed: AtomicU64,
+    window_missed: AtomicU64,
+}
+
+impl<T> Pipeline<T> {
+    pub fn from_config(config: &crate::config::NostrPipelineConfig) -> Self {
+        Self {
+            priority: LaneQueue::new(config.priority_capacity, Overflow::Block),
+            group: LaneQueue::new(
+                config.group_capacity,
+                Overflow::parse(&config.group_overflow, Overflow::DropOldest),
+            ),
+            background: LaneQueue::new(
+                config.background_capacity,
+                Overflow::parse(&config.background_overflow, Overflow::DropNewest),
+            ),
+            lag_warn: Duration::from_millis(config.lag_warn_ms),
+            missed: AtomicU64::new(0),
+            window_missed: AtomicU64::new(0),
+        }
+    }
+
+    pub fn lane(&self, lane: Lane) -> &LaneQueue<T> {
+        match lane {
+            Lane::Priority => &self.priority,
+            Lane::Group => &self.group,
+            Lane::Background => &self.background,
+        }
+    }
+
+    pub fn record_missed(&self, count: u64) {
+        self.missed.fetch_add(count, Ordering::Relaxed);
+        self.window_missed.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Stats for the `control.ping` response.
+    pub fn stats_json(&self) -> serde_json::Value {
+        let lanes: BTreeMap<&str, LaneStats> = Lane::ALL
+            .iter()
+            .map(|lane| (lane.name(), self.lane(*lane).stats()))
+            .collect();
+        serde_json::json!({
+            "lanes": lanes,
+            "missed": self.missed.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Summarize the window since the previous report. Returns the summary
+    /// and whether anything was dropped or waited longer than `lag_warn_ms`.
+    pub fn window_report(&self) -> (String, bool) {
+        let mut degraded = false;
+        let mut parts = Vec::with_capacity(Lane::ALL.len() + 1);
+        for lane in Lane::ALL {
+            let queue = self.lane(lane);
+            let (dropped, lag_ms) = queue.take_window();
+            degraded |= dropped > 0 || Duration::from_millis(lag_ms) > self.lag_warn;
+            parts.push(format!(
+                "{}: depth {}/{}, dropped {dropped}, max lag {lag_ms}ms",
+                lane.name(),
+                queue.len(),
+                queue.capacity
+            ));
+        }
+        let missed = self.window_missed.swap(0, Ordering::Relaxed);
+        if missed > 0 {
+            degraded = true;
+            parts.push(format!("missed {missed}"));
+        }
+        (parts.join("; "), degraded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn kinds_are_routed_to_lanes() {
+        assert_eq!(Lane::for_kind(1059), Lane::Priority);
+        assert_eq!(Lane::for_kind(4), Lane::Priority);
+        assert_eq!(Lane::for_kind(1121), Lane::Priority);
+        assert_eq!(Lane::for_kind(9), Lane::Group);
+        assert_eq!(Lane::for_kind(1631), Lane::Background);
+        assert_eq!(Lane::for_kind(31121), Lane::Background);
+        assert_eq!(Lane::route(9, true), Lane::Priority);
+        assert_eq!(Lane::route(9, false), Lane::Group);
+    }
+
+    #[test]
+    fn overflow_parse_falls_back() {
+        assert_eq!(
+            Overflow::parse("block", Overflow::DropNewest),
+            Overflow::Block
+        );
+        assert_eq!(
+            Overflow::parse("bogus", Overflow::DropOldest),
+            Overflow::DropOldest
+        );
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_keeps_newest_events() {
+        let queue = LaneQueue::new(2, Overflow::DropOldest);
+        assert_eq!(queue.push(1).await, Pushed::Queued);
+        assert_eq!(queue.push(2).await, Pushed::Queued);
+        assert_eq!(queue.push(3).await, Pushed::DroppedOldest);
+
+        assert_eq!(queue.pop().await.0, 2);
+        assert_eq!(queue.pop().await.0, 3);
+        let stats = queue.stats();
+        assert_eq!((stats.queued, stats.dropped, stats.handled), (3, 1, 2));
+        assert_eq!(queue.take_window().0, 1);
+        assert_eq!(queue.take_window().0, 0);
+    }
+
+    #[tokio::test]
+    async fn drop_newest_discards_incoming() {
+        let queue = LaneQueue::new(1, Overflow::DropNewest);
+        queue.push("a").await;
+        assert_eq!(queue.push("b").await, Pushed::DroppedNewest);
+        assert_eq!(queue.pop().await.0, "a");
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn block_waits_for_room() {
+        let queue = Arc::new(LaneQueue::new(1, Overflow::Block));
+        queue.push(1).await;
+
+        let producer = {
+            let queue = queue.clone();
+            tokio::spawn(async move { queue.push(2).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!producer.is_finished());
+
+        assert_eq!(queue.pop().await.0, 1);
+        assert_eq!(producer.await.unwrap(), Pushed::Queued);
+        assert_eq!(queue.pop().await.0, 2);
+        assert_eq!(queue.stats().dropped, 0);
+    }
+
+    #[tokio::test]
+    async fn window_report_flags_drops_and_missed() {
+        let pipeline: Pipeline<u32> =
+            Pipeline::from_config(&crate::config::NostrPipelineConfig::default());
+        let (_, degraded) = pipeline.window_report();
+        assert!(!degraded);
+
+        pipeline.record_missed(5);
+        let (summary, degraded) = pipeline.window_report();
+        assert!(degraded);
+        assert!(summary.contains("missed 5"));
+        assert!(summary.starts_with("priority: depth 0/"));
+    }
+}
@@ -0,0 +1,304 @@
+//! Proactive group posts: scheduled or quiet-group triggers, owner approval
+//! and per-group frequency caps.
+//!
+//! Each `[[channels_config.nostr.proactive.posts]]` entry becomes a
+//! [`ProactivePost`]. When one is due, the listener opens a draft in
+//! [`crate::memory::proactive`] and asks the agent for the text with
+//! [`draft_request`]; the agent's reply is routed back to the channel with
+//! the reply target `proactive:<id>` ([`draft_target`]) instead of going to
+//! the group. With `require_approval` the owner gets the draft by DM
+//! ([`approval_request`]) and answers with `/approve` or `/reject`; approved
+//! drafts are published once [`cap_allows`] does.
+
+use crate::config::NostrProactiveConfig;
+use crate::cron::{next_run_for_schedule, Schedule};
+use crate::memory::proactive::Draft;
+use anyhow::{bail, Context, Result};
+use chrono::{TimeZone, Utc};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+/// Reply target prefix that routes an agent reply into a draft.
+pub const DRAFT_TARGET_PREFIX: &str = "proactive:";
+
+/// Reply target for the draft `id`.
+pub fn draft_target(id: i64) -> String {
+    format!("{DRAFT_TARGET_PREFIX}{id}")
+}
+
+/// Draft id of a `proactive:<id>` reply target.
+pub fn parse_draft_target(target: &str) -> Option<i64> {
+    target.strip_prefix(DRAFT_TARGET_PREFIX)?.parse().ok()
+}
+
+/// What makes a post due.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trigger {
+    /// A cron schedule.
+    Schedule(Schedule),
+    /// The group has been quiet this many seconds.
+    Quiet(u64),
+}
+
+/// A validated proactive post.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProactivePost {
+    pub name: String,
+    /// Group id, without `#`
+    pub group: String,
+    pub prompt: String,
+    pub trigger: Trigger,
+}
+
+impl ProactivePost {
+    /// Whether the post is due at `now`.
+    ///
+    /// `last_fired` is when it last fired (`None` if never) and `started`
+    /// when the listener started; `last_activity` is the latest message in
+    /// the group from anyone but the agent. A schedule is due once its next
+    /// run after the later of the two has passed, so runs missed while the
+    /// agent was down are skipped. A quiet trigger fires once per quiet
+    /// stretch.
+    pub fn is_due(
+        &self,
+        now: i64,
+        last_fired: Option<i64>,
+        started: i64,
+        last_activity: Option<i64>,
+    ) -> bool {
+        match &self.trigger {
+            Trigger::Schedule(schedule) => {
+                let after = last_fired.map_or(started, |fired| fired.max(started));
+                let Some(after) = Utc.timestamp_opt(after, 0).single() else {
+                    return false;
+                };
+                next_run_for_schedule(schedule, after).is_ok_and(|next| next.timestamp() <= now)
+            }
+            Trigger::Quiet(secs) => {
+                let quiet_since = last_activity.unwrap_or(started);
+                let quiet =
+                    now.saturating_sub(quiet_since) >= i64::try_from(*secs).unwrap_or(i64::MAX);
+                quiet && last_fired.is_none_or(|fired| fired < quiet_since)
+            }
+        }
+    }
+}
+
+/// Validate the configured posts.
+pub fn posts(config: &NostrProactiveConfig) -> Result<Vec<ProactivePost>> {
+    let mut names = HashSet::new();
+    let mut posts = Vec::with_capacity(config.posts.len());
+    for post in &config.posts {
+        let name = post.name.trim();
+        if name.is_empty() {
+            bail!("proactive post without a name");
+        }
+        if !names.insert(name.to_string()) {
+            bail!("duplicate proactive post name '{name}'");
+        }
+        let group = post.group.trim().trim_start_matches('#');
+        if group.is_empty() {
+            bail!("proactive post '{name}' has no group");
+        }
+        if post.prompt.trim().is_empty() {
+            bail!("proactive post '{name}' has no prompt");
+        }
+        let trigger = match (&post.schedule, post.quiet_hours) {
+            (Some(expr), None) => {
+                let schedule = Schedule::Cron {
+                    expr: expr.clone(),
+                    tz: post.timezone.clone(),
+                };
+                next_run_for_schedule(&schedule, Utc::now())
+                    .with_context(|| format!("proactive post '{name}'"))?;
+                Trigger::Schedule(schedule)
+            }
+            (None, Some(0)) => bail!("proactive post '{name}': quiet_hours must be at least 1"),
+            (None, Some(hours)) => Trigger::Quiet(hours.saturating_mul(3600)),
+            _ => bail!("proactive post '{name}' needs exactly one of schedule and quiet_hours"),
+        };
+        posts.push(ProactivePost {
+            name: name.to_string(),
+            group: group.to_string(),
+            prompt: post.prompt.trim().to_string(),
+            trigger,
+        });
+    }
+    Ok(posts)
+}
+
+/// Start of the UTC day containing `now`.
+pub fn day_start(now: i64) -> i64 {
+    now - now.rem_euclid(86_400)
+}
+
+/// Whether the caps allow another post in a group that has had
+/// `posted_today` proactive posts today, the latest at `last_posted`.
+pub fn cap_allows(
+    config: &NostrProactiveConfig,
+    now: i64,
+    posted_today: u32,
+    last_posted: Option<i64>,
+) -> bool {
+    let min_gap = i64::try_from(config.min_gap_minutes.saturating_mul(60)).unwrap_or(i64::MAX);
+    posted_today < config.max_per_day
+        && last_posted.is_none_or(|last| now.saturating_sub(last) >= min_gap)
+}
+
+/// Message asking the agent to write the post.
+pub fn draft_request(post: &ProactivePost, history: &str, require_approval: bool) -> String {
+    let mut out = format!(
+        "[nostr:proactive post={} group=#{}]\n\
+         Nobody asked you anything: this is a post you start yourself in #{}. \
+         {}\n\n\
+         Reply with the text of the post only, as it should appear in the \
+         group. If there is nothing worth posting right now, reply NO_REPLY.",
+        post.name, post.group, post.group, post.prompt
+    );
+    if require_approval {
+        out.push_str(" The owner reviews the post before it is published.");
+    }
+    if !history.is_empty() {
+        let _ = write!(out, "\n\n{}", history.trim_end());
+    }
+    out
+}
+
+/// DM asking the owner to approve a draft.
+pub fn approval_request(draft: &Draft, timeout_hours: u64) -> String {
+    format!(
+        "📝 Proactive post #{id} '{name}' for #{group}:\n\n{content}\n\n\
+         Reply /approve {id} to publish it, /approve {id} <text> to publish \
+         your edit, or /reject {id}. Expires in {timeout_hours}h.",
+        id = draft.id,
+        name = draft.name,
+        group = draft.group_id,
+        content = draft.content.trim(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NostrProactivePostConfig;
+    use crate::memory::proactive::DraftStatus;
+
+    fn post_config(
+        name: &str,
+        schedule: Option<&str>,
+        quiet_hours: Option<u64>,
+    ) -> NostrProactivePostConfig {
+        NostrProactivePostConfig {
+            name: name.into(),
+            group: "#dev".into(),
+            prompt: "Share a tip.".into(),
+            schedule: schedule.map(str::to_string),
+            timezone: None,
+            quiet_hours,
+        }
+    }
+
+    fn config(posts: Vec<NostrProactivePostConfig>) -> NostrProactiveConfig {
+        NostrProactiveConfig {
+            enabled: true,
+            posts,
+            ..NostrProactiveConfig::default()
+        }
+    }
+
+    fn ts(h: u32, m: u32) -> i64 {
+        Utc.with_ymd_and_hms(2026, 3, 2, h, m, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn draft_targets_round_trip() {
+        assert_eq!(parse_draft_target(&draft_target(42)), Some(42));
+        assert_eq!(parse_draft_target("#dev"), None);
+        assert_eq!(parse_draft_target("proactive:x"), None);
+    }
+
+    #[test]
+    fn posts_are_validated() {
+        let posts = posts(&config(vec![
+            post_config("tip", Some("0 9 * * *"), None),
+            post_config("nudge", None, Some(6)),
+        ]))
+        .unwrap();
+        assert_eq!(posts[0].group, "dev");
+        assert!(matches!(posts[0].trigger, Trigger::Schedule(_)));
+        assert_eq!(posts[1].trigger, Trigger::Quiet(6 * 3600));
+
+        let err = |posts: Vec<NostrProactivePostConfig>| {
+            super::posts(&config(posts)).unwrap_err().to_string()
+        };
+        assert!(err(vec![post_config("both", Some("0 9 * * *"), Some(1))]).contains("exactly one"));
+        assert!(err(vec![post_config("none", None, None)]).contains("exactly one"));
+        assert!(err(vec![post_config("zero", None, Some(0))]).contains("at least 1"));
+        assert!(err(vec![
+            post_config("tip", None, Some(1)),
+            post_config("tip", None, Some(2))
+        ])
+        .contains("duplicate"));
+        assert!(err(vec![post_config("bad", Some("every day"), None)]).contains("'bad'"));
+    }
+
+    #[test]
+    fn schedules_fire_once_per_run() {
+        let post = &posts(&config(vec![post_config("tip", Some("0 9 * * *"), None)])).unwrap()[0];
+        let started = ts(8, 0);
+        assert!(!post.is_due(ts(8, 59), None, started, None));
+        assert!(post.is_due(ts(9, 0), None, started, None));
+        assert!(!post.is_due(ts(9, 1), Some(ts(9, 0)), started, None));
+        // Started after today's run: wait for tomorrow
+        assert!(!post.is_due(ts(10, 0), None, ts(9, 30), None));
+    }
+
+    #[test]
+    fn quiet_triggers_fire_once_per_quiet_stretch() {
+        let post = &posts(&config(vec![post_config("nudge", None, Some(2))])).unwrap()[0];
+        let started = ts(0, 0);
+        assert!(!post.is_due(ts(3, 0), None, started, Some(ts(2, 0))));
+        assert!(post.is_due(ts(4, 0), None, started, Some(ts(2, 0))));
+        assert!(!post.is_due(ts(6, 0), Some(ts(4, 0)), started, Some(ts(2, 0))));
+        // Someone spoke after the last post: a new stretch
+        assert!(post.is_due(ts(8, 0), Some(ts(4, 0)), started, Some(ts(5, 0))));
+        // No activity since startup counts from startup
+        assert!(post.is_due(ts(2, 0), None, started, None));
+    }
+
+    #[test]
+    fn caps_limit_daily_posts_and_gap() {
+        let config = config(vec![]);
+        assert!(cap_allows(&config, ts(12, 0), 0, None));
+        assert!(!cap_allows(&config, ts(12, 0), 3, None));
+        assert!(!cap_allows(&config, ts(12, 0), 1, Some(ts(11, 0))));
+        assert!(cap_allows(&config, ts(12, 0), 1, Some(ts(10, 0))));
+        assert_eq!(day_start(ts(12, 34)), ts(0, 0));
+    }
+
+    #[test]
+    fn messages_name_the_post_and_commands() {
+        let post = &posts(&config(vec![post_config("tip", None, Some(1))])).unwrap()[0];
+        let request = draft_request(post, "[Recent conversation context]\nalice: hi\n", true);
+        assert!(request.starts_with("[nostr:proactive post=tip group=#dev]"));
+        assert!(request.contains("Share a tip."));
+        assert!(request.contains("NO_REPLY"));
+        assert!(request.ends_with("alice: hi"));
+
+        let draft = Draft {
+            id: 7,
+            name: "tip".into(),
+            group_id: "dev".into(),
+            content: "Try cargo nextest!".into(),
+            status: DraftStatus::Pending,
+            created_at: ts(9, 0),
+            updated_at: ts(9, 0),
+            published_at: None,
+        };
+        let dm = approval_request(&draft, 24);
+        assert!(dm.contains("Try cargo nextest!"));
+        assert!(dm.contains("/approve 7") && dm.contains("/reject 7"));
+    }
+}
@@ -0,0 +1,210 @@
+//! Write coalescing and rate limiting for NIP-78 social memory publishes.
+//!
+//! Every social write (new contact, note, profile update, group member)
+//! used to publish a kind 30078 event straight away, so a busy group sent
+//! one relay publish per message. Writes now only mark their d-tag dirty in
+//! a [`PublishQueue`]: the first write opens a coalescing window, later
+//! writes to the same d-tag fold into it, and the event is built from the
+//! latest SQLite state when the window closes. A global token bucket caps
+//! publishes per minute; anything over the limit stays queued for the next
+//! tick. Pending writes are flushed without delay on shutdown.
+
+use crate::config::NostrPublishConfig;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A replaceable social event to (re)publish; content is read at publish time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PublishKey {
+    /// Hex pubkey of a contact (`snowclaw:memory:npub:<npub>`).
+    Npub(String),
+    /// Group id (`snowclaw:memory:group:<id>`).
+    Group(String),
+    /// Group id whose read markers changed (`snowclaw:memory:read:<id>`).
+    ReadMarkers(String),
+}
+
+/// Per-key coalescing windows.
+#[derive(Debug)]
+struct Coalescer {
+    window: Duration,
+    pending: HashMap<PublishKey, Instant>,
+}
+
+impl Coalescer {
+    fn schedule(&mut self, key: PublishKey, now: Instant) {
+        let window = self.window;
+        self.pending.entry(key).or_insert(now + window);
+    }
+
+    /// Keys whose window has closed, oldest deadline first.
+    fn due(&self, now: Instant) -> Vec<PublishKey> {
+        let mut due: Vec<(&PublishKey, &Instant)> =
+            self.pending.iter().filter(|(_, at)| **at <= now).collect();
+        due.sort_by_key(|(_, at)| **at);
+        due.into_iter().map(|(k, _)| k.clone()).collect()
+    }
+}
+
+/// Token bucket: `per_minute` publishes, refilled continuously. `0` = unlimited.
+#[derive(Debug)]
+struct RateLimiter {
+    per_minute: u32,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    fn new(per_minute: u32, now: Instant) -> Self {
+        Self {
+            per_minute,
+            tokens: f64::from(per_minute),
+            refilled_at: now,
+        }
+    }
+
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        if self.per_minute == 0 {
+            return true;
+        }
+        let capacity = f64::from(self.per_minute);
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * capacity / 60.0).min(capacity);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug)]
+struct QueueState {
+    coalescer: Coalescer,
+    limiter: RateLimiter,
+}
+
+/// Pending social publishes shared by all clones of a `NostrMemory`.
+#[derive(Debug)]
+pub struct PublishQueue {
+    state: parking_lot::Mutex<QueueState>,
+}
+
+impl PublishQueue {
+    pub fn new(config: &NostrPublishConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            state: parking_lot::Mutex::new(QueueState {
+                coalescer: Coalescer {
+                    window: Duration::from_secs(config.coalesce_secs),
+                    pending: HashMap::new(),
+                },
+                limiter: RateLimiter::new(config.max_per_minute, now),
+            }),
+        }
+    }
+
+    /// Mark `key` dirty; a no-op if it is already waiting.
+    pub fn schedule(&self, key: PublishKey) {
+        self.schedule_at(key, Instant::now());
+    }
+
+    fn schedule_at(&self, key: PublishKey, now: Instant) {
+        self.state.lock().coalescer.schedule(key, now);
+    }
+
+    /// Take the keys that are due and fit in the rate limit.
+    pub fn take_ready(&self) -> Vec<PublishKey> {
+        self.take_ready_at(Instant::now())
+    }
+
+    fn take_ready_at(&self, now: Instant) -> Vec<PublishKey> {
+        let mut state = self.state.lock();
+        let mut ready = Vec::new();
+        for key in state.coalescer.due(now) {
+            if !state.limiter.try_acquire(now) {
+                break;
+            }
+            state.coalescer.pending.remove(&key);
+            ready.push(key);
+        }
+        ready
+    }
+
+    /// Take every pending key regardless of window and rate limit (shutdown).
+    pub fn take_all(&self) -> Vec<PublishKey> {
+        self.state
+            .lock()
+            .coalescer
+            .pending
+            .drain()
+            .map(|(k, _)| k)
+            .collect()
+    }
+
+    pub fn pending(&self) -> usize {
+        self.state.lock().coalescer.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(coalesce_secs: u64, max_per_minute: u32) -> PublishQueue {
+        PublishQueue::new(&NostrPublishConfig {
+            coalesce_secs,
+            max_per_minute,
+        })
+    }
+
+    #[test]
+    fn writes_to_one_d_tag_coalesce() {
+        let q = queue(10, 0);
+        let t0 = Instant::now();
+        for i in 0..5 {
+            q.schedule_at(PublishKey::Npub("aa".into()), t0 + Duration::from_secs(i));
+        }
+        q.schedule_at(PublishKey::Group("dev".into()), t0 + Duration::from_secs(3));
+        assert_eq!(q.pending(), 2);
+
+        assert!(q.take_ready_at(t0 + Duration::from_secs(9)).is_empty());
+        assert_eq!(
+            q.take_ready_at(t0 + Duration::from_secs(10)),
+            vec![PublishKey::Npub("aa".into())]
+        );
+        assert_eq!(
+            q.take_ready_at(t0 + Duration::from_secs(13)),
+            vec![PublishKey::Group("dev".into())]
+        );
+        assert_eq!(q.pending(), 0);
+    }
+
+    #[test]
+    fn rate_limit_keeps_overflow_pending() {
+        let q = queue(0, 2);
+        let t0 = Instant::now();
+        for id in ["a", "b", "c"] {
+            q.schedule_at(PublishKey::Npub(id.into()), t0);
+        }
+        assert_eq!(q.take_ready_at(t0).len(), 2);
+        assert_eq!(q.pending(), 1);
+        assert!(q.take_ready_at(t0 + Duration::from_secs(1)).is_empty());
+        // 2/minute refills one token every 30s
+        assert_eq!(q.take_ready_at(t0 + Duration::from_secs(30)).len(), 1);
+    }
+
+    #[test]
+    fn take_all_ignores_window_and_limit() {
+        let q = queue(600, 1);
+        q.schedule(PublishKey::Npub("a".into()));
+        q.schedule(PublishKey::Group("g".into()));
+        assert!(q.take_ready().is_empty());
+        assert_eq!(q.take_all().len(), 2);
+        assert_eq!(q.pending(), 0);
+    }
+}
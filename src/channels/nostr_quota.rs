@@ -0,0 +1,572 @@
+//! Per-contact daily quotas for Nostr DMs.
+//!
+//! Every DM from a non-owner contact is admitted through a [`QuotaGuard`]
+//! before it reaches the agent. Requests are counted per UTC day in
+//! `contact_quota.json` (channel persist dir); cost comes from the
+//! `nostr/<hex pubkey>` rows of `state/costs.jsonl`, which is tail-read so
+//! the file isn't re-parsed for every message. Limits come from
+//! `[channels_config.nostr.quota]` (defaults plus per-tag overrides) and can
+//! be replaced for the rest of the day by the owner (`quota.override`).
+//!
+//! Cost is checked before a request, so the last admitted request of the
+//! day can take a contact past its cost limit.
+
+use crate::config::NostrQuotaConfig;
+use crate::cost::types::CostRecord;
+use crate::stats::{self, ContactQuotaRow};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Usage state file under the channel persist dir.
+const STATE_FILE: &str = "contact_quota.json";
+
+/// Daily limits for one contact. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuotaLimits {
+    pub requests: Option<u32>,
+    pub cost_usd: Option<f64>,
+}
+
+impl QuotaLimits {
+    /// From config values, where `0` means unlimited.
+    fn from_config(requests: u32, cost_usd: f64) -> Self {
+        Self {
+            requests: (requests > 0).then_some(requests),
+            cost_usd: (cost_usd > 0.0).then_some(cost_usd),
+        }
+    }
+
+    /// The more generous of two limits per dimension.
+    fn most_generous(self, other: Self) -> Self {
+        Self {
+            requests: self.requests.zip(other.requests).map(|(a, b)| a.max(b)),
+            cost_usd: self.cost_usd.zip(other.cost_usd).map(|(a, b)| a.max(b)),
+        }
+    }
+
+    /// Reason the limits are exceeded, if they are.
+    pub fn exceeded(&self, requests: u32, cost_usd: f64) -> Option<String> {
+        if let Some(limit) = self.requests {
+            if requests >= limit {
+                return Some(format!("{limit} requests per day"));
+            }
+        }
+        if let Some(limit) = self.cost_usd {
+            if cost_usd >= limit {
+                return Some(format!("${limit:.2} per day"));
+            }
+        }
+        None
+    }
+}
+
+/// Configured limits: defaults plus tagged contact sets.
+#[derive(Debug, Clone)]
+pub struct QuotaPolicy {
+    defaults: QuotaLimits,
+    tags: Vec<(String, QuotaLimits, HashSet<String>)>,
+}
+
+impl QuotaPolicy {
+    /// Build the policy; fails on unparsable tag pubkeys.
+    pub fn from_config(config: &NostrQuotaConfig) -> Result<Self> {
+        let defaults = QuotaLimits::from_config(config.daily_requests, config.daily_cost_usd);
+        let mut tags = Vec::new();
+        let mut names: Vec<&String> = config.tags.keys().collect();
+        names.sort();
+        for name in names {
+            let tag = &config.tags[name];
+            let limits = QuotaLimits::from_config(
+                tag.daily_requests.unwrap_or(config.daily_requests),
+                tag.daily_cost_usd.unwrap_or(config.daily_cost_usd),
+            );
+            let members = tag
+                .pubkeys
+                .iter()
+                .map(|pk| {
+                    PublicKey::parse(pk)
+                        .map(|pk| pk.to_hex())
+                        .with_context(|| format!("quota tag '{name}': invalid pubkey '{pk}'"))
+                })
+                .collect::<Result<HashSet<_>>>()?;
+            tags.push((name.clone(), limits, members));
+        }
+        Ok(Self { defaults, tags })
+    }
+
+    /// Limits for a contact (hex pubkey) and the tag they come from. A
+    /// contact in several tags gets the most generous limits among them.
+    pub fn limits_for(&self, hex: &str) -> (QuotaLimits, Option<String>) {
+        let mut matched: Option<(QuotaLimits, String)> = None;
+        for (name, limits, members) in &self.tags {
+            if !members.contains(hex) {
+                continue;
+            }
+            matched = Some(match matched {
+                None => (*limits, name.clone()),
+                Some((prev, prev_name)) => {
+                    (prev.most_generous(*limits), format!("{prev_name}+{name}"))
+                }
+            });
+        }
+        match matched {
+            Some((limits, tag)) => (limits, Some(tag)),
+            None => (self.defaults, None),
+        }
+    }
+}
+
+/// Persisted request counts, notifications and owner overrides for one day.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    #[serde(default)]
+    pub day: Option<NaiveDate>,
+    #[serde(default)]
+    pub requests: BTreeMap<String, u32>,
+    /// Contacts already told they hit the limit today.
+    #[serde(default)]
+    pub notified: BTreeSet<String>,
+    /// Owner overrides, valid until the day rolls over.
+    #[serde(default)]
+    pub overrides: BTreeMap<String, QuotaLimits>,
+}
+
+impl QuotaUsage {
+    pub fn path(persist_dir: &Path) -> PathBuf {
+        persist_dir.join(STATE_FILE)
+    }
+
+    /// Load usage state; a missing or unreadable file starts fresh.
+    pub fn load(persist_dir: &Path) -> Self {
+        let path = Self::path(persist_dir);
+        let Ok(raw) = std::fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&raw).unwrap_or_else(|e| {
+            warn!("Ignoring corrupt {}: {e}", path.display());
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, persist_dir: &Path) -> Result<()> {
+        let path = Self::path(persist_dir);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Reset counters, notifications and overrides when `today` is a new day.
+    pub fn roll_over(&mut self, today: NaiveDate) {
+        if self.day != Some(today) {
+            *self = Self {
+                day: Some(today),
+                ..Self::default()
+            };
+        }
+    }
+}
+
+/// Today's per-contact cost of Nostr DMs, tail-read from `costs.jsonl`.
+#[derive(Debug)]
+pub struct ContactCosts {
+    path: PathBuf,
+    offset: u64,
+    day: Option<NaiveDate>,
+    costs: HashMap<String, f64>,
+    /// Cost already counted for streamed responses still in progress.
+    streaming: HashMap<String, f64>,
+}
+
+impl ContactCosts {
+    pub fn new(workspace_dir: &Path) -> Self {
+        Self {
+            path: stats::costs_jsonl_path(workspace_dir),
+            offset: 0,
+            day: None,
+            costs: HashMap::new(),
+            streaming: HashMap::new(),
+        }
+    }
+
+    /// Cost attributed to `hex` today.
+    pub fn today(&mut self, hex: &str, today: NaiveDate) -> f64 {
+        if let Err(e) = self.refresh(today) {
+            warn!("Quota: failed to read cost records: {e}");
+        }
+        self.costs.get(hex).copied().unwrap_or(0.0)
+    }
+
+    fn refresh(&mut self, today: NaiveDate) -> Result<()> {
+        if self.day != Some(today) {
+            self.day = Some(today);
+            self.offset = 0;
+            self.costs.clear();
+            self.streaming.clear();
+        }
+        let Ok(mut file) = std::fs::File::open(&self.path) else {
+            return Ok(());
+        };
+        if file.metadata()?.len() < self.offset {
+            // Truncated or rotated: start over.
+            self.offset = 0;
+            self.costs.clear();
+            self.streaming.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 || !line.ends_with('\n') {
+                // EOF or a partially written line: pick it up next time.
+                break;
+            }
+            self.offset += read as u64;
+            if let Ok(record) = serde_json::from_str::<CostRecord>(line.trim()) {
+                if let Some(hex) = dm_contact(&record, today) {
+                    // A streamed response's records supersede each other.
+                    let counted = if record.partial {
+                        self.streaming
+                            .insert(record.id.clone(), record.usage.cost_usd)
+                    } else {
+                        self.streaming.remove(&record.id)
+                    };
+                    *self.costs.entry(hex.to_string()).or_default() +=
+                        record.usage.cost_usd - counted.unwrap_or(0.0);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The DM contact a cost record is billed to, if it is a Nostr DM from `day`.
+fn dm_contact(record: &CostRecord, day: NaiveDate) -> Option<&str> {
+    let room = record.room.as_deref()?;
+    (record.channel.as_deref() == Some("nostr")
+        && record.usage.timestamp.date_naive() == day
+        && room.len() == 64
+        && room.bytes().all(|b| b.is_ascii_hexdigit()))
+    .then_some(room)
+}
+
+/// Outcome of admitting a DM.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuotaDecision {
+    Allowed,
+    /// Over quota. `notify` is true the first time today, when the contact
+    /// should get the quota-exceeded reply.
+    Exceeded {
+        reason: String,
+        notify: bool,
+    },
+}
+
+/// Quota enforcement state shared by the DM handlers.
+#[derive(Debug)]
+pub struct QuotaGuard {
+    policy: QuotaPolicy,
+    usage: QuotaUsage,
+    costs: ContactCosts,
+    persist_dir: PathBuf,
+}
+
+impl QuotaGuard {
+    pub fn new(
+        config: &NostrQuotaConfig,
+        persist_dir: &Path,
+        workspace_dir: &Path,
+    ) -> Result<Self> {
+        Ok(Self {
+            policy: QuotaPolicy::from_config(config)?,
+            usage: QuotaUsage::load(persist_dir),
+            costs: ContactCosts::new(workspace_dir),
+            persist_dir: persist_dir.to_path_buf(),
+        })
+    }
+
+    /// Admit a DM from `hex`, counting it if allowed.
+    pub fn admit(&mut self, hex: &str, today: NaiveDate) -> QuotaDecision {
+        self.usage.roll_over(today);
+        let limits = self.effective_limits(hex);
+        let requests = self.usage.requests.get(hex).copied().unwrap_or(0);
+        let cost = if limits.cost_usd.is_some() {
+            self.costs.today(hex, today)
+        } else {
+            0.0
+        };
+
+        let decision = match limits.exceeded(requests, cost) {
+            None => {
+                *self.usage.requests.entry(hex.to_string()).or_default() += 1;
+                QuotaDecision::Allowed
+            }
+            Some(reason) => QuotaDecision::Exceeded {
+                reason,
+                notify: self.usage.notified.insert(hex.to_string()),
+            },
+        };
+        self.save();
+        decision
+    }
+
+    /// Replace a contact's limits until the end of the day (owner override).
+    /// Clears the "already notified" mark so a later limit is announced again.
+    pub fn set_override(&mut self, hex: &str, limits: QuotaLimits, today: NaiveDate) {
+        self.usage.roll_over(today);
+        self.usage.overrides.insert(hex.to_string(), limits);
+        self.usage.notified.remove(hex);
+        self.save();
+    }
+
+    /// Forget a contact's request count and override for today.
+    pub fn reset(&mut self, hex: &str, today: NaiveDate) {
+        self.usage.roll_over(today);
+        self.usage.requests.remove(hex);
+        self.usage.overrides.remove(hex);
+        self.usage.notified.remove(hex);
+        self.save();
+    }
+
+    fn effective_limits(&self, hex: &str) -> QuotaLimits {
+        self.usage
+            .overrides
+            .get(hex)
+            .copied()
+            .unwrap_or_else(|| self.policy.limits_for(hex).0)
+    }
+
+    fn save(&self) {
+        if let Err(e) = self.usage.save(&self.persist_dir) {
+            warn!("Failed to save contact quota state: {e:#}");
+        }
+    }
+}
+
+/// Fill the exceeded-message template.
+pub fn exceeded_message(template: &str, reason: &str) -> String {
+    template.replace("{reason}", reason)
+}
+
+/// Today's quota rows for `stats`: every contact with requests or DM cost
+/// today, with their effective limits.
+pub fn usage_rows(
+    config: &NostrQuotaConfig,
+    persist_dir: &Path,
+    records: &[CostRecord],
+    today: NaiveDate,
+) -> Result<Vec<ContactQuotaRow>> {
+    let policy = QuotaPolicy::from_config(config)?;
+    let mut usage = QuotaUsage::load(persist_dir);
+    usage.roll_over(today);
+
+    let mut costs: BTreeMap<String, f64> = BTreeMap::new();
+    for record in records {
+        if let Some(hex) = dm_contact(record, today) {
+            *costs.entry(hex.to_string()).or_default() += record.usage.cost_usd;
+        }
+    }
+    let contacts: BTreeSet<&String> = usage.requests.keys().chain(costs.keys()).collect();
+
+    let mut rows: Vec<ContactQuotaRow> = contacts
+        .into_iter()
+        .map(|hex| {
+            let (limits, tag) = match usage.overrides.get(hex) {
+                Some(limits) => (*limits, Some("override".to_string())),
+                None => policy.limits_for(hex),
+            };
+            ContactQuotaRow {
+                contact: contact_label(hex),
+                tag,
+                requests: usage.requests.get(hex).copied().unwrap_or(0),
+                request_limit: limits.requests,
+                cost: costs.get(hex).copied().unwrap_or(0.0),
+                cost_limit: limits.cost_usd,
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.cost
+            .partial_cmp(&a.cost)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.requests.cmp(&a.requests))
+    });
+    Ok(rows)
+}
+
+fn contact_label(hex: &str) -> String {
+    let npub = PublicKey::from_hex(hex)
+        .ok()
+        .and_then(|pk| pk.to_bech32().ok())
+        .unwrap_or_else(|| hex.to_string());
+    if npub.len() > 20 {
+        format!("{}…", &npub[..20])
+    } else {
+        npub
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NostrQuotaTag;
+    use crate::cost::types::TokenUsage;
+
+    fn hex(n: u8) -> String {
+        Keys::parse(&format!("{:064x}", n))
+            .unwrap()
+            .public_key()
+            .to_hex()
+    }
+
+    fn day() -> NaiveDate {
+        chrono::Utc::now().date_naive()
+    }
+
+    fn dm_cost(contact: &str, cost: f64) -> CostRecord {
+        let mut usage = TokenUsage::new("m", 100, 50, 0.0, 0.0);
+        usage.cost_usd = cost;
+        CostRecord::with_context("s", usage, Some("nostr".into()), Some(contact.into()), None)
+    }
+
+    fn config_with_tag() -> NostrQuotaConfig {
+        let mut config = NostrQuotaConfig {
+            enabled: true,
+            daily_requests: 2,
+            daily_cost_usd: 0.0,
+            ..NostrQuotaConfig::default()
+        };
+        config.tags.insert(
+            "friends".into(),
+            NostrQuotaTag {
+                pubkeys: vec![hex(2)],
+                daily_requests: Some(0),
+                daily_cost_usd: Some(1.0),
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn tag_limits_override_defaults() {
+        let policy = QuotaPolicy::from_config(&config_with_tag()).unwrap();
+        let (limits, tag) = policy.limits_for(&hex(1));
+        assert_eq!(limits.requests, Some(2));
+        assert_eq!(limits.cost_usd, None);
+        assert!(tag.is_none());
+
+        let (limits, tag) = policy.limits_for(&hex(2));
+        assert_eq!(limits.requests, None);
+        assert_eq!(limits.cost_usd, Some(1.0));
+        assert_eq!(tag.as_deref(), Some("friends"));
+
+        let mut bad = config_with_tag();
+        bad.tags.get_mut("friends").unwrap().pubkeys = vec!["nope".into()];
+        assert!(QuotaPolicy::from_config(&bad).is_err());
+    }
+
+    #[test]
+    fn request_quota_blocks_and_notifies_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut guard = QuotaGuard::new(&config_with_tag(), dir.path(), dir.path()).unwrap();
+        let contact = hex(1);
+
+        assert_eq!(guard.admit(&contact, day()), QuotaDecision::Allowed);
+        assert_eq!(guard.admit(&contact, day()), QuotaDecision::Allowed);
+        assert_eq!(
+            guard.admit(&contact, day()),
+            QuotaDecision::Exceeded {
+                reason: "2 requests per day".into(),
+                notify: true
+            }
+        );
+        assert!(matches!(
+            guard.admit(&contact, day()),
+            QuotaDecision::Exceeded { notify: false, .. }
+        ));
+
+        // Counts survive a restart; a new day starts fresh.
+        let mut guard = QuotaGuard::new(&config_with_tag(), dir.path(), dir.path()).unwrap();
+        assert!(matches!(
+            guard.admit(&contact, day()),
+            QuotaDecision::Exceeded { .. }
+        ));
+        let tomorrow = day().succ_opt().unwrap();
+        assert_eq!(guard.admit(&contact, tomorrow), QuotaDecision::Allowed);
+    }
+
+    #[test]
+    fn owner_override_lifts_limit_for_the_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut guard = QuotaGuard::new(&config_with_tag(), dir.path(), dir.path()).unwrap();
+        let contact = hex(1);
+        guard.admit(&contact, day());
+        guard.admit(&contact, day());
+        assert!(matches!(
+            guard.admit(&contact, day()),
+            QuotaDecision::Exceeded { .. }
+        ));
+
+        guard.set_override(&contact, QuotaLimits::default(), day());
+        assert_eq!(guard.admit(&contact, day()), QuotaDecision::Allowed);
+
+        guard.reset(&contact, day());
+        assert_eq!(guard.admit(&contact, day()), QuotaDecision::Allowed);
+    }
+
+    #[test]
+    fn cost_quota_reads_appended_cost_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let costs_path = stats::costs_jsonl_path(dir.path());
+        std::fs::create_dir_all(costs_path.parent().unwrap()).unwrap();
+        let friend = hex(2);
+        let append = |record: &CostRecord| {
+            use std::io::Write;
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&costs_path)
+                .unwrap();
+            writeln!(file, "{}", serde_json::to_string(record).unwrap()).unwrap();
+        };
+
+        let mut guard = QuotaGuard::new(&config_with_tag(), dir.path(), dir.path()).unwrap();
+        append(&dm_cost(&friend, 0.6));
+        append(&dm_cost("#dev", 5.0));
+        assert_eq!(guard.admit(&friend, day()), QuotaDecision::Allowed);
+        append(&dm_cost(&friend, 0.5));
+        assert_eq!(
+            guard.admit(&friend, day()),
+            QuotaDecision::Exceeded {
+                reason: "$1.00 per day".into(),
+                notify: true
+            }
+        );
+    }
+
+    #[test]
+    fn usage_rows_combine_requests_and_costs() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config_with_tag();
+        let mut guard = QuotaGuard::new(&config, dir.path(), dir.path()).unwrap();
+        guard.admit(&hex(1), day());
+
+        let rows = usage_rows(&config, dir.path(), &[dm_cost(&hex(2), 0.25)], day()).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].tag.as_deref(), Some("friends"));
+        assert!((rows[0].cost - 0.25).abs() < f64::EPSILON);
+        assert_eq!(rows[0].cost_limit, Some(1.0));
+        assert_eq!(rows[1].requests, 1);
+        assert_eq!(rows[1].request_limit, Some(2));
+        assert!(rows[1].contact.starts_with("npub1"));
+    }
+}
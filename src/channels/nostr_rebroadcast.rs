@@ -0,0 +1,331 @@
+//! Bootstrap re-broadcast of replaceable events to newly added relays.
+//!
+//! Profile, relay lists, agent state and NIP-78 social/memory events are
+//! replaceable: relays only keep the latest version, and a relay added to
+//! config after they were published never sees them. On startup, relays
+//! that are not yet recorded in `relay_rebroadcast.json` (under the channel
+//! persist dir) get the current replaceable set copied from the relays that
+//! already have it.
+//!
+//! Publishing is rate-limited and progress is saved after every event, so an
+//! interrupted run resumes where it stopped on the next start. On the very
+//! first run every configured relay is recorded as bootstrapped.
+
+use anyhow::{Context, Result};
+use nostr_core::normalize_url;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// State file name under the channel persist dir.
+const STATE_FILE: &str = "relay_rebroadcast.json";
+/// Delay between events sent to one relay.
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(250);
+/// Timeout for fetching the replaceable set from source relays.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Kinds we publish as replaceable/addressable events.
+const REBROADCAST_KINDS: &[u16] = &[
+    0,     // profile
+    3,     // contact list
+    10002, // relay list (NIP-65)
+    10050, // messaging relay list (NIP-17)
+    30078, // NIP-78 social data, memories, config
+    31121, // agent state
+];
+
+/// Per-relay bootstrap progress.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayProgress {
+    /// Every event of the replaceable set has been sent.
+    pub complete: bool,
+    /// Event IDs already accepted by the relay (for resuming).
+    #[serde(default)]
+    pub sent: Vec<String>,
+}
+
+/// Persisted bootstrap state, keyed by normalized relay URL.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RebroadcastState {
+    #[serde(default)]
+    pub relays: BTreeMap<String, RelayProgress>,
+}
+
+impl RebroadcastState {
+    pub fn path(persist_dir: &Path) -> PathBuf {
+        persist_dir.join(STATE_FILE)
+    }
+
+    /// Load state; `None` when no state file exists yet (first run).
+    pub fn load(persist_dir: &Path) -> Result<Option<Self>> {
+        let path = Self::path(persist_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let state = serde_json::from_str(&raw)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(Some(state))
+    }
+
+    pub fn save(&self, persist_dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(persist_dir)?;
+        let path = Self::path(persist_dir);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Reconcile with the configured relays and return those still needing
+    /// a bootstrap. Without prior state every relay counts as bootstrapped.
+    pub fn pending(previous: Option<Self>, relays: &[String]) -> (Self, Vec<String>) {
+        let first_run = previous.is_none();
+        let mut state = previous.unwrap_or_default();
+        let mut pending = Vec::new();
+        for relay in relays {
+            let url = normalize_url(relay);
+            let progress = state
+                .relays
+                .entry(url.clone())
+                .or_insert_with(|| RelayProgress {
+                    complete: first_run,
+                    sent: Vec::new(),
+                });
+            if !progress.complete && !pending.contains(&url) {
+                pending.push(url);
+            }
+        }
+        (state, pending)
+    }
+
+    /// Configured relays that already hold the replaceable set.
+    pub fn sources(&self, relays: &[String]) -> Vec<String> {
+        relays
+            .iter()
+            .map(|r| normalize_url(r))
+            .filter(|url| self.relays.get(url).is_some_and(|p| p.complete))
+            .collect()
+    }
+}
+
+/// Keep only the newest event per replaceable coordinate (kind, author, d-tag).
+pub fn latest_replaceable(events: impl IntoIterator<Item = Event>) -> Vec<Event> {
+    let mut latest: HashMap<(u16, PublicKey, String), Event> = HashMap::new();
+    for event in events {
+        if !(event.kind.is_replaceable() || event.kind.is_addressable()) {
+            continue;
+        }
+        let d_tag = if event.kind.is_addressable() {
+            event.tags.identifier().unwrap_or_default().to_string()
+        } else {
+            String::new()
+        };
+        let key = (event.kind.as_u16(), event.pubkey, d_tag);
+        match latest.get(&key) {
+            Some(existing) if existing.created_at >= event.created_at => {}
+            _ => {
+                latest.insert(key, event);
+            }
+        }
+    }
+    let mut events: Vec<Event> = latest.into_values().collect();
+    events.sort_by_key(|e| (e.created_at, e.id));
+    events
+}
+
+/// Bootstrap every pending relay. Runs until done; progress survives restarts.
+pub async fn run(client: Client, author: PublicKey, relays: Vec<String>, persist_dir: PathBuf) {
+    let previous = match RebroadcastState::load(&persist_dir) {
+        Ok(state) => state,
+        Err(e) => {
+            warn!("Relay re-broadcast disabled: {e:#}");
+            return;
+        }
+    };
+    let (mut state, pending) = RebroadcastState::pending(previous, &relays);
+    if let Err(e) = state.save(&persist_dir) {
+        warn!("Failed to save relay re-broadcast state: {e:#}");
+        return;
+    }
+    if pending.is_empty() {
+        return;
+    }
+
+    let sources = state.sources(&relays);
+    if sources.is_empty() {
+        warn!("No bootstrapped relay to copy replaceable events from; skipping re-broadcast");
+        return;
+    }
+
+    let filter = Filter::new()
+        .author(author)
+        .kinds(REBROADCAST_KINDS.iter().map(|k| Kind::from(*k)));
+    let events = match client
+        .fetch_events_from(sources, filter, FETCH_TIMEOUT)
+        .await
+    {
+        Ok(events) => latest_replaceable(events),
+        Err(e) => {
+            warn!("Failed to fetch replaceable events for re-broadcast: {e}");
+            return;
+        }
+    };
+
+    for relay in pending {
+        let sent = rebroadcast_to(&client, &relay, &events, &mut state, &persist_dir).await;
+        info!(
+            "Re-broadcast {sent} replaceable event(s) to new relay {relay}{}",
+            if state.relays.get(&relay).is_some_and(|p| p.complete) {
+                ""
+            } else {
+                " (incomplete, will resume on next start)"
+            }
+        );
+    }
+}
+
+/// Send the replaceable set to one relay, skipping events already sent.
+async fn rebroadcast_to(
+    client: &Client,
+    relay: &str,
+    events: &[Event],
+    state: &mut RebroadcastState,
+    persist_dir: &Path,
+) -> usize {
+    let already: HashSet<String> = state
+        .relays
+        .get(relay)
+        .map(|p| p.sent.iter().cloned().collect())
+        .unwrap_or_default();
+    let mut sent = 0usize;
+    let mut failed = 0usize;
+
+    for event in events.iter().filter(|e| !already.contains(&e.id.to_hex())) {
+        match client.send_event_to([relay], event).await {
+            Ok(output) if !output.success.is_empty() => {
+                sent += 1;
+                if let Some(progress) = state.relays.get_mut(relay) {
+                    progress.sent.push(event.id.to_hex());
+                }
+                if let Err(e) = state.save(persist_dir) {
+                    warn!("Failed to save relay re-broadcast progress: {e:#}");
+                }
+            }
+            Ok(output) => {
+                failed += 1;
+                debug!("Relay {relay} rejected {}: {:?}", event.id, output.failed);
+            }
+            Err(e) => {
+                failed += 1;
+                debug!("Failed to send {} to {relay}: {e}", event.id);
+            }
+        }
+        tokio::time::sleep(PUBLISH_INTERVAL).await;
+    }
+
+    if failed == 0 {
+        if let Some(progress) = state.relays.get_mut(relay) {
+            progress.complete = true;
+            progress.sent.clear();
+        }
+        if let Err(e) = state.save(persist_dir) {
+            warn!("Failed to save relay re-broadcast state: {e:#}");
+        }
+    }
+    sent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relays(urls: &[&str]) -> Vec<String> {
+        urls.iter().map(|u| (*u).to_string()).collect()
+    }
+
+    #[test]
+    fn first_run_marks_all_relays_bootstrapped() {
+        let (state, pending) =
+            RebroadcastState::pending(None, &relays(&["wss://a.example", "wss://b.example/"]));
+        assert!(pending.is_empty());
+        assert!(state.relays.values().all(|p| p.complete));
+        assert!(state.relays.contains_key("wss://b.example"));
+    }
+
+    #[test]
+    fn new_relay_is_pending_and_resumes() {
+        let (state, _) = RebroadcastState::pending(None, &relays(&["wss://a.example"]));
+        let (mut state, pending) = RebroadcastState::pending(
+            Some(state),
+            &relays(&["wss://a.example", "wss://new.example"]),
+        );
+        assert_eq!(pending, vec!["wss://new.example".to_string()]);
+        assert_eq!(
+            state.sources(&relays(&["wss://a.example", "wss://new.example"])),
+            vec!["wss://a.example".to_string()]
+        );
+
+        // Partial progress is kept across a restart.
+        state
+            .relays
+            .get_mut("wss://new.example")
+            .unwrap()
+            .sent
+            .push("abc".into());
+        let (state, pending) = RebroadcastState::pending(
+            Some(state),
+            &relays(&["wss://a.example", "wss://new.example"]),
+        );
+        assert_eq!(pending.len(), 1);
+        assert_eq!(
+            state.relays["wss://new.example"].sent,
+            vec!["abc".to_string()]
+        );
+    }
+
+    #[test]
+    fn state_round_trips_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(RebroadcastState::load(dir.path()).unwrap().is_none());
+        let (state, _) = RebroadcastState::pending(None, &relays(&["wss://a.example"]));
+        state.save(dir.path()).unwrap();
+        assert_eq!(RebroadcastState::load(dir.path()).unwrap(), Some(state));
+    }
+
+    #[test]
+    fn latest_replaceable_keeps_newest_per_coordinate() {
+        let keys = Keys::generate();
+        let sign = |kind: u16, d: Option<&str>, ts: u64| {
+            let mut builder =
+                EventBuilder::new(Kind::from(kind), "x").custom_created_at(Timestamp::from(ts));
+            if let Some(d) = d {
+                builder = builder.tag(Tag::identifier(d));
+            }
+            builder.sign_with_keys(&keys).unwrap()
+        };
+        let events = vec![
+            sign(0, None, 10),
+            sign(0, None, 20),
+            sign(30078, Some("a"), 5),
+            sign(30078, Some("b"), 6),
+            sign(30078, Some("a"), 7),
+            sign(1, None, 30),
+        ];
+
+        let latest = latest_replaceable(events);
+        assert_eq!(latest.len(), 3);
+        assert!(latest
+            .iter()
+            .any(|e| e.kind == Kind::Metadata && e.created_at.as_secs() == 20));
+        assert!(latest.iter().any(|e| e.kind == Kind::Custom(30078)
+            && e.tags.identifier() == Some("a")
+            && e.created_at.as_secs() == 7));
+        assert!(!latest.iter().any(|e| e.kind == Kind::TextNote));
+    }
+}
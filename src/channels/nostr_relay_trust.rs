@@ -0,0 +1,159 @@
+//! Per-relay trust for inbound events.
+//!
+//! Events are signed by their authors, but any relay can still hand the
+//! agent old or selectively withheld ones. `[channels_config.nostr.relay_trust]`
+//! marks each relay as [`NostrRelayTrust::Trusted`], `ReadOnly`, `WriteOnly`
+//! or `Untrusted`. Read and write roles become relay pool flags
+//! ([`RelayTrustMap::relay_options`]); the receive stage drops events a relay
+//! may not deliver ([`RelayTrustMap::accepts`]) before they are marked seen.
+//! The relay pool reports an event only once, from the first relay that
+//! delivers it, so sensitive kinds are instead taken from each relay's raw
+//! `EVENT` messages; a copy from a trusted relay still counts after an
+//! untrusted relay delivered the same event first.
+
+use crate::config::{NostrRelayTrust, NostrRelayTrustConfig};
+use anyhow::{Context, Result};
+use nostr_sdk::prelude::*;
+use std::collections::HashMap;
+
+/// Kinds only honored from relays trusted for actions: kind 1121 action
+/// requests, kind 30078 dynamic config and kind 5 deletions.
+pub const SENSITIVE_KINDS: [u16; 3] = [1121, 30078, 5];
+
+/// Whether `kind` is only honored from relays trusted for actions.
+pub fn is_sensitive(kind: u16) -> bool {
+    SENSITIVE_KINDS.contains(&kind)
+}
+
+/// Validated `[channels_config.nostr.relay_trust]`.
+#[derive(Debug, Clone, Default)]
+pub struct RelayTrustMap {
+    default: NostrRelayTrust,
+    relays: HashMap<RelayUrl, NostrRelayTrust>,
+}
+
+impl RelayTrustMap {
+    pub fn new(config: &NostrRelayTrustConfig) -> Result<Self> {
+        let mut relays = HashMap::with_capacity(config.relays.len());
+        for (url, trust) in &config.relays {
+            let url = RelayUrl::parse(url).with_context(|| format!("invalid relay URL '{url}'"))?;
+            relays.insert(url, *trust);
+        }
+        Ok(Self {
+            default: config.default,
+            relays,
+        })
+    }
+
+    pub fn trust(&self, relay: &RelayUrl) -> NostrRelayTrust {
+        self.relays.get(relay).copied().unwrap_or(self.default)
+    }
+
+    /// Whether an event of `kind` delivered by `relay` may be processed.
+    pub fn accepts(&self, relay: &RelayUrl, kind: u16) -> bool {
+        match self.trust(relay) {
+            NostrRelayTrust::Trusted | NostrRelayTrust::ReadOnly => true,
+            NostrRelayTrust::WriteOnly => false,
+            NostrRelayTrust::Untrusted => !is_sensitive(kind),
+        }
+    }
+
+    /// `base` with the read/write flags for `relay`.
+    pub fn relay_options(&self, relay: &str, base: RelayOptions) -> RelayOptions {
+        let trust = RelayUrl::parse(relay).map_or(self.default, |url| self.trust(&url));
+        match trust {
+            NostrRelayTrust::ReadOnly => base.write(false),
+            NostrRelayTrust::WriteOnly => base.read(false),
+            NostrRelayTrust::Trusted | NostrRelayTrust::Untrusted => base,
+        }
+    }
+
+    /// Of `relays`, those sensitive kinds may be fetched from; `None` when
+    /// all of them qualify.
+    pub fn trusted_relays(&self, relays: &[String]) -> Option<Vec<RelayUrl>> {
+        let urls: Vec<RelayUrl> = relays
+            .iter()
+            .filter_map(|r| RelayUrl::parse(r).ok())
+            .collect();
+        let trusted: Vec<RelayUrl> = urls
+            .iter()
+            .filter(|url| self.accepts(url, 30078))
+            .cloned()
+            .collect();
+        (trusted.len() < urls.len()).then_some(trusted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(default: NostrRelayTrust, relays: &[(&str, NostrRelayTrust)]) -> RelayTrustMap {
+        RelayTrustMap::new(&NostrRelayTrustConfig {
+            default,
+            relays: relays
+                .iter()
+                .map(|(url, trust)| ((*url).to_string(), *trust))
+                .collect(),
+        })
+        .unwrap()
+    }
+
+    fn url(s: &str) -> RelayUrl {
+        RelayUrl::parse(s).unwrap()
+    }
+
+    #[test]
+    fn untrusted_relays_only_deliver_plain_kinds() {
+        let trust = map(
+            NostrRelayTrust::Trusted,
+            &[
+                ("wss://public.example", NostrRelayTrust::Untrusted),
+                ("wss://sink.example", NostrRelayTrust::WriteOnly),
+                ("wss://archive.example", NostrRelayTrust::ReadOnly),
+            ],
+        );
+        let public = url("wss://public.example");
+        assert!(trust.accepts(&public, 9));
+        assert!(!trust.accepts(&public, 1121));
+        assert!(!trust.accepts(&public, 30078));
+        assert!(!trust.accepts(&public, 5));
+
+        assert!(!trust.accepts(&url("wss://sink.example"), 9));
+        assert!(trust.accepts(&url("wss://archive.example"), 1121));
+        assert!(trust.accepts(&url("wss://other.example"), 1121));
+    }
+
+    #[test]
+    fn untrusted_default_makes_an_allowlist() {
+        let trust = map(
+            NostrRelayTrust::Untrusted,
+            &[("wss://home.example", NostrRelayTrust::Trusted)],
+        );
+        assert!(trust.accepts(&url("wss://home.example"), 1121));
+        assert!(!trust.accepts(&url("wss://other.example"), 1121));
+
+        let relays = vec![
+            "wss://home.example".to_string(),
+            "wss://other.example".to_string(),
+        ];
+        assert_eq!(
+            trust.trusted_relays(&relays),
+            Some(vec![url("wss://home.example")])
+        );
+        assert_eq!(
+            RelayTrustMap::default().trusted_relays(&relays),
+            None,
+            "all relays trusted by default"
+        );
+    }
+
+    #[test]
+    fn invalid_relay_urls_are_rejected() {
+        let config = NostrRelayTrustConfig {
+            default: NostrRelayTrust::Trusted,
+            relays: HashMap::from([("not a url".to_string(), NostrRelayTrust::Untrusted)]),
+        };
+        assert!(RelayTrustMap::new(&config).is_err());
+    }
+}
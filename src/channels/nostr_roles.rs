@@ -0,0 +1,250 @@
+//! Owners, admins and operators of the Nostr channel.
+//!
+//! `owner` is the primary owner: digests, alerts, banners, catch-ups and
+//! exports are sent to it by DM. `[[channels_config.nostr.owners]]` adds
+//! further pubkeys with a role. Privileges are cumulative:
+//!
+//! - `operator`: `stop`/`resume` in a group and the group-scoped
+//!   `control.stop`/`control.resume`/`control.unmute` actions
+//! - `admin`: also `halt`, the global control actions, `config.set`,
+//!   NIP-78 dynamic config, `control.update`, `index.rebuild` and
+//!   `quota.override`
+//! - `owner`: everything, including `memory.*`, `proactive.*`,
+//!   `conversation.export`, `conversation.catchup`, `events.delete`, DM
+//!   slash commands and owner notes
+//!
+//! Every role holder bypasses DM quotas, is answered in `respond_mode =
+//! "owner"` groups and is labeled with its role in the prompt context. Each
+//! is tagged in our kind 0 profile, and NIP-AE ownership (a kind 14199 claim
+//! tagging us back) is verified per pubkey.
+
+use nostr_sdk::prelude::*;
+use std::fmt;
+use tracing::warn;
+
+use crate::config::NostrOwnerConfig;
+
+/// Role of a privileged pubkey, ordered by privilege.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    Operator,
+    Admin,
+    Owner,
+}
+
+impl Role {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "owner" => Some(Self::Owner),
+            "admin" => Some(Self::Admin),
+            "operator" => Some(Self::Operator),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Owner => "owner",
+            Self::Admin => "admin",
+            Self::Operator => "operator",
+        }
+    }
+
+    /// Label for the prompt context, e.g. `Admin`.
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Owner => "Owner",
+            Self::Admin => "Admin",
+            Self::Operator => "Operator",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Lowest role that may run a privileged kind 1121 action; `None` for
+/// actions open to allowed contacts. Control actions without a group act on
+/// every group and need an admin.
+pub fn required_role(action: &str, group: Option<&str>) -> Option<Role> {
+    match action {
+        "conversation.export" | "conversation.catchup" | "events.delete" => Some(Role::Owner),
+        _ if action.starts_with("memory.") || action.starts_with("proactive.") => Some(Role::Owner),
+        "config.set" | "control.update" | "index.rebuild" | "quota.override" => Some(Role::Admin),
+        _ if action.starts_with("control.stop")
+            || action.starts_with("control.resume")
+            || action == "control.unmute" =>
+        {
+            Some(if group.is_some() {
+                Role::Operator
+            } else {
+                Role::Admin
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Whether a kind 14199 owner claim tags `agent`.
+pub fn claims_agent(event: &Event, agent: &PublicKey) -> bool {
+    let agent = agent.to_hex();
+    event.tags.iter().any(|tag| {
+        let s = tag.as_slice();
+        s.first().map(|v| v.as_str()) == Some("p")
+            && s.get(1).map(|v| v.as_str()) == Some(agent.as_str())
+    })
+}
+
+/// Privileged pubkeys and their roles.
+#[derive(Debug, Clone, Default)]
+pub struct Roles {
+    members: Vec<(PublicKey, Role)>,
+}
+
+impl Roles {
+    /// `owner` with the owner role, then `entries`. Entries with an invalid
+    /// pubkey or role are skipped with a warning; a pubkey listed twice
+    /// keeps its highest role.
+    pub fn new(owner: Option<PublicKey>, entries: &[NostrOwnerConfig]) -> Self {
+        let mut roles = Self::default();
+        if let Some(owner) = owner {
+            roles.insert(owner, Role::Owner);
+        }
+        for entry in entries {
+            let Ok(pubkey) = PublicKey::parse(entry.pubkey.trim()) else {
+                warn!(
+                    "Ignoring Nostr owner entry with invalid pubkey {}",
+                    entry.pubkey
+                );
+                continue;
+            };
+            let Some(role) = Role::parse(&entry.role) else {
+                warn!(
+                    "Ignoring Nostr owner entry {} with unknown role {} (owner, admin or operator)",
+                    entry.pubkey, entry.role
+                );
+                continue;
+            };
+            roles.insert(pubkey, role);
+        }
+        roles
+    }
+
+    fn insert(&mut self, pubkey: PublicKey, role: Role) {
+        match self.members.iter_mut().find(|(pk, _)| *pk == pubkey) {
+            Some((_, existing)) => *existing = (*existing).max(role),
+            None => self.members.push((pubkey, role)),
+        }
+    }
+
+    pub fn role_of(&self, pubkey: &PublicKey) -> Option<Role> {
+        self.members
+            .iter()
+            .find(|(pk, _)| pk == pubkey)
+            .map(|(_, role)| *role)
+    }
+
+    /// Whether `pubkey` holds `role` or a higher one.
+    pub fn has(&self, pubkey: &PublicKey, role: Role) -> bool {
+        self.role_of(pubkey).is_some_and(|r| r >= role)
+    }
+
+    /// Role holders in config order, the primary owner first.
+    pub fn members(&self) -> &[(PublicKey, Role)] {
+        &self.members
+    }
+
+    /// Pubkeys holding `role` or a higher one.
+    pub fn pubkeys_with(&self, role: Role) -> Vec<PublicKey> {
+        self.members
+            .iter()
+            .filter(|(_, r)| *r >= role)
+            .map(|(pk, _)| *pk)
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pubkey: &str, role: &str) -> NostrOwnerConfig {
+        NostrOwnerConfig {
+            pubkey: pubkey.to_string(),
+            role: role.to_string(),
+        }
+    }
+
+    #[test]
+    fn roles_parse_and_order() {
+        assert_eq!(Role::parse(" Admin "), Some(Role::Admin));
+        assert_eq!(Role::parse("operator"), Some(Role::Operator));
+        assert_eq!(Role::parse("root"), None);
+        assert!(Role::Owner > Role::Admin && Role::Admin > Role::Operator);
+        assert_eq!(Role::Admin.to_string(), "admin");
+    }
+
+    #[test]
+    fn members_keep_their_highest_role() {
+        let owner = Keys::generate().public_key();
+        let admin = Keys::generate().public_key();
+        let roles = Roles::new(
+            Some(owner),
+            &[
+                entry(&admin.to_hex(), "operator"),
+                entry(&admin.to_bech32().unwrap(), "admin"),
+                entry(&owner.to_hex(), "operator"),
+                entry("npub1nope", "admin"),
+                entry(&Keys::generate().public_key().to_hex(), "root"),
+            ],
+        );
+        assert_eq!(roles.members().len(), 2);
+        assert_eq!(roles.role_of(&owner), Some(Role::Owner));
+        assert_eq!(roles.role_of(&admin), Some(Role::Admin));
+        assert!(roles.has(&admin, Role::Operator));
+        assert!(!roles.has(&admin, Role::Owner));
+        assert_eq!(roles.pubkeys_with(Role::Owner), vec![owner]);
+        assert_eq!(roles.pubkeys_with(Role::Admin), vec![owner, admin]);
+        assert!(Roles::new(None, &[]).is_empty());
+    }
+
+    #[test]
+    fn actions_require_roles() {
+        assert_eq!(required_role("memory.search", None), Some(Role::Owner));
+        assert_eq!(required_role("events.delete", None), Some(Role::Owner));
+        assert_eq!(required_role("proactive.approve", None), Some(Role::Owner));
+        assert_eq!(required_role("config.set", None), Some(Role::Admin));
+        assert_eq!(required_role("control.stop", None), Some(Role::Admin));
+        assert_eq!(
+            required_role("control.stop", Some("dev")),
+            Some(Role::Operator)
+        );
+        assert_eq!(
+            required_role("control.unmute", Some("dev")),
+            Some(Role::Operator)
+        );
+        assert_eq!(required_role("config.get", None), None);
+        assert_eq!(required_role("control.ping", None), None);
+    }
+
+    #[test]
+    fn owner_claims_must_tag_the_agent() {
+        let agent = Keys::generate().public_key();
+        let owner = Keys::generate();
+        let claim = |tagged: PublicKey| {
+            EventBuilder::new(Kind::Custom(14199), "")
+                .tag(Tag::public_key(tagged))
+                .sign_with_keys(&owner)
+                .unwrap()
+        };
+        assert!(claims_agent(&claim(agent), &agent));
+        assert!(!claims_agent(&claim(Keys::generate().public_key()), &agent));
+    }
+}
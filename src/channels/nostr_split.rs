@@ -0,0 +1,233 @@
+//! Splitting of long group replies into several events.
+//!
+//! With `[channels_config.nostr.split]` enabled, a reply longer than
+//! `max_chars` is cut at markdown boundaries — paragraphs, then lines, then
+//! sentences or words — and the parts are numbered `(1/3)`, `(2/3)`, ….
+//! Fenced code blocks stay whole when they fit; otherwise each piece is
+//! closed and reopened with the same fence. The channel publishes the parts
+//! as a chain of replies.
+
+use crate::config::NostrSplitConfig;
+
+/// Parts are never cut smaller than this, whatever `max_chars` says.
+pub const MIN_PART_CHARS: usize = 200;
+
+/// Room kept in every part for the `(i/n)` footer.
+const NUMBER_RESERVE: usize = 12;
+
+/// Parts to publish for `text`; a single part when splitting is off or the
+/// text fits.
+pub fn split_reply(text: &str, config: &NostrSplitConfig) -> Vec<String> {
+    let max = config.max_chars.max(MIN_PART_CHARS);
+    if !config.enabled || text.chars().count() <= max {
+        return vec![text.to_string()];
+    }
+    let budget = if config.number_parts {
+        max - NUMBER_RESERVE
+    } else {
+        max
+    };
+    let parts = split_markdown(text, budget);
+    if !config.number_parts || parts.len() < 2 {
+        return parts;
+    }
+    let total = parts.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, part)| format!("{part}\n\n({}/{total})", i + 1))
+        .collect()
+}
+
+/// Cut markdown into parts of at most `max_chars` characters.
+pub fn split_markdown(text: &str, max_chars: usize) -> Vec<String> {
+    let pieces: Vec<String> = blocks(text)
+        .iter()
+        .flat_map(|block| split_block(block, max_chars))
+        .collect();
+    pack(pieces, "\n\n", max_chars)
+}
+
+fn char_len(s: &str) -> usize {
+    s.chars().count()
+}
+
+/// Fence that opens or closes a code block on this line.
+fn fence(line: &str) -> Option<&'static str> {
+    let line = line.trim_start();
+    if line.starts_with("```") {
+        Some("```")
+    } else if line.starts_with("~~~") {
+        Some("~~~")
+    } else {
+        None
+    }
+}
+
+/// Paragraphs separated by blank lines; a fenced code block is one block
+/// even when it contains blank lines.
+fn blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut open_fence: Option<&str> = None;
+    for line in text.lines() {
+        match (open_fence, fence(line)) {
+            (None, Some(f)) => open_fence = Some(f),
+            (Some(open), Some(f)) if open == f => open_fence = None,
+            (None, None) if line.trim().is_empty() => {
+                if !current.is_empty() {
+                    blocks.push(current.join("\n"));
+                    current.clear();
+                }
+                continue;
+            }
+            _ => {}
+        }
+        current.push(line);
+    }
+    if !current.is_empty() {
+        blocks.push(current.join("\n"));
+    }
+    blocks
+}
+
+/// Pieces of one block that each fit `max_chars`.
+fn split_block(block: &str, max_chars: usize) -> Vec<String> {
+    if char_len(block) <= max_chars {
+        return vec![block.to_string()];
+    }
+    let mut lines: Vec<&str> = block.lines().collect();
+    let Some(marker) = lines.first().and_then(|l| fence(l)) else {
+        let pieces = lines
+            .iter()
+            .flat_map(|l| split_line(l, max_chars))
+            .collect();
+        return pack(pieces, "\n", max_chars);
+    };
+
+    // Code block: split its body and wrap every piece in the fence.
+    let open = lines.remove(0);
+    if lines.last().is_some_and(|l| fence(l) == Some(marker)) {
+        lines.pop();
+    }
+    let budget = max_chars
+        .saturating_sub(char_len(open) + marker.len() + 2)
+        .max(1);
+    let body = lines.iter().flat_map(|l| split_line(l, budget)).collect();
+    pack(body, "\n", budget)
+        .into_iter()
+        .map(|piece| format!("{open}\n{piece}\n{marker}"))
+        .collect()
+}
+
+/// Cut one line at sentence ends or spaces, else anywhere.
+fn split_line(line: &str, max_chars: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest = line;
+    while char_len(rest) > max_chars {
+        let hard = rest
+            .char_indices()
+            .nth(max_chars)
+            .map_or(rest.len(), |(i, _)| i);
+        let window = &rest[..hard];
+        let cut = window
+            .rfind(". ")
+            .map(|i| i + 2)
+            .or_else(|| window.rfind(' ').map(|i| i + 1))
+            .filter(|&i| i > hard / 2)
+            .unwrap_or(hard);
+        pieces.push(rest[..cut].trim_end().to_string());
+        rest = &rest[cut..];
+    }
+    pieces.push(rest.to_string());
+    pieces
+}
+
+/// Join pieces with `sep` into as few parts of at most `max_chars` as
+/// possible, keeping their order.
+fn pack(pieces: Vec<String>, sep: &str, max_chars: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for piece in pieces {
+        if !current.is_empty() && char_len(&current) + sep.len() + char_len(&piece) > max_chars {
+            parts.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push_str(sep);
+        }
+        current.push_str(&piece);
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_chars: usize) -> NostrSplitConfig {
+        NostrSplitConfig {
+            max_chars,
+            ..NostrSplitConfig::default()
+        }
+    }
+
+    #[test]
+    fn short_or_disabled_is_one_part() {
+        let text = "word ".repeat(100);
+        assert_eq!(split_reply("hello", &config(200)), vec!["hello"]);
+        let off = NostrSplitConfig {
+            enabled: false,
+            ..config(200)
+        };
+        assert_eq!(split_reply(&text, &off), vec![text.clone()]);
+    }
+
+    #[test]
+    fn splits_at_paragraphs_and_numbers_parts() {
+        let para = |c: char| format!("{}.", c.to_string().repeat(120));
+        let text = format!("{}\n\n{}\n\n{}", para('a'), para('b'), para('c'));
+        let parts = split_reply(&text, &config(250));
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0], format!("{}\n\n(1/3)", para('a')));
+        assert!(parts[2].ends_with("(3/3)"));
+        assert!(parts.iter().all(|p| char_len(p) <= 250));
+    }
+
+    #[test]
+    fn long_lines_cut_at_sentences_then_words() {
+        let text = "First sentence is here. ".repeat(20);
+        for part in split_markdown(text.trim_end(), 100) {
+            assert!(char_len(&part) <= 100);
+            assert!(part.ends_with('.'), "{part:?}");
+        }
+        let words = split_line(&"abc ".repeat(30), 50);
+        assert!(words
+            .iter()
+            .all(|w| !w.starts_with(' ') && char_len(w) <= 50));
+        assert_eq!(split_line(&"x".repeat(120), 50).len(), 3);
+    }
+
+    #[test]
+    fn code_blocks_stay_fenced() {
+        let code: Vec<String> = (0..40).map(|i| format!("let x{i} = {i};")).collect();
+        let text = format!("Intro.\n\n```rust\n{}\n\n```\n\nDone.", code.join("\n"));
+        let parts = split_markdown(&text, 200);
+        assert!(parts.len() > 2);
+        assert!(parts[0].starts_with("Intro.\n\n```rust\nlet x0 = 0;"));
+        for part in &parts[..parts.len() - 1] {
+            assert!(part.ends_with("```"), "{part:?}");
+            assert!(char_len(part) <= 200);
+        }
+        assert!(parts.last().unwrap().ends_with("Done."));
+        assert_eq!(
+            parts
+                .iter()
+                .map(|p| p.matches("```rust").count())
+                .sum::<usize>(),
+            parts.len() - 1
+        );
+    }
+}
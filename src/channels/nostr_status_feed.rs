@@ -0,0 +1,131 @@
+//! Public agent status feed (`[channels_config.nostr.status_feed]`).
+//!
+//! The channel periodically publishes an [`AgentStatus`] as a replaceable
+//! kind 30078 event with d-tag `snow:status`, so a public status page can
+//! show agent health from relays without access to the daemon's API. The
+//! event format lives in snow-memory and is shared with snow-ui, which
+//! parses it in the browser.
+//!
+//! Only counts and component states are published: no message content,
+//! group ids, pubkeys or error text.
+
+use crate::health::HealthSnapshot;
+use crate::memory::message_index;
+use nostr_sdk::prelude::*;
+use rusqlite::Connection;
+use snow_memory::event::{status_to_event_at, KIND_APP_SPECIFIC};
+use snow_memory::AgentStatus;
+
+/// Messages counted as recent in [`AgentStatus::messages_24h`].
+const RECENT_WINDOW_SECS: i64 = 24 * 3600;
+
+/// Collect the public status from the daemon health registry, the channel's
+/// group and relay counts and the message index in `social.db`.
+pub fn collect_status(
+    health: &HealthSnapshot,
+    groups_served: usize,
+    relays: (usize, usize),
+    social: Option<&Connection>,
+    now: i64,
+) -> AgentStatus {
+    let (messages_indexed, messages_24h) = social
+        .map(|conn| {
+            (
+                message_index::count_messages(conn).unwrap_or(0),
+                message_index::count_messages_since(conn, now - RECENT_WINDOW_SECS).unwrap_or(0),
+            )
+        })
+        .unwrap_or_default();
+
+    AgentStatus {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        uptime_seconds: health.uptime_seconds,
+        groups_served: groups_served as u64,
+        messages_indexed: messages_indexed as u64,
+        messages_24h: messages_24h as u64,
+        relays_connected: relays.0 as u64,
+        relays_total: relays.1 as u64,
+        components: health
+            .components
+            .iter()
+            .map(|(name, component)| (name.clone(), component.status.clone()))
+            .collect(),
+    }
+}
+
+/// Unsigned replaceable event carrying `status`.
+pub fn status_builder(status: &AgentStatus) -> EventBuilder {
+    let event = status_to_event_at(status, "", 0);
+    let tags = event
+        .tags
+        .into_iter()
+        .map(|(key, value)| Tag::custom(TagKind::custom(key), vec![value]));
+    EventBuilder::new(Kind::Custom(KIND_APP_SPECIFIC as u16), event.content).tags(tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health::ComponentHealth;
+    use crate::memory::message_index::IndexableMessage;
+
+    fn component(status: &str, last_error: Option<&str>) -> ComponentHealth {
+        ComponentHealth {
+            status: status.into(),
+            updated_at: String::new(),
+            last_ok: None,
+            last_error: last_error.map(String::from),
+            restart_count: 0,
+            detail: None,
+        }
+    }
+
+    #[test]
+    fn collects_counts_without_private_details() {
+        let conn = Connection::open_in_memory().unwrap();
+        message_index::create_message_tables(&conn).unwrap();
+        for (id, at) in [("old", 1_000), ("new", 100_000)] {
+            message_index::index_message(
+                &conn,
+                &IndexableMessage {
+                    event_id: id.into(),
+                    sender_hex: "aa".into(),
+                    group_id: Some("secret-group".into()),
+                    content: "private message content".into(),
+                    created_at: at,
+                    kind: 9,
+                },
+            )
+            .unwrap();
+        }
+        let health = HealthSnapshot {
+            pid: 1,
+            updated_at: String::new(),
+            uptime_seconds: 90,
+            components: [
+                ("channels".to_string(), component("ok", None)),
+                (
+                    "memory_review".to_string(),
+                    component("error", Some("token sk-123 rejected")),
+                ),
+            ]
+            .into(),
+        };
+
+        let status = collect_status(&health, 2, (1, 3), Some(&conn), 100_000);
+        assert_eq!(status.uptime_seconds, 90);
+        assert_eq!(status.groups_served, 2);
+        assert_eq!((status.messages_indexed, status.messages_24h), (2, 1));
+        assert_eq!((status.relays_connected, status.relays_total), (1, 3));
+        assert_eq!(status.components["memory_review"], "error");
+        assert!(!status.healthy());
+
+        let json = serde_json::to_string(&status).unwrap();
+        for private in ["secret-group", "private message", "sk-123", "aa\""] {
+            assert!(!json.contains(private), "{private} leaked into {json}");
+        }
+
+        let empty = collect_status(&health, 0, (0, 0), None, 100_000);
+        assert_eq!(empty.messages_indexed, 0);
+    }
+}
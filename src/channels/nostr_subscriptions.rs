@@ -0,0 +1,311 @@
+//! Subscription auto-tuning (`[channels_config.nostr.subscriptions]`).
+//!
+//! The channel subscribes to a fixed set of named filters (group messages,
+//! DMs, task status, agent state, …) whether or not anything ever arrives on
+//! them. With `auto_tune` enabled, [`SubscriptionTuner`] counts events per
+//! kind and, once a filter has been observed for `window_hours`:
+//!
+//! - drops it when it received fewer than `min_hits` events;
+//! - narrows a multi-kind filter to the kinds actually seen (`narrow`).
+//!
+//! Tuned filters come back at their full width on demand (the agent starts
+//! using the kinds, e.g. it publishes a task and wants status updates) and
+//! on a periodic reprobe every `reprobe_hours`. Filters named in `protected`
+//! are never tuned.
+//!
+//! The tuner only decides; the channel applies each [`Change`] by
+//! unsubscribing and resubscribing on the relay pool.
+
+use crate::config::NostrSubscriptionsConfig;
+use nostr_sdk::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// Current width of a tracked filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterState {
+    /// Subscribed with every kind.
+    Active,
+    /// Subscribed with only these kinds.
+    Narrowed(Vec<u16>),
+    /// Not subscribed.
+    Dropped,
+}
+
+/// Subscription change for the channel to apply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Resubscribe `name` with only `kinds`.
+    Narrow { name: &'static str, kinds: Vec<u16> },
+    /// Unsubscribe `name`.
+    Drop { name: &'static str },
+    /// Resubscribe `name` with every kind.
+    Restore { name: &'static str },
+}
+
+#[derive(Debug)]
+struct TrackedFilter {
+    name: &'static str,
+    kinds: Vec<u16>,
+    protected: bool,
+    state: FilterState,
+    /// Events per kind since `since`.
+    hits: HashMap<u16, u64>,
+    /// Start of the observation window, or when the filter was tuned.
+    since: u64,
+}
+
+impl TrackedFilter {
+    fn subscribed_kinds(&self) -> &[u16] {
+        match &self.state {
+            FilterState::Active => &self.kinds,
+            FilterState::Narrowed(kinds) => kinds,
+            FilterState::Dropped => &[],
+        }
+    }
+
+    fn reset(&mut self, state: FilterState, now: u64) {
+        self.state = state;
+        self.hits.clear();
+        self.since = now;
+    }
+}
+
+/// Per-filter hit counts and the narrow/drop/restore decisions.
+#[derive(Debug)]
+pub struct SubscriptionTuner {
+    config: NostrSubscriptionsConfig,
+    filters: Vec<TrackedFilter>,
+}
+
+impl SubscriptionTuner {
+    pub fn new(config: NostrSubscriptionsConfig) -> Self {
+        Self {
+            config,
+            filters: Vec::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.auto_tune
+    }
+
+    /// Start tracking `name` with `kinds`, replacing an earlier registration
+    /// of the same name.
+    pub fn track(&mut self, name: &'static str, kinds: Vec<u16>, now: u64) {
+        self.filters.retain(|f| f.name != name);
+        self.filters.push(TrackedFilter {
+            name,
+            protected: kinds.is_empty() || self.config.protected.iter().any(|p| p == name),
+            kinds,
+            state: FilterState::Active,
+            hits: HashMap::new(),
+            since: now,
+        });
+    }
+
+    pub fn state(&self, name: &str) -> Option<&FilterState> {
+        self.filters
+            .iter()
+            .find(|f| f.name == name)
+            .map(|f| &f.state)
+    }
+
+    /// Count an event of `kind` against the filter subscribed to it.
+    pub fn record(&mut self, kind: u16) {
+        if let Some(filter) = self
+            .filters
+            .iter_mut()
+            .find(|f| f.subscribed_kinds().contains(&kind))
+        {
+            *filter.hits.entry(kind).or_default() += 1;
+        }
+    }
+
+    /// The agent is about to use `kind`: restore its filter if tuning
+    /// removed the kind.
+    pub fn demand(&mut self, kind: u16, now: u64) -> Option<Change> {
+        if !self.config.auto_tune {
+            return None;
+        }
+        let filter = self
+            .filters
+            .iter_mut()
+            .find(|f| f.kinds.contains(&kind) && !f.subscribed_kinds().contains(&kind))?;
+        filter.reset(FilterState::Active, now);
+        Some(Change::Restore { name: filter.name })
+    }
+
+    /// Changes due at `now`: reprobes of filters tuned `reprobe_hours` ago,
+    /// then drops and narrows of filters whose window ended.
+    pub fn evaluate(&mut self, now: u64) -> Vec<Change> {
+        if !self.config.auto_tune {
+            return Vec::new();
+        }
+        let window = self.config.window_hours.max(1).saturating_mul(3600);
+        let reprobe = self.config.reprobe_hours.saturating_mul(3600);
+        let mut changes = Vec::new();
+
+        for filter in self.filters.iter_mut().filter(|f| !f.protected) {
+            let elapsed = now.saturating_sub(filter.since);
+            if filter.state != FilterState::Active {
+                if reprobe > 0 && elapsed >= reprobe {
+                    filter.reset(FilterState::Active, now);
+                    changes.push(Change::Restore { name: filter.name });
+                }
+                continue;
+            }
+            if elapsed < window {
+                continue;
+            }
+
+            let total: u64 = filter.hits.values().sum();
+            let mut seen: Vec<u16> = filter
+                .kinds
+                .iter()
+                .copied()
+                .filter(|kind| filter.hits.contains_key(kind))
+                .collect();
+            seen.sort_unstable();
+            if total < self.config.min_hits.max(1) {
+                filter.reset(FilterState::Dropped, now);
+                changes.push(Change::Drop { name: filter.name });
+            } else if self.config.narrow && seen.len() < filter.kinds.len() {
+                filter.reset(FilterState::Narrowed(seen.clone()), now);
+                changes.push(Change::Narrow {
+                    name: filter.name,
+                    kinds: seen,
+                });
+            } else {
+                filter.reset(FilterState::Active, now);
+            }
+        }
+        changes
+    }
+
+    /// One-line summary of the tuned filters, e.g.
+    /// `tasks narrowed to 1630, agent_state dropped`.
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        for filter in &self.filters {
+            let state = match &filter.state {
+                FilterState::Active => continue,
+                FilterState::Dropped => "dropped".to_string(),
+                FilterState::Narrowed(kinds) => {
+                    let kinds: Vec<String> = kinds.iter().map(u16::to_string).collect();
+                    format!("narrowed to {}", kinds.join("/"))
+                }
+            };
+            if !summary.is_empty() {
+                summary.push_str(", ");
+            }
+            let _ = write!(summary, "{} {state}", filter.name);
+        }
+        summary
+    }
+}
+
+/// Kinds a filter subscribes to.
+pub fn filter_kinds(filter: &Filter) -> Vec<u16> {
+    filter
+        .kinds
+        .iter()
+        .flatten()
+        .map(|kind| kind.as_u16())
+        .collect()
+}
+
+/// `filter` restricted to `kinds`, starting now so the resubscription does
+/// not replay events already handled.
+pub fn tuned_filter(filter: &Filter, kinds: Option<&[u16]>) -> Filter {
+    let mut tuned = filter.clone();
+    if let Some(kinds) = kinds {
+        tuned.kinds = Some(kinds.iter().map(|&kind| Kind::from(kind)).collect());
+    }
+    if tuned.since.is_some() {
+        tuned.since = Some(Timestamp::now());
+    }
+    tuned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3600;
+
+    fn tuner() -> SubscriptionTuner {
+        let mut tuner = SubscriptionTuner::new(NostrSubscriptionsConfig {
+            auto_tune: true,
+            ..NostrSubscriptionsConfig::default()
+        });
+        tuner.track("groups", vec![9, 11, 12], 0);
+        tuner.track("tasks", (1630..=1637).collect(), 0);
+        tuner.track("agent_state", vec![31121], 0);
+        tuner
+    }
+
+    #[test]
+    fn drops_and_narrows_inactive_filters_after_the_window() {
+        let mut tuner = tuner();
+        tuner.record(1630);
+        tuner.record(1631);
+        assert!(tuner.evaluate(23 * HOUR).is_empty());
+
+        let changes = tuner.evaluate(24 * HOUR);
+        assert_eq!(
+            changes,
+            vec![
+                Change::Narrow {
+                    name: "tasks",
+                    kinds: vec![1630, 1631]
+                },
+                Change::Drop {
+                    name: "agent_state"
+                },
+            ]
+        );
+        // Protected by default, even without a single event
+        assert_eq!(tuner.state("groups"), Some(&FilterState::Active));
+        assert_eq!(
+            tuner.summary(),
+            "tasks narrowed to 1630/1631, agent_state dropped"
+        );
+    }
+
+    #[test]
+    fn restores_on_demand_and_on_reprobe() {
+        let mut tuner = tuner();
+        tuner.evaluate(24 * HOUR);
+        assert_eq!(tuner.state("tasks"), Some(&FilterState::Dropped));
+
+        assert_eq!(
+            tuner.demand(1632, 25 * HOUR),
+            Some(Change::Restore { name: "tasks" })
+        );
+        assert_eq!(tuner.demand(1632, 25 * HOUR), None);
+        assert_eq!(tuner.demand(9, 25 * HOUR), None);
+
+        // The restored filter gets a fresh window before it can drop again
+        let reprobe = 24 * HOUR + tuner.config.reprobe_hours * HOUR;
+        assert_eq!(
+            tuner.evaluate(reprobe),
+            vec![
+                Change::Drop { name: "tasks" },
+                Change::Restore {
+                    name: "agent_state"
+                },
+            ]
+        );
+        assert_eq!(tuner.state("agent_state"), Some(&FilterState::Active));
+    }
+
+    #[test]
+    fn disabled_tuner_changes_nothing() {
+        let mut tuner = SubscriptionTuner::new(NostrSubscriptionsConfig::default());
+        tuner.track("tasks", vec![1630], 0);
+        assert!(tuner.evaluate(1000 * HOUR).is_empty());
+        assert_eq!(tuner.demand(1630, 1000 * HOUR), None);
+        assert_eq!(tuner.state("tasks"), Some(&FilterState::Active));
+    }
+}
@@ -0,0 +1,188 @@
+//! Per-room personas for Nostr conversations.
+//!
+//! A persona bundles a system prompt addition, a model override, a memory
+//! namespace and a tool allowlist. `[personas.groups]` and
+//! `[personas.pubkeys]` map NIP-29 groups and DM peers to personas, so one
+//! daemon can act as a different agent per room while sharing the relay
+//! connection and cost tracking.
+
+use super::traits::ChannelMessage;
+use crate::config::{PersonaConfig, PersonasConfig};
+use nostr_sdk::prelude::PublicKey;
+
+/// A persona selected for an incoming message.
+#[derive(Debug, Clone)]
+pub struct ResolvedPersona {
+    pub name: String,
+    pub config: PersonaConfig,
+}
+
+impl ResolvedPersona {
+    /// Memory session key for this persona, namespaced by its prefix.
+    pub fn memory_session(&self, history_key: &str) -> String {
+        match self.config.memory_prefix.as_deref() {
+            Some(prefix) => format!("{prefix}{history_key}"),
+            None => format!("{}:{history_key}", self.name),
+        }
+    }
+
+    /// Append the persona instructions to a base system prompt.
+    pub fn apply_system_prompt(&self, base: &str) -> String {
+        match self
+            .config
+            .system_prompt
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+        {
+            Some(prompt) => format!("{base}\n\n## Persona: {}\n\n{prompt}\n", self.name),
+            None => base.to_string(),
+        }
+    }
+
+    /// Extend `excluded` with every tool outside this persona's allowlist.
+    pub fn restrict_tools<'a>(
+        &self,
+        tool_names: impl IntoIterator<Item = &'a str>,
+        excluded: &mut Vec<String>,
+    ) {
+        if self.config.allowed_tools.is_empty() {
+            return;
+        }
+        for name in tool_names {
+            let allowed = self.config.allowed_tools.iter().any(|t| t == name);
+            if !allowed && !excluded.iter().any(|t| t == name) {
+                excluded.push(name.to_string());
+            }
+        }
+    }
+}
+
+/// Resolve the persona for a channel message, if one is mapped.
+///
+/// Group messages (`reply_target` = `#<group>`) are looked up in
+/// `personas.groups`; DMs (`reply_target` = sender hex pubkey) in
+/// `personas.pubkeys`. Only Nostr channels take part.
+pub fn resolve(config: &PersonasConfig, msg: &ChannelMessage) -> Option<ResolvedPersona> {
+    if !msg.channel.starts_with("nostr") || config.profiles.is_empty() {
+        return None;
+    }
+
+    let name = match msg.reply_target.strip_prefix('#') {
+        Some(group) => config
+            .groups
+            .get(group)
+            .or_else(|| config.groups.get(&msg.reply_target)),
+        None => lookup_pubkey(config, &msg.reply_target),
+    }?;
+
+    match config.profiles.get(name) {
+        Some(persona) => Some(ResolvedPersona {
+            name: name.clone(),
+            config: persona.clone(),
+        }),
+        None => {
+            tracing::warn!("Persona `{name}` is mapped but not defined in personas.profiles");
+            None
+        }
+    }
+}
+
+fn lookup_pubkey<'a>(config: &'a PersonasConfig, target: &str) -> Option<&'a String> {
+    let target = PublicKey::parse(target).ok()?;
+    config
+        .pubkeys
+        .iter()
+        .find(|(key, _)| PublicKey::parse(key).is_ok_and(|pk| pk == target))
+        .map(|(_, name)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nostr_sdk::prelude::{Keys, ToBech32};
+
+    fn message(channel: &str, reply_target: &str) -> ChannelMessage {
+        ChannelMessage {
+            id: "1".into(),
+            sender: "alice".into(),
+            reply_target: reply_target.into(),
+            content: "hi".into(),
+            channel: channel.into(),
+            timestamp: 0,
+            thread_ts: None,
+        }
+    }
+
+    fn config_with(name: &str, persona: PersonaConfig) -> PersonasConfig {
+        let mut config = PersonasConfig::default();
+        config.profiles.insert(name.into(), persona);
+        config
+    }
+
+    #[test]
+    fn resolves_group_mapping() {
+        let mut config = config_with("reviewer", PersonaConfig::default());
+        config.groups.insert("dev".into(), "reviewer".into());
+
+        let persona = resolve(&config, &message("nostr", "#dev")).unwrap();
+        assert_eq!(persona.name, "reviewer");
+        assert!(resolve(&config, &message("nostr", "#other")).is_none());
+        assert!(resolve(&config, &message("telegram", "#dev")).is_none());
+    }
+
+    #[test]
+    fn resolves_npub_mapping_for_hex_dm_target() {
+        let keys = Keys::generate();
+        let mut config = config_with("tutor", PersonaConfig::default());
+        config
+            .pubkeys
+            .insert(keys.public_key().to_bech32().unwrap(), "tutor".into());
+
+        let hex = keys.public_key().to_hex();
+        let persona = resolve(&config, &message("nostr", &hex)).unwrap();
+        assert_eq!(persona.name, "tutor");
+    }
+
+    #[test]
+    fn memory_session_uses_prefix_or_name() {
+        let mut persona = ResolvedPersona {
+            name: "tutor".into(),
+            config: PersonaConfig::default(),
+        };
+        assert_eq!(persona.memory_session("nostr_bob"), "tutor:nostr_bob");
+        persona.config.memory_prefix = Some("kids/".into());
+        assert_eq!(persona.memory_session("nostr_bob"), "kids/nostr_bob");
+    }
+
+    #[test]
+    fn restrict_tools_excludes_everything_outside_allowlist() {
+        let persona = ResolvedPersona {
+            name: "tutor".into(),
+            config: PersonaConfig {
+                allowed_tools: vec!["memory_recall".into()],
+                ..PersonaConfig::default()
+            },
+        };
+        let mut excluded = vec!["shell".to_string()];
+        persona.restrict_tools(["shell", "file_write", "memory_recall"], &mut excluded);
+        assert_eq!(
+            excluded,
+            vec!["shell".to_string(), "file_write".to_string()]
+        );
+    }
+
+    #[test]
+    fn apply_system_prompt_appends_section() {
+        let persona = ResolvedPersona {
+            name: "tutor".into(),
+            config: PersonaConfig {
+                system_prompt: Some("Be patient.".into()),
+                ..PersonaConfig::default()
+            },
+        };
+        let prompt = persona.apply_system_prompt("base");
+        assert!(prompt.starts_with("base"));
+        assert!(prompt.contains("## Persona: tutor\n\nBe patient."));
+    }
+}
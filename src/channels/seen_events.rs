@@ -3,6 +3,10 @@
 //! Tracks which Nostr event IDs have already been processed, so restarts
 //! don't reprocess DMs from the subscription lookback window. Also stores
 //! per-sender DM conversation history for context continuity.
+//!
+//! DM history is split into sessions: a new session starts after a period
+//! of inactivity or on request, and only the current session's messages
+//! are kept in the ring buffer. Older messages stay in SQLite.
 
 use anyhow::{Context, Result};
 use lru::LruCache;
@@ -44,6 +48,8 @@ pub struct SeenEventsStore {
     cache: Arc<SyncMutex<LruCache<String, ()>>>,
     dm_history: Arc<RwLock<HashMap<String, VecDeque<DmHistoryMessage>>>>,
     dm_history_size: usize,
+    /// Seconds without DMs after which a contact's session ends (0 = never).
+    session_idle_secs: u64,
 }
 
 impl SeenEventsStore {
@@ -53,7 +59,7 @@ impl SeenEventsStore {
             .with_context(|| format!("Failed to create seen-events dir: {}", dir.display()))?;
 
         let db_path = dir.join("seen_events.db");
-        let conn = Connection::open(&db_path)
+        let conn = crate::security::db_encryption::open(&db_path)
             .with_context(|| format!("Failed to open seen-events DB: {}", db_path.display()))?;
 
         conn.execute_batch(
@@ -81,7 +87,12 @@ impl SeenEventsStore {
                 is_outgoing  INTEGER NOT NULL DEFAULT 0
             );
             CREATE INDEX IF NOT EXISTS idx_dm_history_sender
-                ON dm_history(sender_hex, timestamp);",
+                ON dm_history(sender_hex, timestamp);
+
+            CREATE TABLE IF NOT EXISTS dm_sessions (
+                sender_hex   TEXT PRIMARY KEY,
+                started_at   INTEGER NOT NULL
+            );",
         )?;
 
         let max_size = dm_history_size.unwrap_or(DEFAULT_DM_HISTORY_SIZE);
@@ -93,13 +104,20 @@ impl SeenEventsStore {
             ))),
             dm_history: Arc::new(RwLock::new(HashMap::new())),
             dm_history_size: max_size,
+            session_idle_secs: 0,
         };
 
         Ok(store)
     }
 
+    /// End a contact's DM session after `secs` without messages (0 = never).
+    pub fn with_session_idle(mut self, secs: u64) -> Self {
+        self.session_idle_secs = secs;
+        self
+    }
+
     /// Load recent seen event IDs (last N days) into the LRU cache.
-    /// Also load DM history into memory.
+    /// Also load the current session's DM history into memory.
     pub async fn load_recent(&self) -> Result<()> {
         let cutoff = now_secs() - (STARTUP_LOAD_DAYS * 86400);
 
@@ -115,10 +133,12 @@ impl SeenEventsStore {
                 .collect();
 
             let mut stmt2 = conn.prepare(
-                "SELECT sender_hex, sender_name, content, timestamp, event_id, is_outgoing
-                 FROM dm_history
-                 WHERE timestamp >= ?1
-                 ORDER BY timestamp ASC",
+                "SELECT h.sender_hex, h.sender_name, h.content, h.timestamp, h.event_id,
+                        h.is_outgoing
+                 FROM dm_history h
+                 LEFT JOIN dm_sessions s ON s.sender_hex = h.sender_hex
+                 WHERE h.timestamp >= ?1 AND h.timestamp >= COALESCE(s.started_at, 0)
+                 ORDER BY h.timestamp ASC",
             )?;
             let rows: Vec<DmHistoryMessage> = stmt2
                 .query_map(params![cutoff as i64], |row| {
@@ -201,6 +221,21 @@ impl SeenEventsStore {
         }
     }
 
+    /// The IDs among `event_ids` that were received from `sender`.
+    pub fn seen_from(&self, event_ids: &[String], sender: &str) -> Vec<String> {
+        let conn = self.conn.lock();
+        let Ok(mut stmt) =
+            conn.prepare("SELECT 1 FROM seen_events WHERE event_id = ?1 AND sender = ?2")
+        else {
+            return Vec::new();
+        };
+        event_ids
+            .iter()
+            .filter(|id| stmt.exists(params![id, sender]).unwrap_or(false))
+            .cloned()
+            .collect()
+    }
+
     /// Mark multiple events as seen in a batch (for backfill).
     pub async fn mark_seen_batch(&self, events: &[(String, u16, String)]) {
         {
@@ -265,6 +300,84 @@ impl SeenEventsStore {
         }
     }
 
+    /// Remove deleted DMs from history (memory and SQLite): messages
+    /// received from `from` (hex), or the agent's own replies when `from`
+    /// is `None`. Returns the number of messages removed.
+    pub async fn remove_dm_messages(&self, event_ids: &[String], from: Option<&str>) -> usize {
+        let is_target = |msg: &DmHistoryMessage| {
+            event_ids.contains(&msg.event_id)
+                && match from {
+                    Some(sender_hex) => !msg.is_outgoing && msg.sender_hex == sender_hex,
+                    None => msg.is_outgoing,
+                }
+        };
+
+        let mut removed = 0;
+        {
+            let conn = self.conn.lock();
+            for event_id in event_ids {
+                let result = match from {
+                    Some(sender_hex) => conn.execute(
+                        "DELETE FROM dm_history
+                         WHERE event_id = ?1 AND sender_hex = ?2 AND is_outgoing = 0",
+                        params![event_id, sender_hex],
+                    ),
+                    None => conn.execute(
+                        "DELETE FROM dm_history WHERE event_id = ?1 AND is_outgoing = 1",
+                        params![event_id],
+                    ),
+                };
+                match result {
+                    Ok(n) => removed += n,
+                    Err(e) => tracing::warn!("Failed to delete DM history: {e}"),
+                }
+            }
+        }
+
+        for buf in self.dm_history.write().await.values_mut() {
+            buf.retain(|msg| !is_target(msg));
+        }
+        removed
+    }
+
+    /// Start a new DM session with `sender_hex` at `started_at`: earlier
+    /// messages leave the ring buffer (and stay out of it after a restart).
+    pub async fn start_dm_session(&self, sender_hex: &str, started_at: u64) {
+        {
+            let conn = self.conn.lock();
+            if let Err(e) = conn.execute(
+                "INSERT INTO dm_sessions (sender_hex, started_at) VALUES (?1, ?2)
+                 ON CONFLICT(sender_hex) DO UPDATE SET started_at = excluded.started_at",
+                params![sender_hex, started_at as i64],
+            ) {
+                tracing::warn!("Failed to persist DM session start: {e}");
+            }
+        }
+        self.dm_history.write().await.remove(sender_hex);
+    }
+
+    /// Start a new session if the last DM with `sender_hex` is older than
+    /// the idle timeout at `now`. Returns `true` if a session ended.
+    pub async fn expire_idle_dm_session(&self, sender_hex: &str, now: u64) -> bool {
+        if self.session_idle_secs == 0 {
+            return false;
+        }
+        let last = {
+            let history = self.dm_history.read().await;
+            history
+                .get(sender_hex)
+                .and_then(|buf| buf.back())
+                .map(|msg| msg.timestamp)
+        };
+        match last {
+            Some(last) if now.saturating_sub(last) > self.session_idle_secs => {
+                self.start_dm_session(sender_hex, now).await;
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Format DM conversation history for a given sender as LLM context.
     /// Excludes the current event to avoid duplication.
     pub async fn format_dm_context(&self, sender_hex: &str, exclude_event_id: &str) -> String {
@@ -297,6 +410,48 @@ impl SeenEventsStore {
         ctx
     }
 
+    /// Load the newest `limit` persisted DM messages exchanged with `sender_hex`,
+    /// oldest first. Reads SQLite, so it reaches past the in-memory ring buffer.
+    pub fn dm_conversation(&self, sender_hex: &str, limit: usize) -> Result<Vec<DmHistoryMessage>> {
+        self.dm_conversation_between(sender_hex, 0, u64::MAX, limit)
+    }
+
+    /// Like [`Self::dm_conversation`], limited to messages with a timestamp
+    /// in `since..=until`.
+    pub fn dm_conversation_between(
+        &self,
+        sender_hex: &str,
+        since: u64,
+        until: u64,
+        limit: usize,
+    ) -> Result<Vec<DmHistoryMessage>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT sender_hex, sender_name, content, timestamp, event_id, is_outgoing
+             FROM dm_history
+             WHERE sender_hex = ?1 AND timestamp >= ?2 AND timestamp <= ?3
+             ORDER BY timestamp DESC, id DESC
+             LIMIT ?4",
+        )?;
+        let since = i64::try_from(since).unwrap_or(i64::MAX);
+        let until = i64::try_from(until).unwrap_or(i64::MAX);
+        let mut rows: Vec<DmHistoryMessage> = stmt
+            .query_map(params![sender_hex, since, until, limit as i64], |row| {
+                Ok(DmHistoryMessage {
+                    sender_hex: row.get(0)?,
+                    sender_name: row.get(1)?,
+                    content: row.get(2)?,
+                    timestamp: row.get(3)?,
+                    event_id: row.get(4)?,
+                    is_outgoing: row.get::<_, i32>(5)? != 0,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows.reverse();
+        Ok(rows)
+    }
+
     /// Prune old entries from SQLite (older than N days).
     pub async fn prune(&self, older_than_days: u64) -> Result<()> {
         let cutoff = now_secs() - (older_than_days * 86400);
@@ -331,6 +486,17 @@ mod tests {
         (store, dir)
     }
 
+    fn dm(sender: &str, content: &str, timestamp: u64, event_id: &str) -> DmHistoryMessage {
+        DmHistoryMessage {
+            sender_hex: sender.to_string(),
+            sender_name: sender.to_string(),
+            content: content.to_string(),
+            timestamp,
+            event_id: event_id.to_string(),
+            is_outgoing: false,
+        }
+    }
+
     #[tokio::test]
     async fn mark_and_check_seen() {
         let (store, _dir) = test_store();
@@ -433,6 +599,38 @@ mod tests {
         assert!(!ctx.contains("current msg"));
     }
 
+    #[tokio::test]
+    async fn deleted_dms_leave_history() {
+        let (store, _dir) = test_store();
+        store
+            .push_dm_history(dm("alice", "typo", 1000, "ev1"))
+            .await;
+        store
+            .push_dm_history(dm("alice", "fixed", 1001, "ev2"))
+            .await;
+        let mut reply = dm("alice", "noted", 1002, "ev3");
+        reply.is_outgoing = true;
+        store.push_dm_history(reply).await;
+        store.mark_seen("ev1", 4, "alice").await;
+
+        assert_eq!(
+            store.seen_from(&["ev1".to_string(), "ev2".to_string()], "alice"),
+            vec!["ev1".to_string()]
+        );
+
+        // Only the author's own incoming messages are removed
+        let ids = vec!["ev1".to_string(), "ev3".to_string()];
+        assert_eq!(store.remove_dm_messages(&ids, Some("bob")).await, 0);
+        assert_eq!(store.remove_dm_messages(&ids, Some("alice")).await, 1);
+        assert_eq!(store.remove_dm_messages(&ids, None).await, 1);
+
+        let ctx = store.format_dm_context("alice", "").await;
+        assert!(ctx.contains("fixed"));
+        assert!(!ctx.contains("typo"));
+        assert!(!ctx.contains("noted"));
+        assert_eq!(store.dm_conversation("alice", 10).unwrap().len(), 1);
+    }
+
     #[tokio::test]
     async fn empty_context_returns_empty_string() {
         let (store, _dir) = test_store();
@@ -471,6 +669,84 @@ mod tests {
         assert_eq!(history.get("sender1").unwrap().len(), 1);
     }
 
+    #[tokio::test]
+    async fn idle_dm_session_expires_and_stays_expired_after_reload() {
+        let dir = TempDir::new().unwrap();
+        let store = SeenEventsStore::new(dir.path(), Some(10))
+            .unwrap()
+            .with_session_idle(3600);
+        let now = now_secs();
+        store
+            .push_dm_history(dm("alice", "old topic", now - 7200, "e1"))
+            .await;
+
+        // Within the timeout the session continues.
+        assert!(!store.expire_idle_dm_session("alice", now - 5000).await);
+        assert!(store.expire_idle_dm_session("alice", now).await);
+        store
+            .push_dm_history(dm("alice", "fresh start", now, "e2"))
+            .await;
+
+        let ctx = store.format_dm_context("alice", "").await;
+        assert!(ctx.contains("fresh start"));
+        assert!(!ctx.contains("old topic"));
+
+        let reopened = SeenEventsStore::new(dir.path(), Some(10)).unwrap();
+        reopened.load_recent().await.unwrap();
+        let ctx = reopened.format_dm_context("alice", "").await;
+        assert!(ctx.contains("fresh start"));
+        assert!(!ctx.contains("old topic"));
+        // History itself is kept.
+        assert_eq!(reopened.dm_conversation("alice", 10).unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn new_session_clears_context() {
+        let (store, _dir) = test_store();
+        let now = now_secs();
+        store
+            .push_dm_history(dm("bob", "hello", now - 10, "e1"))
+            .await;
+        store.start_dm_session("bob", now).await;
+        assert!(store.format_dm_context("bob", "").await.is_empty());
+        // Sessions never expire by default.
+        assert!(!store.expire_idle_dm_session("bob", now + 86_400).await);
+    }
+
+    #[tokio::test]
+    async fn dm_conversation_reads_past_ring_buffer() {
+        let (store, _dir) = test_store();
+
+        for i in 0..8 {
+            store
+                .push_dm_history(DmHistoryMessage {
+                    sender_hex: "sender1".to_string(),
+                    sender_name: "Alice".to_string(),
+                    content: format!("msg {i}"),
+                    timestamp: 1000 + i,
+                    event_id: format!("ev{i}"),
+                    is_outgoing: i % 2 == 1,
+                })
+                .await;
+        }
+
+        let all = store.dm_conversation("sender1", 100).unwrap();
+        assert_eq!(all.len(), 8);
+        assert_eq!(all.first().unwrap().content, "msg 0");
+        assert!(all[1].is_outgoing);
+
+        let newest = store.dm_conversation("sender1", 3).unwrap();
+        let contents: Vec<&str> = newest.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["msg 5", "msg 6", "msg 7"]);
+        assert!(store.dm_conversation("other", 10).unwrap().is_empty());
+
+        let ranged = store
+            .dm_conversation_between("sender1", 1002, 1004, 100)
+            .unwrap();
+        let contents: Vec<&str> = ranged.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["msg 2", "msg 3", "msg 4"]);
+    }
+
     #[tokio::test]
     async fn prune_removes_old_entries() {
         let (store, _dir) = test_store();
@@ -686,15 +686,8 @@ impl SlackChannel {
         truncated.parse::<u64>().ok()
     }
 
-    fn jitter_ms_from_clock(max_jitter_ms: u64) -> u64 {
-        if max_jitter_ms == 0 {
-            return 0;
-        }
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| u64::from(d.subsec_nanos()))
-            .unwrap_or(0);
-        nanos % (max_jitter_ms + 1)
+    fn retry_jitter_ms(max_jitter_ms: u64) -> u64 {
+        crate::determinism::jitter_ms("slack_retry", max_jitter_ms)
     }
 
     fn compute_retry_delay(base_retry_after_secs: u64, attempt: u32, jitter_ms: u64) -> Duration {
@@ -763,7 +756,7 @@ impl SlackChannel {
 
                 let retry_after_secs = Self::parse_retry_after_secs(&headers)
                     .unwrap_or(SLACK_HISTORY_DEFAULT_RETRY_AFTER_SECS);
-                let jitter_ms = Self::jitter_ms_from_clock(SLACK_HISTORY_MAX_JITTER_MS);
+                let jitter_ms = Self::retry_jitter_ms(SLACK_HISTORY_MAX_JITTER_MS);
                 let wait = Self::compute_retry_delay(retry_after_secs, attempt, jitter_ms);
                 total_wait += wait;
                 let next_retry_at = Self::next_retry_timestamp(wait);
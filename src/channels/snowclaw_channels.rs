@@ -4,34 +4,55 @@
 //! config builder (groups, DMs, respond modes, mentions, etc.) lives here.
 
 use crate::channels::nostr::{NostrChannel, NostrChannelConfig, RespondMode};
+use crate::channels::nostr_roles::Roles;
 use crate::config::Config;
-use std::sync::Arc;
+use crate::memory::Memory;
+use anyhow::Context as _;
+use nostr_core::{load_or_create_client_keys, BunkerUrl, RelayProxy, RemoteSigner, Signer};
+use nostr_sdk::prelude::{Client, IntoNostrSigner};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
 use super::ConfiguredChannel;
 
+/// Client key for NIP-46 sessions, next to `config.toml`.
+const NIP46_CLIENT_KEY_FILE: &str = "nip46_client.key";
+
+/// The channel's signer, for clients without an nsec of their own.
+static SHARED_SIGNER: OnceLock<Signer> = OnceLock::new();
+
 /// Build and append the Nostr channel from Snowclaw config, returning an
 /// error reason string if initialization fails (or `None` on success).
+///
+/// `memory` is the agent memory backend exposed to remote `memory.*` actions.
 pub(crate) async fn append_nostr_channel(
     config: &Config,
     channels: &mut Vec<ConfiguredChannel>,
     startup_context: &str,
+    memory: Option<Arc<dyn Memory>>,
 ) -> Option<String> {
     let ns = config.channels_config.nostr.as_ref()?;
-    let nsec_str = ns
-        .nsec
-        .clone()
-        .or_else(|| std::env::var("SNOWCLAW_NSEC").ok())?;
-    let keys = match nostr_sdk::Keys::parse(&nsec_str) {
-        Ok(k) => k,
+    let persist_dir = config
+        .config_path
+        .parent()
+        .unwrap_or(std::path::Path::new("."))
+        .to_path_buf();
+    let signer = match nostr_signer(ns, &persist_dir).await {
+        Ok(signer) => signer?,
         Err(e) => {
-            let reason = format!("Nostr key parse failed during {startup_context}: {e}");
+            let reason = format!("Nostr signer setup failed during {startup_context}: {e:#}");
             tracing::warn!("{reason}");
             return Some(reason);
         }
     };
+    share_signer(&signer);
+    let owner = ns
+        .owner
+        .as_ref()
+        .and_then(|s| nostr_sdk::PublicKey::parse(s).ok());
     let channel_config = NostrChannelConfig {
         relays: ns.relays.clone(),
-        keys: keys.clone(),
+        signer,
         groups: ns.groups.clone(),
         listen_dms: ns.listen_dms,
         allowed_pubkeys: ns
@@ -53,22 +74,98 @@ pub(crate) async fn append_nostr_channel(
             }
             names
         },
-        owner: ns
-            .owner
-            .as_ref()
-            .and_then(|s| nostr_sdk::PublicKey::parse(s).ok()),
+        owner,
+        roles: Roles::new(owner, &ns.owners),
         context_history: ns.context_history,
         extra_kinds: ns.extra_kinds.clone(),
-        persist_dir: config
-            .config_path
-            .parent()
-            .unwrap_or(std::path::Path::new("."))
-            .to_path_buf(),
+        persist_dir,
         indexed_paths: config.memory.indexed_paths.clone(),
         index_interval_minutes: config.memory.index_interval_minutes,
+        chunking: crate::memory::chunker::ChunkOptions {
+            max_tokens: config.memory.chunk_max_tokens,
+            overlap_tokens: config.memory.chunk_overlap_tokens,
+        },
+        digest: ns.digest.clone(),
+        banner: ns.banner.clone(),
+        workspace_dir: config.workspace_dir.clone(),
+        key_filter: ns.key_filter.clone(),
+        index_redaction: ns.index_redaction.clone(),
+        onboarding: ns.onboarding.clone(),
+        quota: ns.quota.clone(),
+        answer_cache: ns.answer_cache.clone(),
+        pipeline: ns.pipeline.clone(),
+        proxy: ns.proxy.clone(),
+        relay_trust: ns.relay_trust.clone(),
+        publish: ns.publish.clone(),
+        reconnect: ns.reconnect.clone(),
+        catchup: ns.catchup.clone(),
+        cost: config.cost.clone(),
+        dm_sessions: ns.dm_sessions.clone(),
+        dm_presence: ns.dm_presence.clone(),
+        link_index: ns.link_index.clone(),
+        url_access: config.security.url_access.clone(),
+        reply_moderation: ns.reply_moderation.clone(),
+        audit: config.security.audit.clone(),
+        threading: ns.threading.clone(),
+        split: ns.split.clone(),
+        cost_alerts: ns.cost_alerts.clone(),
+        nip05: ns.nip05.clone(),
+        proactive: ns.proactive.clone(),
+        backfill: ns.backfill.clone(),
+        contact_pruning: ns.contact_pruning.clone(),
+        status_feed: ns.status_feed.clone(),
+        subscriptions: ns.subscriptions.clone(),
+        write_policy: config.memory.write_policy.clone(),
+        service_config: Arc::new(config.clone()),
+        context_template: ns.context_template.as_ref().map(|path| {
+            config
+                .config_path
+                .parent()
+                .unwrap_or(std::path::Path::new("."))
+                .join(path)
+        }),
     };
     match NostrChannel::new(channel_config).await {
-        Ok(channel) => {
+        Ok(mut channel) => {
+            if let Some(memory) = memory {
+                channel.set_memory_backend(memory);
+            }
+            if ns.answer_cache.enabled && ns.answer_cache.semantic {
+                if config.memory.embedding_provider.trim() == "none" {
+                    tracing::warn!(
+                        "Nostr answer_cache.semantic needs [memory] embedding_provider; matching exact repeats only"
+                    );
+                } else {
+                    channel.set_answer_embedder(Arc::from(
+                        crate::memory::embeddings::create_embedding_provider(
+                            config.memory.embedding_provider.trim(),
+                            config.api_key.as_deref(),
+                            config.memory.embedding_model.trim(),
+                            config.memory.embedding_dimensions,
+                        ),
+                    ));
+                }
+            }
+            if ns.reply_moderation.enabled && ns.reply_moderation.self_check {
+                match reply_self_check_provider(config) {
+                    Ok(provider) => {
+                        let provider_name =
+                            config.default_provider.as_deref().unwrap_or("openrouter");
+                        let model = ns.reply_moderation.self_check_model.clone().unwrap_or_else(
+                            || {
+                                crate::config::resolve_default_model_id(
+                                    config.default_model.as_deref(),
+                                    Some(provider_name),
+                                )
+                            },
+                        );
+                        channel.set_reply_self_check(provider, model);
+                    }
+                    Err(e) => tracing::warn!(
+                        "Nostr reply_moderation.self_check unavailable, running static checks only: {e}"
+                    ),
+                }
+            }
             channels.push(ConfiguredChannel {
                 display_name: "Nostr",
                 channel: Arc::new(channel),
@@ -82,3 +179,118 @@ pub(crate) async fn append_nostr_channel(
         }
     }
 }
+
+/// Provider for the reply moderation self-check, built like the agent's own.
+fn reply_self_check_provider(
+    config: &Config,
+) -> anyhow::Result<Arc<dyn crate::providers::Provider>> {
+    let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
+    let provider = crate::providers::create_resilient_provider_with_options(
+        provider_name,
+        config.api_key.as_deref(),
+        config.api_url.as_deref(),
+        &config.reliability,
+        &crate::providers::ProviderRuntimeOptions {
+            auth_profile_override: None,
+            provider_api_url: config.api_url.clone(),
+            provider_transport: config.effective_provider_transport(),
+            zeroclaw_dir: config.config_path.parent().map(std::path::PathBuf::from),
+            secrets_encrypt: config.secrets.encrypt,
+            reasoning_enabled: config.runtime.reasoning_enabled,
+            reasoning_level: config.effective_provider_reasoning_level(),
+            custom_provider_api_mode: config.provider_api.map(|mode| mode.as_compatible_mode()),
+            custom_provider_auth_header: config.effective_custom_provider_auth_header(),
+            max_tokens_override: None,
+            model_support_vision: config.model_support_vision,
+        },
+    )?;
+    Ok(Arc::from(provider))
+}
+
+/// The agent's signer: a NIP-46 session with the bunker from
+/// `[channels_config.nostr.signer]` (or `SNOWCLAW_BUNKER_URL`) when one is
+/// set, else the nsec. `None` when neither is configured.
+pub(crate) async fn nostr_signer(
+    ns: &crate::config::NostrConfig,
+    persist_dir: &std::path::Path,
+) -> anyhow::Result<Option<Signer>> {
+    let bunker_url = ns
+        .signer
+        .bunker_url
+        .clone()
+        .or_else(|| std::env::var("SNOWCLAW_BUNKER_URL").ok());
+    if let Some(url) = bunker_url {
+        let url = BunkerUrl::parse(&url)?;
+        let client_keys = load_or_create_client_keys(&persist_dir.join(NIP46_CLIENT_KEY_FILE))
+            .context("Failed to load the NIP-46 client key")?;
+        let remote = RemoteSigner::connect(
+            &url,
+            client_keys,
+            Duration::from_secs(ns.signer.timeout_secs.max(1)),
+            ns.signer.queue_size,
+            &nostr_relay_proxy(&ns.proxy)?,
+        )
+        .await
+        .context("Failed to connect to the NIP-46 remote signer")?;
+        tracing::info!(
+            "Nostr: signing through remote signer {} as {}",
+            url.signer.to_hex(),
+            remote.public_key().to_hex()
+        );
+        return Ok(Some(Signer::Remote(remote)));
+    }
+    let Some(nsec) = ns
+        .nsec
+        .clone()
+        .or_else(|| std::env::var("SNOWCLAW_NSEC").ok())
+    else {
+        return Ok(None);
+    };
+    let keys = nostr_sdk::Keys::parse(&nsec).context("Nostr key parse failed")?;
+    Ok(Some(Signer::Local(keys)))
+}
+
+/// Make `signer` the fallback of [`signer_or_shared`]. The first one set
+/// stays for the life of the process.
+pub(crate) fn share_signer(signer: &Signer) {
+    let _ = SHARED_SIGNER.set(signer.clone());
+}
+
+/// Signer for a client with an optional nsec of its own (memory backends):
+/// the nsec when set, else the channel's signer once the channel started.
+pub(crate) fn signer_or_shared(nsec: Option<&str>) -> anyhow::Result<Option<Signer>> {
+    match nsec {
+        Some(nsec) => {
+            let keys = nostr_sdk::Keys::parse(nsec).context("Nostr key parse failed")?;
+            Ok(Some(Signer::Local(keys)))
+        }
+        None => Ok(SHARED_SIGNER.get().cloned()),
+    }
+}
+
+/// Whether [`signer_or_shared`] has a signer for `nsec`, without parsing it.
+pub(crate) fn has_signer(nsec: Option<&str>) -> bool {
+    nsec.is_some() || SHARED_SIGNER.get().is_some()
+}
+
+/// Relay routing from `[channels_config.nostr.proxy]`.
+pub(crate) fn nostr_relay_proxy(
+    proxy: &crate::config::NostrProxyConfig,
+) -> anyhow::Result<RelayProxy> {
+    RelayProxy::new(proxy.socks5.as_deref(), proxy.onion_only, &proxy.relays)
+        .context("Invalid [channels_config.nostr.proxy]")
+}
+
+/// Relay routing of the loaded config, for clients built outside the channel.
+pub(crate) fn runtime_relay_proxy() -> anyhow::Result<RelayProxy> {
+    nostr_relay_proxy(&crate::config::runtime_nostr_proxy())
+}
+
+/// A client for `relays` signing with `signer`, routed through the relay
+/// proxy of the loaded config. The caller connects it.
+pub(crate) async fn nostr_client(
+    signer: impl IntoNostrSigner,
+    relays: &[String],
+) -> anyhow::Result<Client> {
+    Ok(runtime_relay_proxy()?.client(signer, relays).await?)
+}
@@ -106,22 +106,9 @@ fn split_message_for_telegram(message: &str) -> Vec<String> {
     chunks
 }
 
-fn pick_uniform_index(len: usize) -> usize {
-    debug_assert!(len > 0);
-    let upper = len as u64;
-    let reject_threshold = (u64::MAX / upper) * upper;
-
-    loop {
-        let value = rand::random::<u64>();
-        if value < reject_threshold {
-            #[allow(clippy::cast_possible_truncation)]
-            return (value % upper) as usize;
-        }
-    }
-}
-
 fn random_telegram_ack_reaction() -> &'static str {
-    TELEGRAM_ACK_REACTIONS[pick_uniform_index(TELEGRAM_ACK_REACTIONS.len())]
+    let index = crate::determinism::pick_index("telegram_ack", TELEGRAM_ACK_REACTIONS.len());
+    TELEGRAM_ACK_REACTIONS[index]
 }
 
 fn build_telegram_ack_reaction_request(
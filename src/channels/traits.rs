@@ -148,6 +148,12 @@ pub trait Channel: Send + Sync {
             .await
     }
 
+    /// Model profile selected for a conversation by the channel's own
+    /// runtime config (e.g. Nostr NIP-78), if any. See `[model_profiles]`.
+    async fn model_profile(&self, _reply_target: &str) -> Option<String> {
+        None
+    }
+
     /// Add a reaction (emoji) to a message.
     ///
     /// `channel_id` is the platform channel/conversation identifier (e.g. Discord channel ID).
@@ -235,6 +241,7 @@ mod tests {
         assert!(channel.health_check().await);
         assert!(channel.start_typing("bob").await.is_ok());
         assert!(channel.stop_typing("bob").await.is_ok());
+        assert!(channel.model_profile("bob").await.is_none());
         assert!(channel
             .send(&SendMessage::new("hello", "bob"))
             .await
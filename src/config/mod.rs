@@ -6,30 +6,43 @@ pub mod traits;
 pub use schema::{
     apply_runtime_proxy_to_builder, build_runtime_proxy_client,
     build_runtime_proxy_client_with_timeouts, default_model_fallback_for_provider,
-    resolve_default_model_id, runtime_proxy_config, set_runtime_proxy_config,
-    AckReactionChannelsConfig, AckReactionChatType, AckReactionConfig, AckReactionRuleAction,
-    AckReactionRuleConfig, AckReactionStrategy, AgentConfig, AgentLoadBalanceStrategy,
-    AgentSessionBackend, AgentSessionConfig, AgentSessionStrategy, AgentTeamsConfig,
-    AgentsIpcConfig, AuditConfig, AutonomyConfig, BrowserComputerUseConfig, BrowserConfig,
-    BuiltinHooksConfig, ChannelsConfig, ClassificationRule, CollectiveMemoryConfig,
+    resolve_default_model_id, runtime_nostr_proxy, runtime_proxy_config, set_runtime_nostr_proxy,
+    set_runtime_proxy_config, AckReactionChannelsConfig, AckReactionChatType, AckReactionConfig,
+    AckReactionRuleAction, AckReactionRuleConfig, AckReactionStrategy, AgentConfig,
+    AgentLoadBalanceStrategy, AgentSessionBackend, AgentSessionConfig, AgentSessionStrategy,
+    AgentTeamsConfig, AgentsIpcConfig, AuditConfig, AutonomyConfig, BrowserComputerUseConfig,
+    BrowserConfig, BuiltinHooksConfig, ChannelsConfig, ClassificationRule, CollectiveArchiveConfig,
+    CollectiveConflictsConfig, CollectiveDistillConfig, CollectiveGroupsConfig,
+    CollectiveMemoryConfig, CollectiveQuorumConfig, CollectiveRelayRoutesConfig,
+    CollectiveReputationConfig, CollectiveReviewConfig, CollectiveUsageConfig,
     CommandContextRuleAction, CommandContextRuleConfig, ComposioConfig, Config, ContextVmEntry,
-    CoordinationConfig, CostConfig, CronConfig, DelegateAgentConfig, DiscordConfig,
-    DockerRuntimeConfig, EconomicConfig, EconomicTokenPricing, EmbeddingRouteConfig, EstopConfig,
-    FeishuConfig, GatewayConfig, GroupReplyConfig, GroupReplyMode, HardwareConfig,
-    HardwareTransport, HeartbeatConfig, HooksConfig, HttpRequestConfig,
-    HttpRequestCredentialProfile, IMessageConfig, IdentityConfig, LarkConfig, MatrixConfig,
-    MemoryConfig, ModelRouteConfig, MultimodalConfig, NextcloudTalkConfig,
-    NonCliNaturalLanguageApprovalMode, NostrConfig, ObservabilityConfig, OtpChallengeDelivery,
+    CoordinationConfig, CostConfig, CronConfig, DelegateAgentConfig, DeterminismConfig,
+    DiscordConfig, DockerRuntimeConfig, EconomicConfig, EconomicTokenPricing, EmbeddingRouteConfig,
+    EstopConfig, ExecutionPoliciesConfig, ExecutionPolicyConfig, FeishuConfig, GatewayConfig,
+    GroupReplyConfig, GroupReplyMode, HardwareConfig, HardwareTransport, HeartbeatConfig,
+    HooksConfig, HttpRequestConfig, HttpRequestCredentialProfile, IMessageConfig, IdentityConfig,
+    KeyFilterConfig, KeyFilterPattern, LarkConfig, LatencyBudgetConfig, MatrixConfig, MemoryConfig,
+    MemoryWritePolicyConfig, ModelProfileConfig, ModelProfilesConfig, ModelRouteConfig,
+    ModelRouterConfig, MultimodalConfig, NextcloudTalkConfig, NonCliNaturalLanguageApprovalMode,
+    NostrAnswerCacheConfig, NostrBackfillConfig, NostrBannerConfig, NostrCatchupConfig,
+    NostrConfig, NostrContactPruningConfig, NostrCostAlertConfig, NostrDigestConfig,
+    NostrDmPresenceConfig, NostrDmSessionConfig, NostrIndexRedactionConfig, NostrLinkIndexConfig,
+    NostrNip05Config, NostrOnboardingConfig, NostrOwnerConfig, NostrPipelineConfig,
+    NostrProactiveConfig, NostrProactivePostConfig, NostrProxyConfig, NostrPublishConfig,
+    NostrQuotaConfig, NostrQuotaTag, NostrReconnectConfig, NostrRelayTrust, NostrRelayTrustConfig,
+    NostrReplyModerationConfig, NostrSignerConfig, NostrSplitConfig, NostrStatusFeedConfig,
+    NostrSubscriptionsConfig, NostrThreadingConfig, ObservabilityConfig, OtpChallengeDelivery,
     OtpConfig, OtpMethod, OutboundLeakGuardAction, OutboundLeakGuardConfig, PeripheralBoardConfig,
-    PeripheralsConfig, PerplexityFilterConfig, PluginEntryConfig, PluginsConfig, ProgressMode,
-    ProviderConfig, ProxyConfig, ProxyScope, QdrantConfig, QueryClassificationConfig,
-    ReliabilityConfig, ResearchPhaseConfig, ResearchTrigger, ResourceLimitsConfig, RuntimeConfig,
-    SandboxBackend, SandboxConfig, SchedulerConfig, SecretsConfig, SecurityConfig,
-    SecurityRoleConfig, SkillsConfig, SkillsPromptInjectionMode, SlackConfig, StorageConfig,
-    StorageProviderConfig, StorageProviderSection, StreamMode, SubAgentsConfig,
-    SyscallAnomalyConfig, TelegramConfig, TranscriptionConfig, TunnelConfig, UrlAccessConfig,
+    PeripheralsConfig, PerplexityFilterConfig, PersonaConfig, PersonasConfig, PluginEntryConfig,
+    PluginsConfig, ProgressMode, ProviderConfig, ProxyConfig, ProxyScope, QdrantConfig,
+    QueryClassificationConfig, ReliabilityConfig, ResearchPhaseConfig, ResearchTrigger,
+    ResourceLimitsConfig, RuntimeConfig, SandboxBackend, SandboxConfig, SchedulerConfig,
+    SecretsConfig, SecurityConfig, SecurityRoleConfig, SkillsConfig, SkillsPromptInjectionMode,
+    SlackConfig, StatsConfig, StorageConfig, StorageProviderConfig, StorageProviderSection,
+    StreamMode, SubAgentsConfig, SyscallAnomalyConfig, TelegramConfig, ToolCacheConfig,
+    TranscriptionConfig, TunnelConfig, UnknownModelPricing, UpdateConfig, UrlAccessConfig,
     WasmCapabilityEscalationMode, WasmConfig, WasmModuleHashPolicy, WasmRuntimeConfig,
-    WasmSecurityConfig, WebFetchConfig, WebSearchConfig, WebhookConfig, APP_DIR_NAME,
+    WasmSecurityConfig, WebFetchConfig, WebSearchConfig, WebhookConfig, WeekStart, APP_DIR_NAME,
     DEFAULT_MODEL_FALLBACK,
 };
 
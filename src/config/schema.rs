@@ -6,9 +6,19 @@ use crate::security::{AutonomyLevel, DomainMatcher};
 use anyhow::{Context, Result};
 use directories::UserDirs;
 
-pub use crate::config::snowclaw_schema::CollectiveMemoryConfig;
 pub use crate::config::snowclaw_schema::ContextVmEntry;
 pub use crate::config::snowclaw_schema::APP_DIR_NAME;
+pub use crate::config::snowclaw_schema::{
+    CollectiveArchiveConfig, CollectiveConflictsConfig, CollectiveDistillConfig,
+    CollectiveGroupsConfig, CollectiveMemoryConfig, CollectiveQuorumConfig,
+    CollectiveRelayRoutesConfig, CollectiveReputationConfig, CollectiveReviewConfig,
+    CollectiveUsageConfig,
+};
+pub use crate::config::snowclaw_schema::{
+    DeterminismConfig, ExecutionPoliciesConfig, ExecutionPolicyConfig, LatencyBudgetConfig,
+    ModelProfileConfig, ModelProfilesConfig, ModelRouterConfig, PersonaConfig, PersonasConfig,
+    StatsConfig, UpdateConfig, WeekStart,
+};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -341,6 +351,10 @@ pub struct Config {
     #[serde(default)]
     pub web_search: WebSearchConfig,
 
+    /// Tool result cache configuration (`[tool_cache]`).
+    #[serde(default)]
+    pub tool_cache: ToolCacheConfig,
+
     /// Proxy configuration for outbound HTTP/HTTPS/SOCKS5 traffic (`[proxy]`).
     #[serde(default)]
     pub proxy: ProxyConfig,
@@ -398,6 +412,34 @@ pub struct Config {
     #[serde(default)]
     pub contextvm: Option<ContextVmEntry>,
 
+    /// Per-room personas (`[personas]`).
+    #[serde(default)]
+    pub personas: PersonasConfig,
+
+    /// Tool execution policies for channel conversations (`[execution_policies]`).
+    #[serde(default)]
+    pub execution_policies: ExecutionPoliciesConfig,
+
+    /// Named temperature/max_tokens/reasoning profiles (`[model_profiles]`).
+    #[serde(default)]
+    pub model_profiles: ModelProfilesConfig,
+
+    /// Complexity and budget based model routing (`[model_router]`).
+    #[serde(default)]
+    pub model_router: ModelRouterConfig,
+
+    /// Token usage stats time zone and week layout (`[stats]`).
+    #[serde(default)]
+    pub stats: StatsConfig,
+
+    /// Release signature key for `control.update` (`[update]`).
+    #[serde(default)]
+    pub update: UpdateConfig,
+
+    /// Deterministic seed mode for tests and bug reports (`[determinism]`).
+    #[serde(default)]
+    pub determinism: DeterminismConfig,
+
     /// Vision support override for the active provider/model.
     /// - `None` (default): use provider's built-in default
     /// - `Some(true)`: force vision support on (e.g. Ollama running llava)
@@ -1067,6 +1109,10 @@ pub struct AgentConfig {
     /// Enable parallel tool execution within a single iteration. Default: `false`.
     #[serde(default)]
     pub parallel_tools: bool,
+    /// Most tool calls of one turn the runtime tool loop runs at once. Default: `4`.
+    /// `1` runs them one at a time.
+    #[serde(default = "default_agent_max_parallel_tools")]
+    pub max_parallel_tools: usize,
     /// Tool dispatch strategy (e.g. `"auto"`). Default: `"auto"`.
     #[serde(default = "default_agent_tool_dispatcher")]
     pub tool_dispatcher: String,
@@ -1113,6 +1159,10 @@ pub struct AgentConfig {
     /// set to `0` for explicit disable.
     #[serde(default = "default_safety_heartbeat_turn_interval")]
     pub safety_heartbeat_turn_interval: usize,
+    /// Hours an idle per-conversation artifact directory is kept before
+    /// garbage collection. Set to `0` to disable collection. Default: `24`.
+    #[serde(default = "default_agent_artifact_ttl_hours")]
+    pub artifact_ttl_hours: u64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -1164,6 +1214,10 @@ fn default_agent_max_history_messages() -> usize {
     50
 }
 
+pub(crate) fn default_agent_max_parallel_tools() -> usize {
+    4
+}
+
 fn default_agent_tool_dispatcher() -> String {
     "auto".into()
 }
@@ -1204,6 +1258,10 @@ fn default_safety_heartbeat_turn_interval() -> usize {
     10
 }
 
+fn default_agent_artifact_ttl_hours() -> u64 {
+    24
+}
+
 impl Default for AgentConfig {
     fn default() -> Self {
         Self {
@@ -1212,6 +1270,7 @@ impl Default for AgentConfig {
             max_tool_iterations: default_agent_max_tool_iterations(),
             max_history_messages: default_agent_max_history_messages(),
             parallel_tools: false,
+            max_parallel_tools: default_agent_max_parallel_tools(),
             tool_dispatcher: default_agent_tool_dispatcher(),
             allowed_tools: Vec::new(),
             denied_tools: Vec::new(),
@@ -1222,6 +1281,7 @@ impl Default for AgentConfig {
             loop_detection_failure_streak: default_loop_detection_failure_streak(),
             safety_heartbeat_interval: default_safety_heartbeat_interval(),
             safety_heartbeat_turn_interval: default_safety_heartbeat_turn_interval(),
+            artifact_ttl_hours: default_agent_artifact_ttl_hours(),
         }
     }
 }
@@ -1443,11 +1503,27 @@ pub struct CostConfig {
     #[serde(default)]
     pub prices: std::collections::HashMap<String, ModelPricing>,
 
+    /// Pricing for models found in neither `prices`, `pricing.toml` nor the
+    /// built-in table (default: conservative).
+    #[serde(default)]
+    pub unknown_model_pricing: UnknownModelPricing,
+
     /// Runtime budget enforcement policy (`[cost.enforcement]`).
     #[serde(default)]
     pub enforcement: CostEnforcementConfig,
 }
 
+/// How usage of a model with no known price is costed.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownModelPricing {
+    /// Charge $3 input / $15 output per 1M tokens so budgets stay safe.
+    #[default]
+    Conservative,
+    /// Record usage at $0 and warn once per model.
+    Zero,
+}
+
 /// Budget enforcement behavior when projected spend approaches/exceeds limits.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -1529,6 +1605,7 @@ impl Default for CostConfig {
             warn_at_percent: default_warn_percent(),
             allow_override: false,
             prices: get_default_pricing(),
+            unknown_model_pricing: UnknownModelPricing::default(),
             enforcement: CostEnforcementConfig::default(),
         }
     }
@@ -1903,11 +1980,19 @@ pub struct SecretsConfig {
     /// Enable encryption for API keys and tokens in config.toml
     #[serde(default = "default_true")]
     pub encrypt: bool,
+    /// Encrypt the memory, seen-events and social SQLite databases at rest
+    /// with SQLCipher, keyed from the secret store (needs the `sqlcipher`
+    /// build feature)
+    #[serde(default)]
+    pub encrypt_databases: bool,
 }
 
 impl Default for SecretsConfig {
     fn default() -> Self {
-        Self { encrypt: true }
+        Self {
+            encrypt: true,
+            encrypt_databases: false,
+        }
     }
 }
 
@@ -2406,6 +2491,64 @@ fn default_user_agent() -> String {
     "ZeroClaw/1.0".into()
 }
 
+// ── Tool result cache ───────────────────────────────────────────
+
+/// Tool result cache configuration (`[tool_cache]` section).
+///
+/// Repeated calls with the same arguments to a tool that declares itself
+/// idempotent are answered from the cache while the result is fresh. A call
+/// to any tool that changes shared state (shell, file writes, git, memory)
+/// clears the cache.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ToolCacheConfig {
+    /// Enable the tool result cache. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds a cached result stays fresh. Default: `300`.
+    #[serde(default = "default_tool_cache_ttl_secs")]
+    pub ttl_secs: u64,
+    /// Most results kept per tool; the oldest is evicted first. Default: `64`.
+    #[serde(default = "default_tool_cache_max_entries")]
+    pub max_entries: usize,
+    /// Per-tool overrides, keyed by tool name.
+    #[serde(default)]
+    pub tools: HashMap<String, ToolCachePolicy>,
+}
+
+/// Cache settings for one tool (`[tool_cache.tools.<name>]`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ToolCachePolicy {
+    /// Never cache this tool's results. Use for tools that are not
+    /// idempotent in practice, e.g. reads of files edited outside the agent.
+    #[serde(default)]
+    pub bypass: bool,
+    /// Override `tool_cache.ttl_secs` for this tool.
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    /// Override `tool_cache.max_entries` for this tool.
+    #[serde(default)]
+    pub max_entries: Option<usize>,
+}
+
+fn default_tool_cache_ttl_secs() -> u64 {
+    300
+}
+
+fn default_tool_cache_max_entries() -> usize {
+    64
+}
+
+impl Default for ToolCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: default_tool_cache_ttl_secs(),
+            max_entries: default_tool_cache_max_entries(),
+            tools: HashMap::new(),
+        }
+    }
+}
+
 // ── Proxy ───────────────────────────────────────────────────────
 
 /// Proxy application scope — determines which outbound traffic uses the proxy.
@@ -3054,6 +3197,10 @@ pub struct MemoryConfig {
     /// Max tokens per chunk for document splitting
     #[serde(default = "default_chunk_size")]
     pub chunk_max_tokens: usize,
+    /// Tokens repeated from the end of a document chunk at the start of the
+    /// next one (default: 64)
+    #[serde(default = "crate::config::snowclaw_schema::default_chunk_overlap_tokens")]
+    pub chunk_overlap_tokens: usize,
 
     // ── Response Cache (saves tokens on repeated prompts) ──────
     /// Enable LLM response caching to avoid paying for duplicate prompts
@@ -3137,6 +3284,10 @@ pub struct MemoryConfig {
     /// Collective memory backend configuration.
     #[serde(default)]
     pub collective: crate::config::snowclaw_schema::CollectiveMemoryConfig,
+
+    /// What the agent may remember (`[memory.write_policy]`).
+    #[serde(default)]
+    pub write_policy: crate::config::snowclaw_schema::MemoryWritePolicyConfig,
 }
 
 fn default_sqlite_journal_mode() -> String {
@@ -3203,6 +3354,7 @@ impl Default for MemoryConfig {
             min_relevance_score: default_min_relevance_score(),
             embedding_cache_size: default_cache_size(),
             chunk_max_tokens: default_chunk_size(),
+            chunk_overlap_tokens: crate::config::snowclaw_schema::default_chunk_overlap_tokens(),
             response_cache_enabled: false,
             response_cache_ttl_minutes: default_response_cache_ttl(),
             response_cache_max_entries: default_response_cache_max(),
@@ -3221,6 +3373,7 @@ impl Default for MemoryConfig {
             ),
             nomen_socket_path: None,
             collective: crate::config::snowclaw_schema::CollectiveMemoryConfig::default(),
+            write_policy: crate::config::snowclaw_schema::MemoryWritePolicyConfig::default(),
         }
     }
 }
@@ -4569,6 +4722,9 @@ pub struct ChannelsConfig {
     /// Default: 300s for on-device LLMs (Ollama) which are slower than cloud APIs.
     #[serde(default = "default_channel_message_timeout_secs")]
     pub message_timeout_secs: u64,
+    /// Per-stage timeouts within a reply (`[channels_config.latency]`).
+    #[serde(default)]
+    pub latency: LatencyBudgetConfig,
 }
 
 impl ChannelsConfig {
@@ -4717,6 +4873,7 @@ impl Default for ChannelsConfig {
             clawdtalk: None,
             ack_reaction: AckReactionChannelsConfig::default(),
             message_timeout_secs: default_channel_message_timeout_secs(),
+            latency: LatencyBudgetConfig::default(),
         }
     }
 }
@@ -6545,7 +6702,17 @@ impl ChannelConfig for QQConfig {
     }
 }
 
-pub use crate::config::snowclaw_schema::{default_nostr_relays, NostrConfig};
+pub use crate::config::snowclaw_schema::{
+    default_nostr_relays, runtime_nostr_proxy, set_runtime_nostr_proxy, KeyFilterConfig,
+    KeyFilterPattern, MemoryWritePolicyConfig, NostrAnswerCacheConfig, NostrBackfillConfig,
+    NostrBannerConfig, NostrCatchupConfig, NostrConfig, NostrContactPruningConfig,
+    NostrCostAlertConfig, NostrDigestConfig, NostrDmPresenceConfig, NostrDmSessionConfig,
+    NostrIndexRedactionConfig, NostrLinkIndexConfig, NostrNip05Config, NostrOnboardingConfig,
+    NostrOwnerConfig, NostrPipelineConfig, NostrProactiveConfig, NostrProactivePostConfig,
+    NostrProxyConfig, NostrPublishConfig, NostrQuotaConfig, NostrQuotaTag, NostrReconnectConfig,
+    NostrRelayTrust, NostrRelayTrustConfig, NostrReplyModerationConfig, NostrSignerConfig,
+    NostrSplitConfig, NostrStatusFeedConfig, NostrSubscriptionsConfig, NostrThreadingConfig,
+};
 
 // ── Config impl ──────────────────────────────────────────────────
 
@@ -6592,6 +6759,7 @@ impl Default for Config {
             multimodal: MultimodalConfig::default(),
             web_fetch: WebFetchConfig::default(),
             web_search: WebSearchConfig::default(),
+            tool_cache: ToolCacheConfig::default(),
             proxy: ProxyConfig::default(),
             identity: IdentityConfig::default(),
             cost: CostConfig::default(),
@@ -6607,6 +6775,13 @@ impl Default for Config {
             agents_ipc: AgentsIpcConfig::default(),
             mcp: McpConfig::default(),
             contextvm: None,
+            personas: PersonasConfig::default(),
+            execution_policies: ExecutionPoliciesConfig::default(),
+            model_profiles: ModelProfilesConfig::default(),
+            model_router: ModelRouterConfig::default(),
+            stats: StatsConfig::default(),
+            update: UpdateConfig::default(),
+            determinism: DeterminismConfig::default(),
             model_support_vision: None,
             wasm: WasmConfig::default(),
         }
@@ -8138,6 +8313,11 @@ impl Config {
         if let Some(acp) = &self.channels_config.acp {
             acp.validate()?;
         }
+        self.personas.validate()?;
+        self.execution_policies.validate()?;
+        self.model_profiles.validate()?;
+        self.model_router.validate(&self.model_routes)?;
+        self.stats.validate()?;
 
         // Gateway
         if self.gateway.host.trim().is_empty() {
@@ -9115,6 +9295,14 @@ impl Config {
             }
         }
 
+        // Seed mode: ZEROCLAW_SEED
+        if let Ok(seed) = std::env::var("ZEROCLAW_SEED") {
+            if let Ok(seed) = seed.trim().parse::<u64>() {
+                self.determinism.enabled = true;
+                self.determinism.seed = seed;
+            }
+        }
+
         // Reasoning override: ZEROCLAW_REASONING_ENABLED or REASONING_ENABLED
         if let Ok(flag) = std::env::var("ZEROCLAW_REASONING_ENABLED")
             .or_else(|_| std::env::var("REASONING_ENABLED"))
@@ -9583,6 +9771,9 @@ impl Config {
         }
 
         set_runtime_proxy_config(self.proxy.clone());
+        if let Some(nostr) = &self.channels_config.nostr {
+            set_runtime_nostr_proxy(nostr.proxy.clone());
+        }
     }
 
     pub async fn save(&self) -> Result<()> {
@@ -10505,6 +10696,7 @@ ws_url = "ws://127.0.0.1:3002"
                 clawdtalk: None,
                 ack_reaction: AckReactionChannelsConfig::default(),
                 message_timeout_secs: 300,
+                latency: LatencyBudgetConfig::default(),
             },
             memory: MemoryConfig::default(),
             storage: StorageConfig::default(),
@@ -10517,6 +10709,7 @@ ws_url = "ws://127.0.0.1:3002"
             multimodal: MultimodalConfig::default(),
             web_fetch: WebFetchConfig::default(),
             web_search: WebSearchConfig::default(),
+            tool_cache: ToolCacheConfig::default(),
             proxy: ProxyConfig::default(),
             agent: AgentConfig::default(),
             identity: IdentityConfig::default(),
@@ -10530,6 +10723,13 @@ ws_url = "ws://127.0.0.1:3002"
             agents_ipc: AgentsIpcConfig::default(),
             mcp: McpConfig::default(),
             contextvm: None,
+            personas: PersonasConfig::default(),
+            execution_policies: ExecutionPoliciesConfig::default(),
+            model_profiles: ModelProfilesConfig::default(),
+            model_router: ModelRouterConfig::default(),
+            stats: StatsConfig::default(),
+            update: UpdateConfig::default(),
+            determinism: DeterminismConfig::default(),
             model_support_vision: None,
             wasm: WasmConfig::default(),
         };
@@ -10813,6 +11013,7 @@ reasoning_level = "high"
         assert_eq!(cfg.max_tool_iterations, 20);
         assert_eq!(cfg.max_history_messages, 50);
         assert!(!cfg.parallel_tools);
+        assert_eq!(cfg.max_parallel_tools, 4);
         assert_eq!(cfg.tool_dispatcher, "auto");
         assert!(cfg.allowed_tools.is_empty());
         assert!(cfg.denied_tools.is_empty());
@@ -10827,6 +11028,7 @@ compact_context = true
 max_tool_iterations = 20
 max_history_messages = 80
 parallel_tools = true
+max_parallel_tools = 2
 tool_dispatcher = "xml"
 allowed_tools = ["delegate", "task_plan"]
 denied_tools = ["shell"]
@@ -10836,6 +11038,7 @@ denied_tools = ["shell"]
         assert_eq!(parsed.agent.max_tool_iterations, 20);
         assert_eq!(parsed.agent.max_history_messages, 80);
         assert!(parsed.agent.parallel_tools);
+        assert_eq!(parsed.agent.max_parallel_tools, 2);
         assert_eq!(parsed.agent.tool_dispatcher, "xml");
         assert_eq!(
             parsed.agent.allowed_tools,
@@ -10906,6 +11109,7 @@ denied_tools = ["shell"]
             multimodal: MultimodalConfig::default(),
             web_fetch: WebFetchConfig::default(),
             web_search: WebSearchConfig::default(),
+            tool_cache: ToolCacheConfig::default(),
             proxy: ProxyConfig::default(),
             agent: AgentConfig::default(),
             identity: IdentityConfig::default(),
@@ -10919,6 +11123,13 @@ denied_tools = ["shell"]
             agents_ipc: AgentsIpcConfig::default(),
             mcp: McpConfig::default(),
             contextvm: None,
+            personas: PersonasConfig::default(),
+            execution_policies: ExecutionPoliciesConfig::default(),
+            model_profiles: ModelProfilesConfig::default(),
+            model_router: ModelRouterConfig::default(),
+            stats: StatsConfig::default(),
+            update: UpdateConfig::default(),
+            determinism: DeterminismConfig::default(),
             model_support_vision: None,
             wasm: WasmConfig::default(),
         };
@@ -11508,6 +11719,7 @@ allowed_users = ["@ops:matrix.org"]
             clawdtalk: None,
             ack_reaction: AckReactionChannelsConfig::default(),
             message_timeout_secs: 300,
+            latency: LatencyBudgetConfig::default(),
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -11882,6 +12094,7 @@ allowed_sender_ids = ["U111", "U222"]
             clawdtalk: None,
             ack_reaction: AckReactionChannelsConfig::default(),
             message_timeout_secs: 300,
+            latency: LatencyBudgetConfig::default(),
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -12115,10 +12328,14 @@ enable = true
 
     #[test]
     async fn secrets_config_serde_roundtrip() {
-        let s = SecretsConfig { encrypt: false };
+        let s = SecretsConfig {
+            encrypt: false,
+            encrypt_databases: true,
+        };
         let toml_str = toml::to_string(&s).unwrap();
         let parsed: SecretsConfig = toml::from_str(&toml_str).unwrap();
         assert!(!parsed.encrypt);
+        assert!(parsed.encrypt_databases);
     }
 
     #[test]
@@ -12255,6 +12472,72 @@ default_temperature = 0.7
         assert!(ws.jina_site_filters.is_empty());
     }
 
+    #[test]
+    async fn tool_cache_config_parses_per_tool_policies() {
+        let toml_str = r#"
+workspace_dir = "/tmp/workspace"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[tool_cache]
+enabled = true
+ttl_secs = 60
+
+[tool_cache.tools.web_search_tool]
+ttl_secs = 600
+max_entries = 8
+
+[tool_cache.tools.file_read]
+bypass = true
+"#;
+
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        let cache = &parsed.tool_cache;
+        assert!(cache.enabled);
+        assert_eq!(cache.ttl_secs, 60);
+        assert_eq!(cache.max_entries, 64);
+        let search = &cache.tools["web_search_tool"];
+        assert_eq!((search.ttl_secs, search.max_entries), (Some(600), Some(8)));
+        assert!(!search.bypass);
+        assert!(cache.tools["file_read"].bypass);
+        assert!(!Config::default().tool_cache.enabled);
+    }
+
+    #[test]
+    async fn config_validate_checks_execution_policy_mappings() {
+        let mut config = Config::default();
+        config.execution_policies.profiles.insert(
+            "public".into(),
+            ExecutionPolicyConfig {
+                allowed_tools: vec!["web_search".into()],
+                timeout_secs: Some(30),
+                ..ExecutionPolicyConfig::default()
+            },
+        );
+        config
+            .execution_policies
+            .channels
+            .insert("nostr".into(), "public".into());
+        assert!(config.execution_policies.validate().is_ok());
+
+        config
+            .execution_policies
+            .rooms
+            .insert("dev".into(), "missing".into());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("unknown policy `missing`"), "{err}");
+
+        config.execution_policies.rooms.clear();
+        config
+            .execution_policies
+            .profiles
+            .get_mut("public")
+            .unwrap()
+            .denied_argument_patterns = vec!["(".into()];
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("invalid argument pattern"), "{err}");
+    }
+
     #[test]
     async fn config_validate_rejects_unknown_browser_open_value() {
         let mut config = Config::default();
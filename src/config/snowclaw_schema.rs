@@ -23,6 +23,9 @@ pub struct NostrConfig {
     /// Nostr secret key (nsec1... or hex). Can also be set via SNOWCLAW_NSEC env var.
     #[serde(default)]
     pub nsec: Option<String>,
+    /// NIP-46 remote signer used instead of `nsec`
+    #[serde(default)]
+    pub signer: NostrSignerConfig,
     /// Relay URLs (wss://). Defaults to popular public relays if omitted.
     #[serde(default = "default_nostr_relays")]
     pub relays: Vec<String>,
@@ -32,6 +35,9 @@ pub struct NostrConfig {
     /// Owner pubkey (hex or npub) for admin commands
     #[serde(default)]
     pub owner: Option<String>,
+    /// Further owners, admins and operators with role-based privileges
+    #[serde(default)]
+    pub owners: Vec<NostrOwnerConfig>,
     /// NIP-29 group IDs to join
     #[serde(default)]
     pub groups: Vec<String>,
@@ -52,58 +58,1788 @@ pub struct NostrConfig {
     pub context_history: usize,
     /// Extra Nostr event kinds to subscribe to beyond NIP-29 defaults (e.g. [1311, 1312] for NIP-53 live)
     #[serde(default)]
-    pub extra_kinds: Vec<u16>,
+    pub extra_kinds: Vec<u16>,
+    /// Scheduled activity and cost digest sent to the owner
+    #[serde(default)]
+    pub digest: NostrDigestConfig,
+    /// Status DM to the owner on startup and shutdown
+    #[serde(default)]
+    pub banner: NostrBannerConfig,
+    /// Secret detection patterns applied before messages reach the LLM
+    #[serde(default)]
+    pub key_filter: KeyFilterConfig,
+    /// Redaction of secrets and optional PII before messages are indexed
+    #[serde(default)]
+    pub index_redaction: NostrIndexRedactionConfig,
+    /// Welcome flow for members first seen in a configured group
+    #[serde(default)]
+    pub onboarding: NostrOnboardingConfig,
+    /// Daily request/cost quotas for DMs from non-owner contacts
+    #[serde(default)]
+    pub quota: NostrQuotaConfig,
+    /// Short-lived cache answering exact repeat questions in a group
+    #[serde(default)]
+    pub answer_cache: NostrAnswerCacheConfig,
+    /// Bounded per-lane queues between receiving and handling events
+    #[serde(default)]
+    pub pipeline: NostrPipelineConfig,
+    /// SOCKS5/Tor proxy for relay connections
+    #[serde(default)]
+    pub proxy: NostrProxyConfig,
+    /// Per-relay read/write roles and trust for actions and config
+    #[serde(default)]
+    pub relay_trust: NostrRelayTrustConfig,
+    /// Coalescing and rate limit for NIP-78 social memory publishes
+    #[serde(default)]
+    pub publish: NostrPublishConfig,
+    /// Adaptive relay reconnection with per-relay backoff
+    #[serde(default)]
+    pub reconnect: NostrReconnectConfig,
+    /// Per-group read markers and `conversation.catchup` summaries
+    #[serde(default)]
+    pub catchup: NostrCatchupConfig,
+    /// Per-contact DM conversation sessions
+    #[serde(default)]
+    pub dm_sessions: NostrDmSessionConfig,
+    /// Typing indicators and read receipts for DM correspondents
+    #[serde(default)]
+    pub dm_presence: NostrDmPresenceConfig,
+    /// Fetching and indexing of links shared in groups
+    #[serde(default)]
+    pub link_index: NostrLinkIndexConfig,
+    /// Checks on outgoing group replies before they are published
+    #[serde(default)]
+    pub reply_moderation: NostrReplyModerationConfig,
+    /// NIP-29 threading and quoting of group replies
+    #[serde(default)]
+    pub threading: NostrThreadingConfig,
+    /// Splitting of long group replies into several events
+    #[serde(default)]
+    pub split: NostrSplitConfig,
+    /// Owner DMs about cost spikes, tool loops and costly requests
+    #[serde(default)]
+    pub cost_alerts: NostrCostAlertConfig,
+    /// NIP-05 verification of senders and trust for verified domains
+    #[serde(default)]
+    pub nip05: NostrNip05Config,
+    /// Scheduled and trigger-based group posts with owner approval
+    #[serde(default)]
+    pub proactive: NostrProactiveConfig,
+    /// Deep history backfill and topic summary for newly joined groups
+    #[serde(default)]
+    pub backfill: NostrBackfillConfig,
+    /// Archiving of inactive one-off contacts
+    #[serde(default)]
+    pub contact_pruning: NostrContactPruningConfig,
+    /// Public agent status event for status pages
+    #[serde(default)]
+    pub status_feed: NostrStatusFeedConfig,
+    /// Auto-tuning of relay subscriptions for unused event kinds
+    #[serde(default)]
+    pub subscriptions: NostrSubscriptionsConfig,
+    /// minijinja template for the prompt context of group messages and DMs
+    /// (relative to the config directory; built-in layout when unset)
+    #[serde(default)]
+    pub context_template: Option<String>,
+}
+
+impl ChannelConfig for NostrConfig {
+    fn name() -> &'static str {
+        "Nostr"
+    }
+    fn desc() -> &'static str {
+        "Nostr DMs"
+    }
+}
+
+fn default_respond_mode() -> String {
+    "mention_only".into()
+}
+fn default_context_history() -> usize {
+    10
+}
+fn default_true() -> bool {
+    true
+}
+
+/// Owner digest DM (`[channels_config.nostr.digest]`).
+///
+/// Summarizes messages seen/answered per group, cost, new contacts and
+/// security flags since the previous digest and sends it to `owner` as a
+/// NIP-17 DM.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrDigestConfig {
+    /// Send the digest (requires `owner`)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Cron expression for delivery (default: daily at 08:00)
+    #[serde(default = "default_digest_schedule")]
+    pub schedule: String,
+    /// IANA timezone for `schedule` (default: UTC)
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// "summary" (totals only) | "detailed" (per-group, contact and flag lines)
+    #[serde(default = "default_digest_verbosity")]
+    pub verbosity: String,
+}
+
+impl Default for NostrDigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            schedule: default_digest_schedule(),
+            timezone: None,
+            verbosity: default_digest_verbosity(),
+        }
+    }
+}
+
+/// Owner status DM on startup/shutdown (`[channels_config.nostr.banner]`).
+///
+/// Reports version, groups, unpublished lessons and remaining cost budget
+/// so unexpected restarts are noticed without watching logs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct NostrBannerConfig {
+    /// DM the owner once the channel is connected
+    #[serde(default)]
+    pub startup: bool,
+    /// DM the owner when the daemon stops on SIGINT/SIGTERM
+    #[serde(default)]
+    pub shutdown: bool,
+}
+
+/// Secret detection for inbound Nostr messages (`[channels_config.nostr.key_filter]`).
+///
+/// nsecs are always detected; built-in patterns cover common API keys
+/// (`openai_api_key`, `anthropic_api_key`, `aws_access_key`, `github_token`,
+/// `slack_token`, `google_api_key`, `stripe_secret_key`, `private_key_block`).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KeyFilterConfig {
+    /// Enable the built-in API key patterns
+    #[serde(default = "default_true")]
+    pub builtin_patterns: bool,
+    /// Action overrides by pattern name, including `nsec` (redact | block | alert_owner)
+    #[serde(default)]
+    pub actions: std::collections::HashMap<String, String>,
+    /// Additional user patterns
+    #[serde(default)]
+    pub patterns: Vec<KeyFilterPattern>,
+    /// Regexes for known-safe strings that are never flagged (full match)
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+}
+
+impl Default for KeyFilterConfig {
+    fn default() -> Self {
+        Self {
+            builtin_patterns: true,
+            actions: std::collections::HashMap::new(),
+            patterns: Vec::new(),
+            allowlist: Vec::new(),
+        }
+    }
+}
+
+/// Redaction before messages are indexed (`[channels_config.nostr.index_redaction]`).
+///
+/// Messages are scrubbed with the `key_filter` patterns (nsecs, API keys,
+/// user patterns, honouring the allowlist) before they are written to the
+/// message index. `pii` adds built-in personal data patterns on top.
+/// `snowclaw memory rescrub` applies the current settings to rows indexed
+/// earlier.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrIndexRedactionConfig {
+    /// Scrub messages before indexing
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Personal data patterns to redact as well: "email", "phone"
+    #[serde(default)]
+    pub pii: Vec<String>,
+}
+
+impl Default for NostrIndexRedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pii: Vec::new(),
+        }
+    }
+}
+
+/// Group onboarding (`[channels_config.nostr.onboarding]`).
+///
+/// When a member is first seen in a configured group, the agent can send a
+/// templated welcome and records the onboarding status in social memory so
+/// nobody is welcomed twice.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrOnboardingConfig {
+    /// Run the onboarding hook
+    #[serde(default)]
+    pub enabled: bool,
+    /// "dm" (NIP-17 welcome DM) | "mention" (group reply tagging the member) | "record" (status only)
+    #[serde(default = "default_onboarding_mode")]
+    pub mode: String,
+    /// Welcome text; `{name}`, `{group}` and `{npub}` are substituted
+    #[serde(default = "default_onboarding_template")]
+    pub template: String,
+    /// Groups to onboard in (default: all configured groups)
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Only welcome contacts the agent has never seen anywhere (default: true).
+    /// Set to false to also welcome known contacts joining another group.
+    #[serde(default = "default_true")]
+    pub new_contacts_only: bool,
+}
+
+impl Default for NostrOnboardingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: default_onboarding_mode(),
+            template: default_onboarding_template(),
+            groups: Vec::new(),
+            new_contacts_only: true,
+        }
+    }
+}
+
+/// Per-contact DM quotas (`[channels_config.nostr.quota]`).
+///
+/// Limits reset at 00:00 UTC. The owner is never limited. A limit of `0`
+/// means unlimited.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrQuotaConfig {
+    /// Enforce quotas
+    #[serde(default)]
+    pub enabled: bool,
+    /// Default requests per contact per day
+    #[serde(default = "default_quota_requests")]
+    pub daily_requests: u32,
+    /// Default LLM cost per contact per day (USD)
+    #[serde(default = "default_quota_cost")]
+    pub daily_cost_usd: f64,
+    /// Named contact tags with their own limits
+    #[serde(default)]
+    pub tags: std::collections::HashMap<String, NostrQuotaTag>,
+    /// Reply sent once per day when a contact hits a limit; `{reason}` is substituted
+    #[serde(default = "default_quota_message")]
+    pub exceeded_message: String,
+}
+
+impl Default for NostrQuotaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            daily_requests: default_quota_requests(),
+            daily_cost_usd: default_quota_cost(),
+            tags: std::collections::HashMap::new(),
+            exceeded_message: default_quota_message(),
+        }
+    }
+}
+
+/// Quota override for a tagged set of contacts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct NostrQuotaTag {
+    /// Contacts with this tag (npub or hex)
+    #[serde(default)]
+    pub pubkeys: Vec<String>,
+    /// Requests per day (unset: default, 0: unlimited)
+    #[serde(default)]
+    pub daily_requests: Option<u32>,
+    /// Cost per day in USD (unset: default, 0: unlimited)
+    #[serde(default)]
+    pub daily_cost_usd: Option<f64>,
+}
+
+/// Answer cache for repeated group questions (`[channels_config.nostr.answer_cache]`).
+///
+/// An exact repeat of a recent question (after normalizing case, whitespace,
+/// mentions and trailing punctuation) in the same group is answered with the
+/// cached reply instead of a new LLM call. With `semantic` enabled, paraphrases
+/// whose embedding is at least `similarity_threshold` similar also hit.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrAnswerCacheConfig {
+    /// Answer repeats from cache
+    #[serde(default)]
+    pub enabled: bool,
+    /// How long an answer stays reusable, in seconds
+    #[serde(default = "default_answer_cache_ttl")]
+    pub ttl_secs: u64,
+    /// Maximum cached answers across all groups
+    #[serde(default = "default_answer_cache_entries")]
+    pub max_entries: usize,
+    /// Line prepended to cached replies; `{age}` is substituted
+    #[serde(default = "default_answer_cache_annotation")]
+    pub annotation: String,
+    /// Also match paraphrased questions by embedding similarity (uses the
+    /// `[memory]` embedding provider)
+    #[serde(default)]
+    pub semantic: bool,
+    /// Cosine similarity a paraphrase needs to count as the same question
+    #[serde(default = "default_answer_cache_similarity")]
+    pub similarity_threshold: f64,
+    /// Groups that never use the cache
+    #[serde(default)]
+    pub exclude_groups: Vec<String>,
+}
+
+impl Default for NostrAnswerCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: default_answer_cache_ttl(),
+            max_entries: default_answer_cache_entries(),
+            annotation: default_answer_cache_annotation(),
+            semantic: false,
+            similarity_threshold: default_answer_cache_similarity(),
+            exclude_groups: Vec::new(),
+        }
+    }
+}
+
+/// Subscribe pipeline queues (`[channels_config.nostr.pipeline]`).
+///
+/// Accepted events are queued per lane — priority (DMs, actions, owner
+/// events), group messages and background kinds (task status, agent state)
+/// — and each lane is handled independently, so a busy group never delays
+/// DMs. The priority lane never drops; the others apply an overflow policy
+/// when full: "drop_oldest", "drop_newest" or "block".
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrPipelineConfig {
+    /// Queue size for DMs, action requests and owner events
+    #[serde(default = "default_pipeline_priority_capacity")]
+    pub priority_capacity: usize,
+    /// Queue size for group messages
+    #[serde(default = "default_pipeline_group_capacity")]
+    pub group_capacity: usize,
+    /// What a full group queue does with new messages
+    #[serde(default = "default_pipeline_group_overflow")]
+    pub group_overflow: String,
+    /// Queue size for task status, agent state and extra kinds
+    #[serde(default = "default_pipeline_background_capacity")]
+    pub background_capacity: usize,
+    /// What a full background queue does with new events
+    #[serde(default = "default_pipeline_background_overflow")]
+    pub background_overflow: String,
+    /// Warn when an event waited longer than this in its queue, in milliseconds
+    #[serde(default = "default_pipeline_lag_warn_ms")]
+    pub lag_warn_ms: u64,
+}
+
+impl Default for NostrPipelineConfig {
+    fn default() -> Self {
+        Self {
+            priority_capacity: default_pipeline_priority_capacity(),
+            group_capacity: default_pipeline_group_capacity(),
+            group_overflow: default_pipeline_group_overflow(),
+            background_capacity: default_pipeline_background_capacity(),
+            background_overflow: default_pipeline_background_overflow(),
+            lag_warn_ms: default_pipeline_lag_warn_ms(),
+        }
+    }
+}
+
+/// Relay connection proxy (`[channels_config.nostr.proxy]`).
+///
+/// Routes relay websockets through a SOCKS5 proxy, typically a local Tor
+/// daemon. Onion relays are only reachable through the proxy; with
+/// `onion_only` clearnet relays keep connecting directly.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct NostrProxyConfig {
+    /// SOCKS5 proxy address (`host:port`, e.g. `127.0.0.1:9050` for Tor)
+    #[serde(default)]
+    pub socks5: Option<String>,
+    /// Only proxy `.onion` relays
+    #[serde(default)]
+    pub onion_only: bool,
+    /// Per-relay overrides: relay URL -> `host:port` or `direct`
+    #[serde(default)]
+    pub relays: std::collections::HashMap<String, String>,
+    /// Onion relay URLs added to `relays` when a proxy is configured
+    #[serde(default)]
+    pub onion_relays: Vec<String>,
+}
+
+static RUNTIME_NOSTR_PROXY: std::sync::OnceLock<parking_lot::RwLock<NostrProxyConfig>> =
+    std::sync::OnceLock::new();
+
+/// Set the relay proxy for Nostr clients built outside the channel (memory
+/// backends, CLI commands). Applied on config load, like
+/// [`set_runtime_proxy_config`](crate::config::set_runtime_proxy_config).
+pub fn set_runtime_nostr_proxy(config: NostrProxyConfig) {
+    *RUNTIME_NOSTR_PROXY.get_or_init(Default::default).write() = config;
+}
+
+/// The relay proxy from the loaded config.
+pub fn runtime_nostr_proxy() -> NostrProxyConfig {
+    RUNTIME_NOSTR_PROXY
+        .get_or_init(Default::default)
+        .read()
+        .clone()
+}
+
+/// What the agent uses one relay for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NostrRelayTrust {
+    /// Read and write; every kind is honored
+    #[default]
+    Trusted,
+    /// Only read from; nothing is published there
+    ReadOnly,
+    /// Only published to; inbound events are ignored
+    WriteOnly,
+    /// Read and write, but actions, config and deletions from it are ignored
+    Untrusted,
+}
+
+/// Per-relay trust (`[channels_config.nostr.relay_trust]`).
+///
+/// Any relay can hand the agent events. Sensitive kinds — kind 1121 action
+/// requests, kind 30078 dynamic config and kind 5 deletions — are only
+/// honored when they arrive from a `trusted` or `read_only` relay.
+/// `default = "untrusted"` plus `trusted` overrides makes an allowlist.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct NostrRelayTrustConfig {
+    /// Trust for relays without an override
+    #[serde(default)]
+    pub default: NostrRelayTrust,
+    /// Per-relay overrides: relay URL -> trust
+    #[serde(default)]
+    pub relays: std::collections::HashMap<String, NostrRelayTrust>,
+}
+
+/// Adaptive relay reconnection (`[channels_config.nostr.reconnect]`).
+///
+/// Replaces the relay pool's own retries: a relay that fails to reconnect
+/// waits `base_secs`, doubled per further failure up to `max_secs`, with
+/// up to `jitter` of each delay randomized away so flapping relays don't
+/// cause reconnect storms.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrReconnectConfig {
+    /// Handle relay reconnects with backoff instead of the relay pool
+    #[serde(default)]
+    pub enabled: bool,
+    /// Delay after the first failed attempt (seconds)
+    #[serde(default = "default_reconnect_base_secs")]
+    pub base_secs: u64,
+    /// Cap on the delay between attempts (seconds)
+    #[serde(default = "default_reconnect_max_secs")]
+    pub max_secs: u64,
+    /// Fraction (0–1) of each delay that is randomized away
+    #[serde(default = "default_reconnect_jitter")]
+    pub jitter: f64,
+}
+
+impl Default for NostrReconnectConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            base_secs: default_reconnect_base_secs(),
+            max_secs: default_reconnect_max_secs(),
+            jitter: default_reconnect_jitter(),
+        }
+    }
+}
+
+/// Social memory publishing (`[channels_config.nostr.publish]`).
+///
+/// Contact and group updates are republished as NIP-78 events; writes to
+/// the same d-tag within `coalesce_secs` become one publish, and all social
+/// publishes share a per-minute budget.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrPublishConfig {
+    /// Seconds to wait after the first write to a d-tag before publishing
+    #[serde(default = "default_publish_coalesce_secs")]
+    pub coalesce_secs: u64,
+    /// Max social publishes per minute across all d-tags (0 = unlimited)
+    #[serde(default = "default_publish_max_per_minute")]
+    pub max_per_minute: u32,
+}
+
+impl Default for NostrPublishConfig {
+    fn default() -> Self {
+        Self {
+            coalesce_secs: default_publish_coalesce_secs(),
+            max_per_minute: default_publish_max_per_minute(),
+        }
+    }
+}
+
+/// Group catch-up (`[channels_config.nostr.catchup]`).
+///
+/// The last group message read by the owner and by the agent is tracked in
+/// `social.db`. The owner-only `conversation.catchup` action has the agent
+/// summarize everything after the owner's marker and moves the marker.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrCatchupConfig {
+    /// Most messages included in one catch-up summary
+    #[serde(default = "default_catchup_max_messages")]
+    pub max_messages: usize,
+    /// Also publish read markers as NIP-78 events, so other devices and
+    /// instances sharing the key pick them up
+    #[serde(default)]
+    pub sync_markers: bool,
+}
+
+impl Default for NostrCatchupConfig {
+    fn default() -> Self {
+        Self {
+            max_messages: default_catchup_max_messages(),
+            sync_markers: false,
+        }
+    }
+}
+
+fn default_catchup_max_messages() -> usize {
+    200
+}
+
+/// NIP-46 remote signer (`[channels_config.nostr.signer]`).
+///
+/// With a bunker URL the channel signs, encrypts and decrypts through the
+/// bunker and never holds the agent's private key; `nsec` is not needed.
+/// The channel connects to the bunker with its own client key, kept in
+/// `nip46_client.key` next to `config.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrSignerConfig {
+    /// `bunker://<signer-pubkey>?relay=wss://...&secret=...`. Can also be
+    /// set via SNOWCLAW_BUNKER_URL env var.
+    #[serde(default)]
+    pub bunker_url: Option<String>,
+    /// Seconds to wait for each signer response
+    #[serde(default = "default_signer_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Requests waiting for the signer before new ones fail
+    #[serde(default = "default_signer_queue_size")]
+    pub queue_size: usize,
+}
+
+impl Default for NostrSignerConfig {
+    fn default() -> Self {
+        Self {
+            bunker_url: None,
+            timeout_secs: default_signer_timeout_secs(),
+            queue_size: default_signer_queue_size(),
+        }
+    }
+}
+
+fn default_signer_timeout_secs() -> u64 {
+    30
+}
+fn default_signer_queue_size() -> usize {
+    64
+}
+
+/// Deep history backfill (`[channels_config.nostr.backfill]`).
+///
+/// A group that has never been backfilled gets its last `days` of messages
+/// fetched page by page, indexed and recorded in social memory without any
+/// replies, then summarized into a group note. Finished groups are kept in
+/// `group_backfill.json`; an interrupted backfill starts over on restart.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrBackfillConfig {
+    /// Backfill groups that have not been backfilled yet
+    #[serde(default)]
+    pub enabled: bool,
+    /// Days of history to fetch
+    #[serde(default = "default_backfill_days")]
+    pub days: u64,
+    /// Events requested per relay page
+    #[serde(default = "default_backfill_page_size")]
+    pub page_size: usize,
+    /// Most messages fetched per group
+    #[serde(default = "default_backfill_max_messages")]
+    pub max_messages: usize,
+    /// Have the agent summarize recent topics into a group note
+    #[serde(default = "default_true")]
+    pub summarize: bool,
+    /// Most recent messages included in the summary request
+    #[serde(default = "default_catchup_max_messages")]
+    pub summary_messages: usize,
+}
+
+impl Default for NostrBackfillConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            days: default_backfill_days(),
+            page_size: default_backfill_page_size(),
+            max_messages: default_backfill_max_messages(),
+            summarize: true,
+            summary_messages: default_catchup_max_messages(),
+        }
+    }
+}
+
+fn default_backfill_days() -> u64 {
+    30
+}
+fn default_backfill_page_size() -> usize {
+    500
+}
+fn default_backfill_max_messages() -> usize {
+    5000
+}
+
+/// Contact pruning (`[channels_config.nostr.contact_pruning]`).
+///
+/// Every `interval_hours`, contacts not seen for `inactive_days` that have
+/// no notes, owner notes or preferences are moved to a compressed archive
+/// table and out of the social search index. An archived contact is
+/// restored as soon as it is seen again.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrContactPruningConfig {
+    /// Archive inactive contacts
+    #[serde(default)]
+    pub enabled: bool,
+    /// Days without interaction before a contact is archived
+    #[serde(default = "default_pruning_inactive_days")]
+    pub inactive_days: u64,
+    /// Hours between pruning runs (the first runs at startup)
+    #[serde(default = "default_pruning_interval_hours")]
+    pub interval_hours: u64,
+}
+
+impl Default for NostrContactPruningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            inactive_days: default_pruning_inactive_days(),
+            interval_hours: default_pruning_interval_hours(),
+        }
+    }
+}
+
+fn default_pruning_inactive_days() -> u64 {
+    180
+}
+fn default_pruning_interval_hours() -> u64 {
+    24
+}
+
+/// Public status feed (`[channels_config.nostr.status_feed]`).
+///
+/// Every `interval_minutes` the agent publishes a replaceable kind 30078
+/// event (d-tag `snow:status`) with uptime, groups served, message counts,
+/// relay counts and daemon component states. It carries no message content,
+/// group ids, pubkeys or error text, so a public status page can show agent
+/// health straight from relays.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrStatusFeedConfig {
+    /// Publish the status event
+    #[serde(default)]
+    pub enabled: bool,
+    /// Minutes between status publishes (the first is at startup)
+    #[serde(default = "default_status_feed_interval_minutes")]
+    pub interval_minutes: u64,
+}
+
+impl Default for NostrStatusFeedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: default_status_feed_interval_minutes(),
+        }
+    }
+}
+
+fn default_status_feed_interval_minutes() -> u64 {
+    15
+}
+
+/// Subscription auto-tuning (`[channels_config.nostr.subscriptions]`).
+///
+/// The channel subscribes to named filters (`groups`, `dms`, `tasks`,
+/// `config`, `owner_claims`, `actions`, `deletions`, `agent_state`,
+/// `heartbeats`). With `auto_tune`, a filter that received fewer than
+/// `min_hits` events in `window_hours` is dropped, and a multi-kind filter
+/// that only saw some of its kinds is narrowed to them. Tuned filters are
+/// restored when the agent needs the kinds again and every `reprobe_hours`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrSubscriptionsConfig {
+    /// Narrow or drop inactive filters
+    #[serde(default)]
+    pub auto_tune: bool,
+    /// Hours a filter is observed before it is tuned
+    #[serde(default = "default_subscriptions_window_hours")]
+    pub window_hours: u64,
+    /// Events a filter needs per window to stay subscribed
+    #[serde(default = "default_subscriptions_min_hits")]
+    pub min_hits: u64,
+    /// Narrow multi-kind filters to the kinds seen instead of keeping them whole
+    #[serde(default = "default_true")]
+    pub narrow: bool,
+    /// Hours after which a tuned filter is restored at full width (0 = only on demand)
+    #[serde(default = "default_subscriptions_reprobe_hours")]
+    pub reprobe_hours: u64,
+    /// Filters that are never tuned
+    #[serde(default = "default_subscriptions_protected")]
+    pub protected: Vec<String>,
+}
+
+impl Default for NostrSubscriptionsConfig {
+    fn default() -> Self {
+        Self {
+            auto_tune: false,
+            window_hours: default_subscriptions_window_hours(),
+            min_hits: default_subscriptions_min_hits(),
+            narrow: true,
+            reprobe_hours: default_subscriptions_reprobe_hours(),
+            protected: default_subscriptions_protected(),
+        }
+    }
+}
+
+fn default_subscriptions_window_hours() -> u64 {
+    24
+}
+fn default_subscriptions_min_hits() -> u64 {
+    1
+}
+fn default_subscriptions_reprobe_hours() -> u64 {
+    72
+}
+fn default_subscriptions_protected() -> Vec<String> {
+    ["groups", "dms", "config", "owner_claims", "actions"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+/// DM conversation sessions (`[channels_config.nostr.dm_sessions]`).
+///
+/// Each contact's recent DMs are kept in a ring buffer (persisted in
+/// `seen_events.db`) and included in the prompt. A session ends after a
+/// period of inactivity, or when the owner sends `new session`; messages
+/// from an ended session leave the prompt context.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrDmSessionConfig {
+    /// Minutes without DMs after which the next message starts a new
+    /// session (0 = sessions never expire)
+    #[serde(default = "default_dm_session_idle_minutes")]
+    pub idle_minutes: u64,
+    /// Messages per contact kept in the prompt (default: `context_history`)
+    #[serde(default)]
+    pub max_messages: Option<usize>,
+}
+
+impl Default for NostrDmSessionConfig {
+    fn default() -> Self {
+        Self {
+            idle_minutes: default_dm_session_idle_minutes(),
+            max_messages: None,
+        }
+    }
+}
+
+fn default_dm_session_idle_minutes() -> u64 {
+    240
+}
+
+/// DM presence signals (`[channels_config.nostr.dm_presence]`).
+///
+/// Lets a human DM correspondent see that a reply is being worked on
+/// instead of silence during long LLM calls. Both are off by default: they
+/// publish events that reveal who the agent is talking to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct NostrDmPresenceConfig {
+    /// Send typing events (kind 21122) while a DM reply is being generated;
+    /// gift-wrapped for NIP-17 correspondents
+    #[serde(default)]
+    pub typing_indicator: bool,
+    /// React 👀 (kind 7) to a DM when it is handed to the agent; gift-wrapped
+    /// for NIP-17 senders
+    #[serde(default)]
+    pub read_receipts: bool,
+}
+
+/// Indexing of links shared in groups (`[channels_config.nostr.link_index]`).
+///
+/// URLs in indexed group messages are fetched, reduced to readable text and
+/// added to the document index, so the agent can recall what a shared page
+/// said. Off by default; only hosts in `allowed_domains` are fetched.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrLinkIndexConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Hosts to fetch (`example.com` also matches subdomains; `*` allows
+    /// any public host)
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    /// Hosts never fetched, checked before `allowed_domains`
+    #[serde(default)]
+    pub blocked_domains: Vec<String>,
+    /// Groups whose links are indexed (empty = all groups)
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Largest response body fetched, in bytes
+    #[serde(default = "default_link_index_max_bytes")]
+    pub max_bytes: usize,
+    /// Extracted text kept per page, in characters
+    #[serde(default = "default_link_index_max_chars")]
+    pub max_chars: usize,
+    /// Request timeout in seconds
+    #[serde(default = "default_link_index_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Pages fetched per hour; links beyond this are skipped
+    #[serde(default = "default_link_index_max_per_hour")]
+    pub max_per_hour: u32,
+}
+
+impl Default for NostrLinkIndexConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allowed_domains: Vec::new(),
+            blocked_domains: Vec::new(),
+            groups: Vec::new(),
+            max_bytes: default_link_index_max_bytes(),
+            max_chars: default_link_index_max_chars(),
+            timeout_secs: default_link_index_timeout_secs(),
+            max_per_hour: default_link_index_max_per_hour(),
+        }
+    }
+}
+
+fn default_link_index_max_bytes() -> usize {
+    2 * 1024 * 1024
+}
+fn default_link_index_max_chars() -> usize {
+    50_000
+}
+fn default_link_index_timeout_secs() -> u64 {
+    20
+}
+fn default_link_index_max_per_hour() -> u32 {
+    30
+}
+
+/// Moderation of outgoing group replies (`[channels_config.nostr.reply_moderation]`).
+///
+/// A group reply that fails a check is not published. The violation is
+/// written to the audit log (`[security.audit]`) and, with `notify_owner`,
+/// sent to the owner by DM. DMs are not checked.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrReplyModerationConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Groups whose replies are checked (empty = all groups)
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Case-insensitive regexes for topics the agent must not post about
+    #[serde(default)]
+    pub banned_patterns: Vec<String>,
+    /// Longest reply in characters (0 = unlimited)
+    #[serde(default)]
+    pub max_length: usize,
+    /// Links in replies: `allow`, `deny`, or `allowlist` (only `allowed_domains`)
+    #[serde(default = "default_reply_moderation_links")]
+    pub links: String,
+    /// Hosts replies may link to under `links = "allowlist"` (`example.com`
+    /// also matches subdomains)
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    /// Ask the LLM whether the reply is fit to post in the group
+    #[serde(default)]
+    pub self_check: bool,
+    /// Model for the self-check (default model when unset)
+    #[serde(default)]
+    pub self_check_model: Option<String>,
+    /// DM the owner when a reply is blocked
+    #[serde(default = "default_true")]
+    pub notify_owner: bool,
+}
+
+impl Default for NostrReplyModerationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            groups: Vec::new(),
+            banned_patterns: Vec::new(),
+            max_length: 0,
+            links: default_reply_moderation_links(),
+            allowed_domains: Vec::new(),
+            self_check: false,
+            self_check_model: None,
+            notify_owner: true,
+        }
+    }
+}
+
+fn default_reply_moderation_links() -> String {
+    "allow".into()
+}
+
+/// How group replies thread (`[channels_config.nostr.threading]`).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrThreadingConfig {
+    /// Answer kind 11 threads and kind 12 replies with a kind 12 reply in
+    /// the same thread
+    #[serde(default = "default_true")]
+    pub in_thread: bool,
+    /// Quote the answered message (`q` and `p` tags) in kind 9 replies
+    #[serde(default = "default_true")]
+    pub quote: bool,
+    /// Post long answers to chat messages as a new kind 11 thread
+    #[serde(default)]
+    pub thread_long_answers: bool,
+    /// Answers of at least this many characters count as long
+    #[serde(default = "default_long_answer_chars")]
+    pub long_answer_chars: usize,
+}
+
+impl Default for NostrThreadingConfig {
+    fn default() -> Self {
+        Self {
+            in_thread: true,
+            quote: true,
+            thread_long_answers: false,
+            long_answer_chars: default_long_answer_chars(),
+        }
+    }
+}
+
+fn default_long_answer_chars() -> usize {
+    1200
+}
+
+/// Splitting of long group replies (`[channels_config.nostr.split]`).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrSplitConfig {
+    /// Split replies longer than `max_chars` into a chain of events
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Longest event content in characters (at least 200)
+    #[serde(default = "default_split_max_chars")]
+    pub max_chars: usize,
+    /// Add `(1/3)`-style numbers to the parts
+    #[serde(default = "default_true")]
+    pub number_parts: bool,
+    /// Publish replies of at least this many characters as a NIP-23 article
+    /// plus a short link message instead (0 = never)
+    #[serde(default)]
+    pub article_chars: usize,
+}
+
+impl Default for NostrSplitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_chars: default_split_max_chars(),
+            number_parts: true,
+            article_chars: 0,
+        }
+    }
+}
+
+fn default_split_max_chars() -> usize {
+    4000
+}
+
+/// Cost anomaly alerts (`[channels_config.nostr.cost_alerts]`).
+///
+/// Every five minutes `state/costs.jsonl` is checked for an hourly spend
+/// spike, runaway tool loops and single costly requests; each finding is
+/// sent to `owner` as a DM, at most once per `cooldown_minutes`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrCostAlertConfig {
+    /// Watch cost records and alert the owner (requires `owner`)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Alert when the last hour's spend exceeds this multiple of the
+    /// trailing hourly average (0 = off)
+    #[serde(default = "default_cost_spike_factor")]
+    pub spike_factor: f64,
+    /// Hours before the last one that make up the trailing average
+    #[serde(default = "default_cost_trailing_hours")]
+    pub trailing_hours: u32,
+    /// Smallest hourly spend in USD that counts as a spike
+    #[serde(default = "default_cost_min_hourly_usd")]
+    pub min_hourly_usd: f64,
+    /// Alert on any single request costing more than this in USD (0 = off)
+    #[serde(default = "default_cost_request_usd")]
+    pub request_usd: f64,
+    /// Alert when one session makes this many tool calls within
+    /// `tool_loop_minutes` (0 = off)
+    #[serde(default = "default_cost_tool_loop_calls")]
+    pub tool_loop_calls: u32,
+    /// Window in minutes for counting a session's tool calls
+    #[serde(default = "default_cost_tool_loop_minutes")]
+    pub tool_loop_minutes: u32,
+    /// Minutes before the same kind of alert (per room or session) repeats
+    #[serde(default = "default_cost_alert_cooldown_minutes")]
+    pub cooldown_minutes: u64,
+}
+
+impl Default for NostrCostAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            spike_factor: default_cost_spike_factor(),
+            trailing_hours: default_cost_trailing_hours(),
+            min_hourly_usd: default_cost_min_hourly_usd(),
+            request_usd: default_cost_request_usd(),
+            tool_loop_calls: default_cost_tool_loop_calls(),
+            tool_loop_minutes: default_cost_tool_loop_minutes(),
+            cooldown_minutes: default_cost_alert_cooldown_minutes(),
+        }
+    }
+}
+
+fn default_cost_spike_factor() -> f64 {
+    3.0
+}
+fn default_cost_trailing_hours() -> u32 {
+    24
+}
+fn default_cost_min_hourly_usd() -> f64 {
+    0.5
+}
+fn default_cost_request_usd() -> f64 {
+    1.0
+}
+fn default_cost_tool_loop_calls() -> u32 {
+    30
+}
+fn default_cost_tool_loop_minutes() -> u32 {
+    10
+}
+fn default_cost_alert_cooldown_minutes() -> u64 {
+    60
+}
+
+/// NIP-05 sender verification (`[channels_config.nostr.nip05]`).
+///
+/// Display names are free text; a `nip05` identifier in a sender's profile
+/// is checked against `https://<domain>/.well-known/nostr.json`, and
+/// verified senders are marked in social memory and the group header.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrNip05Config {
+    /// Resolve and verify the NIP-05 identifiers of senders
+    #[serde(default)]
+    pub enabled: bool,
+    /// Hours before a sender's identifier is checked again
+    #[serde(default = "default_nip05_recheck_hours")]
+    pub recheck_hours: u64,
+    /// Domains whose verified members get the trust below (subdomains included)
+    #[serde(default)]
+    pub trusted_domains: Vec<String>,
+    /// Non-owner actions that members of trusted domains may run even when
+    /// not in `allowed_pubkeys` (`*` = all)
+    #[serde(default)]
+    pub trusted_actions: Vec<String>,
+    /// Leave unknown 64-char hex from members of trusted domains unflagged
+    /// by the key filter (nsecs and key patterns are still redacted)
+    #[serde(default)]
+    pub trust_hex: bool,
+}
+
+impl Default for NostrNip05Config {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            recheck_hours: default_nip05_recheck_hours(),
+            trusted_domains: Vec::new(),
+            trusted_actions: Vec::new(),
+            trust_hex: false,
+        }
+    }
+}
+
+fn default_nip05_recheck_hours() -> u64 {
+    24
+}
+
+/// A privileged pubkey (`[[channels_config.nostr.owners]]`).
+///
+/// `owner` stays the primary owner that receives DMs; these entries add
+/// pubkeys with a role: `owner` (everything), `admin` (HALT, global
+/// control and config changes) or `operator` (stop/resume in a group).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrOwnerConfig {
+    /// Pubkey (hex or npub)
+    pub pubkey: String,
+    /// owner | admin | operator
+    #[serde(default = "default_nostr_owner_role")]
+    pub role: String,
+}
+
+fn default_nostr_owner_role() -> String {
+    "admin".into()
+}
+
+/// Proactive group posts (`[channels_config.nostr.proactive]`).
+///
+/// Each `[[channels_config.nostr.proactive.posts]]` entry fires on a cron
+/// schedule or once a group has been quiet for a while. The agent drafts the
+/// post, the owner approves or rejects it by DM, and approved posts are
+/// published within the per-group frequency caps.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrProactiveConfig {
+    /// Run the configured posts
+    #[serde(default)]
+    pub enabled: bool,
+    /// Hold drafts until the owner approves them (requires `owner`)
+    #[serde(default = "default_true")]
+    pub require_approval: bool,
+    /// Most proactive posts per group per UTC day
+    #[serde(default = "default_proactive_max_per_day")]
+    pub max_per_day: u32,
+    /// Minimum minutes between two proactive posts in the same group
+    #[serde(default = "default_proactive_min_gap_minutes")]
+    pub min_gap_minutes: u64,
+    /// Hours a draft waits for approval before it expires
+    #[serde(default = "default_proactive_approval_timeout_hours")]
+    pub approval_timeout_hours: u64,
+    /// Scheduled and trigger-based posts
+    #[serde(default)]
+    pub posts: Vec<NostrProactivePostConfig>,
+}
+
+impl Default for NostrProactiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            require_approval: true,
+            max_per_day: default_proactive_max_per_day(),
+            min_gap_minutes: default_proactive_min_gap_minutes(),
+            approval_timeout_hours: default_proactive_approval_timeout_hours(),
+            posts: Vec::new(),
+        }
+    }
+}
+
+/// One proactive post (`[[channels_config.nostr.proactive.posts]]`).
+/// Exactly one of `schedule` and `quiet_hours` must be set.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NostrProactivePostConfig {
+    /// Unique name, shown in approval requests
+    pub name: String,
+    /// NIP-29 group to post in
+    pub group: String,
+    /// What the agent should write
+    pub prompt: String,
+    /// Cron expression (e.g. "0 9 * * 1-5")
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// IANA timezone for `schedule` (default: UTC)
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Fire once the group has been quiet this many hours (once per quiet
+    /// stretch)
+    #[serde(default)]
+    pub quiet_hours: Option<u64>,
+}
+
+fn default_proactive_max_per_day() -> u32 {
+    3
+}
+fn default_proactive_min_gap_minutes() -> u64 {
+    120
+}
+fn default_proactive_approval_timeout_hours() -> u64 {
+    24
+}
+
+fn default_publish_coalesce_secs() -> u64 {
+    10
+}
+fn default_publish_max_per_minute() -> u32 {
+    30
+}
+fn default_reconnect_base_secs() -> u64 {
+    2
+}
+fn default_reconnect_max_secs() -> u64 {
+    300
+}
+fn default_reconnect_jitter() -> f64 {
+    0.5
+}
+
+/// A user-defined secret pattern.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KeyFilterPattern {
+    /// Name used in redaction markers, flags and metrics
+    pub name: String,
+    /// Regex matched against message content
+    pub regex: String,
+    /// redact | block | alert_owner
+    #[serde(default = "default_key_filter_action")]
+    pub action: String,
+}
+
+fn default_key_filter_action() -> String {
+    "redact".into()
+}
+
+fn default_answer_cache_ttl() -> u64 {
+    300
+}
+fn default_answer_cache_entries() -> usize {
+    256
+}
+fn default_answer_cache_annotation() -> String {
+    "♻️ Same question was answered {age} ago:".into()
+}
+fn default_answer_cache_similarity() -> f64 {
+    0.92
+}
+fn default_pipeline_priority_capacity() -> usize {
+    1024
+}
+fn default_pipeline_group_capacity() -> usize {
+    256
+}
+fn default_pipeline_group_overflow() -> String {
+    "drop_oldest".into()
+}
+fn default_pipeline_background_capacity() -> usize {
+    64
+}
+fn default_pipeline_background_overflow() -> String {
+    "drop_newest".into()
+}
+fn default_pipeline_lag_warn_ms() -> u64 {
+    5000
+}
+fn default_quota_requests() -> u32 {
+    50
+}
+fn default_quota_cost() -> f64 {
+    0.5
+}
+fn default_quota_message() -> String {
+    "Thanks for your message! You've reached today's usage limit ({reason}), so I'll be back after 00:00 UTC.".into()
+}
+
+fn default_onboarding_mode() -> String {
+    "dm".into()
+}
+fn default_onboarding_template() -> String {
+    "Welcome to #{group}, {name}! I'm the group's assistant; mention me if you need anything."
+        .into()
+}
+
+fn default_digest_schedule() -> String {
+    "0 8 * * *".into()
+}
+fn default_digest_verbosity() -> String {
+    "summary".into()
+}
+
+pub fn default_nostr_relays() -> Vec<String> {
+    vec![
+        "wss://relay.damus.io".to_string(),
+        "wss://nos.lol".to_string(),
+        "wss://relay.primal.net".to_string(),
+        "wss://relay.snort.social".to_string(),
+    ]
+}
+
+// ── ContextVM (MCP over Nostr) ──────────────────────────────────
+
+/// ContextVM (MCP over Nostr) configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ContextVmEntry {
+    /// Enable ContextVM discovery and tool registration.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Nostr relays to connect to for ContextVM.
+    #[serde(default)]
+    pub relays: Vec<String>,
+    /// Optional: only discover tools from these server npubs.
+    #[serde(default)]
+    pub servers: Vec<String>,
+    /// RPC call timeout in seconds (default: 30).
+    #[serde(default = "default_contextvm_timeout")]
+    pub timeout_secs: u64,
+}
+
+pub fn default_contextvm_timeout() -> u64 {
+    30
+}
+
+// ── Personas ────────────────────────────────────────────────────
+
+/// Named personas and the rooms they apply to (`[personas]` section).
+///
+/// Lets one daemon behave differently per Nostr group or DM peer while
+/// sharing the relay connection and cost tracking.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PersonasConfig {
+    /// Persona definitions keyed by persona name.
+    #[serde(default)]
+    pub profiles: std::collections::HashMap<String, PersonaConfig>,
+    /// NIP-29 group ID -> persona name.
+    #[serde(default)]
+    pub groups: std::collections::HashMap<String, String>,
+    /// DM peer pubkey (hex or npub) -> persona name.
+    #[serde(default)]
+    pub pubkeys: std::collections::HashMap<String, String>,
+}
+
+/// A single persona definition (`[personas.profiles.<name>]`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PersonaConfig {
+    /// Persona instructions appended to the base system prompt.
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    /// Provider override (defaults to the active provider).
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Model override (defaults to the active model).
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Prefix for memory session keys. Defaults to `<persona>:`.
+    #[serde(default)]
+    pub memory_prefix: Option<String>,
+    /// Tools this persona may use. Empty = all tools.
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+}
+
+impl PersonasConfig {
+    /// Check that every mapping points at a defined persona.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (section, mapping) in [("groups", &self.groups), ("pubkeys", &self.pubkeys)] {
+            for (key, persona) in mapping {
+                if !self.profiles.contains_key(persona) {
+                    anyhow::bail!(
+                        "personas.{section}.{key} references unknown persona `{persona}`"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ── Tool execution policies ─────────────────────────────────────
+
+/// Tool execution policies for channel conversations
+/// (`[execution_policies]` section).
+///
+/// A policy narrows what tools may do in the conversations it is selected
+/// for: by persona, by room, by channel, or as the default. Conversations no
+/// mapping selects run without a policy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ExecutionPoliciesConfig {
+    /// Policy definitions keyed by policy name.
+    #[serde(default)]
+    pub profiles: std::collections::HashMap<String, ExecutionPolicyConfig>,
+    /// Persona name -> policy name.
+    #[serde(default)]
+    pub personas: std::collections::HashMap<String, String>,
+    /// Room -> policy name. Keys are NIP-29 group IDs or a channel's reply
+    /// target (for Nostr DMs, the peer's hex pubkey).
+    #[serde(default)]
+    pub rooms: std::collections::HashMap<String, String>,
+    /// Channel name (e.g. `nostr`, `telegram`) -> policy name.
+    #[serde(default)]
+    pub channels: std::collections::HashMap<String, String>,
+    /// Policy for channel conversations no other mapping selects.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// A single policy (`[execution_policies.profiles.<name>]`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ExecutionPolicyConfig {
+    /// Tools the policy allows (`"*"` = all). Empty = all tools.
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// Tools the policy denies, applied after `allowed_tools`.
+    #[serde(default)]
+    pub denied_tools: Vec<String>,
+    /// Regexes matched against each call's JSON arguments; a match blocks
+    /// the call.
+    #[serde(default)]
+    pub denied_argument_patterns: Vec<String>,
+    /// Directories path arguments must stay inside, absolute or relative to
+    /// the workspace. Empty = no extra restriction.
+    #[serde(default)]
+    pub allowed_paths: Vec<String>,
+    /// Longest a single tool call may run, in seconds. Unset = no limit.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl ExecutionPoliciesConfig {
+    /// Check that every mapping points at a defined policy and every pattern
+    /// compiles.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (name, policy) in &self.profiles {
+            for pattern in &policy.denied_argument_patterns {
+                if let Err(err) = regex::Regex::new(pattern) {
+                    anyhow::bail!(
+                        "execution_policies.profiles.{name}: invalid argument pattern `{pattern}`: {err}"
+                    );
+                }
+            }
+            if policy.timeout_secs == Some(0) {
+                anyhow::bail!(
+                    "execution_policies.profiles.{name}.timeout_secs must be greater than 0"
+                );
+            }
+        }
+        for (section, mapping) in [
+            ("personas", &self.personas),
+            ("rooms", &self.rooms),
+            ("channels", &self.channels),
+        ] {
+            for (key, policy) in mapping {
+                if !self.profiles.contains_key(policy) {
+                    anyhow::bail!(
+                        "execution_policies.{section}.{key} references unknown policy `{policy}`"
+                    );
+                }
+            }
+        }
+        if let Some(policy) = &self.default {
+            if !self.profiles.contains_key(policy) {
+                anyhow::bail!("execution_policies.default references unknown policy `{policy}`");
+            }
+        }
+        Ok(())
+    }
+}
+
+// ── Model parameter profiles ────────────────────────────────────
+
+/// Named generation parameter profiles (`[model_profiles]` section).
+///
+/// A profile overrides temperature, max output tokens and reasoning effort
+/// for the messages it is selected for: by query classification hint, by the
+/// channel's dynamic config (NIP-78 `model_profile` for Nostr), by group, or
+/// as the default. Unset values keep the global settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ModelProfilesConfig {
+    /// Profile definitions keyed by profile name.
+    #[serde(default)]
+    pub profiles: std::collections::HashMap<String, ModelProfileConfig>,
+    /// Query classification hint -> profile name.
+    #[serde(default)]
+    pub hints: std::collections::HashMap<String, String>,
+    /// Group ID (NIP-29 group, or a channel's reply target) -> profile name.
+    #[serde(default)]
+    pub groups: std::collections::HashMap<String, String>,
+    /// Profile for messages no other mapping selects.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// A single profile (`[model_profiles.profiles.<name>]`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ModelProfileConfig {
+    /// Sampling temperature (0.0-2.0).
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    /// Cap on output tokens per provider request.
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    /// Reasoning effort for providers that support it ("minimal" | "low" | "medium" | "high" | "xhigh").
+    #[serde(default)]
+    pub reasoning_level: Option<String>,
+}
+
+impl ModelProfilesConfig {
+    /// Check that every mapping points at a defined profile and values are in range.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (name, profile) in &self.profiles {
+            if let Some(t) = profile.temperature {
+                if !(0.0..=2.0).contains(&t) {
+                    anyhow::bail!(
+                        "model_profiles.profiles.{name}.temperature must be between 0.0 and 2.0"
+                    );
+                }
+            }
+            if profile.max_tokens == Some(0) {
+                anyhow::bail!("model_profiles.profiles.{name}.max_tokens must be greater than 0");
+            }
+        }
+        for (section, mapping) in [("hints", &self.hints), ("groups", &self.groups)] {
+            for (key, profile) in mapping {
+                if !self.profiles.contains_key(profile) {
+                    anyhow::bail!(
+                        "model_profiles.{section}.{key} references unknown profile `{profile}`"
+                    );
+                }
+            }
+        }
+        if let Some(profile) = &self.default {
+            if !self.profiles.contains_key(profile) {
+                anyhow::bail!("model_profiles.default references unknown profile `{profile}`");
+            }
+        }
+        Ok(())
+    }
+}
+
+// ── Complexity/budget model router ──────────────────────────────
+
+/// Tier names accepted as `[model_router.routes]` keys.
+pub const MODEL_ROUTER_TIERS: [&str; 5] = ["ack", "standard", "complex", "tool", "economy"];
+
+/// Complexity and budget based model routing (`[model_router]` section).
+///
+/// Messages that no `[query_classification]` rule matches are sorted into
+/// tiers — short acknowledgements, standard questions, complex questions and
+/// tool-heavy tasks — and sent to the `[[model_routes]]` hint mapped for the
+/// tier. When the remaining daily or monthly `[cost]` budget drops below
+/// `low_budget_percent`, non-ack tiers use the `economy` route instead.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ModelRouterConfig {
+    /// Enable the router. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Messages up to this many characters without a question mark are acks.
+    #[serde(default = "default_router_ack_max_chars")]
+    pub ack_max_chars: usize,
+    /// Messages of at least this many characters are complex.
+    #[serde(default = "default_router_complex_min_chars")]
+    pub complex_min_chars: usize,
+    /// Case-insensitive words/phrases marking a tool-heavy task.
+    #[serde(default = "default_router_tool_keywords")]
+    pub tool_keywords: Vec<String>,
+    /// Remaining budget (percent of the daily or monthly limit) below which
+    /// the `economy` route is used. `0` disables budget routing.
+    #[serde(default = "default_router_low_budget_percent")]
+    pub low_budget_percent: u8,
+    /// Tier name -> `[[model_routes]]` hint. Unmapped tiers use the default route.
+    #[serde(default)]
+    pub routes: std::collections::HashMap<String, String>,
+}
+
+impl Default for ModelRouterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ack_max_chars: default_router_ack_max_chars(),
+            complex_min_chars: default_router_complex_min_chars(),
+            tool_keywords: default_router_tool_keywords(),
+            low_budget_percent: default_router_low_budget_percent(),
+            routes: std::collections::HashMap::new(),
+        }
+    }
+}
+
+impl ModelRouterConfig {
+    /// Check tier names and that every route hint exists in `[[model_routes]]`.
+    pub fn validate(&self, model_routes: &[crate::config::ModelRouteConfig]) -> anyhow::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        for (tier, hint) in &self.routes {
+            if !MODEL_ROUTER_TIERS.contains(&tier.as_str()) {
+                anyhow::bail!(
+                    "model_router.routes.{tier} is not a tier (expected one of: {})",
+                    MODEL_ROUTER_TIERS.join(", ")
+                );
+            }
+            if !model_routes.iter().any(|r| &r.hint == hint) {
+                anyhow::bail!(
+                    "model_router.routes.{tier} references unknown model_routes hint `{hint}`"
+                );
+            }
+        }
+        if self.low_budget_percent > 100 {
+            anyhow::bail!("model_router.low_budget_percent must be between 0 and 100");
+        }
+        Ok(())
+    }
+}
+
+fn default_router_ack_max_chars() -> usize {
+    24
+}
+fn default_router_complex_min_chars() -> usize {
+    400
+}
+fn default_router_tool_keywords() -> Vec<String> {
+    [
+        "search",
+        "look up",
+        "fetch",
+        "browse",
+        "download",
+        "run",
+        "execute",
+        "install",
+        "deploy",
+        "schedule",
+        "remind me",
+        "create a file",
+        "edit the file",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+fn default_router_low_budget_percent() -> u8 {
+    20
+}
+
+// ── Reply latency budget ────────────────────────────────────────
+
+/// Per-stage latency budget for channel replies (`[channels_config.latency]`).
+///
+/// `message_timeout_secs` caps a whole reply; these timeouts cut off single
+/// stages so one slow step cannot hold the reply for minutes. A slow memory
+/// recall is skipped (and recall pauses for `recall_cooldown_secs`), a slow
+/// tool call returns an error the model can work around, a slow LLM call
+/// fails the turn and a slow send is abandoned. A timeout of `0` leaves that
+/// stage unlimited. Per-stage latency is reported as the `reply_latency`
+/// health component.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LatencyBudgetConfig {
+    /// Enforce the stage timeouts and record stage latency
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds for context assembly (memory recall) before the reply goes
+    /// out without memory context
+    #[serde(default = "default_latency_context_secs")]
+    pub context_secs: u64,
+    /// Seconds for a single LLM call
+    #[serde(default = "default_latency_llm_secs")]
+    pub llm_secs: u64,
+    /// Seconds for a single tool call
+    #[serde(default = "default_latency_tool_secs")]
+    pub tool_secs: u64,
+    /// Seconds for sending the reply on the channel
+    #[serde(default = "default_latency_publish_secs")]
+    pub publish_secs: u64,
+    /// Seconds memory recall is skipped after it exceeded `context_secs`
+    #[serde(default = "default_latency_recall_cooldown_secs")]
+    pub recall_cooldown_secs: u64,
 }
 
-impl ChannelConfig for NostrConfig {
-    fn name() -> &'static str {
-        "Nostr"
-    }
-    fn desc() -> &'static str {
-        "Nostr DMs"
+impl Default for LatencyBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            context_secs: default_latency_context_secs(),
+            llm_secs: default_latency_llm_secs(),
+            tool_secs: default_latency_tool_secs(),
+            publish_secs: default_latency_publish_secs(),
+            recall_cooldown_secs: default_latency_recall_cooldown_secs(),
+        }
     }
 }
 
-fn default_respond_mode() -> String {
-    "mention_only".into()
+fn default_latency_context_secs() -> u64 {
+    5
 }
-fn default_context_history() -> usize {
-    10
+fn default_latency_llm_secs() -> u64 {
+    120
 }
-fn default_true() -> bool {
-    true
+fn default_latency_tool_secs() -> u64 {
+    60
+}
+fn default_latency_publish_secs() -> u64 {
+    20
+}
+fn default_latency_recall_cooldown_secs() -> u64 {
+    300
 }
 
-pub fn default_nostr_relays() -> Vec<String> {
-    vec![
-        "wss://relay.damus.io".to_string(),
-        "wss://nos.lol".to_string(),
-        "wss://relay.primal.net".to_string(),
-        "wss://relay.snort.social".to_string(),
-    ]
+// ── Stats ───────────────────────────────────────────────────────
+
+/// Token usage stats (`[stats]`).
+///
+/// Day, week and month ranges of `snowclaw stats` follow local dates in
+/// `timezone`; `--tz` overrides it for one run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct StatsConfig {
+    /// IANA timezone for day boundaries (default: UTC)
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// First day of a `--period week`: "monday" (default) | "sunday"
+    #[serde(default)]
+    pub week_start: WeekStart,
 }
 
-// ── ContextVM (MCP over Nostr) ──────────────────────────────────
+impl StatsConfig {
+    /// Check that `timezone` names an IANA zone.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(tz) = &self.timezone {
+            if tz.parse::<chrono_tz::Tz>().is_err() {
+                anyhow::bail!("stats.timezone is not an IANA timezone: {tz}");
+            }
+        }
+        Ok(())
+    }
+}
 
-/// ContextVM (MCP over Nostr) configuration.
+/// First day of a stats week.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+impl WeekStart {
+    pub fn weekday(self) -> chrono::Weekday {
+        match self {
+            Self::Monday => chrono::Weekday::Mon,
+            Self::Sunday => chrono::Weekday::Sun,
+        }
+    }
+}
+
+// ── Update ──────────────────────────────────────────────────────
+
+/// Release signature checks for remote updates (`[update]`).
+///
+/// `control.update` only installs a release whose `SHA256SUMS` carries a
+/// valid minisign signature (`SHA256SUMS.minisig`) by this key, or by the
+/// key built into the binary when unset.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateConfig {
+    /// Minisign public key (the base64 line of `minisign.pub`)
+    #[serde(default)]
+    pub signing_key: Option<String>,
+}
+
+// ── Deterministic seed mode ─────────────────────────────────────
+
+/// Reproducible runs for tests and bug reports (`[determinism]`).
+///
+/// While enabled, non-security randomness (retry and reconnect jitter,
+/// ack reaction sampling and picks) comes from streams seeded by `seed`,
+/// the default sampling temperature is `temperature`, and the cron
+/// scheduler runs on a mock clock ([`crate::determinism::now`]). Keys,
+/// tokens and OTP secrets always use the OS random source.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
-pub struct ContextVmEntry {
-    /// Enable ContextVM discovery and tool registration.
+pub struct DeterminismConfig {
+    /// Enable seed mode. Also enabled by `ZEROCLAW_SEED`.
     #[serde(default)]
     pub enabled: bool,
-    /// Nostr relays to connect to for ContextVM.
+    /// Seed for every random stream
     #[serde(default)]
-    pub relays: Vec<String>,
-    /// Optional: only discover tools from these server npubs.
+    pub seed: u64,
+    /// Start of the mock clock (Unix seconds); unset = wall clock at startup
     #[serde(default)]
-    pub servers: Vec<String>,
-    /// RPC call timeout in seconds (default: 30).
-    #[serde(default = "default_contextvm_timeout")]
-    pub timeout_secs: u64,
+    pub clock_start: Option<i64>,
+    /// Default sampling temperature while enabled
+    #[serde(default)]
+    pub temperature: f64,
 }
 
-pub fn default_contextvm_timeout() -> u64 {
-    30
+impl Default for DeterminismConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seed: 0,
+            clock_start: None,
+            temperature: 0.0,
+        }
+    }
 }
 
 // ── MCP server entry (alternative/simplified representation) ────
@@ -144,6 +1880,11 @@ pub fn default_index_interval_minutes() -> u64 {
     30
 }
 
+/// Default overlap between document chunks, in tokens.
+pub fn default_chunk_overlap_tokens() -> usize {
+    64
+}
+
 /// Default for cost tracking (enabled in snowclaw).
 pub fn default_cost_enabled() -> bool {
     true
@@ -182,6 +1923,9 @@ pub struct CollectiveMemoryConfig {
     /// Relay URLs for publishing/subscribing to collective memories.
     #[serde(default)]
     pub relay_urls: Vec<String>,
+    /// Relays per memory tier; tiers without a route use `relay_urls`.
+    #[serde(default)]
+    pub relay_routes: CollectiveRelayRoutesConfig,
     /// Source trust preferences for ranking.
     #[serde(default)]
     pub source_preferences: Vec<CollectiveSourceEntry>,
@@ -197,6 +1941,396 @@ pub struct CollectiveMemoryConfig {
     /// Tier 4 model patterns (lowest capability).
     #[serde(default)]
     pub tier4: Vec<String>,
+    /// Spaced re-validation of stale shared memories.
+    #[serde(default)]
+    pub review: CollectiveReviewConfig,
+    /// Extraction of facts from indexed messages into memories.
+    #[serde(default)]
+    pub distill: CollectiveDistillConfig,
+    /// Archive tier for memories nobody has used in months.
+    #[serde(default)]
+    pub archive: CollectiveArchiveConfig,
+    /// Background conflict scan after relay sync.
+    #[serde(default)]
+    pub conflicts: CollectiveConflictsConfig,
+    /// Per-group isolation of memories learned in group chats.
+    #[serde(default)]
+    pub groups: CollectiveGroupsConfig,
+    /// Multi-relay acknowledgment for important memories.
+    #[serde(default)]
+    pub quorum: CollectiveQuorumConfig,
+    /// Trust learned from conflict outcomes and owner corrections.
+    #[serde(default)]
+    pub reputation: CollectiveReputationConfig,
+    /// Ranking boost for memories that keep being recalled.
+    #[serde(default)]
+    pub usage: CollectiveUsageConfig,
+}
+
+/// Per-group memory namespaces (`[memory.collective.groups]`).
+///
+/// Memories stored while answering in a group are scoped to that group's
+/// namespace, and recall in a group only returns public memories and the
+/// group's own. Groups listed in `shared` are exempt: their memories keep
+/// their default tier and their namespace is visible in every conversation.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CollectiveGroupsConfig {
+    /// Scope memories learned in a group to that group
+    #[serde(default = "default_true")]
+    pub isolate: bool,
+    /// Group ids whose memories are shared with every conversation
+    #[serde(default)]
+    pub shared: Vec<String>,
+}
+
+impl Default for CollectiveGroupsConfig {
+    fn default() -> Self {
+        Self {
+            isolate: true,
+            shared: Vec::new(),
+        }
+    }
+}
+
+impl CollectiveGroupsConfig {
+    /// Whether `group_id` is listed as a shared namespace.
+    pub fn is_shared(&self, group_id: &str) -> bool {
+        self.shared.iter().any(|g| g == group_id)
+    }
+}
+
+/// Per-tier relay routing (`[memory.collective.relay_routes]`).
+///
+/// Memories of a routed tier are published to that tier's relays instead of
+/// `relay_urls`. With `fallback`, the remaining `relay_urls` are used when
+/// none of the routed relays accepts the event.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CollectiveRelayRoutesConfig {
+    /// Relays for private memories
+    #[serde(default)]
+    pub private: Vec<String>,
+    /// Relays for group memories
+    #[serde(default)]
+    pub group: Vec<String>,
+    /// Relays for public memories
+    #[serde(default)]
+    pub public: Vec<String>,
+    /// Fall back to `relay_urls` when the routed relays are down
+    #[serde(default = "default_true")]
+    pub fallback: bool,
+}
+
+impl Default for CollectiveRelayRoutesConfig {
+    fn default() -> Self {
+        Self {
+            private: Vec::new(),
+            group: Vec::new(),
+            public: Vec::new(),
+            fallback: true,
+        }
+    }
+}
+
+/// Quorum publishing (`[memory.collective.quorum]`).
+///
+/// Memories at `min_tier` or wider must be acknowledged (relay `OK`) by
+/// `min_acks` relays. Relays that did not acknowledge are retried
+/// `retries` times, `retry_delay_secs` apart. Until quorum is reached the
+/// local copy carries the `provisional` tag.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CollectiveQuorumConfig {
+    /// Require quorum for memories at or above `min_tier`
+    #[serde(default)]
+    pub enabled: bool,
+    /// Relays that must acknowledge; capped at the number of relays
+    #[serde(default = "default_quorum_min_acks")]
+    pub min_acks: usize,
+    /// Narrowest tier that needs quorum: `private`, `group` or `public`
+    #[serde(default = "default_quorum_min_tier")]
+    pub min_tier: String,
+    /// Retry rounds on relays that did not acknowledge
+    #[serde(default = "default_quorum_retries")]
+    pub retries: u32,
+    /// Seconds between retry rounds
+    #[serde(default = "default_quorum_retry_delay_secs")]
+    pub retry_delay_secs: u64,
+}
+
+impl Default for CollectiveQuorumConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_acks: default_quorum_min_acks(),
+            min_tier: default_quorum_min_tier(),
+            retries: default_quorum_retries(),
+            retry_delay_secs: default_quorum_retry_delay_secs(),
+        }
+    }
+}
+
+fn default_quorum_min_acks() -> usize {
+    2
+}
+fn default_quorum_min_tier() -> String {
+    "group".to_string()
+}
+fn default_quorum_retries() -> u32 {
+    3
+}
+fn default_quorum_retry_delay_secs() -> u64 {
+    30
+}
+
+/// Source reputation learning (`[memory.collective.reputation]`).
+///
+/// Each time a source loses a conflict or the owner corrects one of its
+/// topics, its trust is multiplied by `decay`, down to `floor` times the
+/// configured `source_preferences` trust. Each conflict it wins gives back
+/// `recovery`, up to the configured trust. Reputation is kept in the
+/// collective DB and reported by `snowclaw memory reputation`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CollectiveReputationConfig {
+    /// Learn source reputation and apply it to ranking
+    #[serde(default)]
+    pub enabled: bool,
+    /// Trust multiplier per lost conflict or owner correction
+    #[serde(default = "default_reputation_decay")]
+    pub decay: f64,
+    /// Lowest share of the configured trust a source can fall to
+    #[serde(default = "default_reputation_floor")]
+    pub floor: f64,
+    /// Share of the configured trust restored per won conflict
+    #[serde(default = "default_reputation_recovery")]
+    pub recovery: f64,
+}
+
+impl Default for CollectiveReputationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            decay: default_reputation_decay(),
+            floor: default_reputation_floor(),
+            recovery: default_reputation_recovery(),
+        }
+    }
+}
+
+fn default_reputation_decay() -> f64 {
+    0.8
+}
+fn default_reputation_floor() -> f64 {
+    0.2
+}
+fn default_reputation_recovery() -> f64 {
+    0.05
+}
+
+/// Usage-based ranking (`[memory.collective.usage]`).
+///
+/// Every recall into a prompt bumps a memory's access count and last-used
+/// time in the collective DB. Ranking multiplies a used memory's score by up
+/// to `1 + weight`: the boost grows with the access count and halves for
+/// every `half_life_days` the memory goes unused.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CollectiveUsageConfig {
+    /// Largest ranking boost from usage (`0.0` ignores usage)
+    #[serde(default = "default_usage_weight")]
+    pub weight: f64,
+    /// Days after which the boost of an unused memory halves (`0` = no decay)
+    #[serde(default = "default_usage_half_life_days")]
+    pub half_life_days: u64,
+}
+
+impl Default for CollectiveUsageConfig {
+    fn default() -> Self {
+        Self {
+            weight: default_usage_weight(),
+            half_life_days: default_usage_half_life_days(),
+        }
+    }
+}
+
+fn default_usage_weight() -> f64 {
+    0.25
+}
+fn default_usage_half_life_days() -> u64 {
+    30
+}
+
+/// Background conflict scanner (`[memory.collective.conflicts]`).
+///
+/// After each relay sync, topics with memories created in the last
+/// `window_hours` are checked for disagreeing sources. New conflicts are
+/// recorded in the collective DB, sent to `owner` by DM with both versions,
+/// and optionally published as a `snow:conflict:<topic>` NIP-78 event.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CollectiveConflictsConfig {
+    /// Scan for conflicts after each relay sync
+    #[serde(default)]
+    pub enabled: bool,
+    /// Only topics with a memory created this many hours back are scanned
+    #[serde(default = "default_conflict_window_hours")]
+    pub window_hours: u64,
+    /// Owner pubkey (hex or npub) notified by DM about new conflicts
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Publish a machine-readable conflict notice for other agents
+    #[serde(default)]
+    pub publish: bool,
+}
+
+impl Default for CollectiveConflictsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_hours: default_conflict_window_hours(),
+            owner: None,
+            publish: false,
+        }
+    }
+}
+
+fn default_conflict_window_hours() -> u64 {
+    168
+}
+
+/// Archive tier for old collective memories (`[memory.collective.archive]`).
+///
+/// Memories neither stored nor returned by recall for `after_months` are
+/// moved out of the search index into a compact archive table. Default
+/// recall skips them; an explicit archive search or newer information on
+/// the same topic moves them back.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CollectiveArchiveConfig {
+    /// Archive untouched memories when the store opens and after relay sync
+    #[serde(default)]
+    pub enabled: bool,
+    /// Months without use before a memory is archived
+    #[serde(default = "default_archive_after_months")]
+    pub after_months: u32,
+}
+
+impl Default for CollectiveArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            after_months: default_archive_after_months(),
+        }
+    }
+}
+
+fn default_archive_after_months() -> u32 {
+    6
+}
+
+/// Spaced review of stale collective memories (`[memory.collective.review]`).
+///
+/// Public and group memories older than `stale_after_days` are re-checked by
+/// the LLM against recent indexed messages and documents. Each memory is then
+/// refreshed, demoted one tier, or marked superseded, and the new version is
+/// published as a NIP-78 event. A memory confirmed `n` times in a row waits
+/// `stale_after_days * 2^n` days before its next review.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CollectiveReviewConfig {
+    /// Run the review job in the daemon
+    #[serde(default)]
+    pub enabled: bool,
+    /// Minutes between review passes
+    #[serde(default = "default_review_interval_minutes")]
+    pub interval_minutes: u64,
+    /// Age in days after which a memory is due for its first review
+    #[serde(default = "default_review_stale_after_days")]
+    pub stale_after_days: u64,
+    /// Memories reviewed per pass
+    #[serde(default = "default_review_batch_size")]
+    pub batch_size: usize,
+    /// Indexed messages and document chunks shown to the LLM per memory
+    #[serde(default = "default_review_evidence_limit")]
+    pub evidence_limit: usize,
+    /// Model for review calls (default: `default_model`)
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+impl Default for CollectiveReviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: default_review_interval_minutes(),
+            stale_after_days: default_review_stale_after_days(),
+            batch_size: default_review_batch_size(),
+            evidence_limit: default_review_evidence_limit(),
+            model: None,
+        }
+    }
+}
+
+fn default_review_interval_minutes() -> u64 {
+    360
+}
+fn default_review_stale_after_days() -> u64 {
+    30
+}
+fn default_review_batch_size() -> usize {
+    5
+}
+fn default_review_evidence_limit() -> usize {
+    8
+}
+
+/// Distillation of indexed messages into memories (`[memory.collective.distill]`).
+///
+/// Each pass reads the messages indexed in `social.db` since the previous
+/// pass, asks the LLM for durable facts and preferences in the substantial
+/// ones, and stores those not already known as collective memories. Facts
+/// from a group get that group's tier, facts from direct messages are
+/// private. Each memory records the ids of the events it came from.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CollectiveDistillConfig {
+    /// Run the distillation job in the daemon
+    #[serde(default)]
+    pub enabled: bool,
+    /// Minutes between distillation passes
+    #[serde(default = "default_distill_interval_minutes")]
+    pub interval_minutes: u64,
+    /// Indexed messages read per pass
+    #[serde(default = "default_distill_batch_size")]
+    pub batch_size: usize,
+    /// Messages shorter than this many characters are skipped
+    #[serde(default = "default_distill_min_chars")]
+    pub min_chars: usize,
+    /// Facts kept per conversation per pass
+    #[serde(default = "default_distill_max_facts")]
+    pub max_facts: usize,
+    /// Model for extraction calls (default: `default_model`)
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+impl Default for CollectiveDistillConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: default_distill_interval_minutes(),
+            batch_size: default_distill_batch_size(),
+            min_chars: default_distill_min_chars(),
+            max_facts: default_distill_max_facts(),
+            model: None,
+        }
+    }
+}
+
+fn default_distill_interval_minutes() -> u64 {
+    60
+}
+fn default_distill_batch_size() -> usize {
+    50
+}
+fn default_distill_min_chars() -> usize {
+    80
+}
+fn default_distill_max_facts() -> usize {
+    5
 }
 
 fn default_collective_db_path() -> String {
@@ -209,16 +2343,43 @@ impl Default for CollectiveMemoryConfig {
             enabled: false,
             db_path: default_collective_db_path(),
             relay_urls: vec![],
+            relay_routes: CollectiveRelayRoutesConfig::default(),
             source_preferences: vec![],
             tier1: vec![],
             tier2: vec![],
             tier3: vec![],
             tier4: vec![],
+            review: CollectiveReviewConfig::default(),
+            distill: CollectiveDistillConfig::default(),
+            archive: CollectiveArchiveConfig::default(),
+            conflicts: CollectiveConflictsConfig::default(),
+            groups: CollectiveGroupsConfig::default(),
+            quorum: CollectiveQuorumConfig::default(),
+            reputation: CollectiveReputationConfig::default(),
+            usage: CollectiveUsageConfig::default(),
         }
     }
 }
 
 impl CollectiveMemoryConfig {
+    /// `relay_urls` followed by routed relays not already listed.
+    pub fn all_relay_urls(&self) -> Vec<String> {
+        let routes = &self.relay_routes;
+        let mut urls: Vec<String> = Vec::new();
+        for url in self
+            .relay_urls
+            .iter()
+            .chain(&routes.private)
+            .chain(&routes.group)
+            .chain(&routes.public)
+        {
+            if !urls.contains(url) {
+                urls.push(url.clone());
+            }
+        }
+        urls
+    }
+
     /// Convert to the snow-memory crate's `MemoryConfig` for ranking.
     pub fn to_snow_memory_config(&self) -> snow_memory::config::MemoryConfig {
         let sm_defaults = snow_memory::config::MemoryConfig::default();
@@ -252,12 +2413,75 @@ impl CollectiveMemoryConfig {
             } else {
                 self.tier4.clone()
             },
-            relays_public: self.relay_urls.clone(),
-            relays_group: vec![],
+            relays_public: self.routed(&self.relay_routes.public),
+            relays_group: self.routed(&self.relay_routes.group),
+            relays_private: self.routed(&self.relay_routes.private),
+            usage_weight: self.usage.weight,
+            usage_half_life_days: self.usage.half_life_days,
+        }
+    }
+
+    /// A tier's routed relays, or `relay_urls` when the tier has no route.
+    fn routed(&self, route: &[String]) -> Vec<String> {
+        if route.is_empty() {
+            self.relay_urls.clone()
+        } else {
+            route.to_vec()
+        }
+    }
+}
+
+/// What the agent may remember (`[memory.write_policy]`).
+///
+/// Checked before every `Memory::store` and before agent-written social
+/// notes are saved and published as NIP-78 events. A write is refused in a
+/// `no_memory_groups` group or when its key or content matches a banned
+/// pattern; otherwise its category decides: `auto_store` categories are
+/// stored, `confirm` categories wait for the owner (`/memory approve`,
+/// `snowclaw memory approve`) and any other category is refused.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MemoryWritePolicyConfig {
+    /// Enforce the policy
+    #[serde(default)]
+    pub enabled: bool,
+    /// Categories stored without asking (`core`, `daily`, `conversation` or
+    /// a custom category name)
+    #[serde(default = "default_write_policy_auto_store")]
+    pub auto_store: Vec<String>,
+    /// Categories held until the owner approves them
+    #[serde(default)]
+    pub confirm: Vec<String>,
+    /// Regexes; a write whose key or content matches one is refused
+    #[serde(default)]
+    pub banned_patterns: Vec<String>,
+    /// Group ids (with or without `#`) where nothing is remembered
+    #[serde(default)]
+    pub no_memory_groups: Vec<String>,
+    /// Hours a write waits for the owner before it is dropped
+    #[serde(default = "default_write_policy_pending_hours")]
+    pub pending_hours: u64,
+}
+
+impl Default for MemoryWritePolicyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            auto_store: default_write_policy_auto_store(),
+            confirm: vec![],
+            banned_patterns: vec![],
+            no_memory_groups: vec![],
+            pending_hours: default_write_policy_pending_hours(),
         }
     }
 }
 
+fn default_write_policy_auto_store() -> Vec<String> {
+    vec!["core".into(), "daily".into(), "conversation".into()]
+}
+fn default_write_policy_pending_hours() -> u64 {
+    72
+}
+
 // ── Browser pinchtab extension ──────────────────────────────────
 
 /// Default Pinchtab HTTP API base URL.
@@ -0,0 +1,115 @@
+//! Task-scoped cost attribution.
+//!
+//! Channel message handlers wrap the tool loop in [`scope`] so that usage
+//! recorded by the cost observer can be attributed to the originating
+//! channel, room, persona and model route without threading context through providers.
+//!
+//! The scope also collects tool latency: the tool loop reports each call
+//! with [`record_tool_latency`], the tool result cache reports lookups with
+//! [`record_tool_cache`], and the next usage record of the task takes the
+//! pending calls with [`take_tool_latency`].
+
+use super::types::ToolLatency;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::Duration;
+
+/// Who a provider call should be billed to in cost records.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CostAttribution {
+    pub channel: Option<String>,
+    pub room: Option<String>,
+    pub persona: Option<String>,
+    /// Model route label, e.g. `complex -> reasoning`.
+    pub route: Option<String>,
+}
+
+tokio::task_local! {
+    static CURRENT_ATTRIBUTION: CostAttribution;
+    static PENDING_TOOL_LATENCY: RefCell<BTreeMap<String, ToolLatency>>;
+}
+
+/// Run `fut` with `attribution` applied to any usage it records.
+pub async fn scope<F: Future>(attribution: CostAttribution, fut: F) -> F::Output {
+    CURRENT_ATTRIBUTION
+        .scope(
+            attribution,
+            PENDING_TOOL_LATENCY.scope(RefCell::default(), fut),
+        )
+        .await
+}
+
+/// Attribution of the current task, if it runs inside [`scope`].
+pub fn current() -> Option<CostAttribution> {
+    CURRENT_ATTRIBUTION.try_with(Clone::clone).ok()
+}
+
+/// Count a `tool` call that took `duration` toward the next usage record of
+/// the current task. Does nothing outside [`scope`].
+pub fn record_tool_latency(tool: &str, duration: Duration) {
+    let _ = PENDING_TOOL_LATENCY.try_with(|pending| {
+        pending
+            .borrow_mut()
+            .entry(tool.to_string())
+            .or_default()
+            .record(duration);
+    });
+}
+
+/// Count a `tool` result cache lookup toward the next usage record of the
+/// current task. Does nothing outside [`scope`].
+pub fn record_tool_cache(tool: &str, hit: bool) {
+    let _ = PENDING_TOOL_LATENCY.try_with(|pending| {
+        pending
+            .borrow_mut()
+            .entry(tool.to_string())
+            .or_default()
+            .record_cache(hit);
+    });
+}
+
+/// Tool calls recorded since the last take, by tool name.
+pub fn take_tool_latency() -> BTreeMap<String, ToolLatency> {
+    PENDING_TOOL_LATENCY
+        .try_with(|pending| std::mem::take(&mut *pending.borrow_mut()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn current_is_scoped_to_task() {
+        assert!(current().is_none());
+        let attribution = CostAttribution {
+            channel: Some("nostr".into()),
+            room: Some("#dev".into()),
+            persona: Some("reviewer".into()),
+            route: Some("complex -> reasoning".into()),
+        };
+        let seen = scope(attribution.clone(), async { current() }).await;
+        assert_eq!(seen, Some(attribution));
+    }
+
+    #[tokio::test]
+    async fn tool_latency_is_taken_once() {
+        record_tool_latency("shell", Duration::from_millis(5));
+        assert!(take_tool_latency().is_empty());
+
+        let (first, second) = scope(CostAttribution::default(), async {
+            record_tool_latency("shell", Duration::from_millis(40));
+            record_tool_latency("web_fetch", Duration::from_millis(900));
+            record_tool_latency("shell", Duration::from_millis(10));
+            record_tool_cache("web_fetch", false);
+            (take_tool_latency(), take_tool_latency())
+        })
+        .await;
+        assert_eq!(first["shell"].calls, 2);
+        assert_eq!(first["shell"].total_ms, 50);
+        assert_eq!(first["web_fetch"].max_ms, 900);
+        assert_eq!(first["web_fetch"].cache_misses, 1);
+        assert!(second.is_empty());
+    }
+}
@@ -1,4 +1,6 @@
+pub mod attribution;
 pub mod pricing;
+pub mod stream;
 pub mod tracker;
 pub mod types;
 
@@ -8,5 +10,5 @@ pub use tracker::CostTracker;
 #[allow(unused_imports)]
 pub use types::{
     BudgetCheck, ChannelStats, CostRecord, CostSummary, ModelStats, PromptBreakdown, RoomStats,
-    TokenBreakdown, TokenUsage, UsageBreakdown, UsagePeriod,
+    TokenBreakdown, TokenUsage, ToolLatency, UsageBreakdown, UsagePeriod,
 };
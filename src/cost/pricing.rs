@@ -1,3 +1,334 @@
+//! Model pricing lookup.
+//!
+//! Rates come from, in order: the user-editable pricing file
+//! (`pricing.toml` next to `config.toml`), `[cost.prices]`, the built-in
+//! table in [`model_pricing`], and finally `[cost].unknown_model_pricing`.
+//! The pricing file is re-read when its mtime changes, so new models can be
+//! costed without a restart or a crate release.
+
+use crate::config::schema::{CostConfig, UnknownModelPricing};
+use anyhow::{Context, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::TokenUsage;
+
+pub const PRICING_FILE_NAME: &str = "pricing.toml";
+
+/// How often lookups may stat the pricing file for changes.
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Conservative rates charged for unknown models (USD per 1M tokens).
+const CONSERVATIVE_RATES: ModelRates = ModelRates {
+    input: 3.0,
+    output: 15.0,
+    cache_read: None,
+    cache_write: None,
+};
+
+/// Per-model rates in USD per 1M tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelRates {
+    pub input: f64,
+    pub output: f64,
+    /// Prompt-cache read rate; cache reads are charged at `input` when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+    /// Prompt-cache write rate; cache writes are charged at `input` when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write: Option<f64>,
+}
+
+impl ModelRates {
+    const ZERO: Self = Self {
+        input: 0.0,
+        output: 0.0,
+        cache_read: None,
+        cache_write: None,
+    };
+
+    /// Price one call at these rates, including prompt-cache tokens.
+    pub fn usage(
+        &self,
+        model: impl Into<String>,
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_read_tokens: Option<u64>,
+        cache_write_tokens: Option<u64>,
+    ) -> TokenUsage {
+        let mut usage = TokenUsage::with_cache(
+            model,
+            input_tokens,
+            output_tokens,
+            self.input,
+            self.output,
+            cache_read_tokens,
+            cache_write_tokens,
+        );
+        let per_million = |tokens: Option<u64>, rate: Option<f64>| {
+            let rate = rate.unwrap_or(self.input);
+            if rate.is_finite() && rate > 0.0 {
+                (tokens.unwrap_or(0) as f64 / 1_000_000.0) * rate
+            } else {
+                0.0
+            }
+        };
+        usage.cost_usd += per_million(cache_read_tokens, self.cache_read)
+            + per_million(cache_write_tokens, self.cache_write);
+        usage
+    }
+}
+
+/// Where a model's rates were found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceSource {
+    File,
+    Config,
+    Builtin,
+    Fallback,
+}
+
+impl PriceSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::File => "pricing.toml",
+            Self::Config => "config",
+            Self::Builtin => "built-in",
+            Self::Fallback => "fallback",
+        }
+    }
+}
+
+/// Contents of `pricing.toml`: a `[models."<provider>/<model>"]` table per
+/// model.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PricingFile {
+    #[serde(default)]
+    pub models: BTreeMap<String, ModelRates>,
+}
+
+impl PricingFile {
+    /// Read a pricing file; a missing file is empty.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let raw = toml::to_string_pretty(self).context("failed to serialize pricing file")?;
+        std::fs::write(path, raw).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// Path of the pricing file for a config at `config_path`.
+pub fn pricing_file_path(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(PRICING_FILE_NAME)
+}
+
+struct WatchState {
+    models: BTreeMap<String, ModelRates>,
+    modified: Option<SystemTime>,
+    checked: Option<Instant>,
+}
+
+/// `pricing.toml`, reloaded when its mtime changes. A file that fails to
+/// parse keeps the last good rates.
+pub struct PricingFileWatch {
+    path: PathBuf,
+    state: RwLock<WatchState>,
+}
+
+impl PricingFileWatch {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            state: RwLock::new(WatchState {
+                models: BTreeMap::new(),
+                modified: None,
+                checked: None,
+            }),
+        }
+    }
+
+    fn refresh(&self) {
+        if self
+            .state
+            .read()
+            .checked
+            .is_some_and(|at| at.elapsed() < RELOAD_CHECK_INTERVAL)
+        {
+            return;
+        }
+        let mut state = self.state.write();
+        state.checked = Some(Instant::now());
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|meta| meta.modified())
+            .ok();
+        if modified == state.modified {
+            return;
+        }
+        state.modified = modified;
+        match PricingFile::load(&self.path) {
+            Ok(file) => {
+                tracing::debug!(
+                    "Loaded {} model prices from {}",
+                    file.models.len(),
+                    self.path.display()
+                );
+                state.models = file.models;
+            }
+            Err(e) => tracing::warn!("Keeping previous model pricing: {e:#}"),
+        }
+    }
+
+    fn lookup(&self, provider: &str, model: &str) -> Option<ModelRates> {
+        self.refresh();
+        find_rates(&self.state.read().models, provider, model)
+    }
+}
+
+static FILE_WATCH: OnceLock<Arc<PricingFileWatch>> = OnceLock::new();
+
+/// Register the process-wide pricing file. Pricing built afterwards with
+/// [`Pricing::from_config`] consults it.
+pub fn init(config_path: &Path) {
+    let _ = FILE_WATCH.set(Arc::new(PricingFileWatch::new(pricing_file_path(
+        config_path,
+    ))));
+}
+
+/// Resolves model rates from every pricing source.
+pub struct Pricing {
+    file: Option<Arc<PricingFileWatch>>,
+    configured: BTreeMap<String, ModelRates>,
+    unknown: UnknownModelPricing,
+    warned: Mutex<HashSet<String>>,
+}
+
+impl Pricing {
+    /// Pricing from `[cost]` plus the registered pricing file, if any.
+    pub fn from_config(cost: &CostConfig) -> Self {
+        Self::new(cost, FILE_WATCH.get().cloned())
+    }
+
+    pub fn new(cost: &CostConfig, file: Option<Arc<PricingFileWatch>>) -> Self {
+        Self {
+            file,
+            configured: cost
+                .prices
+                .iter()
+                .map(|(name, price)| {
+                    (
+                        name.clone(),
+                        ModelRates {
+                            input: price.input,
+                            output: price.output,
+                            cache_read: None,
+                            cache_write: None,
+                        },
+                    )
+                })
+                .collect(),
+            unknown: cost.unknown_model_pricing,
+            warned: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Rates for `provider`/`model` and where they came from.
+    pub fn lookup(&self, provider: &str, model: &str) -> (ModelRates, PriceSource) {
+        if let Some(rates) = self
+            .file
+            .as_ref()
+            .and_then(|file| file.lookup(provider, model))
+        {
+            return (rates, PriceSource::File);
+        }
+        if let Some(rates) = find_rates(&self.configured, provider, model) {
+            return (rates, PriceSource::Config);
+        }
+        let (input, output) = model_pricing(model);
+        if input > 0.0 || output > 0.0 {
+            let rates = ModelRates {
+                input,
+                output,
+                cache_read: None,
+                cache_write: None,
+            };
+            return (rates, PriceSource::Builtin);
+        }
+        (self.fallback(provider, model), PriceSource::Fallback)
+    }
+
+    pub fn rates(&self, provider: &str, model: &str) -> ModelRates {
+        self.lookup(provider, model).0
+    }
+
+    fn fallback(&self, provider: &str, model: &str) -> ModelRates {
+        match self.unknown {
+            UnknownModelPricing::Conservative => {
+                tracing::debug!(
+                    "No pricing found for {provider}/{model}, using defaults (${}/{} per 1M tokens)",
+                    CONSERVATIVE_RATES.input,
+                    CONSERVATIVE_RATES.output
+                );
+                CONSERVATIVE_RATES
+            }
+            UnknownModelPricing::Zero => {
+                if self.warned.lock().insert(format!("{provider}/{model}")) {
+                    tracing::warn!(
+                        "No pricing found for {provider}/{model}; recording usage at $0. \
+                         Add it with `snowclaw pricing set`."
+                    );
+                }
+                ModelRates::ZERO
+            }
+        }
+    }
+}
+
+/// Find rates by exact `provider/model`, bare model, then model family
+/// (e.g. `claude-sonnet-4` matches any `claude-sonnet-4-*`).
+fn find_rates(
+    prices: &BTreeMap<String, ModelRates>,
+    provider: &str,
+    model: &str,
+) -> Option<ModelRates> {
+    if let Some(rates) = prices.get(&format!("{provider}/{model}")) {
+        return Some(*rates);
+    }
+    if let Some(rates) = prices.get(model) {
+        return Some(*rates);
+    }
+    for (key, rates) in prices {
+        let key_model = key.split('/').next_back().unwrap_or(key);
+        if model.starts_with(key_model) || key_model.starts_with(model) {
+            return Some(*rates);
+        }
+        // e.g. "claude-3-5-sonnet-20241022" should match "claude-3.5-sonnet"
+        let normalized_model = model.replace('-', ".");
+        let normalized_key = key_model.replace('-', ".");
+        if normalized_model.contains(&normalized_key) || normalized_key.contains(&normalized_model)
+        {
+            return Some(*rates);
+        }
+    }
+    None
+}
+
 /// Return (input_price_per_million, output_price_per_million) for known models.
 /// Returns (0.0, 0.0) for unknown models — cost will be recorded as $0.
 pub fn model_pricing(model: &str) -> (f64, f64) {
@@ -72,4 +403,90 @@ mod tests {
         assert_eq!(inp, 0.0);
         assert_eq!(out, 0.0);
     }
+
+    fn no_prices() -> CostConfig {
+        CostConfig {
+            prices: std::collections::HashMap::new(),
+            ..CostConfig::default()
+        }
+    }
+
+    #[test]
+    fn lookup_falls_through_sources_in_order() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join(PRICING_FILE_NAME);
+        let mut file = PricingFile::default();
+        file.models.insert(
+            "openai/gpt-4o".into(),
+            ModelRates {
+                input: 1.0,
+                output: 2.0,
+                cache_read: Some(0.5),
+                cache_write: None,
+            },
+        );
+        file.save(&path).unwrap();
+
+        let mut cost = no_prices();
+        cost.prices.insert(
+            "acme/widget-1".into(),
+            crate::config::schema::ModelPricing {
+                input: 4.0,
+                output: 8.0,
+            },
+        );
+        let pricing = Pricing::new(&cost, Some(Arc::new(PricingFileWatch::new(path))));
+
+        let (rates, source) = pricing.lookup("openai", "gpt-4o-2024-08-06");
+        assert_eq!((rates.input, source), (1.0, PriceSource::File));
+        let (rates, source) = pricing.lookup("acme", "widget-1");
+        assert_eq!((rates.input, source), (4.0, PriceSource::Config));
+        let (rates, source) = pricing.lookup("anthropic", "claude-opus-4-1");
+        assert_eq!((rates.input, source), (15.0, PriceSource::Builtin));
+        let (rates, source) = pricing.lookup("acme", "mystery");
+        assert_eq!((rates.input, source), (3.0, PriceSource::Fallback));
+
+        cost.unknown_model_pricing = UnknownModelPricing::Zero;
+        let pricing = Pricing::new(&cost, None);
+        assert_eq!(pricing.rates("acme", "mystery"), ModelRates::ZERO);
+    }
+
+    #[test]
+    fn pricing_file_reloads_on_change() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join(PRICING_FILE_NAME);
+        let watch = PricingFileWatch::new(path.clone());
+        assert!(watch.lookup("acme", "widget-2").is_none());
+
+        std::fs::write(
+            &path,
+            "[models.\"acme/widget-2\"]\ninput = 2.0\noutput = 6.0\n",
+        )
+        .unwrap();
+        // Force the next lookup past the reload interval.
+        watch.state.write().checked = None;
+        let rates = watch.lookup("acme", "widget-2").unwrap();
+        assert_eq!((rates.input, rates.output), (2.0, 6.0));
+
+        // A broken edit keeps the last good rates.
+        std::fs::write(&path, "not = [valid").unwrap();
+        let mut state = watch.state.write();
+        state.checked = None;
+        state.modified = None;
+        drop(state);
+        assert!(watch.lookup("acme", "widget-2").is_some());
+    }
+
+    #[test]
+    fn usage_charges_cache_tokens() {
+        let rates = ModelRates {
+            input: 3.0,
+            output: 15.0,
+            cache_read: Some(0.3),
+            cache_write: None,
+        };
+        let usage = rates.usage("a/b", 1_000_000, 0, Some(1_000_000), Some(1_000_000));
+        // 3.0 input + 0.3 cache read + 3.0 cache write (falls back to input)
+        assert!((usage.cost_usd - 6.3).abs() < 1e-9);
+    }
 }
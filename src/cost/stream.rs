@@ -0,0 +1,203 @@
+//! Incremental cost accounting for streamed responses.
+//!
+//! A [`StreamCost`] keeps one cost record per streamed response. While
+//! chunks arrive it periodically rewrites a `partial` record with the
+//! running total; [`StreamCost::finish`] writes the final one. Dropping it
+//! before that (client disconnect, provider error) writes the usage so far
+//! with `cancelled` set, so an aborted stream is still billed.
+
+use super::pricing::ModelRates;
+use super::types::CostRecord;
+use super::CostTracker;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Minimum time between partial records of one stream.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct StreamCost {
+    tracker: Arc<CostTracker>,
+    rates: ModelRates,
+    record: CostRecord,
+    input_tokens: u64,
+    output_tokens: u64,
+    last_flush: Instant,
+    done: bool,
+}
+
+impl StreamCost {
+    /// Start accounting for a stream of `model` (`provider/model`) whose
+    /// prompt is estimated at `input_tokens`.
+    pub fn new(
+        tracker: Arc<CostTracker>,
+        model: impl Into<String>,
+        rates: ModelRates,
+        input_tokens: u64,
+    ) -> Self {
+        let usage = rates.usage(model, input_tokens, 0, None, None);
+        let mut record = CostRecord::new(tracker.session_id(), usage);
+        if let Some(attribution) = super::attribution::current() {
+            record.channel = attribution.channel;
+            record.room = attribution.room;
+            record.persona = attribution.persona;
+            record.route = attribution.route;
+            record.message_type = Some("user_message".to_string());
+            record.tool_latency = super::attribution::take_tool_latency();
+        }
+        record.partial = true;
+
+        Self {
+            tracker,
+            rates,
+            record,
+            input_tokens,
+            output_tokens: 0,
+            last_flush: Instant::now(),
+            done: false,
+        }
+    }
+
+    /// Count `tokens` more output tokens, writing a partial record when the
+    /// last one is older than the flush interval.
+    pub fn add_output(&mut self, tokens: u64) {
+        self.output_tokens = self.output_tokens.saturating_add(tokens);
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush();
+        }
+    }
+
+    /// Write a partial record with the running total now.
+    pub fn flush(&mut self) {
+        self.last_flush = Instant::now();
+        self.write(true, false);
+    }
+
+    /// Write the final record. `usage` replaces the running estimate with
+    /// the provider's `(input, output)` token counts when it reports them.
+    pub fn finish(mut self, usage: Option<(u64, u64)>) {
+        if let Some((input, output)) = usage {
+            self.input_tokens = input;
+            self.output_tokens = output;
+        }
+        self.done = true;
+        self.write(false, false);
+    }
+
+    /// Write the final record for a stream stopped before completion.
+    pub fn cancel(mut self) {
+        self.done = true;
+        self.write(false, true);
+    }
+
+    fn write(&mut self, partial: bool, cancelled: bool) {
+        let started_at = self.record.usage.timestamp;
+        let mut usage = self.rates.usage(
+            std::mem::take(&mut self.record.usage.model),
+            self.input_tokens,
+            self.output_tokens,
+            None,
+            None,
+        );
+        // Keep the start time so every update lands in the same period.
+        usage.timestamp = started_at;
+        self.record.usage = usage;
+        self.record.partial = partial;
+        self.record.cancelled = cancelled;
+        if let Err(e) = self.tracker.record_stream_update(self.record.clone()) {
+            tracing::warn!("Failed to record streamed cost usage: {e}");
+        }
+    }
+}
+
+impl Drop for StreamCost {
+    fn drop(&mut self) {
+        if !self.done {
+            self.done = true;
+            self.write(false, true);
+        }
+    }
+}
+
+/// Rough token estimate for streamed text (~4 chars per token).
+pub fn estimate_tokens(text: &str) -> u64 {
+    text.len().div_ceil(4) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::CostConfig;
+    use tempfile::TempDir;
+
+    const RATES: ModelRates = ModelRates {
+        input: 1.0,
+        output: 2.0,
+        cache_read: None,
+        cache_write: None,
+    };
+
+    fn tracker(tmp: &TempDir) -> Arc<CostTracker> {
+        let config = CostConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        Arc::new(CostTracker::new(config, tmp.path()).unwrap())
+    }
+
+    fn stored(tmp: &TempDir) -> Vec<CostRecord> {
+        crate::stats::read_records(&crate::stats::costs_jsonl_path(tmp.path())).unwrap()
+    }
+
+    #[test]
+    fn partial_updates_collapse_into_final_record() {
+        let tmp = TempDir::new().unwrap();
+        let tracker = tracker(&tmp);
+
+        let mut stream = StreamCost::new(tracker.clone(), "acme/widget", RATES, 1_000_000);
+        stream.add_output(250_000);
+        stream.flush();
+        let summary = tracker.get_summary().unwrap();
+        assert_eq!(summary.request_count, 1);
+        assert!((summary.daily_cost_usd - 1.5).abs() < 1e-9);
+
+        stream.add_output(250_000);
+        stream.finish(None);
+
+        let summary = tracker.get_summary().unwrap();
+        assert_eq!(summary.request_count, 1);
+        assert!((summary.session_cost_usd - 2.0).abs() < 1e-9);
+        assert!((summary.daily_cost_usd - 2.0).abs() < 1e-9);
+
+        let records = stored(&tmp);
+        assert_eq!(records.len(), 1);
+        assert!(!records[0].partial && !records[0].cancelled);
+        assert_eq!(records[0].usage.output_tokens, 500_000);
+    }
+
+    #[test]
+    fn dropped_stream_is_recorded_as_cancelled() {
+        let tmp = TempDir::new().unwrap();
+        let tracker = tracker(&tmp);
+
+        let mut stream = StreamCost::new(tracker.clone(), "acme/widget", RATES, 1_000_000);
+        stream.add_output(500_000);
+        drop(stream);
+
+        let records = stored(&tmp);
+        assert_eq!(records.len(), 1);
+        assert!(records[0].cancelled && !records[0].partial);
+        assert!((records[0].usage.cost_usd - 2.0).abs() < 1e-9);
+
+        // A fresh tracker rebuilds the same daily total from disk.
+        let reopened = CostTracker::new(
+            CostConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            tmp.path(),
+        )
+        .unwrap();
+        let summary = reopened.get_summary().unwrap();
+        assert!((summary.daily_cost_usd - 2.0).abs() < 1e-9);
+    }
+}
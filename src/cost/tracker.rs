@@ -1,9 +1,11 @@
-use super::types::{BudgetCheck, CostRecord, CostSummary, ModelStats, TokenUsage, UsagePeriod};
+use super::types::{
+    latest_records, BudgetCheck, CostRecord, CostSummary, ModelStats, TokenUsage, UsagePeriod,
+};
 use crate::config::schema::CostConfig;
 use anyhow::{anyhow, Context, Result};
 use chrono::{Datelike, NaiveDate, Utc};
 use parking_lot::{Mutex, MutexGuard};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
@@ -232,6 +234,86 @@ impl CostTracker {
         Ok(())
     }
 
+    /// Record a usage event attributed to the channel/room/persona of the
+    /// task that made the request, with the latency of the tools whose
+    /// results it carried.
+    pub fn record_usage_attributed(
+        &self,
+        usage: TokenUsage,
+        attribution: super::attribution::CostAttribution,
+        tool_latency: BTreeMap<String, super::types::ToolLatency>,
+    ) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        if !usage.cost_usd.is_finite() || usage.cost_usd < 0.0 {
+            return Err(anyhow!(
+                "Token usage cost must be a finite, non-negative value"
+            ));
+        }
+
+        let mut record = CostRecord::with_context(
+            &self.session_id,
+            usage,
+            attribution.channel,
+            attribution.room,
+            Some("user_message".to_string()),
+        );
+        record.persona = attribution.persona;
+        record.route = attribution.route;
+        record.tool_latency = tool_latency;
+
+        {
+            let mut storage = self.lock_storage();
+            storage.add_record(record.clone())?;
+        }
+
+        let mut session_costs = self.lock_session_costs();
+        session_costs.push(record);
+
+        Ok(())
+    }
+
+    /// Record the running total of a streamed response. Each call
+    /// supersedes the previous record with the same id.
+    pub fn record_stream_update(&self, record: CostRecord) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        if !record.usage.cost_usd.is_finite() || record.usage.cost_usd < 0.0 {
+            return Err(anyhow!(
+                "Token usage cost must be a finite, non-negative value"
+            ));
+        }
+
+        let previous = self
+            .lock_session_costs()
+            .iter()
+            .find(|existing| existing.id == record.id)
+            .cloned();
+
+        {
+            let mut storage = self.lock_storage();
+            if let Some(ref previous) = previous {
+                storage.forget_cached(previous)?;
+            }
+            storage.add_record(record.clone())?;
+        }
+
+        let mut session_costs = self.lock_session_costs();
+        match session_costs
+            .iter_mut()
+            .find(|existing| existing.id == record.id)
+        {
+            Some(existing) => *existing = record,
+            None => session_costs.push(record),
+        }
+
+        Ok(())
+    }
+
     /// Get usage breakdown for a specific date.
     pub fn get_usage_breakdown(
         &self,
@@ -360,6 +442,7 @@ impl CostStorage {
         let file = File::open(&self.path)
             .with_context(|| format!("Failed to read cost storage from {}", self.path.display()))?;
         let reader = BufReader::new(file);
+        let mut records = Vec::new();
 
         for (line_number, line) in reader.lines().enumerate() {
             let raw_line = line.with_context(|| {
@@ -376,7 +459,7 @@ impl CostStorage {
             }
 
             match serde_json::from_str::<CostRecord>(trimmed) {
-                Ok(record) => on_record(record),
+                Ok(record) => records.push(record),
                 Err(error) => {
                     tracing::warn!(
                         "Skipping malformed cost record at {}:{}: {error}",
@@ -387,6 +470,10 @@ impl CostStorage {
             }
         }
 
+        for record in latest_records(records) {
+            on_record(record);
+        }
+
         Ok(())
     }
 
@@ -454,6 +541,21 @@ impl CostStorage {
         Ok(())
     }
 
+    /// Drop a superseded record's cost from the cached aggregates.
+    fn forget_cached(&mut self, record: &CostRecord) -> Result<()> {
+        self.ensure_period_cache_current()?;
+
+        let timestamp = record.usage.timestamp.naive_utc();
+        if timestamp.date() == self.cached_day {
+            self.daily_cost_usd -= record.usage.cost_usd;
+        }
+        if timestamp.year() == self.cached_year && timestamp.month() == self.cached_month {
+            self.monthly_cost_usd -= record.usage.cost_usd;
+        }
+
+        Ok(())
+    }
+
     /// Get aggregated costs for current day and month.
     fn get_aggregated_costs(&mut self) -> Result<(f64, f64)> {
         self.ensure_period_cache_current()?;
@@ -490,6 +592,7 @@ impl CostStorage {
             by_model: HashMap::new(),
             by_channel: HashMap::new(),
             token_breakdown: None,
+            by_tool: BTreeMap::new(),
         };
         let mut agg_bd = super::types::TokenBreakdown::default();
         let mut has_any_breakdown = false;
@@ -506,6 +609,14 @@ impl CostStorage {
             breakdown.total_cache_write_tokens += record.usage.cache_write_tokens.unwrap_or(0);
             breakdown.request_count += 1;
 
+            for (tool, latency) in &record.tool_latency {
+                breakdown
+                    .by_tool
+                    .entry(tool.clone())
+                    .or_default()
+                    .merge(latency);
+            }
+
             // Aggregate token breakdowns
             if let Some(ref bd) = record.breakdown {
                 has_any_breakdown = true;
@@ -676,6 +787,34 @@ mod tests {
         assert_eq!(summary.by_model.len(), 1);
     }
 
+    #[test]
+    fn usage_breakdown_sums_tool_latency() {
+        let tmp = TempDir::new().unwrap();
+        let tracker = CostTracker::new(enabled_config(), tmp.path()).unwrap();
+        let latency = |ms| {
+            let mut latency = crate::cost::ToolLatency::default();
+            latency.record(std::time::Duration::from_millis(ms));
+            BTreeMap::from([("web_fetch".to_string(), latency)])
+        };
+        for ms in [200, 500] {
+            tracker
+                .record_usage_attributed(
+                    TokenUsage::new("test/model", 100, 50, 1.0, 2.0),
+                    crate::cost::attribution::CostAttribution::default(),
+                    latency(ms),
+                )
+                .unwrap();
+        }
+
+        let breakdown = tracker
+            .get_usage_breakdown(Utc::now().date_naive())
+            .unwrap();
+        let web_fetch = breakdown.by_tool["web_fetch"];
+        assert_eq!(web_fetch.calls, 2);
+        assert_eq!(web_fetch.total_ms, 700);
+        assert_eq!(web_fetch.max_ms, 500);
+    }
+
     #[test]
     fn budget_exceeded_daily_limit() {
         let tmp = TempDir::new().unwrap();
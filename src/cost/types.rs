@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
 
 /// Token usage information from a single API call.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -169,6 +171,49 @@ impl TokenBreakdown {
     }
 }
 
+/// Time spent in one tool's calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolLatency {
+    pub calls: u32,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    /// Calls answered from the tool result cache.
+    #[serde(default)]
+    pub cache_hits: u32,
+    /// Cacheable calls that had to run the tool.
+    #[serde(default)]
+    pub cache_misses: u32,
+}
+
+impl ToolLatency {
+    /// Count one call that took `duration`.
+    pub fn record(&mut self, duration: Duration) {
+        let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        self.calls = self.calls.saturating_add(1);
+        self.total_ms = self.total_ms.saturating_add(ms);
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    /// Count a lookup in the tool result cache.
+    pub fn record_cache(&mut self, hit: bool) {
+        let counter = if hit {
+            &mut self.cache_hits
+        } else {
+            &mut self.cache_misses
+        };
+        *counter = counter.saturating_add(1);
+    }
+
+    /// Add `other`'s calls to these.
+    pub fn merge(&mut self, other: &Self) {
+        self.calls = self.calls.saturating_add(other.calls);
+        self.total_ms = self.total_ms.saturating_add(other.total_ms);
+        self.max_ms = self.max_ms.max(other.max_ms);
+        self.cache_hits = self.cache_hits.saturating_add(other.cache_hits);
+        self.cache_misses = self.cache_misses.saturating_add(other.cache_misses);
+    }
+}
+
 /// Breakdown of system prompt section sizes (bytes), returned alongside the prompt string.
 #[derive(Debug, Clone, Default)]
 pub struct PromptBreakdown {
@@ -209,6 +254,24 @@ pub struct CostRecord {
     /// Token breakdown by category
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub breakdown: Option<TokenBreakdown>,
+    /// Persona that handled the request (see `[personas]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<String>,
+    /// Model route that served the request (see `[model_router]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    /// Running total of a response that is still streaming. A later record
+    /// with the same `id` supersedes it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+    /// Streamed response cancelled before completion; usage covers what was
+    /// produced until then.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+    /// Tools run in the turn since the previous request, by tool name. This
+    /// request carried their results.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tool_latency: BTreeMap<String, ToolLatency>,
 }
 
 impl CostRecord {
@@ -222,6 +285,11 @@ impl CostRecord {
             room: None,
             message_type: None,
             breakdown: None,
+            persona: None,
+            route: None,
+            partial: false,
+            cancelled: false,
+            tool_latency: BTreeMap::new(),
         }
     }
 
@@ -241,6 +309,11 @@ impl CostRecord {
             room,
             message_type,
             breakdown: None,
+            persona: None,
+            route: None,
+            partial: false,
+            cancelled: false,
+            tool_latency: BTreeMap::new(),
         }
     }
 
@@ -261,8 +334,31 @@ impl CostRecord {
             room,
             message_type,
             breakdown,
+            persona: None,
+            route: None,
+            partial: false,
+            cancelled: false,
+            tool_latency: BTreeMap::new(),
+        }
+    }
+}
+
+/// Keep only the latest record per `id`, at the position of its first
+/// record. Streamed responses append partial records that later ones
+/// supersede.
+pub fn latest_records(records: Vec<CostRecord>) -> Vec<CostRecord> {
+    let mut index: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    let mut latest: Vec<CostRecord> = Vec::with_capacity(records.len());
+    for record in records {
+        match index.get(&record.id) {
+            Some(&at) => latest[at] = record,
+            None => {
+                index.insert(record.id.clone(), latest.len());
+                latest.push(record);
+            }
         }
     }
+    latest
 }
 
 /// Budget enforcement result.
@@ -336,6 +432,9 @@ pub struct UsageBreakdown {
     /// Aggregated token breakdown across all records in the period
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub token_breakdown: Option<TokenBreakdown>,
+    /// Tool latency across all records in the period, by tool name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_tool: BTreeMap<String, ToolLatency>,
 }
 
 /// Statistics for a specific channel.
@@ -400,6 +499,28 @@ mod tests {
         assert_eq!(usage.total_tokens, 2000);
     }
 
+    #[test]
+    fn tool_latency_accumulates_calls() {
+        let mut latency = ToolLatency::default();
+        latency.record(Duration::from_millis(120));
+        latency.record(Duration::from_millis(30));
+        latency.record_cache(true);
+        latency.record_cache(false);
+        let mut total = ToolLatency::default();
+        total.merge(&latency);
+        total.merge(&latency);
+        assert_eq!(
+            total,
+            ToolLatency {
+                calls: 4,
+                total_ms: 300,
+                max_ms: 120,
+                cache_hits: 2,
+                cache_misses: 2,
+            }
+        );
+    }
+
     #[test]
     fn cost_record_creation() {
         let usage = TokenUsage::new("test/model", 100, 50, 1.0, 2.0);
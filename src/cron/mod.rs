@@ -15,8 +15,8 @@ pub use schedule::{
 };
 #[allow(unused_imports)]
 pub use store::{
-    add_agent_job, due_jobs, get_job, list_jobs, list_runs, record_last_run, record_run,
-    remove_job, reschedule_after_run, update_job,
+    add_agent_job, count_runs, due_jobs, get_job, list_jobs, list_runs, record_last_run,
+    record_run, remove_job, reschedule_after_run, update_job,
 };
 pub use types::{CronJob, CronJobPatch, CronRun, DeliveryConfig, JobType, Schedule, SessionTarget};
 
@@ -86,7 +86,7 @@ pub fn update_shell_job_with_approval(
 }
 
 #[allow(clippy::needless_pass_by_value)]
-pub fn handle_command(command: crate::CronCommands, config: &Config) -> Result<()> {
+pub fn handle_command(command: crate::CronCommands, config: &Config, dry_run: bool) -> Result<()> {
     match command {
         crate::CronCommands::List => {
             let jobs = list_jobs(config)?;
@@ -208,6 +208,15 @@ pub fn handle_command(command: crate::CronCommands, config: &Config) -> Result<(
             println!("  Cmd : {}", job.command);
             Ok(())
         }
+        crate::CronCommands::Remove { id } if dry_run => {
+            let job = get_job(config, &id)?;
+            let runs = count_runs(config, &id)?;
+            println!("[dry-run] Would remove cron job {}", job.id);
+            println!("  Expr: {}", job.expression);
+            println!("  Cmd : {}", job.command);
+            println!("  Rows: 1 job, {runs} run history row(s)");
+            Ok(())
+        }
         crate::CronCommands::Remove { id } => remove_job(config, &id),
         crate::CronCommands::Pause { id } => {
             pause_job(config, &id)?;
@@ -224,7 +233,7 @@ pub fn handle_command(command: crate::CronCommands, config: &Config) -> Result<(
 
 pub fn add_once(config: &Config, delay: &str, command: &str) -> Result<CronJob> {
     let duration = parse_delay(delay)?;
-    let at = chrono::Utc::now() + duration;
+    let at = crate::determinism::now() + duration;
     add_once_at(config, at, command)
 }
 
@@ -235,7 +244,7 @@ pub fn add_once_with_approval(
     approved: bool,
 ) -> Result<CronJob> {
     let duration = parse_delay(delay)?;
-    let at = chrono::Utc::now() + duration;
+    let at = crate::determinism::now() + duration;
     add_once_at_with_approval(config, at, command, approved)
 }
 
@@ -346,6 +355,7 @@ mod tests {
                 name: name.map(Into::into),
             },
             config,
+            false,
         )
     }
 
@@ -583,4 +593,20 @@ mod tests {
             .to_string()
             .contains("explicit approval"));
     }
+
+    #[test]
+    fn remove_dry_run_keeps_job() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let job = make_job(&config, "*/5 * * * *", None, "echo keep");
+
+        handle_command(
+            crate::CronCommands::Remove { id: job.id.clone() },
+            &config,
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(get_job(&config, &job.id).unwrap().command, "echo keep");
+    }
 }
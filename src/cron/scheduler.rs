@@ -44,7 +44,9 @@ pub async fn run(config: Config) -> Result<()> {
         // Keep scheduler liveness fresh even when there are no due jobs.
         crate::health::mark_component_ok(SCHEDULER_COMPONENT);
 
-        let jobs = match due_jobs(&config, Utc::now()) {
+        // In seed mode the mock clock moves one poll interval per tick
+        crate::determinism::advance(chrono::Duration::seconds(poll_secs as i64));
+        let jobs = match due_jobs(&config, crate::determinism::now()) {
             Ok(jobs) => jobs,
             Err(e) => {
                 crate::health::mark_component_error(SCHEDULER_COMPONENT, e.to_string());
@@ -97,7 +99,7 @@ async fn execute_job_with_retry(
         }
 
         if attempt < retries {
-            let jitter_ms = u64::from(Utc::now().timestamp_subsec_millis() % 250);
+            let jitter_ms = crate::determinism::jitter_ms("cron_retry", 249);
             time::sleep(Duration::from_millis(backoff_ms + jitter_ms)).await;
             backoff_ms = (backoff_ms.saturating_mul(2)).min(30_000);
         }
@@ -148,9 +150,9 @@ async fn execute_and_persist_job(
     crate::health::mark_component_ok(component);
     warn_if_high_frequency_agent_job(job);
 
-    let started_at = Utc::now();
+    let started_at = crate::determinism::now();
     let (success, output) = execute_job_with_retry(config, security, job, false).await;
-    let finished_at = Utc::now();
+    let finished_at = crate::determinism::now();
     let success = persist_job_result(config, job, success, &output, started_at, finished_at).await;
 
     (job.id.clone(), success, output)
@@ -283,7 +285,7 @@ fn warn_if_high_frequency_agent_job(job: &CronJob) {
     let too_frequent = match &job.schedule {
         Schedule::Every { every_ms } => *every_ms < 5 * 60 * 1000,
         Schedule::Cron { .. } => {
-            let now = Utc::now();
+            let now = crate::determinism::now();
             match (
                 next_run_for_schedule(&job.schedule, now),
                 next_run_for_schedule(&job.schedule, now + chrono::Duration::seconds(1)),
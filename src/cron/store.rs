@@ -33,7 +33,7 @@ pub fn add_shell_job(
     schedule: Schedule,
     command: &str,
 ) -> Result<CronJob> {
-    let now = Utc::now();
+    let now = crate::determinism::now();
     validate_schedule(&schedule, now)?;
     let next_run = next_run_for_schedule(&schedule, now)?;
     let id = Uuid::new_v4().to_string();
@@ -78,7 +78,7 @@ pub fn add_agent_job(
     delivery: Option<DeliveryConfig>,
     delete_after_run: bool,
 ) -> Result<CronJob> {
-    let now = Utc::now();
+    let now = crate::determinism::now();
     validate_schedule(&schedule, now)?;
     let next_run = next_run_for_schedule(&schedule, now)?;
     let id = Uuid::new_v4().to_string();
@@ -190,7 +190,7 @@ pub fn update_job(config: &Config, job_id: &str, patch: CronJobPatch) -> Result<
     let mut schedule_changed = false;
 
     if let Some(schedule) = patch.schedule {
-        validate_schedule(&schedule, Utc::now())?;
+        validate_schedule(&schedule, crate::determinism::now())?;
         job.schedule = schedule;
         job.expression = schedule_cron_expression(&job.schedule).unwrap_or_default();
         schedule_changed = true;
@@ -221,7 +221,7 @@ pub fn update_job(config: &Config, job_id: &str, patch: CronJobPatch) -> Result<
     }
 
     if schedule_changed {
-        job.next_run = next_run_for_schedule(&job.schedule, Utc::now())?;
+        job.next_run = next_run_for_schedule(&job.schedule, crate::determinism::now())?;
     }
 
     with_connection(config, |conn| {
@@ -281,7 +281,7 @@ pub fn reschedule_after_run(
     success: bool,
     output: &str,
 ) -> Result<()> {
-    let now = Utc::now();
+    let now = crate::determinism::now();
     let next_run = next_run_for_schedule(&job.schedule, now)?;
     let status = if success { "ok" } else { "error" };
     let bounded_output = truncate_cron_output(output);
@@ -373,6 +373,18 @@ fn truncate_cron_output(output: &str) -> String {
     truncated
 }
 
+/// Number of run history rows kept for a job.
+pub fn count_runs(config: &Config, job_id: &str) -> Result<usize> {
+    with_connection(config, |conn| {
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM cron_runs WHERE job_id = ?1",
+            params![job_id],
+            |row| row.get(0),
+        )?;
+        Ok(usize::try_from(count).unwrap_or_default())
+    })
+}
+
 pub fn list_runs(config: &Config, job_id: &str, limit: usize) -> Result<Vec<CronRun>> {
     with_connection(config, |conn| {
         let lim = i64::try_from(limit.max(1)).context("Run history limit overflow")?;
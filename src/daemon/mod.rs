@@ -1,3 +1,5 @@
+pub mod shutdown;
+
 use crate::config::Config;
 use anyhow::{bail, Result};
 use chrono::Utc;
@@ -10,6 +12,8 @@ use tokio::time::Duration;
 
 const STATUS_FLUSH_SECONDS: u64 = 5;
 const SHUTDOWN_GRACE_SECONDS: u64 = 5;
+/// How long in-flight replies may keep running after a shutdown signal.
+const SHUTDOWN_DRAIN_SECONDS: u64 = 30;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum ShutdownSignal {
@@ -82,6 +86,7 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
         .max(initial_backoff);
 
     crate::health::mark_component_ok("daemon");
+    crate::update::watch_pending_update(&config);
 
     if config.heartbeat.enabled {
         let _ =
@@ -153,6 +158,32 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
         tracing::info!("Cron disabled; scheduler supervisor not started");
     }
 
+    if config.memory.collective.review.enabled {
+        let review_cfg = config.clone();
+        handles.push(spawn_component_supervisor(
+            "memory_review",
+            initial_backoff,
+            max_backoff,
+            move || {
+                let cfg = review_cfg.clone();
+                async move { crate::memory::review::run(cfg).await }
+            },
+        ));
+    }
+
+    if config.memory.collective.distill.enabled {
+        let distill_cfg = config.clone();
+        handles.push(spawn_component_supervisor(
+            "memory_distill",
+            initial_backoff,
+            max_backoff,
+            move || {
+                let cfg = distill_cfg.clone();
+                async move { crate::memory::distill::run(cfg).await }
+            },
+        ));
+    }
+
     // Context-VM: Nostr-native request/response interface via Nomen.
     if config.contextvm.as_ref().is_some_and(|c| c.enabled) {
         let cvm_cfg = config.clone();
@@ -177,6 +208,42 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
 
     let signal = wait_for_shutdown_signal().await?;
     crate::health::mark_component_error("daemon", shutdown_reason(signal));
+    shutdown::begin_drain();
+    let in_flight = shutdown::in_flight();
+    if in_flight > 0 {
+        tracing::info!(in_flight, "Waiting for in-flight replies before shutdown");
+    }
+    let unfinished = shutdown::wait_idle(Duration::from_secs(SHUTDOWN_DRAIN_SECONDS)).await;
+    if unfinished > 0 {
+        tracing::warn!(
+            unfinished,
+            drain_seconds = SHUTDOWN_DRAIN_SECONDS,
+            "In-flight replies did not finish before the drain deadline"
+        );
+    }
+    if tokio::time::timeout(
+        Duration::from_secs(SHUTDOWN_GRACE_SECONDS),
+        crate::channels::nostr_memory::flush_pending_publishes(),
+    )
+    .await
+    .is_err()
+    {
+        tracing::warn!("Timed out flushing pending Nostr social publishes");
+    }
+    if tokio::time::timeout(
+        Duration::from_secs(SHUTDOWN_GRACE_SECONDS),
+        crate::channels::nostr::announce_offline(),
+    )
+    .await
+    .is_err()
+    {
+        tracing::warn!("Timed out publishing offline Nostr agent state");
+    }
+    if let Err(e) =
+        crate::channels::nostr_banner::send_shutdown(&config, shutdown_reason(signal)).await
+    {
+        tracing::warn!("Failed to send Nostr shutdown banner: {e:#}");
+    }
     let aborted =
         shutdown_handles_with_grace(handles, Duration::from_secs(SHUTDOWN_GRACE_SECONDS)).await;
     if aborted > 0 {
@@ -263,6 +330,13 @@ where
                     // Clean exit — reset backoff since the component ran successfully
                     backoff = initial_backoff_secs.max(1);
                 }
+                Err(e) if is_fatal(&e) => {
+                    crate::health::mark_component_error(name, e.to_string());
+                    tracing::error!(
+                        "Daemon component '{name}' failed with a configuration error, not restarting: {e:#}"
+                    );
+                    return;
+                }
                 Err(e) => {
                     crate::health::mark_component_error(name, e.to_string());
                     tracing::error!("Daemon component '{name}' failed: {e}");
@@ -277,6 +351,19 @@ where
     })
 }
 
+/// Whether restarting cannot fix `err`: a relay or ranking configuration
+/// problem anywhere in its chain. Relay timeouts, busy databases and
+/// untyped errors are retried with backoff.
+fn is_fatal(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<nostr_core::RelayError>()
+            .is_some_and(|e| !e.is_transient())
+            || cause.is::<snow_memory::RankingError>()
+            || cause.is::<snow_memory::ConfigError>()
+    })
+}
+
 async fn run_heartbeat_worker(config: Config) -> Result<()> {
     let observer: std::sync::Arc<dyn crate::observability::Observer> =
         std::sync::Arc::from(crate::observability::create_observer(&config.observability));
@@ -604,6 +691,22 @@ mod tests {
         assert_eq!(path, tmp.path().join("daemon_state.json"));
     }
 
+    #[test]
+    fn config_errors_stop_supervision() {
+        let onion = anyhow::Error::new(nostr_core::RelayError::OnionWithoutProxy(
+            "ws://abc.onion".into(),
+        ))
+        .context("Nostr channel startup failed");
+        assert!(is_fatal(&onion));
+
+        let timeout = anyhow::Error::new(nostr_core::RelayError::Timeout {
+            operation: "fetching events",
+            after: Duration::from_secs(5),
+        });
+        assert!(!is_fatal(&timeout));
+        assert!(!is_fatal(&anyhow::anyhow!("gateway bind failed")));
+    }
+
     #[test]
     fn shutdown_reason_for_ctrl_c_mentions_sigint() {
         assert_eq!(
@@ -0,0 +1,76 @@
+//! Shutdown coordination between the daemon and the channel dispatcher.
+//!
+//! On SIGINT/SIGTERM the daemon calls [`begin_drain`]. From then on the
+//! dispatch loop drops new channel messages, while replies already being
+//! generated (each holding an [`InFlight`] guard) run to completion.
+//! [`wait_idle`] waits for them up to a deadline; after that the daemon
+//! flushes pending publishes, announces itself offline and stops its tasks.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+static DRAINING: AtomicBool = AtomicBool::new(false);
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Stop accepting new channel messages.
+pub fn begin_drain() {
+    DRAINING.store(true, Ordering::SeqCst);
+}
+
+/// Whether a shutdown is in progress.
+pub fn is_draining() -> bool {
+    DRAINING.load(Ordering::SeqCst)
+}
+
+/// Replies currently being processed.
+pub fn in_flight() -> usize {
+    IN_FLIGHT.load(Ordering::SeqCst)
+}
+
+/// Marks one reply as in flight until dropped.
+#[derive(Debug)]
+pub struct InFlight(());
+
+impl InFlight {
+    pub fn start() -> Self {
+        IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+        Self(())
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Wait until no reply is in flight or `deadline` passes. Returns the
+/// number still running at the deadline (0 when fully drained).
+pub async fn wait_idle(deadline: Duration) -> usize {
+    let until = tokio::time::Instant::now() + deadline;
+    while in_flight() > 0 && tokio::time::Instant::now() < until {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    in_flight()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn wait_idle_returns_once_guards_drop() {
+        let guard = InFlight::start();
+        let waiter = tokio::spawn(wait_idle(Duration::from_secs(5)));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!waiter.is_finished());
+        drop(guard);
+        assert_eq!(waiter.await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn wait_idle_gives_up_at_deadline() {
+        let _guard = InFlight::start();
+        assert!(wait_idle(Duration::from_millis(100)).await >= 1);
+    }
+}
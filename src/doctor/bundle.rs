@@ -0,0 +1,536 @@
+//! Diagnostics bundle for bug reports (`snowclaw doctor bundle`).
+//!
+//! Collects the regular doctor checks plus config validation, SQLite
+//! integrity, relay reachability with latencies, a provider connectivity
+//! probe, disk space and a summary of recent errors into one JSON document.
+//! With `--redact`, keys, tokens and home paths are scrubbed so the file can
+//! be attached to a public issue.
+
+use super::{diagnose, disk_available_mb, DiagResult, Severity};
+use crate::config::Config;
+use crate::security::db_encryption;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use regex::Regex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Timeout for each relay and provider probe.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(8);
+/// How deep to look for SQLite databases under the workspace.
+const SQLITE_SCAN_DEPTH: usize = 3;
+/// Number of trace events scanned for failures.
+const TRACE_SCAN_LIMIT: usize = 500;
+/// Maximum error lines kept from traces and the daemon log.
+const MAX_RECENT_ERRORS: usize = 20;
+/// Bytes read from the end of the daemon stderr log.
+const LOG_TAIL_BYTES: u64 = 256 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct DiagnosticsBundle {
+    pub generated_at: String,
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    pub redacted: bool,
+    pub config_validation: CheckOutcome,
+    pub checks: Vec<DiagResult>,
+    pub sqlite: Vec<SqliteCheck>,
+    pub relays: Vec<RelayCheck>,
+    pub provider: Option<ProviderCheck>,
+    pub disk_available_mb: Option<u64>,
+    pub recent_errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckOutcome {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SqliteCheck {
+    pub path: String,
+    pub size_bytes: u64,
+    pub ok: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RelayCheck {
+    pub url: String,
+    pub reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProviderCheck {
+    pub provider: String,
+    pub ok: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Collect a diagnostics bundle. `offline` skips relay and provider probes.
+pub async fn collect(config: &Config, redact: bool, offline: bool) -> DiagnosticsBundle {
+    let config_validation = match config.validate() {
+        Ok(()) => CheckOutcome {
+            ok: true,
+            detail: None,
+        },
+        Err(e) => CheckOutcome {
+            ok: false,
+            detail: Some(format!("{e:#}")),
+        },
+    };
+
+    let (relays, provider) = if offline {
+        (Vec::new(), None)
+    } else {
+        let relays =
+            futures_util::future::join_all(relay_urls(config).into_iter().map(probe_relay)).await;
+        (relays, Some(probe_provider(config).await))
+    };
+
+    let mut bundle = DiagnosticsBundle {
+        generated_at: Utc::now().to_rfc3339(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        redacted: redact,
+        config_validation,
+        checks: diagnose(config),
+        sqlite: check_sqlite_databases(&config.workspace_dir),
+        relays,
+        provider,
+        disk_available_mb: disk_available_mb(&config.workspace_dir),
+        recent_errors: recent_errors(config),
+    };
+
+    if redact {
+        redact_bundle(&mut bundle, &Redactor::from_config(config));
+    }
+    bundle
+}
+
+/// Collect a bundle, write it as JSON and print a short summary.
+pub async fn run(
+    config: &Config,
+    output: Option<&Path>,
+    redact: bool,
+    offline: bool,
+) -> Result<()> {
+    println!("🩺 ZeroClaw Doctor — Diagnostics Bundle");
+    println!();
+
+    let bundle = collect(config, redact, offline).await;
+    let path = output.map(Path::to_path_buf).unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "snowclaw-diagnostics-{}.json",
+            Utc::now().format("%Y%m%dT%H%M%SZ")
+        ))
+    });
+    let json = serde_json::to_string_pretty(&bundle)?;
+    std::fs::write(&path, json)
+        .with_context(|| format!("Failed to write diagnostics bundle to {}", path.display()))?;
+
+    let icon = |ok: bool| if ok { "✅" } else { "❌" };
+    println!("  {} config validation", icon(bundle.config_validation.ok));
+    let failed_checks = bundle
+        .checks
+        .iter()
+        .filter(|c| c.severity == Severity::Error)
+        .count();
+    println!(
+        "  {} doctor checks ({failed_checks} errors)",
+        icon(failed_checks == 0)
+    );
+    for db in &bundle.sqlite {
+        println!("  {} sqlite {}: {}", icon(db.ok), db.path, db.detail);
+    }
+    for relay in &bundle.relays {
+        match relay.latency_ms {
+            Some(ms) if relay.reachable => println!("  ✅ relay {} ({ms} ms)", relay.url),
+            _ => println!(
+                "  ❌ relay {}: {}",
+                relay.url,
+                relay.error.as_deref().unwrap_or("unreachable")
+            ),
+        }
+    }
+    if let Some(provider) = &bundle.provider {
+        println!(
+            "  {} provider {} ({} ms)",
+            icon(provider.ok),
+            provider.provider,
+            provider.latency_ms
+        );
+    }
+    if let Some(mb) = bundle.disk_available_mb {
+        println!("  {} disk: {mb} MB available", icon(mb >= 100));
+    }
+    println!("  recent errors: {}", bundle.recent_errors.len());
+    println!();
+    println!(
+        "  Bundle written to {}{}",
+        path.display(),
+        if redact { " (redacted)" } else { "" }
+    );
+    if !redact {
+        println!("  💡 Re-run with --redact before attaching it to a public bug report.");
+    }
+    Ok(())
+}
+
+// ── SQLite ───────────────────────────────────────────────────────
+
+fn check_sqlite_databases(workspace_dir: &Path) -> Vec<SqliteCheck> {
+    let mut found = Vec::new();
+    find_sqlite_files(workspace_dir, SQLITE_SCAN_DEPTH, &mut found);
+    found.sort();
+    found
+        .into_iter()
+        .map(|path| {
+            let size_bytes = std::fs::metadata(&path).map_or(0, |m| m.len());
+            let (ok, detail) = match sqlite_quick_check(&path) {
+                Ok(result) => (result == "ok", result),
+                Err(e) => (false, format!("{e:#}")),
+            };
+            SqliteCheck {
+                path: path.display().to_string(),
+                size_bytes,
+                ok,
+                detail,
+            }
+        })
+        .collect()
+}
+
+fn find_sqlite_files(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth > 0 {
+                find_sqlite_files(&path, depth - 1, out);
+            }
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| matches!(e, "db" | "sqlite" | "sqlite3"))
+        {
+            out.push(path);
+        }
+    }
+}
+
+/// Run `PRAGMA quick_check` on `path`. Files without the plaintext SQLite
+/// header are keyed with the database key (`[secrets] encrypt_databases`);
+/// without a key they can't be told apart from corrupt files and are skipped.
+fn sqlite_quick_check(path: &Path) -> Result<String> {
+    let encrypted = !db_encryption::is_plaintext(path)?;
+    if encrypted && !db_encryption::has_key() {
+        bail!(
+            "skipped: encrypted or corrupt (no database key; `[secrets] encrypt_databases` is off)"
+        );
+    }
+    let conn = rusqlite::Connection::open_with_flags(
+        path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    if encrypted {
+        db_encryption::apply_key(&conn)?;
+    }
+    let mut stmt = conn.prepare("PRAGMA quick_check")?;
+    let rows: Vec<String> = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<std::result::Result<_, _>>()?;
+    Ok(rows.join("; "))
+}
+
+// ── Network probes ───────────────────────────────────────────────
+
+fn relay_urls(config: &Config) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    if let Some(nostr) = &config.channels_config.nostr {
+        urls.extend(nostr.relays.iter().cloned());
+    }
+    if let Some(cvm) = config.contextvm.as_ref().filter(|c| c.enabled) {
+        urls.extend(cvm.relays.iter().cloned());
+    }
+    urls.sort();
+    urls.dedup();
+    urls
+}
+
+async fn probe_relay(url: String) -> RelayCheck {
+    let started = Instant::now();
+    match tokio::time::timeout(
+        PROBE_TIMEOUT,
+        tokio_tungstenite::connect_async(url.as_str()),
+    )
+    .await
+    {
+        Ok(Ok((mut stream, _))) => {
+            let latency_ms = started.elapsed().as_millis() as u64;
+            let _ = stream.close(None).await;
+            RelayCheck {
+                url,
+                reachable: true,
+                latency_ms: Some(latency_ms),
+                error: None,
+            }
+        }
+        Ok(Err(e)) => RelayCheck {
+            url,
+            reachable: false,
+            latency_ms: None,
+            error: Some(e.to_string()),
+        },
+        Err(_) => RelayCheck {
+            url,
+            reachable: false,
+            latency_ms: None,
+            error: Some(format!("timed out after {}s", PROBE_TIMEOUT.as_secs())),
+        },
+    }
+}
+
+async fn probe_provider(config: &Config) -> ProviderCheck {
+    let name = config
+        .default_provider
+        .clone()
+        .unwrap_or_else(|| "openrouter".to_string());
+    let started = Instant::now();
+    let result = match crate::providers::create_provider_with_url(
+        &name,
+        config.api_key.as_deref(),
+        config.api_url.as_deref(),
+    ) {
+        Ok(provider) => match tokio::time::timeout(PROBE_TIMEOUT, provider.warmup()).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow::anyhow!(
+                "timed out after {}s",
+                PROBE_TIMEOUT.as_secs()
+            )),
+        },
+        Err(e) => Err(e),
+    };
+    ProviderCheck {
+        provider: name,
+        ok: result.is_ok(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: result
+            .err()
+            .map(|e| crate::providers::sanitize_api_error(&format!("{e:#}"))),
+    }
+}
+
+// ── Recent errors ────────────────────────────────────────────────
+
+fn recent_errors(config: &Config) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    let trace_path = crate::observability::runtime_trace::resolve_trace_path(
+        &config.observability,
+        &config.workspace_dir,
+    );
+    if let Ok(events) =
+        crate::observability::runtime_trace::load_events(&trace_path, TRACE_SCAN_LIMIT, None, None)
+    {
+        errors.extend(
+            events
+                .into_iter()
+                .filter(|e| e.success == Some(false))
+                .take(MAX_RECENT_ERRORS)
+                .map(|e| {
+                    format!(
+                        "{} {}: {}",
+                        e.timestamp,
+                        e.event_type,
+                        e.message.unwrap_or_default()
+                    )
+                }),
+        );
+    }
+
+    let log_path = config
+        .config_path
+        .parent()
+        .map_or_else(|| PathBuf::from("."), PathBuf::from)
+        .join("logs")
+        .join("daemon.stderr.log");
+    if let Some(tail) = read_tail(&log_path, LOG_TAIL_BYTES) {
+        let mut log_errors: Vec<String> = tail
+            .lines()
+            .filter(|line| line.contains("ERROR"))
+            .map(str::to_string)
+            .collect();
+        let skip = log_errors.len().saturating_sub(MAX_RECENT_ERRORS);
+        errors.extend(log_errors.drain(skip..));
+    }
+
+    errors
+}
+
+fn read_tail(path: &Path, max_bytes: u64) -> Option<String> {
+    use std::io::{Read, Seek, SeekFrom};
+    let mut file = std::fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(max_bytes)))
+        .ok()?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).ok()?;
+    Some(String::from_utf8_lossy(&buf).into_owned())
+}
+
+// ── Redaction ────────────────────────────────────────────────────
+
+/// Scrubs secrets, Nostr keys and the home directory from bundle text.
+struct Redactor {
+    secrets: Vec<String>,
+    home: Option<String>,
+}
+
+impl Redactor {
+    fn from_config(config: &Config) -> Self {
+        let mut secrets: Vec<String> = [
+            config.api_key.clone(),
+            config
+                .channels_config
+                .nostr
+                .as_ref()
+                .and_then(|n| n.nsec.clone()),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|s| !s.trim().is_empty())
+        .collect();
+        secrets.extend(
+            config
+                .model_providers
+                .values()
+                .filter_map(|p| p.api_key.clone())
+                .filter(|s| !s.trim().is_empty()),
+        );
+        Self {
+            secrets,
+            home: directories::UserDirs::new()
+                .map(|dirs| dirs.home_dir().display().to_string())
+                .filter(|home| home.len() > 1),
+        }
+    }
+
+    fn redact(&self, input: &str) -> String {
+        static KEY_PATTERN: OnceLock<Regex> = OnceLock::new();
+        let pattern = KEY_PATTERN.get_or_init(|| {
+            Regex::new(
+                r"\b(?:nsec1[02-9ac-hj-np-z]{20,}|npub1[02-9ac-hj-np-z]{20,}|[0-9a-fA-F]{64})\b",
+            )
+            .expect("valid key pattern")
+        });
+
+        let mut out = input.to_string();
+        for secret in &self.secrets {
+            out = out.replace(secret.as_str(), "[REDACTED]");
+        }
+        out = crate::providers::scrub_secret_patterns(&out);
+        out = pattern.replace_all(&out, "[REDACTED_KEY]").into_owned();
+        if let Some(home) = &self.home {
+            out = out.replace(home.as_str(), "~");
+        }
+        out
+    }
+}
+
+fn redact_bundle(bundle: &mut DiagnosticsBundle, redactor: &Redactor) {
+    if let Some(detail) = bundle.config_validation.detail.as_mut() {
+        *detail = redactor.redact(detail);
+    }
+    for check in &mut bundle.checks {
+        check.message = redactor.redact(&check.message);
+    }
+    for db in &mut bundle.sqlite {
+        db.path = redactor.redact(&db.path);
+        db.detail = redactor.redact(&db.detail);
+    }
+    for relay in &mut bundle.relays {
+        if let Some(error) = relay.error.as_mut() {
+            *error = redactor.redact(error);
+        }
+    }
+    if let Some(error) = bundle.provider.as_mut().and_then(|p| p.error.as_mut()) {
+        *error = redactor.redact(error);
+    }
+    for line in &mut bundle.recent_errors {
+        *line = redactor.redact(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor() -> Redactor {
+        Redactor {
+            secrets: vec!["super-secret-value".into()],
+            home: Some("/home/alice".into()),
+        }
+    }
+
+    #[test]
+    fn redact_scrubs_keys_secrets_and_home() {
+        let hex = "a".repeat(64);
+        let input = format!(
+            "key={hex} npub1qqqqqqqqqqqqqqqqqqqqqqqqqqqq token super-secret-value at /home/alice/.snowclaw"
+        );
+        let out = redactor().redact(&input);
+        assert!(!out.contains(&hex));
+        assert!(!out.contains("npub1"));
+        assert!(!out.contains("super-secret-value"));
+        assert!(out.contains("~/.snowclaw"));
+    }
+
+    #[test]
+    fn sqlite_check_reports_ok_and_corrupt_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let good = tmp.path().join("memory").join("brain.db");
+        std::fs::create_dir_all(good.parent().unwrap()).unwrap();
+        let conn = rusqlite::Connection::open(&good).unwrap();
+        conn.execute("CREATE TABLE t (x INTEGER)", []).unwrap();
+        drop(conn);
+        std::fs::write(tmp.path().join("broken.db"), b"not a database").unwrap();
+
+        let checks = check_sqlite_databases(tmp.path());
+        assert_eq!(checks.len(), 2);
+        let good_check = checks
+            .iter()
+            .find(|c| c.path.ends_with("brain.db"))
+            .unwrap();
+        assert!(good_check.ok);
+        let broken = checks
+            .iter()
+            .find(|c| c.path.ends_with("broken.db"))
+            .unwrap();
+        assert!(!broken.ok);
+        assert!(broken.detail.contains("no database key"));
+    }
+
+    #[test]
+    fn read_tail_returns_last_bytes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("log");
+        std::fs::write(&path, "0123456789").unwrap();
+        assert_eq!(read_tail(&path, 4).as_deref(), Some("6789"));
+        assert!(read_tail(&tmp.path().join("missing"), 4).is_none());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod bundle;
+
 use crate::config::Config;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
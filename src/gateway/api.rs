@@ -59,6 +59,12 @@ pub struct MemoryStoreBody {
     pub category: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct NostrCommandBody {
+    /// Owner command, e.g. `/mute #dev 1h`.
+    pub command: String,
+}
+
 #[derive(Deserialize)]
 pub struct CronAddBody {
     pub name: Option<String>,
@@ -916,6 +922,49 @@ pub async fn handle_api_health(
     Json(serde_json::json!({"health": snapshot})).into_response()
 }
 
+/// GET /api/nostr/live — live view of the Nostr channel (`snowclaw top`)
+pub async fn handle_api_nostr_live(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    match crate::channels::nostr_live::snapshot() {
+        Some(view) => Json(view).into_response(),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Nostr channel is not running"})),
+        )
+            .into_response(),
+    }
+}
+
+/// POST /api/nostr/command — run an owner command on the Nostr channel
+pub async fn handle_api_nostr_command(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<NostrCommandBody>,
+) -> impl IntoResponse {
+    use crate::channels::nostr_live::{self, CommandError};
+
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    match nostr_live::registry().run_command(&body.command).await {
+        Ok(reply) => Json(serde_json::json!({"reply": reply})).into_response(),
+        Err(e) => {
+            let status = match e {
+                CommandError::NotRunning => StatusCode::SERVICE_UNAVAILABLE,
+                CommandError::Invalid(_) => StatusCode::BAD_REQUEST,
+            };
+            (status, Json(serde_json::json!({"error": e.to_string()}))).into_response()
+        }
+    }
+}
+
 /// GET /api/pairing/devices — list paired devices
 pub async fn handle_api_pairing_devices(
     State(state): State<AppState>,
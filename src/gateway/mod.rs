@@ -20,6 +20,8 @@ use crate::channels::{
     SendMessage, WatiChannel, WhatsAppChannel,
 };
 use crate::config::Config;
+use crate::cost::pricing::Pricing;
+use crate::cost::stream::{self as cost_stream, StreamCost};
 use crate::cost::CostTracker;
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::providers::{self, ChatMessage, Provider};
@@ -897,6 +899,8 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .route("/api/cost", get(api::handle_api_cost))
         .route("/api/cli-tools", get(api::handle_api_cli_tools))
         .route("/api/health", get(api::handle_api_health))
+        .route("/api/nostr/live", get(api::handle_api_nostr_live))
+        .route("/api/nostr/command", post(api::handle_api_nostr_command))
         .route("/api/node-control", post(handle_node_control))
         // ── SSE event stream ──
         .route("/api/events", get(sse::handle_sse_events))
@@ -1393,6 +1397,30 @@ async fn handle_webhook_usage() -> impl IntoResponse {
     )
 }
 
+/// Start cost accounting for a streamed provider response when cost
+/// tracking is enabled. The prompt size is estimated from `messages`.
+/// A stream cost dropped unfinished, when the client disconnects, is
+/// recorded as cancelled.
+fn start_stream_cost(
+    state: &AppState,
+    provider_label: &str,
+    model: &str,
+    messages: &[ChatMessage],
+) -> Option<StreamCost> {
+    let tracker = state.cost_tracker.clone()?;
+    let rates = Pricing::from_config(&state.config.lock().cost).rates(provider_label, model);
+    let prompt_tokens = messages
+        .iter()
+        .map(|m| cost_stream::estimate_tokens(&m.content))
+        .sum();
+    Some(StreamCost::new(
+        tracker,
+        format!("{provider_label}/{model}"),
+        rates,
+        prompt_tokens,
+    ))
+}
+
 fn handle_webhook_streaming(
     state: AppState,
     prepared_messages: Vec<ChatMessage>,
@@ -1519,9 +1547,14 @@ fn handle_webhook_streaming(
     let provider_label_for_stream = provider_label.clone();
     let model_label_for_stream = model_label.clone();
     let mut stream_failed = false;
+    let mut stream_cost =
+        start_stream_cost(&state, &provider_label, &model_label, &prepared_messages);
 
     let sse_stream = provider_stream.map(move |result| match result {
         Ok(chunk) if chunk.is_final => {
+            if let Some(cost) = stream_cost.take() {
+                cost.finish(None);
+            }
             if !stream_failed {
                 let duration = started_at.elapsed();
                 state_for_stream.observer.record_event(
@@ -1554,6 +1587,9 @@ fn handle_webhook_streaming(
             if chunk.delta.is_empty() {
                 return Ok(Bytes::new());
             }
+            if let Some(cost) = stream_cost.as_mut() {
+                cost.add_output(cost_stream::estimate_tokens(&chunk.delta));
+            }
             let payload = serde_json::json!({
                 "delta": chunk.delta,
                 "model": model_label_for_stream
@@ -1562,6 +1598,9 @@ fn handle_webhook_streaming(
         }
         Err(e) => {
             stream_failed = true;
+            if let Some(cost) = stream_cost.take() {
+                cost.cancel();
+            }
             let duration = started_at.elapsed();
             let sanitized = providers::sanitize_api_error(&e.to_string());
 
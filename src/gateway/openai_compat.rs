@@ -523,9 +523,13 @@ fn handle_streaming(
     let provider_label_for_stream = provider_label.clone();
     let mut first_chunk = true;
     let mut errored = false;
+    let mut stream_cost = super::start_stream_cost(&state, &provider_label, &model, &messages);
 
     let sse_stream = provider_stream.map(move |result| match result {
         Ok(chunk) if chunk.is_final => {
+            if let Some(cost) = stream_cost.take() {
+                cost.finish(None);
+            }
             if !errored {
                 let duration = started_at.elapsed();
                 record_success(
@@ -538,6 +542,9 @@ fn handle_streaming(
             Ok::<_, std::io::Error>(axum::body::Bytes::from("data: [DONE]\n\n"))
         }
         Ok(chunk) => {
+            if let Some(cost) = stream_cost.as_mut() {
+                cost.add_output(crate::cost::stream::estimate_tokens(&chunk.delta));
+            }
             let role = if first_chunk {
                 first_chunk = false;
                 Some("assistant")
@@ -568,6 +575,9 @@ fn handle_streaming(
         }
         Err(e) => {
             errored = true;
+            if let Some(cost) = stream_cost.take() {
+                cost.cancel();
+            }
             let duration = started_at.elapsed();
             let msg = e.to_string();
             record_failure(
@@ -12,6 +12,9 @@ pub struct ComponentHealth {
     pub last_ok: Option<String>,
     pub last_error: Option<String>,
     pub restart_count: u64,
+    /// Component-specific status (e.g. indexing lag), replaced on each report.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -54,6 +57,7 @@ where
             last_ok: None,
             last_error: None,
             restart_count: 0,
+            detail: None,
         });
     update(entry);
     entry.updated_at = now;
@@ -82,6 +86,12 @@ pub fn bump_component_restart(component: &str) {
     });
 }
 
+pub fn set_component_detail(component: &str, detail: serde_json::Value) {
+    upsert_component(component, move |entry| {
+        entry.detail = Some(detail);
+    });
+}
+
 pub fn snapshot() -> HealthSnapshot {
     let components = registry().components.lock().clone();
 
@@ -181,4 +191,17 @@ mod tests {
         assert!(component_json["last_ok"].as_str().is_some());
         assert!(json["uptime_seconds"].as_u64().is_some());
     }
+
+    #[test]
+    fn component_detail_is_reported_with_status() {
+        let component = unique_component("health-detail");
+
+        mark_component_ok(&component);
+        set_component_detail(&component, serde_json::json!({"lag_seconds": 3}));
+
+        let json = snapshot_json();
+        let component_json = &json["components"][&component];
+        assert_eq!(component_json["status"], "ok");
+        assert_eq!(component_json["detail"]["lag_seconds"], 3);
+    }
 }
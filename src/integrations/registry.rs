@@ -1154,9 +1154,11 @@ mod tests {
         let mut config = Config::default();
         config.channels_config.nostr = Some(NostrConfig {
             nsec: Some("nsec1example".into()),
+            signer: crate::config::NostrSignerConfig::default(),
             relays: vec!["wss://relay.example.com".into()],
             allowed_pubkeys: vec!["*".into()],
             owner: None,
+            owners: Vec::new(),
             groups: vec![],
             respond_mode: "always".into(),
             group_respond_mode: std::collections::HashMap::new(),
@@ -1164,6 +1166,33 @@ mod tests {
             listen_dms: true,
             context_history: 5,
             extra_kinds: vec![],
+            digest: crate::config::NostrDigestConfig::default(),
+            banner: crate::config::NostrBannerConfig::default(),
+            key_filter: crate::config::KeyFilterConfig::default(),
+            index_redaction: crate::config::NostrIndexRedactionConfig::default(),
+            onboarding: crate::config::NostrOnboardingConfig::default(),
+            quota: crate::config::NostrQuotaConfig::default(),
+            answer_cache: crate::config::NostrAnswerCacheConfig::default(),
+            pipeline: crate::config::NostrPipelineConfig::default(),
+            proxy: crate::config::NostrProxyConfig::default(),
+            relay_trust: crate::config::NostrRelayTrustConfig::default(),
+            publish: crate::config::NostrPublishConfig::default(),
+            reconnect: crate::config::NostrReconnectConfig::default(),
+            catchup: crate::config::NostrCatchupConfig::default(),
+            dm_sessions: crate::config::NostrDmSessionConfig::default(),
+            dm_presence: crate::config::NostrDmPresenceConfig::default(),
+            link_index: crate::config::NostrLinkIndexConfig::default(),
+            reply_moderation: crate::config::NostrReplyModerationConfig::default(),
+            threading: crate::config::NostrThreadingConfig::default(),
+            split: crate::config::NostrSplitConfig::default(),
+            cost_alerts: crate::config::NostrCostAlertConfig::default(),
+            nip05: crate::config::NostrNip05Config::default(),
+            proactive: crate::config::NostrProactiveConfig::default(),
+            backfill: crate::config::NostrBackfillConfig::default(),
+            contact_pruning: crate::config::NostrContactPruningConfig::default(),
+            status_feed: crate::config::NostrStatusFeedConfig::default(),
+            subscriptions: crate::config::NostrSubscriptionsConfig::default(),
+            context_template: None,
         });
         let entries = all_integrations();
         let nostr = entries.iter().find(|e| e.name == "Nostr").unwrap();
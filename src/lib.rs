@@ -83,6 +83,7 @@ pub mod coordination;
 pub(crate) mod cost;
 pub(crate) mod cron;
 pub(crate) mod daemon;
+pub mod determinism;
 pub(crate) mod doctor;
 pub mod economic;
 pub mod gateway;
@@ -183,6 +184,38 @@ Examples:
         /// Telegram identity to allow (username without '@' or numeric user ID)
         identity: String,
     },
+    /// Export a Nostr group thread or DM conversation as markdown, JSONL or JSON
+    #[command(long_about = "\
+Export a Nostr group thread or DM conversation.
+
+DM conversations are read from the persisted DM history; group threads \
+combine the local message index with messages fetched from the configured \
+relays. The agent's own replies are included and senders are shown by \
+display name.
+
+Examples:
+  zeroclaw channel export '#dev'
+  zeroclaw channel export '#dev' --from 2026-03-01 --to 2026-03-07 --format json
+  zeroclaw channel export npub1... --format jsonl --output dm.jsonl")]
+    Export {
+        /// `#group` or the DM peer's npub/hex pubkey
+        target: String,
+        /// Output format: markdown, jsonl or json
+        #[arg(long, default_value = "markdown")]
+        format: String,
+        /// Maximum number of messages (newest kept)
+        #[arg(long)]
+        limit: Option<usize>,
+        /// First day to include (YYYY-MM-DD, UTC)
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day to include (YYYY-MM-DD, UTC)
+        #[arg(long)]
+        to: Option<String>,
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+    },
 }
 
 /// Skills management subcommands
@@ -241,10 +274,6 @@ pub enum MigrateCommands {
         #[arg(long)]
         source_config: Option<std::path::PathBuf>,
 
-        /// Validate and preview migration without writing any data
-        #[arg(long)]
-        dry_run: bool,
-
         /// Skip memory migration
         #[arg(long)]
         no_memory: bool,
@@ -417,10 +446,58 @@ pub enum MemoryCommands {
         progress: bool,
     },
     /// Migrate legacy SQLite memories to Nomen (kind 31234)
-    MigrateToNomen {
-        /// Preview what would be migrated without writing anything
+    MigrateToNomen,
+    /// Re-sync social records and/or agent memories from the Nostr relay
+    Sync {
+        /// Which records to pull
+        #[arg(long, value_enum, default_value = "all")]
+        scope: crate::memory::relay_sync::SyncScope,
+        /// Only events created at or after this Unix timestamp
+        #[arg(long)]
+        since: Option<u64>,
+        /// Only social records scoped to this group
         #[arg(long)]
-        dry_run: bool,
+        group: Option<String>,
+    },
+    /// Search social records, messages, documents and collective memories
+    Search {
+        /// Search query
+        query: String,
+        /// Only search this source (default: all)
+        #[arg(long, value_enum)]
+        source: Option<crate::memory::search_cli::SearchSource>,
+        /// Only results scoped to this group
+        #[arg(long)]
+        group: Option<String>,
+        /// Maximum results per source
+        #[arg(long, default_value = "10")]
+        limit: usize,
+        /// Print results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Apply `[channels_config.nostr.index_redaction]` to already indexed messages
+    Rescrub,
+    /// Show learned trust of collective memory sources and recent changes
+    Reputation {
+        /// Days of reputation changes to list
+        #[arg(long, default_value = "7")]
+        days: u64,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List memory writes waiting for owner approval (`[memory.write_policy]`)
+    Pending,
+    /// Approve and store a pending memory write
+    Approve {
+        /// Pending write id
+        id: u64,
+    },
+    /// Drop a pending memory write
+    Reject {
+        /// Pending write id
+        id: u64,
     },
 }
 
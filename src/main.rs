@@ -142,6 +142,7 @@ async fn open_url_in_default_browser(url: &str) -> Result<()> {
 }
 
 mod agent;
+mod agents_cli;
 mod approval;
 mod auth;
 mod channels;
@@ -150,6 +151,7 @@ mod coordination;
 mod cost;
 mod cron;
 mod daemon;
+mod determinism;
 mod doctor;
 mod gateway;
 mod goals;
@@ -166,8 +168,10 @@ mod migration;
 mod multimodal;
 mod observability;
 mod onboard;
+mod outbox_cli;
 mod peripherals;
 mod plugins;
+mod pricing_cli;
 mod providers;
 mod rag;
 mod runtime;
@@ -181,6 +185,7 @@ mod task_cli;
 #[cfg(test)]
 mod test_locks;
 mod tools;
+mod top;
 mod tunnel;
 mod update;
 mod util;
@@ -229,6 +234,10 @@ struct Cli {
     #[arg(long, global = true)]
     config_dir: Option<String>,
 
+    /// Report what destructive commands would change without changing it
+    #[arg(long, global = true)]
+    dry_run: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -466,7 +475,9 @@ Examples:
   snowclaw stats --period month           # this month
   snowclaw stats --room techteam          # filter by room
   snowclaw stats --json                   # JSON output
-  snowclaw stats --live                   # real-time TUI dashboard")]
+  snowclaw stats --live                   # real-time TUI dashboard
+  snowclaw stats --engagement             # group activity heatmap, last 7 days
+  snowclaw stats --engagement --period month --room techteam")]
     Stats {
         /// Show specific date (YYYY-MM-DD)
         #[arg(long)]
@@ -480,13 +491,30 @@ Examples:
         #[arg(long)]
         room: Option<String>,
 
-        /// Output as JSON
+        /// IANA timezone for day boundaries (overrides `[stats] timezone`)
         #[arg(long)]
+        tz: Option<String>,
+
+        /// Only show one breakdown: room, model, provider or tool
+        #[arg(long, value_enum)]
+        by: Option<stats::StatsGroup>,
+
+        /// Output as JSON (same as `--format json`)
+        #[arg(long, conflicts_with = "format")]
         json: bool,
 
+        /// Output format: text, json, or csv (one row per day and room)
+        #[arg(long, value_enum, default_value_t = stats::StatsFormat::Text)]
+        format: stats::StatsFormat,
+
         /// Launch real-time TUI dashboard
         #[arg(long)]
         live: bool,
+
+        /// Group activity heatmap, top contributors and reply rate from the
+        /// Nostr message index (defaults to the current week)
+        #[arg(long, conflicts_with_all = ["live", "by"])]
+        engagement: bool,
     },
 
     /// Engage, inspect, and resume emergency-stop states.
@@ -624,23 +652,107 @@ Examples:
         skill_command: SkillCommands,
     },
 
-    /// Manage Nostr-native tasks (create, list, status, update)
-    #[command(long_about = "\
-Manage Nostr-native tasks.
+    /// Manage the Nostr task board (add, list, status, update, done)
+    #[command(
+        name = "task",
+        alias = "tasks",
+        long_about = "\
+Manage the Nostr task board.
 
-Create, list, inspect, and update tasks using Nostr event kinds \
-(1621 for tasks, 1630-1637 for status transitions).
+Tasks are kept in social.db and published by the Nostr channel as kind \
+1621 events, with status transitions as kinds 1630-1637. Tasks have \
+sequential SNOW-N ids, optional assignees, a due date and a group.
 
 Examples:
-  snowclaw task list
-  snowclaw task create 'Build homepage' --assignee <npub>
-  snowclaw task status <event-id>
-  snowclaw task update <event-id> --status in-progress")]
+  snowclaw tasks list
+  snowclaw tasks list --assignee alice --all
+  snowclaw tasks add 'Build homepage' --assignee <npub> --due 2026-03-02 --group dev
+  snowclaw tasks status SNOW-1
+  snowclaw tasks update SNOW-1 --status executing
+  snowclaw tasks done SNOW-1 --detail 'shipped'"
+    )]
     Task {
         #[command(subcommand)]
         task_command: task_cli::TaskCommands,
     },
 
+    /// Inspect our own and peer agents in a multi-agent deployment
+    #[command(long_about = "\
+Inspect our own and peer agents in a multi-agent deployment.
+
+Reads the kind 31121 agent state history that the Nostr channel records \
+in social.db. --history adds per-day uptime, restarts and version \
+changes; agents whose version differs from the fleet's main version are \
+marked. An agent still `online` after three missed heartbeats (15 min each \
+by default) is shown as `stale`.
+
+Examples:
+  snowclaw agents status
+  snowclaw agents status --history
+  snowclaw agents status --history --days 30
+  snowclaw agents status --json")]
+    Agents {
+        #[command(subcommand)]
+        agents_command: agents_cli::AgentsCommands,
+    },
+
+    /// Manage model pricing used for cost tracking
+    #[command(long_about = "\
+Manage model pricing used for cost tracking.
+
+Prices (USD per 1M tokens) are stored in pricing.toml next to config.toml \
+and take precedence over [cost.prices] and the built-in table. Running \
+agents reload the file when it changes.
+
+Examples:
+  snowclaw pricing list
+  snowclaw pricing set openai/gpt-5 --input 1.25 --output 10
+  snowclaw pricing set anthropic/claude-sonnet-4 --input 3 --output 15 --cache-read 0.3 --cache-write 3.75
+  snowclaw pricing remove openai/gpt-5")]
+    Pricing {
+        #[command(subcommand)]
+        pricing_command: pricing_cli::PricingCommands,
+    },
+
+    /// Inspect the Nostr outbox of unsent events
+    #[command(long_about = "\
+Inspect the Nostr outbox of unsent events.
+
+Events that no relay accepted (replies, DMs, memory and profile updates) \
+are spooled in outbox.db next to config.toml and in nostr_sqlite/outbox.db \
+in the workspace, and retried with backoff while the agent runs.
+
+Examples:
+  snowclaw outbox status
+  snowclaw outbox status --json")]
+    Outbox {
+        #[command(subcommand)]
+        outbox_command: outbox_cli::OutboxCommands,
+    },
+
+    /// Live dashboard of the running agent
+    #[command(long_about = "\
+Live dashboard of the running agent.
+
+Connects to the daemon's local API and shows recent messages per group, \
+replies still pending, relay status, today's budget and recent security \
+flags. Keys act on the selected group: m mutes it for 1h, u unmutes, \
+s stops, r resumes, c sends the owner a catch-up; : runs any owner \
+command (e.g. /config set #dev mode=all). When pairing is required, pass \
+a paired bearer token with --token or SNOWCLAW_GATEWAY_TOKEN.
+
+Examples:
+  snowclaw top
+  snowclaw top --url http://127.0.0.1:42617 --token <token>")]
+    Top {
+        /// Gateway URL (default: from [gateway] host and port)
+        #[arg(long)]
+        url: Option<String>,
+        /// Gateway bearer token (default: $SNOWCLAW_GATEWAY_TOKEN)
+        #[arg(long)]
+        token: Option<String>,
+    },
+
     /// Migrate data from other agent runtimes
     Migrate {
         #[command(subcommand)]
@@ -716,6 +828,7 @@ Inspect, query, and modify configuration settings.
 
 Examples:
   zeroclaw config show                        # show effective config (secrets masked)
+  zeroclaw config show --effective            # snow-memory config with the source of each field
   zeroclaw config get gateway.port            # query a specific value by dot-path
   zeroclaw config set gateway.port 8080       # update a value and save to config.toml
   zeroclaw config schema                      # print full JSON Schema to stdout")]
@@ -744,7 +857,11 @@ Examples:
 #[derive(Subcommand, Debug)]
 enum ConfigCommands {
     /// Show the current effective configuration (secrets masked)
-    Show,
+    Show {
+        /// Show the layered snow-memory config with the source of each field
+        #[arg(long)]
+        effective: bool,
+    },
     /// Get a specific configuration value by dot-path (e.g. "gateway.port")
     Get {
         /// Dot-separated config path, e.g. "security.estop.enabled"
@@ -793,6 +910,9 @@ enum SecurityCommands {
         #[arg(long)]
         checksum: Option<String>,
     },
+    /// Encrypt plaintext memory, seen-events and social databases in place
+    /// (SQLCipher; stop the daemon first)
+    EncryptDatabases,
 }
 
 #[derive(Subcommand, Debug)]
@@ -936,6 +1056,18 @@ enum DoctorCommands {
         #[arg(long, default_value = "20")]
         limit: usize,
     },
+    /// Write a diagnostics bundle (checks, SQLite integrity, relay and provider probes, recent errors)
+    Bundle {
+        /// Output file (default: ./snowclaw-diagnostics-<timestamp>.json)
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+        /// Scrub keys, tokens and home paths so the bundle can be shared
+        #[arg(long)]
+        redact: bool,
+        /// Skip relay and provider network probes
+        #[arg(long)]
+        offline: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -976,11 +1108,53 @@ enum MemoryCommands {
         progress: bool,
     },
     /// Migrate legacy SQLite memories to Nomen (kind 31234)
-    MigrateToNomen {
-        /// Preview what would be migrated without writing anything
+    MigrateToNomen,
+    /// Re-sync social records and/or agent memories from the Nostr relay
+    Sync {
+        /// Which records to pull
+        #[arg(long, value_enum, default_value = "all")]
+        scope: crate::memory::relay_sync::SyncScope,
+        /// Only events created at or after this Unix timestamp
         #[arg(long)]
-        dry_run: bool,
+        since: Option<u64>,
+        /// Only social records scoped to this group
+        #[arg(long)]
+        group: Option<String>,
     },
+    /// Search social records, messages, documents and collective memories
+    Search {
+        /// Search query
+        query: String,
+        /// Only search this source (default: all)
+        #[arg(long, value_enum)]
+        source: Option<crate::memory::search_cli::SearchSource>,
+        /// Only results scoped to this group
+        #[arg(long)]
+        group: Option<String>,
+        /// Maximum results per source
+        #[arg(long, default_value = "10")]
+        limit: usize,
+        /// Print results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Apply `[channels_config.nostr.index_redaction]` to already indexed messages
+    Rescrub,
+    /// Show learned trust of collective memory sources and recent changes
+    Reputation {
+        /// Days of reputation changes to list
+        #[arg(long, default_value = "7")]
+        days: u64,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List memory writes waiting for owner approval (`[memory.write_policy]`)
+    Pending,
+    /// Approve and store a pending memory write
+    Approve { id: u64 },
+    /// Drop a pending memory write
+    Reject { id: u64 },
 }
 
 #[tokio::main]
@@ -1140,7 +1314,10 @@ async fn main() -> Result<()> {
     // All other commands need config loaded first
     let mut config = Config::load_or_init().await?;
     config.apply_env_overrides();
+    determinism::init_from_config(&mut config);
     observability::runtime_trace::init_from_config(&config.observability, &config.workspace_dir);
+    security::db_encryption::init(&config)?;
+    cost::pricing::init(&config.config_path);
     if config.security.otp.enabled {
         let config_dir = config
             .config_path
@@ -1364,7 +1541,8 @@ async fn main() -> Result<()> {
                 update::print_update_instructions()?;
                 Ok(())
             } else {
-                update::self_update(force, check).await?;
+                // A dry run only reports whether an update is available
+                update::self_update(force, check || cli.dry_run).await?;
                 Ok(())
             }
         }
@@ -1377,10 +1555,10 @@ async fn main() -> Result<()> {
         } => handle_estop_command(&config, estop_command, level, domains, tools),
 
         Commands::Security { security_command } => {
-            handle_security_command(&config, security_command).await
+            handle_security_command(&config, security_command, cli.dry_run).await
         }
 
-        Commands::Cron { cron_command } => cron::handle_command(cron_command, &config),
+        Commands::Cron { cron_command } => cron::handle_command(cron_command, &config, cli.dry_run),
 
         Commands::Models { model_command } => match model_command {
             ModelCommands::Refresh {
@@ -1450,7 +1628,7 @@ async fn main() -> Result<()> {
             service_init,
         } => {
             let init_system = service_init.parse()?;
-            service::handle_command(&service_command, &config, init_system)
+            service::handle_command(&service_command, &config, init_system, cli.dry_run)
         }
 
         Commands::Doctor { doctor_command } => match doctor_command {
@@ -1470,6 +1648,11 @@ async fn main() -> Result<()> {
                 contains.as_deref(),
                 limit,
             ),
+            Some(DoctorCommands::Bundle {
+                output,
+                redact,
+                offline,
+            }) => doctor::bundle::run(&config, output.as_deref(), redact, offline).await,
             None => doctor::run(&config),
         },
 
@@ -1483,24 +1666,49 @@ async fn main() -> Result<()> {
             integration_command,
         } => integrations::handle_command(integration_command, &config),
 
-        Commands::Skills { skill_command } => skills::handle_command(skill_command, &config),
+        Commands::Skills { skill_command } => {
+            skills::handle_command(skill_command, &config, cli.dry_run)
+        }
 
         Commands::Task { task_command } => task_cli::handle_command(task_command, &config),
 
+        Commands::Agents { agents_command } => agents_cli::handle_command(agents_command, &config),
+
+        Commands::Pricing { pricing_command } => {
+            pricing_cli::handle_command(pricing_command, &config, cli.dry_run)
+        }
+
+        Commands::Outbox { outbox_command } => outbox_cli::handle_command(outbox_command, &config),
+
+        Commands::Top { url, token } => {
+            // The dashboard uses a blocking HTTP client
+            tokio::task::spawn_blocking(move || top::run(&config, url, token)).await?
+        }
+
         Commands::Stats {
             date,
             period,
             room,
+            tz,
+            by,
             json,
+            format,
             live,
-        } => snowclaw_cli::handle_stats(&config, date, period, room, json, live),
+            engagement,
+        } => {
+            if engagement {
+                snowclaw_cli::handle_engagement(&config, date, period, room, tz, json, format)
+            } else {
+                snowclaw_cli::handle_stats(&config, date, period, room, tz, by, json, format, live)
+            }
+        }
 
         Commands::Migrate { migrate_command } => {
-            migration::handle_command(migrate_command, &config).await
+            migration::handle_command(migrate_command, &config, cli.dry_run).await
         }
 
         Commands::Memory { memory_command } => {
-            memory::cli::handle_command(memory_command, &config).await
+            memory::cli::handle_command(memory_command, &config, cli.dry_run).await
         }
 
         Commands::Auth { auth_command } => handle_auth_command(auth_command, &config).await,
@@ -1514,7 +1722,18 @@ async fn main() -> Result<()> {
         }
 
         Commands::Config { config_command } => match config_command {
-            ConfigCommands::Show => {
+            ConfigCommands::Show { effective: true } => {
+                let dynamic = memory::search_cli::open_collective_index(&config)?
+                    .and_then(|index| memory::collective::dynamic_config(&index));
+                let layered = memory::collective::effective_memory_config(
+                    &config.memory.collective,
+                    dynamic.as_deref(),
+                )
+                .context("Invalid snow-memory config")?;
+                print!("{}", layered.report());
+                Ok(())
+            }
+            ConfigCommands::Show { effective: false } => {
                 let mut json =
                     serde_json::to_value(&config).context("Failed to serialize config")?;
                 redact_config_secrets(&mut json);
@@ -1846,8 +2065,12 @@ fn write_shell_completion<W: Write>(shell: CompletionShell, writer: &mut W) -> R
 async fn handle_security_command(
     config: &Config,
     security_command: SecurityCommands,
+    dry_run: bool,
 ) -> Result<()> {
     match security_command {
+        SecurityCommands::UpdateGuardCorpus { .. } if dry_run => {
+            bail!("`security update-guard-corpus` does not support --dry-run")
+        }
         SecurityCommands::UpdateGuardCorpus { source, checksum } => {
             let report = security::semantic_guard::update_guard_corpus(
                 config,
@@ -1864,6 +2087,35 @@ async fn handle_security_command(
             println!("  Collection:       {}", report.collection);
             Ok(())
         }
+        SecurityCommands::EncryptDatabases if dry_run => {
+            for path in security::db_encryption::database_paths(config) {
+                let status = if !path.exists() {
+                    "not present"
+                } else if security::db_encryption::is_plaintext(&path)? {
+                    "would encrypt"
+                } else {
+                    "already encrypted"
+                };
+                println!("  [dry-run] {status:<18} {}", path.display());
+            }
+            Ok(())
+        }
+        SecurityCommands::EncryptDatabases => {
+            for (path, converted) in security::db_encryption::encrypt_databases(config)? {
+                let status = if converted {
+                    "encrypted"
+                } else if path.exists() {
+                    "already encrypted"
+                } else {
+                    "not present"
+                };
+                println!("  {status:<18} {}", path.display());
+            }
+            if !config.secrets.encrypt_databases {
+                println!("Set `encrypt_databases = true` under [secrets] to open them encrypted.");
+            }
+            Ok(())
+        }
     }
 }
 
@@ -2857,13 +3109,13 @@ mod tests {
         ])
         .expect("migrate openclaw flags should parse");
 
+        assert!(cli.dry_run);
         match cli.command {
             Commands::Migrate {
                 migrate_command:
                     MigrateCommands::Openclaw {
                         source,
                         source_config,
-                        dry_run,
                         no_memory,
                         no_config,
                     },
@@ -2876,7 +3128,6 @@ mod tests {
                     source_config.as_deref(),
                     Some(std::path::Path::new("/tmp/openclaw.json"))
                 );
-                assert!(dry_run);
                 assert!(!no_memory);
                 assert!(no_config);
             }
@@ -2943,11 +3194,20 @@ mod tests {
             Cli::try_parse_from(["zeroclaw", "config", "show"]).expect("config show should parse");
         match show.command {
             Commands::Config {
-                config_command: ConfigCommands::Show,
+                config_command: ConfigCommands::Show { effective: false },
             } => {}
             other => panic!("expected config show, got {other:?}"),
         }
 
+        let effective = Cli::try_parse_from(["zeroclaw", "config", "show", "--effective"])
+            .expect("config show --effective should parse");
+        match effective.command {
+            Commands::Config {
+                config_command: ConfigCommands::Show { effective: true },
+            } => {}
+            other => panic!("expected config show --effective, got {other:?}"),
+        }
+
         let get = Cli::try_parse_from(["zeroclaw", "config", "get", "gateway.port"])
             .expect("config get should parse");
         match get.command {
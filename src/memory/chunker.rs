@@ -1,8 +1,12 @@
-// Line-based markdown chunker — splits documents into semantic chunks.
+// Line-based document chunker — splits documents into semantic chunks.
 //
-// Splits on markdown headings and paragraph boundaries, respecting
-// a max token limit per chunk. Preserves heading context.
+// Splits on structure first (markdown headings, TOML tables, top-level code
+// items), then on paragraph and line boundaries, respecting a max token
+// limit per chunk. Preserves heading context, keeps fenced code blocks
+// whole where they fit, and can repeat the tail of a chunk at the start of
+// the next one so text near a split is findable from both sides.
 
+use std::path::Path;
 use std::rc::Rc;
 
 /// A single chunk of text with metadata.
@@ -13,6 +17,75 @@ pub struct Chunk {
     pub heading: Option<Rc<str>>,
 }
 
+/// How a document is split into sections before size-based splitting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkStrategy {
+    /// Sections at `#`–`###` headings; fenced code blocks are not split
+    /// at blank lines and headings inside them are ignored
+    Markdown,
+    /// Sections at top-level items (a blank line followed by an unindented
+    /// line); doc comments and attributes stay with their item
+    Code,
+    /// Sections at `[table]` and `[[array]]` headers, with the comments
+    /// right above a header
+    Toml,
+    /// Paragraphs only
+    Text,
+}
+
+impl ChunkStrategy {
+    /// Pick a strategy from a file extension. Unknown extensions are
+    /// treated as markdown.
+    pub fn for_path(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match ext.as_str() {
+            "rs" | "py" | "go" | "js" | "ts" | "c" | "h" | "cpp" | "java" | "kt" | "swift"
+            | "sh" => Self::Code,
+            "toml" => Self::Toml,
+            "txt" | "text" | "log" => Self::Text,
+            _ => Self::Markdown,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Code => "code",
+            Self::Toml => "toml",
+            Self::Text => "text",
+        }
+    }
+
+    /// Small neighbouring sections are packed into one chunk (code and
+    /// TOML), instead of one chunk per section (markdown).
+    fn packs_sections(self) -> bool {
+        matches!(self, Self::Code | Self::Toml)
+    }
+}
+
+/// Chunk size limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkOptions {
+    /// Max approximate tokens per chunk
+    pub max_tokens: usize,
+    /// Approximate tokens of trailing lines repeated at the start of the
+    /// next chunk of the same section (capped at half of `max_tokens`)
+    pub overlap_tokens: usize,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self {
+            max_tokens: 512,
+            overlap_tokens: 0,
+        }
+    }
+}
+
 /// Split markdown text into chunks, each under `max_tokens` approximate tokens.
 ///
 /// Strategy:
@@ -22,80 +95,64 @@ pub struct Chunk {
 ///
 /// Token estimation: ~4 chars per token (rough English average).
 pub fn chunk_markdown(text: &str, max_tokens: usize) -> Vec<Chunk> {
+    chunk_text(
+        text,
+        ChunkStrategy::Markdown,
+        &ChunkOptions {
+            max_tokens,
+            overlap_tokens: 0,
+        },
+    )
+}
+
+/// Split text into chunks using `strategy` for section boundaries.
+///
+/// A section that fits is one chunk. A larger one is split on paragraphs,
+/// then lines; a fenced code block that has to be split is re-fenced in
+/// every piece. Continuation chunks repeat the section heading.
+pub fn chunk_text(text: &str, strategy: ChunkStrategy, options: &ChunkOptions) -> Vec<Chunk> {
     if text.trim().is_empty() {
         return Vec::new();
     }
 
-    let max_chars = max_tokens * 4;
-    let sections = split_on_headings(text);
+    let max_chars = options.max_tokens * 4;
+    let overlap_chars = (options.overlap_tokens * 4).min(max_chars / 2);
+    let mut sections = split_sections(text, strategy);
+    if strategy.packs_sections() {
+        sections = pack_sections(sections, max_chars);
+    }
     let mut chunks = Vec::with_capacity(sections.len());
 
-    for (heading, body) in sections {
-        let heading: Option<Rc<str>> = heading.map(Rc::from);
-        let full = if let Some(ref h) = heading {
-            format!("{h}\n{body}")
-        } else {
-            body.clone()
+    for section in sections {
+        let heading: Option<Rc<str>> = section.heading.as_deref().map(Rc::from);
+        let full = match heading {
+            Some(ref h) if !section.heading_in_body => format!("{h}\n{}", section.body),
+            _ => section.body.clone(),
         };
 
         if full.len() <= max_chars {
             chunks.push(Chunk {
                 index: chunks.len(),
                 content: full.trim().to_string(),
-                heading: heading.clone(),
+                heading,
             });
-        } else {
-            // Split on paragraphs (blank lines)
-            let paragraphs = split_on_blank_lines(&body);
-            let mut current = heading
-                .as_deref()
-                .map_or_else(String::new, |h| format!("{h}\n"));
-
-            for para in paragraphs {
-                if current.len() + para.len() > max_chars && !current.trim().is_empty() {
-                    chunks.push(Chunk {
-                        index: chunks.len(),
-                        content: current.trim().to_string(),
-                        heading: heading.clone(),
-                    });
-                    current = heading
-                        .as_deref()
-                        .map_or_else(String::new, |h| format!("{h}\n"));
-                }
-
-                if para.len() > max_chars {
-                    // Paragraph too big — split on lines
-                    if !current.trim().is_empty() {
-                        chunks.push(Chunk {
-                            index: chunks.len(),
-                            content: current.trim().to_string(),
-                            heading: heading.clone(),
-                        });
-                        current = heading
-                            .as_deref()
-                            .map_or_else(String::new, |h| format!("{h}\n"));
-                    }
-                    for line_chunk in split_on_lines(&para, max_chars) {
-                        chunks.push(Chunk {
-                            index: chunks.len(),
-                            content: line_chunk.trim().to_string(),
-                            heading: heading.clone(),
-                        });
-                    }
-                } else {
-                    current.push_str(&para);
-                    current.push('\n');
-                }
-            }
+            continue;
+        }
 
-            if !current.trim().is_empty() {
-                chunks.push(Chunk {
-                    index: chunks.len(),
-                    content: current.trim().to_string(),
-                    heading: heading.clone(),
-                });
-            }
+        let mut builder = SectionChunks {
+            chunks: &mut chunks,
+            heading,
+            heading_in_body: section.heading_in_body,
+            max_chars,
+            overlap_chars,
+            current: String::new(),
+            carried: 0,
+            emitted: false,
+        };
+        for block in split_blocks(&section.body, strategy) {
+            builder.add_block(block);
         }
+        builder.flush();
     }
 
     // Filter out empty chunks
@@ -109,22 +166,238 @@ pub fn chunk_markdown(text: &str, max_tokens: usize) -> Vec<Chunk> {
     chunks
 }
 
+/// A heading (or item signature) and the text under it.
+struct Section {
+    heading: Option<String>,
+    body: String,
+    /// The heading line is part of `body` (code and TOML), so only
+    /// continuation chunks repeat it.
+    heading_in_body: bool,
+}
+
+/// A paragraph, or a whole fenced code block with its opening fence line.
+struct Block {
+    text: String,
+    fence: Option<String>,
+}
+
+/// Accumulates the blocks of one oversized section into chunks.
+struct SectionChunks<'a> {
+    chunks: &'a mut Vec<Chunk>,
+    heading: Option<Rc<str>>,
+    heading_in_body: bool,
+    max_chars: usize,
+    overlap_chars: usize,
+    /// Text of the chunk being built, without the heading prefix
+    current: String,
+    /// Bytes at the start of `current` carried over from the last chunk
+    carried: usize,
+    emitted: bool,
+}
+
+impl SectionChunks<'_> {
+    fn prefix_len(&self) -> usize {
+        match self.heading {
+            Some(ref h) if self.emitted || !self.heading_in_body => h.len() + 1,
+            _ => 0,
+        }
+    }
+
+    fn fits(&self, text: &str) -> bool {
+        self.prefix_len() + self.current.len() + text.len() <= self.max_chars
+    }
+
+    fn has_new_text(&self) -> bool {
+        !self.current[self.carried..].trim().is_empty()
+    }
+
+    /// Add a piece of text, starting a new chunk first if it does not fit.
+    fn add(&mut self, text: &str) {
+        if !self.fits(text) {
+            if self.has_new_text() {
+                self.flush();
+            }
+            if !self.fits(text) {
+                // The carried-over tail would push this piece over the limit.
+                self.current.clear();
+                self.carried = 0;
+            }
+        }
+        self.current.push_str(text);
+        if !text.ends_with('\n') {
+            self.current.push('\n');
+        }
+    }
+
+    fn add_block(&mut self, block: Block) {
+        let para = format!("{}\n", block.text.trim_end());
+        if self.fits(&para) {
+            self.add(&para);
+            return;
+        }
+        if self.has_new_text() {
+            self.flush();
+        }
+        if self.fits(&para) {
+            self.add(&para);
+            return;
+        }
+
+        // Block too big on its own — split on lines
+        let budget = self
+            .max_chars
+            .saturating_sub(self.heading.as_ref().map_or(0, |h| h.len() + 1));
+        match block.fence {
+            Some(open) => {
+                let mut lines: Vec<&str> = block.text.lines().skip(1).collect();
+                let close = match lines.last() {
+                    Some(last) if is_fence_close(last, &open) => lines.pop().unwrap_or_default(),
+                    _ => fence_marker(&open).unwrap_or("```"),
+                };
+                let inner = lines.join("\n");
+                let budget = budget.saturating_sub(open.len() + close.len() + 2);
+                for piece in split_on_lines(&inner, budget) {
+                    self.add(&format!("{open}\n{piece}{close}\n"));
+                }
+            }
+            None => {
+                for piece in split_on_lines(&block.text, budget) {
+                    self.add(&piece);
+                }
+            }
+        }
+    }
+
+    /// Emit the current chunk and carry its tail into the next one.
+    fn flush(&mut self) {
+        if !self.has_new_text() {
+            return;
+        }
+        let content = match self.heading {
+            Some(ref h) if self.emitted || !self.heading_in_body => {
+                format!("{h}\n{}", self.current)
+            }
+            _ => self.current.clone(),
+        };
+        self.chunks.push(Chunk {
+            index: self.chunks.len(),
+            content: content.trim().to_string(),
+            heading: self.heading.clone(),
+        });
+        self.emitted = true;
+
+        let tail = overlap_tail(&self.current, self.overlap_chars);
+        self.carried = tail.len();
+        self.current = tail;
+    }
+}
+
+/// Whole trailing lines of `text` totalling at most `max_chars`. Empty when
+/// they would cut through a fenced code block.
+fn overlap_tail(text: &str, max_chars: usize) -> String {
+    let mut lines = Vec::new();
+    let mut len = 0;
+    for line in text.lines().rev() {
+        if len + line.len() + 1 > max_chars {
+            break;
+        }
+        len += line.len() + 1;
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    if lines.iter().any(|line| fence_marker(line).is_some()) {
+        return String::new();
+    }
+    lines.iter().rev().fold(String::new(), |mut tail, line| {
+        tail.push_str(line);
+        tail.push('\n');
+        tail
+    })
+}
+
+/// Split text into sections according to `strategy`.
+fn split_sections(text: &str, strategy: ChunkStrategy) -> Vec<Section> {
+    match strategy {
+        ChunkStrategy::Markdown => split_on_headings(text)
+            .into_iter()
+            .map(|(heading, body)| Section {
+                heading,
+                body,
+                heading_in_body: false,
+            })
+            .collect(),
+        ChunkStrategy::Code => split_on_items(text),
+        ChunkStrategy::Toml => split_on_tables(text),
+        ChunkStrategy::Text => vec![Section {
+            heading: None,
+            body: text.to_string(),
+            heading_in_body: false,
+        }],
+    }
+}
+
+/// Merge neighbouring sections while the result fits in `max_chars`.
+fn pack_sections(sections: Vec<Section>, max_chars: usize) -> Vec<Section> {
+    let mut packed: Vec<Section> = Vec::with_capacity(sections.len());
+    for section in sections {
+        if let Some(last) = packed.last_mut() {
+            if last.body.trim_end().len() + 2 + section.body.len() <= max_chars {
+                last.body = format!("{}\n\n{}", last.body.trim_end(), section.body);
+                if last.heading.is_none() {
+                    last.heading = section.heading;
+                }
+                continue;
+            }
+        }
+        packed.push(section);
+    }
+    packed
+}
+
+/// Opening fence of a markdown code block (```` ``` ```` or `~~~`, possibly
+/// longer), if `line` starts one.
+fn fence_marker(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let ch = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.chars().take_while(|c| *c == ch).count();
+    (len >= 3).then(|| &trimmed[..len])
+}
+
+/// Whether `line` closes the fenced block opened by `open`.
+fn is_fence_close(line: &str, open: &str) -> bool {
+    let Some(marker) = fence_marker(open) else {
+        return false;
+    };
+    let trimmed = line.trim();
+    trimmed.starts_with(marker) && trimmed.chars().all(|c| marker.starts_with(c))
+}
+
 /// Split text into `(heading, body)` sections.
 fn split_on_headings(text: &str) -> Vec<(Option<String>, String)> {
     let mut sections = Vec::new();
     let mut current_heading: Option<String> = None;
     let mut current_body = String::new();
+    let mut fence: Option<&str> = None;
 
     for line in text.lines() {
-        if line.starts_with("# ") || line.starts_with("## ") || line.starts_with("### ") {
-            if !current_body.trim().is_empty() || current_heading.is_some() {
-                sections.push((current_heading.take(), std::mem::take(&mut current_body)));
+        match fence {
+            Some(open) if is_fence_close(line, open) => fence = None,
+            Some(_) => {}
+            None if fence_marker(line).is_some() => fence = Some(line),
+            None => {
+                if line.starts_with("# ") || line.starts_with("## ") || line.starts_with("### ") {
+                    if !current_body.trim().is_empty() || current_heading.is_some() {
+                        sections.push((current_heading.take(), std::mem::take(&mut current_body)));
+                    }
+                    current_heading = Some(line.to_string());
+                    continue;
+                }
             }
-            current_heading = Some(line.to_string());
-        } else {
-            current_body.push_str(line);
-            current_body.push('\n');
         }
+        current_body.push_str(line);
+        current_body.push('\n');
     }
 
     if !current_body.trim().is_empty() || current_heading.is_some() {
@@ -134,6 +407,157 @@ fn split_on_headings(text: &str) -> Vec<(Option<String>, String)> {
     sections
 }
 
+/// Whether a source line is a comment or attribute rather than code.
+fn is_code_annotation(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    ["//", "/*", "*", "#", "@", "--"]
+        .iter()
+        .any(|prefix| trimmed.starts_with(prefix))
+}
+
+/// Split source code into top-level items. An item starts at an unindented
+/// line after a blank line; its heading is the first line that is not a
+/// comment or attribute.
+fn split_on_items(text: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut body = String::new();
+    let mut after_blank = false;
+
+    let finish = |body: String, sections: &mut Vec<Section>| {
+        if body.trim().is_empty() {
+            return;
+        }
+        let heading = body
+            .lines()
+            .find(|line| !line.trim().is_empty() && !is_code_annotation(line))
+            .map(|line| line.trim().to_string());
+        sections.push(Section {
+            heading,
+            body,
+            heading_in_body: true,
+        });
+    };
+
+    for line in text.lines() {
+        let starts_item = after_blank
+            && !line.trim().is_empty()
+            && !line.starts_with(char::is_whitespace)
+            && !line.starts_with(['}', ')', ']']);
+        if starts_item {
+            finish(std::mem::take(&mut body), &mut sections);
+        }
+        after_blank = line.trim().is_empty();
+        body.push_str(line);
+        body.push('\n');
+    }
+    finish(body, &mut sections);
+
+    sections
+}
+
+/// Whether `line` is a TOML table or array-of-tables header.
+fn is_toml_header(line: &str) -> bool {
+    let code = line.split('#').next().unwrap_or_default().trim_end();
+    code.starts_with('[') && code.ends_with(']') && !code.contains(',')
+}
+
+/// Split TOML into tables. Comment lines directly above a header belong to
+/// that table.
+fn split_on_tables(text: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut heading: Option<String> = None;
+    let mut lines: Vec<&str> = Vec::new();
+
+    for line in text.lines() {
+        if is_toml_header(line) {
+            let comments = lines
+                .iter()
+                .rev()
+                .take_while(|l| l.trim_start().starts_with('#'))
+                .count();
+            let leading = lines.split_off(lines.len() - comments);
+            if lines.iter().any(|l| !l.trim().is_empty()) {
+                sections.push(Section {
+                    heading: heading.take(),
+                    body: lines.join("\n") + "\n",
+                    heading_in_body: true,
+                });
+            }
+            lines = leading;
+            heading = Some(line.trim().to_string());
+        }
+        lines.push(line);
+    }
+    if lines.iter().any(|l| !l.trim().is_empty()) {
+        sections.push(Section {
+            heading,
+            body: lines.join("\n") + "\n",
+            heading_in_body: true,
+        });
+    }
+
+    sections
+}
+
+/// Split a section body into paragraphs; for markdown, fenced code blocks
+/// are single blocks even when they contain blank lines.
+fn split_blocks(text: &str, strategy: ChunkStrategy) -> Vec<Block> {
+    if strategy != ChunkStrategy::Markdown {
+        return split_on_blank_lines(text)
+            .into_iter()
+            .map(|text| Block { text, fence: None })
+            .collect();
+    }
+
+    let mut blocks = Vec::new();
+    let mut current = String::new();
+    let mut fence: Option<String> = None;
+
+    for line in text.lines() {
+        if let Some(ref open) = fence {
+            current.push_str(line);
+            current.push('\n');
+            if is_fence_close(line, open) {
+                blocks.push(Block {
+                    text: std::mem::take(&mut current),
+                    fence: fence.take(),
+                });
+            }
+        } else if fence_marker(line).is_some() {
+            if !current.trim().is_empty() {
+                blocks.push(Block {
+                    text: std::mem::take(&mut current),
+                    fence: None,
+                });
+            }
+            current.clear();
+            current.push_str(line);
+            current.push('\n');
+            fence = Some(line.to_string());
+        } else if line.trim().is_empty() {
+            if !current.trim().is_empty() {
+                blocks.push(Block {
+                    text: std::mem::take(&mut current),
+                    fence: None,
+                });
+            }
+            current.clear();
+        } else {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+
+    if !current.trim().is_empty() {
+        blocks.push(Block {
+            text: current,
+            fence,
+        });
+    }
+
+    blocks
+}
+
 /// Split text on blank lines (paragraph boundaries)
 fn split_on_blank_lines(text: &str) -> Vec<String> {
     let mut paragraphs = Vec::new();
@@ -374,4 +798,180 @@ mod tests {
             );
         }
     }
+
+    // ── Strategies, code fences and overlap ─────────────────────
+
+    fn numbered_lines(prefix: &str, count: usize) -> String {
+        (0..count).fold(String::new(), |mut s, i| {
+            use std::fmt::Write;
+            let _ = writeln!(s, "{prefix} line {i} with a few more words.");
+            s
+        })
+    }
+
+    #[test]
+    fn strategy_for_path() {
+        assert_eq!(
+            ChunkStrategy::for_path(Path::new("notes/README.md")),
+            ChunkStrategy::Markdown
+        );
+        assert_eq!(
+            ChunkStrategy::for_path(Path::new("src/main.RS")),
+            ChunkStrategy::Code
+        );
+        assert_eq!(
+            ChunkStrategy::for_path(Path::new("Cargo.toml")),
+            ChunkStrategy::Toml
+        );
+        assert_eq!(
+            ChunkStrategy::for_path(Path::new("todo.txt")),
+            ChunkStrategy::Text
+        );
+        assert_eq!(
+            ChunkStrategy::for_path(Path::new("LICENSE")),
+            ChunkStrategy::Markdown
+        );
+    }
+
+    #[test]
+    fn fenced_code_block_is_not_split() {
+        let text = format!(
+            "## Setup\n{}\n```bash\n# install deps\ncargo fetch\n\ncargo build\n```\n\n{}",
+            numbered_lines("Intro", 4),
+            numbered_lines("Outro", 4)
+        );
+        let chunks = chunk_markdown(&text, 60);
+        assert!(chunks.len() > 1);
+        // "# install deps" inside the fence is not a heading.
+        assert!(chunks
+            .iter()
+            .all(|c| c.heading.as_deref() == Some("## Setup")));
+        let code = chunks
+            .iter()
+            .filter(|c| c.content.contains("```"))
+            .collect::<Vec<_>>();
+        assert_eq!(code.len(), 1);
+        assert!(code[0]
+            .content
+            .contains("```bash\n# install deps\ncargo fetch\n\ncargo build\n```"));
+    }
+
+    #[test]
+    fn oversized_code_block_is_refenced() {
+        let text = format!("```rust\n{}```\n", numbered_lines("let x = 1; //", 40));
+        let chunks = chunk_markdown(&text, 50);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.content.starts_with("```rust\n"), "{}", chunk.content);
+            assert!(chunk.content.ends_with("\n```"), "{}", chunk.content);
+        }
+    }
+
+    #[test]
+    fn overlap_repeats_tail_of_previous_chunk() {
+        let text = format!(
+            "## Log\n{}",
+            numbered_lines("Entry", 60).replace('\n', "\n\n")
+        );
+        let options = ChunkOptions {
+            max_tokens: 60,
+            overlap_tokens: 12,
+        };
+        let chunks = chunk_text(&text, ChunkStrategy::Markdown, &options);
+        assert!(chunks.len() > 2);
+        for pair in chunks.windows(2) {
+            let last_line = pair[0].content.lines().last().unwrap();
+            let next_body = pair[1].content.strip_prefix("## Log\n").unwrap();
+            assert!(
+                next_body.starts_with(last_line),
+                "{last_line:?} not repeated in {next_body:?}"
+            );
+            assert!(pair[1].content.len() <= 60 * 4);
+        }
+
+        // Without overlap nothing is repeated.
+        let plain = chunk_markdown(&text, 60);
+        let total: usize = plain
+            .iter()
+            .map(|c| c.content.matches("Entry").count())
+            .sum();
+        assert_eq!(total, 60);
+    }
+
+    #[test]
+    fn code_splits_at_items_and_packs_small_ones() {
+        let big_body = (0..30).fold(String::new(), |mut s, i| {
+            use std::fmt::Write;
+            let _ = writeln!(s, "    let value_{i} = compute({i});\n");
+            s
+        });
+        let text = format!(
+            "use std::fmt;\nuse std::io;\n\n/// Small helper.\nfn small() {{}}\n\n\
+             /// Does a lot.\n#[inline]\npub fn big() {{\n{big_body}}}\n\nfn tail() {{}}\n"
+        );
+        let options = ChunkOptions {
+            max_tokens: 60,
+            overlap_tokens: 0,
+        };
+        let chunks = chunk_text(&text, ChunkStrategy::Code, &options);
+
+        // Imports and the small fn share a chunk; docs stay with their item.
+        assert!(chunks[0].content.contains("use std::io;"));
+        assert!(chunks[0].content.contains("/// Small helper.\nfn small()"));
+        assert!(chunks[1]
+            .content
+            .starts_with("/// Does a lot.\n#[inline]\npub fn big()"));
+        // Continuations of the big fn name it; the next item starts fresh.
+        let big: Vec<_> = chunks
+            .iter()
+            .filter(|c| c.heading.as_deref() == Some("pub fn big() {"))
+            .collect();
+        assert!(big.len() > 1);
+        assert!(big[1..]
+            .iter()
+            .all(|c| c.content.starts_with("pub fn big() {\n")));
+        assert_eq!(chunks.last().unwrap().content, "fn tail() {}");
+    }
+
+    #[test]
+    fn toml_splits_at_tables_with_their_comments() {
+        let text = format!(
+            "title = \"demo\"\n\n# Server settings\n[server]\nport = 8080\n\n[[plugins]]\nname = \"a\"\nvalues = [\n  [1, 2],\n]\n{}",
+            (0..20).fold(String::from("\n[limits]\n"), |mut s, i| {
+                use std::fmt::Write;
+                let _ = writeln!(s, "limit_{i} = {i}");
+                s
+            })
+        );
+        let options = ChunkOptions {
+            max_tokens: 25,
+            overlap_tokens: 0,
+        };
+        let chunks = chunk_text(&text, ChunkStrategy::Toml, &options);
+
+        let server = chunks
+            .iter()
+            .find(|c| c.content.contains("[server]"))
+            .unwrap();
+        assert!(server.content.contains("# Server settings\n[server]"));
+        // An inline array of arrays is not a table header.
+        assert!(chunks
+            .iter()
+            .any(|c| c.content.contains("name = \"a\"") && c.content.contains("[1, 2],")));
+        // Split tables repeat their header.
+        let limits: Vec<_> = chunks
+            .iter()
+            .filter(|c| c.heading.as_deref() == Some("[limits]"))
+            .collect();
+        assert!(limits.len() > 1);
+        assert!(limits.iter().all(|c| c.content.starts_with("[limits]\n")));
+    }
+
+    #[test]
+    fn text_strategy_ignores_headings() {
+        let text = "# not a heading\nplain text\n\n## still plain";
+        let chunks = chunk_text(text, ChunkStrategy::Text, &ChunkOptions::default());
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].heading.is_none());
+    }
 }
@@ -9,8 +9,13 @@ use anyhow::Context;
 use anyhow::{bail, Result};
 use console::style;
 
-/// Handle `zeroclaw memory <subcommand>` CLI commands.
-pub async fn handle_command(command: crate::MemoryCommands, config: &Config) -> Result<()> {
+/// Handle `zeroclaw memory <subcommand>` CLI commands. With `dry_run`,
+/// `clear` and `reindex` only report what they would change.
+pub async fn handle_command(
+    command: crate::MemoryCommands,
+    config: &Config,
+    dry_run: bool,
+) -> Result<()> {
     match command {
         crate::MemoryCommands::List {
             category,
@@ -21,15 +26,148 @@ pub async fn handle_command(command: crate::MemoryCommands, config: &Config) ->
         crate::MemoryCommands::Get { key } => handle_get(config, &key).await,
         crate::MemoryCommands::Stats => handle_stats(config).await,
         crate::MemoryCommands::Clear { key, category, yes } => {
-            handle_clear(config, key, category, yes).await
+            handle_clear(config, key, category, yes, dry_run).await
         }
         crate::MemoryCommands::Reindex { yes, progress } => {
-            handle_reindex(config, yes, progress).await
+            handle_reindex(config, yes, progress, dry_run).await
         }
-        crate::MemoryCommands::MigrateToNomen { .. } => {
+        crate::MemoryCommands::MigrateToNomen => {
             anyhow::bail!("migrate-to-nomen has been removed (nomen embedded backend removed)")
         }
+        crate::MemoryCommands::Sync {
+            scope,
+            since,
+            group,
+        } => {
+            let filter = super::relay_sync::RelaySyncFilter { since, group };
+            super::relay_sync::handle_sync(config, scope, filter).await
+        }
+        crate::MemoryCommands::Search {
+            query,
+            source,
+            group,
+            limit,
+            json,
+        } => {
+            let options = super::search_cli::SearchOptions {
+                query,
+                source,
+                group,
+                limit,
+                json,
+            };
+            super::search_cli::handle_search(config, &options)
+        }
+        crate::MemoryCommands::Rescrub => handle_rescrub(config, dry_run),
+        crate::MemoryCommands::Reputation { days, json } => {
+            super::reputation_cli::handle_reputation(config, days, json)
+        }
+        crate::MemoryCommands::Pending => handle_pending(config),
+        crate::MemoryCommands::Approve { id } => handle_approve(config, id, dry_run).await,
+        crate::MemoryCommands::Reject { id } => handle_reject(config, id, dry_run),
+    }
+}
+
+/// List writes held by `[memory.write_policy]`.
+fn handle_pending(config: &Config) -> Result<()> {
+    let writes = super::write_policy::pending(&config.workspace_dir)?;
+    if writes.is_empty() {
+        println!("No memory writes waiting for approval.");
+        return Ok(());
+    }
+    println!("{} memory writes waiting for approval:\n", writes.len());
+    for write in &writes {
+        let group = write
+            .group_id
+            .as_deref()
+            .map(|g| format!(" in #{g}"))
+            .unwrap_or_default();
+        println!(
+            "#{} {} [{}]{group}",
+            write.id,
+            style(&write.key).white().bold(),
+            write.category
+        );
+        println!("    {}", truncate_content(&write.content, 80));
+    }
+    println!("\nApprove with `memory approve <id>` or drop with `memory reject <id>`.");
+    Ok(())
+}
+
+/// Held write `id`, if it is still waiting for approval.
+fn pending_write(config: &Config, id: u64) -> Result<Option<super::write_policy::PendingWrite>> {
+    Ok(super::write_policy::pending(&config.workspace_dir)?
+        .into_iter()
+        .find(|write| write.id == id))
+}
+
+async fn handle_approve(config: &Config, id: u64, dry_run: bool) -> Result<()> {
+    if dry_run {
+        match pending_write(config, id)? {
+            Some(write) => println!(
+                "[dry-run] Would store memory write #{id}: {} ({})",
+                write.key, write.category
+            ),
+            None => println!("No memory write #{id} waiting for approval."),
+        }
+        return Ok(());
+    }
+    let mem = create_memory(&config.memory, &config.workspace_dir, None)?;
+    match super::write_policy::approve(mem.as_ref(), &config.workspace_dir, id).await? {
+        Some(write) => println!("Stored memory: {} ({})", write.key, write.category),
+        None => println!("No memory write #{id} waiting for approval."),
+    }
+    Ok(())
+}
+
+fn handle_reject(config: &Config, id: u64, dry_run: bool) -> Result<()> {
+    if dry_run {
+        match pending_write(config, id)? {
+            Some(write) => println!("[dry-run] Would drop memory write #{id}: {}", write.key),
+            None => println!("No memory write #{id} waiting for approval."),
+        }
+        return Ok(());
+    }
+    match super::write_policy::reject(&config.workspace_dir, id)? {
+        Some(write) => println!("Dropped memory write #{id}: {}", write.key),
+        None => println!("No memory write #{id} waiting for approval."),
     }
+    Ok(())
+}
+
+/// Re-run index redaction over the message index in `social.db`.
+fn handle_rescrub(config: &Config, dry_run: bool) -> Result<()> {
+    let nostr = config.channels_config.nostr.as_ref();
+    let key_filter = crate::channels::nostr::build_key_filter(
+        &nostr.map(|n| n.key_filter.clone()).unwrap_or_default(),
+    )?;
+    let redaction = nostr.map(|n| n.index_redaction.clone()).unwrap_or_default();
+    let Some(redactor) =
+        super::index_redaction::IndexRedactor::from_config(&redaction, key_filter)?
+    else {
+        bail!(
+            "Index redaction is disabled ([channels_config.nostr.index_redaction] enabled = false)"
+        );
+    };
+
+    let social_db = config
+        .config_path
+        .parent()
+        .unwrap_or(std::path::Path::new("."))
+        .join("social.db");
+    if !social_db.exists() {
+        println!("No message index at {}", social_db.display());
+        return Ok(());
+    }
+    let conn =
+        crate::security::db_encryption::open(&social_db).context("Failed to open social.db")?;
+    let stats = super::index_redaction::rescrub_messages(&conn, &redactor, dry_run)?;
+    let verb = if dry_run { "Would update" } else { "Updated" };
+    println!(
+        "{verb} {} of {} indexed messages ({} redactions).",
+        stats.updated, stats.scanned, stats.redactions
+    );
+    Ok(())
 }
 
 /// Create a lightweight memory backend for CLI management operations.
@@ -215,12 +353,13 @@ async fn handle_clear(
     key: Option<String>,
     category: Option<String>,
     yes: bool,
+    dry_run: bool,
 ) -> Result<()> {
     let mem = create_cli_memory(config)?;
 
     // Single-key deletion (exact or prefix match).
     if let Some(key) = key {
-        return handle_clear_key(&*mem, &key, yes).await;
+        return handle_clear_key(&*mem, &key, yes, dry_run).await;
     }
 
     // Batch deletion by category (or all).
@@ -235,6 +374,14 @@ async fn handle_clear(
     let scope = category.as_deref().unwrap_or("all categories");
     println!("Found {} entries in '{scope}'.", entries.len());
 
+    if dry_run {
+        for entry in &entries {
+            println!("- {} [{}]", entry.key, entry.category);
+        }
+        println!("[dry-run] Would delete {} entries.", entries.len());
+        return Ok(());
+    }
+
     if !yes {
         let confirmed = dialoguer::Confirm::new()
             .with_prompt(format!("  Delete {} entries?", entries.len()))
@@ -263,7 +410,7 @@ async fn handle_clear(
 }
 
 /// Delete a single entry by exact key or prefix match.
-async fn handle_clear_key(mem: &dyn Memory, key: &str, yes: bool) -> Result<()> {
+async fn handle_clear_key(mem: &dyn Memory, key: &str, yes: bool, dry_run: bool) -> Result<()> {
     // Resolve the target key (exact match or unique prefix).
     let target = if mem.get(key).await?.is_some() {
         key.to_string()
@@ -291,6 +438,11 @@ async fn handle_clear_key(mem: &dyn Memory, key: &str, yes: bool) -> Result<()>
         }
     };
 
+    if dry_run {
+        println!("[dry-run] Would delete key: {target}");
+        return Ok(());
+    }
+
     if !yes {
         let confirmed = dialoguer::Confirm::new()
             .with_prompt(format!("  Delete '{target}'?"))
@@ -310,7 +462,7 @@ async fn handle_clear_key(mem: &dyn Memory, key: &str, yes: bool) -> Result<()>
 }
 
 /// Rebuild embeddings for all memories using current embedding configuration.
-async fn handle_reindex(config: &Config, yes: bool, progress: bool) -> Result<()> {
+async fn handle_reindex(config: &Config, yes: bool, progress: bool, dry_run: bool) -> Result<()> {
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
 
@@ -334,6 +486,11 @@ async fn handle_reindex(config: &Config, yes: bool, progress: bool) -> Result<()
         "  This will clear the embedding cache and recompute all embeddings\n  using the current embedding provider configuration.\n"
     );
 
+    if dry_run {
+        println!("[dry-run] Would recompute embeddings for {total} memories.");
+        return Ok(());
+    }
+
     if !yes {
         let confirmed = dialoguer::Confirm::new()
             .with_prompt("  Proceed with reindex?")
@@ -389,7 +546,7 @@ fn parse_category(s: &str) -> MemoryCategory {
 
 
 
-fn truncate_content(s: &str, max_len: usize) -> String {
+pub(crate) fn truncate_content(s: &str, max_len: usize) -> String {
     let line = s.lines().next().unwrap_or(s);
     if line.len() <= max_len {
         return line.to_string();
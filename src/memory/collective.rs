@@ -8,19 +8,55 @@
 //! ## Relay sync (Phase 2+3)
 //!
 //! When relay URLs and an nsec are configured, the backend will:
-//! - Publish kind 30078 events to relay after each `store()` (fire-and-forget)
+//! - Publish kind 30078 events to relay after each `store()` (fire-and-forget,
+//!   or retried until enough relays acknowledge when
+//!   `[memory.collective.quorum]` covers the memory's tier)
 //! - Sync events from relay on startup via `sync_from_relay()`
 //! - Track `last_sync_timestamp` in the DB for incremental syncs
+//! - Scan recently changed topics for conflicts after each sync, when
+//!   `[memory.collective.conflicts]` is enabled
+//!
+//! ## Layered ranking config
+//!
+//! The snow-memory ranking config is `[memory.collective]`, overridden by
+//! `SNOW_MEMORY_*` env vars and then by the agent's NIP-78 event with d-tag
+//! `snow:config:memory`, which relay sync keeps in the collective DB
+//! ([`effective_memory_config`]). Relay lists only come from the file.
+//!
+//! ## Source reputation
+//!
+//! With `[memory.collective.reputation]` enabled, resolved conflicts and
+//! owner corrections adjust a learned trust factor per source, stored in
+//! the collective DB. [`ranking_config`] scales `source_preferences` trust
+//! by it for every ranked search.
+//!
+//! ## Group namespaces
+//!
+//! Inside a group conversation (see [`super::snowclaw_ext::scope`]), stores
+//! are scoped to that group's namespace and recall, `get` and `list` hide
+//! private memories and other groups' memories, unless the group is listed
+//! in `[memory.collective.groups] shared`.
+//!
+//! Private memories are hidden in every conversation except the owner's DM
+//! session, whether or not groups are isolated.
 
-use super::snowclaw_ext::RecallContext;
+use super::snowclaw_ext::{current_context, RecallContext};
 use super::traits::{Memory, MemoryCategory, MemoryEntry};
-use crate::config::snowclaw_schema::CollectiveMemoryConfig;
+use crate::config::snowclaw_schema::{
+    CollectiveConflictsConfig, CollectiveGroupsConfig, CollectiveMemoryConfig,
+    CollectiveQuorumConfig, CollectiveReputationConfig,
+};
+use anyhow::Context as _;
 use async_trait::async_trait;
-use nostr_sdk::nips::nip44;
+use nostr_core::Signer;
+use nostr_sdk::NostrSigner as _;
 use parking_lot::Mutex;
-use snow_memory::types::{Memory as SnowMemory, MemoryTier};
-use snow_memory::SqliteMemoryIndex;
+use snow_memory::config::MemoryConfig;
+use snow_memory::loader::{DYNAMIC_CONFIG_D_TAG, ENV_PREFIX};
+use snow_memory::types::{Memory as SnowMemory, MemoryTier, ReputationChange, ReputationReason};
+use snow_memory::{ConfigError, ConfigLoader, Layered, SqliteMemoryIndex};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
@@ -44,6 +80,50 @@ pub struct ConflictEntry {
     pub version: u32,
 }
 
+// ── Review types ────────────────────────────────────────────────
+
+/// Tag on memories that review found outdated. Superseded memories are kept
+/// (and published) so other agents learn about it, but recall skips them.
+pub const SUPERSEDED_TAG: &str = "superseded";
+
+/// Tag on local memories not yet acknowledged by a quorum of relays. It is
+/// never published and is cleared once quorum is reached.
+pub const PROVISIONAL_TAG: &str = "provisional";
+
+/// Upper bound on how many memories a review pass scans for due entries.
+const REVIEW_SCAN_LIMIT: usize = 1000;
+
+/// Cap on the review interval doubling (`stale_after * 2^passes`).
+const MAX_REVIEW_DOUBLINGS: u32 = 4;
+
+/// Tag on memories the distillation job extracted from indexed messages.
+pub const DISTILLED_TAG: &str = "distilled";
+
+/// Prefix of the context line recording the events a distilled memory came from.
+pub const DISTILL_SOURCES_PREFIX: &str = "sources: ";
+
+/// Share of words two memories must have in common to count as duplicates.
+const DUPLICATE_WORD_OVERLAP: f64 = 0.8;
+
+// ── Archive tier ────────────────────────────────────────────────
+
+/// Recall queries starting with this prefix also search the archive and
+/// rehydrate matching memories before the normal search runs.
+pub const ARCHIVE_QUERY_PREFIX: &str = "archive:";
+
+const SECS_PER_MONTH: u64 = 30 * 86_400;
+
+/// Outcome of re-validating a memory against recent messages and documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewVerdict {
+    /// Still accurate: refresh its timestamp.
+    Refresh,
+    /// Partly outdated or too broad to share: lower visibility one tier.
+    Demote,
+    /// No longer true: mark it superseded.
+    Supersede,
+}
+
 /// Agent profile for Nostr kind 0 (metadata) events.
 ///
 /// Published as a replaceable event so relays always serve the latest version.
@@ -60,7 +140,7 @@ pub struct AgentProfile {
 
 /// Collective memory backend backed by `snow-memory` `SqliteMemoryIndex`.
 pub struct CollectiveMemory {
-    index: Mutex<SqliteMemoryIndex>,
+    index: Arc<Mutex<SqliteMemoryIndex>>,
     config: CollectiveMemoryConfig,
     #[allow(dead_code)]
     db_path: PathBuf,
@@ -68,10 +148,10 @@ pub struct CollectiveMemory {
     relay: Option<RelayState>,
 }
 
-/// Holds the nostr_sdk Client + Keys for relay operations.
+/// Holds the nostr_sdk Client + Signer for relay operations.
 struct RelayState {
     client: nostr_sdk::Client,
-    keys: nostr_sdk::Keys,
+    signer: Signer,
 }
 
 impl CollectiveMemory {
@@ -105,8 +185,15 @@ impl CollectiveMemory {
             std::fs::create_dir_all(parent)?;
         }
 
-        let index = SqliteMemoryIndex::open(&db_path)
-            .map_err(|e| anyhow::anyhow!("failed to open collective memory DB: {e}"))?;
+        let layered = effective_memory_config(config, None)
+            .context("invalid collective memory ranking config")?;
+        for warning in &layered.warnings {
+            tracing::warn!("collective memory config: {warning}");
+        }
+        quorum_min_rank(&config.quorum)?;
+        validate_relay_urls(config)?;
+        let index =
+            SqliteMemoryIndex::open(&db_path).context("failed to open collective memory DB")?;
 
         // Initialize metadata table for sync tracking
         init_metadata_table(&index)?;
@@ -114,29 +201,31 @@ impl CollectiveMemory {
         let relay = Self::init_relay(config, nsec);
 
         let mem = Self {
-            index: Mutex::new(index),
+            index: Arc::new(Mutex::new(index)),
             config: config.clone(),
             db_path,
             relay,
         };
 
+        if let Err(e) = mem.archive_untouched(now_unix()) {
+            tracing::warn!("collective memory: archiving failed: {e}");
+        }
+
         // Spawn background relay connect + sync if relay is configured.
         // This is fire-and-forget — errors are logged, not propagated.
         if mem.relay_enabled() {
             // We need to share `mem` with the spawned task. Since we return
             // mem as Box<dyn Memory> (owned), we can't share it easily.
             // Instead, clone the relay state and config for the background task.
-            let relay_urls = mem.config.relay_urls.clone();
+            let relay_urls = mem.config.all_relay_urls();
             let relay_client = mem.relay.as_ref().unwrap().client.clone();
             // For sync, we need index access — use a separate connection to the same DB.
             let sync_db_path = mem.db_path.clone();
-            let relay_keys = mem.relay.as_ref().unwrap().keys.clone();
+            let relay_signer = mem.relay.as_ref().unwrap().signer.clone();
+            let sync_config = mem.config.clone();
             tokio::spawn(async move {
-                // Connect to relays
-                for url in &relay_urls {
-                    if let Err(e) = relay_client.add_relay(url.as_str()).await {
-                        tracing::warn!("collective memory: failed to add relay {url}: {e}");
-                    }
+                if !add_relays(&relay_client, &relay_urls).await {
+                    return;
                 }
                 relay_client.connect().await;
                 tracing::info!(
@@ -145,7 +234,9 @@ impl CollectiveMemory {
                 );
 
                 // Incremental sync from relay
-                if let Err(e) = background_sync(&relay_client, &relay_keys, &sync_db_path).await {
+                if let Err(e) =
+                    background_sync(&relay_client, &relay_signer, &sync_db_path, &sync_config).await
+                {
                     tracing::warn!("collective memory: startup sync failed: {e}");
                 }
             });
@@ -162,7 +253,7 @@ impl CollectiveMemory {
         init_metadata_table(&index)?;
 
         Ok(Self {
-            index: Mutex::new(index),
+            index: Arc::new(Mutex::new(index)),
             config: config.clone(),
             db_path: PathBuf::from(":memory:"),
             relay: None,
@@ -182,7 +273,7 @@ impl CollectiveMemory {
         let relay = Self::init_relay(config, Some(nsec));
 
         Ok(Self {
-            index: Mutex::new(index),
+            index: Arc::new(Mutex::new(index)),
             config: config.clone(),
             db_path: PathBuf::from(":memory:"),
             relay,
@@ -192,20 +283,20 @@ impl CollectiveMemory {
     /// Try to create a relay state from config + nsec.
     fn init_relay(config: &CollectiveMemoryConfig, nsec: Option<&str>) -> Option<RelayState> {
         let nsec = nsec?;
-        if config.relay_urls.is_empty() {
+        if config.all_relay_urls().is_empty() {
             return None;
         }
 
-        let keys = match nostr_sdk::Keys::parse(nsec) {
-            Ok(k) => k,
+        let signer = match crate::channels::snowclaw_channels::signer_or_shared(Some(nsec)) {
+            Ok(signer) => signer?,
             Err(e) => {
-                tracing::warn!("collective memory: invalid nsec, relay sync disabled: {e}");
+                tracing::warn!("collective memory: invalid nsec, relay sync disabled: {e:#}");
                 return None;
             }
         };
 
-        let client = nostr_sdk::Client::new(keys.clone());
-        Some(RelayState { client, keys })
+        let client = nostr_sdk::Client::new(signer.clone());
+        Some(RelayState { client, signer })
     }
 
     /// Connect the relay client to configured relay URLs.
@@ -216,16 +307,15 @@ impl CollectiveMemory {
             None => return,
         };
 
-        for url in &self.config.relay_urls {
-            if let Err(e) = relay.client.add_relay(url.as_str()).await {
-                tracing::warn!("collective memory: failed to add relay {url}: {e}");
-            }
+        let relay_urls = self.config.all_relay_urls();
+        if !add_relays(&relay.client, &relay_urls).await {
+            return;
         }
 
         relay.client.connect().await;
         tracing::info!(
             "collective memory: connected to {} relay(s)",
-            self.config.relay_urls.len()
+            relay_urls.len()
         );
     }
 
@@ -335,12 +425,12 @@ impl CollectiveMemory {
         let source = self
             .relay
             .as_ref()
-            .map(|r| r.keys.public_key().to_hex())
+            .map(|r| r.signer.public_key().to_hex())
             .unwrap_or_else(|| "self".to_string());
 
         let memory = SnowMemory {
             id,
-            tier,
+            tier: self.scoped_tier(tier),
             topic: key.to_string(),
             summary,
             detail,
@@ -356,11 +446,83 @@ impl CollectiveMemory {
 
         {
             let idx = self.index.lock();
+            rehydrate_conflicting(&idx, &memory.topic);
             idx.upsert(&memory, None)
                 .map_err(|e| anyhow::anyhow!("collective store failed: {e}"))?;
+            self.record_owner_corrections(&idx, &memory);
+        }
+
+        self.publish_to_relay(&memory);
+        Ok(())
+    }
+
+    /// Store a fact distilled from indexed messages unless an equivalent
+    /// memory already exists. The ids of the `sources` events are recorded
+    /// in the memory's context. Returns `false` for duplicates.
+    pub fn store_distilled(
+        &self,
+        topic: &str,
+        content: &str,
+        tier: MemoryTier,
+        sources: &[String],
+        now: u64,
+    ) -> anyhow::Result<bool> {
+        let (summary, detail) = match content.split_once("\n\n") {
+            Some((s, d)) => (s.trim().to_string(), d.trim().to_string()),
+            None => (content.trim().to_string(), String::new()),
+        };
+
+        let idx = self.index.lock();
+        if find_duplicate(&idx, topic, &summary)?.is_some() {
+            return Ok(false);
         }
 
+        let memory = SnowMemory {
+            id: Uuid::new_v4().to_string(),
+            tier,
+            topic: topic.to_string(),
+            summary,
+            detail,
+            context: Some(format!("{DISTILL_SOURCES_PREFIX}{}", sources.join(", "))),
+            source: self
+                .relay
+                .as_ref()
+                .map(|r| r.signer.public_key().to_hex())
+                .unwrap_or_else(|| "self".to_string()),
+            model: String::new(),
+            confidence: 0.6,
+            supersedes: None,
+            version: 1,
+            tags: vec![DISTILLED_TAG.to_string()],
+            created_at: now,
+        };
+        rehydrate_conflicting(&idx, &memory.topic);
+        idx.upsert(&memory, None)
+            .map_err(|e| anyhow::anyhow!("distilled store failed: {e}"))?;
+        drop(idx);
+
         self.publish_to_relay(&memory);
+        Ok(true)
+    }
+
+    /// Rowid of the last `social.db` message the distillation job has seen.
+    pub fn distill_cursor(&self) -> i64 {
+        self.index
+            .lock()
+            .query_raw("SELECT value FROM collective_metadata WHERE key = 'distill_cursor'")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(0)
+    }
+
+    pub fn set_distill_cursor(&self, rowid: i64) -> anyhow::Result<()> {
+        self.index
+            .lock()
+            .execute_raw(&format!(
+                "INSERT INTO collective_metadata (key, value) VALUES ('distill_cursor', '{rowid}') \
+                 ON CONFLICT(key) DO UPDATE SET value = '{rowid}'"
+            ))
+            .map_err(|e| anyhow::anyhow!("failed to set distill cursor: {e}"))?;
         Ok(())
     }
 
@@ -370,69 +532,137 @@ impl CollectiveMemory {
     /// (self-to-self) before publishing, and an `["encrypted", "nip44"]` tag
     /// is added so sync can detect and decrypt it.
     ///
-    /// Fire-and-forget: errors are logged, not propagated.
+    /// The event goes to the relays routed for the memory's tier (see
+    /// [`relay_route`]); the relays that accept it are recorded for
+    /// [`published_relays`].
+    ///
+    /// Fire-and-forget: errors are logged, not propagated. When
+    /// `[memory.collective.quorum]` covers the memory's tier, the local copy
+    /// is tagged [`PROVISIONAL_TAG`] and the event is re-sent to relays that
+    /// did not acknowledge it until enough have.
     fn publish_to_relay(&self, memory: &SnowMemory) {
         let relay = match &self.relay {
             Some(r) => r,
             None => return,
         };
 
-        let mem_event = snow_memory::event::memory_to_event(memory);
-
-        // Encrypt content for Private tier memories
-        let (content, extra_tags) = match &memory.tier {
-            MemoryTier::Private(_) => {
-                match nip44::encrypt(
-                    relay.keys.secret_key(),
-                    &relay.keys.public_key(),
-                    mem_event.content.as_bytes(),
-                    nip44::Version::V2,
-                ) {
+        let quorum = quorum_acks(&self.config, &memory.tier);
+        if quorum.is_some() {
+            if let Err(e) = set_provisional(&self.index.lock(), &memory.id, true) {
+                tracing::warn!(
+                    "collective memory: failed to mark '{}' provisional: {e}",
+                    memory.topic
+                );
+            }
+        }
+
+        let mut published = memory.clone();
+        published.tags.retain(|t| t != PROVISIONAL_TAG);
+        let mem_event = snow_memory::event::memory_to_event(&published);
+
+        let mut tags: Vec<nostr_sdk::Tag> = mem_event
+            .tags
+            .iter()
+            .map(|(k, v)| {
+                nostr_sdk::Tag::custom(nostr_sdk::TagKind::custom(k.clone()), vec![v.clone()])
+            })
+            .collect();
+        let private = matches!(memory.tier, MemoryTier::Private(_));
+
+        let client = relay.client.clone();
+        let signer = relay.signer.clone();
+        let route = relay_route(&self.config, &memory.tier);
+        let quorum = quorum.map(|required| (required, self.config.quorum.clone()));
+        let index = Arc::clone(&self.index);
+        let (id, topic) = (memory.id.clone(), memory.topic.clone());
+        tokio::spawn(async move {
+            // Encrypt content for Private tier memories
+            let mut content = mem_event.content;
+            if private {
+                match signer.nip44_encrypt(&signer.public_key(), &content).await {
                     Ok(ciphertext) => {
                         tracing::debug!(
-                            "collective memory: encrypted Private tier event for '{}'",
-                            memory.topic
+                            "collective memory: encrypted Private tier event for '{topic}'"
                         );
-                        let tag = nostr_sdk::Tag::custom(
+                        content = ciphertext;
+                        tags.push(nostr_sdk::Tag::custom(
                             nostr_sdk::TagKind::custom("encrypted"),
                             vec!["nip44".to_string()],
-                        );
-                        (ciphertext, vec![tag])
+                        ));
                     }
                     Err(e) => {
                         tracing::warn!(
-                            "collective memory: NIP-44 encrypt failed for '{}', publishing plaintext: {e}",
-                            memory.topic
+                            "collective memory: NIP-44 encrypt failed for '{topic}', publishing plaintext: {e}"
                         );
-                        (mem_event.content.clone(), vec![])
                     }
                 }
             }
-            _ => (mem_event.content.clone(), vec![]),
-        };
-
-        // Build tags from mem_event + any encryption tag
-        let mut tags: Vec<nostr_sdk::Tag> = mem_event
-            .tags
-            .iter()
-            .map(|(k, v)| {
-                nostr_sdk::Tag::custom(nostr_sdk::TagKind::custom(k.clone()), vec![v.clone()])
-            })
-            .collect();
-        tags.extend(extra_tags);
+            let builder =
+                nostr_sdk::EventBuilder::new(nostr_sdk::Kind::Custom(30078), content).tags(tags);
 
-        let builder =
-            nostr_sdk::EventBuilder::new(nostr_sdk::Kind::Custom(30078), &content).tags(tags);
-
-        let client = relay.client.clone();
+            let event = match client.sign_event_builder(builder).await {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::warn!("collective memory: signing '{topic}' failed: {e}");
+                    return;
+                }
+            };
+
+            let (required, retries, retry_delay) = match &quorum {
+                Some((required, q)) => (*required, q.retries, q.retry_delay_secs),
+                None => (1, 0, 0),
+            };
+            let mut acked = std::collections::HashSet::new();
+            for attempt in 0..=retries {
+                if attempt > 0 {
+                    tokio::time::sleep(Duration::from_secs(retry_delay)).await;
+                }
+                let primary = unacknowledged(&route.primary, &acked);
+                send_to(&client, &event, primary, &mut acked, &topic).await;
+                if acked.len() < required && !route.fallback.is_empty() {
+                    let fallback = unacknowledged(&route.fallback, &acked);
+                    send_to(&client, &event, fallback, &mut acked, &topic).await;
+                }
+                if acked.len() >= required {
+                    break;
+                }
+            }
 
-        tokio::spawn(async move {
-            match client.send_event_builder(builder).await {
-                Ok(_output) => {
-                    tracing::debug!("collective memory: published to relay");
+            let mut relays: Vec<String> = acked.iter().map(ToString::to_string).collect();
+            relays.sort();
+            if !relays.is_empty() {
+                if let Err(e) = set_published_relays(&index.lock(), &id, &relays) {
+                    tracing::warn!("collective memory: failed to record relays of '{topic}': {e}");
                 }
-                Err(e) => {
-                    tracing::warn!("collective memory: relay publish failed: {e}");
+            }
+            match quorum {
+                None if relays.is_empty() => {
+                    tracing::warn!("collective memory: no relay accepted '{topic}'");
+                }
+                None => {
+                    tracing::debug!(
+                        "collective memory: published '{topic}' to {}",
+                        relays.join(", ")
+                    );
+                }
+                Some(_) if acked.len() >= required => {
+                    if let Err(e) = set_provisional(&index.lock(), &id, false) {
+                        tracing::warn!(
+                            "collective memory: failed to clear provisional on '{topic}': {e}"
+                        );
+                    }
+                    tracing::debug!(
+                        "collective memory: '{topic}' acknowledged by {}",
+                        relays.join(", ")
+                    );
+                }
+                Some(_) => {
+                    let missing = unacknowledged(&route.all(), &acked);
+                    tracing::warn!(
+                        "collective memory: '{topic}' acknowledged by {}/{required} relay(s), kept provisional (missing: {})",
+                        acked.len(),
+                        missing.join(", ")
+                    );
                 }
             }
         });
@@ -448,7 +678,7 @@ impl CollectiveMemory {
             None => anyhow::bail!("relay sync not configured (no keys or relay URLs)"),
         };
 
-        let pubkey = relay.keys.public_key();
+        let pubkey = relay.signer.public_key();
         let last_sync = {
             let idx = self.index.lock();
             get_last_sync_timestamp(&idx)
@@ -478,7 +708,11 @@ impl CollectiveMemory {
 
             // Decrypt NIP-44 encrypted content if tagged
             if is_nip44_encrypted(event) {
-                match nip44::decrypt(relay.keys.secret_key(), &event.pubkey, &mem_event.content) {
+                match relay
+                    .signer
+                    .nip44_decrypt(&event.pubkey, &mem_event.content)
+                    .await
+                {
                     Ok(plaintext) => {
                         mem_event.content = plaintext;
                     }
@@ -503,6 +737,7 @@ impl CollectiveMemory {
                     .ok();
 
                     let idx = self.index.lock();
+                    rehydrate_conflicting(&idx, &memory.topic);
                     if let Err(e) = idx.upsert(&memory, event_json.as_deref()) {
                         tracing::warn!("collective memory: failed to upsert synced event: {e}");
                         continue;
@@ -519,6 +754,8 @@ impl CollectiveMemory {
             }
         }
 
+        remember_dynamic_config(&self.index.lock(), &self.config, &events);
+
         // Update last sync timestamp
         if synced > 0 {
             let idx = self.index.lock();
@@ -528,6 +765,25 @@ impl CollectiveMemory {
         }
 
         tracing::info!("collective memory: synced {synced}/{total} events from relay");
+        if let Err(e) = self.archive_untouched(now_unix()) {
+            tracing::warn!("collective memory: archiving failed: {e}");
+        }
+
+        let now = now_unix();
+        let conflicts = scan_after_sync(
+            &self.index.lock(),
+            &self.config.conflicts,
+            &self.config.reputation,
+            now,
+        );
+        announce_conflicts(
+            &relay.client,
+            &relay.signer,
+            &self.config.conflicts,
+            &conflicts,
+            now,
+        )
+        .await;
         Ok(synced)
     }
 
@@ -589,6 +845,145 @@ impl CollectiveMemory {
         Ok(())
     }
 
+    /// Shared (public and group) memories due for review, oldest first.
+    ///
+    /// A memory is due once it is older than `stale_after_secs * 2^passes`,
+    /// where `passes` counts the reviews in a row that confirmed it.
+    pub fn due_for_review(
+        &self,
+        stale_after_secs: u64,
+        limit: usize,
+        now: u64,
+    ) -> anyhow::Result<Vec<SnowMemory>> {
+        let idx = self.index.lock();
+        let mut due: Vec<SnowMemory> = idx
+            .list_all(None, REVIEW_SCAN_LIMIT)
+            .map_err(|e| anyhow::anyhow!("review scan failed: {e}"))?
+            .into_iter()
+            .filter(|m| tier_rank(&m.tier) > 0 && !is_superseded(m))
+            .filter(|m| {
+                let passes = get_review_passes(&idx, &m.topic).min(MAX_REVIEW_DOUBLINGS);
+                now.saturating_sub(m.created_at) >= stale_after_secs.saturating_mul(1 << passes)
+            })
+            .collect();
+        due.sort_by_key(|m| m.created_at);
+        due.truncate(limit);
+        Ok(due)
+    }
+
+    /// Apply a review verdict: store a new version of the memory, publish it
+    /// to relay and update its review schedule.
+    pub fn apply_review(
+        &self,
+        memory: &SnowMemory,
+        verdict: ReviewVerdict,
+        reason: &str,
+        now: u64,
+    ) -> anyhow::Result<SnowMemory> {
+        let mut updated = SnowMemory {
+            id: Uuid::new_v4().to_string(),
+            supersedes: Some(memory.id.clone()),
+            version: memory.version + 1,
+            created_at: now,
+            ..memory.clone()
+        };
+        if verdict != ReviewVerdict::Refresh && !reason.is_empty() {
+            updated.context = Some(format!("review: {reason}"));
+        }
+
+        let idx = self.index.lock();
+        let passes = match verdict {
+            ReviewVerdict::Refresh => get_review_passes(&idx, &memory.topic) + 1,
+            ReviewVerdict::Demote => {
+                updated.tier = demoted_tier(&memory.tier);
+                0
+            }
+            ReviewVerdict::Supersede => {
+                updated.tags.push(SUPERSEDED_TAG.to_string());
+                updated.confidence = 0.0;
+                0
+            }
+        };
+        // The index upsert keeps tier and timestamp on conflict, so replace the row.
+        idx.delete_by_topic(&memory.topic)
+            .and_then(|_| idx.upsert(&updated, None))
+            .map_err(|e| anyhow::anyhow!("review upsert failed: {e}"))?;
+        set_review_passes(&idx, &memory.topic, passes)?;
+        drop(idx);
+
+        self.publish_to_relay(&updated);
+        Ok(updated)
+    }
+
+    /// In the owner's session, a stored memory that disagrees with other
+    /// sources' latest memories on its topic counts as a correction of each.
+    fn record_owner_corrections(&self, idx: &SqliteMemoryIndex, memory: &SnowMemory) {
+        let reputation = &self.config.reputation;
+        if !reputation.enabled || !current_context().is_some_and(|ctx| ctx.is_main_session) {
+            return;
+        }
+        let corrected = match live_latest(idx, &memory.topic) {
+            Ok(latest) => latest,
+            Err(e) => {
+                tracing::warn!("collective memory: correction lookup failed: {e}");
+                return;
+            }
+        };
+        for other in corrected
+            .iter()
+            .filter(|m| m.source != memory.source && m.summary != memory.summary)
+        {
+            if let Err(e) = adjust_reputation(
+                idx,
+                reputation,
+                &other.source,
+                ReputationReason::OwnerCorrection,
+                &memory.topic,
+                memory.created_at,
+            ) {
+                tracing::warn!("collective memory: reputation update failed: {e}");
+            }
+        }
+    }
+
+    /// Conversation context that group isolation applies to, if any.
+    fn isolation_context(&self) -> Option<RecallContext> {
+        if !self.config.groups.isolate {
+            return None;
+        }
+        current_context()
+    }
+
+    /// Tier for a memory stored in the current conversation. Inside an
+    /// isolated group, anything visible outside the group is scoped to it.
+    fn scoped_tier(&self, tier: MemoryTier) -> MemoryTier {
+        let group_id = match self
+            .isolation_context()
+            .and_then(|ctx| ctx.group_id)
+            .filter(|id| !self.config.groups.is_shared(id))
+        {
+            Some(id) => id,
+            None => return tier,
+        };
+        match tier {
+            MemoryTier::Private(_) => tier,
+            MemoryTier::Group(id) if id == group_id => MemoryTier::Group(id),
+            MemoryTier::Public | MemoryTier::Group(_) => MemoryTier::Group(group_id),
+        }
+    }
+
+    /// Whether a memory with `tier` may be shown in the current conversation.
+    /// Private memories need the owner's session even without isolation.
+    fn visible_here(&self, tier: &MemoryTier) -> bool {
+        let Some(ctx) = current_context() else {
+            return true;
+        };
+        if matches!(tier, MemoryTier::Private(_)) && !ctx.is_main_session {
+            return false;
+        }
+        !self.config.groups.isolate || tier_visible_in_context(tier, &ctx, &self.config.groups)
+    }
+
     /// Recall memories with tier-based context filtering.
     ///
     /// When `context` is provided, results are filtered by privacy tier:
@@ -596,6 +991,9 @@ impl CollectiveMemory {
     /// - Group chat: Public + matching Group only
     /// - Other: Public only
     ///
+    /// Groups listed in `[memory.collective.groups] shared` are visible in
+    /// every context.
+    ///
     /// When `context` is `None`, no filtering is applied.
     pub async fn recall_with_context(
         &self,
@@ -604,8 +1002,8 @@ impl CollectiveMemory {
         _session_id: Option<&str>,
         context: Option<&RecallContext>,
     ) -> anyhow::Result<Vec<MemoryEntry>> {
-        let sm_config = self.config.to_snow_memory_config();
         let idx = self.index.lock();
+        let sm_config = ranking_config(&idx, &self.config);
 
         // Fetch extra results when filtering so we still return up to `limit` after filtering.
         let fetch_limit = if context.is_some() { limit * 3 } else { limit };
@@ -615,15 +1013,17 @@ impl CollectiveMemory {
 
         let entries: Vec<MemoryEntry> = results
             .iter()
+            .filter(|r| !is_superseded(&r.memory))
             .filter(|r| {
                 match context {
-                    Some(ctx) => tier_visible_in_context(&r.memory.tier, ctx),
+                    Some(ctx) => tier_visible_in_context(&r.memory.tier, ctx, &self.config.groups),
                     None => true, // no context = no filtering
                 }
             })
             .take(limit)
             .map(|r| snow_to_entry(&r.memory, Some(r.effective_score)))
             .collect();
+        touch_entries(&idx, &entries);
 
         Ok(entries)
     }
@@ -638,6 +1038,42 @@ impl CollectiveMemory {
         self.sync_from_relay().await
     }
 
+    /// Move memories untouched for `archive.after_months` into the archive.
+    /// Returns the number archived; a no-op unless the archive is enabled.
+    pub fn archive_untouched(&self, now: u64) -> anyhow::Result<usize> {
+        let archive = &self.config.archive;
+        if !archive.enabled || archive.after_months == 0 {
+            return Ok(0);
+        }
+        let cutoff = now.saturating_sub(u64::from(archive.after_months) * SECS_PER_MONTH);
+        let archived = self
+            .index
+            .lock()
+            .archive_untouched(cutoff, now)
+            .map_err(|e| anyhow::anyhow!("collective archive failed: {e}"))?;
+        if archived > 0 {
+            tracing::info!("collective memory: archived {archived} untouched memories");
+        }
+        Ok(archived)
+    }
+
+    /// Search the archive and move matching memories back into the index.
+    pub fn rehydrate_matching(&self, query: &str, limit: usize) -> anyhow::Result<Vec<SnowMemory>> {
+        let idx = self.index.lock();
+        let now = now_unix();
+        let mut restored = Vec::new();
+        for memory in idx
+            .search_archive(query, limit)
+            .map_err(|e| anyhow::anyhow!("archive search failed: {e}"))?
+        {
+            restored.extend(
+                idx.rehydrate(&memory.id, now)
+                    .map_err(|e| anyhow::anyhow!("rehydrate failed: {e}"))?,
+            );
+        }
+        Ok(restored)
+    }
+
     /// Detect conflicting memories for a given topic.
     ///
     /// Searches the index for memories matching `topic`, then identifies
@@ -675,17 +1111,7 @@ impl CollectiveMemory {
 
             // Only flag as conflict if multiple distinct sources exist
             if sources.len() > 1 {
-                let entries = mems
-                    .iter()
-                    .map(|m| ConflictEntry {
-                        memory_id: m.id.clone(),
-                        source: m.source.clone(),
-                        summary: m.summary.clone(),
-                        confidence: m.confidence,
-                        created_at: m.created_at,
-                        version: m.version,
-                    })
-                    .collect();
+                let entries = mems.iter().map(conflict_entry).collect();
 
                 conflicts.push(MemoryConflict {
                     topic: topic_key,
@@ -696,14 +1122,72 @@ impl CollectiveMemory {
 
         Ok(conflicts)
     }
+
+    /// Detect conflicts on topics with a memory created in `[since, until]`.
+    ///
+    /// Unlike [`Self::detect_conflicts`], only the latest memory from each
+    /// source counts, and sources whose latest memory is superseded drop out.
+    pub fn detect_conflicts_between(
+        &self,
+        since: u64,
+        until: u64,
+    ) -> anyhow::Result<Vec<MemoryConflict>> {
+        let idx = self.index.lock();
+        let topics = idx
+            .topics_created_between(since, until)
+            .map_err(|e| anyhow::anyhow!("conflict window scan failed: {e}"))?;
+        let mut conflicts = Vec::new();
+        for topic in topics {
+            conflicts.extend(topic_conflict(&idx, &topic)?);
+        }
+        Ok(conflicts)
+    }
+
+    /// Scan the last `conflicts.window_hours` and record conflicts in the
+    /// collective DB. Returns the ones not recorded before; a no-op unless
+    /// the scanner is enabled.
+    pub fn scan_conflicts(&self, now: u64) -> anyhow::Result<Vec<MemoryConflict>> {
+        if !self.config.conflicts.enabled {
+            return Ok(Vec::new());
+        }
+        record_conflicts(
+            &self.index.lock(),
+            self.config.conflicts.window_hours,
+            &self.config.reputation,
+            now,
+        )
+    }
+
+    /// Conflicts recorded by the scanner that are not resolved yet.
+    pub fn unresolved_conflicts(&self) -> anyhow::Result<Vec<MemoryConflict>> {
+        let idx = self.index.lock();
+        let records = idx
+            .unresolved_conflicts()
+            .map_err(|e| anyhow::anyhow!("conflict lookup failed: {e}"))?;
+        let mut conflicts = Vec::new();
+        for (topic, ids, _detected_at) in records {
+            let mut entries = Vec::new();
+            for id in &ids {
+                if let Some(m) = idx
+                    .get(id)
+                    .map_err(|e| anyhow::anyhow!("conflict lookup failed: {e}"))?
+                {
+                    entries.push(conflict_entry(&m));
+                }
+            }
+            conflicts.push(MemoryConflict { topic, entries });
+        }
+        Ok(conflicts)
+    }
 }
 
 /// Background sync: fetch events from relay and upsert into local DB.
 /// Uses a separate DB connection since this runs on a spawned task.
 async fn background_sync(
     client: &nostr_sdk::Client,
-    keys: &nostr_sdk::Keys,
+    signer: &Signer,
     db_path: &Path,
+    config: &CollectiveMemoryConfig,
 ) -> anyhow::Result<()> {
     // Open a separate connection for the background sync
     let index = if db_path.to_str() == Some(":memory:") {
@@ -716,7 +1200,7 @@ async fn background_sync(
     init_metadata_table(&index)?;
 
     let last_sync = get_last_sync_timestamp(&index);
-    let pubkey = keys.public_key();
+    let pubkey = signer.public_key();
 
     let mut filter = nostr_sdk::Filter::new()
         .author(pubkey)
@@ -741,7 +1225,10 @@ async fn background_sync(
 
         // Decrypt NIP-44 encrypted content if tagged
         if is_nip44_encrypted(event) {
-            match nip44::decrypt(keys.secret_key(), &event.pubkey, &mem_event.content) {
+            match signer
+                .nip44_decrypt(&event.pubkey, &mem_event.content)
+                .await
+            {
                 Ok(plaintext) => {
                     mem_event.content = plaintext;
                 }
@@ -757,6 +1244,7 @@ async fn background_sync(
 
         match snow_memory::event::memory_from_event(&mem_event) {
             Ok(memory) => {
+                rehydrate_conflicting(&index, &memory.topic);
                 if let Err(e) = index.upsert(&memory, None) {
                     tracing::warn!("background sync: failed to upsert event: {e}");
                     continue;
@@ -773,6 +1261,8 @@ async fn background_sync(
         }
     }
 
+    remember_dynamic_config(&index, config, &events);
+
     if synced > 0 {
         if let Err(e) = set_last_sync_timestamp(&index, max_ts) {
             tracing::warn!("background sync: failed to update sync timestamp: {e}");
@@ -780,92 +1270,561 @@ async fn background_sync(
     }
 
     tracing::info!("collective memory: synced {synced}/{total} events from relay");
+
+    let now = now_unix();
+    let found = scan_after_sync(&index, &config.conflicts, &config.reputation, now);
+    announce_conflicts(client, signer, &config.conflicts, &found, now).await;
     Ok(())
 }
 
-/// Check if a nostr event has the `["encrypted", "nip44"]` tag.
-fn is_nip44_encrypted(event: &nostr_sdk::Event) -> bool {
-    event.tags.iter().any(|t| {
-        let s = t.as_slice();
-        s.first().map(|v| v.as_str()) == Some("encrypted")
-            && s.get(1).map(|v| v.as_str()) == Some("nip44")
-    })
+// ── Conflict scanner ────────────────────────────────────────────
+
+/// The conflict on `topic` between the latest memories of each source, if
+/// more than one source still holds a live (not superseded) memory.
+fn topic_conflict(
+    index: &SqliteMemoryIndex,
+    topic: &str,
+) -> anyhow::Result<Option<MemoryConflict>> {
+    let latest = live_latest(index, topic)?;
+    Ok((latest.len() > 1).then(|| MemoryConflict {
+        topic: topic.to_string(),
+        entries: latest.iter().map(conflict_entry).collect(),
+    }))
 }
 
-// ── Conversion helpers ──────────────────────────────────────────
-
-/// Convert a `snow_memory::Memory` to a `nostr_sdk::EventBuilder` (kind 30078).
-fn memory_to_event_builder(memory: &SnowMemory) -> nostr_sdk::EventBuilder {
-    let mem_event = snow_memory::event::memory_to_event(memory);
-
-    let tags: Vec<nostr_sdk::Tag> = mem_event
-        .tags
-        .iter()
-        .map(|(k, v)| {
-            nostr_sdk::Tag::custom(nostr_sdk::TagKind::custom(k.clone()), vec![v.clone()])
-        })
-        .collect();
-
-    nostr_sdk::EventBuilder::new(nostr_sdk::Kind::Custom(30078), &mem_event.content).tags(tags)
+/// The latest memory of each source on `topic`, newest first, leaving out
+/// sources whose latest memory is superseded.
+fn live_latest(index: &SqliteMemoryIndex, topic: &str) -> anyhow::Result<Vec<SnowMemory>> {
+    let mut latest: Vec<SnowMemory> = Vec::new();
+    for m in index
+        .list_by_topic(topic)
+        .map_err(|e| anyhow::anyhow!("conflict topic lookup failed: {e}"))?
+    {
+        if !latest.iter().any(|l| l.source == m.source) {
+            latest.push(m);
+        }
+    }
+    latest.retain(|m| !is_superseded(m));
+    Ok(latest)
 }
 
-/// Convert a `nostr_sdk::Event` to a `snow_memory::event::MemoryEvent`.
-fn nostr_event_to_memory_event(event: &nostr_sdk::Event) -> snow_memory::event::MemoryEvent {
-    let tags = event
-        .tags
-        .iter()
-        .filter_map(|t| {
-            let s = t.as_slice();
-            if s.len() >= 2 {
-                Some((s[0].to_string(), s[1].to_string()))
-            } else {
-                None
+/// Check every topic created in the window: record its conflict, or resolve
+/// earlier records once the sources agree again. Returns new conflicts.
+fn record_conflicts(
+    index: &SqliteMemoryIndex,
+    window_hours: u64,
+    reputation: &CollectiveReputationConfig,
+    now: u64,
+) -> anyhow::Result<Vec<MemoryConflict>> {
+    let since = now.saturating_sub(window_hours.saturating_mul(3600));
+    let topics = index
+        .topics_created_between(since, now)
+        .map_err(|e| anyhow::anyhow!("conflict window scan failed: {e}"))?;
+
+    let mut found = Vec::new();
+    for topic in topics {
+        match topic_conflict(index, &topic)? {
+            Some(conflict) => {
+                let ids: Vec<&str> = conflict
+                    .entries
+                    .iter()
+                    .map(|e| e.memory_id.as_str())
+                    .collect();
+                if index
+                    .record_conflict(&topic, &ids, now)
+                    .map_err(|e| anyhow::anyhow!("failed to record conflict: {e}"))?
+                {
+                    found.push(conflict);
+                }
             }
-        })
-        .collect();
-
-    snow_memory::event::MemoryEvent {
-        id: event.id.to_hex(),
-        kind: event.kind.as_u16() as u64,
-        pubkey: event.pubkey.to_hex(),
-        created_at: event.created_at.as_secs(),
-        tags,
-        content: event.content.clone(),
+            None => {
+                if reputation.enabled {
+                    record_conflict_outcome(index, reputation, &topic, now)?;
+                }
+                index
+                    .resolve_conflicts(&topic, now)
+                    .map_err(|e| anyhow::anyhow!("failed to resolve conflict: {e}"))?;
+            }
+        }
     }
+    Ok(found)
 }
 
-// ── Metadata table for sync tracking ────────────────────────────
-
-fn init_metadata_table(index: &SqliteMemoryIndex) -> anyhow::Result<()> {
-    index
-        .execute_raw(
-            "CREATE TABLE IF NOT EXISTS collective_metadata (
-                key TEXT PRIMARY KEY,
-                value TEXT NOT NULL
-            )",
-        )
-        .map_err(|e| anyhow::anyhow!("failed to create metadata table: {e}"))?;
-    Ok(())
+/// Post-sync scan; errors are logged and yield no conflicts.
+fn scan_after_sync(
+    index: &SqliteMemoryIndex,
+    config: &CollectiveConflictsConfig,
+    reputation: &CollectiveReputationConfig,
+    now: u64,
+) -> Vec<MemoryConflict> {
+    if !config.enabled {
+        return Vec::new();
+    }
+    match record_conflicts(index, config.window_hours, reputation, now) {
+        Ok(found) => {
+            if !found.is_empty() {
+                tracing::info!("collective memory: found {} new conflict(s)", found.len());
+            }
+            found
+        }
+        Err(e) => {
+            tracing::warn!("collective memory: conflict scan failed: {e}");
+            Vec::new()
+        }
+    }
 }
 
-fn get_last_sync_timestamp(index: &SqliteMemoryIndex) -> Option<u64> {
-    index
-        .query_raw("SELECT value FROM collective_metadata WHERE key = 'last_sync_timestamp'")
-        .ok()
-        .and_then(|v| v.parse::<u64>().ok())
+// ── Layered ranking config ──────────────────────────────────────
+
+/// Fields env and dynamic config cannot change: relay connections are
+/// opened from the file config at startup.
+const FILE_ONLY_FIELDS: &[&str] = &["relays_public", "relays_group", "relays_private"];
+
+/// Metadata key of the stored dynamic memory config.
+const DYNAMIC_CONFIG_KEY: &str = "dynamic_config";
+
+/// The snow-memory config from `[memory.collective]`, `SNOW_MEMORY_*` env
+/// vars and the `dynamic` NIP-78 config content, with the source of each
+/// field.
+pub fn effective_memory_config(
+    config: &CollectiveMemoryConfig,
+    dynamic: Option<&str>,
+) -> Result<Layered<MemoryConfig>, ConfigError> {
+    let mut loader = ConfigLoader::new()
+        .fixed(FILE_ONLY_FIELDS)
+        .file_config(&config.to_snow_memory_config())?
+        .env(ENV_PREFIX, std::env::vars())?;
+    if let Some(content) = dynamic {
+        loader = loader.dynamic(content)?;
+    }
+    loader.load()
 }
 
-fn set_last_sync_timestamp(index: &SqliteMemoryIndex, ts: u64) -> anyhow::Result<()> {
+/// Dynamic memory config content kept by relay sync.
+pub fn dynamic_config(index: &SqliteMemoryIndex) -> Option<String> {
     index
-        .execute_raw(&format!(
-            "INSERT INTO collective_metadata (key, value) VALUES ('last_sync_timestamp', '{ts}') \
-             ON CONFLICT(key) DO UPDATE SET value = '{ts}'"
+        .query_raw(&format!(
+            "SELECT value FROM collective_metadata WHERE key = '{DYNAMIC_CONFIG_KEY}'"
         ))
-        .map_err(|e| anyhow::anyhow!("failed to set sync timestamp: {e}"))?;
-    Ok(())
+        .ok()
 }
 
-// ── Category/tier/entry conversions ─────────────────────────────
+/// Keep the newest dynamic memory config among synced `events`, unless it
+/// fails to load on top of `config`.
+fn remember_dynamic_config(
+    index: &SqliteMemoryIndex,
+    config: &CollectiveMemoryConfig,
+    events: &[nostr_sdk::Event],
+) {
+    let Some(event) = events
+        .iter()
+        .filter(|e| e.tags.identifier() == Some(DYNAMIC_CONFIG_D_TAG))
+        .max_by_key(|e| e.created_at)
+    else {
+        return;
+    };
+    if let Err(e) = effective_memory_config(config, Some(&event.content)) {
+        tracing::warn!("collective memory: ignoring dynamic config: {e}");
+        return;
+    }
+    let value = event.content.replace('\'', "''");
+    if let Err(e) = index.execute_raw(&format!(
+        "INSERT INTO collective_metadata (key, value) VALUES ('{DYNAMIC_CONFIG_KEY}', '{value}') \
+         ON CONFLICT(key) DO UPDATE SET value = '{value}'"
+    )) {
+        tracing::warn!("collective memory: failed to store dynamic config: {e}");
+    }
+}
+
+// ── Source reputation ───────────────────────────────────────────
+
+/// Ranking config for the collective index: the layered config
+/// ([`effective_memory_config`]), with `source_preferences` trust scaled by
+/// each source's learned reputation when reputation is enabled.
+pub fn ranking_config(index: &SqliteMemoryIndex, config: &CollectiveMemoryConfig) -> MemoryConfig {
+    let mut sm_config = match effective_memory_config(config, dynamic_config(index).as_deref()) {
+        Ok(layered) => layered.config,
+        Err(e) => {
+            tracing::warn!("collective memory: invalid layered config, using the file: {e}");
+            config.to_snow_memory_config()
+        }
+    };
+    if !config.reputation.enabled {
+        return sm_config;
+    }
+    let reputations = match index.list_reputations() {
+        Ok(reputations) => reputations,
+        Err(e) => {
+            tracing::warn!("collective memory: reputation lookup failed: {e}");
+            return sm_config;
+        }
+    };
+    for pref in &mut sm_config.sources {
+        let learned = pref
+            .npub
+            .as_ref()
+            .and_then(|npub| reputations.iter().find(|r| &r.source == npub));
+        if let Some(reputation) = learned {
+            pref.trust *= reputation.factor;
+        }
+    }
+    sm_config
+}
+
+/// Factor after one more `reason` outcome, kept within `[floor, 1.0]`.
+fn next_reputation_factor(
+    factor: f64,
+    reason: ReputationReason,
+    config: &CollectiveReputationConfig,
+) -> f64 {
+    let next = match reason {
+        ReputationReason::ConflictLost | ReputationReason::OwnerCorrection => factor * config.decay,
+        ReputationReason::ConflictWon => factor + config.recovery,
+    };
+    next.max(config.floor).min(1.0)
+}
+
+/// Apply one `reason` outcome to `source` and record the change.
+fn adjust_reputation(
+    index: &SqliteMemoryIndex,
+    config: &CollectiveReputationConfig,
+    source: &str,
+    reason: ReputationReason,
+    topic: &str,
+    now: u64,
+) -> anyhow::Result<()> {
+    let factor_before = index
+        .get_reputation(source)
+        .map_err(|e| anyhow::anyhow!("reputation lookup failed: {e}"))?
+        .map_or(1.0, |r| r.factor);
+    let change = ReputationChange {
+        source: source.to_string(),
+        reason,
+        topic: topic.to_string(),
+        factor_before,
+        factor_after: next_reputation_factor(factor_before, reason, config),
+        at: now,
+    };
+    index
+        .record_reputation_change(&change)
+        .map_err(|e| anyhow::anyhow!("failed to record reputation change: {e}"))
+}
+
+/// `topic` no longer conflicts: if one source is left holding it, the sides
+/// of its unresolved conflict records won or lost against that source.
+fn record_conflict_outcome(
+    index: &SqliteMemoryIndex,
+    config: &CollectiveReputationConfig,
+    topic: &str,
+    now: u64,
+) -> anyhow::Result<()> {
+    let live = live_latest(index, topic)?;
+    let [winner] = live.as_slice() else {
+        return Ok(());
+    };
+    let mut sides: Vec<String> = Vec::new();
+    let records = index
+        .unresolved_conflicts()
+        .map_err(|e| anyhow::anyhow!("conflict lookup failed: {e}"))?;
+    for (_, ids, _) in records.into_iter().filter(|(t, _, _)| t == topic) {
+        for id in ids {
+            if let Some(m) = index
+                .get(&id)
+                .map_err(|e| anyhow::anyhow!("conflict lookup failed: {e}"))?
+            {
+                if !sides.contains(&m.source) {
+                    sides.push(m.source);
+                }
+            }
+        }
+    }
+    for source in sides {
+        let reason = if source == winner.source {
+            ReputationReason::ConflictWon
+        } else {
+            ReputationReason::ConflictLost
+        };
+        adjust_reputation(index, config, &source, reason, topic, now)?;
+    }
+    Ok(())
+}
+
+/// DM the owner about each new conflict and publish a conflict notice when
+/// configured. Errors are logged, not propagated.
+async fn announce_conflicts(
+    client: &nostr_sdk::Client,
+    signer: &Signer,
+    config: &CollectiveConflictsConfig,
+    conflicts: &[MemoryConflict],
+    now: u64,
+) {
+    if conflicts.is_empty() {
+        return;
+    }
+    let owner = config
+        .owner
+        .as_deref()
+        .and_then(|o| match nostr_sdk::PublicKey::parse(o) {
+            Ok(pk) => Some(pk),
+            Err(e) => {
+                tracing::warn!("collective memory: invalid conflict owner '{o}': {e}");
+                None
+            }
+        });
+
+    for conflict in conflicts {
+        if let Some(owner) = owner {
+            if let Err(e) = client
+                .send_private_msg(
+                    owner,
+                    render_conflict_dm(conflict),
+                    Vec::<nostr_sdk::Tag>::new(),
+                )
+                .await
+            {
+                tracing::warn!(
+                    "collective memory: conflict DM for '{}' failed: {e}",
+                    conflict.topic
+                );
+            }
+        }
+        if config.publish {
+            let notice = conflict_notice(conflict, now);
+            let event = snow_memory::event::conflict_notice_to_event(
+                &notice,
+                &signer.public_key().to_hex(),
+            );
+            if let Err(e) = client.send_event_builder(event_builder(&event)).await {
+                tracing::warn!(
+                    "collective memory: conflict notice for '{}' failed: {e}",
+                    conflict.topic
+                );
+            }
+        }
+    }
+}
+
+/// Owner DM listing every side of a conflict.
+fn render_conflict_dm(conflict: &MemoryConflict) -> String {
+    let mut text = format!("Conflicting memories on `{}`:\n", conflict.topic);
+    for (i, entry) in conflict.entries.iter().enumerate() {
+        let when = chrono::DateTime::from_timestamp(entry.created_at as i64, 0)
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        text.push_str(&format!(
+            "\n{}. {} (v{}, {when}, confidence {:.2})\n   {}\n",
+            i + 1,
+            entry.source,
+            entry.version,
+            entry.confidence,
+            entry.summary
+        ));
+    }
+    text
+}
+
+fn conflict_notice(
+    conflict: &MemoryConflict,
+    detected_at: u64,
+) -> snow_memory::event::ConflictNotice {
+    snow_memory::event::ConflictNotice {
+        topic: conflict.topic.clone(),
+        detected_at,
+        sides: conflict
+            .entries
+            .iter()
+            .map(|e| snow_memory::event::ConflictSide {
+                memory_id: e.memory_id.clone(),
+                source: e.source.clone(),
+                summary: e.summary.clone(),
+                confidence: e.confidence,
+                version: e.version,
+                created_at: e.created_at,
+            })
+            .collect(),
+    }
+}
+
+fn conflict_entry(m: &SnowMemory) -> ConflictEntry {
+    ConflictEntry {
+        memory_id: m.id.clone(),
+        source: m.source.clone(),
+        summary: m.summary.clone(),
+        confidence: m.confidence,
+        created_at: m.created_at,
+        version: m.version,
+    }
+}
+
+/// Check if a nostr event has the `["encrypted", "nip44"]` tag.
+fn is_nip44_encrypted(event: &nostr_sdk::Event) -> bool {
+    event.tags.iter().any(|t| {
+        let s = t.as_slice();
+        s.first().map(|v| v.as_str()) == Some("encrypted")
+            && s.get(1).map(|v| v.as_str()) == Some("nip44")
+    })
+}
+
+// ── Conversion helpers ──────────────────────────────────────────
+
+/// Add `relay_urls` to `client` through the relay proxy. `false` when the
+/// proxy config is invalid or an onion relay has no proxy, leaving relay
+/// sync off.
+async fn add_relays(client: &nostr_sdk::Client, relay_urls: &[String]) -> bool {
+    let relay_proxy = match crate::channels::snowclaw_channels::runtime_relay_proxy() {
+        Ok(proxy) => proxy,
+        Err(e) => {
+            tracing::warn!("collective memory: relay sync disabled: {e:#}");
+            return false;
+        }
+    };
+    if let Err(e) = relay_proxy.check_relays(relay_urls) {
+        tracing::warn!("collective memory: relay sync disabled: {e}");
+        return false;
+    }
+    for url in relay_urls {
+        if let Err(e) = relay_proxy.add_relay(client, url).await {
+            tracing::warn!("collective memory: failed to add relay {url}: {e}");
+        }
+    }
+    true
+}
+
+/// Convert a `snow_memory::Memory` to a `nostr_sdk::EventBuilder` (kind 30078).
+fn memory_to_event_builder(memory: &SnowMemory) -> nostr_sdk::EventBuilder {
+    event_builder(&snow_memory::event::memory_to_event(memory))
+}
+
+/// Convert a `snow_memory::event::MemoryEvent` to a `nostr_sdk::EventBuilder`.
+fn event_builder(mem_event: &snow_memory::event::MemoryEvent) -> nostr_sdk::EventBuilder {
+    let tags: Vec<nostr_sdk::Tag> = mem_event
+        .tags
+        .iter()
+        .map(|(k, v)| {
+            nostr_sdk::Tag::custom(nostr_sdk::TagKind::custom(k.clone()), vec![v.clone()])
+        })
+        .collect();
+
+    nostr_sdk::EventBuilder::new(
+        nostr_sdk::Kind::Custom(mem_event.kind as u16),
+        &mem_event.content,
+    )
+    .tags(tags)
+}
+
+/// Convert a `nostr_sdk::Event` to a `snow_memory::event::MemoryEvent`.
+fn nostr_event_to_memory_event(event: &nostr_sdk::Event) -> snow_memory::event::MemoryEvent {
+    let tags = event
+        .tags
+        .iter()
+        .filter_map(|t| {
+            let s = t.as_slice();
+            if s.len() >= 2 {
+                Some((s[0].to_string(), s[1].to_string()))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    snow_memory::event::MemoryEvent {
+        id: event.id.to_hex(),
+        kind: event.kind.as_u16() as u64,
+        pubkey: event.pubkey.to_hex(),
+        created_at: event.created_at.as_secs(),
+        tags,
+        content: event.content.clone(),
+    }
+}
+
+// ── Metadata table for sync tracking ────────────────────────────
+
+fn init_metadata_table(index: &SqliteMemoryIndex) -> anyhow::Result<()> {
+    index
+        .execute_raw(
+            "CREATE TABLE IF NOT EXISTS collective_metadata (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+        )
+        .map_err(|e| anyhow::anyhow!("failed to create metadata table: {e}"))?;
+    Ok(())
+}
+
+fn get_last_sync_timestamp(index: &SqliteMemoryIndex) -> Option<u64> {
+    index
+        .query_raw("SELECT value FROM collective_metadata WHERE key = 'last_sync_timestamp'")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+}
+
+fn set_last_sync_timestamp(index: &SqliteMemoryIndex, ts: u64) -> anyhow::Result<()> {
+    index
+        .execute_raw(&format!(
+            "INSERT INTO collective_metadata (key, value) VALUES ('last_sync_timestamp', '{ts}') \
+             ON CONFLICT(key) DO UPDATE SET value = '{ts}'"
+        ))
+        .map_err(|e| anyhow::anyhow!("failed to set sync timestamp: {e}"))?;
+    Ok(())
+}
+
+/// Relays that accepted memory `id` when it was published from here, sorted.
+/// Empty for memories this agent did not publish or no relay accepted.
+pub fn published_relays(index: &SqliteMemoryIndex, id: &str) -> Vec<String> {
+    index
+        .query_raw(&format!(
+            "SELECT value FROM collective_metadata WHERE key = 'relays:{}'",
+            id.replace('\'', "''")
+        ))
+        .map(|v| {
+            v.split(',')
+                .filter(|url| !url.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn set_published_relays(
+    index: &SqliteMemoryIndex,
+    id: &str,
+    relays: &[String],
+) -> anyhow::Result<()> {
+    let key = format!("relays:{}", id.replace('\'', "''"));
+    let value = relays.join(",").replace('\'', "''");
+    index
+        .execute_raw(&format!(
+            "INSERT INTO collective_metadata (key, value) VALUES ('{key}', '{value}') \
+             ON CONFLICT(key) DO UPDATE SET value = '{value}'"
+        ))
+        .map_err(|e| anyhow::anyhow!("failed to record published relays: {e}"))?;
+    Ok(())
+}
+
+/// Consecutive confirming reviews of a topic (0 if never reviewed).
+fn get_review_passes(index: &SqliteMemoryIndex, topic: &str) -> u32 {
+    index
+        .query_raw(&format!(
+            "SELECT value FROM collective_metadata WHERE key = 'review_passes:{}'",
+            topic.replace('\'', "''")
+        ))
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(0)
+}
+
+fn set_review_passes(index: &SqliteMemoryIndex, topic: &str, passes: u32) -> anyhow::Result<()> {
+    let key = format!("review_passes:{}", topic.replace('\'', "''"));
+    index
+        .execute_raw(&format!(
+            "INSERT INTO collective_metadata (key, value) VALUES ('{key}', '{passes}') \
+             ON CONFLICT(key) DO UPDATE SET value = '{passes}'"
+        ))
+        .map_err(|e| anyhow::anyhow!("failed to set review schedule: {e}"))?;
+    Ok(())
+}
+
+// ── Category/tier/entry conversions ─────────────────────────────
 
 /// Convert a `MemoryCategory` to a `MemoryTier`.
 fn category_to_tier(category: &MemoryCategory) -> MemoryTier {
@@ -898,12 +1857,18 @@ pub fn scope_to_tier(key: &str) -> MemoryTier {
 }
 
 /// Check whether a memory with the given tier should be visible in the given context.
-fn tier_visible_in_context(tier: &MemoryTier, context: &RecallContext) -> bool {
+fn tier_visible_in_context(
+    tier: &MemoryTier,
+    context: &RecallContext,
+    groups: &CollectiveGroupsConfig,
+) -> bool {
     match tier {
         MemoryTier::Public => true,
         MemoryTier::Private(_) => context.is_main_session,
         MemoryTier::Group(group_id) => {
-            context.is_main_session || context.group_id.as_deref() == Some(group_id.as_str())
+            context.is_main_session
+                || context.group_id.as_deref() == Some(group_id.as_str())
+                || groups.is_shared(group_id)
         }
     }
 }
@@ -917,6 +1882,184 @@ fn tier_rank(tier: &MemoryTier) -> u8 {
     }
 }
 
+/// Rank of `[memory.collective.quorum] min_tier`, on the [`tier_rank`] scale.
+fn quorum_min_rank(config: &CollectiveQuorumConfig) -> anyhow::Result<u8> {
+    match config.min_tier.to_ascii_lowercase().as_str() {
+        "private" => Ok(0),
+        "group" => Ok(1),
+        "public" => Ok(2),
+        other => anyhow::bail!(
+            "invalid memory.collective.quorum.min_tier `{other}` (expected private, group or public)"
+        ),
+    }
+}
+
+/// Relay acknowledgments a memory of `tier` must collect, or `None` when it
+/// is published fire-and-forget. Capped at the number of relays the tier
+/// can use.
+fn quorum_acks(config: &CollectiveMemoryConfig, tier: &MemoryTier) -> Option<usize> {
+    let quorum = &config.quorum;
+    if !quorum.enabled {
+        return None;
+    }
+    let min_rank = quorum_min_rank(quorum).ok()?;
+    let relays = relay_route(config, tier).all().len();
+    (relays > 0 && tier_rank(tier) >= min_rank).then(|| quorum.min_acks.clamp(1, relays))
+}
+
+/// Relays a memory is published to.
+struct RelayRoute {
+    /// The tier's routed relays, or `relay_urls` when it has no route.
+    primary: Vec<String>,
+    /// Remaining `relay_urls`, tried when the primary relays fall short.
+    fallback: Vec<String>,
+}
+
+impl RelayRoute {
+    fn all(&self) -> Vec<String> {
+        self.primary.iter().chain(&self.fallback).cloned().collect()
+    }
+}
+
+/// Route for memories of `tier` under `[memory.collective.relay_routes]`.
+fn relay_route(config: &CollectiveMemoryConfig, tier: &MemoryTier) -> RelayRoute {
+    let primary = config.to_snow_memory_config().relays_for(tier).to_vec();
+    let fallback = if config.relay_routes.fallback {
+        config
+            .relay_urls
+            .iter()
+            .filter(|url| !primary.contains(url))
+            .cloned()
+            .collect()
+    } else {
+        Vec::new()
+    };
+    RelayRoute { primary, fallback }
+}
+
+/// Fail on relay URLs in `relay_urls` or `relay_routes` that do not parse.
+fn validate_relay_urls(config: &CollectiveMemoryConfig) -> anyhow::Result<()> {
+    let routes = &config.relay_routes;
+    for (field, urls) in [
+        ("relay_urls", &config.relay_urls),
+        ("relay_routes.private", &routes.private),
+        ("relay_routes.group", &routes.group),
+        ("relay_routes.public", &routes.public),
+    ] {
+        for url in urls {
+            nostr_sdk::RelayUrl::parse(url).map_err(|e| {
+                anyhow::anyhow!("invalid relay URL `{url}` in memory.collective.{field}: {e}")
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Send `event` to `urls`, adding the relays that accepted it to `acked`.
+async fn send_to(
+    client: &nostr_sdk::Client,
+    event: &nostr_sdk::Event,
+    urls: Vec<String>,
+    acked: &mut std::collections::HashSet<nostr_sdk::RelayUrl>,
+    topic: &str,
+) {
+    if urls.is_empty() {
+        return;
+    }
+    match client.send_event_to(urls, event).await {
+        Ok(output) => acked.extend(output.success),
+        Err(e) => tracing::debug!("collective memory: publishing '{topic}' failed: {e}"),
+    }
+}
+
+/// Configured relays whose URL is not among the acknowledging ones.
+fn unacknowledged(
+    relay_urls: &[String],
+    acked: &std::collections::HashSet<nostr_sdk::RelayUrl>,
+) -> Vec<String> {
+    relay_urls
+        .iter()
+        .filter(|url| !nostr_sdk::RelayUrl::parse(url.as_str()).is_ok_and(|u| acked.contains(&u)))
+        .cloned()
+        .collect()
+}
+
+/// Add or remove [`PROVISIONAL_TAG`] on the stored memory `id`, if it is
+/// still stored.
+fn set_provisional(idx: &SqliteMemoryIndex, id: &str, provisional: bool) -> anyhow::Result<()> {
+    let Some(mut memory) = idx
+        .get(id)
+        .map_err(|e| anyhow::anyhow!("collective lookup failed: {e}"))?
+    else {
+        return Ok(());
+    };
+    let tagged = memory.tags.iter().any(|t| t == PROVISIONAL_TAG);
+    if tagged == provisional {
+        return Ok(());
+    }
+    if provisional {
+        memory.tags.push(PROVISIONAL_TAG.to_string());
+    } else {
+        memory.tags.retain(|t| t != PROVISIONAL_TAG);
+    }
+    idx.upsert(&memory, None)
+        .map_err(|e| anyhow::anyhow!("collective upsert failed: {e}"))
+}
+
+/// One step down in visibility: Public → Group, Group → Private.
+fn demoted_tier(tier: &MemoryTier) -> MemoryTier {
+    match tier {
+        MemoryTier::Public => MemoryTier::Group("default".to_string()),
+        MemoryTier::Group(_) | MemoryTier::Private(_) => MemoryTier::Private("self".to_string()),
+    }
+}
+
+/// Whether review marked this memory superseded.
+fn is_superseded(memory: &SnowMemory) -> bool {
+    memory.tags.iter().any(|t| t == SUPERSEDED_TAG)
+}
+
+/// A live memory saying the same as `summary`: the same text under `topic`,
+/// or a search hit sharing most of its words.
+fn find_duplicate(
+    index: &SqliteMemoryIndex,
+    topic: &str,
+    summary: &str,
+) -> anyhow::Result<Option<SnowMemory>> {
+    let words = word_set(summary);
+    let same_topic = index
+        .get_by_topic(topic)
+        .map_err(|e| anyhow::anyhow!("duplicate lookup failed: {e}"))?;
+    let similar = index
+        .search(summary, None, 5)
+        .map_err(|e| anyhow::anyhow!("duplicate search failed: {e}"))?;
+    Ok(same_topic
+        .into_iter()
+        .chain(similar.into_iter().map(|(m, _)| m))
+        .filter(|m| !is_superseded(m))
+        .find(|m| word_overlap(&words, &word_set(&m.summary)) >= DUPLICATE_WORD_OVERLAP))
+}
+
+fn word_set(text: &str) -> std::collections::HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Jaccard similarity of two word sets.
+fn word_overlap(
+    a: &std::collections::HashSet<String>,
+    b: &std::collections::HashSet<String>,
+) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let overlap = a.intersection(b).count() as f64 / a.union(b).count() as f64;
+    overlap
+}
+
 /// Human-readable label for a tier (used in error messages).
 fn tier_label(tier: &MemoryTier) -> &'static str {
     match tier {
@@ -961,6 +2104,27 @@ fn now_unix() -> u64 {
         .as_secs()
 }
 
+/// Mark recalled memories as used so they stay out of the archive.
+fn touch_entries(index: &SqliteMemoryIndex, entries: &[MemoryEntry]) {
+    let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+    if let Err(e) = index.touch(&ids, now_unix()) {
+        tracing::debug!("collective memory: failed to record use: {e}");
+    }
+}
+
+/// New information about an archived topic brings the archived versions
+/// back, so they take part in supersedes chains and conflict detection.
+fn rehydrate_conflicting(index: &SqliteMemoryIndex, topic: &str) {
+    match index.rehydrate_topic(topic, now_unix()) {
+        Ok(restored) if !restored.is_empty() => tracing::info!(
+            "collective memory: rehydrated {} archived memories for '{topic}'",
+            restored.len()
+        ),
+        Ok(_) => {}
+        Err(e) => tracing::warn!("collective memory: rehydrate failed for '{topic}': {e}"),
+    }
+}
+
 #[async_trait]
 impl Memory for CollectiveMemory {
     fn name(&self) -> &str {
@@ -984,11 +2148,12 @@ impl Memory for CollectiveMemory {
         let source = self
             .relay
             .as_ref()
-            .map(|r| r.keys.public_key().to_hex())
+            .map(|r| r.signer.public_key().to_hex())
             .unwrap_or_else(|| "self".to_string());
 
         // Determine tier: if the key has a recognized scope prefix, use that;
-        // otherwise fall back to category-based classification.
+        // otherwise fall back to category-based classification. Inside an
+        // isolated group the result is narrowed to that group.
         let tier = if key.contains(':') {
             scope_to_tier(key)
         } else {
@@ -997,7 +2162,7 @@ impl Memory for CollectiveMemory {
 
         let memory = SnowMemory {
             id,
-            tier,
+            tier: self.scoped_tier(tier),
             topic: key.to_string(),
             summary,
             detail,
@@ -1014,8 +2179,10 @@ impl Memory for CollectiveMemory {
         // Store locally first
         {
             let idx = self.index.lock();
+            rehydrate_conflicting(&idx, &memory.topic);
             idx.upsert(&memory, None)
                 .map_err(|e| anyhow::anyhow!("collective store failed: {e}"))?;
+            self.record_owner_corrections(&idx, &memory);
         }
 
         // Fire-and-forget relay publish
@@ -1030,18 +2197,39 @@ impl Memory for CollectiveMemory {
         limit: usize,
         _session_id: Option<&str>,
     ) -> anyhow::Result<Vec<MemoryEntry>> {
-        let sm_config = self.config.to_snow_memory_config();
+        let query = match query.strip_prefix(ARCHIVE_QUERY_PREFIX) {
+            Some(rest) => {
+                let rest = rest.trim();
+                let restored = self.rehydrate_matching(rest, limit)?;
+                if !restored.is_empty() {
+                    tracing::info!(
+                        "collective memory: rehydrated {} archived memories",
+                        restored.len()
+                    );
+                }
+                rest
+            }
+            None => query,
+        };
+
+        let scoped = current_context().is_some();
         let idx = self.index.lock();
+        let sm_config = ranking_config(&idx, &self.config);
 
+        // Fetch extra results when filtering so we still return up to `limit` after filtering.
+        let fetch_limit = if scoped { limit * 3 } else { limit };
         let results = idx
-            .ranked_search(query, None, &sm_config, limit)
+            .ranked_search(query, None, &sm_config, fetch_limit)
             .map_err(|e| anyhow::anyhow!("collective recall failed: {e}"))?;
 
         let entries: Vec<MemoryEntry> = results
             .iter()
+            .filter(|r| !is_superseded(&r.memory))
+            .filter(|r| self.visible_here(&r.memory.tier))
             .take(limit)
             .map(|r| snow_to_entry(&r.memory, Some(r.effective_score)))
             .collect();
+        touch_entries(&idx, &entries);
 
         Ok(entries)
     }
@@ -1051,7 +2239,10 @@ impl Memory for CollectiveMemory {
         let result = idx
             .get_by_topic(key)
             .map_err(|e| anyhow::anyhow!("collective get failed: {e}"))?;
-        Ok(result.as_ref().map(|m| snow_to_entry(m, None)))
+        Ok(result
+            .as_ref()
+            .filter(|m| self.visible_here(&m.tier))
+            .map(|m| snow_to_entry(m, None)))
     }
 
     async fn list(
@@ -1070,7 +2261,11 @@ impl Memory for CollectiveMemory {
             .list_all(tier_filter, 1000)
             .map_err(|e| anyhow::anyhow!("collective list failed: {e}"))?;
 
-        Ok(memories.iter().map(|m| snow_to_entry(m, None)).collect())
+        Ok(memories
+            .iter()
+            .filter(|m| self.visible_here(&m.tier))
+            .map(|m| snow_to_entry(m, None))
+            .collect())
     }
 
     async fn forget(&self, key: &str) -> anyhow::Result<bool> {
@@ -1207,11 +2402,53 @@ mod tests {
     }
 
     #[test]
-    fn nostr_event_to_memory_event_conversion() {
+    fn snapshot_memory_event_tags() {
+        let memory = SnowMemory {
+            id: "test123".to_string(),
+            tier: MemoryTier::Public,
+            topic: "rust/errors".to_string(),
+            summary: "Use anyhow for errors".to_string(),
+            detail: "Prefer anyhow::Result in app code.".to_string(),
+            context: None,
+            source: "deadbeef".to_string(),
+            model: "test/model".to_string(),
+            confidence: 0.9,
+            supersedes: Some("old123".to_string()),
+            version: 2,
+            tags: vec!["rust".to_string(), "errors".to_string()],
+            created_at: 1700000000,
+        };
+
         let keys = nostr_sdk::Keys::generate();
+        let event = memory_to_event_builder(&memory)
+            .sign_with_keys(&keys)
+            .unwrap();
+        let tags: Vec<Vec<String>> = event.tags.iter().map(|t| t.as_slice().to_vec()).collect();
 
-        let tags = vec![
-            nostr_sdk::Tag::custom(
+        assert_eq!(event.kind, nostr_sdk::Kind::Custom(30078));
+        assert_eq!(
+            serde_json::json!(tags),
+            serde_json::json!([
+                ["d", "snow:memory:rust/errors"],
+                ["snow:tier", "public"],
+                ["snow:model", "test/model"],
+                ["snow:confidence", "0.90"],
+                ["snow:source", "deadbeef"],
+                ["snow:version", "2"],
+                ["snow:schema", "2"],
+                ["snow:supersedes", "old123"],
+                ["t", "rust"],
+                ["t", "errors"],
+            ])
+        );
+    }
+
+    #[test]
+    fn nostr_event_to_memory_event_conversion() {
+        let keys = nostr_sdk::Keys::generate();
+
+        let tags = vec![
+            nostr_sdk::Tag::custom(
                 nostr_sdk::TagKind::custom("d"),
                 vec!["snow:memory:test/topic".to_string()],
             ),
@@ -1327,6 +2564,148 @@ mod tests {
         assert!(mem.relay_enabled());
     }
 
+    fn quorum_config(min_tier: &str) -> CollectiveMemoryConfig {
+        CollectiveMemoryConfig {
+            relay_urls: vec![
+                "wss://relay-a.example.com".to_string(),
+                "wss://relay-b.example.com".to_string(),
+            ],
+            quorum: CollectiveQuorumConfig {
+                enabled: true,
+                min_acks: 3,
+                min_tier: min_tier.to_string(),
+                retries: 0,
+                ..CollectiveQuorumConfig::default()
+            },
+            ..CollectiveMemoryConfig::default()
+        }
+    }
+
+    #[test]
+    fn quorum_covers_tiers_from_min_tier() {
+        let config = quorum_config("group");
+        assert_eq!(quorum_acks(&config, &MemoryTier::Private("s".into())), None);
+        // min_acks is capped at the two configured relays.
+        assert_eq!(
+            quorum_acks(&config, &MemoryTier::Group("g".into())),
+            Some(2)
+        );
+        assert_eq!(quorum_acks(&config, &MemoryTier::Public), Some(2));
+        assert_eq!(
+            quorum_acks(&quorum_config("private"), &MemoryTier::Private("s".into())),
+            Some(2)
+        );
+
+        let disabled = CollectiveMemoryConfig {
+            quorum: CollectiveQuorumConfig::default(),
+            ..config
+        };
+        assert_eq!(quorum_acks(&disabled, &MemoryTier::Public), None);
+        assert!(quorum_min_rank(&quorum_config("everyone").quorum).is_err());
+    }
+
+    #[test]
+    fn unacknowledged_lists_missing_relays() {
+        let urls = quorum_config("group").relay_urls;
+        let acked =
+            std::iter::once(nostr_sdk::RelayUrl::parse("wss://relay-a.example.com").unwrap())
+                .collect();
+        assert_eq!(
+            unacknowledged(&urls, &acked),
+            vec!["wss://relay-b.example.com".to_string()]
+        );
+    }
+
+    #[test]
+    fn routed_tiers_fall_back_to_relay_urls() {
+        let mut config = quorum_config("group");
+        config.relay_routes.private = vec![
+            "wss://fast.example.com".to_string(),
+            "wss://relay-a.example.com".to_string(),
+        ];
+
+        let private = relay_route(&config, &MemoryTier::Private("self".into()));
+        assert_eq!(
+            private.primary,
+            ["wss://fast.example.com", "wss://relay-a.example.com"]
+        );
+        assert_eq!(private.fallback, ["wss://relay-b.example.com"]);
+        let public = relay_route(&config, &MemoryTier::Public);
+        assert_eq!(public.primary, config.relay_urls);
+        assert!(public.fallback.is_empty());
+        // Quorum counts acknowledgments from the fallback relay too.
+        config.quorum.min_tier = "private".to_string();
+        assert_eq!(
+            quorum_acks(&config, &MemoryTier::Private("s".into())),
+            Some(3)
+        );
+
+        config.relay_routes.fallback = false;
+        let private = relay_route(&config, &MemoryTier::Private("self".into()));
+        assert!(private.fallback.is_empty());
+        assert_eq!(
+            config.all_relay_urls(),
+            [
+                "wss://relay-a.example.com",
+                "wss://relay-b.example.com",
+                "wss://fast.example.com"
+            ]
+        );
+
+        assert!(validate_relay_urls(&config).is_ok());
+        config.relay_routes.group = vec!["fast.example.com/".to_string()];
+        let err = validate_relay_urls(&config).unwrap_err().to_string();
+        assert!(err.contains("memory.collective.relay_routes.group"));
+    }
+
+    #[test]
+    fn published_relays_round_trip() {
+        let mem = CollectiveMemory::new_in_memory(&test_config()).unwrap();
+        let idx = mem.index.lock();
+        assert!(published_relays(&idx, "m1").is_empty());
+        let relays = vec![
+            "wss://a.example.com".to_string(),
+            "wss://b.example.com".to_string(),
+        ];
+        set_published_relays(&idx, "m1", &relays).unwrap();
+        assert_eq!(published_relays(&idx, "m1"), relays);
+    }
+
+    #[tokio::test]
+    async fn memories_without_quorum_stay_provisional() {
+        let keys = nostr_sdk::Keys::generate();
+        let nsec = keys.secret_key().to_bech32().unwrap();
+        let mem =
+            CollectiveMemory::new_in_memory_with_relay(&quorum_config("public"), &nsec).unwrap();
+
+        mem.store_with_tier(
+            "lesson:deploys",
+            "Deploy on Tuesdays",
+            MemoryCategory::Core,
+            MemoryTier::Public,
+        )
+        .await
+        .unwrap();
+        mem.store_with_tier(
+            "pref:coffee",
+            "Oat milk",
+            MemoryCategory::Core,
+            MemoryTier::Private("self".into()),
+        )
+        .await
+        .unwrap();
+
+        let idx = mem.index.lock();
+        let public = idx.get_by_topic("lesson:deploys").unwrap().unwrap();
+        assert!(public.tags.iter().any(|t| t == PROVISIONAL_TAG));
+        let private = idx.get_by_topic("pref:coffee").unwrap().unwrap();
+        assert!(private.tags.is_empty());
+
+        set_provisional(&idx, &public.id, false).unwrap();
+        let acked = idx.get(&public.id).unwrap().unwrap();
+        assert!(!acked.tags.iter().any(|t| t == PROVISIONAL_TAG));
+    }
+
     #[test]
     fn is_nip44_encrypted_detects_tag() {
         let keys = nostr_sdk::Keys::generate();
@@ -1348,22 +2727,21 @@ mod tests {
         assert!(is_nip44_encrypted(&enc_event));
     }
 
-    #[test]
-    fn nip44_encrypt_decrypt_roundtrip_collective() {
-        let keys = nostr_sdk::Keys::generate();
+    #[tokio::test]
+    async fn nip44_encrypt_decrypt_roundtrip_collective() {
+        let signer = Signer::Local(nostr_sdk::Keys::generate());
         let plaintext = "secret collective memory content";
 
-        let ciphertext = nip44::encrypt(
-            keys.secret_key(),
-            &keys.public_key(),
-            plaintext,
-            nip44::Version::V2,
-        )
-        .expect("NIP-44 encrypt should succeed");
+        let ciphertext = signer
+            .nip44_encrypt(&signer.public_key(), plaintext)
+            .await
+            .expect("NIP-44 encrypt should succeed");
 
         assert_ne!(ciphertext, plaintext);
 
-        let decrypted = nip44::decrypt(keys.secret_key(), &keys.public_key(), &ciphertext)
+        let decrypted = signer
+            .nip44_decrypt(&signer.public_key(), &ciphertext)
+            .await
             .expect("NIP-44 decrypt should succeed");
 
         assert_eq!(decrypted, plaintext);
@@ -1812,3 +3190,633 @@ mod promote_tests {
         assert!(tier_rank(&MemoryTier::Private("s".into())) < tier_rank(&MemoryTier::Public));
     }
 }
+
+#[cfg(test)]
+mod review_tests {
+    use super::*;
+
+    const DAY: u64 = 86_400;
+
+    fn insert(mem: &CollectiveMemory, topic: &str, tier: MemoryTier, created_at: u64) {
+        let memory = SnowMemory {
+            id: format!("id-{topic}"),
+            tier,
+            topic: topic.to_string(),
+            summary: format!("Snowclaw review fact {topic}"),
+            detail: String::new(),
+            context: None,
+            source: "self".to_string(),
+            model: String::new(),
+            confidence: 0.8,
+            supersedes: None,
+            version: 1,
+            tags: vec![],
+            created_at,
+        };
+        mem.index.lock().upsert(&memory, None).unwrap();
+    }
+
+    fn due_topics(mem: &CollectiveMemory, now: u64) -> Vec<String> {
+        mem.due_for_review(30 * DAY, 10, now)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.topic)
+            .collect()
+    }
+
+    #[test]
+    fn picks_stale_shared_memories_oldest_first() {
+        let mem = CollectiveMemory::new_in_memory(&CollectiveMemoryConfig::default()).unwrap();
+        let now = 1_000 * DAY;
+        insert(&mem, "old", MemoryTier::Public, now - 90 * DAY);
+        insert(
+            &mem,
+            "older",
+            MemoryTier::Group("g".into()),
+            now - 120 * DAY,
+        );
+        insert(&mem, "fresh", MemoryTier::Public, now - 5 * DAY);
+        insert(
+            &mem,
+            "private",
+            MemoryTier::Private("self".into()),
+            now - 90 * DAY,
+        );
+
+        assert_eq!(due_topics(&mem, now), vec!["older", "old"]);
+    }
+
+    #[test]
+    fn confirmed_memories_wait_longer_each_time() {
+        let mem = CollectiveMemory::new_in_memory(&CollectiveMemoryConfig::default()).unwrap();
+        let now = 1_000 * DAY;
+        insert(&mem, "fact", MemoryTier::Public, now - 40 * DAY);
+        let memory = mem.due_for_review(30 * DAY, 10, now).unwrap().remove(0);
+
+        let refreshed = mem
+            .apply_review(&memory, ReviewVerdict::Refresh, "", now)
+            .unwrap();
+        assert_eq!(refreshed.created_at, now);
+        assert_eq!(refreshed.version, 2);
+        // One confirmation doubles the wait: not due after 40 days, due after 60.
+        assert!(due_topics(&mem, now + 40 * DAY).is_empty());
+        assert_eq!(due_topics(&mem, now + 60 * DAY), vec!["fact"]);
+    }
+
+    #[tokio::test]
+    async fn demote_and_supersede() {
+        let mem = CollectiveMemory::new_in_memory(&CollectiveMemoryConfig::default()).unwrap();
+        let now = 1_000 * DAY;
+        insert(&mem, "broad", MemoryTier::Public, now - 40 * DAY);
+        insert(&mem, "wrong", MemoryTier::Public, now - 40 * DAY);
+
+        let broad = mem.index.lock().get_by_topic("broad").unwrap().unwrap();
+        let demoted = mem
+            .apply_review(&broad, ReviewVerdict::Demote, "only true for one team", now)
+            .unwrap();
+        assert_eq!(demoted.tier, MemoryTier::Group("default".into()));
+        assert_eq!(
+            demoted.context.as_deref(),
+            Some("review: only true for one team")
+        );
+
+        let wrong = mem.index.lock().get_by_topic("wrong").unwrap().unwrap();
+        mem.apply_review(&wrong, ReviewVerdict::Supersede, "relay moved", now)
+            .unwrap();
+        let recalled = mem.recall("Snowclaw review fact", 10, None).await.unwrap();
+        assert!(recalled.iter().all(|e| e.key != "wrong"));
+        assert!(!due_topics(&mem, now + 365 * DAY).contains(&"wrong".to_string()));
+    }
+}
+
+#[cfg(test)]
+mod archive_tests {
+    use super::*;
+
+    const DAY: u64 = 86_400;
+
+    fn archiving_memory() -> CollectiveMemory {
+        let config = CollectiveMemoryConfig {
+            archive: crate::config::CollectiveArchiveConfig {
+                enabled: true,
+                after_months: 6,
+            },
+            ..CollectiveMemoryConfig::default()
+        };
+        CollectiveMemory::new_in_memory(&config).unwrap()
+    }
+
+    fn insert_old(mem: &CollectiveMemory, topic: &str, summary: &str) {
+        let memory = SnowMemory {
+            id: format!("id-{topic}"),
+            tier: MemoryTier::Public,
+            topic: topic.to_string(),
+            summary: summary.to_string(),
+            detail: String::new(),
+            context: None,
+            source: "self".to_string(),
+            model: String::new(),
+            confidence: 0.8,
+            supersedes: None,
+            version: 1,
+            tags: vec![],
+            created_at: now_unix() - 365 * DAY,
+        };
+        mem.index.lock().upsert(&memory, None).unwrap();
+    }
+
+    #[tokio::test]
+    async fn archived_memories_need_explicit_search() {
+        let mem = archiving_memory();
+        insert_old(&mem, "infra:staging", "Staging relay runs on relay1");
+        assert_eq!(mem.archive_untouched(now_unix()).unwrap(), 1);
+
+        assert!(mem
+            .recall("staging relay", 5, None)
+            .await
+            .unwrap()
+            .is_empty());
+        let found = mem
+            .recall(&format!("{ARCHIVE_QUERY_PREFIX} staging relay"), 5, None)
+            .await
+            .unwrap();
+        assert_eq!(found[0].key, "infra:staging");
+        // Back in the index and marked used.
+        assert_eq!(mem.recall("staging relay", 5, None).await.unwrap().len(), 1);
+        assert_eq!(mem.archive_untouched(now_unix()).unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn new_information_rehydrates_the_topic() {
+        let mem = archiving_memory();
+        insert_old(&mem, "infra:staging", "Staging relay runs on relay1");
+        mem.archive_untouched(now_unix()).unwrap();
+
+        mem.store(
+            "infra:staging",
+            "Staging relay runs on relay2",
+            MemoryCategory::Core,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(mem.count().await.unwrap(), 2);
+        assert_eq!(mem.index.lock().archive_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn archive_disabled_by_default() {
+        let mem = CollectiveMemory::new_in_memory(&CollectiveMemoryConfig::default()).unwrap();
+        insert_old(&mem, "infra:staging", "Staging relay runs on relay1");
+        assert_eq!(mem.archive_untouched(now_unix()).unwrap(), 0);
+    }
+}
+
+#[cfg(test)]
+mod conflict_scan_tests {
+    use super::*;
+
+    const HOUR: u64 = 3600;
+
+    fn scanning_memory() -> CollectiveMemory {
+        let config = CollectiveMemoryConfig {
+            conflicts: CollectiveConflictsConfig {
+                enabled: true,
+                window_hours: 24,
+                ..CollectiveConflictsConfig::default()
+            },
+            ..CollectiveMemoryConfig::default()
+        };
+        CollectiveMemory::new_in_memory(&config).unwrap()
+    }
+
+    fn insert(mem: &CollectiveMemory, id: &str, source: &str, summary: &str, created_at: u64) {
+        let memory = SnowMemory {
+            id: id.to_string(),
+            tier: MemoryTier::Public,
+            topic: "infra:staging".to_string(),
+            summary: summary.to_string(),
+            detail: String::new(),
+            context: None,
+            source: source.to_string(),
+            model: String::new(),
+            confidence: 0.8,
+            supersedes: None,
+            version: 1,
+            tags: vec![],
+            created_at,
+        };
+        mem.index.lock().upsert(&memory, None).unwrap();
+    }
+
+    #[test]
+    fn window_limits_scanned_topics() {
+        let mem = scanning_memory();
+        let now = now_unix();
+        insert(&mem, "a", "alice", "relay1", now - 48 * HOUR);
+        insert(&mem, "b", "bob", "relay2", now - 47 * HOUR);
+
+        assert!(mem
+            .detect_conflicts_between(now - 24 * HOUR, now)
+            .unwrap()
+            .is_empty());
+        let conflicts = mem.detect_conflicts_between(now - 72 * HOUR, now).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].entries[0].memory_id, "b");
+    }
+
+    #[test]
+    fn scan_records_each_conflict_once() {
+        let mem = scanning_memory();
+        let now = now_unix();
+        insert(&mem, "a1", "alice", "relay1", now - 3 * HOUR);
+        insert(&mem, "a2", "alice", "relay3", now - 2 * HOUR);
+        insert(&mem, "b", "bob", "relay2", now - HOUR);
+
+        let found = mem.scan_conflicts(now).unwrap();
+        assert_eq!(found.len(), 1);
+        // Only the latest memory from each source.
+        let ids: Vec<&str> = found[0]
+            .entries
+            .iter()
+            .map(|e| e.memory_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["b", "a2"]);
+        assert!(mem.scan_conflicts(now + 60).unwrap().is_empty());
+        assert_eq!(mem.unresolved_conflicts().unwrap().len(), 1);
+
+        let dm = render_conflict_dm(&found[0]);
+        assert!(dm.contains("relay2") && dm.contains("relay3"));
+        assert!(!dm.contains("relay1"));
+        let notice = conflict_notice(&found[0], now);
+        assert_eq!(notice.sides.len(), 2);
+    }
+
+    #[test]
+    fn superseding_one_side_resolves_the_conflict() {
+        let mem = scanning_memory();
+        let now = now_unix();
+        insert(&mem, "a", "alice", "relay1", now - 2 * HOUR);
+        insert(&mem, "b", "bob", "relay2", now - HOUR);
+        assert_eq!(mem.scan_conflicts(now).unwrap().len(), 1);
+
+        let mut superseded = mem.index.lock().get("a").unwrap().unwrap();
+        superseded.id = "a2".to_string();
+        superseded.created_at = now;
+        superseded.tags.push(SUPERSEDED_TAG.to_string());
+        mem.index.lock().upsert(&superseded, None).unwrap();
+
+        assert!(mem.scan_conflicts(now + 60).unwrap().is_empty());
+        assert!(mem.unresolved_conflicts().unwrap().is_empty());
+    }
+
+    fn reputation_memory() -> CollectiveMemory {
+        let trust = |npub: &str| crate::config::snowclaw_schema::CollectiveSourceEntry {
+            npub: Some(npub.to_string()),
+            group: None,
+            trust: 1.0,
+        };
+        let config = CollectiveMemoryConfig {
+            source_preferences: vec![trust("alice"), trust("bob")],
+            reputation: CollectiveReputationConfig {
+                enabled: true,
+                ..CollectiveReputationConfig::default()
+            },
+            ..scanning_memory().config
+        };
+        CollectiveMemory::new_in_memory(&config).unwrap()
+    }
+
+    fn trust_of(mem: &CollectiveMemory, npub: &str) -> f64 {
+        ranking_config(&mem.index.lock(), &mem.config)
+            .sources
+            .iter()
+            .find(|s| s.npub.as_deref() == Some(npub))
+            .unwrap()
+            .trust
+    }
+
+    #[test]
+    fn lost_conflicts_lower_trust() {
+        let mem = reputation_memory();
+        let now = now_unix();
+        insert(&mem, "a", "alice", "relay1", now - 2 * HOUR);
+        insert(&mem, "b", "bob", "relay2", now - HOUR);
+        mem.scan_conflicts(now).unwrap();
+
+        let mut superseded = mem.index.lock().get("a").unwrap().unwrap();
+        superseded.id = "a2".to_string();
+        superseded.created_at = now;
+        superseded.tags.push(SUPERSEDED_TAG.to_string());
+        mem.index.lock().upsert(&superseded, None).unwrap();
+        mem.scan_conflicts(now + 60).unwrap();
+
+        assert!((trust_of(&mem, "alice") - 0.8).abs() < 1e-9);
+        assert!((trust_of(&mem, "bob") - 1.0).abs() < 1e-9);
+        let changes = mem.index.lock().reputation_changes_since(0).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert!(changes
+            .iter()
+            .any(|c| c.source == "alice" && c.reason == ReputationReason::ConflictLost));
+    }
+
+    #[test]
+    fn reputation_factor_stays_bounded() {
+        let config = CollectiveReputationConfig::default();
+        let mut factor = 1.0;
+        for _ in 0..20 {
+            factor = next_reputation_factor(factor, ReputationReason::ConflictLost, &config);
+        }
+        assert!((factor - config.floor).abs() < 1e-9);
+        for _ in 0..40 {
+            factor = next_reputation_factor(factor, ReputationReason::ConflictWon, &config);
+        }
+        assert!((factor - 1.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn owner_stores_correct_other_sources() {
+        use crate::memory::snowclaw_ext::{scope, RecallContext};
+
+        let mem = reputation_memory();
+        let now = now_unix();
+        insert(&mem, "a", "alice", "relay1", now - HOUR);
+        insert(&mem, "b", "bob", "relay2", now - HOUR);
+
+        // Outside the owner's session a store is not a correction.
+        mem.store("infra:staging", "relay3", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+        assert!((trust_of(&mem, "alice") - 1.0).abs() < 1e-9);
+
+        let owner = Some(RecallContext::direct("nostr", true));
+        scope(
+            owner,
+            mem.store("infra:staging", "relay2", MemoryCategory::Core, None),
+        )
+        .await
+        .unwrap();
+        assert!((trust_of(&mem, "alice") - 0.8).abs() < 1e-9);
+        // Bob already agreed with the owner.
+        assert!((trust_of(&mem, "bob") - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn scan_disabled_by_default() {
+        let mem = CollectiveMemory::new_in_memory(&CollectiveMemoryConfig::default()).unwrap();
+        let now = now_unix();
+        insert(&mem, "a", "alice", "relay1", now - HOUR);
+        insert(&mem, "b", "bob", "relay2", now - HOUR);
+        assert!(mem.scan_conflicts(now).unwrap().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod group_scope_tests {
+    use super::*;
+    use crate::memory::snowclaw_ext::scope;
+
+    fn scoped_memory(shared: &[&str]) -> CollectiveMemory {
+        let config = CollectiveMemoryConfig {
+            source_preferences: vec![crate::config::snowclaw_schema::CollectiveSourceEntry {
+                npub: Some("self".to_string()),
+                group: None,
+                trust: 1.0,
+            }],
+            groups: CollectiveGroupsConfig {
+                isolate: true,
+                shared: shared.iter().map(|g| (*g).to_string()).collect(),
+            },
+            ..CollectiveMemoryConfig::default()
+        };
+        CollectiveMemory::new_in_memory(&config).unwrap()
+    }
+
+    fn in_group(group: &str) -> Option<RecallContext> {
+        Some(RecallContext::group("nostr", group))
+    }
+
+    fn tier_of(mem: &CollectiveMemory, topic: &str) -> MemoryTier {
+        mem.index.lock().get_by_topic(topic).unwrap().unwrap().tier
+    }
+
+    #[tokio::test]
+    async fn group_memories_stay_in_their_group() {
+        let mem = scoped_memory(&[]);
+        scope(
+            in_group("infra"),
+            mem.store(
+                "deploy/staging",
+                "Staging deploys run from the ops branch",
+                MemoryCategory::Core,
+                None,
+            ),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            tier_of(&mem, "deploy/staging"),
+            MemoryTier::Group("infra".into())
+        );
+
+        let here = scope(in_group("infra"), mem.recall("staging deploys", 5, None))
+            .await
+            .unwrap();
+        assert_eq!(here.len(), 1);
+
+        let elsewhere = scope(in_group("lobby"), mem.recall("staging deploys", 5, None))
+            .await
+            .unwrap();
+        assert!(elsewhere.is_empty());
+        let got = scope(in_group("lobby"), mem.get("deploy/staging"))
+            .await
+            .unwrap();
+        assert!(got.is_none());
+        let listed = scope(in_group("lobby"), mem.list(None, None))
+            .await
+            .unwrap();
+        assert!(listed.is_empty());
+
+        // Outside any conversation (CLI, daemon jobs) nothing is filtered.
+        assert_eq!(
+            mem.recall("staging deploys", 5, None).await.unwrap().len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn group_cannot_write_into_another_namespace() {
+        let mem = scoped_memory(&[]);
+        scope(
+            in_group("infra"),
+            mem.store(
+                "group:lobby:relay",
+                "Relay is relay1",
+                MemoryCategory::Core,
+                None,
+            ),
+        )
+        .await
+        .unwrap();
+        scope(
+            in_group("infra"),
+            mem.store("pref:lang", "Prefers Rust", MemoryCategory::Core, None),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            tier_of(&mem, "group:lobby:relay"),
+            MemoryTier::Group("infra".into())
+        );
+        assert!(matches!(tier_of(&mem, "pref:lang"), MemoryTier::Private(_)));
+    }
+
+    #[tokio::test]
+    async fn shared_namespaces_are_visible_everywhere() {
+        let mem = scoped_memory(&["lobby"]);
+        scope(
+            in_group("lobby"),
+            mem.store(
+                "relay/main",
+                "Main relay is relay1",
+                MemoryCategory::Core,
+                None,
+            ),
+        )
+        .await
+        .unwrap();
+        mem.store(
+            "group:lobby:rules",
+            "Be kind in the lobby",
+            MemoryCategory::Core,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(tier_of(&mem, "relay/main"), MemoryTier::Public);
+        let seen = scope(in_group("infra"), mem.recall("lobby", 5, None))
+            .await
+            .unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].key, "group:lobby:rules");
+    }
+
+    #[tokio::test]
+    async fn isolation_can_be_disabled() {
+        let config = CollectiveMemoryConfig {
+            groups: CollectiveGroupsConfig {
+                isolate: false,
+                shared: vec![],
+            },
+            ..CollectiveMemoryConfig::default()
+        };
+        let mem = CollectiveMemory::new_in_memory(&config).unwrap();
+        scope(
+            in_group("infra"),
+            mem.store("deploy/staging", "Ops branch", MemoryCategory::Core, None),
+        )
+        .await
+        .unwrap();
+        assert_eq!(tier_of(&mem, "deploy/staging"), MemoryTier::Public);
+    }
+
+    #[tokio::test]
+    async fn private_memories_only_reach_the_owner() {
+        use crate::memory::snowclaw_ext::Audience;
+
+        // Private memories stay hidden even without group isolation.
+        let mut config = scoped_memory(&[]).config;
+        config.groups.isolate = false;
+        let mem = CollectiveMemory::new_in_memory(&config).unwrap();
+        mem.store(
+            "pref:owner:lang",
+            "Owner prefers Finnish",
+            MemoryCategory::Core,
+            None,
+        )
+        .await
+        .unwrap();
+        mem.store(
+            "core:relay",
+            "Owner runs relay1",
+            MemoryCategory::Core,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let mem = &mem;
+        let recall = move |audience: Audience| async move {
+            let context = Some(audience.recall_context("nostr"));
+            let hits = scope(context.clone(), mem.recall("Owner", 5, None))
+                .await
+                .unwrap();
+            let got = scope(context, mem.get("pref:owner:lang")).await.unwrap();
+            (hits.len(), got.is_some())
+        };
+        assert_eq!(recall(Audience::OwnerDm).await, (2, true));
+        assert_eq!(recall(Audience::Dm).await, (1, false));
+        assert_eq!(recall(Audience::Group("infra".into())).await, (1, false));
+    }
+}
+
+#[cfg(test)]
+mod layered_config_tests {
+    use super::*;
+    use snow_memory::ConfigSource;
+
+    fn config_event(keys: &nostr_sdk::Keys, content: &str, at: u64) -> nostr_sdk::Event {
+        nostr_sdk::EventBuilder::new(nostr_sdk::Kind::Custom(30078), content)
+            .tag(nostr_sdk::Tag::identifier(DYNAMIC_CONFIG_D_TAG))
+            .custom_created_at(nostr_sdk::Timestamp::from(at))
+            .sign_with_keys(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn file_fields_report_their_source() {
+        let config = CollectiveMemoryConfig {
+            tier1: vec!["local/big".into()],
+            ..CollectiveMemoryConfig::default()
+        };
+        let layered = effective_memory_config(&config, None).unwrap();
+        assert_eq!(layered.source("tier1"), ConfigSource::File);
+        assert_eq!(layered.source("usage_weight"), ConfigSource::Default);
+    }
+
+    #[test]
+    fn newest_valid_dynamic_config_drives_ranking() {
+        let mem = CollectiveMemory::new_in_memory(&CollectiveMemoryConfig::default()).unwrap();
+        let keys = nostr_sdk::Keys::generate();
+        let events = vec![
+            config_event(&keys, r#"{"usage_weight": 0.5}"#, 100),
+            config_event(&keys, r#"{"usage_weight": 0.75}"#, 200),
+        ];
+        remember_dynamic_config(&mem.index.lock(), &mem.config, &events);
+        let ranking = ranking_config(&mem.index.lock(), &mem.config);
+        assert_eq!(ranking.usage_weight, 0.75);
+
+        // An out-of-range config is not kept
+        let bad = vec![config_event(&keys, r#"{"usage_weight": -2}"#, 300)];
+        remember_dynamic_config(&mem.index.lock(), &mem.config, &bad);
+        let stored = dynamic_config(&mem.index.lock()).unwrap();
+        assert_eq!(stored, r#"{"usage_weight": 0.75}"#);
+    }
+
+    #[test]
+    fn relays_only_come_from_the_file() {
+        let config = CollectiveMemoryConfig {
+            relay_urls: vec!["wss://file.example".into()],
+            ..CollectiveMemoryConfig::default()
+        };
+        let layered = effective_memory_config(
+            &config,
+            Some(r#"{"relays_public": ["wss://other.example"]}"#),
+        )
+        .unwrap();
+        assert_eq!(layered.config.relays_public, vec!["wss://file.example"]);
+        assert_eq!(layered.warnings.len(), 1);
+    }
+}
@@ -0,0 +1,519 @@
+//! Distillation of indexed messages into collective memories.
+//!
+//! Runs as the `memory_distill` daemon component when
+//! `[memory.collective.distill]` is enabled. Each pass reads the messages
+//! indexed in `social.db` since the previous pass, groups the substantial
+//! ones by conversation and asks the LLM for durable facts and preferences.
+//! [`CollectiveMemory::store_distilled`] drops facts that are already known
+//! and stores the rest with the ids of the events they came from.
+//!
+//! `[memory.write_policy]` applies here too: messages from no-memory groups
+//! are never shown to the LLM, and facts matching a banned pattern are
+//! dropped before they are stored.
+
+use super::collective::CollectiveMemory;
+use super::message_index::{self, IndexableMessage};
+use super::review::{background_provider, now_unix, open_social_db, snippet, social_db_path};
+use super::write_policy::WritePolicy;
+use crate::config::{CollectiveDistillConfig, Config};
+use crate::providers::Provider;
+use anyhow::Result;
+use rusqlite::Connection;
+use snow_memory::types::MemoryTier;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const DISTILL_SYSTEM_PROMPT: &str = "You maintain a shared memory store for an AI agent. \
+You are given numbered messages from one conversation. Extract facts and preferences worth \
+remembering for months: decisions, plans, how things are set up, what people like or need. \
+Skip small talk, questions, opinions about the moment and anything already obvious.\n\
+Reply with a single JSON object: {\"facts\": [{\"topic\": \"...\", \"fact\": \"...\", \
+\"sources\": [1, 2], \"private\": false}]}\n\
+- topic: a short key such as `core:staging-relay` or `pref:alice:editor`\n\
+- fact: one self-contained sentence\n\
+- sources: numbers of the messages the fact comes from\n\
+- private: true for personal details that should stay with the owner\n\
+Reply {\"facts\": []} when there is nothing worth keeping.";
+
+/// Message text shown to the LLM is cut to this many characters.
+const MESSAGE_SNIPPET_CHARS: usize = 1_000;
+
+/// Substantial messages from one conversation, oldest first.
+#[derive(Debug, Clone)]
+pub struct Conversation {
+    /// `None` for direct messages.
+    pub group_id: Option<String>,
+    pub messages: Vec<IndexableMessage>,
+}
+
+impl Conversation {
+    /// Tier of memories learned here: the group's, or private for DMs.
+    fn tier(&self) -> MemoryTier {
+        match &self.group_id {
+            Some(id) => MemoryTier::Group(id.clone()),
+            None => MemoryTier::Private("self".to_string()),
+        }
+    }
+}
+
+/// A fact the LLM extracted, with the events it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub topic: String,
+    pub fact: String,
+    pub private: bool,
+    pub sources: Vec<String>,
+}
+
+/// Counts for one distillation pass.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DistillStats {
+    pub stored: usize,
+    pub duplicates: usize,
+    /// Facts the write policy refused.
+    pub refused: usize,
+    pub failed: usize,
+}
+
+/// Substantial messages indexed after rowid `after`, grouped by
+/// conversation, and the rowid the next pass starts from. Messages from
+/// `policy`'s no-memory groups are left out.
+pub fn prepare_conversations(
+    conn: &Connection,
+    after: i64,
+    config: &CollectiveDistillConfig,
+    policy: Option<&WritePolicy>,
+) -> Result<(Vec<Conversation>, i64)> {
+    let rows = message_index::messages_after(conn, after, config.batch_size.max(1))?;
+    let cursor = rows.last().map_or(after, |(rowid, _)| *rowid);
+
+    let mut by_group: BTreeMap<Option<String>, Vec<IndexableMessage>> = BTreeMap::new();
+    for (_, message) in rows {
+        let no_memory = policy.is_some_and(|policy| {
+            message
+                .group_id
+                .as_deref()
+                .is_some_and(|group| policy.is_no_memory_group(group))
+        });
+        if !no_memory && message.content.trim().chars().count() >= config.min_chars {
+            by_group
+                .entry(message.group_id.clone())
+                .or_default()
+                .push(message);
+        }
+    }
+    let conversations = by_group
+        .into_iter()
+        .map(|(group_id, messages)| Conversation { group_id, messages })
+        .collect();
+    Ok((conversations, cursor))
+}
+
+/// User message asking the LLM for facts in `conversation`.
+pub fn build_prompt(conversation: &Conversation) -> String {
+    let mut prompt = match &conversation.group_id {
+        Some(id) => format!("Messages from group `{id}`:\n"),
+        None => "Direct messages:\n".to_string(),
+    };
+    for (n, message) in conversation.messages.iter().enumerate() {
+        let when = chrono::DateTime::from_timestamp(message.created_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        let sender: String = message.sender_hex.chars().take(8).collect();
+        prompt.push_str(&format!(
+            "[{}] {when} {sender}: {}\n",
+            n + 1,
+            snippet(&message.content, MESSAGE_SNIPPET_CHARS)
+        ));
+    }
+    prompt
+}
+
+/// Parse the LLM reply. Accepts the JSON object anywhere in the text and
+/// maps message numbers back to event ids. Facts without a valid source
+/// are dropped.
+pub fn parse_reply(reply: &str, conversation: &Conversation) -> Option<Vec<Candidate>> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    let value: serde_json::Value = serde_json::from_str(reply.get(start..=end)?).ok()?;
+    let facts = value["facts"].as_array()?;
+
+    Some(
+        facts
+            .iter()
+            .filter_map(|fact| {
+                let topic = fact["topic"].as_str()?.trim();
+                let text = fact["fact"].as_str()?.trim();
+                if topic.is_empty() || text.is_empty() {
+                    return None;
+                }
+                let mut indices: Vec<usize> = fact["sources"]
+                    .as_array()?
+                    .iter()
+                    .filter_map(serde_json::Value::as_u64)
+                    .filter_map(|n| usize::try_from(n).ok()?.checked_sub(1))
+                    .collect();
+                indices.sort_unstable();
+                indices.dedup();
+                let sources: Vec<String> = indices
+                    .into_iter()
+                    .filter_map(|i| conversation.messages.get(i))
+                    .map(|m| m.event_id.clone())
+                    .collect();
+                if sources.is_empty() {
+                    return None;
+                }
+                Some(Candidate {
+                    topic: topic.to_string(),
+                    fact: text.to_string(),
+                    private: fact["private"].as_bool().unwrap_or(false),
+                    sources,
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Ask the LLM about each conversation and store the new facts that
+/// `policy` does not refuse. A failed call or unreadable reply is counted;
+/// the caller keeps its cursor so the messages are read again on the next
+/// pass.
+pub async fn distill(
+    memory: &CollectiveMemory,
+    provider: &dyn Provider,
+    model: &str,
+    conversations: Vec<Conversation>,
+    config: &CollectiveDistillConfig,
+    policy: Option<&WritePolicy>,
+) -> DistillStats {
+    let mut stats = DistillStats::default();
+
+    for conversation in conversations {
+        let prompt = build_prompt(&conversation);
+        let candidates = match provider
+            .chat_with_system(Some(DISTILL_SYSTEM_PROMPT), &prompt, model, 0.0)
+            .await
+        {
+            Ok(reply) => parse_reply(&reply, &conversation),
+            Err(e) => {
+                tracing::warn!("memory distill: LLM call failed: {e}");
+                None
+            }
+        };
+        let Some(candidates) = candidates else {
+            stats.failed += 1;
+            continue;
+        };
+
+        for candidate in candidates.into_iter().take(config.max_facts) {
+            if let Some(reason) = policy.and_then(|policy| {
+                policy.refusal(
+                    &candidate.topic,
+                    &candidate.fact,
+                    conversation.group_id.as_deref(),
+                )
+            }) {
+                tracing::info!("memory distill: dropped '{}': {reason}", candidate.topic);
+                stats.refused += 1;
+                continue;
+            }
+            let tier = if candidate.private {
+                MemoryTier::Private("self".to_string())
+            } else {
+                conversation.tier()
+            };
+            match memory.store_distilled(
+                &candidate.topic,
+                &candidate.fact,
+                tier,
+                &candidate.sources,
+                now_unix(),
+            ) {
+                Ok(true) => {
+                    tracing::info!(
+                        "memory distill: stored '{}' from {}",
+                        candidate.topic,
+                        candidate.sources.join(", ")
+                    );
+                    stats.stored += 1;
+                }
+                Ok(false) => stats.duplicates += 1,
+                Err(e) => {
+                    tracing::warn!("memory distill: failed to store '{}': {e}", candidate.topic);
+                    stats.failed += 1;
+                }
+            }
+        }
+    }
+    stats
+}
+
+/// Daemon worker: distill newly indexed messages every `interval_minutes`.
+pub async fn run(config: Config) -> Result<()> {
+    let distill_config = config.memory.collective.distill.clone();
+    let nsec = config
+        .memory
+        .nsec
+        .clone()
+        .or_else(|| std::env::var("SNOWCLAW_NSEC").ok());
+    let memory = CollectiveMemory::new_with_relay(
+        &config.workspace_dir,
+        &config.memory.collective,
+        nsec.as_deref(),
+    )?;
+    let (provider, model) = background_provider(&config, distill_config.model.as_deref())?;
+    let write_policy = WritePolicy::from_config(&config.memory.write_policy)?;
+
+    let social_path = social_db_path(&config);
+
+    let mut interval = tokio::time::interval(Duration::from_secs(
+        distill_config.interval_minutes.max(1) * 60,
+    ));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        interval.tick().await;
+        let prepared = open_social_db(&social_path, "memory distill")
+            .map(|conn| {
+                prepare_conversations(
+                    &conn,
+                    memory.distill_cursor(),
+                    &distill_config,
+                    write_policy.as_ref(),
+                )
+            })
+            .transpose()?;
+        if let Some((conversations, cursor)) = prepared {
+            let stats = distill(
+                &memory,
+                provider.as_ref(),
+                &model,
+                conversations,
+                &distill_config,
+                write_policy.as_ref(),
+            )
+            .await;
+            if stats.failed == 0 {
+                memory.set_distill_cursor(cursor)?;
+            }
+            if stats != DistillStats::default() {
+                tracing::info!(
+                    "memory distill: {} stored, {} duplicates, {} refused, {} failed",
+                    stats.stored,
+                    stats.duplicates,
+                    stats.refused,
+                    stats.failed
+                );
+            }
+        }
+        crate::health::mark_component_ok("memory_distill");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CollectiveMemoryConfig;
+    use crate::memory::collective::DISTILL_SOURCES_PREFIX;
+    use async_trait::async_trait;
+
+    struct ScriptedProvider(&'static str);
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    fn message(id: &str, group: Option<&str>, content: &str) -> IndexableMessage {
+        IndexableMessage {
+            event_id: id.into(),
+            sender_hex: "aabbccddeeff".into(),
+            group_id: group.map(String::from),
+            content: content.into(),
+            created_at: 1_700_000_000,
+            kind: 9,
+        }
+    }
+
+    fn dev_conversation() -> Conversation {
+        Conversation {
+            group_id: Some("dev".into()),
+            messages: vec![
+                message(
+                    "e1",
+                    Some("dev"),
+                    "we moved the staging relay to relay2 today",
+                ),
+                message(
+                    "e2",
+                    Some("dev"),
+                    "deploys now go out every tuesday morning",
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn groups_substantial_messages_by_conversation() {
+        let conn = Connection::open_in_memory().unwrap();
+        message_index::create_message_tables(&conn).unwrap();
+        for msg in [
+            message(
+                "e1",
+                Some("dev"),
+                "we moved the staging relay to relay2 today",
+            ),
+            message("e2", None, "remind me that I prefer short answers please"),
+            message("e3", Some("dev"), "ok thanks!"),
+        ] {
+            message_index::index_message(&conn, &msg).unwrap();
+        }
+        let config = CollectiveDistillConfig {
+            min_chars: 20,
+            ..CollectiveDistillConfig::default()
+        };
+
+        let (conversations, cursor) = prepare_conversations(&conn, 0, &config, None).unwrap();
+        assert_eq!(conversations.len(), 2);
+        assert_eq!(conversations[0].group_id, None);
+        assert_eq!(conversations[1].group_id.as_deref(), Some("dev"));
+        assert_eq!(conversations[1].messages.len(), 1);
+        assert!(build_prompt(&conversations[1]).contains("[1] 2023-11-14 aabbccdd: we moved"));
+
+        // Short messages still advance the cursor.
+        let (rest, next) = prepare_conversations(&conn, cursor, &config, None).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(next, cursor);
+    }
+
+    #[test]
+    fn parses_facts_with_sources() {
+        let conversation = dev_conversation();
+        let candidates = parse_reply(
+            "Here you go:\n```json\n{\"facts\": [\
+             {\"topic\": \"core:staging-relay\", \"fact\": \"Staging relay is relay2\", \"sources\": [1, 1]},\
+             {\"topic\": \"core:deploys\", \"fact\": \"Deploys run on Tuesdays\", \"sources\": [9]},\
+             {\"topic\": \"\", \"fact\": \"no topic\", \"sources\": [2]}]}\n```",
+            &conversation,
+        )
+        .unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].topic, "core:staging-relay");
+        assert_eq!(candidates[0].sources, vec!["e1"]);
+        assert!(!candidates[0].private);
+
+        assert!(parse_reply("{\"facts\": []}", &conversation)
+            .unwrap()
+            .is_empty());
+        assert!(parse_reply("nothing to add", &conversation).is_none());
+    }
+
+    #[tokio::test]
+    async fn stores_new_facts_once_with_provenance() {
+        let mem = CollectiveMemory::new_in_memory(&CollectiveMemoryConfig::default()).unwrap();
+        let provider = ScriptedProvider(
+            "{\"facts\": [{\"topic\": \"core:staging-relay\", \
+             \"fact\": \"The staging relay is relay2\", \"sources\": [1]}]}",
+        );
+        let config = CollectiveDistillConfig::default();
+
+        let stats = distill(
+            &mem,
+            &provider,
+            "m",
+            vec![dev_conversation()],
+            &config,
+            None,
+        )
+        .await;
+        assert_eq!(stats.stored, 1);
+        let stored = mem.due_for_review(0, 10, u64::MAX).unwrap();
+        assert_eq!(stored[0].tier, MemoryTier::Group("dev".into()));
+        assert_eq!(
+            stored[0].context.as_deref(),
+            Some(format!("{DISTILL_SOURCES_PREFIX}e1").as_str())
+        );
+
+        // The same fact from a later pass is recognised as known.
+        let stats = distill(
+            &mem,
+            &provider,
+            "m",
+            vec![dev_conversation()],
+            &config,
+            None,
+        )
+        .await;
+        assert_eq!(stats.stored, 0);
+        assert_eq!(stats.duplicates, 1);
+
+        let failed = distill(
+            &mem,
+            &ScriptedProvider("sorry"),
+            "m",
+            vec![dev_conversation()],
+            &config,
+            None,
+        )
+        .await;
+        assert_eq!(failed.failed, 1);
+    }
+
+    #[tokio::test]
+    async fn write_policy_keeps_no_memory_groups_and_banned_facts_out() {
+        let policy = WritePolicy::from_config(&crate::config::MemoryWritePolicyConfig {
+            enabled: true,
+            no_memory_groups: vec!["#secret".into()],
+            banned_patterns: vec!["(?i)relay2".into()],
+            ..crate::config::MemoryWritePolicyConfig::default()
+        })
+        .unwrap()
+        .unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        message_index::create_message_tables(&conn).unwrap();
+        for msg in [
+            message(
+                "e1",
+                Some("secret"),
+                "the launch date moved to the first of may",
+            ),
+            message(
+                "e2",
+                Some("dev"),
+                "deploys now go out every tuesday morning",
+            ),
+        ] {
+            message_index::index_message(&conn, &msg).unwrap();
+        }
+        let (conversations, _) =
+            prepare_conversations(&conn, 0, &CollectiveDistillConfig::default(), Some(&policy))
+                .unwrap();
+        assert_eq!(conversations.len(), 1);
+        assert_eq!(conversations[0].group_id.as_deref(), Some("dev"));
+
+        let mem = CollectiveMemory::new_in_memory(&CollectiveMemoryConfig::default()).unwrap();
+        let provider = ScriptedProvider(
+            "{\"facts\": [{\"topic\": \"core:staging-relay\", \
+             \"fact\": \"The staging relay is relay2\", \"sources\": [1]}]}",
+        );
+        let stats = distill(
+            &mem,
+            &provider,
+            "m",
+            vec![dev_conversation()],
+            &CollectiveDistillConfig::default(),
+            Some(&policy),
+        )
+        .await;
+        assert_eq!(stats.stored, 0);
+        assert_eq!(stats.refused, 1);
+    }
+}
@@ -101,8 +101,20 @@ fn content_hash(text: &str) -> String {
     )
 }
 
-/// Max tokens per chunk for the markdown chunker.
-const CHUNK_MAX_TOKENS: usize = 512;
+/// Hash of content as chunked with `strategy` and `options`, so changing
+/// either re-chunks files whose content did not change.
+fn chunked_hash(
+    content: &str,
+    strategy: chunker::ChunkStrategy,
+    options: &chunker::ChunkOptions,
+) -> String {
+    content_hash(&format!(
+        "{}:{}:{}\n{content}",
+        strategy.as_str(),
+        options.max_tokens,
+        options.overlap_tokens
+    ))
+}
 
 // ── Operations ───────────────────────────────────────────────────
 
@@ -111,6 +123,17 @@ const CHUNK_MAX_TOKENS: usize = 512;
 /// Uses hash-based change detection: if the file's content hash matches
 /// the stored hash, indexing is skipped. Returns the number of chunks indexed.
 pub fn index_file(conn: &Connection, path: &Path, category: &str) -> Result<usize> {
+    index_file_with(conn, path, category, &chunker::ChunkOptions::default())
+}
+
+/// Like [`index_file`], with explicit chunk size and overlap. The chunking
+/// strategy follows the file extension (markdown, code, TOML, plain text).
+pub fn index_file_with(
+    conn: &Connection,
+    path: &Path,
+    category: &str,
+    options: &chunker::ChunkOptions,
+) -> Result<usize> {
     let path_str = path.to_string_lossy().to_string();
     let content = std::fs::read_to_string(path)
         .with_context(|| format!("failed to read file: {}", path_str))?;
@@ -121,7 +144,8 @@ pub fn index_file(conn: &Connection, path: &Path, category: &str) -> Result<usiz
         return Ok(0);
     }
 
-    let hash = content_hash(&content);
+    let strategy = chunker::ChunkStrategy::for_path(path);
+    let hash = chunked_hash(&content, strategy, options);
 
     // Check if file is unchanged (compare hash of first chunk)
     let existing_hash: Option<String> = conn
@@ -142,7 +166,7 @@ pub fn index_file(conn: &Connection, path: &Path, category: &str) -> Result<usiz
     unindex_file(conn, path)?;
 
     // Chunk the content
-    let chunks = chunker::chunk_markdown(&content, CHUNK_MAX_TOKENS);
+    let chunks = chunker::chunk_text(&content, strategy, options);
     let now = Local::now().to_rfc3339();
 
     for chunk in &chunks {
@@ -180,7 +204,8 @@ pub fn index_content(
         return Ok(0);
     }
 
-    let hash = content_hash(content);
+    let options = chunker::ChunkOptions::default();
+    let hash = chunked_hash(content, chunker::ChunkStrategy::Markdown, &options);
 
     let existing_hash: Option<String> = conn
         .query_row(
@@ -197,7 +222,7 @@ pub fn index_content(
 
     unindex_path(conn, virtual_path)?;
 
-    let chunks = chunker::chunk_markdown(content, CHUNK_MAX_TOKENS);
+    let chunks = chunker::chunk_text(content, chunker::ChunkStrategy::Markdown, &options);
     let now = Local::now().to_rfc3339();
 
     for chunk in &chunks {
@@ -220,16 +245,25 @@ pub fn index_content(
     Ok(chunks.len())
 }
 
-/// Remove a file from the index by path.
-pub fn unindex_file(conn: &Connection, path: &Path) -> Result<()> {
+/// Remove a file from the index by path. Returns the number of chunks removed.
+pub fn unindex_file(conn: &Connection, path: &Path) -> Result<usize> {
     let path_str = path.to_string_lossy().to_string();
     unindex_path(conn, &path_str)
 }
 
-/// Remove all chunks for a given path string.
-fn unindex_path(conn: &Connection, path: &str) -> Result<()> {
-    conn.execute("DELETE FROM indexed_docs WHERE path = ?1", params![path])?;
-    Ok(())
+/// Remove all chunks for a given path string (a file or virtual path).
+pub fn unindex_path(conn: &Connection, path: &str) -> Result<usize> {
+    let removed = conn.execute("DELETE FROM indexed_docs WHERE path = ?1", params![path])?;
+    Ok(removed)
+}
+
+/// List the distinct paths (files and virtual paths) in the index.
+pub fn indexed_paths(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT path FROM indexed_docs ORDER BY path")?;
+    let paths = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(paths)
 }
 
 /// Search documents using FTS5.
@@ -398,6 +432,40 @@ mod tests {
         assert_eq!(chunks2, 0);
     }
 
+    #[test]
+    fn index_rechunks_when_options_change() {
+        let conn = test_conn();
+        let dir = TempDir::new().unwrap();
+        let mut content = String::new();
+        for i in 0..20 {
+            use std::fmt::Write;
+            let _ = write!(content, "fn item_{i}() {{\n    work({i});\n}}\n\n");
+        }
+        let path = write_temp_file(dir.path(), "lib.rs", &content);
+
+        let coarse = index_file(&conn, &path, "document").unwrap();
+        assert_eq!(coarse, 1);
+        assert_eq!(index_file(&conn, &path, "document").unwrap(), 0);
+
+        let fine = chunker::ChunkOptions {
+            max_tokens: 20,
+            overlap_tokens: 0,
+        };
+        let chunks = index_file_with(&conn, &path, "document", &fine).unwrap();
+        assert!(chunks > 1);
+        assert_eq!(count_docs(&conn).unwrap(), chunks);
+        // Code is split between items, not inside them.
+        let first: String = conn
+            .query_row(
+                "SELECT content FROM indexed_docs WHERE chunk_index = 0",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(first.starts_with("fn item_0() {"));
+        assert!(first.ends_with('}'));
+    }
+
     #[test]
     fn index_changed_file_re_indexes() {
         let conn = test_conn();
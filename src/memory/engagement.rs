@@ -0,0 +1,442 @@
+//! Group activity and engagement analytics.
+//!
+//! Aggregates the message index in `social.db` into per-group activity:
+//! an hour-of-day × weekday heatmap, daily counts and top contributors.
+//! The agent's own replies are not indexed, so engagement (messages answered
+//! / seen) comes from hourly counters in `group_engagement`, which the Nostr
+//! channel bumps for every group message it sees and every reply it sends.
+//!
+//! Used by `snowclaw stats --engagement` and the `group_activity` tool.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+
+use super::social;
+
+/// Contributors kept per group.
+pub const TOP_CONTRIBUTORS: usize = 5;
+
+// ── Data structures ──────────────────────────────────────────────
+
+/// Range and scope of an engagement query.
+#[derive(Debug, Clone)]
+pub struct EngagementFilter {
+    /// Unix seconds, inclusive.
+    pub since: i64,
+    /// Unix seconds, inclusive.
+    pub until: i64,
+    /// Only this group (without `#`).
+    pub group: Option<String>,
+    /// Zone of the heatmap hours and daily buckets.
+    pub tz: Tz,
+}
+
+/// Messages of one sender in a group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Contributor {
+    pub sender_hex: String,
+    pub name: String,
+    pub messages: u64,
+}
+
+/// Activity and engagement of one group over the filtered range.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupEngagement {
+    pub group_id: String,
+    /// Indexed messages.
+    pub messages: u64,
+    /// Indexed messages by weekday (Monday first) and local hour.
+    pub heatmap: [[u64; 24]; 7],
+    /// Indexed messages per local date.
+    pub daily: BTreeMap<NaiveDate, u64>,
+    /// Most active senders, busiest first.
+    pub top_contributors: Vec<Contributor>,
+    /// Group messages the agent saw (indexed or not).
+    pub seen: u64,
+    /// Replies the agent sent.
+    pub answered: u64,
+}
+
+impl GroupEngagement {
+    fn new(group_id: String) -> Self {
+        Self {
+            group_id,
+            messages: 0,
+            heatmap: [[0; 24]; 7],
+            daily: BTreeMap::new(),
+            top_contributors: Vec::new(),
+            seen: 0,
+            answered: 0,
+        }
+    }
+
+    /// Replies per message seen; `None` before anything was seen.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn engagement_ratio(&self) -> Option<f64> {
+        (self.seen > 0).then(|| self.answered as f64 / self.seen as f64)
+    }
+
+    /// Messages per local hour of day, summed over weekdays.
+    pub fn hourly(&self) -> [u64; 24] {
+        let mut hours = [0; 24];
+        for day in &self.heatmap {
+            for (hour, count) in day.iter().enumerate() {
+                hours[hour] += count;
+            }
+        }
+        hours
+    }
+
+    /// Up to `n` busiest local hours, busiest first; quiet hours are left out.
+    pub fn peak_hours(&self, n: usize) -> Vec<u32> {
+        let mut hours: Vec<(u32, u64)> = (0u32..)
+            .zip(self.hourly())
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        hours.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hours.into_iter().take(n).map(|(hour, _)| hour).collect()
+    }
+
+    /// Busiest weekday, if any message was indexed.
+    pub fn peak_weekday(&self) -> Option<Weekday> {
+        let (index, count) = self
+            .heatmap
+            .iter()
+            .map(|day| day.iter().sum::<u64>())
+            .enumerate()
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))?;
+        (count > 0).then(|| weekday(index))
+    }
+}
+
+/// Weekday of a heatmap row (0 = Monday).
+pub fn weekday(index: usize) -> Weekday {
+    Weekday::try_from(u8::try_from(index % 7).unwrap_or(0)).unwrap_or(Weekday::Mon)
+}
+
+// ── Schema ───────────────────────────────────────────────────────
+
+/// Create the hourly engagement counters.
+pub fn create_engagement_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS group_engagement (
+            group_id TEXT NOT NULL,
+            hour INTEGER NOT NULL,
+            seen INTEGER NOT NULL DEFAULT 0,
+            answered INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (group_id, hour)
+        );",
+    )
+    .context("failed to create group_engagement table")?;
+    Ok(())
+}
+
+// ── Counters ─────────────────────────────────────────────────────
+
+fn bump(conn: &Connection, group_id: &str, at: i64, seen: u64, answered: u64) -> Result<()> {
+    conn.execute(
+        "INSERT INTO group_engagement (group_id, hour, seen, answered)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(group_id, hour) DO UPDATE SET
+            seen = seen + excluded.seen,
+            answered = answered + excluded.answered",
+        params![
+            group_id,
+            at - at.rem_euclid(3600),
+            i64::try_from(seen).unwrap_or(i64::MAX),
+            i64::try_from(answered).unwrap_or(i64::MAX),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Count a group message seen at `at` (unix seconds).
+pub fn record_seen(conn: &Connection, group_id: &str, at: i64) -> Result<()> {
+    bump(conn, group_id, at, 1, 0)
+}
+
+/// Count a reply sent to a group at `at` (unix seconds).
+pub fn record_answered(conn: &Connection, group_id: &str, at: i64) -> Result<()> {
+    bump(conn, group_id, at, 0, 1)
+}
+
+// ── Aggregation ──────────────────────────────────────────────────
+
+/// Activity and engagement per group in the filter range, most active first.
+/// Groups with counters but no indexed messages are included.
+pub fn group_engagement(
+    conn: &Connection,
+    filter: &EngagementFilter,
+) -> Result<Vec<GroupEngagement>> {
+    let mut groups: BTreeMap<String, GroupEngagement> = BTreeMap::new();
+    let mut senders: HashMap<String, HashMap<String, u64>> = HashMap::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT group_id, sender_hex, created_at FROM message_index
+         WHERE group_id IS NOT NULL AND created_at >= ?1 AND created_at <= ?2
+           AND (?3 IS NULL OR group_id = ?3)",
+    )?;
+    let rows = stmt.query_map(params![filter.since, filter.until, filter.group], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, i64>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (group_id, sender_hex, created_at) = row?;
+        let Some(local) = DateTime::<Utc>::from_timestamp(created_at, 0) else {
+            continue;
+        };
+        let local = local.with_timezone(&filter.tz);
+        let group = groups
+            .entry(group_id.clone())
+            .or_insert_with(|| GroupEngagement::new(group_id.clone()));
+        group.messages += 1;
+        group.heatmap[local.weekday().num_days_from_monday() as usize][local.hour() as usize] += 1;
+        *group.daily.entry(local.date_naive()).or_default() += 1;
+        *senders
+            .entry(group_id)
+            .or_default()
+            .entry(sender_hex)
+            .or_default() += 1;
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT group_id, SUM(seen), SUM(answered) FROM group_engagement
+         WHERE hour >= ?1 AND hour <= ?2 AND (?3 IS NULL OR group_id = ?3)
+         GROUP BY group_id",
+    )?;
+    let since_hour = filter.since - filter.since.rem_euclid(3600);
+    let rows = stmt.query_map(params![since_hour, filter.until, filter.group], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, i64>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (group_id, seen, answered) = row?;
+        let group = groups
+            .entry(group_id.clone())
+            .or_insert_with(|| GroupEngagement::new(group_id));
+        group.seen = u64::try_from(seen).unwrap_or(0);
+        group.answered = u64::try_from(answered).unwrap_or(0);
+    }
+
+    let mut result: Vec<GroupEngagement> = groups.into_values().collect();
+    for group in &mut result {
+        let Some(counts) = senders.remove(&group.group_id) else {
+            continue;
+        };
+        let mut counts: Vec<(String, u64)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        group.top_contributors = counts
+            .into_iter()
+            .take(TOP_CONTRIBUTORS)
+            .map(|(sender_hex, messages)| Contributor {
+                name: social::display_name_or_short(conn, &sender_hex),
+                sender_hex,
+                messages,
+            })
+            .collect();
+    }
+    result.sort_by(|a, b| {
+        b.messages
+            .cmp(&a.messages)
+            .then(b.seen.cmp(&a.seen))
+            .then_with(|| a.group_id.cmp(&b.group_id))
+    });
+    Ok(result)
+}
+
+// ── Agent summary ────────────────────────────────────────────────
+
+/// Short activity summary of a group for the agent, relative to `now`:
+/// busiest hours and day, how busy the current hour usually is, top
+/// contributors and the agent's reply rate. `days` is the length of the
+/// range the stats cover.
+pub fn summary(group: &GroupEngagement, now: DateTime<Tz>, days: u32) -> String {
+    let mut out = format!(
+        "#{}: {} indexed message(s) in the last {days} day(s)",
+        group.group_id, group.messages
+    );
+    if group.messages > 0 {
+        let peaks: Vec<String> = group
+            .peak_hours(3)
+            .iter()
+            .map(|hour| format!("{hour:02}:00"))
+            .collect();
+        let _ = write!(
+            out,
+            "\n  busiest hours ({}): {}",
+            now.timezone(),
+            peaks.join(", ")
+        );
+        if let Some(day) = group.peak_weekday() {
+            let _ = write!(out, "; busiest day: {day}");
+        }
+        let now_hour =
+            group.heatmap[now.weekday().num_days_from_monday() as usize][now.hour() as usize];
+        #[allow(clippy::cast_precision_loss)]
+        let per_week = now_hour as f64 * 7.0 / f64::from(days.max(1));
+        let _ = write!(
+            out,
+            "\n  this hour ({} {:02}:00): {per_week:.1} message(s) in a typical week",
+            now.weekday(),
+            now.hour()
+        );
+        if let Some(last) = group.daily.keys().next_back() {
+            let _ = write!(out, "; last active {last}");
+        }
+    }
+    if !group.top_contributors.is_empty() {
+        let top: Vec<String> = group
+            .top_contributors
+            .iter()
+            .map(|c| format!("{} ({})", c.name, c.messages))
+            .collect();
+        let _ = write!(out, "\n  top contributors: {}", top.join(", "));
+    }
+    match group.engagement_ratio() {
+        Some(ratio) => {
+            let _ = write!(
+                out,
+                "\n  you answered {} of {} message(s) seen ({:.0}%)",
+                group.answered,
+                group.seen,
+                ratio * 100.0
+            );
+        }
+        None if group.answered > 0 => {
+            let _ = write!(out, "\n  you sent {} message(s)", group.answered);
+        }
+        None => {}
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::message_index::{create_message_tables, index_message, IndexableMessage};
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        social::create_social_tables(&conn).unwrap();
+        create_message_tables(&conn).unwrap();
+        create_engagement_tables(&conn).unwrap();
+        conn
+    }
+
+    fn message(conn: &Connection, id: &str, sender: &str, group: Option<&str>, at: i64) {
+        index_message(
+            conn,
+            &IndexableMessage {
+                event_id: id.into(),
+                sender_hex: sender.into(),
+                group_id: group.map(str::to_string),
+                content: "a message long enough to be indexed".into(),
+                created_at: at,
+                kind: 9,
+            },
+        )
+        .unwrap();
+    }
+
+    fn filter(group: Option<&str>, tz: Tz) -> EngagementFilter {
+        EngagementFilter {
+            since: 0,
+            until: i64::MAX,
+            group: group.map(str::to_string),
+            tz,
+        }
+    }
+
+    // 2026-03-02 is a Monday.
+    const MONDAY_14H: i64 = 1_772_460_000;
+
+    #[test]
+    fn aggregates_heatmap_daily_and_contributors() {
+        let conn = test_conn();
+        message(&conn, "1", "alice", Some("dev"), MONDAY_14H);
+        message(&conn, "2", "alice", Some("dev"), MONDAY_14H + 600);
+        message(&conn, "3", "bob", Some("dev"), MONDAY_14H + 86_400);
+        message(&conn, "4", "carol", Some("ops"), MONDAY_14H);
+        message(&conn, "5", "dave", None, MONDAY_14H);
+
+        let groups = group_engagement(&conn, &filter(None, Tz::UTC)).unwrap();
+        assert_eq!(groups.len(), 2);
+        let dev = &groups[0];
+        assert_eq!(dev.group_id, "dev");
+        assert_eq!(dev.messages, 3);
+        assert_eq!(dev.heatmap[0][14], 2);
+        assert_eq!(dev.heatmap[1][14], 1);
+        assert_eq!(dev.daily.len(), 2);
+        assert_eq!(dev.top_contributors[0].sender_hex, "alice");
+        assert_eq!(dev.top_contributors[0].messages, 2);
+        assert_eq!(dev.peak_hours(3), vec![14]);
+        assert_eq!(dev.peak_weekday(), Some(Weekday::Mon));
+
+        let only_ops = group_engagement(&conn, &filter(Some("ops"), Tz::UTC)).unwrap();
+        assert_eq!(only_ops.len(), 1);
+        assert_eq!(only_ops[0].group_id, "ops");
+    }
+
+    #[test]
+    fn heatmap_uses_the_filter_timezone() {
+        let conn = test_conn();
+        message(&conn, "1", "alice", Some("dev"), MONDAY_14H);
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let groups = group_engagement(&conn, &filter(None, tz)).unwrap();
+        assert_eq!(groups[0].heatmap[0][9], 1);
+    }
+
+    #[test]
+    fn counters_give_the_engagement_ratio() {
+        let conn = test_conn();
+        for offset in 0..4 {
+            record_seen(&conn, "dev", MONDAY_14H + offset * 60).unwrap();
+        }
+        record_answered(&conn, "dev", MONDAY_14H + 300).unwrap();
+        record_seen(&conn, "dev", MONDAY_14H - 7200).unwrap();
+
+        let hours: i64 = conn
+            .query_row("SELECT COUNT(*) FROM group_engagement", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(hours, 2);
+
+        let mut range = filter(None, Tz::UTC);
+        range.since = MONDAY_14H;
+        let groups = group_engagement(&conn, &range).unwrap();
+        assert_eq!(groups[0].messages, 0);
+        assert_eq!((groups[0].seen, groups[0].answered), (4, 1));
+        assert_eq!(groups[0].engagement_ratio(), Some(0.25));
+    }
+
+    #[test]
+    fn summary_mentions_peaks_and_reply_rate() {
+        let conn = test_conn();
+        message(&conn, "1", "alice", Some("dev"), MONDAY_14H);
+        record_seen(&conn, "dev", MONDAY_14H).unwrap();
+        record_answered(&conn, "dev", MONDAY_14H).unwrap();
+        let groups = group_engagement(&conn, &filter(None, Tz::UTC)).unwrap();
+
+        let now = DateTime::from_timestamp(MONDAY_14H + 7 * 86_400, 0)
+            .unwrap()
+            .with_timezone(&Tz::UTC);
+        let text = summary(&groups[0], now, 7);
+        assert!(text.starts_with("#dev: 1 indexed message(s) in the last 7 day(s)"));
+        assert!(text.contains("busiest hours (UTC): 14:00; busiest day: Mon"));
+        assert!(text.contains("this hour (Mon 14:00): 1.0 message(s) in a typical week"));
+        assert!(text.contains("top contributors: alice"));
+        assert!(text.contains("you answered 1 of 1 message(s) seen (100%)"));
+    }
+}
@@ -2,22 +2,67 @@
 //!
 //! Resolves configured glob patterns against the workspace directory and
 //! indexes matching files using `doc_index`. Hash-based change detection
-//! (already in `doc_index`) avoids re-indexing unchanged files.
+//! (already in `doc_index`) avoids re-indexing unchanged files, and chunks
+//! of files that were deleted or no longer match are removed.
+//!
+//! [`FileIndexer::watch`] keeps the index current between full passes: it
+//! watches the directories the patterns point into and re-indexes only the
+//! files that changed, reporting pending changes under the `doc_index`
+//! health component.
 
 use anyhow::Result;
+use chrono::Utc;
+use notify::{EventKind, RecursiveMode, Watcher};
 use parking_lot::Mutex as ParkingMutex;
 use rusqlite::Connection;
-use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
+use super::chunker::ChunkOptions;
 use super::doc_index;
 
+/// Health registry component for the index watcher.
+pub const HEALTH_COMPONENT: &str = "doc_index";
+
+/// Quiet period after the last event for a file before it is re-indexed,
+/// so an editor's write/rename sequence costs one re-index.
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Longest a file that keeps changing waits before it is re-indexed anyway.
+const WATCH_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// How often pending changes are checked and lag is reported.
+const WATCH_TICK: Duration = Duration::from_secs(1);
+
+const MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Result of re-indexing a set of changed files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ChangeReport {
+    /// Files whose content changed and were re-chunked
+    pub reindexed: usize,
+    /// Chunks written for those files
+    pub chunks: usize,
+    /// Files dropped from the index because they were deleted
+    pub removed: usize,
+    /// Files that could not be read or indexed
+    pub failed: usize,
+}
+
 /// Indexes configured file patterns into the document search tables.
 pub struct FileIndexer {
     conn: Arc<ParkingMutex<Connection>>,
     workspace_dir: PathBuf,
     patterns: Vec<String>,
+    matchers: Vec<glob::Pattern>,
+    chunking: ChunkOptions,
 }
 
 impl FileIndexer {
@@ -30,14 +75,30 @@ impl FileIndexer {
         workspace_dir: &Path,
         patterns: Vec<String>,
     ) -> Self {
+        // Invalid patterns are reported by `resolve_patterns`.
+        let matchers = patterns
+            .iter()
+            .filter_map(|pattern| {
+                glob::Pattern::new(&workspace_dir.join(pattern).to_string_lossy()).ok()
+            })
+            .collect();
         Self {
             conn,
             workspace_dir: workspace_dir.to_path_buf(),
             patterns,
+            matchers,
+            chunking: ChunkOptions::default(),
         }
     }
 
-    /// Index all files matching the configured patterns.
+    /// Use `chunking` for chunk size and overlap instead of the defaults.
+    pub fn with_chunking(mut self, chunking: ChunkOptions) -> Self {
+        self.chunking = chunking;
+        self
+    }
+
+    /// Index all files matching the configured patterns and drop chunks of
+    /// files that are gone or no longer match.
     ///
     /// Returns total number of chunks indexed (0 for unchanged files).
     /// Errors on individual files are logged and skipped.
@@ -47,21 +108,20 @@ impl FileIndexer {
         }
 
         let files = self.resolve_patterns();
-        if files.is_empty() {
-            debug!("No files matched indexed_paths patterns");
-            return Ok(0);
-        }
-
-        let db = self.conn.lock();
         let mut total_chunks = 0;
 
-        for path in &files {
-            match doc_index::index_file(&db, path, "document") {
-                Ok(chunks) => {
-                    total_chunks += chunks;
-                }
-                Err(e) => {
-                    warn!(path = %path.display(), "Failed to index file: {e}");
+        if files.is_empty() {
+            debug!("No files matched indexed_paths patterns");
+        } else {
+            let db = self.conn.lock();
+            for path in &files {
+                match doc_index::index_file_with(&db, path, "document", &self.chunking) {
+                    Ok(chunks) => {
+                        total_chunks += chunks;
+                    }
+                    Err(e) => {
+                        warn!(path = %path.display(), "Failed to index file: {e}");
+                    }
                 }
             }
         }
@@ -74,9 +134,237 @@ impl FileIndexer {
             );
         }
 
+        let removed = self.remove_stale()?;
+        if removed > 0 {
+            info!(
+                files = removed,
+                "Removed stale files from the document index"
+            );
+        }
+
         Ok(total_chunks)
     }
 
+    /// Whether `path` matches one of the configured patterns.
+    pub fn matches(&self, path: &Path) -> bool {
+        self.matchers
+            .iter()
+            .any(|pattern| pattern.matches_path_with(path, MATCH_OPTIONS))
+    }
+
+    /// Re-index the given changed paths. Paths that do not match a pattern
+    /// are ignored; matching paths that no longer exist are unindexed.
+    pub fn index_changed(&self, paths: &[PathBuf]) -> ChangeReport {
+        let mut report = ChangeReport::default();
+        let db = self.conn.lock();
+
+        for path in paths.iter().filter(|path| self.matches(path)) {
+            if path.is_file() {
+                match doc_index::index_file_with(&db, path, "document", &self.chunking) {
+                    Ok(0) => {}
+                    Ok(chunks) => {
+                        report.reindexed += 1;
+                        report.chunks += chunks;
+                    }
+                    Err(e) => {
+                        report.failed += 1;
+                        warn!(path = %path.display(), "Failed to index file: {e}");
+                    }
+                }
+            } else if !path.exists() {
+                match doc_index::unindex_file(&db, path) {
+                    Ok(0) => {}
+                    Ok(_) => report.removed += 1,
+                    Err(e) => {
+                        report.failed += 1;
+                        warn!(path = %path.display(), "Failed to unindex file: {e}");
+                    }
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Remove indexed files under the workspace that were deleted or no
+    /// longer match a pattern. Returns the number of files removed.
+    ///
+    /// Virtual paths from `doc_index::index_content` are left alone.
+    pub fn remove_stale(&self) -> Result<usize> {
+        let db = self.conn.lock();
+        let mut removed = 0;
+
+        for path in doc_index::indexed_paths(&db)? {
+            let path = Path::new(&path);
+            if !path.starts_with(&self.workspace_dir) || (path.is_file() && self.matches(path)) {
+                continue;
+            }
+            if doc_index::unindex_file(&db, path)? > 0 {
+                debug!(path = %path.display(), "Removed stale file from the index");
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Directories to watch for the configured patterns.
+    ///
+    /// Each pattern is watched at its literal prefix (`rooms` for
+    /// `"rooms/*.md"`), recursively when the rest spans directories. A
+    /// prefix that does not exist yet is replaced by its nearest existing
+    /// ancestor, watched recursively so the directory is seen when created.
+    pub fn watch_roots(&self) -> Vec<(PathBuf, RecursiveMode)> {
+        let mut roots: Vec<(PathBuf, RecursiveMode)> = Vec::new();
+
+        for pattern in &self.patterns {
+            let components: Vec<Component<'_>> = Path::new(pattern).components().collect();
+            let literal = components
+                .iter()
+                .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+                .count();
+            // A fully literal pattern names a file: watch its directory.
+            let literal = literal.min(components.len().saturating_sub(1));
+            let rest = &components[literal..];
+
+            let mut base = self.workspace_dir.clone();
+            base.extend(&components[..literal]);
+            let mut recursive = rest.len() > 1 || rest.iter().any(|c| c.as_os_str() == "**");
+            while !base.is_dir() && base != self.workspace_dir && base.pop() {
+                recursive = true;
+            }
+            if !base.is_dir() {
+                continue;
+            }
+
+            let mode = if recursive {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            match roots.iter_mut().find(|(root, _)| *root == base) {
+                Some(existing) if mode == RecursiveMode::Recursive => existing.1 = mode,
+                Some(_) => {}
+                None => roots.push((base, mode)),
+            }
+        }
+
+        // Drop roots already covered by a recursive ancestor.
+        let covered: Vec<bool> = roots
+            .iter()
+            .map(|(root, _)| {
+                roots.iter().any(|(other, mode)| {
+                    *mode == RecursiveMode::Recursive && other != root && root.starts_with(other)
+                })
+            })
+            .collect();
+        let mut covered = covered.into_iter();
+        roots.retain(|_| !covered.next().unwrap_or(false));
+        roots.sort_by(|a, b| a.0.cmp(&b.0));
+        roots
+    }
+
+    /// Watch the configured patterns and re-index files as they change.
+    ///
+    /// Changed files are re-indexed once they have been quiet for a moment;
+    /// deleted ones are dropped from the index. Runs until the watcher
+    /// cannot be started or its event channel closes. The number of
+    /// pending files and the age of the oldest pending change are reported
+    /// under the [`HEALTH_COMPONENT`] health component.
+    pub async fn watch(self) {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<PathBuf>();
+        let watcher =
+            notify::recommended_watcher(
+                move |result: notify::Result<notify::Event>| match result {
+                    Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                        for path in event.paths {
+                            let _ = tx.send(path);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => debug!("Index watcher event error: {e}"),
+                },
+            );
+        let mut watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                warn!("Failed to start the indexed_paths watcher: {e}");
+                crate::health::mark_component_error(HEALTH_COMPONENT, e);
+                return;
+            }
+        };
+
+        let mut watching = 0;
+        for (root, mode) in self.watch_roots() {
+            match watcher.watch(&root, mode) {
+                Ok(()) => watching += 1,
+                Err(e) => warn!(path = %root.display(), "Failed to watch indexed path: {e}"),
+            }
+        }
+        if watching == 0 {
+            crate::health::mark_component_error(HEALTH_COMPONENT, "no indexed path to watch");
+            return;
+        }
+        info!(
+            roots = watching,
+            "Watching indexed_paths for incremental re-indexing"
+        );
+
+        let mut pending = PendingChanges::default();
+        let mut last_indexed_at: Option<String> = None;
+        let mut last_failed = 0;
+        let mut tick = tokio::time::interval(WATCH_TICK);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            tokio::select! {
+                path = rx.recv() => {
+                    let Some(path) = path else { break };
+                    if self.matches(&path) {
+                        pending.touch(path, Instant::now());
+                    }
+                }
+                _ = tick.tick() => {
+                    let ready = pending.take_ready(Instant::now());
+                    if !ready.is_empty() {
+                        let report = self.index_changed(&ready);
+                        if report.reindexed + report.removed > 0 {
+                            info!(
+                                reindexed = report.reindexed,
+                                chunks = report.chunks,
+                                removed = report.removed,
+                                "Incremental re-index"
+                            );
+                        }
+                        last_indexed_at = Some(Utc::now().to_rfc3339());
+                        last_failed = report.failed;
+                    }
+
+                    if last_failed > 0 {
+                        crate::health::mark_component_error(
+                            HEALTH_COMPONENT,
+                            format!("{last_failed} file(s) failed to index"),
+                        );
+                    } else {
+                        crate::health::mark_component_ok(HEALTH_COMPONENT);
+                    }
+                    crate::health::set_component_detail(
+                        HEALTH_COMPONENT,
+                        serde_json::json!({
+                            "watched_roots": watching,
+                            "pending_files": pending.len(),
+                            "lag_seconds": pending.lag(Instant::now()).as_secs(),
+                            "last_indexed_at": last_indexed_at,
+                        }),
+                    );
+                }
+            }
+        }
+
+        drop(watcher);
+        warn!("indexed_paths watcher stopped");
+    }
+
     /// Resolve all glob patterns against the workspace directory.
     fn resolve_patterns(&self) -> Vec<PathBuf> {
         let mut results = Vec::new();
@@ -111,6 +399,53 @@ impl FileIndexer {
     }
 }
 
+/// Changed paths waiting to be re-indexed, with the time of their first
+/// and latest event.
+#[derive(Default)]
+struct PendingChanges {
+    paths: HashMap<PathBuf, (Instant, Instant)>,
+}
+
+impl PendingChanges {
+    fn touch(&mut self, path: PathBuf, now: Instant) {
+        self.paths
+            .entry(path)
+            .and_modify(|(_, last)| *last = now)
+            .or_insert((now, now));
+    }
+
+    /// Take the paths that have been quiet for the debounce period, or
+    /// have waited the maximum delay.
+    fn take_ready(&mut self, now: Instant) -> Vec<PathBuf> {
+        let ready: Vec<PathBuf> = self
+            .paths
+            .iter()
+            .filter(|(_, (first, last))| {
+                now.duration_since(*last) >= WATCH_DEBOUNCE
+                    || now.duration_since(*first) >= WATCH_MAX_DELAY
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &ready {
+            self.paths.remove(path);
+        }
+        ready
+    }
+
+    fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Age of the oldest change not yet indexed.
+    fn lag(&self, now: Instant) -> Duration {
+        self.paths
+            .values()
+            .map(|(first, _)| now.duration_since(*first))
+            .max()
+            .unwrap_or_default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -275,4 +610,142 @@ mod tests {
         let db = conn.lock();
         assert_eq!(doc_index::count_docs(&db).unwrap(), 2);
     }
+
+    #[test]
+    fn index_changed_reindexes_changed_and_drops_deleted() {
+        let dir = TempDir::new().unwrap();
+        let kept = write_file(dir.path(), "kept.md", "Kept file content.");
+        let edited = write_file(dir.path(), "edited.md", "Original wording.");
+        let deleted = write_file(dir.path(), "deleted.md", "Soon gone plugh.");
+        let ignored = write_file(dir.path(), "notes.txt", "Not a pattern match.");
+
+        let conn = test_conn();
+        let indexer = FileIndexer::new(conn.clone(), dir.path(), vec!["*.md".to_string()]);
+        indexer.index_configured_files().unwrap();
+
+        std::fs::write(&edited, "Revised wording.").unwrap();
+        std::fs::remove_file(&deleted).unwrap();
+        let report = indexer.index_changed(&[kept, edited, deleted, ignored]);
+
+        assert_eq!(
+            report,
+            ChangeReport {
+                reindexed: 1,
+                chunks: 1,
+                removed: 1,
+                failed: 0,
+            }
+        );
+        let db = conn.lock();
+        assert_eq!(doc_index::count_docs(&db).unwrap(), 2);
+        assert_eq!(doc_index::search_docs(&db, "Revised", 10).unwrap().len(), 1);
+        assert!(doc_index::search_docs(&db, "plugh", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn full_pass_removes_stale_files_but_keeps_virtual_paths() {
+        let dir = TempDir::new().unwrap();
+        let deleted = write_file(dir.path(), "rooms/old.md", "Old room notes.");
+        write_file(dir.path(), "rooms/dev.md", "Development room notes.");
+        write_file(dir.path(), "MEMORY.md", "Agent memory content.");
+
+        let conn = test_conn();
+        doc_index::index_content(&conn.lock(), "room:general", "Room description.", "room")
+            .unwrap();
+        FileIndexer::new(
+            conn.clone(),
+            dir.path(),
+            vec!["rooms/*.md".to_string(), "MEMORY.md".to_string()],
+        )
+        .index_configured_files()
+        .unwrap();
+
+        // One file deleted, one pattern dropped from the config.
+        std::fs::remove_file(&deleted).unwrap();
+        let indexer = FileIndexer::new(conn.clone(), dir.path(), vec!["rooms/*.md".to_string()]);
+        indexer.index_configured_files().unwrap();
+
+        let db = conn.lock();
+        let paths = doc_index::indexed_paths(&db).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                dir.path()
+                    .join("rooms/dev.md")
+                    .to_string_lossy()
+                    .to_string(),
+                "room:general".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn watch_roots_follow_pattern_prefixes() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("rooms")).unwrap();
+        std::fs::create_dir_all(dir.path().join("docs/guides")).unwrap();
+
+        let indexer = FileIndexer::new(
+            test_conn(),
+            dir.path(),
+            vec![
+                "MEMORY.md".to_string(),
+                "rooms/*.md".to_string(),
+                "docs/**/*.md".to_string(),
+                "docs/guides/*.md".to_string(),
+            ],
+        );
+        assert_eq!(
+            indexer.watch_roots(),
+            vec![
+                (dir.path().to_path_buf(), RecursiveMode::NonRecursive),
+                (dir.path().join("docs"), RecursiveMode::Recursive),
+                (dir.path().join("rooms"), RecursiveMode::NonRecursive),
+            ]
+        );
+
+        // A missing directory is watched through its nearest ancestor.
+        let indexer = FileIndexer::new(test_conn(), dir.path(), vec!["notes/*.md".to_string()]);
+        assert_eq!(
+            indexer.watch_roots(),
+            vec![(dir.path().to_path_buf(), RecursiveMode::Recursive)]
+        );
+    }
+
+    #[test]
+    fn matches_respects_directory_separators() {
+        let dir = TempDir::new().unwrap();
+        let indexer = FileIndexer::new(test_conn(), dir.path(), vec!["*.md".to_string()]);
+
+        assert!(indexer.matches(&dir.path().join("README.md")));
+        assert!(!indexer.matches(&dir.path().join("rooms/dev.md")));
+        assert!(!indexer.matches(&dir.path().join("notes.txt")));
+    }
+
+    #[test]
+    fn pending_changes_wait_for_quiet_period() {
+        let start = Instant::now();
+        let mut pending = PendingChanges::default();
+        pending.touch(PathBuf::from("/w/a.md"), start);
+        pending.touch(PathBuf::from("/w/b.md"), start);
+
+        // b.md keeps changing; a.md is quiet long enough.
+        let later = start + Duration::from_secs(1);
+        pending.touch(PathBuf::from("/w/b.md"), later);
+        assert!(pending.take_ready(later).is_empty());
+        assert_eq!(pending.lag(later), Duration::from_secs(1));
+
+        let ready = pending.take_ready(start + WATCH_DEBOUNCE);
+        assert_eq!(ready, vec![PathBuf::from("/w/a.md")]);
+        assert_eq!(pending.len(), 1);
+
+        // A file that never goes quiet is indexed after the maximum delay.
+        let mut now = start + WATCH_DEBOUNCE;
+        while now < start + WATCH_MAX_DELAY {
+            pending.touch(PathBuf::from("/w/b.md"), now);
+            now += Duration::from_secs(1);
+        }
+        assert_eq!(pending.take_ready(now), vec![PathBuf::from("/w/b.md")]);
+        assert_eq!(pending.lag(now), Duration::ZERO);
+    }
 }
@@ -1,7 +1,7 @@
 use crate::config::MemoryConfig;
 use anyhow::Result;
 use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -305,7 +305,7 @@ fn prune_conversation_rows(workspace_dir: &Path, retention_days: u32) -> Result<
         return Ok(0);
     }
 
-    let conn = Connection::open(db_path)?;
+    let conn = crate::security::db_encryption::open(&db_path)?;
     // Use WAL so hygiene pruning doesn't block agent reads
     conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")?;
     let cutoff = (Local::now() - Duration::days(i64::from(retention_days))).to_rfc3339();
@@ -379,6 +379,7 @@ fn split_name(filename: &str) -> (&str, &str) {
 mod tests {
     use super::*;
     use crate::memory::{Memory, MemoryCategory, SqliteMemory};
+    use rusqlite::Connection;
     use tempfile::TempDir;
 
     fn default_cfg() -> MemoryConfig {
@@ -0,0 +1,286 @@
+//! Shared-link records for the document index.
+//!
+//! Pages fetched from links shared in Nostr groups are indexed into
+//! `indexed_docs` under their URL, with category `link`. The
+//! `indexed_links` table keeps where each URL came from (the sharing
+//! event, group and sender) and how the fetch went, so a URL is fetched
+//! once and a search hit can be traced back to the message that shared it.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::doc_index;
+
+/// `indexed_docs` category of fetched link pages.
+pub const LINK_CATEGORY: &str = "link";
+
+// ── Data structures ──────────────────────────────────────────────
+
+/// A URL found in a group message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedLink {
+    pub url: String,
+    pub event_id: String,
+    pub group_id: String,
+    pub sender_hex: String,
+    pub shared_at: i64,
+}
+
+/// Stored outcome of fetching a shared link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkRecord {
+    pub link: SharedLink,
+    pub title: Option<String>,
+    /// `indexed` or `failed`
+    pub status: String,
+    pub error: Option<String>,
+    pub chunks: usize,
+    pub fetched_at: i64,
+}
+
+// ── Schema ───────────────────────────────────────────────────────
+
+/// Create the shared-link table in the given connection.
+pub fn create_link_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS indexed_links (
+            url TEXT PRIMARY KEY,
+            event_id TEXT NOT NULL,
+            group_id TEXT NOT NULL,
+            sender_hex TEXT NOT NULL,
+            shared_at INTEGER NOT NULL,
+            title TEXT,
+            status TEXT NOT NULL,
+            error TEXT,
+            chunks INTEGER NOT NULL DEFAULT 0,
+            fetched_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_links_event ON indexed_links(event_id);",
+    )
+    .context("failed to create indexed_links table")?;
+    Ok(())
+}
+
+// ── Operations ───────────────────────────────────────────────────
+
+/// Whether `url` was already fetched, successfully or not.
+pub fn is_known(conn: &Connection, url: &str) -> Result<bool> {
+    let known = conn
+        .query_row(
+            "SELECT 1 FROM indexed_links WHERE url = ?1",
+            params![url],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    Ok(known)
+}
+
+/// Index the extracted text of a shared page and record its source.
+///
+/// The indexed content starts with the page title, URL and the sharing
+/// message, so a hit carries its attribution. Returns the number of
+/// chunks indexed.
+pub fn index_link(
+    conn: &Connection,
+    link: &SharedLink,
+    title: Option<&str>,
+    text: &str,
+) -> Result<usize> {
+    let heading = title.filter(|t| !t.trim().is_empty()).unwrap_or(&link.url);
+    let sender = &link.sender_hex[..8.min(link.sender_hex.len())];
+    let content = format!(
+        "# {}\n\n{}\nShared in #{} by {sender} (event {})\n\n{}",
+        heading.trim(),
+        link.url,
+        link.group_id,
+        link.event_id,
+        text.trim()
+    );
+    let chunks = doc_index::index_content(conn, &link.url, &content, LINK_CATEGORY)?;
+    record(conn, link, title, "indexed", None, chunks)?;
+    Ok(chunks)
+}
+
+/// Record a link that could not be fetched, so it is not retried.
+pub fn record_failure(conn: &Connection, link: &SharedLink, error: &str) -> Result<()> {
+    record(conn, link, None, "failed", Some(error), 0)
+}
+
+/// Remove the links `sender_hex` shared in the given events, with their
+/// indexed pages. Returns the number of links removed.
+pub fn remove_shared_in(
+    conn: &Connection,
+    event_ids: &[String],
+    sender_hex: &str,
+) -> Result<usize> {
+    let mut select =
+        conn.prepare("SELECT url FROM indexed_links WHERE event_id = ?1 AND sender_hex = ?2")?;
+    let mut removed = 0;
+    for event_id in event_ids {
+        let urls = select
+            .query_map(params![event_id, sender_hex], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for url in urls {
+            doc_index::unindex_path(conn, &url)?;
+            removed += conn.execute("DELETE FROM indexed_links WHERE url = ?1", params![url])?;
+        }
+    }
+    Ok(removed)
+}
+
+fn record(
+    conn: &Connection,
+    link: &SharedLink,
+    title: Option<&str>,
+    status: &str,
+    error: Option<&str>,
+    chunks: usize,
+) -> Result<()> {
+    #[allow(clippy::cast_possible_wrap)]
+    let chunks = chunks as i64;
+    conn.execute(
+        "INSERT OR REPLACE INTO indexed_links
+             (url, event_id, group_id, sender_hex, shared_at, title, status, error, chunks, fetched_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            link.url,
+            link.event_id,
+            link.group_id,
+            link.sender_hex,
+            link.shared_at,
+            title,
+            status,
+            error,
+            chunks,
+            Utc::now().timestamp()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Look up where an indexed URL came from.
+pub fn link_source(conn: &Connection, url: &str) -> Result<Option<LinkRecord>> {
+    let record = conn
+        .query_row(
+            "SELECT url, event_id, group_id, sender_hex, shared_at, title, status, error,
+                    chunks, fetched_at
+             FROM indexed_links WHERE url = ?1",
+            params![url],
+            |row| {
+                let chunks: i64 = row.get(8)?;
+                Ok(LinkRecord {
+                    link: SharedLink {
+                        url: row.get(0)?,
+                        event_id: row.get(1)?,
+                        group_id: row.get(2)?,
+                        sender_hex: row.get(3)?,
+                        shared_at: row.get(4)?,
+                    },
+                    title: row.get(5)?,
+                    status: row.get(6)?,
+                    error: row.get(7)?,
+                    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+                    chunks: chunks as usize,
+                    fetched_at: row.get(9)?,
+                })
+            },
+        )
+        .optional()?;
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        doc_index::create_doc_tables(&conn).unwrap();
+        create_link_tables(&conn).unwrap();
+        conn
+    }
+
+    fn link(url: &str) -> SharedLink {
+        SharedLink {
+            url: url.to_string(),
+            event_id: "ab".repeat(32),
+            group_id: "dev".to_string(),
+            sender_hex: "cd".repeat(32),
+            shared_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn indexed_page_is_searchable_with_attribution() {
+        let conn = test_conn();
+        let shared = link("https://example.com/post");
+
+        let chunks = index_link(
+            &conn,
+            &shared,
+            Some("Release notes"),
+            "The frobnicator now supports widgets.",
+        )
+        .unwrap();
+        assert_eq!(chunks, 1);
+
+        let hits = doc_index::search_docs(&conn, "frobnicator", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].path, "https://example.com/post");
+        assert_eq!(hits[0].category, LINK_CATEGORY);
+        assert!(hits[0].content.starts_with("# Release notes\n"));
+        assert!(hits[0].content.contains(&format!(
+            "Shared in #dev by cdcdcdcd (event {})",
+            "ab".repeat(32)
+        )));
+
+        let record = link_source(&conn, "https://example.com/post")
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.link, shared);
+        assert_eq!(record.status, "indexed");
+        assert_eq!(record.title.as_deref(), Some("Release notes"));
+        assert_eq!(record.chunks, 1);
+    }
+
+    #[test]
+    fn deleting_the_sharing_message_removes_the_page() {
+        let conn = test_conn();
+        let shared = link("https://example.com/post");
+        index_link(
+            &conn,
+            &shared,
+            None,
+            "The frobnicator now supports widgets.",
+        )
+        .unwrap();
+        let ids = vec![shared.event_id.clone()];
+
+        assert_eq!(remove_shared_in(&conn, &ids, &"ef".repeat(32)).unwrap(), 0);
+        assert_eq!(
+            remove_shared_in(&conn, &ids, &shared.sender_hex).unwrap(),
+            1
+        );
+
+        assert!(!is_known(&conn, &shared.url).unwrap());
+        assert_eq!(doc_index::count_docs(&conn).unwrap(), 0);
+    }
+
+    #[test]
+    fn failures_are_remembered() {
+        let conn = test_conn();
+        let shared = link("https://example.com/missing");
+        assert!(!is_known(&conn, &shared.url).unwrap());
+
+        record_failure(&conn, &shared, "HTTP 404 Not Found").unwrap();
+
+        assert!(is_known(&conn, &shared.url).unwrap());
+        let record = link_source(&conn, &shared.url).unwrap().unwrap();
+        assert_eq!(record.status, "failed");
+        assert_eq!(record.error.as_deref(), Some("HTTP 404 Not Found"));
+        assert_eq!(doc_index::count_docs(&conn).unwrap(), 0);
+    }
+}
@@ -229,6 +229,7 @@ impl Memory for MarkdownMemory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::memory::traits::{MemoryOp, NewMemory};
     use tempfile::TempDir;
 
     fn temp_workspace() -> (TempDir, MarkdownMemory) {
@@ -340,6 +341,24 @@ mod tests {
         assert!(!removed, "Markdown memory is append-only");
     }
 
+    #[tokio::test]
+    async fn markdown_transaction_falls_back_to_single_ops() {
+        let (_tmp, mem) = temp_workspace();
+        assert!(!mem.supports_transactions());
+
+        let applied = mem
+            .transaction(&[
+                MemoryOp::Store(NewMemory::new("a", "first fact", MemoryCategory::Core)),
+                MemoryOp::Forget("a".into()),
+                MemoryOp::Store(NewMemory::new("b", "second fact", MemoryCategory::Core)),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(applied, vec![true, false, true]);
+        assert_eq!(mem.recall("fact", 10, None).await.unwrap().len(), 2);
+    }
+
     #[tokio::test]
     async fn markdown_empty_recall() {
         let (_tmp, mem) = temp_workspace();
@@ -217,6 +217,95 @@ pub fn search_messages(conn: &Connection, query: &str, limit: usize) -> Result<V
     Ok(results)
 }
 
+/// Indexed messages of a group with `created_at` in `since..=until`:
+/// the newest `limit`, oldest first.
+pub fn group_messages(
+    conn: &Connection,
+    group_id: &str,
+    since: i64,
+    until: i64,
+    limit: usize,
+) -> Result<Vec<IndexableMessage>> {
+    #[allow(clippy::cast_possible_wrap)]
+    let limit_i64 = limit as i64;
+
+    let mut stmt = conn.prepare(
+        "SELECT event_id, sender_hex, group_id, content, created_at, kind
+         FROM message_index
+         WHERE group_id = ?1 AND created_at >= ?2 AND created_at <= ?3
+         ORDER BY created_at DESC
+         LIMIT ?4",
+    )?;
+    let rows = stmt.query_map(params![group_id, since, until, limit_i64], |row| {
+        Ok(IndexableMessage {
+            event_id: row.get(0)?,
+            sender_hex: row.get(1)?,
+            group_id: row.get(2)?,
+            content: row.get(3)?,
+            created_at: row.get(4)?,
+            kind: row.get(5)?,
+        })
+    })?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        results.push(row?);
+    }
+    results.reverse();
+    Ok(results)
+}
+
+/// Up to `limit` indexed messages stored after rowid `after`, oldest
+/// first, each with its rowid.
+pub fn messages_after(
+    conn: &Connection,
+    after: i64,
+    limit: usize,
+) -> Result<Vec<(i64, IndexableMessage)>> {
+    #[allow(clippy::cast_possible_wrap)]
+    let limit_i64 = limit as i64;
+
+    let mut stmt = conn.prepare(
+        "SELECT rowid, event_id, sender_hex, group_id, content, created_at, kind
+         FROM message_index
+         WHERE rowid > ?1
+         ORDER BY rowid
+         LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![after, limit_i64], |row| {
+        Ok((
+            row.get(0)?,
+            IndexableMessage {
+                event_id: row.get(1)?,
+                sender_hex: row.get(2)?,
+                group_id: row.get(3)?,
+                content: row.get(4)?,
+                created_at: row.get(5)?,
+                kind: row.get(6)?,
+            },
+        ))
+    })?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        results.push(row?);
+    }
+    Ok(results)
+}
+
+/// Delete indexed messages by event ID, only those written by
+/// `sender_hex` (NIP-09 deletions are honoured for the author alone).
+/// Returns the number of messages removed.
+pub fn delete_messages(conn: &Connection, event_ids: &[String], sender_hex: &str) -> Result<usize> {
+    let mut stmt =
+        conn.prepare("DELETE FROM message_index WHERE event_id = ?1 AND sender_hex = ?2")?;
+    let mut removed = 0;
+    for event_id in event_ids {
+        removed += stmt.execute(params![event_id, sender_hex])?;
+    }
+    Ok(removed)
+}
+
 /// Count indexed messages.
 pub fn count_messages(conn: &Connection) -> Result<usize> {
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM message_index", [], |row| row.get(0))?;
@@ -224,6 +313,17 @@ pub fn count_messages(conn: &Connection) -> Result<usize> {
     Ok(count as usize)
 }
 
+/// Count indexed messages created at or after `since` (unix seconds).
+pub fn count_messages_since(conn: &Connection, since: i64) -> Result<usize> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM message_index WHERE created_at >= ?1",
+        params![since],
+        |row| row.get(0),
+    )?;
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    Ok(count as usize)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -368,6 +468,21 @@ mod tests {
         assert!(results[0].score > 0.0);
     }
 
+    #[test]
+    fn delete_messages_only_from_the_author() {
+        let conn = test_conn();
+        index_message(&conn, &sample_message("evt1", "Rust is a systems language")).unwrap();
+        index_message(&conn, &sample_message("evt2", "Rust has a borrow checker")).unwrap();
+
+        let ids = vec!["evt1".to_string()];
+        assert_eq!(delete_messages(&conn, &ids, "ccdd").unwrap(), 0);
+        assert_eq!(delete_messages(&conn, &ids, "aabb").unwrap(), 1);
+
+        let results = search_messages(&conn, "Rust", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].event_id, "evt2");
+    }
+
     #[test]
     fn search_no_match() {
         let conn = test_conn();
@@ -490,4 +605,53 @@ mod tests {
         assert!(!results.is_empty());
         assert!(results[0].content.contains("Unicode"));
     }
+
+    #[test]
+    fn group_messages_filters_by_group_and_range() {
+        let conn = test_conn();
+        for (id, group, at) in [
+            ("a", "test_group", 100),
+            ("b", "test_group", 200),
+            ("c", "other_group", 200),
+            ("d", "test_group", 300),
+            ("e", "test_group", 400),
+        ] {
+            let mut msg = sample_message(id, "message content for the export");
+            msg.group_id = Some(group.to_string());
+            msg.created_at = at;
+            index_message(&conn, &msg).unwrap();
+        }
+
+        let ids = |msgs: Vec<IndexableMessage>| -> Vec<String> {
+            msgs.into_iter().map(|m| m.event_id).collect()
+        };
+        assert_eq!(
+            ids(group_messages(&conn, "test_group", 150, 400, 10).unwrap()),
+            vec!["b", "d", "e"]
+        );
+        // The limit keeps the newest, still oldest first.
+        assert_eq!(
+            ids(group_messages(&conn, "test_group", 0, i64::MAX, 2).unwrap()),
+            vec!["d", "e"]
+        );
+        assert!(group_messages(&conn, "missing", 0, i64::MAX, 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn messages_after_pages_by_rowid() {
+        let conn = test_conn();
+        for id in ["a", "b", "c"] {
+            index_message(&conn, &sample_message(id, "message content for the cursor")).unwrap();
+        }
+
+        let first = messages_after(&conn, 0, 2).unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].1.event_id, "a");
+        let rest = messages_after(&conn, first[1].0, 10).unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].1.event_id, "c");
+        assert!(messages_after(&conn, rest[0].0, 10).unwrap().is_empty());
+    }
 }
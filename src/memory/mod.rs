@@ -5,11 +5,15 @@ pub mod collective;
 pub mod contextvm_bridge;
 pub mod cortex;
 pub mod decay;
+pub mod distill;
 pub mod doc_index;
 pub mod embeddings;
+pub mod engagement;
 pub mod file_indexer;
 pub mod hybrid;
 pub mod hygiene;
+pub mod index_redaction;
+pub mod link_index;
 pub mod lucid;
 pub mod markdown;
 pub mod message_index;
@@ -20,18 +24,25 @@ pub mod nostr;
 pub mod nostr_sqlite;
 #[cfg(feature = "memory-postgres")]
 pub mod postgres;
+pub mod proactive;
 pub mod qdrant;
+pub mod relay_sync;
+pub mod reputation_cli;
 pub mod response_cache;
 pub mod retrieval;
+pub mod review;
 pub mod runtime_context;
+pub mod search_cli;
 pub mod snapshot;
 pub mod snowclaw_backends;
 pub mod snowclaw_ext;
 pub mod social;
 pub mod sqlite;
+pub mod task_board;
 pub mod traits;
 pub mod unified_search;
 pub mod vector;
+pub mod write_policy;
 
 #[allow(unused_imports)]
 pub use backend::{
@@ -62,10 +73,10 @@ pub use qdrant::QdrantMemory;
 pub use response_cache::ResponseCache;
 pub use runtime_context::{MemoryRuntimeContext, MemoryVisibility};
 #[allow(unused_imports)]
-pub use snowclaw_ext::{RecallContext, SnowclawMemoryExt};
+pub use snowclaw_ext::{Audience, RecallContext, SnowclawMemoryExt};
 pub use sqlite::SqliteMemory;
 pub use traits::Memory;
-pub use traits::{MemoryCategory, MemoryEntry};
+pub use traits::{MemoryCategory, MemoryEntry, MemoryOp, NewMemory};
 
 use crate::config::{EmbeddingRouteConfig, MemoryConfig, StorageProviderConfig};
 use anyhow::Context;
@@ -230,12 +241,39 @@ pub fn create_memory_with_storage(
 }
 
 /// Factory: create memory with optional storage-provider override and embedding routes.
+///
+/// With `[memory.write_policy]` enabled the backend is wrapped in a
+/// [`write_policy::PolicyMemory`].
 pub fn create_memory_with_storage_and_routes(
     config: &MemoryConfig,
     embedding_routes: &[EmbeddingRouteConfig],
     storage_provider: Option<&StorageProviderConfig>,
     workspace_dir: &Path,
     api_key: Option<&str>,
+) -> anyhow::Result<Box<dyn Memory>> {
+    let memory = create_memory_backend(
+        config,
+        embedding_routes,
+        storage_provider,
+        workspace_dir,
+        api_key,
+    )?;
+    match write_policy::WritePolicy::from_config(&config.write_policy)? {
+        Some(policy) => Ok(Box::new(write_policy::PolicyMemory::new(
+            memory,
+            policy,
+            workspace_dir,
+        ))),
+        None => Ok(memory),
+    }
+}
+
+fn create_memory_backend(
+    config: &MemoryConfig,
+    embedding_routes: &[EmbeddingRouteConfig],
+    storage_provider: Option<&StorageProviderConfig>,
+    workspace_dir: &Path,
+    api_key: Option<&str>,
 ) -> anyhow::Result<Box<dyn Memory>> {
     let backend_name = effective_memory_backend_name(&config.backend, storage_provider);
     let backend_kind = classify_memory_backend(&backend_name);
@@ -411,32 +449,7 @@ pub fn create_memory_with_storage_and_routes(
         snowclaw_backends::classify(&backend_name),
         snowclaw_backends::SnowclawBackendKind::Nostr
     ) {
-        let nsec = config
-            .nsec
-            .clone()
-            .or_else(|| std::env::var("SNOWCLAW_NSEC").ok());
-
-        let embedder: Arc<dyn embeddings::EmbeddingProvider> =
-            Arc::from(embeddings::create_embedding_provider(
-                &resolved_embedding.provider,
-                resolved_embedding.api_key.as_deref(),
-                &resolved_embedding.model,
-                resolved_embedding.dimensions,
-            ));
-
-        #[allow(clippy::cast_possible_truncation)]
-        let mem = NostrSqliteMemory::new(
-            config.local_relay.as_deref(),
-            config.local_relay.as_deref(),
-            nsec.as_deref(),
-            workspace_dir,
-            embedder,
-            config.vector_weight as f32,
-            config.keyword_weight as f32,
-            config.embedding_cache_size,
-            config.sqlite_open_timeout_secs,
-            config.encrypted_memory.unwrap_or(false),
-        )?;
+        let mem = build_nostr_sqlite_memory(config, workspace_dir, &resolved_embedding)?;
         return Ok(Box::new(mem));
     }
 
@@ -473,6 +486,52 @@ pub fn create_memory_with_storage_and_routes(
     )
 }
 
+/// Build the `nostr` memory backend (composite Nostr+SQLite).
+fn build_nostr_sqlite_memory(
+    config: &MemoryConfig,
+    workspace_dir: &Path,
+    resolved_embedding: &ResolvedEmbeddingConfig,
+) -> anyhow::Result<NostrSqliteMemory> {
+    let nsec = config
+        .nsec
+        .clone()
+        .or_else(|| std::env::var("SNOWCLAW_NSEC").ok());
+
+    let embedder: Arc<dyn embeddings::EmbeddingProvider> =
+        Arc::from(embeddings::create_embedding_provider(
+            &resolved_embedding.provider,
+            resolved_embedding.api_key.as_deref(),
+            &resolved_embedding.model,
+            resolved_embedding.dimensions,
+        ));
+
+    #[allow(clippy::cast_possible_truncation)]
+    let mem = NostrSqliteMemory::new(
+        config.local_relay.as_deref(),
+        config.local_relay.as_deref(),
+        nsec.as_deref(),
+        workspace_dir,
+        embedder,
+        config.vector_weight as f32,
+        config.keyword_weight as f32,
+        config.embedding_cache_size,
+        config.sqlite_open_timeout_secs,
+        config.encrypted_memory.unwrap_or(false),
+    )?;
+    Ok(mem)
+}
+
+/// Create the `nostr` memory backend directly, for operations that need
+/// more than the `Memory` trait (e.g. `snowclaw memory sync`).
+pub fn create_nostr_sqlite_memory(
+    config: &MemoryConfig,
+    workspace_dir: &Path,
+    api_key: Option<&str>,
+) -> anyhow::Result<NostrSqliteMemory> {
+    let resolved_embedding = resolve_embedding_config(config, &[], api_key);
+    build_nostr_sqlite_memory(config, workspace_dir, &resolved_embedding)
+}
+
 pub fn create_memory_for_migration(
     backend: &str,
     workspace_dir: &Path,
@@ -14,6 +14,8 @@ use tokio::sync::{OnceCell, RwLock};
 use tracing::{debug, info, warn};
 
 use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use crate::channels::snowclaw_channels;
+use nostr_core::Signer;
 
 /// Local JSON cache for offline fallback.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
@@ -31,7 +33,7 @@ pub struct NostrMemory {
     relay_url: Option<String>,
     local_relay_url: Option<String>,
     nsec: Option<String>,
-    relay_client: OnceCell<(Client, PublicKey)>,
+    relay_client: OnceCell<(Client, Signer)>,
     app_tag: String,
     cache: Arc<RwLock<LocalCache>>,
     cache_path: PathBuf,
@@ -92,23 +94,23 @@ impl NostrMemory {
     }
 
     /// Lazily initialize the relay client on first use.
-    async fn get_relay(&self) -> Option<&(Client, PublicKey)> {
+    async fn get_relay(&self) -> Option<&(Client, Signer)> {
         let relay_url = self.relay_url.as_deref()?;
-        let nsec = self.nsec.as_deref()?;
 
         self.relay_client
             .get_or_try_init(|| async {
-                let keys = Keys::parse(nsec).context("Invalid nsec for Nostr memory")?;
-                let public_key = keys.public_key();
-                let client = Client::new(keys);
-
-                client
-                    .add_relay(relay_url)
+                let signer = snowclaw_channels::signer_or_shared(self.nsec.as_deref())
+                    .context("Invalid nsec for Nostr memory")?
+                    .context("No signer for Nostr memory")?;
+                let public_key = signer.public_key();
+                let relay_proxy = snowclaw_channels::runtime_relay_proxy()?;
+                let client = relay_proxy
+                    .client(signer.clone(), &[relay_url.to_string()])
                     .await
                     .context("Failed to add memory relay")?;
 
                 if let Some(local_url) = &self.local_relay_url {
-                    if let Err(e) = client.add_relay(local_url.as_str()).await {
+                    if let Err(e) = relay_proxy.add_relay(&client, local_url).await {
                         warn!("Failed to add local relay {local_url}: {e}");
                     }
                 }
@@ -121,7 +123,7 @@ impl NostrMemory {
                     public_key.to_bech32().unwrap_or_default()
                 );
 
-                Ok::<_, anyhow::Error>((client, public_key))
+                Ok::<_, anyhow::Error>((client, signer))
             })
             .await
             .ok()
@@ -159,9 +161,19 @@ impl NostrMemory {
         Ok(events.into_iter().collect())
     }
 
+    /// Convert Nostr events to MemoryEntries, skipping foreign `d` tags.
+    async fn events_to_entries(&self, signer: &Signer, events: &[Event]) -> Vec<MemoryEntry> {
+        let mut entries = Vec::with_capacity(events.len());
+        for event in events {
+            entries.extend(self.event_to_entry(signer, event).await);
+        }
+        entries
+    }
+
     /// Convert a Nostr event to a MemoryEntry.
-    /// If the event has an `["encrypted","nip44"]` tag, attempts NIP-44 decryption.
-    fn event_to_entry(&self, event: &Event) -> Option<MemoryEntry> {
+    /// If the event has an `["encrypted","nip44"]` tag, attempts NIP-44
+    /// decryption through `signer`.
+    async fn event_to_entry(&self, signer: &Signer, event: &Event) -> Option<MemoryEntry> {
         let d_tag = event
             .tags
             .iter()
@@ -176,29 +188,7 @@ impl NostrMemory {
             .find(|t| t.as_slice().first().map(|s| s.as_str()) == Some("session"))
             .and_then(|t| t.as_slice().get(1).map(|s| s.to_string()));
 
-        // Check for NIP-44 encryption tag
-        let is_encrypted = event.tags.iter().any(|t| {
-            let s = t.as_slice();
-            s.first().map(|v| v.as_str()) == Some("encrypted")
-                && s.get(1).map(|v| v.as_str()) == Some("nip44")
-        });
-
-        let content = if is_encrypted {
-            if let Some(keys) = self.keys() {
-                match nip44::decrypt(keys.secret_key(), &event.pubkey, &event.content) {
-                    Ok(plaintext) => plaintext,
-                    Err(e) => {
-                        warn!("NIP-44 decryption failed for key {key}, using raw content: {e}");
-                        event.content.clone()
-                    }
-                }
-            } else {
-                warn!("Encrypted event but no nsec available for key {key}");
-                event.content.clone()
-            }
-        } else {
-            event.content.clone()
-        };
+        let content = memory_event_content(signer, event).await;
 
         Some(MemoryEntry {
             id: event.id.to_hex(),
@@ -227,12 +217,7 @@ impl NostrMemory {
     }
 
     fn has_relay_config(&self) -> bool {
-        self.relay_url.is_some() && self.nsec.is_some()
-    }
-
-    /// Parse the nsec into Keys for NIP-44 encrypt/decrypt.
-    fn keys(&self) -> Option<Keys> {
-        self.nsec.as_deref().and_then(|nsec| Keys::parse(nsec).ok())
+        self.relay_url.is_some() && snowclaw_channels::has_signer(self.nsec.as_deref())
     }
 }
 
@@ -270,53 +255,30 @@ impl Memory for NostrMemory {
         }
 
         // Write to relay if available
-        if let Some((client, public_key)) = self.get_relay().await {
+        if let Some((client, signer)) = self.get_relay().await {
             let d_tag = self.d_tag(key, &category);
 
-            let mut tags = vec![
-                Tag::custom(TagKind::custom("d"), vec![d_tag]),
-                Tag::custom(TagKind::custom("app"), vec![self.app_tag.clone()]),
-                Tag::custom(TagKind::custom("category"), vec![category.to_string()]),
-                Tag::custom(TagKind::custom("agent"), vec!["snowclaw".to_string()]),
-            ];
-
-            if let Some(sid) = session_id {
-                tags.push(Tag::custom(
-                    TagKind::custom("session"),
-                    vec![sid.to_string()],
-                ));
-            }
-
             // Encrypt content with NIP-44 if enabled
-            let publish_content = if self.encrypted {
-                if let Some(keys) = self.keys() {
-                    match nip44::encrypt(
-                        keys.secret_key(),
-                        &public_key,
-                        content,
-                        nip44::Version::V2,
-                    ) {
-                        Ok(ciphertext) => {
-                            tags.push(Tag::custom(
-                                TagKind::custom("encrypted"),
-                                vec!["nip44".to_string()],
-                            ));
-                            ciphertext
-                        }
-                        Err(e) => {
-                            warn!("NIP-44 encryption failed, publishing plaintext: {e}");
-                            content.to_string()
-                        }
+            let (publish_content, encrypted) = if self.encrypted {
+                match signer.nip44_encrypt(&signer.public_key(), content).await {
+                    Ok(ciphertext) => (ciphertext, true),
+                    Err(e) => {
+                        warn!("NIP-44 encryption failed, publishing plaintext: {e}");
+                        (content.to_string(), false)
                     }
-                } else {
-                    warn!("Encryption enabled but no nsec available, publishing plaintext");
-                    content.to_string()
                 }
             } else {
-                content.to_string()
+                (content.to_string(), false)
             };
 
-            let builder = EventBuilder::new(Kind::Custom(30078), &publish_content).tags(tags);
+            let builder = memory_event_builder(
+                &d_tag,
+                &self.app_tag,
+                &category.to_string(),
+                session_id,
+                &publish_content,
+                encrypted,
+            );
 
             match client.send_event_builder(builder).await {
                 Ok(_) => debug!("Stored memory to relay: {} ({})", key, category),
@@ -338,17 +300,18 @@ impl Memory for NostrMemory {
 
         // Try relay first if available
         if self.has_relay_config() {
-            if let Some((_, public_key)) = self.get_relay().await {
+            if let Some((_, signer)) = self.get_relay().await {
                 let filter = Filter::new()
-                    .author(*public_key)
+                    .author(signer.public_key())
                     .kind(Kind::Custom(30078))
                     .limit(500);
 
                 match self.fetch(filter).await {
                     Ok(events) if !events.is_empty() => {
-                        let mut results: Vec<MemoryEntry> = events
-                            .iter()
-                            .filter_map(|e| self.event_to_entry(e))
+                        let mut results: Vec<MemoryEntry> = self
+                            .events_to_entries(signer, &events)
+                            .await
+                            .into_iter()
                             .filter(|entry| {
                                 let matches_query =
                                     entry.content.to_lowercase().contains(&query_lower)
@@ -392,7 +355,7 @@ impl Memory for NostrMemory {
     async fn get(&self, key: &str) -> Result<Option<MemoryEntry>> {
         // Try relay first
         if self.has_relay_config() {
-            if let Some((_, public_key)) = self.get_relay().await {
+            if let Some((_, signer)) = self.get_relay().await {
                 for cat in &[
                     MemoryCategory::Core,
                     MemoryCategory::Daily,
@@ -400,7 +363,7 @@ impl Memory for NostrMemory {
                 ] {
                     let d_tag = self.d_tag(key, cat);
                     let filter = Filter::new()
-                        .author(*public_key)
+                        .author(signer.public_key())
                         .kind(Kind::Custom(30078))
                         .custom_tag(SingleLetterTag::lowercase(Alphabet::D), d_tag)
                         .limit(1);
@@ -408,7 +371,7 @@ impl Memory for NostrMemory {
                     match self.fetch(filter).await {
                         Ok(events) => {
                             if let Some(event) = events.first() {
-                                if let Some(entry) = self.event_to_entry(event) {
+                                if let Some(entry) = self.event_to_entry(signer, event).await {
                                     return Ok(Some(entry));
                                 }
                             }
@@ -434,17 +397,18 @@ impl Memory for NostrMemory {
     ) -> Result<Vec<MemoryEntry>> {
         // Try relay first
         if self.has_relay_config() {
-            if let Some((_, public_key)) = self.get_relay().await {
+            if let Some((_, signer)) = self.get_relay().await {
                 let filter = Filter::new()
-                    .author(*public_key)
+                    .author(signer.public_key())
                     .kind(Kind::Custom(30078))
                     .limit(1000);
 
                 match self.fetch(filter).await {
                     Ok(events) if !events.is_empty() => {
-                        let entries: Vec<MemoryEntry> = events
-                            .iter()
-                            .filter_map(|e| self.event_to_entry(e))
+                        let entries: Vec<MemoryEntry> = self
+                            .events_to_entries(signer, &events)
+                            .await
+                            .into_iter()
                             .filter(|entry| {
                                 category.map_or(true, |cat| &entry.category == cat)
                                     && session_id.map_or(true, |sid| {
@@ -527,6 +491,65 @@ impl Memory for NostrMemory {
     }
 }
 
+/// Kind 30078 memory event (`d` = `<app>:<category>:<key>`), shared with
+/// [`super::nostr_sqlite`].
+///
+/// `encrypted` marks `content` as NIP-44 ciphertext.
+pub(crate) fn memory_event_builder(
+    d_tag: &str,
+    app_tag: &str,
+    category: &str,
+    session_id: Option<&str>,
+    content: &str,
+    encrypted: bool,
+) -> EventBuilder {
+    let mut tags = vec![
+        Tag::custom(TagKind::custom("d"), vec![d_tag.to_string()]),
+        Tag::custom(TagKind::custom("app"), vec![app_tag.to_string()]),
+        Tag::custom(TagKind::custom("category"), vec![category.to_string()]),
+        Tag::custom(TagKind::custom("agent"), vec!["snowclaw".to_string()]),
+    ];
+
+    if let Some(sid) = session_id {
+        tags.push(Tag::custom(
+            TagKind::custom("session"),
+            vec![sid.to_string()],
+        ));
+    }
+    if encrypted {
+        tags.push(Tag::custom(
+            TagKind::custom("encrypted"),
+            vec!["nip44".to_string()],
+        ));
+    }
+
+    EventBuilder::new(Kind::Custom(30078), content).tags(tags)
+}
+
+/// Content of a memory event, NIP-44 decrypted through `signer` when it
+/// has an `["encrypted","nip44"]` tag. Falls back to the raw content if
+/// decryption fails.
+pub(crate) async fn memory_event_content(signer: &Signer, event: &Event) -> String {
+    let is_encrypted = event.tags.iter().any(|t| {
+        let s = t.as_slice();
+        s.first().map(|v| v.as_str()) == Some("encrypted")
+            && s.get(1).map(|v| v.as_str()) == Some("nip44")
+    });
+    if !is_encrypted {
+        return event.content.clone();
+    }
+    match signer.nip44_decrypt(&event.pubkey, &event.content).await {
+        Ok(plaintext) => plaintext,
+        Err(e) => {
+            warn!(
+                "NIP-44 decryption failed for memory event {}, using raw content: {e}",
+                event.id
+            );
+            event.content.clone()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -633,25 +656,107 @@ mod tests {
         assert!(mem.health_check().await);
     }
 
-    #[test]
-    fn nip44_encrypt_decrypt_roundtrip() {
-        let keys = Keys::generate();
-        let public_key = keys.public_key();
+    #[tokio::test]
+    async fn nip44_encrypt_decrypt_roundtrip() {
+        let signer = Signer::Local(Keys::generate());
+        let public_key = signer.public_key();
         let plaintext = "secret memory content";
 
-        let ciphertext = nip44::encrypt(
-            keys.secret_key(),
-            &public_key,
-            plaintext,
-            nip44::Version::V2,
-        )
-        .expect("NIP-44 encrypt should succeed");
+        let ciphertext = signer
+            .nip44_encrypt(&public_key, plaintext)
+            .await
+            .expect("NIP-44 encrypt should succeed");
 
         assert_ne!(ciphertext, plaintext);
 
-        let decrypted = nip44::decrypt(keys.secret_key(), &public_key, &ciphertext)
+        let decrypted = signer
+            .nip44_decrypt(&public_key, &ciphertext)
+            .await
             .expect("NIP-44 decrypt should succeed");
 
         assert_eq!(decrypted, plaintext);
     }
+
+    #[tokio::test]
+    async fn snapshot_memory_event() {
+        let signer = Signer::Local(
+            Keys::parse("0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap(),
+        );
+        let event = memory_event_builder(
+            "snowclaw:core:user:profile",
+            "snowclaw",
+            "core",
+            Some("nostr_alice"),
+            "ciphertext",
+            true,
+        )
+        .custom_created_at(Timestamp::from(1_700_000_000))
+        .sign(&signer)
+        .await
+        .unwrap();
+        let tags: Vec<Vec<String>> = event.tags.iter().map(|t| t.as_slice().to_vec()).collect();
+
+        assert_eq!(event.kind, Kind::Custom(30078));
+        assert_eq!(event.content, "ciphertext");
+        assert_eq!(
+            serde_json::json!(tags),
+            serde_json::json!([
+                ["d", "snowclaw:core:user:profile"],
+                ["app", "snowclaw"],
+                ["category", "core"],
+                ["agent", "snowclaw"],
+                ["session", "nostr_alice"],
+                ["encrypted", "nip44"],
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn snapshot_memory_event_plaintext_without_session() {
+        let signer = Signer::Local(
+            Keys::parse("0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap(),
+        );
+        let event = memory_event_builder("snowclaw:daily:d", "snowclaw", "daily", None, "x", false)
+            .sign(&signer)
+            .await
+            .unwrap();
+        let tags: Vec<Vec<String>> = event.tags.iter().map(|t| t.as_slice().to_vec()).collect();
+        assert_eq!(
+            serde_json::json!(tags),
+            serde_json::json!([
+                ["d", "snowclaw:daily:d"],
+                ["app", "snowclaw"],
+                ["category", "daily"],
+                ["agent", "snowclaw"],
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn memory_event_content_decrypts_tagged_events() {
+        let signer = Signer::Local(Keys::generate());
+        let own = signer.public_key();
+        let ciphertext = signer.nip44_encrypt(&own, "secret").await.unwrap();
+        let sealed = memory_event_builder(
+            "snowclaw:core:k",
+            "snowclaw",
+            "core",
+            None,
+            &ciphertext,
+            true,
+        )
+        .sign(&signer)
+        .await
+        .unwrap();
+        assert_eq!(memory_event_content(&signer, &sealed).await, "secret");
+
+        let plain =
+            memory_event_builder("snowclaw:core:k", "snowclaw", "core", None, "open", false)
+                .sign(&signer)
+                .await
+                .unwrap();
+        assert_eq!(memory_event_content(&signer, &plain).await, "open");
+    }
 }
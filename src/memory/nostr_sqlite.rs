@@ -4,12 +4,15 @@
 //! - **Write path:** store in local SQLite (fast) → publish kind 30078 to relay (async)
 //! - **Read/recall path:** query local SQLite using hybrid search (vector + FTS5)
 //! - **Startup sync:** fetch missing events from relay → upsert into SQLite
+//! - **Offline spool:** events no relay accepts wait in `outbox.db` and are
+//!   re-sent when a relay is reachable again
 //!
 //! The relay provides durable, portable persistence. SQLite provides fast local
 //! semantic search with embeddings and FTS5. Best of both worlds.
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use futures_util::future::join_all;
 use nostr_sdk::prelude::*;
 use std::path::Path;
 use std::sync::Arc;
@@ -18,8 +21,13 @@ use tokio::sync::OnceCell;
 use tracing::{debug, info, warn};
 
 use super::embeddings::EmbeddingProvider;
+use super::nostr::{memory_event_builder, memory_event_content};
+use super::relay_sync::{decide, RelaySyncReport, SyncDecision};
 use super::sqlite::SqliteMemory;
-use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use super::traits::{Memory, MemoryCategory, MemoryEntry, MemoryOp};
+use crate::channels::nostr_outbox::{self, Outbox};
+use crate::channels::snowclaw_channels;
+use nostr_core::Signer;
 
 /// Composite Nostr+SQLite memory backend.
 ///
@@ -31,10 +39,12 @@ pub struct NostrSqliteMemory {
     relay_url: Option<String>,
     local_relay_url: Option<String>,
     nsec: Option<String>,
-    relay_client: OnceCell<(Client, PublicKey)>,
+    relay_client: OnceCell<(Client, Signer)>,
     app_tag: String,
     synced: OnceCell<()>,
     encrypted: bool,
+    /// Spool for publishes no relay accepted (`nostr_sqlite/outbox.db`).
+    outbox: Option<Outbox>,
 }
 
 impl NostrSqliteMemory {
@@ -63,6 +73,14 @@ impl NostrSqliteMemory {
             cache_max,
             sqlite_open_timeout_secs,
         )?;
+        let outbox_path = sqlite_workspace.join(nostr_outbox::OUTBOX_FILE);
+        let outbox = match Outbox::open(&outbox_path, "nostr_memory_outbox") {
+            Ok(outbox) => Some(outbox),
+            Err(e) => {
+                warn!("Memory outbox unavailable, publishing without spooling: {e:#}");
+                None
+            }
+        };
 
         Ok(Self {
             sqlite,
@@ -73,6 +91,7 @@ impl NostrSqliteMemory {
             app_tag: "snowclaw".to_string(),
             synced: OnceCell::new(),
             encrypted,
+            outbox,
         })
     }
 
@@ -98,28 +117,31 @@ impl NostrSqliteMemory {
     }
 
     /// Lazily initialize the relay client on first use.
-    async fn get_relay(&self) -> Option<&(Client, PublicKey)> {
+    async fn get_relay(&self) -> Option<&(Client, Signer)> {
         let relay_url = self.relay_url.as_deref()?;
-        let nsec = self.nsec.as_deref()?;
 
         self.relay_client
             .get_or_try_init(|| async {
-                let keys = Keys::parse(nsec).context("Invalid nsec for Nostr memory")?;
-                let public_key = keys.public_key();
-                let client = Client::new(keys);
-
-                client
-                    .add_relay(relay_url)
+                let signer = snowclaw_channels::signer_or_shared(self.nsec.as_deref())
+                    .context("Invalid nsec for Nostr memory")?
+                    .context("No signer for Nostr memory")?;
+                let public_key = signer.public_key();
+                let relay_proxy = snowclaw_channels::runtime_relay_proxy()?;
+                let client = relay_proxy
+                    .client(signer.clone(), &[relay_url.to_string()])
                     .await
                     .context("Failed to add memory relay")?;
 
                 if let Some(local_url) = &self.local_relay_url {
-                    if let Err(e) = client.add_relay(local_url.as_str()).await {
+                    if let Err(e) = relay_proxy.add_relay(&client, local_url).await {
                         warn!("Failed to add local relay {local_url}: {e}");
                     }
                 }
 
                 client.connect().await;
+                if let Some(ref outbox) = self.outbox {
+                    tokio::spawn(outbox.clone().run(client.clone()));
+                }
 
                 info!(
                     "Nostr+SQLite memory relay connected (relay: {}, pubkey: {})",
@@ -127,7 +149,7 @@ impl NostrSqliteMemory {
                     public_key.to_bech32().unwrap_or_default()
                 );
 
-                Ok::<_, anyhow::Error>((client, public_key))
+                Ok::<_, anyhow::Error>((client, signer))
             })
             .await
             .ok()
@@ -153,12 +175,7 @@ impl NostrSqliteMemory {
     }
 
     fn has_relay_config(&self) -> bool {
-        self.relay_url.is_some() && self.nsec.is_some()
-    }
-
-    /// Parse the nsec into Keys for NIP-44 encrypt/decrypt.
-    fn keys(&self) -> Option<Keys> {
-        self.nsec.as_deref().and_then(|nsec| Keys::parse(nsec).ok())
+        self.relay_url.is_some() && snowclaw_channels::has_signer(self.nsec.as_deref())
     }
 
     /// Sync missing events from relay into local SQLite.
@@ -166,12 +183,33 @@ impl NostrSqliteMemory {
     /// Fetches all kind 30078 events authored by our pubkey and upserts them.
     /// Called lazily on first memory operation.
     async fn sync_from_relay(&self) -> Result<usize> {
-        let (client, public_key) = match self.get_relay().await {
+        let report = self.resync(None, &mut |_, _| {}).await?;
+        Ok(report.upserted)
+    }
+
+    /// Pull memory events created at or after `since` (all when `None`)
+    /// from the relay into SQLite.
+    ///
+    /// A local entry that differs and was updated after the relay event is
+    /// kept and reported as a conflict. `progress` is called with
+    /// (processed, total) after each event.
+    pub async fn resync(
+        &self,
+        since: Option<u64>,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<RelaySyncReport> {
+        let mut report = RelaySyncReport::default();
+        let (client, signer) = match self.get_relay().await {
             Some(r) => r,
-            None => return Ok(0),
+            None => return Ok(report),
         };
 
-        let filter = Filter::new().author(*public_key).kind(Kind::Custom(30078));
+        let mut filter = Filter::new()
+            .author(signer.public_key())
+            .kind(Kind::Custom(30078));
+        if let Some(since) = since {
+            filter = filter.since(Timestamp::from(since));
+        }
 
         let events = client
             .fetch_events(filter, Duration::from_secs(15))
@@ -180,9 +218,10 @@ impl NostrSqliteMemory {
 
         let events: Vec<Event> = events.into_iter().collect();
         let total = events.len();
-        let mut synced = 0;
+        report.fetched = total;
 
-        for event in &events {
+        for (i, event) in events.iter().enumerate() {
+            progress(i + 1, total);
             let d_tag = event
                 .tags
                 .iter()
@@ -205,49 +244,50 @@ impl NostrSqliteMemory {
                 .find(|t| t.as_slice().first().map(|s| s.as_str()) == Some("session"))
                 .and_then(|t| t.as_slice().get(1).map(|s| s.to_string()));
 
-            // Decrypt NIP-44 encrypted content if present
-            let is_encrypted = event.tags.iter().any(|t| {
-                let s = t.as_slice();
-                s.first().map(|v| v.as_str()) == Some("encrypted")
-                    && s.get(1).map(|v| v.as_str()) == Some("nip44")
-            });
-
-            let content = if is_encrypted {
-                if let Some(keys) = self.keys() {
-                    match nip44::decrypt(keys.secret_key(), &event.pubkey, &event.content) {
-                        Ok(plaintext) => plaintext,
-                        Err(e) => {
-                            warn!("NIP-44 decryption failed for {d_tag}, using raw content: {e}");
-                            event.content.clone()
-                        }
-                    }
-                } else {
-                    warn!("Encrypted event but no nsec for {d_tag}");
-                    event.content.clone()
+            let content = memory_event_content(signer, event).await;
+
+            let local = self.sqlite.get(&key).await.ok().flatten();
+            let decision = decide(
+                local.as_ref().and_then(|l| {
+                    chrono::DateTime::parse_from_rfc3339(&l.timestamp)
+                        .ok()
+                        .map(|t| t.timestamp())
+                }),
+                local.as_ref().is_some_and(|l| l.content == content),
+                event.created_at.as_secs() as i64,
+            );
+
+            if decision == SyncDecision::Upsert {
+                // Upsert into SQLite — SqliteMemory handles ON CONFLICT(key) DO UPDATE
+                if let Err(e) = self
+                    .sqlite
+                    .store(&key, &content, category, session_id.as_deref())
+                    .await
+                {
+                    warn!("Failed to sync event {d_tag} into SQLite: {e}");
+                    report.skipped += 1;
+                    continue;
                 }
-            } else {
-                event.content.clone()
-            };
-
-            // Upsert into SQLite — SqliteMemory handles ON CONFLICT(key) DO UPDATE
-            if let Err(e) = self
-                .sqlite
-                .store(&key, &content, category, session_id.as_deref())
-                .await
-            {
-                warn!("Failed to sync event {d_tag} into SQLite: {e}");
-                continue;
             }
-            synced += 1;
+            report.record(decision, &d_tag);
         }
 
-        if synced > 0 {
-            info!("Nostr→SQLite sync complete: {synced}/{total} events indexed");
+        if report.upserted > 0 {
+            info!(
+                "Nostr→SQLite sync complete: {}/{total} events indexed",
+                report.upserted
+            );
         } else {
             debug!("Nostr→SQLite sync: no new events to index ({total} total on relay)");
         }
+        if !report.conflicts.is_empty() {
+            info!(
+                "Nostr→SQLite sync kept {} local entries newer than the relay",
+                report.conflicts.len()
+            );
+        }
 
-        Ok(synced)
+        Ok(report)
     }
 
     /// Ensure relay sync has happened at least once. Lazy, idempotent.
@@ -273,56 +313,114 @@ impl NostrSqliteMemory {
         category: &MemoryCategory,
         session_id: Option<&str>,
     ) {
-        let Some((client, public_key)) = self.get_relay().await else {
+        let Some((client, signer)) = self.get_relay().await else {
             return;
         };
 
-        let d_tag = self.d_tag(key, category);
+        let builder = self
+            .memory_event(signer, key, content, category, session_id)
+            .await;
+        match self.send(client, builder).await {
+            Ok(_) => debug!("Published memory to relay: {} ({})", key, category),
+            Err(e) => warn!("Failed to publish memory to relay: {e} (persisted in SQLite)"),
+        }
+    }
+
+    /// Send an event, through the outbox when one is open.
+    async fn send(&self, client: &Client, builder: EventBuilder) -> Result<EventId> {
+        match self.outbox {
+            Some(ref outbox) => outbox.send_builder(client, builder).await,
+            None => Ok(client.send_event_builder(builder).await?.val),
+        }
+    }
+
+    /// Publish the stores of a committed transaction and request deletion of
+    /// forgotten entries in one event (best-effort). Without an outbox the
+    /// stores are sent concurrently; the outbox sends them in order.
+    async fn publish_transaction(&self, ops: &[MemoryOp], forgotten: Vec<EventId>) {
+        let Some((client, signer)) = self.get_relay().await else {
+            return;
+        };
+
+        let mut builders = Vec::with_capacity(ops.len());
+        for op in ops {
+            if let MemoryOp::Store(entry) = op {
+                builders.push(
+                    self.memory_event(
+                        signer,
+                        &entry.key,
+                        &entry.content,
+                        &entry.category,
+                        entry.session_id.as_deref(),
+                    )
+                    .await,
+                );
+            }
+        }
+        let results = if self.outbox.is_some() {
+            let mut results = Vec::with_capacity(builders.len());
+            for builder in builders {
+                results.push(self.send(client, builder).await);
+            }
+            results
+        } else {
+            join_all(builders.into_iter().map(|b| self.send(client, b))).await
+        };
+        let failed = results.iter().filter(|r| r.is_err()).count();
+        if failed > 0 {
+            warn!(
+                "Failed to publish {failed} of {} memories to relay (persisted in SQLite)",
+                results.len()
+            );
+        } else {
+            debug!("Published {} memories to relay", results.len());
+        }
 
-        let mut tags = vec![
-            Tag::custom(TagKind::custom("d"), vec![d_tag]),
-            Tag::custom(TagKind::custom("app"), vec![self.app_tag.clone()]),
-            Tag::custom(TagKind::custom("category"), vec![category.to_string()]),
-            Tag::custom(TagKind::custom("agent"), vec!["snowclaw".to_string()]),
-        ];
-
-        if let Some(sid) = session_id {
-            tags.push(Tag::custom(
-                TagKind::custom("session"),
-                vec![sid.to_string()],
-            ));
+        if !forgotten.is_empty() {
+            let count = forgotten.len();
+            let deletion = forgotten
+                .into_iter()
+                .fold(EventDeletionRequest::new(), |request, id| request.id(id));
+            match self.send(client, EventBuilder::delete(deletion)).await {
+                Ok(_) => debug!("Deleted {count} memories from relay"),
+                Err(e) => warn!("Failed to delete from relay: {e}"),
+            }
         }
+    }
+
+    /// Kind 30078 event for one entry, NIP-44 encrypted to ourselves
+    /// through `signer` when enabled.
+    async fn memory_event(
+        &self,
+        signer: &Signer,
+        key: &str,
+        content: &str,
+        category: &MemoryCategory,
+        session_id: Option<&str>,
+    ) -> EventBuilder {
+        let d_tag = self.d_tag(key, category);
 
         // Encrypt content with NIP-44 if enabled
-        let publish_content = if self.encrypted {
-            if let Some(keys) = self.keys() {
-                match nip44::encrypt(keys.secret_key(), &public_key, content, nip44::Version::V2) {
-                    Ok(ciphertext) => {
-                        tags.push(Tag::custom(
-                            TagKind::custom("encrypted"),
-                            vec!["nip44".to_string()],
-                        ));
-                        ciphertext
-                    }
-                    Err(e) => {
-                        warn!("NIP-44 encryption failed, publishing plaintext: {e}");
-                        content.to_string()
-                    }
+        let (publish_content, encrypted) = if self.encrypted {
+            match signer.nip44_encrypt(&signer.public_key(), content).await {
+                Ok(ciphertext) => (ciphertext, true),
+                Err(e) => {
+                    warn!("NIP-44 encryption failed, publishing plaintext: {e}");
+                    (content.to_string(), false)
                 }
-            } else {
-                warn!("Encryption enabled but no nsec available, publishing plaintext");
-                content.to_string()
             }
         } else {
-            content.to_string()
+            (content.to_string(), false)
         };
 
-        let builder = EventBuilder::new(Kind::Custom(30078), &publish_content).tags(tags);
-
-        match client.send_event_builder(builder).await {
-            Ok(_) => debug!("Published memory to relay: {} ({})", key, category),
-            Err(e) => warn!("Failed to publish memory to relay: {e} (persisted in SQLite)"),
-        }
+        memory_event_builder(
+            &d_tag,
+            &self.app_tag,
+            &category.to_string(),
+            session_id,
+            &publish_content,
+            encrypted,
+        )
     }
 }
 
@@ -380,7 +478,7 @@ impl Memory for NostrSqliteMemory {
             return Ok(None);
         }
 
-        let Some((_, public_key)) = self.get_relay().await else {
+        let Some((_, signer)) = self.get_relay().await else {
             return Ok(None);
         };
 
@@ -391,7 +489,7 @@ impl Memory for NostrSqliteMemory {
         ] {
             let d_tag = self.d_tag(key, cat);
             let filter = Filter::new()
-                .author(*public_key)
+                .author(signer.public_key())
                 .kind(Kind::Custom(30078))
                 .custom_tag(SingleLetterTag::lowercase(Alphabet::D), d_tag)
                 .limit(1);
@@ -406,9 +504,10 @@ impl Memory for NostrSqliteMemory {
                             .and_then(|t| t.as_slice().get(1).map(|s| s.to_string()));
 
                         // Cache in SQLite for next time
+                        let content = memory_event_content(signer, event).await;
                         if let Err(e) = self
                             .sqlite
-                            .store(key, &event.content, cat.clone(), session_id.as_deref())
+                            .store(key, &content, cat.clone(), session_id.as_deref())
                             .await
                         {
                             warn!("Failed to cache relay result in SQLite: {e}");
@@ -446,7 +545,7 @@ impl Memory for NostrSqliteMemory {
                 if let Ok(event_id) = EventId::from_hex(&entry.id) {
                     let deletion = EventDeletionRequest::new().id(event_id);
                     let builder = EventBuilder::delete(deletion);
-                    match client.send_event_builder(builder).await {
+                    match self.send(client, builder).await {
                         Ok(_) => debug!("Deleted memory from relay: {}", key),
                         Err(e) => warn!("Failed to delete from relay: {e}"),
                     }
@@ -458,6 +557,35 @@ impl Memory for NostrSqliteMemory {
         self.sqlite.forget(key).await
     }
 
+    fn supports_transactions(&self) -> bool {
+        true
+    }
+
+    async fn transaction(&self, ops: &[MemoryOp]) -> Result<Vec<bool>> {
+        self.ensure_synced().await;
+
+        // Look up relay event IDs before the entries are gone
+        let mut forgotten = Vec::new();
+        if self.has_relay_config() {
+            for op in ops {
+                if let MemoryOp::Forget(key) = op {
+                    if let Ok(Some(entry)) = self.sqlite.get(key).await {
+                        if let Ok(event_id) = EventId::from_hex(&entry.id) {
+                            forgotten.push(event_id);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Commit locally (atomic), then mirror to the relay
+        let applied = self.sqlite.transaction(ops).await?;
+        self.publish_transaction(ops, forgotten).await;
+
+        debug!("Applied {} memory operations (nostr+sqlite)", ops.len());
+        Ok(applied)
+    }
+
     async fn count(&self) -> Result<usize> {
         self.ensure_synced().await;
         self.sqlite.count().await
@@ -498,6 +626,7 @@ impl NostrSqliteMemory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::memory::traits::NewMemory;
 
     #[test]
     fn d_tag_format() {
@@ -563,6 +692,29 @@ mod tests {
         assert!(entry.is_none());
     }
 
+    #[tokio::test]
+    async fn local_only_transaction() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mem = NostrSqliteMemory::new_default(None, None, None, tmp.path()).unwrap();
+        mem.store("old", "stale", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+
+        let applied = mem
+            .transaction(&[
+                MemoryOp::Forget("old".into()),
+                MemoryOp::Store(NewMemory::new("a", "alpha", MemoryCategory::Core)),
+                MemoryOp::Store(NewMemory::new("b", "beta", MemoryCategory::Daily)),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(applied, vec![true, true, true]);
+        assert!(mem.supports_transactions());
+        assert!(mem.get("old").await.unwrap().is_none());
+        assert_eq!(mem.count().await.unwrap(), 2);
+    }
+
     #[tokio::test]
     async fn local_only_count_and_list() {
         let tmp = tempfile::TempDir::new().unwrap();
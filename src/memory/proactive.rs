@@ -0,0 +1,377 @@
+//! Proactive post queue: drafts of agent-initiated group posts.
+//!
+//! Stored in `social.db`. A draft is created `drafting` when a configured
+//! post fires, gets its text from the agent (`pending`, or `approved` when
+//! no approval is required), and is `approved` or `rejected` by the owner.
+//! Approved drafts are `published` once the group's frequency caps allow.
+//! Drafts nobody decides on in time become `expired`; drafts the agent
+//! declines to write become `skipped`. `proactive_fired` keeps the last
+//! time each post fired, so schedules survive restarts.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+// ── Data structures ──────────────────────────────────────────────
+
+/// State of a draft.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DraftStatus {
+    /// Waiting for the agent's text.
+    Drafting,
+    /// Waiting for the owner.
+    Pending,
+    /// Waiting for the frequency caps.
+    Approved,
+    Published,
+    Rejected,
+    Expired,
+    /// The agent had nothing to post.
+    Skipped,
+}
+
+impl DraftStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Drafting => "drafting",
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Published => "published",
+            Self::Rejected => "rejected",
+            Self::Expired => "expired",
+            Self::Skipped => "skipped",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "drafting" => Some(Self::Drafting),
+            "pending" => Some(Self::Pending),
+            "approved" => Some(Self::Approved),
+            "published" => Some(Self::Published),
+            "rejected" => Some(Self::Rejected),
+            "expired" => Some(Self::Expired),
+            "skipped" => Some(Self::Skipped),
+            _ => None,
+        }
+    }
+
+    /// Whether the draft may still be published.
+    pub fn is_open(self) -> bool {
+        matches!(self, Self::Drafting | Self::Pending | Self::Approved)
+    }
+}
+
+/// A proactive post draft.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Draft {
+    pub id: i64,
+    /// Name of the configured post.
+    pub name: String,
+    pub group_id: String,
+    pub content: String,
+    pub status: DraftStatus,
+    /// Unix seconds
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub published_at: Option<i64>,
+}
+
+// ── Schema ───────────────────────────────────────────────────────
+
+/// Create the draft queue and fire times.
+pub fn create_proactive_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS proactive_drafts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            group_id TEXT NOT NULL,
+            content TEXT NOT NULL DEFAULT '',
+            status TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            published_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_proactive_drafts_group
+            ON proactive_drafts(group_id, status);
+
+        CREATE TABLE IF NOT EXISTS proactive_fired (
+            name TEXT PRIMARY KEY,
+            fired_at INTEGER NOT NULL
+        );",
+    )
+    .context("failed to create proactive post tables")?;
+    Ok(())
+}
+
+// ── Fire times ───────────────────────────────────────────────────
+
+/// When the post `name` last fired.
+pub fn last_fired(conn: &Connection, name: &str) -> Result<Option<i64>> {
+    Ok(conn
+        .query_row(
+            "SELECT fired_at FROM proactive_fired WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+pub fn record_fired(conn: &Connection, name: &str, at: i64) -> Result<()> {
+    conn.execute(
+        "INSERT INTO proactive_fired (name, fired_at) VALUES (?1, ?2)
+         ON CONFLICT(name) DO UPDATE SET fired_at = excluded.fired_at",
+        params![name, at],
+    )?;
+    Ok(())
+}
+
+// ── Drafts ───────────────────────────────────────────────────────
+
+const DRAFT_COLUMNS: &str =
+    "id, name, group_id, content, status, created_at, updated_at, published_at";
+
+fn draft_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Draft> {
+    let status: String = row.get(4)?;
+    Ok(Draft {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        group_id: row.get(2)?,
+        content: row.get(3)?,
+        status: DraftStatus::parse(&status).unwrap_or(DraftStatus::Expired),
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        published_at: row.get(7)?,
+    })
+}
+
+fn query_drafts(
+    conn: &Connection,
+    filter: &str,
+    args: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<Draft>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {DRAFT_COLUMNS} FROM proactive_drafts WHERE {filter} ORDER BY id"
+    ))?;
+    let rows = stmt.query_map(args, draft_from_row)?;
+    let mut drafts = Vec::new();
+    for row in rows {
+        drafts.push(row?);
+    }
+    Ok(drafts)
+}
+
+/// Start a draft for the post `name`, waiting for the agent's text.
+pub fn create_draft(conn: &Connection, name: &str, group_id: &str, now: i64) -> Result<Draft> {
+    conn.execute(
+        "INSERT INTO proactive_drafts (name, group_id, status, created_at, updated_at)
+         VALUES (?1, ?2, 'drafting', ?3, ?3)",
+        params![name, group_id, now],
+    )?;
+    get_draft(conn, conn.last_insert_rowid())?.context("draft vanished after insert")
+}
+
+pub fn get_draft(conn: &Connection, id: i64) -> Result<Option<Draft>> {
+    Ok(query_drafts(conn, "id = ?1", &[&id])?.into_iter().next())
+}
+
+/// Move a draft from one of `from` to `to`, optionally replacing its text.
+/// Returns the updated draft, or `None` when it doesn't exist or is in
+/// another state.
+pub fn transition(
+    conn: &Connection,
+    id: i64,
+    from: &[DraftStatus],
+    to: DraftStatus,
+    content: Option<&str>,
+    now: i64,
+) -> Result<Option<Draft>> {
+    let Some(draft) = get_draft(conn, id)? else {
+        return Ok(None);
+    };
+    if !from.contains(&draft.status) {
+        return Ok(None);
+    }
+    let published_at = (to == DraftStatus::Published).then_some(now);
+    conn.execute(
+        "UPDATE proactive_drafts
+         SET status = ?1, content = COALESCE(?2, content), updated_at = ?3,
+             published_at = COALESCE(?4, published_at)
+         WHERE id = ?5",
+        params![to.as_str(), content, now, published_at, id],
+    )?;
+    get_draft(conn, id)
+}
+
+/// Drafts still waiting for the agent, the owner or the caps, oldest first.
+pub fn open_drafts(conn: &Connection) -> Result<Vec<Draft>> {
+    query_drafts(conn, "status IN ('drafting', 'pending', 'approved')", &[])
+}
+
+/// Expire drafting and pending drafts last changed before `before`.
+/// Returns the expired drafts.
+pub fn expire_stale(conn: &Connection, before: i64, now: i64) -> Result<Vec<Draft>> {
+    let stale = query_drafts(
+        conn,
+        "status IN ('drafting', 'pending') AND updated_at < ?1",
+        &[&before],
+    )?;
+    conn.execute(
+        "UPDATE proactive_drafts SET status = 'expired', updated_at = ?1
+         WHERE status IN ('drafting', 'pending') AND updated_at < ?2",
+        params![now, before],
+    )?;
+    Ok(stale)
+}
+
+/// Posts published in `group_id` since `since`, and the time of the latest.
+pub fn published_since(
+    conn: &Connection,
+    group_id: &str,
+    since: i64,
+) -> Result<(u32, Option<i64>)> {
+    let (count, last): (i64, Option<i64>) = conn.query_row(
+        "SELECT COUNT(*), MAX(published_at) FROM proactive_drafts
+         WHERE group_id = ?1 AND status = 'published' AND published_at >= ?2",
+        params![group_id, since],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    Ok((u32::try_from(count).unwrap_or(u32::MAX), last))
+}
+
+/// Latest publish time in `group_id`, if any.
+pub fn last_published(conn: &Connection, group_id: &str) -> Result<Option<i64>> {
+    Ok(conn.query_row(
+        "SELECT MAX(published_at) FROM proactive_drafts
+         WHERE group_id = ?1 AND status = 'published'",
+        params![group_id],
+        |row| row.get(0),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        create_proactive_tables(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn create_tables_idempotent() {
+        let conn = test_conn();
+        create_proactive_tables(&conn).unwrap();
+    }
+
+    #[test]
+    fn fire_times_are_upserted() {
+        let conn = test_conn();
+        assert_eq!(last_fired(&conn, "standup").unwrap(), None);
+        record_fired(&conn, "standup", 100).unwrap();
+        record_fired(&conn, "standup", 200).unwrap();
+        assert_eq!(last_fired(&conn, "standup").unwrap(), Some(200));
+    }
+
+    #[test]
+    fn drafts_move_through_their_states() {
+        let conn = test_conn();
+        let draft = create_draft(&conn, "standup", "dev", 100).unwrap();
+        assert_eq!(draft.status, DraftStatus::Drafting);
+        assert!(draft.content.is_empty());
+
+        let draft = transition(
+            &conn,
+            draft.id,
+            &[DraftStatus::Drafting],
+            DraftStatus::Pending,
+            Some("Good morning!"),
+            110,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(draft.status, DraftStatus::Pending);
+        assert_eq!(draft.content, "Good morning!");
+
+        // Wrong source state: nothing changes
+        assert!(transition(
+            &conn,
+            draft.id,
+            &[DraftStatus::Approved],
+            DraftStatus::Published,
+            None,
+            120
+        )
+        .unwrap()
+        .is_none());
+        assert!(transition(
+            &conn,
+            99,
+            &[DraftStatus::Pending],
+            DraftStatus::Rejected,
+            None,
+            120
+        )
+        .unwrap()
+        .is_none());
+
+        transition(
+            &conn,
+            draft.id,
+            &[DraftStatus::Pending],
+            DraftStatus::Approved,
+            None,
+            120,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(open_drafts(&conn).unwrap().len(), 1);
+        let published = transition(
+            &conn,
+            draft.id,
+            &[DraftStatus::Approved],
+            DraftStatus::Published,
+            None,
+            130,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(published.content, "Good morning!");
+        assert_eq!(published.published_at, Some(130));
+        assert!(open_drafts(&conn).unwrap().is_empty());
+
+        assert_eq!(published_since(&conn, "dev", 0).unwrap(), (1, Some(130)));
+        assert_eq!(published_since(&conn, "dev", 131).unwrap(), (0, None));
+        assert_eq!(last_published(&conn, "dev").unwrap(), Some(130));
+        assert_eq!(last_published(&conn, "ops").unwrap(), None);
+    }
+
+    #[test]
+    fn stale_drafts_expire() {
+        let conn = test_conn();
+        let old = create_draft(&conn, "standup", "dev", 100).unwrap();
+        let approved = create_draft(&conn, "standup", "dev", 100).unwrap();
+        transition(
+            &conn,
+            approved.id,
+            &[DraftStatus::Drafting],
+            DraftStatus::Approved,
+            Some("hi"),
+            100,
+        )
+        .unwrap();
+        let fresh = create_draft(&conn, "standup", "dev", 500).unwrap();
+
+        let expired = expire_stale(&conn, 400, 600).unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, old.id);
+        assert_eq!(
+            get_draft(&conn, old.id).unwrap().unwrap().status,
+            DraftStatus::Expired
+        );
+        let open: Vec<i64> = open_drafts(&conn).unwrap().iter().map(|d| d.id).collect();
+        assert_eq!(open, vec![approved.id, fresh.id]);
+    }
+}
@@ -0,0 +1,220 @@
+//! Targeted relay → SQLite re-sync (`snowclaw memory sync`).
+//!
+//! On startup the Nostr channel pulls every social record and the Nostr
+//! memory backend every memory record from the relay. `snowclaw memory
+//! sync` re-runs those pulls on demand, optionally limited to one scope,
+//! to events newer than `--since`, or to one group. A record whose local
+//! copy differs and was updated after the relay copy is reported as a
+//! conflict and left untouched; the same rule applies to startup sync.
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+
+use crate::channels::nostr_memory::NostrMemory;
+use crate::channels::snowclaw_channels;
+use crate::config::Config;
+
+/// Which records `snowclaw memory sync` pulls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncScope {
+    /// Contacts and groups (`snowclaw:memory:npub:*`, `snowclaw:memory:group:*`)
+    Social,
+    /// Agent memories of the `nostr` memory backend
+    Memory,
+    /// Both
+    All,
+}
+
+/// Narrows a re-sync to part of the relay history.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelaySyncFilter {
+    /// Only events created at or after this Unix timestamp.
+    pub since: Option<u64>,
+    /// Only records scoped to this group (`h` tag). Social scope only.
+    pub group: Option<String>,
+}
+
+/// Outcome of one re-sync pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelaySyncReport {
+    /// Events returned by the relay.
+    pub fetched: usize,
+    /// Records written to SQLite.
+    pub upserted: usize,
+    /// Records identical to the local copy.
+    pub unchanged: usize,
+    /// Events that could not be parsed or stored.
+    pub skipped: usize,
+    /// d-tags whose newer local copy was kept.
+    pub conflicts: Vec<String>,
+}
+
+/// What to do with one relay record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncDecision {
+    Upsert,
+    Unchanged,
+    Conflict,
+}
+
+/// Compare a relay record with the local copy. `local_updated` is `None`
+/// when there is no local copy; timestamps are Unix seconds.
+pub fn decide(local_updated: Option<i64>, same_content: bool, remote_updated: i64) -> SyncDecision {
+    match local_updated {
+        None => SyncDecision::Upsert,
+        Some(_) if same_content => SyncDecision::Unchanged,
+        Some(local) if local > remote_updated => SyncDecision::Conflict,
+        Some(_) => SyncDecision::Upsert,
+    }
+}
+
+impl RelaySyncReport {
+    /// Count a decision; `d_tag` is recorded for conflicts.
+    pub fn record(&mut self, decision: SyncDecision, d_tag: &str) {
+        match decision {
+            SyncDecision::Upsert => self.upserted += 1,
+            SyncDecision::Unchanged => self.unchanged += 1,
+            SyncDecision::Conflict => self.conflicts.push(d_tag.to_string()),
+        }
+    }
+
+    /// One-line summary plus one line per conflict.
+    pub fn summary(&self, scope: &str) -> String {
+        let mut out = format!(
+            "{scope}: {} fetched, {} upserted, {} unchanged, {} conflict(s), {} skipped\n",
+            self.fetched,
+            self.upserted,
+            self.unchanged,
+            self.conflicts.len(),
+            self.skipped
+        );
+        for d_tag in &self.conflicts {
+            let _ = writeln!(out, "  kept newer local copy: {d_tag}");
+        }
+        out
+    }
+}
+
+/// Handle `snowclaw memory sync`.
+pub async fn handle_sync(config: &Config, scope: SyncScope, filter: RelaySyncFilter) -> Result<()> {
+    if filter.group.is_some() && scope == SyncScope::Memory {
+        bail!("--group only applies to --scope social");
+    }
+    if matches!(scope, SyncScope::Social | SyncScope::All) {
+        let report = sync_social(config, &filter).await?;
+        print!("{}", report.summary("social"));
+    }
+    if matches!(scope, SyncScope::Memory | SyncScope::All) {
+        if filter.group.is_some() {
+            println!("memory: skipped (--group only applies to social records)");
+        } else {
+            let report = sync_memory(config, &filter).await?;
+            print!("{}", report.summary("memory"));
+        }
+    }
+    Ok(())
+}
+
+async fn sync_social(config: &Config, filter: &RelaySyncFilter) -> Result<RelaySyncReport> {
+    let ns = config
+        .channels_config
+        .nostr
+        .as_ref()
+        .context("Social sync needs [channels_config.nostr]")?;
+    let persist_dir = config.config_path.parent().unwrap_or(Path::new("."));
+    let signer = snowclaw_channels::nostr_signer(ns, persist_dir)
+        .await?
+        .context("Social sync needs a Nostr key (nsec, SNOWCLAW_NSEC or a remote signer)")?;
+    let public_key = signer.public_key();
+
+    let client = snowclaw_channels::nostr_client(signer, &ns.relays).await?;
+    client.connect().await;
+
+    let conn = crate::security::db_encryption::open(&persist_dir.join("social.db"))
+        .context("Failed to open social.db")?;
+    crate::memory::social::create_social_tables(&conn)?;
+    let conn = std::sync::Arc::new(parking_lot::Mutex::new(conn));
+    let mut memory = NostrMemory::with_sqlite(persist_dir, conn);
+    memory.set_relay_client(client.clone(), public_key, &ns.publish);
+
+    let report = memory
+        .sync_social(filter, &mut progress_printer("social"))
+        .await;
+    client.disconnect().await;
+    report
+}
+
+async fn sync_memory(config: &Config, filter: &RelaySyncFilter) -> Result<RelaySyncReport> {
+    let backend = config.memory.backend.as_str();
+    if !matches!(
+        super::snowclaw_backends::classify(backend),
+        super::snowclaw_backends::SnowclawBackendKind::Nostr
+    ) {
+        bail!("Memory sync needs memory backend 'nostr' (configured: '{backend}')");
+    }
+    // Without a memory nsec the backend signs with the agent's signer
+    let memory_nsec = config.memory.nsec.is_some() || std::env::var("SNOWCLAW_NSEC").is_ok();
+    if let (false, Some(ns)) = (memory_nsec, config.channels_config.nostr.as_ref()) {
+        let persist_dir = config.config_path.parent().unwrap_or(Path::new("."));
+        if let Some(signer) = snowclaw_channels::nostr_signer(ns, persist_dir).await? {
+            snowclaw_channels::share_signer(&signer);
+        }
+    }
+    let memory = super::create_nostr_sqlite_memory(
+        &config.memory,
+        &config.workspace_dir,
+        config.api_key.as_deref(),
+    )?;
+    memory
+        .resync(filter.since, &mut progress_printer("memory"))
+        .await
+}
+
+/// Progress line rewritten in place: `social: 120/450`.
+fn progress_printer(scope: &'static str) -> impl FnMut(usize, usize) {
+    move |done, total| {
+        if done == total || done % 50 == 0 {
+            print!("\r{scope}: {done}/{total}");
+            if done == total {
+                println!();
+            }
+            let _ = std::io::stdout().flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_differing_local_copy_is_a_conflict() {
+        assert_eq!(decide(None, false, 100), SyncDecision::Upsert);
+        assert_eq!(decide(Some(200), true, 100), SyncDecision::Unchanged);
+        assert_eq!(decide(Some(200), false, 100), SyncDecision::Conflict);
+        assert_eq!(decide(Some(100), false, 100), SyncDecision::Upsert);
+        assert_eq!(decide(Some(50), false, 100), SyncDecision::Upsert);
+    }
+
+    #[test]
+    fn summary_lists_conflicts() {
+        let mut report = RelaySyncReport {
+            fetched: 3,
+            ..RelaySyncReport::default()
+        };
+        report.record(SyncDecision::Upsert, "snowclaw:memory:group:dev");
+        report.record(SyncDecision::Unchanged, "snowclaw:memory:group:ops");
+        report.record(SyncDecision::Conflict, "snowclaw:memory:npub:npub1x");
+        assert_eq!(
+            report.summary("social"),
+            "social: 3 fetched, 1 upserted, 1 unchanged, 1 conflict(s), 0 skipped\n  \
+             kept newer local copy: snowclaw:memory:npub:npub1x\n"
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! `snowclaw memory reputation`: learned trust of collective memory sources.
+//!
+//! Lists every source with a recorded conflict outcome or owner correction,
+//! its reputation factor and the trust ranking gives it, followed by the
+//! reputation changes of the last `--days` days. `--json` prints the same
+//! report as a JSON object.
+
+use anyhow::Result;
+use serde::Serialize;
+use snow_memory::types::{ReputationChange, SourceReputation};
+use snow_memory::SqliteMemoryIndex;
+
+use crate::config::{CollectiveMemoryConfig, Config};
+
+/// One source in the report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceRow {
+    #[serde(flatten)]
+    pub reputation: SourceReputation,
+    /// Trust from `source_preferences`; `None` when the source is not listed.
+    pub configured_trust: Option<f64>,
+    /// Trust ranking uses: configured trust scaled by the factor.
+    pub effective_trust: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReputationReport {
+    /// Least trusted first.
+    pub sources: Vec<SourceRow>,
+    /// Newest first.
+    pub changes: Vec<ReputationChange>,
+}
+
+/// Handle `snowclaw memory reputation`.
+pub fn handle_reputation(config: &Config, days: u64, json: bool) -> Result<()> {
+    let Some(index) = super::search_cli::open_collective_index(config)? else {
+        println!("Collective memory is not enabled or has no database yet.");
+        return Ok(());
+    };
+    let since = chrono::Utc::now()
+        .timestamp()
+        .unsigned_abs()
+        .saturating_sub(days.saturating_mul(86_400));
+    let report = build_report(&index, &config.memory.collective, since)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        if !config.memory.collective.reputation.enabled {
+            println!("Note: [memory.collective.reputation] is disabled; nothing new is learned.");
+        }
+        print!("{}", render(&report, days));
+    }
+    Ok(())
+}
+
+/// Learned reputations, and the changes at or after `since`.
+pub fn build_report(
+    index: &SqliteMemoryIndex,
+    config: &CollectiveMemoryConfig,
+    since: u64,
+) -> Result<ReputationReport> {
+    let sources = index
+        .list_reputations()?
+        .into_iter()
+        .map(|reputation| {
+            let configured_trust = config
+                .source_preferences
+                .iter()
+                .find(|s| s.npub.as_deref() == Some(reputation.source.as_str()))
+                .map(|s| s.trust);
+            SourceRow {
+                effective_trust: configured_trust.map(|t| t * reputation.factor),
+                configured_trust,
+                reputation,
+            }
+        })
+        .collect();
+    Ok(ReputationReport {
+        sources,
+        changes: index.reputation_changes_since(since)?,
+    })
+}
+
+fn render(report: &ReputationReport, days: u64) -> String {
+    use std::fmt::Write as _;
+
+    if report.sources.is_empty() {
+        return "No source reputation learned yet.\n".to_string();
+    }
+    let mut out = String::from("Source reputation:\n");
+    for row in &report.sources {
+        let r = &row.reputation;
+        let trust = match (row.configured_trust, row.effective_trust) {
+            (Some(configured), Some(effective)) => {
+                format!("trust {configured:.2} -> {effective:.2}")
+            }
+            _ => "not in source_preferences".to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "  {}  factor {:.2}  {trust}  lost {}  won {}  corrected {}",
+            r.source, r.factor, r.conflicts_lost, r.conflicts_won, r.corrections
+        );
+    }
+
+    let _ = writeln!(out, "\nChanges in the last {days} day(s):");
+    if report.changes.is_empty() {
+        out.push_str("  none\n");
+    }
+    for change in &report.changes {
+        #[allow(clippy::cast_possible_wrap)]
+        let at = chrono::DateTime::from_timestamp(change.at as i64, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "  {at}  {}  {} on {}  {:.2} -> {:.2}",
+            change.source,
+            change.reason.as_str(),
+            change.topic,
+            change.factor_before,
+            change.factor_after
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::snowclaw_schema::CollectiveSourceEntry;
+    use snow_memory::types::ReputationReason;
+
+    #[test]
+    fn report_scales_configured_trust() {
+        let index = SqliteMemoryIndex::open_in_memory().unwrap();
+        for (source, after) in [("alice", 0.8), ("carol", 0.8)] {
+            index
+                .record_reputation_change(&ReputationChange {
+                    source: source.into(),
+                    reason: ReputationReason::ConflictLost,
+                    topic: "infra:staging".into(),
+                    factor_before: 1.0,
+                    factor_after: after,
+                    at: 1_700_000_000,
+                })
+                .unwrap();
+        }
+        let config = CollectiveMemoryConfig {
+            source_preferences: vec![CollectiveSourceEntry {
+                npub: Some("alice".into()),
+                group: None,
+                trust: 0.5,
+            }],
+            ..CollectiveMemoryConfig::default()
+        };
+
+        let report = build_report(&index, &config, 0).unwrap();
+        assert_eq!(report.sources[0].effective_trust, Some(0.4));
+        assert_eq!(report.sources[1].configured_trust, None);
+        assert_eq!(report.changes.len(), 2);
+
+        let text = render(&report, 7);
+        assert!(text.contains("alice  factor 0.80  trust 0.50 -> 0.40  lost 1  won 0"));
+        assert!(text.contains("carol  factor 0.80  not in source_preferences"));
+        assert!(text.contains("conflict_lost on infra:staging  1.00 -> 0.80"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["sources"][0]["source"], "alice");
+        assert_eq!(json["changes"][0]["reason"], "conflict_lost");
+
+        let empty = ReputationReport {
+            sources: vec![],
+            changes: vec![],
+        };
+        assert_eq!(render(&empty, 7), "No source reputation learned yet.\n");
+    }
+}
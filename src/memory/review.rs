@@ -0,0 +1,433 @@
+//! Spaced review of stale collective memories.
+//!
+//! Runs as the `memory_review` daemon component when
+//! `[memory.collective.review]` is enabled. Each pass takes the shared
+//! memories due for review, collects recent indexed messages and document
+//! chunks about them from `social.db`, and asks the LLM whether each memory
+//! still holds. [`CollectiveMemory::apply_review`] stores and publishes the
+//! result.
+
+use super::collective::{CollectiveMemory, ReviewVerdict};
+use super::{doc_index, message_index};
+use crate::config::{CollectiveReviewConfig, Config};
+use crate::providers::{self, Provider};
+use crate::security::db_encryption;
+use anyhow::Result;
+use rusqlite::Connection;
+use snow_memory::types::Memory as SnowMemory;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const REVIEW_SYSTEM_PROMPT: &str = "You maintain a shared memory store for an AI agent. \
+You are given one stored memory and recent messages and documents that mention its topic. \
+Decide whether the memory is still accurate.\n\
+Reply with a single JSON object: {\"verdict\": \"refresh\" | \"demote\" | \"supersede\", \"reason\": \"...\"}\n\
+- refresh: still accurate, or nothing contradicts it\n\
+- demote: partly outdated, or only true for a narrower audience\n\
+- supersede: contradicted or replaced by newer information";
+
+/// Evidence snippets are cut to this many characters.
+const EVIDENCE_SNIPPET_CHARS: usize = 400;
+
+/// LLM decision for one memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewOutcome {
+    pub verdict: ReviewVerdict,
+    pub reason: String,
+}
+
+/// Counts for one review pass.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReviewStats {
+    pub refreshed: usize,
+    pub demoted: usize,
+    pub superseded: usize,
+    pub failed: usize,
+}
+
+/// User message asking the LLM to re-validate `memory`.
+pub fn build_prompt(memory: &SnowMemory, evidence: &[String]) -> String {
+    let age_days = now_unix().saturating_sub(memory.created_at) / 86_400;
+    let mut prompt = format!(
+        "Memory (topic `{}`, {}, stored {age_days} days ago):\n{}",
+        memory.topic, memory.tier, memory.summary
+    );
+    if !memory.detail.is_empty() {
+        prompt.push_str("\n\n");
+        prompt.push_str(&memory.detail);
+    }
+    prompt.push_str("\n\nRecent evidence:\n");
+    if evidence.is_empty() {
+        prompt.push_str("(none found)\n");
+    }
+    for item in evidence {
+        prompt.push_str("- ");
+        prompt.push_str(item);
+        prompt.push('\n');
+    }
+    prompt
+}
+
+/// Parse the LLM reply. Accepts the JSON object anywhere in the text.
+pub fn parse_reply(reply: &str) -> Option<ReviewOutcome> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    let value: serde_json::Value = serde_json::from_str(reply.get(start..=end)?).ok()?;
+    let verdict = match value["verdict"]
+        .as_str()?
+        .trim()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "refresh" | "confirm" | "keep" => ReviewVerdict::Refresh,
+        "demote" => ReviewVerdict::Demote,
+        "supersede" | "superseded" => ReviewVerdict::Supersede,
+        _ => return None,
+    };
+    Some(ReviewOutcome {
+        verdict,
+        reason: value["reason"]
+            .as_str()
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    })
+}
+
+/// Recent indexed messages and document chunks matching the memory, newest
+/// messages first.
+pub fn gather_evidence(conn: &Connection, memory: &SnowMemory, limit: usize) -> Vec<String> {
+    let query = format!(
+        "{} {}",
+        memory.topic.replace([':', '/', '_', '-'], " "),
+        memory.summary
+    );
+    let mut messages = message_index::search_messages(conn, &query, limit).unwrap_or_default();
+    messages.sort_by_key(|m| std::cmp::Reverse(m.created_at));
+    let docs = doc_index::search_docs(conn, &query, limit).unwrap_or_default();
+
+    let messages = messages.into_iter().map(|m| {
+        let when = chrono::DateTime::from_timestamp(m.created_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        format!(
+            "[message {when}] {}",
+            snippet(&m.content, EVIDENCE_SNIPPET_CHARS)
+        )
+    });
+    let docs = docs.into_iter().map(|d| {
+        format!(
+            "[doc {}] {}",
+            d.path,
+            snippet(&d.content, EVIDENCE_SNIPPET_CHARS)
+        )
+    });
+    messages.chain(docs).take(limit).collect()
+}
+
+/// Memories due for review, each with its review prompt. Runs before any
+/// LLM call so the `social.db` connection is not held across awaits.
+pub fn prepare_due(
+    memory: &CollectiveMemory,
+    social: Option<&Connection>,
+    config: &CollectiveReviewConfig,
+) -> Result<Vec<(SnowMemory, String)>> {
+    let stale_after = config.stale_after_days.max(1) * 86_400;
+    let due = memory.due_for_review(stale_after, config.batch_size, now_unix())?;
+    Ok(due
+        .into_iter()
+        .map(|entry| {
+            let evidence = social
+                .map(|conn| gather_evidence(conn, &entry, config.evidence_limit))
+                .unwrap_or_default();
+            let prompt = build_prompt(&entry, &evidence);
+            (entry, prompt)
+        })
+        .collect())
+}
+
+/// Ask the LLM about each prepared memory and apply its verdict. Failures
+/// are counted and the memory is retried on the next pass.
+pub async fn review_due(
+    memory: &CollectiveMemory,
+    provider: &dyn Provider,
+    model: &str,
+    due: Vec<(SnowMemory, String)>,
+) -> ReviewStats {
+    let mut stats = ReviewStats::default();
+
+    for (entry, prompt) in due {
+        let outcome = match provider
+            .chat_with_system(Some(REVIEW_SYSTEM_PROMPT), &prompt, model, 0.0)
+            .await
+        {
+            Ok(reply) => parse_reply(&reply),
+            Err(e) => {
+                tracing::warn!("memory review: LLM call failed for '{}': {e}", entry.topic);
+                None
+            }
+        };
+        let Some(outcome) = outcome else {
+            stats.failed += 1;
+            continue;
+        };
+
+        match memory.apply_review(&entry, outcome.verdict, &outcome.reason, now_unix()) {
+            Ok(_) => {
+                tracing::info!(
+                    "memory review: {} '{}' {}",
+                    verdict_name(outcome.verdict),
+                    entry.topic,
+                    outcome.reason
+                );
+                match outcome.verdict {
+                    ReviewVerdict::Refresh => stats.refreshed += 1,
+                    ReviewVerdict::Demote => stats.demoted += 1,
+                    ReviewVerdict::Supersede => stats.superseded += 1,
+                }
+            }
+            Err(e) => {
+                tracing::warn!("memory review: failed to update '{}': {e}", entry.topic);
+                stats.failed += 1;
+            }
+        }
+    }
+    stats
+}
+
+/// Daemon worker: review due memories every `interval_minutes`.
+pub async fn run(config: Config) -> Result<()> {
+    let review = config.memory.collective.review.clone();
+    let nsec = config
+        .memory
+        .nsec
+        .clone()
+        .or_else(|| std::env::var("SNOWCLAW_NSEC").ok());
+    let memory = CollectiveMemory::new_with_relay(
+        &config.workspace_dir,
+        &config.memory.collective,
+        nsec.as_deref(),
+    )?;
+
+    let (provider, model) = background_provider(&config, review.model.as_deref())?;
+
+    let social_path = social_db_path(&config);
+
+    let mut interval =
+        tokio::time::interval(Duration::from_secs(review.interval_minutes.max(1) * 60));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        interval.tick().await;
+        let due = {
+            let social = open_social_db(&social_path, "memory review");
+            prepare_due(&memory, social.as_ref(), &review)?
+        };
+        let stats = review_due(&memory, provider.as_ref(), &model, due).await;
+        if stats != ReviewStats::default() {
+            tracing::info!(
+                "memory review: {} refreshed, {} demoted, {} superseded, {} failed",
+                stats.refreshed,
+                stats.demoted,
+                stats.superseded,
+                stats.failed
+            );
+        }
+        crate::health::mark_component_ok("memory_review");
+    }
+}
+
+/// `social.db` next to the config file, written by the Nostr channel.
+pub(crate) fn social_db_path(config: &Config) -> PathBuf {
+    config
+        .config_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("social.db")
+}
+
+/// Open `social.db` for one pass of the background `job`; `None` when it
+/// doesn't exist (yet) or can't be opened. Opened per pass: the Nostr
+/// channel owns social.db and may create it later.
+pub(crate) fn open_social_db(path: &Path, job: &str) -> Option<Connection> {
+    if !path.exists() {
+        return None;
+    }
+    db_encryption::open(path)
+        .map_err(|e| tracing::warn!("{job}: social.db unavailable: {e}"))
+        .ok()
+}
+
+/// Provider and model for a background memory job: `model` when set,
+/// otherwise the configured default model.
+pub(crate) fn background_provider(
+    config: &Config,
+    model: Option<&str>,
+) -> Result<(Box<dyn Provider>, String)> {
+    let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
+    let provider = providers::create_resilient_provider_with_options(
+        provider_name,
+        config.api_key.as_deref(),
+        config.api_url.as_deref(),
+        &config.reliability,
+        &providers::ProviderRuntimeOptions {
+            auth_profile_override: None,
+            provider_api_url: config.api_url.clone(),
+            provider_transport: config.effective_provider_transport(),
+            zeroclaw_dir: config.config_path.parent().map(std::path::PathBuf::from),
+            secrets_encrypt: config.secrets.encrypt,
+            reasoning_enabled: config.runtime.reasoning_enabled,
+            reasoning_level: config.effective_provider_reasoning_level(),
+            custom_provider_api_mode: config.provider_api.map(|mode| mode.as_compatible_mode()),
+            custom_provider_auth_header: config.effective_custom_provider_auth_header(),
+            max_tokens_override: None,
+            model_support_vision: config.model_support_vision,
+        },
+    )?;
+    let model = model.map(str::to_string).unwrap_or_else(|| {
+        crate::config::resolve_default_model_id(
+            config.default_model.as_deref(),
+            Some(provider_name),
+        )
+    });
+    Ok((provider, model))
+}
+
+fn verdict_name(verdict: ReviewVerdict) -> &'static str {
+    match verdict {
+        ReviewVerdict::Refresh => "refreshed",
+        ReviewVerdict::Demote => "demoted",
+        ReviewVerdict::Supersede => "superseded",
+    }
+}
+
+/// `text` with whitespace collapsed, cut to `max_chars` characters.
+pub(crate) fn snippet(text: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(max_chars) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text,
+    }
+}
+
+pub(crate) fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CollectiveMemoryConfig;
+    use async_trait::async_trait;
+    use snow_memory::types::MemoryTier;
+
+    struct ScriptedProvider(&'static str);
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn parses_verdicts() {
+        let outcome =
+            parse_reply("Sure:\n```json\n{\"verdict\": \"Supersede\", \"reason\": \"moved\"}\n```")
+                .unwrap();
+        assert_eq!(outcome.verdict, ReviewVerdict::Supersede);
+        assert_eq!(outcome.reason, "moved");
+        assert_eq!(
+            parse_reply("{\"verdict\":\"refresh\"}").unwrap().verdict,
+            ReviewVerdict::Refresh
+        );
+        assert!(parse_reply("{\"verdict\":\"maybe\"}").is_none());
+        assert!(parse_reply("no json here").is_none());
+    }
+
+    #[test]
+    fn gathers_messages_and_docs() {
+        let conn = Connection::open_in_memory().unwrap();
+        message_index::create_message_tables(&conn).unwrap();
+        doc_index::create_doc_tables(&conn).unwrap();
+        message_index::index_message(
+            &conn,
+            &message_index::IndexableMessage {
+                event_id: "e1".into(),
+                sender_hex: "aa".into(),
+                group_id: Some("dev".into()),
+                content: "the staging relay moved to relay2".into(),
+                created_at: 1_700_000_000,
+                kind: 9,
+            },
+        )
+        .unwrap();
+        doc_index::index_content(
+            &conn,
+            "notes/relays.md",
+            "staging relay is relay1",
+            "document",
+        )
+        .unwrap();
+
+        let memory = SnowMemory {
+            id: "m".into(),
+            tier: MemoryTier::Public,
+            topic: "infra/staging-relay".into(),
+            summary: "staging relay".into(),
+            detail: String::new(),
+            context: None,
+            source: "self".into(),
+            model: String::new(),
+            confidence: 0.8,
+            supersedes: None,
+            version: 1,
+            tags: vec![],
+            created_at: 0,
+        };
+        let evidence = gather_evidence(&conn, &memory, 5);
+        assert_eq!(evidence.len(), 2);
+        assert!(evidence[0].starts_with("[message 2023-11-14]"));
+        assert!(evidence[1].starts_with("[doc notes/relays.md]"));
+        assert!(build_prompt(&memory, &evidence).contains("relay2"));
+    }
+
+    #[tokio::test]
+    async fn review_pass_applies_llm_verdict() {
+        let mem = CollectiveMemory::new_in_memory(&CollectiveMemoryConfig::default()).unwrap();
+        mem.store_with_tier(
+            "infra:relay",
+            "Staging relay is relay1",
+            crate::memory::MemoryCategory::Core,
+            MemoryTier::Public,
+        )
+        .await
+        .unwrap();
+        let config = CollectiveReviewConfig {
+            stale_after_days: 1,
+            ..CollectiveReviewConfig::default()
+        };
+
+        // Nothing is stale yet.
+        let provider = ScriptedProvider("{\"verdict\": \"demote\", \"reason\": \"team only\"}");
+        let due = prepare_due(&mem, None, &config).unwrap();
+        assert!(due.is_empty());
+
+        // Backdate it by recording a confirming review at t=0.
+        let stored = mem.due_for_review(0, 1, now_unix()).unwrap().remove(0);
+        mem.apply_review(&stored, ReviewVerdict::Refresh, "", 0)
+            .unwrap();
+        let due = prepare_due(&mem, None, &config).unwrap();
+        assert!(due[0].1.contains("Staging relay is relay1"));
+        let stats = review_due(&mem, &provider, "m", due).await;
+        assert_eq!(stats.demoted, 1);
+    }
+}
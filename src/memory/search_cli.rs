@@ -0,0 +1,355 @@
+//! `snowclaw memory search`: query the memory stack from the terminal.
+//!
+//! Social records, indexed messages and documents come from `social.db`
+//! through [`super::unified_search`]; collective memories come from the
+//! `[memory.collective]` index, ranked by source trust (with any learned
+//! reputation) and model tier.
+//! Each hit carries its score and where it came from (author, group,
+//! document path, model, and the relays a collective memory was published
+//! to); `--json` prints the same rows as a JSON array.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use snow_memory::types::{MemoryTier, SearchResult};
+use snow_memory::SqliteMemoryIndex;
+use std::path::{Path, PathBuf};
+
+use super::unified_search::{self, UnifiedHit};
+use crate::config::Config;
+
+/// Which part of the memory stack `snowclaw memory search` queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchSource {
+    /// Contacts in `social.db`
+    Social,
+    /// Indexed channel messages
+    Messages,
+    /// Indexed documents
+    Docs,
+    /// Collective memories shared over Nostr
+    Collective,
+}
+
+impl SearchSource {
+    /// [`UnifiedHit::source`] label of the hits this source covers.
+    fn unified_label(self) -> Option<&'static str> {
+        match self {
+            Self::Social => Some("social"),
+            Self::Messages => Some("message"),
+            Self::Docs => Some("document"),
+            Self::Collective => None,
+        }
+    }
+}
+
+/// Options of one `snowclaw memory search` run.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub query: String,
+    pub source: Option<SearchSource>,
+    pub group: Option<String>,
+    pub limit: usize,
+    pub json: bool,
+}
+
+/// One search result with its score and provenance.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchRow {
+    /// `social`, `messages`, `docs`, `collective` or `memory`.
+    pub source: &'static str,
+    pub score: f64,
+    pub content: String,
+    /// Event id, memory id, document id or contact pubkey.
+    pub id: String,
+    /// Sender, contact or publishing agent (hex pubkey).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Unix timestamp of the message or memory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<i64>,
+    /// Document path, or collective memory topic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Relays that accepted a collective memory published by this agent.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub relays: Vec<String>,
+    /// How the collective ranking arrived at `score`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+}
+
+impl SearchRow {
+    fn from_unified(hit: UnifiedHit) -> Self {
+        let score = hit.score();
+        let row = |source, id: String, content: String| Self {
+            source,
+            score,
+            content,
+            id,
+            author: None,
+            group: None,
+            created_at: None,
+            path: None,
+            model: None,
+            relays: Vec::new(),
+            explanation: None,
+        };
+        match hit {
+            UnifiedHit::Memory(entry) => Self {
+                path: Some(entry.key.clone()),
+                ..row("memory", entry.id, entry.content)
+            },
+            UnifiedHit::Social(npub) => Self {
+                author: Some(npub.hex_pubkey.clone()),
+                group: npub.first_seen_group,
+                created_at: Some(npub.last_interaction),
+                ..row("social", npub.hex_pubkey, npub.display_name)
+            },
+            UnifiedHit::Message(msg) => Self {
+                author: Some(msg.sender_hex),
+                group: msg.group_id,
+                created_at: Some(msg.created_at),
+                ..row("messages", msg.event_id, msg.content)
+            },
+            UnifiedHit::Document(doc) => Self {
+                path: Some(format!("{}#{}", doc.path, doc.chunk_index)),
+                ..row("docs", doc.id, doc.content)
+            },
+        }
+    }
+
+    fn from_collective(result: SearchResult, relays: Vec<String>) -> Self {
+        let memory = result.memory;
+        let group = match memory.tier {
+            MemoryTier::Group(group) => Some(group),
+            MemoryTier::Public | MemoryTier::Private(_) => None,
+        };
+        #[allow(clippy::cast_possible_wrap)]
+        let created_at = memory.created_at as i64;
+        Self {
+            source: "collective",
+            score: result.effective_score,
+            content: memory.summary,
+            id: memory.id,
+            author: Some(memory.source),
+            group,
+            created_at: Some(created_at),
+            path: Some(memory.topic),
+            model: Some(memory.model),
+            relays,
+            explanation: Some(result.explanation),
+        }
+    }
+}
+
+/// Handle `snowclaw memory search`.
+pub fn handle_search(config: &Config, options: &SearchOptions) -> Result<()> {
+    let rows = search(config, options)?;
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+    } else {
+        print!("{}", render(&rows));
+    }
+    Ok(())
+}
+
+/// Run the search: up to `limit` rows from each queried source, each
+/// source ranked by its own score.
+pub fn search(config: &Config, options: &SearchOptions) -> Result<Vec<SearchRow>> {
+    let mut rows = Vec::new();
+
+    let labels: Vec<&str> = match options.source {
+        Some(source) => source.unified_label().into_iter().collect(),
+        None => unified_search::ALL_SOURCES.to_vec(),
+    };
+    let social_db = config
+        .config_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("social.db");
+    if !labels.is_empty() && social_db.exists() {
+        let conn =
+            crate::security::db_encryption::open(&social_db).context("Failed to open social.db")?;
+        for label in labels {
+            // Over-fetch so group filtering still fills the limit.
+            let hits = unified_search::unified_recall_from(
+                &conn,
+                &options.query,
+                options.limit * 4,
+                &[label],
+            )?;
+            rows.extend(
+                hits.into_iter()
+                    .map(SearchRow::from_unified)
+                    .filter(|row| in_group(row, options.group.as_deref()))
+                    .take(options.limit),
+            );
+        }
+    }
+
+    if matches!(options.source, None | Some(SearchSource::Collective)) {
+        if let Some(index) = open_collective_index(config)? {
+            let tier = options.group.as_ref().map(|group| format!("group:{group}"));
+            let results = index.ranked_search(
+                &options.query,
+                tier.as_deref(),
+                &super::collective::ranking_config(&index, &config.memory.collective),
+                options.limit,
+            )?;
+            rows.extend(results.into_iter().map(|result| {
+                let relays = super::collective::published_relays(&index, &result.memory.id);
+                SearchRow::from_collective(result, relays)
+            }));
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Whether `row` belongs to `group`. Documents and core memories are not
+/// scoped to groups and are dropped when one is given.
+fn in_group(row: &SearchRow, group: Option<&str>) -> bool {
+    group.is_none_or(|group| row.group.as_deref() == Some(group))
+}
+
+/// The collective index, if collective memory is enabled and has a database.
+pub(crate) fn open_collective_index(config: &Config) -> Result<Option<SqliteMemoryIndex>> {
+    let collective = &config.memory.collective;
+    if !collective.enabled {
+        return Ok(None);
+    }
+    let db_path = if Path::new(&collective.db_path).is_absolute() {
+        PathBuf::from(&collective.db_path)
+    } else {
+        config.workspace_dir.join(&collective.db_path)
+    };
+    if !db_path.exists() {
+        return Ok(None);
+    }
+    let index = SqliteMemoryIndex::open(&db_path).context("Failed to open collective memory DB")?;
+    Ok(Some(index))
+}
+
+fn render(rows: &[SearchRow]) -> String {
+    use std::fmt::Write as _;
+
+    if rows.is_empty() {
+        return "No results.\n".to_string();
+    }
+    let mut out = String::new();
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "[{}] {:.3}  {}",
+            row.source,
+            row.score,
+            super::cli::truncate_content(&row.content, 100)
+        );
+        let mut provenance = vec![format!("id={}", row.id)];
+        if let Some(author) = &row.author {
+            provenance.push(format!("from={author}"));
+        }
+        if let Some(group) = &row.group {
+            provenance.push(format!("group={group}"));
+        }
+        if let Some(path) = &row.path {
+            provenance.push(format!("at={path}"));
+        }
+        if let Some(model) = &row.model {
+            provenance.push(format!("model={model}"));
+        }
+        if !row.relays.is_empty() {
+            provenance.push(format!("relays={}", row.relays.join(",")));
+        }
+        if let Some(created_at) = row.created_at {
+            if let Some(time) = chrono::DateTime::from_timestamp(created_at, 0) {
+                provenance.push(time.format("%Y-%m-%d %H:%M").to_string());
+            }
+        }
+        let _ = writeln!(out, "    {}", provenance.join("  "));
+        if let Some(explanation) = &row.explanation {
+            let _ = writeln!(out, "    {explanation}");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::message_index::MessageHit;
+    use snow_memory::types::Memory as SnowMemory;
+
+    fn message(group: Option<&str>) -> SearchRow {
+        SearchRow::from_unified(UnifiedHit::Message(MessageHit {
+            event_id: "evt1".into(),
+            sender_hex: "aabb".into(),
+            group_id: group.map(str::to_string),
+            content: "Rust borrow checker".into(),
+            created_at: 1_700_000_000,
+            kind: 9,
+            score: 2.5,
+        }))
+    }
+
+    #[test]
+    fn rows_carry_provenance_and_filter_by_group() {
+        let row = message(Some("dev"));
+        assert_eq!(row.source, "messages");
+        assert_eq!(row.author.as_deref(), Some("aabb"));
+        assert!(in_group(&row, Some("dev")));
+        assert!(!in_group(&row, Some("ops")));
+        assert!(!in_group(&message(None), Some("dev")));
+        assert!(in_group(&message(None), None));
+
+        let result = SearchResult {
+            memory: SnowMemory {
+                id: "m1".into(),
+                tier: MemoryTier::Group("dev".into()),
+                topic: "rust/errors".into(),
+                summary: "Use anyhow".into(),
+                detail: String::new(),
+                context: None,
+                source: "ccdd".into(),
+                model: "test/model".into(),
+                confidence: 0.9,
+                supersedes: None,
+                version: 1,
+                tags: vec![],
+                created_at: 1_700_000_000,
+            },
+            relevance: 0.8,
+            source_trust: 1.0,
+            model_tier: 1,
+            tier_weight: 1.0,
+            recency: 1.0,
+            usage: Default::default(),
+            usage_score: 0.0,
+            effective_score: 0.8,
+            explanation: "relevance 0.80 x trust 1.00".into(),
+        };
+        let collective = SearchRow::from_collective(result, vec!["wss://fast.example.com".into()]);
+        assert_eq!(collective.group.as_deref(), Some("dev"));
+        assert_eq!(collective.path.as_deref(), Some("rust/errors"));
+
+        let json = serde_json::to_value([&row, &collective]).unwrap();
+        assert_eq!(json[0]["source"], "messages");
+        assert!(json[0].get("model").is_none());
+        assert_eq!(json[1]["model"], "test/model");
+        assert!(json[0].get("relays").is_none());
+        assert_eq!(json[1]["relays"][0], "wss://fast.example.com");
+
+        let text = render(&[row, collective]);
+        assert!(text.contains("[messages] 2.500  Rust borrow checker"));
+        assert!(text.contains(
+            "from=ccdd  group=dev  at=rust/errors  model=test/model  relays=wss://fast.example.com"
+        ));
+        assert_eq!(render(&[]), "No results.\n");
+    }
+}
@@ -8,7 +8,7 @@
 
 use anyhow::Result;
 use chrono::Local;
-use rusqlite::{params, Connection};
+use rusqlite::params;
 use std::fmt::Write;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -32,7 +32,7 @@ pub fn export_snapshot(workspace_dir: &Path) -> Result<usize> {
         return Ok(0);
     }
 
-    let conn = Connection::open(&db_path)?;
+    let conn = crate::security::db_encryption::open(&db_path)?;
     conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")?;
 
     let mut stmt = conn.prepare(
@@ -111,7 +111,7 @@ pub fn hydrate_from_snapshot(workspace_dir: &Path) -> Result<usize> {
     fs::create_dir_all(&db_dir)?;
 
     let db_path = db_dir.join("brain.db");
-    let conn = Connection::open(&db_path)?;
+    let conn = crate::security::db_encryption::open(&db_path)?;
     conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")?;
 
     // Initialize schema (same as SqliteMemory::init_schema)
@@ -262,6 +262,7 @@ fn parse_snapshot(input: &str) -> Vec<(String, String)> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusqlite::Connection;
     use tempfile::TempDir;
 
     #[test]
@@ -11,6 +11,7 @@
 use super::traits::{Memory, MemoryCategory};
 use async_trait::async_trait;
 use snow_memory::types::MemoryTier;
+use std::future::Future;
 
 /// Context for tier-aware recall filtering.
 ///
@@ -27,6 +28,79 @@ pub struct RecallContext {
     pub group_id: Option<String>,
 }
 
+impl RecallContext {
+    /// Context for a message in group `group_id` on `channel`.
+    pub fn group(channel: &str, group_id: &str) -> Self {
+        Self {
+            is_main_session: false,
+            channel: Some(channel.to_string()),
+            group_id: Some(group_id.to_string()),
+        }
+    }
+
+    /// Context for a direct conversation on `channel`; `with_owner` marks
+    /// the owner's own DM session.
+    pub fn direct(channel: &str, with_owner: bool) -> Self {
+        Self {
+            is_main_session: with_owner,
+            channel: Some(channel.to_string()),
+            group_id: None,
+        }
+    }
+}
+
+/// Who reads the reply a piece of context goes into.
+///
+/// Owner notes and private memories are only put in front of the agent in
+/// the owner's DMs, so they cannot end up in a reply to anyone else.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Audience {
+    /// A DM with the owner.
+    OwnerDm,
+    /// A DM with anyone else.
+    Dm,
+    /// A group conversation.
+    Group(String),
+}
+
+impl Audience {
+    /// Whether owner notes and private memories may be shown.
+    pub fn is_owner(&self) -> bool {
+        matches!(self, Self::OwnerDm)
+    }
+
+    /// Group of a group conversation.
+    pub fn group_id(&self) -> Option<&str> {
+        match self {
+            Self::Group(id) => Some(id),
+            Self::OwnerDm | Self::Dm => None,
+        }
+    }
+
+    /// Recall scope for this audience on `channel`.
+    pub fn recall_context(&self, channel: &str) -> RecallContext {
+        match self {
+            Self::Group(id) => RecallContext::group(channel, id),
+            Self::OwnerDm | Self::Dm => RecallContext::direct(channel, self.is_owner()),
+        }
+    }
+}
+
+tokio::task_local! {
+    static CURRENT_CONTEXT: Option<RecallContext>;
+}
+
+/// Run `fut` with `context` as the conversation scope of any memory
+/// store or recall it makes. `None` leaves memory unscoped.
+pub async fn scope<F: Future>(context: Option<RecallContext>, fut: F) -> F::Output {
+    CURRENT_CONTEXT.scope(context, fut).await
+}
+
+/// Conversation scope of the current task, if it runs inside [`scope`].
+pub fn current_context() -> Option<RecallContext> {
+    CURRENT_CONTEXT.try_with(Clone::clone).ok().flatten()
+}
+
 /// Snowclaw-specific extensions to the `Memory` trait.
 ///
 /// Provides tiered storage and promotion via a blanket impl.
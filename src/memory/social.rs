@@ -6,11 +6,13 @@
 //! live in `nostr_sqlite.rs`.
 
 use anyhow::{Context, Result};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::fmt::Write as _;
 use tracing::debug;
 
+use super::snowclaw_ext::Audience;
+
 // ── Data structures ──────────────────────────────────────────────
 
 /// A social contact's profile and context, stored in `social_npubs`.
@@ -104,7 +106,59 @@ pub fn create_social_tables(conn: &Connection) -> Result<()> {
             INSERT INTO social_fts(rowid, hex_pubkey, display_name, notes, owner_notes)
             VALUES (new.rowid, new.hex_pubkey, new.display_name,
                     COALESCE(new.notes_json, ''), COALESCE(new.owner_notes_json, ''));
-        END;",
+        END;
+
+        -- Group onboarding status (one row per member per group)
+        CREATE TABLE IF NOT EXISTS social_onboarding (
+            group_id TEXT NOT NULL,
+            hex_pubkey TEXT NOT NULL,
+            status TEXT NOT NULL,
+            method TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (group_id, hex_pubkey)
+        );
+
+        -- Rolling history of kind 31121 agent state events (ours and peers')
+        CREATE TABLE IF NOT EXISTS social_agent_states (
+            hex_pubkey TEXT NOT NULL,
+            d_tag TEXT NOT NULL,
+            status TEXT NOT NULL,
+            version TEXT,
+            content TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (hex_pubkey, d_tag, created_at)
+        );
+
+        -- Last group message read by the owner or the agent (catch-up marker)
+        CREATE TABLE IF NOT EXISTS social_read_markers (
+            reader TEXT NOT NULL,
+            group_id TEXT NOT NULL,
+            event_id TEXT NOT NULL,
+            read_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (reader, group_id)
+        );
+
+        -- Inactive contacts without notes, as zlib-compressed JSON. Not in
+        -- social_fts; restored into social_npubs when seen again.
+        CREATE TABLE IF NOT EXISTS social_npubs_archive (
+            hex_pubkey TEXT PRIMARY KEY,
+            display_name TEXT NOT NULL,
+            last_interaction INTEGER NOT NULL,
+            archived_at INTEGER NOT NULL,
+            data BLOB NOT NULL
+        );
+
+        -- Reply/mention counts between contacts, per group
+        CREATE TABLE IF NOT EXISTS social_interactions (
+            group_id TEXT NOT NULL,
+            from_hex TEXT NOT NULL,
+            to_hex TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            count INTEGER NOT NULL,
+            last_at INTEGER NOT NULL,
+            PRIMARY KEY (group_id, from_hex, to_hex, kind)
+        );",
     )
     .context("failed to create social tables")?;
 
@@ -195,6 +249,8 @@ pub fn add_npub_note(
         "notes_json"
     };
 
+    restore_archived_npub(conn, hex_pubkey)?;
+
     // Read current notes
     let sql = format!("SELECT {column} FROM social_npubs WHERE hex_pubkey = ?1");
     let current: Option<String> = conn
@@ -257,7 +313,9 @@ pub fn set_group_purpose(conn: &Connection, group_id: &str, purpose: &str) -> Re
 }
 
 /// Record a member in a group's members JSON array (deduplicates).
-pub fn record_group_member(conn: &Connection, group_id: &str, hex_pubkey: &str) -> Result<()> {
+///
+/// Returns `true` when the member was not recorded for the group before.
+pub fn record_group_member(conn: &Connection, group_id: &str, hex_pubkey: &str) -> Result<bool> {
     let current: Option<String> = conn
         .query_row(
             "SELECT members_json FROM social_groups WHERE group_id = ?1",
@@ -276,17 +334,384 @@ pub fn record_group_member(conn: &Connection, group_id: &str, hex_pubkey: &str)
         members.push(hex_pubkey.to_string());
         let updated = serde_json::to_string(&members)?;
 
-        conn.execute(
+        let changed = conn.execute(
             "UPDATE social_groups SET members_json = ?1 WHERE group_id = ?2",
             params![updated, group_id],
         )?;
 
         debug!(group_id = %group_id, hex = %hex_pubkey, "recorded group member");
+        return Ok(changed > 0);
+    }
+
+    Ok(false)
+}
+
+/// Onboarding status of a member in a group, stored in `social_onboarding`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnboardingRecord {
+    pub group_id: String,
+    pub hex_pubkey: String,
+    /// `pending` | `welcomed` | `recorded` | `failed`
+    pub status: String,
+    /// Delivery mode used (`dm`, `mention`, `record`).
+    pub method: String,
+    pub updated_at: i64,
+}
+
+/// Claim onboarding for a member as `pending`.
+///
+/// Returns `false` if the member already has an onboarding row for the
+/// group, so concurrent or repeated sightings never welcome twice.
+pub fn claim_onboarding(
+    conn: &Connection,
+    group_id: &str,
+    hex_pubkey: &str,
+    method: &str,
+    timestamp: i64,
+) -> Result<bool> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO social_onboarding (group_id, hex_pubkey, status, method, updated_at)
+         VALUES (?1, ?2, 'pending', ?3, ?4)",
+        params![group_id, hex_pubkey, method, timestamp],
+    )?;
+    Ok(inserted > 0)
+}
+
+/// Update the onboarding status of a claimed member.
+pub fn set_onboarding_status(
+    conn: &Connection,
+    group_id: &str,
+    hex_pubkey: &str,
+    status: &str,
+    timestamp: i64,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE social_onboarding SET status = ?1, updated_at = ?2
+         WHERE group_id = ?3 AND hex_pubkey = ?4",
+        params![status, timestamp, group_id, hex_pubkey],
+    )?;
+    Ok(())
+}
+
+/// Get the onboarding record of a member in a group.
+pub fn get_onboarding(
+    conn: &Connection,
+    group_id: &str,
+    hex_pubkey: &str,
+) -> Result<Option<OnboardingRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT group_id, hex_pubkey, status, method, updated_at
+         FROM social_onboarding WHERE group_id = ?1 AND hex_pubkey = ?2",
+    )?;
+    let mut rows = stmt.query_map(params![group_id, hex_pubkey], |row| {
+        Ok(OnboardingRecord {
+            group_id: row.get(0)?,
+            hex_pubkey: row.get(1)?,
+            status: row.get(2)?,
+            method: row.get(3)?,
+            updated_at: row.get(4)?,
+        })
+    })?;
+    Ok(rows.next().transpose()?)
+}
+
+/// States kept per agent and `d` tag in `social_agent_states`; older rows
+/// are pruned on insert.
+pub const AGENT_STATE_HISTORY_LIMIT: usize = 2000;
+
+/// One kind 31121 agent state event, stored in `social_agent_states`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentStateRecord {
+    pub hex_pubkey: String,
+    pub d_tag: String,
+    /// `status` tag (`online`, ...).
+    pub status: String,
+    /// `version` tag, when the agent sent one.
+    pub version: Option<String>,
+    /// Raw event content (JSON with `uptime_start`, `groups`, ...).
+    pub content: String,
+    pub created_at: i64,
+}
+
+/// Append an agent state to the history. Returns `false` if the same event
+/// was already recorded.
+pub fn record_agent_state(conn: &Connection, state: &AgentStateRecord) -> Result<bool> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO social_agent_states
+             (hex_pubkey, d_tag, status, version, content, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            state.hex_pubkey,
+            state.d_tag,
+            state.status,
+            state.version,
+            state.content,
+            state.created_at
+        ],
+    )?;
+    if inserted > 0 {
+        #[allow(clippy::cast_possible_wrap)]
+        let keep = AGENT_STATE_HISTORY_LIMIT as i64;
+        conn.execute(
+            "DELETE FROM social_agent_states
+             WHERE hex_pubkey = ?1 AND d_tag = ?2 AND created_at < (
+                 SELECT created_at FROM social_agent_states
+                 WHERE hex_pubkey = ?1 AND d_tag = ?2
+                 ORDER BY created_at DESC LIMIT 1 OFFSET ?3
+             )",
+            params![state.hex_pubkey, state.d_tag, keep - 1],
+        )?;
+    }
+    Ok(inserted > 0)
+}
+
+/// Agent states created at or after `since`, per agent oldest first.
+pub fn list_agent_states(conn: &Connection, since: i64) -> Result<Vec<AgentStateRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT hex_pubkey, d_tag, status, version, content, created_at
+         FROM social_agent_states WHERE created_at >= ?1
+         ORDER BY hex_pubkey, created_at",
+    )?;
+    let rows = stmt.query_map(params![since], |row| {
+        Ok(AgentStateRecord {
+            hex_pubkey: row.get(0)?,
+            d_tag: row.get(1)?,
+            status: row.get(2)?,
+            version: row.get(3)?,
+            content: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Latest state of every agent with any recorded history.
+pub fn latest_agent_states(conn: &Connection) -> Result<Vec<AgentStateRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT s.hex_pubkey, s.d_tag, s.status, s.version, s.content, s.created_at
+         FROM social_agent_states s
+         WHERE s.created_at = (
+             SELECT MAX(created_at) FROM social_agent_states
+             WHERE hex_pubkey = s.hex_pubkey
+         )
+         GROUP BY s.hex_pubkey
+         ORDER BY s.hex_pubkey",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(AgentStateRecord {
+            hex_pubkey: row.get(0)?,
+            d_tag: row.get(1)?,
+            status: row.get(2)?,
+            version: row.get(3)?,
+            content: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Last message of a group read by `reader` (`owner` or `agent`), stored
+/// in `social_read_markers`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadMarker {
+    pub reader: String,
+    pub group_id: String,
+    pub event_id: String,
+    /// `created_at` of the read event; later messages are unread.
+    pub read_at: i64,
+    pub updated_at: i64,
+}
+
+/// Move a read marker forward. A marker older than the stored one is
+/// ignored, so markers from several devices merge to the furthest read.
+/// Returns `true` if the stored marker changed.
+pub fn advance_read_marker(conn: &Connection, marker: &ReadMarker) -> Result<bool> {
+    let changed = conn.execute(
+        "INSERT INTO social_read_markers (reader, group_id, event_id, read_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(reader, group_id) DO UPDATE SET
+             event_id = excluded.event_id,
+             read_at = excluded.read_at,
+             updated_at = excluded.updated_at
+         WHERE excluded.read_at > social_read_markers.read_at
+            OR (excluded.read_at = social_read_markers.read_at
+                AND excluded.event_id != social_read_markers.event_id
+                AND excluded.updated_at > social_read_markers.updated_at)",
+        params![
+            marker.reader,
+            marker.group_id,
+            marker.event_id,
+            marker.read_at,
+            marker.updated_at
+        ],
+    )?;
+    Ok(changed > 0)
+}
+
+/// Read marker of `reader` in a group.
+pub fn get_read_marker(
+    conn: &Connection,
+    reader: &str,
+    group_id: &str,
+) -> Result<Option<ReadMarker>> {
+    let mut stmt = conn.prepare(
+        "SELECT reader, group_id, event_id, read_at, updated_at
+         FROM social_read_markers WHERE reader = ?1 AND group_id = ?2",
+    )?;
+    let mut rows = stmt.query_map(params![reader, group_id], read_marker_from_row)?;
+    Ok(rows.next().transpose()?)
+}
+
+/// All read markers of a group, one per reader.
+pub fn list_read_markers(conn: &Connection, group_id: &str) -> Result<Vec<ReadMarker>> {
+    let mut stmt = conn.prepare(
+        "SELECT reader, group_id, event_id, read_at, updated_at
+         FROM social_read_markers WHERE group_id = ?1 ORDER BY reader",
+    )?;
+    let rows = stmt.query_map(params![group_id], read_marker_from_row)?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+fn read_marker_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ReadMarker> {
+    Ok(ReadMarker {
+        reader: row.get(0)?,
+        group_id: row.get(1)?,
+        event_id: row.get(2)?,
+        read_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+/// How one contact addressed another in a group message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InteractionKind {
+    /// The message replied to a message of the other contact.
+    Reply,
+    /// The message mentioned the other contact (`p` tag).
+    Mention,
+}
+
+impl InteractionKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Reply => "reply",
+            Self::Mention => "mention",
+        }
     }
 
+    fn parse(s: &str) -> Self {
+        if s == "reply" {
+            Self::Reply
+        } else {
+            Self::Mention
+        }
+    }
+}
+
+/// Aggregated interactions from one contact to another in a group, stored
+/// in `social_interactions`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InteractionEdge {
+    pub group_id: String,
+    pub from_hex: String,
+    pub to_hex: String,
+    pub kind: InteractionKind,
+    pub count: u64,
+    /// `created_at` of the latest counted message.
+    pub last_at: i64,
+}
+
+/// Interactions in both directions a pair needs before the context calls
+/// them collaborators.
+pub const COLLABORATION_MIN_INTERACTIONS: u64 = 3;
+
+/// Count one interaction from `from_hex` to `to_hex` in a group. Messages
+/// addressed to oneself are ignored.
+pub fn record_interaction(
+    conn: &Connection,
+    group_id: &str,
+    from_hex: &str,
+    to_hex: &str,
+    kind: InteractionKind,
+    at: i64,
+) -> Result<()> {
+    if from_hex == to_hex {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO social_interactions (group_id, from_hex, to_hex, kind, count, last_at)
+         VALUES (?1, ?2, ?3, ?4, 1, ?5)
+         ON CONFLICT(group_id, from_hex, to_hex, kind) DO UPDATE SET
+             count = count + 1,
+             last_at = MAX(last_at, excluded.last_at)",
+        params![group_id, from_hex, to_hex, kind.as_str(), at],
+    )?;
     Ok(())
 }
 
+/// Interaction graph of a group, most frequent edges first.
+pub fn group_interactions(conn: &Connection, group_id: &str) -> Result<Vec<InteractionEdge>> {
+    let mut stmt = conn.prepare(
+        "SELECT group_id, from_hex, to_hex, kind, count, last_at
+         FROM social_interactions WHERE group_id = ?1
+         ORDER BY count DESC, last_at DESC, from_hex, to_hex",
+    )?;
+    let rows = stmt.query_map(params![group_id], |row| {
+        let kind: String = row.get(3)?;
+        let count: i64 = row.get(4)?;
+        Ok(InteractionEdge {
+            group_id: row.get(0)?,
+            from_hex: row.get(1)?,
+            to_hex: row.get(2)?,
+            kind: InteractionKind::parse(&kind),
+            count: u64::try_from(count).unwrap_or(0),
+            last_at: row.get(5)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Contacts `hex_pubkey` interacts with most in a group, counting replies
+/// and mentions in both directions, as `(hex_pubkey, count)` pairs with at
+/// least `min_count` interactions.
+pub fn top_collaborators(
+    conn: &Connection,
+    group_id: &str,
+    hex_pubkey: &str,
+    min_count: u64,
+    limit: usize,
+) -> Result<Vec<(String, u64)>> {
+    let mut stmt = conn.prepare(
+        "SELECT peer, SUM(count) AS total FROM (
+             SELECT to_hex AS peer, count FROM social_interactions
+             WHERE group_id = ?1 AND from_hex = ?2
+             UNION ALL
+             SELECT from_hex AS peer, count FROM social_interactions
+             WHERE group_id = ?1 AND to_hex = ?2
+         )
+         GROUP BY peer HAVING total >= ?3
+         ORDER BY total DESC, peer LIMIT ?4",
+    )?;
+    #[allow(clippy::cast_possible_wrap)]
+    let rows = stmt.query_map(
+        params![group_id, hex_pubkey, min_count as i64, limit as i64],
+        |row| {
+            let total: i64 = row.get(1)?;
+            Ok((row.get(0)?, u64::try_from(total).unwrap_or(0)))
+        },
+    )?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Display name of a contact, or a shortened hex pubkey when unknown.
+pub fn display_name_or_short(conn: &Connection, hex_pubkey: &str) -> String {
+    match get_npub(conn, hex_pubkey) {
+        Ok(Some(npub)) => npub.display_name,
+        _ => hex_pubkey[..hex_pubkey.len().min(12)].to_string(),
+    }
+}
+
 /// Update last_interaction and optionally track a name change for an npub.
 pub fn touch_npub(
     conn: &Connection,
@@ -303,6 +728,11 @@ pub fn touch_npub(
         )
         .ok();
 
+    if current_name.is_none() && restore_archived_npub(conn, hex_pubkey)?.is_some() {
+        debug!(hex = %hex_pubkey, "restored archived social npub");
+        return touch_npub(conn, hex_pubkey, display_name, timestamp);
+    }
+
     if let Some(ref old_name) = current_name {
         if old_name != display_name {
             // Append to name history
@@ -419,15 +849,19 @@ pub fn get_group(conn: &Connection, group_id: &str) -> Result<Option<SocialGroup
     }
 }
 
-/// Build concise LLM context string for a sender in a group.
+/// Build concise LLM context string for a sender, shaped for `audience`.
 ///
 /// Returns a formatted string suitable for prompt injection with group and
-/// sender context from social memory.
-pub fn build_social_context(conn: &Connection, sender_hex: &str, group_id: &str) -> String {
+/// sender context from social memory. Owner notes are only included for
+/// [`Audience::OwnerDm`]; group purpose, notes and collaborators only for
+/// group conversations.
+pub fn build_social_context(conn: &Connection, sender_hex: &str, audience: &Audience) -> String {
     let mut ctx = String::new();
 
     // Group context
-    if let Ok(Some(group)) = get_group(conn, group_id) {
+    let group_id = audience.group_id();
+    if let Some(Ok(Some(group))) = group_id.map(|id| get_group(conn, id)) {
+        let group_id = &group.group_id;
         if let Some(ref purpose) = group.purpose {
             let _ = writeln!(ctx, "[Group #{} purpose: {}]", group_id, purpose);
         }
@@ -444,7 +878,12 @@ pub fn build_social_context(conn: &Connection, sender_hex: &str, group_id: &str)
     if let Ok(Some(npub)) = get_npub(conn, sender_hex) {
         let mut parts = Vec::new();
 
-        if let Some(ref owner_notes_json) = npub.owner_notes_json {
+        // Owner notes are the owner's words; they stay out of other conversations.
+        let owner_notes_json = npub
+            .owner_notes_json
+            .as_ref()
+            .filter(|_| audience.is_owner());
+        if let Some(owner_notes_json) = owner_notes_json {
             if let Ok(notes) = serde_json::from_str::<Vec<String>>(owner_notes_json) {
                 if !notes.is_empty() {
                     parts.push(format!("owner says: {}", notes.join("; ")));
@@ -474,9 +913,140 @@ pub fn build_social_context(conn: &Connection, sender_hex: &str, group_id: &str)
         }
     }
 
+    // Interaction context
+    let Some(group_id) = group_id else {
+        return ctx;
+    };
+    if let Ok(peers) = top_collaborators(
+        conn,
+        group_id,
+        sender_hex,
+        COLLABORATION_MIN_INTERACTIONS,
+        3,
+    ) {
+        if !peers.is_empty() {
+            let names: Vec<String> = peers
+                .iter()
+                .map(|(hex, _)| display_name_or_short(conn, hex))
+                .collect();
+            let _ = writeln!(
+                ctx,
+                "[{} frequently collaborates with {} in #{}]",
+                display_name_or_short(conn, sender_hex),
+                names.join(", "),
+                group_id
+            );
+        }
+    }
+
     ctx
 }
 
+// ── Contact archive ──────────────────────────────────────────────
+
+/// Move contacts not seen since `cutoff` into `social_npubs_archive`.
+///
+/// Only one-off contacts are archived: owners and contacts with notes,
+/// owner notes or preferences stay. Archived rows leave `social_fts`
+/// through the delete trigger. Returns the number archived.
+pub fn archive_inactive_npubs(conn: &Connection, cutoff: i64, now: i64) -> Result<usize> {
+    let empty = |column: &str| format!("COALESCE({column}, '') IN ('', '[]', '{{}}')");
+    let sql = format!(
+        "SELECT hex_pubkey FROM social_npubs
+         WHERE last_interaction < ?1 AND is_owner = 0 AND {} AND {} AND {}",
+        empty("notes_json"),
+        empty("owner_notes_json"),
+        empty("preferences_json"),
+    );
+    let stale: Vec<String> = conn
+        .prepare(&sql)?
+        .query_map(params![cutoff], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let tx = conn.unchecked_transaction()?;
+    for hex_pubkey in &stale {
+        let Some(npub) = get_npub(&tx, hex_pubkey)? else {
+            continue;
+        };
+        tx.execute(
+            "INSERT OR REPLACE INTO social_npubs_archive
+                (hex_pubkey, display_name, last_interaction, archived_at, data)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                npub.hex_pubkey,
+                npub.display_name,
+                npub.last_interaction,
+                now,
+                compress_npub(&npub)?,
+            ],
+        )?;
+        tx.execute(
+            "DELETE FROM social_npubs WHERE hex_pubkey = ?1",
+            params![hex_pubkey],
+        )?;
+    }
+    tx.commit()?;
+    Ok(stale.len())
+}
+
+/// Move an archived contact back into `social_npubs`. Returns the
+/// contact, or `None` if it is not archived.
+pub fn restore_archived_npub(conn: &Connection, hex_pubkey: &str) -> Result<Option<SocialNpub>> {
+    let data: Option<Vec<u8>> = conn
+        .query_row(
+            "SELECT data FROM social_npubs_archive WHERE hex_pubkey = ?1",
+            params![hex_pubkey],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(data) = data else {
+        return Ok(None);
+    };
+    let npub =
+        decompress_npub(&data).with_context(|| format!("corrupt archived contact {hex_pubkey}"))?;
+
+    let tx = conn.unchecked_transaction()?;
+    upsert_npub(&tx, &npub)?;
+    tx.execute(
+        "DELETE FROM social_npubs_archive WHERE hex_pubkey = ?1",
+        params![hex_pubkey],
+    )?;
+    tx.commit()?;
+    Ok(Some(npub))
+}
+
+/// `last_interaction` of an archived contact.
+pub fn archived_last_interaction(conn: &Connection, hex_pubkey: &str) -> Result<Option<i64>> {
+    Ok(conn
+        .query_row(
+            "SELECT last_interaction FROM social_npubs_archive WHERE hex_pubkey = ?1",
+            params![hex_pubkey],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Number of archived contacts.
+pub fn archived_npub_count(conn: &Connection) -> Result<usize> {
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM social_npubs_archive", [], |row| {
+        row.get(0)
+    })?;
+    Ok(usize::try_from(count).unwrap_or(0))
+}
+
+fn compress_npub(npub: &SocialNpub) -> Result<Vec<u8>> {
+    use std::io::Write as _;
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&serde_json::to_vec(npub)?)?;
+    Ok(encoder.finish()?)
+}
+
+fn decompress_npub(data: &[u8]) -> Result<SocialNpub> {
+    Ok(serde_json::from_reader(flate2::read::ZlibDecoder::new(
+        data,
+    ))?)
+}
+
 /// Search social data using FTS5.
 pub fn search_social(conn: &Connection, query: &str, limit: usize) -> Result<Vec<SocialNpub>> {
     if query.trim().is_empty() {
@@ -712,6 +1282,86 @@ mod tests {
 
     // ── Group CRUD tests ────────────────────────────────────────
 
+    #[test]
+    fn record_group_member_reports_new_members() {
+        let conn = test_conn();
+        upsert_group(&conn, &sample_group("g1")).unwrap();
+        assert!(record_group_member(&conn, "g1", "eeff").unwrap());
+        assert!(!record_group_member(&conn, "g1", "eeff").unwrap());
+        assert!(!record_group_member(&conn, "g1", "aabb").unwrap());
+    }
+
+    #[test]
+    fn onboarding_claim_is_deduplicated() {
+        let conn = test_conn();
+        assert!(claim_onboarding(&conn, "g1", "aabb", "dm", 100).unwrap());
+        assert!(!claim_onboarding(&conn, "g1", "aabb", "dm", 200).unwrap());
+        assert!(claim_onboarding(&conn, "g2", "aabb", "dm", 200).unwrap());
+
+        set_onboarding_status(&conn, "g1", "aabb", "welcomed", 150).unwrap();
+        let record = get_onboarding(&conn, "g1", "aabb").unwrap().unwrap();
+        assert_eq!(record.status, "welcomed");
+        assert_eq!(record.method, "dm");
+        assert_eq!(record.updated_at, 150);
+        assert!(get_onboarding(&conn, "g1", "ccdd").unwrap().is_none());
+    }
+
+    #[test]
+    fn read_markers_only_move_forward() {
+        let conn = test_conn();
+        let marker = |event: &str, read_at: i64, updated_at: i64| ReadMarker {
+            reader: "owner".to_string(),
+            group_id: "g1".to_string(),
+            event_id: event.to_string(),
+            read_at,
+            updated_at,
+        };
+        assert!(advance_read_marker(&conn, &marker("e2", 200, 10)).unwrap());
+        // An older marker (e.g. synced from another device) is ignored.
+        assert!(!advance_read_marker(&conn, &marker("e1", 100, 20)).unwrap());
+        assert!(!advance_read_marker(&conn, &marker("e2", 200, 30)).unwrap());
+        assert!(advance_read_marker(&conn, &marker("e3", 300, 40)).unwrap());
+
+        let stored = get_read_marker(&conn, "owner", "g1").unwrap().unwrap();
+        assert_eq!(stored.event_id, "e3");
+        assert_eq!(stored.read_at, 300);
+        assert!(get_read_marker(&conn, "agent", "g1").unwrap().is_none());
+        assert_eq!(list_read_markers(&conn, "g1").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn agent_state_history_is_rolling() {
+        let conn = test_conn();
+        let state = |hex: &str, version: &str, ts: i64| AgentStateRecord {
+            hex_pubkey: hex.to_string(),
+            d_tag: "snowclaw:status".to_string(),
+            status: "online".to_string(),
+            version: Some(version.to_string()),
+            content: "{}".to_string(),
+            created_at: ts,
+        };
+        assert!(record_agent_state(&conn, &state("aabb", "0.4.0", 100)).unwrap());
+        assert!(!record_agent_state(&conn, &state("aabb", "0.4.0", 100)).unwrap());
+        record_agent_state(&conn, &state("aabb", "0.5.0", 200)).unwrap();
+        record_agent_state(&conn, &state("ccdd", "0.5.0", 150)).unwrap();
+
+        assert_eq!(list_agent_states(&conn, 0).unwrap().len(), 3);
+        assert_eq!(list_agent_states(&conn, 150).unwrap().len(), 2);
+        let latest = latest_agent_states(&conn).unwrap();
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].version.as_deref(), Some("0.5.0"));
+
+        for ts in 0..AGENT_STATE_HISTORY_LIMIT as i64 {
+            record_agent_state(&conn, &state("aabb", "0.5.0", 1_000 + ts)).unwrap();
+        }
+        let kept = list_agent_states(&conn, 0).unwrap();
+        assert_eq!(
+            kept.iter().filter(|s| s.hex_pubkey == "aabb").count(),
+            AGENT_STATE_HISTORY_LIMIT
+        );
+        assert!(kept.iter().all(|s| s.created_at != 100));
+    }
+
     #[test]
     fn upsert_and_get_group() {
         let conn = test_conn();
@@ -749,10 +1399,14 @@ mod tests {
 
     // ── build_social_context tests ──────────────────────────────
 
+    fn in_group(group_id: &str) -> Audience {
+        Audience::Group(group_id.to_string())
+    }
+
     #[test]
     fn build_context_empty_db() {
         let conn = test_conn();
-        let ctx = build_social_context(&conn, "aabb", "test");
+        let ctx = build_social_context(&conn, "aabb", &in_group("test"));
         assert!(ctx.is_empty());
     }
 
@@ -761,7 +1415,7 @@ mod tests {
         let conn = test_conn();
         upsert_group(&conn, &sample_group("dev")).unwrap();
 
-        let ctx = build_social_context(&conn, "aabb", "dev");
+        let ctx = build_social_context(&conn, "aabb", &in_group("dev"));
         assert!(ctx.contains("testing"));
     }
 
@@ -774,7 +1428,7 @@ mod tests {
         };
         upsert_group(&conn, &group).unwrap();
 
-        let ctx = build_social_context(&conn, "aabb", "dev");
+        let ctx = build_social_context(&conn, "aabb", &in_group("dev"));
         assert!(ctx.contains("active channel"));
     }
 
@@ -785,12 +1439,34 @@ mod tests {
         add_npub_note(&conn, "aabb", "likes Rust", false).unwrap();
         add_npub_note(&conn, "aabb", "core team", true).unwrap();
 
-        let ctx = build_social_context(&conn, "aabb", "test");
+        let ctx = build_social_context(&conn, "aabb", &Audience::OwnerDm);
         assert!(ctx.contains("likes Rust"));
         assert!(ctx.contains("core team"));
         assert!(ctx.contains("Alice"));
     }
 
+    #[test]
+    fn build_context_keeps_owner_notes_to_owner_dms() {
+        let conn = test_conn();
+        upsert_npub(&conn, &sample_npub("aabb", "Alice")).unwrap();
+        upsert_group(&conn, &sample_group("dev")).unwrap();
+        add_npub_note(&conn, "aabb", "likes Rust", false).unwrap();
+        add_npub_note(&conn, "aabb", "do not trust with keys", true).unwrap();
+
+        for audience in [in_group("dev"), Audience::Dm] {
+            let ctx = build_social_context(&conn, "aabb", &audience);
+            assert!(ctx.contains("likes Rust"));
+            assert!(
+                !ctx.contains("do not trust"),
+                "owner note leaked to {audience:?}"
+            );
+            assert!(!ctx.contains("owner says"));
+        }
+        // Group details only go to the group.
+        assert!(!build_social_context(&conn, "aabb", &Audience::Dm).contains("testing"));
+        assert!(build_social_context(&conn, "aabb", &Audience::OwnerDm).contains("do not trust"));
+    }
+
     #[test]
     fn build_context_limits_notes_to_3() {
         let conn = test_conn();
@@ -799,7 +1475,7 @@ mod tests {
             add_npub_note(&conn, "aabb", &format!("note_{i}"), false).unwrap();
         }
 
-        let ctx = build_social_context(&conn, "aabb", "test");
+        let ctx = build_social_context(&conn, "aabb", &in_group("test"));
         // Should contain the last 3 notes
         assert!(ctx.contains("note_7"));
         assert!(ctx.contains("note_8"));
@@ -808,6 +1484,32 @@ mod tests {
         assert!(!ctx.contains("note_0"));
     }
 
+    #[test]
+    fn build_context_names_frequent_collaborators() {
+        let conn = test_conn();
+        upsert_npub(&conn, &sample_npub("aabb", "Alice")).unwrap();
+        upsert_npub(&conn, &sample_npub("ccdd", "Bob")).unwrap();
+        for at in 0..2 {
+            record_interaction(&conn, "dev", "aabb", "ccdd", InteractionKind::Reply, at).unwrap();
+        }
+        // Below the threshold, nothing is said.
+        assert!(!build_social_context(&conn, "aabb", &in_group("dev")).contains("collaborates"));
+
+        record_interaction(&conn, "dev", "ccdd", "aabb", InteractionKind::Mention, 5).unwrap();
+        record_interaction(&conn, "dev", "aabb", "aabb", InteractionKind::Mention, 6).unwrap();
+        let ctx = build_social_context(&conn, "aabb", &in_group("dev"));
+        assert!(ctx.contains("[Alice frequently collaborates with Bob in #dev]"));
+        // Counts are per group.
+        assert!(!build_social_context(&conn, "aabb", &in_group("other")).contains("collaborates"));
+
+        let edges = group_interactions(&conn, "dev").unwrap();
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[0].from_hex, "aabb");
+        assert_eq!(edges[0].kind, InteractionKind::Reply);
+        assert_eq!(edges[0].count, 2);
+        assert_eq!(edges[1].last_at, 5);
+    }
+
     // ── FTS5 search tests ───────────────────────────────────────
 
     #[test]
@@ -904,6 +1606,77 @@ mod tests {
         assert!(results.len() <= 10);
     }
 
+    // ── Contact archive tests ───────────────────────────────────
+
+    #[test]
+    fn archive_moves_only_inactive_contacts_without_notes() {
+        let conn = test_conn();
+        upsert_npub(&conn, &sample_npub("old", "Drifter")).unwrap();
+        upsert_npub(
+            &conn,
+            &SocialNpub {
+                last_interaction: 9000,
+                ..sample_npub("recent", "Regular")
+            },
+        )
+        .unwrap();
+        upsert_npub(
+            &conn,
+            &SocialNpub {
+                is_owner: true,
+                ..sample_npub("owner", "Boss")
+            },
+        )
+        .unwrap();
+        upsert_npub(&conn, &sample_npub("noted", "Noted")).unwrap();
+        add_npub_note(&conn, "noted", "runs the relay", false).unwrap();
+        upsert_npub(
+            &conn,
+            &SocialNpub {
+                notes_json: Some("[]".to_string()),
+                ..sample_npub("empty", "Empty")
+            },
+        )
+        .unwrap();
+
+        assert_eq!(archive_inactive_npubs(&conn, 5000, 10_000).unwrap(), 2);
+        assert_eq!(archived_npub_count(&conn).unwrap(), 2);
+        assert!(get_npub(&conn, "old").unwrap().is_none());
+        assert!(get_npub(&conn, "empty").unwrap().is_none());
+        assert!(search_social(&conn, "Drifter", 10).unwrap().is_empty());
+        for kept in ["recent", "owner", "noted"] {
+            assert!(get_npub(&conn, kept).unwrap().is_some(), "{kept}");
+        }
+        assert_eq!(archived_last_interaction(&conn, "old").unwrap(), Some(2000));
+    }
+
+    #[test]
+    fn archived_contact_is_restored_when_seen_again() {
+        let conn = test_conn();
+        upsert_npub(
+            &conn,
+            &SocialNpub {
+                profile_json: Some(r#"{"about":"lurker"}"#.to_string()),
+                ..sample_npub("old", "Drifter")
+            },
+        )
+        .unwrap();
+        archive_inactive_npubs(&conn, 5000, 10_000).unwrap();
+
+        // Seen again under a new name: restored, not a new contact
+        assert!(!touch_npub(&conn, "old", "Returner", 20_000).unwrap());
+        let npub = get_npub(&conn, "old").unwrap().unwrap();
+        assert_eq!(npub.display_name, "Returner");
+        assert_eq!(npub.first_seen, 1000);
+        assert_eq!(npub.last_interaction, 20_000);
+        assert_eq!(npub.profile_json.as_deref(), Some(r#"{"about":"lurker"}"#));
+        assert_eq!(archived_npub_count(&conn).unwrap(), 0);
+        assert_eq!(search_social(&conn, "Returner", 10).unwrap().len(), 1);
+
+        assert!(restore_archived_npub(&conn, "old").unwrap().is_none());
+        assert!(touch_npub(&conn, "never", "Stranger", 20_000).unwrap());
+    }
+
     // ── Unicode / edge cases ────────────────────────────────────
 
     #[test]
@@ -1,5 +1,5 @@
 use super::embeddings::EmbeddingProvider;
-use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use super::traits::{Memory, MemoryCategory, MemoryEntry, MemoryOp};
 use super::vector;
 use anyhow::Context;
 use async_trait::async_trait;
@@ -153,6 +153,8 @@ impl SqliteMemory {
         } else {
             Connection::open(&path_buf).context("SQLite failed to open database")?
         };
+        crate::security::db_encryption::apply_key(&conn)
+            .with_context(|| format!("Failed to unlock {}", db_path.display()))?;
 
         Ok(conn)
     }
@@ -254,6 +256,39 @@ impl SqliteMemory {
         )
     }
 
+    /// Insert or update one entry by key
+    fn upsert(
+        conn: &Connection,
+        key: &str,
+        content: &str,
+        category: &MemoryCategory,
+        embedding: Option<&[u8]>,
+        session_id: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let now = Local::now().to_rfc3339();
+        let cat = Self::category_to_str(category);
+        let id = Uuid::new_v4().to_string();
+
+        conn.execute(
+            "INSERT INTO memories (id, key, content, category, embedding, created_at, updated_at, session_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(key) DO UPDATE SET
+                content = excluded.content,
+                category = excluded.category,
+                embedding = excluded.embedding,
+                updated_at = excluded.updated_at,
+                session_id = excluded.session_id",
+            params![id, key, content, cat, embedding, now, now, session_id],
+        )?;
+        Ok(())
+    }
+
+    /// Delete one entry by key; `true` if it existed
+    fn delete(conn: &Connection, key: &str) -> anyhow::Result<bool> {
+        let affected = conn.execute("DELETE FROM memories WHERE key = ?1", params![key])?;
+        Ok(affected > 0)
+    }
+
     /// Get embedding from cache, or compute + cache it
     async fn get_or_compute_embedding(&self, text: &str) -> anyhow::Result<Option<Vec<f32>>> {
         if self.embedder.dimensions() == 0 {
@@ -489,22 +524,14 @@ impl Memory for SqliteMemory {
 
         tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
             let conn = conn.lock();
-            let now = Local::now().to_rfc3339();
-            let cat = Self::category_to_str(&category);
-            let id = Uuid::new_v4().to_string();
-
-            conn.execute(
-                "INSERT INTO memories (id, key, content, category, embedding, created_at, updated_at, session_id)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-                 ON CONFLICT(key) DO UPDATE SET
-                    content = excluded.content,
-                    category = excluded.category,
-                    embedding = excluded.embedding,
-                    updated_at = excluded.updated_at,
-                    session_id = excluded.session_id",
-                params![id, key, content, cat, embedding_bytes, now, now, sid],
-            )?;
-            Ok(())
+            Self::upsert(
+                &conn,
+                &key,
+                &content,
+                &category,
+                embedding_bytes.as_deref(),
+                sid.as_deref(),
+            )
         })
         .await?
     }
@@ -787,8 +814,55 @@ impl Memory for SqliteMemory {
 
         tokio::task::spawn_blocking(move || -> anyhow::Result<bool> {
             let conn = conn.lock();
-            let affected = conn.execute("DELETE FROM memories WHERE key = ?1", params![key])?;
-            Ok(affected > 0)
+            Self::delete(&conn, &key)
+        })
+        .await?
+    }
+
+    fn supports_transactions(&self) -> bool {
+        true
+    }
+
+    async fn transaction(&self, ops: &[MemoryOp]) -> anyhow::Result<Vec<bool>> {
+        // Embeddings first (async), so the write lock is held only for SQL.
+        let mut embeddings = Vec::with_capacity(ops.len());
+        for op in ops {
+            let bytes = match op {
+                MemoryOp::Store(entry) => self
+                    .get_or_compute_embedding(&entry.content)
+                    .await?
+                    .map(|emb| vector::vec_to_bytes(&emb)),
+                MemoryOp::Forget(_) => None,
+            };
+            embeddings.push(bytes);
+        }
+
+        let conn = self.conn.clone();
+        let ops = ops.to_vec();
+
+        tokio::task::spawn_blocking(move || -> anyhow::Result<Vec<bool>> {
+            let mut conn = conn.lock();
+            let tx = conn.transaction()?;
+            let mut applied = Vec::with_capacity(ops.len());
+            for (op, embedding) in ops.iter().zip(&embeddings) {
+                let changed = match op {
+                    MemoryOp::Store(entry) => {
+                        Self::upsert(
+                            &tx,
+                            &entry.key,
+                            &entry.content,
+                            &entry.category,
+                            embedding.as_deref(),
+                            entry.session_id.as_deref(),
+                        )?;
+                        true
+                    }
+                    MemoryOp::Forget(key) => Self::delete(&tx, key)?,
+                };
+                applied.push(changed);
+            }
+            tx.commit()?;
+            Ok(applied)
         })
         .await?
     }
@@ -874,6 +948,7 @@ impl Memory for SqliteMemory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::memory::traits::NewMemory;
     use tempfile::TempDir;
 
     fn temp_sqlite() -> (TempDir, SqliteMemory) {
@@ -1655,6 +1730,74 @@ mod tests {
 
     // ── Edge cases: reindex ──────────────────────────────────────
 
+    #[tokio::test]
+    async fn store_batch_writes_all_entries() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store_batch(&[
+            NewMemory::new("lang", "Rust", MemoryCategory::Core),
+            NewMemory::new("editor", "Helix", MemoryCategory::Core).with_session("s1"),
+        ])
+        .await
+        .unwrap();
+
+        assert_eq!(mem.count().await.unwrap(), 2);
+        let editor = mem.get("editor").await.unwrap().unwrap();
+        assert_eq!(editor.content, "Helix");
+        assert_eq!(editor.session_id.as_deref(), Some("s1"));
+        assert!(mem.supports_transactions());
+    }
+
+    #[tokio::test]
+    async fn transaction_applies_ops_in_order() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("old", "stale", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+
+        let applied = mem
+            .transaction(&[
+                MemoryOp::Forget("old".into()),
+                MemoryOp::Forget("missing".into()),
+                MemoryOp::Store(NewMemory::new("new", "fresh", MemoryCategory::Daily)),
+                MemoryOp::Store(NewMemory::new("new", "fresher", MemoryCategory::Daily)),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(applied, vec![true, false, true, true]);
+        assert!(mem.get("old").await.unwrap().is_none());
+        assert_eq!(mem.get("new").await.unwrap().unwrap().content, "fresher");
+        assert_eq!(mem.recall("fresher", 10, None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_transaction_rolls_back() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("keep", "original", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+        mem.conn
+            .lock()
+            .execute_batch(
+                "CREATE TRIGGER reject_boom BEFORE INSERT ON memories
+                 WHEN NEW.key = 'boom' BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+            )
+            .unwrap();
+
+        let result = mem
+            .transaction(&[
+                MemoryOp::Store(NewMemory::new("keep", "changed", MemoryCategory::Core)),
+                MemoryOp::Store(NewMemory::new("added", "new", MemoryCategory::Core)),
+                MemoryOp::Store(NewMemory::new("boom", "fails", MemoryCategory::Core)),
+            ])
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(mem.count().await.unwrap(), 1);
+        assert_eq!(mem.get("keep").await.unwrap().unwrap().content, "original");
+        assert!(mem.recall("changed", 10, None).await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn reindex_empty_db() {
         let (_tmp, mem) = temp_sqlite();
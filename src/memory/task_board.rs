@@ -0,0 +1,885 @@
+//! Task board: Nostr tasks mirrored in SQLite.
+//!
+//! Tasks carry sequential SNOW-N ids and are stored in `social.db`
+//! (`nostr_tasks`, with every status change in `nostr_task_updates`). The
+//! Nostr channel publishes new tasks as kind 1621 events and status changes
+//! as kinds 1630-1637 referencing them; rows without an `event_id` are still
+//! waiting to be published. Status events from other agents and people that
+//! reference a known task are mirrored into the same tables.
+//!
+//! Boards kept in the older `nostr_tasks.json` workspace file are imported
+//! on first open.
+
+use anyhow::{bail, Context, Result};
+use chrono::{NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::social;
+
+/// Kind of the event that defines a task.
+pub const TASK_KIND: u16 = 1621;
+
+/// Status names, indexed by `kind - 1630`.
+pub const STATUSES: [&str; 8] = [
+    "queued",
+    "done",
+    "cancelled",
+    "draft",
+    "executing",
+    "blocked",
+    "review",
+    "failed",
+];
+
+/// Statuses that close a task; closed tasks are hidden from default lists.
+pub const CLOSED_STATUSES: [&str; 3] = ["done", "cancelled", "failed"];
+
+/// Former JSON task store in the workspace.
+const LEGACY_STORE: &str = "nostr_tasks.json";
+
+// ── Data structures ──────────────────────────────────────────────
+
+/// A task on the board.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Task {
+    pub number: u64,
+    pub title: String,
+    pub description: String,
+    pub status: String,
+    pub group_id: Option<String>,
+    /// Hex pubkeys
+    pub assignees: Vec<String>,
+    /// Unix seconds
+    pub due_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Kind 1621 event, once published
+    pub event_id: Option<String>,
+}
+
+impl Task {
+    /// `SNOW-N`
+    pub fn id(&self) -> String {
+        format!("SNOW-{}", self.number)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        CLOSED_STATUSES.contains(&self.status.as_str())
+    }
+
+    /// Tags shared by the task event and its status events: group,
+    /// assignees and due date.
+    pub fn context_tags(&self) -> Vec<Vec<String>> {
+        let mut tags = Vec::new();
+        if let Some(group) = &self.group_id {
+            tags.push(vec!["h".to_string(), group.clone()]);
+        }
+        for hex in &self.assignees {
+            tags.push(vec!["p".to_string(), hex.clone()]);
+        }
+        if let Some(due) = self.due_at {
+            tags.push(vec!["due".to_string(), due.to_string()]);
+        }
+        tags
+    }
+
+    /// Tags of the kind 1621 task event.
+    pub fn event_tags(&self) -> Vec<Vec<String>> {
+        let mut tags = vec![
+            vec!["subject".to_string(), self.title.clone()],
+            vec!["t".to_string(), self.id().to_lowercase()],
+        ];
+        tags.extend(self.context_tags());
+        tags
+    }
+}
+
+/// One status change of a task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskUpdate {
+    pub id: i64,
+    pub task_number: u64,
+    pub status: String,
+    pub detail: String,
+    /// Hex pubkey of a remote author; `None` for local changes
+    pub author: Option<String>,
+    pub created_at: i64,
+    /// Status event, once published (always set for remote updates)
+    pub event_id: Option<String>,
+}
+
+impl TaskUpdate {
+    /// Event kind of the status.
+    pub fn kind(&self) -> u16 {
+        status_kind(&self.status).unwrap_or(1630)
+    }
+}
+
+/// Fields of a new task.
+#[derive(Debug, Clone, Default)]
+pub struct NewTask {
+    pub title: String,
+    pub description: String,
+    /// Defaults to `draft`
+    pub status: Option<String>,
+    pub group_id: Option<String>,
+    pub assignees: Vec<String>,
+    pub due_at: Option<i64>,
+}
+
+/// A change to an existing task. Unset fields are kept.
+#[derive(Debug, Clone, Default)]
+pub struct TaskChange {
+    pub status: Option<String>,
+    pub detail: String,
+    pub assignees: Option<Vec<String>>,
+    /// `Some(None)` clears the due date
+    pub due_at: Option<Option<i64>>,
+}
+
+/// Which tasks to list.
+#[derive(Debug, Clone, Default)]
+pub struct TaskFilter {
+    pub status: Option<String>,
+    pub group_id: Option<String>,
+    /// Hex pubkey
+    pub assignee: Option<String>,
+    /// Include done, cancelled and failed tasks
+    pub include_closed: bool,
+}
+
+// ── Schema ───────────────────────────────────────────────────────
+
+/// Create the task board tables in the given connection.
+pub fn create_task_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS nostr_tasks (
+            number INTEGER PRIMARY KEY,
+            title TEXT NOT NULL,
+            description TEXT NOT NULL DEFAULT '',
+            status TEXT NOT NULL,
+            group_id TEXT,
+            assignees_json TEXT NOT NULL DEFAULT '[]',
+            due_at INTEGER,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            event_id TEXT UNIQUE
+        );
+        CREATE TABLE IF NOT EXISTS nostr_task_updates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_number INTEGER NOT NULL REFERENCES nostr_tasks(number),
+            status TEXT NOT NULL,
+            detail TEXT NOT NULL DEFAULT '',
+            author TEXT,
+            created_at INTEGER NOT NULL,
+            event_id TEXT UNIQUE
+        );
+        CREATE INDEX IF NOT EXISTS idx_task_updates_task
+            ON nostr_task_updates(task_number, created_at);",
+    )
+    .context("failed to create task board tables")?;
+    Ok(())
+}
+
+/// Open the board in `social.db` under `config_dir`, creating its tables
+/// and importing a `nostr_tasks.json` left in `workspace_dir`.
+pub fn open(config_dir: &Path, workspace_dir: &Path) -> Result<Connection> {
+    std::fs::create_dir_all(config_dir)
+        .with_context(|| format!("failed to create {}", config_dir.display()))?;
+    let conn = crate::security::db_encryption::open(&config_dir.join("social.db"))?;
+    conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA busy_timeout = 5000;")?;
+    social::create_social_tables(&conn)?;
+    create_task_tables(&conn)?;
+    let imported = migrate_json_store(&conn, workspace_dir)?;
+    if imported > 0 {
+        tracing::info!("Imported {imported} task(s) from {LEGACY_STORE} into social.db");
+    }
+    Ok(conn)
+}
+
+/// Import tasks from the former `nostr_tasks.json` in `workspace_dir` into
+/// an empty board, then rename the file to `nostr_tasks.json.migrated`.
+/// Returns the number of tasks imported.
+pub fn migrate_json_store(conn: &Connection, workspace_dir: &Path) -> Result<usize> {
+    let path = workspace_dir.join(LEGACY_STORE);
+    if !path.exists() {
+        return Ok(0);
+    }
+    let existing: i64 = conn.query_row("SELECT COUNT(*) FROM nostr_tasks", [], |row| row.get(0))?;
+    if existing > 0 {
+        return Ok(0);
+    }
+    let data = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let tasks: Vec<serde_json::Value> =
+        serde_json::from_str(&data).with_context(|| format!("invalid {}", path.display()))?;
+
+    let timestamp = |v: Option<&serde_json::Value>| {
+        v.and_then(|v| v.as_str())
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map_or_else(|| Utc::now().timestamp(), |t| t.timestamp())
+    };
+    let mut imported = 0;
+    for task in &tasks {
+        let Some(number) = task
+            .get("id")
+            .and_then(|v| v.as_str())
+            .and_then(parse_task_number)
+        else {
+            continue;
+        };
+        let status = task
+            .get("status")
+            .and_then(|v| v.as_str())
+            .filter(|s| status_kind(s).is_some())
+            .unwrap_or("draft");
+        let text = |key: &str| task.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+        conn.execute(
+            "INSERT OR IGNORE INTO nostr_tasks
+                (number, title, description, status, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                number as i64,
+                text("title"),
+                text("description"),
+                status,
+                timestamp(task.get("created_at")),
+                timestamp(task.get("updated_at")),
+            ],
+        )?;
+        let history = task.get("status_history").and_then(|v| v.as_array());
+        for entry in history.into_iter().flatten() {
+            let Some(status) = entry
+                .get("status")
+                .and_then(|v| v.as_str())
+                .filter(|s| status_kind(s).is_some())
+            else {
+                continue;
+            };
+            conn.execute(
+                "INSERT INTO nostr_task_updates (task_number, status, detail, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    number as i64,
+                    status,
+                    entry.get("detail").and_then(|v| v.as_str()).unwrap_or(""),
+                    timestamp(entry.get("timestamp")),
+                ],
+            )?;
+        }
+        imported += 1;
+    }
+    std::fs::rename(&path, path.with_extension("json.migrated"))
+        .with_context(|| format!("failed to rename {}", path.display()))?;
+    Ok(imported)
+}
+
+// ── Parsing ──────────────────────────────────────────────────────
+
+/// Event kind of a status name.
+pub fn status_kind(status: &str) -> Option<u16> {
+    STATUSES
+        .iter()
+        .position(|s| s.eq_ignore_ascii_case(status.trim()))
+        .map(|i| 1630 + i as u16)
+}
+
+/// Status name of a kind 1630-1637 event.
+pub fn status_for_kind(kind: u16) -> Option<&'static str> {
+    kind.checked_sub(1630)
+        .and_then(|i| STATUSES.get(usize::from(i)))
+        .copied()
+}
+
+/// Normalize a status name, or fail listing the valid ones.
+pub fn parse_status(status: &str) -> Result<String> {
+    match status_kind(status) {
+        Some(kind) => Ok(STATUSES[usize::from(kind - 1630)].to_string()),
+        None => bail!("Unknown status: {status}. Valid: {}", STATUSES.join(", ")),
+    }
+}
+
+/// Task number from `SNOW-N`, `snow-N` or a bare `N`.
+pub fn parse_task_number(input: &str) -> Option<u64> {
+    let trimmed = input.trim();
+    let digits = match trimmed.get(..5) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("snow-") => &trimmed[5..],
+        _ => trimmed,
+    };
+    digits.parse().ok().filter(|n| *n > 0)
+}
+
+/// Due date from `YYYY-MM-DD` (end of that day, UTC) or RFC 3339.
+pub fn parse_due(input: &str) -> Result<i64> {
+    let input = input.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        let end = date.and_hms_opt(23, 59, 59).context("invalid due date")?;
+        return Ok(end.and_utc().timestamp());
+    }
+    chrono::DateTime::parse_from_rfc3339(input)
+        .map(|t| t.timestamp())
+        .with_context(|| format!("Invalid due date `{input}` (expected YYYY-MM-DD)"))
+}
+
+/// `YYYY-MM-DD` of a due timestamp.
+pub fn format_due(due_at: i64) -> String {
+    chrono::DateTime::from_timestamp(due_at, 0)
+        .map_or_else(|| due_at.to_string(), |t| t.format("%Y-%m-%d").to_string())
+}
+
+/// Hex pubkey of an assignee given as npub, hex or a known contact's
+/// display name.
+pub fn resolve_assignee(conn: &Connection, input: &str) -> Result<String> {
+    let input = input.trim().trim_start_matches('@');
+    if let Ok(pk) = nostr_sdk::PublicKey::parse(input) {
+        return Ok(pk.to_hex());
+    }
+    let matches: Vec<_> = social::list_npubs(conn)?
+        .into_iter()
+        .filter(|n| n.display_name.eq_ignore_ascii_case(input))
+        .collect();
+    match matches.as_slice() {
+        [one] => Ok(one.hex_pubkey.clone()),
+        [] => bail!("Unknown assignee `{input}`: use an npub, hex pubkey or a known contact name"),
+        _ => bail!("Several contacts are named `{input}`: use their npub"),
+    }
+}
+
+// ── Operations ───────────────────────────────────────────────────
+
+const TASK_COLUMNS: &str = "number, title, description, status, group_id, assignees_json, \
+                            due_at, created_at, updated_at, event_id";
+
+fn task_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Task> {
+    let number: i64 = row.get(0)?;
+    let assignees: String = row.get(5)?;
+    Ok(Task {
+        number: number as u64,
+        title: row.get(1)?,
+        description: row.get(2)?,
+        status: row.get(3)?,
+        group_id: row.get(4)?,
+        assignees: serde_json::from_str(&assignees).unwrap_or_default(),
+        due_at: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+        event_id: row.get(9)?,
+    })
+}
+
+fn update_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<TaskUpdate> {
+    let task_number: i64 = row.get(1)?;
+    Ok(TaskUpdate {
+        id: row.get(0)?,
+        task_number: task_number as u64,
+        status: row.get(2)?,
+        detail: row.get(3)?,
+        author: row.get(4)?,
+        created_at: row.get(5)?,
+        event_id: row.get(6)?,
+    })
+}
+
+/// Add a task with the next SNOW-N number. Its initial status is recorded
+/// as the first update, so both are published.
+pub fn add_task(conn: &Connection, task: &NewTask, now: i64) -> Result<Task> {
+    let title = task.title.trim();
+    if title.is_empty() {
+        bail!("Task title cannot be empty");
+    }
+    let status = parse_status(task.status.as_deref().unwrap_or("draft"))?;
+    let number: i64 = conn.query_row(
+        "SELECT COALESCE(MAX(number), 0) + 1 FROM nostr_tasks",
+        [],
+        |row| row.get(0),
+    )?;
+    conn.execute(
+        "INSERT INTO nostr_tasks
+            (number, title, description, status, group_id, assignees_json, due_at,
+             created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)",
+        params![
+            number,
+            title,
+            task.description.trim(),
+            status,
+            task.group_id,
+            serde_json::to_string(&task.assignees)?,
+            task.due_at,
+            now,
+        ],
+    )?;
+    conn.execute(
+        "INSERT INTO nostr_task_updates (task_number, status, created_at) VALUES (?1, ?2, ?3)",
+        params![number, status, now],
+    )?;
+    get_task(conn, number as u64)?.context("task vanished after insert")
+}
+
+/// A task by number.
+pub fn get_task(conn: &Connection, number: u64) -> Result<Option<Task>> {
+    let task = conn
+        .query_row(
+            &format!("SELECT {TASK_COLUMNS} FROM nostr_tasks WHERE number = ?1"),
+            params![number as i64],
+            task_from_row,
+        )
+        .optional()?;
+    Ok(task)
+}
+
+/// Tasks matching `filter`: open ones first by due date, then newest.
+pub fn list_tasks(conn: &Connection, filter: &TaskFilter) -> Result<Vec<Task>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {TASK_COLUMNS} FROM nostr_tasks
+         WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR group_id = ?2)
+         ORDER BY due_at IS NULL, due_at, number DESC"
+    ))?;
+    let status = filter.status.as_deref().map(parse_status).transpose()?;
+    let tasks = stmt
+        .query_map(params![status, filter.group_id], task_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(tasks
+        .into_iter()
+        .filter(|t| filter.include_closed || filter.status.is_some() || !t.is_closed())
+        .filter(|t| {
+            filter
+                .assignee
+                .as_ref()
+                .is_none_or(|hex| t.assignees.contains(hex))
+        })
+        .collect())
+}
+
+/// Apply `change` to a task and queue a status event for it, carrying the
+/// (possibly unchanged) status. Returns `None` when there is no such task.
+pub fn update_task(
+    conn: &Connection,
+    number: u64,
+    change: &TaskChange,
+    now: i64,
+) -> Result<Option<Task>> {
+    let Some(mut task) = get_task(conn, number)? else {
+        return Ok(None);
+    };
+    if let Some(status) = &change.status {
+        task.status = parse_status(status)?;
+    }
+    if let Some(assignees) = &change.assignees {
+        task.assignees.clone_from(assignees);
+    }
+    if let Some(due_at) = change.due_at {
+        task.due_at = due_at;
+    }
+    task.updated_at = now;
+    conn.execute(
+        "UPDATE nostr_tasks SET status = ?1, assignees_json = ?2, due_at = ?3, updated_at = ?4
+         WHERE number = ?5",
+        params![
+            task.status,
+            serde_json::to_string(&task.assignees)?,
+            task.due_at,
+            now,
+            number as i64
+        ],
+    )?;
+    conn.execute(
+        "INSERT INTO nostr_task_updates (task_number, status, detail, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![number as i64, task.status, change.detail.trim(), now],
+    )?;
+    Ok(Some(task))
+}
+
+/// Status history of a task, oldest first.
+pub fn task_history(conn: &Connection, number: u64) -> Result<Vec<TaskUpdate>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, task_number, status, detail, author, created_at, event_id
+         FROM nostr_task_updates WHERE task_number = ?1 ORDER BY created_at, id",
+    )?;
+    let updates = stmt
+        .query_map(params![number as i64], update_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(updates)
+}
+
+/// Tasks whose kind 1621 event is not published yet.
+pub fn pending_tasks(conn: &Connection) -> Result<Vec<Task>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {TASK_COLUMNS} FROM nostr_tasks WHERE event_id IS NULL ORDER BY number"
+    ))?;
+    let tasks = stmt
+        .query_map([], task_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(tasks)
+}
+
+/// Unpublished status updates of published tasks, oldest first, with
+/// their task.
+pub fn pending_updates(conn: &Connection) -> Result<Vec<(TaskUpdate, Task)>> {
+    let mut stmt = conn.prepare(
+        "SELECT u.id, u.task_number, u.status, u.detail, u.author, u.created_at, u.event_id
+         FROM nostr_task_updates u JOIN nostr_tasks t ON t.number = u.task_number
+         WHERE u.event_id IS NULL AND t.event_id IS NOT NULL
+         ORDER BY u.id",
+    )?;
+    let updates = stmt
+        .query_map([], update_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    updates
+        .into_iter()
+        .map(|update| {
+            let task = get_task(conn, update.task_number)?.context("update without task")?;
+            Ok((update, task))
+        })
+        .collect()
+}
+
+/// Record the published kind 1621 event of a task.
+pub fn mark_task_published(conn: &Connection, number: u64, event_id: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE nostr_tasks SET event_id = ?1 WHERE number = ?2",
+        params![event_id, number as i64],
+    )?;
+    Ok(())
+}
+
+/// Record the published status event of an update.
+pub fn mark_update_published(conn: &Connection, id: i64, event_id: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE nostr_task_updates SET event_id = ?1 WHERE id = ?2",
+        params![event_id, id],
+    )?;
+    Ok(())
+}
+
+/// Mirror a status event from someone else that references `task_event_id`.
+/// The task takes the status unless a newer update is already recorded.
+/// Returns the task when the event was new and the task is on the board.
+pub fn record_remote_update(
+    conn: &Connection,
+    task_event_id: &str,
+    kind: u16,
+    detail: &str,
+    author_hex: &str,
+    created_at: i64,
+    event_id: &str,
+) -> Result<Option<Task>> {
+    let Some(status) = status_for_kind(kind) else {
+        return Ok(None);
+    };
+    let Some(number) = conn
+        .query_row(
+            "SELECT number FROM nostr_tasks WHERE event_id = ?1",
+            params![task_event_id],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?
+    else {
+        return Ok(None);
+    };
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO nostr_task_updates
+            (task_number, status, detail, author, created_at, event_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![number, status, detail, author_hex, created_at, event_id],
+    )?;
+    if inserted == 0 {
+        return Ok(None);
+    }
+    conn.execute(
+        "UPDATE nostr_tasks SET status = ?1, updated_at = ?2
+         WHERE number = ?3 AND updated_at <= ?2",
+        params![status, created_at, number],
+    )?;
+    get_task(conn, number as u64)
+}
+
+/// One line per task for lists: `[status] SNOW-N: title (#group, @names, due YYYY-MM-DD)`.
+pub fn format_task_line(conn: &Connection, task: &Task) -> String {
+    let mut extras = Vec::new();
+    if let Some(group) = &task.group_id {
+        extras.push(format!("#{group}"));
+    }
+    for hex in &task.assignees {
+        extras.push(format!("@{}", social::display_name_or_short(conn, hex)));
+    }
+    if let Some(due) = task.due_at {
+        extras.push(format!("due {}", format_due(due)));
+    }
+    let extras = if extras.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", extras.join(", "))
+    };
+    format!("[{}] {}: {}{extras}", task.status, task.id(), task.title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        social::create_social_tables(&conn).unwrap();
+        create_task_tables(&conn).unwrap();
+        conn
+    }
+
+    fn new_task(title: &str) -> NewTask {
+        NewTask {
+            title: title.to_string(),
+            ..NewTask::default()
+        }
+    }
+
+    #[test]
+    fn statuses_map_to_kinds() {
+        assert_eq!(status_kind("queued"), Some(1630));
+        assert_eq!(status_kind("Done"), Some(1631));
+        assert_eq!(status_kind("failed"), Some(1637));
+        assert_eq!(status_kind("in-progress"), None);
+        for kind in 1630..=1637 {
+            assert_eq!(status_kind(status_for_kind(kind).unwrap()), Some(kind));
+        }
+        assert_eq!(status_for_kind(1629), None);
+        assert_eq!(status_for_kind(1638), None);
+        assert!(parse_status("nope")
+            .unwrap_err()
+            .to_string()
+            .contains("draft"));
+    }
+
+    #[test]
+    fn task_numbers_and_due_dates_parse() {
+        assert_eq!(parse_task_number("SNOW-12"), Some(12));
+        assert_eq!(parse_task_number(" snow-3 "), Some(3));
+        assert_eq!(parse_task_number("7"), Some(7));
+        assert_eq!(parse_task_number("SNOW-"), None);
+        assert_eq!(parse_task_number("0"), None);
+        assert_eq!(parse_task_number("task-1700000000"), None);
+
+        let due = parse_due("2026-03-02").unwrap();
+        assert_eq!(due, 1_772_495_999);
+        assert_eq!(format_due(due), "2026-03-02");
+        assert_eq!(parse_due("2026-03-02T10:00:00Z").unwrap(), 1_772_445_600);
+        assert!(parse_due("next week").is_err());
+    }
+
+    #[test]
+    fn tasks_get_sequential_numbers_and_an_initial_update() {
+        let conn = test_conn();
+        let first = add_task(&conn, &new_task("First"), 100).unwrap();
+        let second = add_task(
+            &conn,
+            &NewTask {
+                status: Some("queued".into()),
+                group_id: Some("dev".into()),
+                ..new_task("Second")
+            },
+            101,
+        )
+        .unwrap();
+        assert_eq!(first.id(), "SNOW-1");
+        assert_eq!(first.status, "draft");
+        assert_eq!(second.id(), "SNOW-2");
+        assert_eq!(task_history(&conn, 2).unwrap()[0].status, "queued");
+        assert!(add_task(&conn, &new_task("  "), 102).is_err());
+    }
+
+    #[test]
+    fn lists_hide_closed_tasks_and_filter() {
+        let conn = test_conn();
+        let alice = "aa".repeat(32);
+        add_task(
+            &conn,
+            &NewTask {
+                group_id: Some("dev".into()),
+                assignees: vec![alice.clone()],
+                due_at: Some(500),
+                ..new_task("Ship")
+            },
+            100,
+        )
+        .unwrap();
+        add_task(&conn, &new_task("Later"), 101).unwrap();
+        add_task(&conn, &new_task("Old"), 102).unwrap();
+        let change = TaskChange {
+            status: Some("done".into()),
+            ..TaskChange::default()
+        };
+        update_task(&conn, 3, &change, 103).unwrap();
+
+        let open = list_tasks(&conn, &TaskFilter::default()).unwrap();
+        let numbers: Vec<u64> = open.iter().map(|t| t.number).collect();
+        // Tasks with a due date come first.
+        assert_eq!(numbers, vec![1, 2]);
+
+        let all = TaskFilter {
+            include_closed: true,
+            ..TaskFilter::default()
+        };
+        assert_eq!(list_tasks(&conn, &all).unwrap().len(), 3);
+        let done = TaskFilter {
+            status: Some("DONE".into()),
+            ..TaskFilter::default()
+        };
+        assert_eq!(list_tasks(&conn, &done).unwrap()[0].number, 3);
+        let mine = TaskFilter {
+            assignee: Some(alice),
+            group_id: Some("dev".into()),
+            ..TaskFilter::default()
+        };
+        assert_eq!(list_tasks(&conn, &mine).unwrap()[0].title, "Ship");
+    }
+
+    #[test]
+    fn updates_wait_for_the_task_event_then_publish_in_order() {
+        let conn = test_conn();
+        add_task(&conn, &new_task("Publish me"), 100).unwrap();
+        let change = TaskChange {
+            status: Some("executing".into()),
+            detail: "on it".into(),
+            assignees: Some(vec!["bb".repeat(32)]),
+            due_at: Some(Some(900)),
+        };
+        let updated = update_task(&conn, 1, &change, 101).unwrap().unwrap();
+        assert_eq!(updated.status, "executing");
+        assert_eq!(updated.due_at, Some(900));
+        assert!(update_task(&conn, 9, &change, 101).unwrap().is_none());
+
+        // The task event carries the current state.
+        assert_eq!(pending_tasks(&conn).unwrap(), vec![updated]);
+        assert!(pending_updates(&conn).unwrap().is_empty());
+
+        mark_task_published(&conn, 1, "task-event").unwrap();
+        assert!(pending_tasks(&conn).unwrap().is_empty());
+        let pending = pending_updates(&conn).unwrap();
+        let statuses: Vec<&str> = pending.iter().map(|(u, _)| u.status.as_str()).collect();
+        assert_eq!(statuses, vec!["draft", "executing"]);
+        assert_eq!(pending[1].0.kind(), 1634);
+        assert_eq!(pending[1].1.assignees, vec!["bb".repeat(32)]);
+
+        mark_update_published(&conn, pending[0].0.id, "s1").unwrap();
+        mark_update_published(&conn, pending[1].0.id, "s2").unwrap();
+        assert!(pending_updates(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn remote_status_events_are_mirrored_once() {
+        let conn = test_conn();
+        add_task(&conn, &new_task("Shared"), 100).unwrap();
+        mark_task_published(&conn, 1, "task-event").unwrap();
+        let author = "cc".repeat(32);
+
+        let task = record_remote_update(&conn, "task-event", 1631, "merged", &author, 200, "e1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.status, "done");
+        assert!(
+            record_remote_update(&conn, "task-event", 1631, "merged", &author, 200, "e1")
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            record_remote_update(&conn, "unknown", 1631, "", &author, 200, "e2")
+                .unwrap()
+                .is_none()
+        );
+
+        // An older event is kept in the history but doesn't change the status.
+        record_remote_update(&conn, "task-event", 1635, "", &author, 150, "e3").unwrap();
+        assert_eq!(get_task(&conn, 1).unwrap().unwrap().status, "done");
+        let history = task_history(&conn, 1).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[2].author.as_deref(), Some(author.as_str()));
+        assert!(pending_updates(&conn)
+            .unwrap()
+            .iter()
+            .all(|(u, _)| u.author.is_none()));
+    }
+
+    #[test]
+    fn event_tags_carry_group_assignees_and_due() {
+        let task = Task {
+            number: 4,
+            title: "Tag me".into(),
+            description: String::new(),
+            status: "queued".into(),
+            group_id: Some("dev".into()),
+            assignees: vec!["aa".repeat(32)],
+            due_at: Some(1_000),
+            created_at: 0,
+            updated_at: 0,
+            event_id: None,
+        };
+        assert_eq!(
+            task.event_tags(),
+            vec![
+                vec!["subject".to_string(), "Tag me".to_string()],
+                vec!["t".to_string(), "snow-4".to_string()],
+                vec!["h".to_string(), "dev".to_string()],
+                vec!["p".to_string(), "aa".repeat(32)],
+                vec!["due".to_string(), "1000".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn assignees_resolve_from_keys_and_contact_names() {
+        let conn = test_conn();
+        let hex = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        social::upsert_npub(
+            &conn,
+            &social::SocialNpub {
+                hex_pubkey: hex.to_string(),
+                display_name: "Alice".to_string(),
+                first_seen: 0,
+                first_seen_group: None,
+                last_interaction: 0,
+                profile_json: None,
+                name_history_json: None,
+                notes_json: None,
+                owner_notes_json: None,
+                preferences_json: None,
+                is_owner: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(resolve_assignee(&conn, hex).unwrap(), hex);
+        assert_eq!(resolve_assignee(&conn, "@alice").unwrap(), hex);
+        assert!(resolve_assignee(&conn, "bob").is_err());
+    }
+
+    #[test]
+    fn json_store_is_imported_once() {
+        let conn = test_conn();
+        let dir = tempfile::TempDir::new().unwrap();
+        let json = serde_json::json!([{
+            "id": "SNOW-3",
+            "title": "Legacy",
+            "description": "from json",
+            "status": "review",
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-02T00:00:00Z",
+            "status_history": [
+                {"status": "draft", "timestamp": "2026-01-01T00:00:00Z"},
+                {"status": "review", "detail": "ready", "timestamp": "2026-01-02T00:00:00Z"}
+            ]
+        }, {"id": "task-123", "title": "No number"}]);
+        std::fs::write(dir.path().join(LEGACY_STORE), json.to_string()).unwrap();
+
+        assert_eq!(migrate_json_store(&conn, dir.path()).unwrap(), 1);
+        let task = get_task(&conn, 3).unwrap().unwrap();
+        assert_eq!(task.status, "review");
+        assert_eq!(task.updated_at, 1_767_312_000);
+        assert_eq!(task_history(&conn, 3).unwrap()[1].detail, "ready");
+        assert!(!dir.path().join(LEGACY_STORE).exists());
+        assert!(dir.path().join("nostr_tasks.json.migrated").exists());
+        // New tasks continue after the imported numbers.
+        assert_eq!(add_task(&conn, &new_task("Next"), 1).unwrap().number, 4);
+        assert_eq!(migrate_json_store(&conn, dir.path()).unwrap(), 0);
+    }
+}
@@ -51,6 +51,55 @@ impl std::fmt::Display for MemoryCategory {
     }
 }
 
+/// An entry to write with [`Memory::store_batch`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewMemory {
+    pub key: String,
+    pub content: String,
+    pub category: MemoryCategory,
+    pub session_id: Option<String>,
+}
+
+impl NewMemory {
+    pub fn new(
+        key: impl Into<String>,
+        content: impl Into<String>,
+        category: MemoryCategory,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            content: content.into(),
+            category,
+            session_id: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+}
+
+/// One step of a [`Memory::transaction`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryOp {
+    /// Store (or overwrite) an entry
+    Store(NewMemory),
+    /// Remove the entry with this key
+    Forget(String),
+}
+
+impl MemoryOp {
+    /// Key the operation writes
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Store(entry) => &entry.key,
+            Self::Forget(key) => key,
+        }
+    }
+}
+
 /// Core memory trait — implement for any persistence backend
 #[async_trait]
 pub trait Memory: Send + Sync {
@@ -93,6 +142,45 @@ pub trait Memory: Send + Sync {
     /// Health check
     async fn health_check(&self) -> bool;
 
+    /// Whether [`Memory::transaction`] is atomic for this backend.
+    fn supports_transactions(&self) -> bool {
+        false
+    }
+
+    /// Store several entries in one call; atomic where
+    /// [`Memory::supports_transactions`] is true.
+    async fn store_batch(&self, entries: &[NewMemory]) -> anyhow::Result<()> {
+        let ops: Vec<MemoryOp> = entries.iter().cloned().map(MemoryOp::Store).collect();
+        self.transaction(&ops).await.map(|_| ())
+    }
+
+    /// Apply stores and forgets in order. Returns, per operation, whether it
+    /// changed anything (a forget of a missing key is `false`).
+    ///
+    /// Backends that support transactions apply all operations or none. The
+    /// default applies them one at a time and stops at the first error,
+    /// leaving earlier operations in place.
+    async fn transaction(&self, ops: &[MemoryOp]) -> anyhow::Result<Vec<bool>> {
+        let mut applied = Vec::with_capacity(ops.len());
+        for op in ops {
+            let changed = match op {
+                MemoryOp::Store(entry) => {
+                    self.store(
+                        &entry.key,
+                        &entry.content,
+                        entry.category.clone(),
+                        entry.session_id.as_deref(),
+                    )
+                    .await?;
+                    true
+                }
+                MemoryOp::Forget(key) => self.forget(key).await?,
+            };
+            applied.push(changed);
+        }
+        Ok(applied)
+    }
+
     /// Rebuild embeddings for all memories using the current embedding provider.
     /// Returns the number of memories reindexed, or an error if not supported.
     ///
@@ -61,6 +61,9 @@ impl UnifiedHit {
 
 // ── Search ───────────────────────────────────────────────────────
 
+/// Every [`UnifiedHit::source`] label, for [`unified_recall_from`].
+pub const ALL_SOURCES: &[&str] = &["memory", "social", "message", "document"];
+
 /// Search across all memory types and merge results by relevance.
 ///
 /// Queries:
@@ -73,6 +76,17 @@ impl UnifiedHit {
 /// Each subsystem search is best-effort — if a table doesn't exist yet,
 /// that source is silently skipped.
 pub fn unified_recall(conn: &Connection, query: &str, limit: usize) -> Result<Vec<UnifiedHit>> {
+    unified_recall_from(conn, query, limit, ALL_SOURCES)
+}
+
+/// [`unified_recall`] limited to the subsystems whose
+/// [`UnifiedHit::source`] label is in `sources`.
+pub fn unified_recall_from(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+    sources: &[&str],
+) -> Result<Vec<UnifiedHit>> {
     if query.trim().is_empty() {
         return Ok(Vec::new());
     }
@@ -81,30 +95,38 @@ pub fn unified_recall(conn: &Connection, query: &str, limit: usize) -> Result<Ve
     let mut hits: Vec<UnifiedHit> = Vec::new();
 
     // 1. Core memories
-    if let Ok(entries) = search_memories_fts(conn, query, per_source_limit) {
-        for entry in entries {
-            hits.push(UnifiedHit::Memory(entry));
+    if sources.contains(&"memory") {
+        if let Ok(entries) = search_memories_fts(conn, query, per_source_limit) {
+            for entry in entries {
+                hits.push(UnifiedHit::Memory(entry));
+            }
         }
     }
 
     // 2. Social data
-    if let Ok(npubs) = social::search_social(conn, query, per_source_limit) {
-        for npub in npubs {
-            hits.push(UnifiedHit::Social(npub));
+    if sources.contains(&"social") {
+        if let Ok(npubs) = social::search_social(conn, query, per_source_limit) {
+            for npub in npubs {
+                hits.push(UnifiedHit::Social(npub));
+            }
         }
     }
 
     // 3. Messages
-    if let Ok(messages) = message_index::search_messages(conn, query, per_source_limit) {
-        for msg in messages {
-            hits.push(UnifiedHit::Message(msg));
+    if sources.contains(&"message") {
+        if let Ok(messages) = message_index::search_messages(conn, query, per_source_limit) {
+            for msg in messages {
+                hits.push(UnifiedHit::Message(msg));
+            }
         }
     }
 
     // 4. Documents
-    if let Ok(docs) = doc_index::search_docs(conn, query, per_source_limit) {
-        for doc in docs {
-            hits.push(UnifiedHit::Document(doc));
+    if sources.contains(&"document") {
+        if let Ok(docs) = doc_index::search_docs(conn, query, per_source_limit) {
+            for doc in docs {
+                hits.push(UnifiedHit::Document(doc));
+            }
         }
     }
 
@@ -275,6 +297,34 @@ mod tests {
         assert!(matches!(results[0], UnifiedHit::Memory(_)));
     }
 
+    #[test]
+    fn recall_from_skips_other_sources() {
+        let (_tmp, conn) = test_env();
+        conn.execute(
+            "INSERT INTO memories (id, key, content, category, created_at, updated_at)
+             VALUES ('m1', 'k', 'Rust borrow checker', 'core', '2025-01-01', '2025-01-01')",
+            [],
+        )
+        .unwrap();
+        message_index::index_message(
+            &conn,
+            &IndexableMessage {
+                event_id: "evt1".to_string(),
+                sender_hex: "aabb".to_string(),
+                group_id: Some("dev".to_string()),
+                content: "Rust borrow checker errors".to_string(),
+                created_at: 1000,
+                kind: 9,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(unified_recall(&conn, "borrow", 10).unwrap().len(), 2);
+        let messages = unified_recall_from(&conn, "borrow", 10, &["message"]).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].source(), "message");
+    }
+
     #[test]
     fn finds_social_data() {
         let (_tmp, conn) = test_env();
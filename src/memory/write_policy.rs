@@ -0,0 +1,642 @@
+//! Memory write policy: what the agent is allowed to remember.
+//!
+//! `[memory.write_policy]` compiles into a [`WritePolicy`]. The memory
+//! factory wraps the configured backend in a [`PolicyMemory`], so every
+//! `Memory::store` — including the NIP-78 publish of relay-backed backends —
+//! is checked first; the Nostr channel checks agent-written social notes
+//! the same way before they are saved and published.
+//!
+//! A write in a no-memory group (the group of the current
+//! [`super::snowclaw_ext::scope`]) or matching a banned pattern is refused
+//! with [`WritePolicyError::Denied`]. Writes in a `confirm` category are
+//! queued in `state/memory_pending.json` under the workspace and fail with
+//! [`WritePolicyError::Pending`]; [`approve`] stores them once the owner
+//! agrees, [`reject`] drops them, and they expire after `pending_hours`.
+
+use super::traits::{Memory, MemoryCategory, MemoryEntry, MemoryOp};
+use crate::config::MemoryWritePolicyConfig;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Pending writes file, under `<workspace>/state`.
+const PENDING_FILE: &str = "memory_pending.json";
+
+/// What the policy does with a write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteDecision {
+    Allow,
+    /// Hold until the owner approves.
+    Confirm,
+    /// Refuse, with the reason.
+    Deny(String),
+}
+
+/// A write the policy did not let through.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WritePolicyError {
+    #[error("memory write '{key}' is waiting for owner approval (pending #{id})")]
+    Pending { key: String, id: u64 },
+    #[error("memory write '{key}' refused by the write policy: {reason}")]
+    Denied { key: String, reason: String },
+}
+
+/// Compiled `[memory.write_policy]`.
+#[derive(Debug, Clone)]
+pub struct WritePolicy {
+    auto_store: HashSet<String>,
+    confirm: HashSet<String>,
+    banned: Vec<Regex>,
+    no_memory_groups: HashSet<String>,
+    pending_secs: i64,
+}
+
+impl WritePolicy {
+    /// The policy, or `None` when it is disabled.
+    pub fn from_config(config: &MemoryWritePolicyConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let names = |list: &[String]| -> HashSet<String> {
+            list.iter()
+                .map(|c| c.trim().to_ascii_lowercase())
+                .filter(|c| !c.is_empty())
+                .collect()
+        };
+        let mut banned = Vec::with_capacity(config.banned_patterns.len());
+        for pattern in &config.banned_patterns {
+            banned.push(
+                Regex::new(pattern)
+                    .with_context(|| format!("invalid banned memory pattern '{pattern}'"))?,
+            );
+        }
+        Ok(Some(Self {
+            auto_store: names(&config.auto_store),
+            confirm: names(&config.confirm),
+            banned,
+            no_memory_groups: config
+                .no_memory_groups
+                .iter()
+                .map(|g| g.trim().trim_start_matches('#').to_string())
+                .filter(|g| !g.is_empty())
+                .collect(),
+            pending_secs: i64::try_from(config.pending_hours.saturating_mul(3600))
+                .unwrap_or(i64::MAX),
+        }))
+    }
+
+    pub fn is_no_memory_group(&self, group: &str) -> bool {
+        self.no_memory_groups.contains(group)
+    }
+
+    /// Why `key`/`content` may not be remembered in `group`, if it may not.
+    /// Checks the no-memory groups and banned patterns, not the category.
+    pub fn refusal(&self, key: &str, content: &str, group: Option<&str>) -> Option<String> {
+        if let Some(group) = group.filter(|g| self.is_no_memory_group(g)) {
+            return Some(format!("#{group} is a no-memory group"));
+        }
+        self.banned
+            .iter()
+            .find(|re| re.is_match(key) || re.is_match(content))
+            .map(|re| format!("matches banned pattern '{}'", re.as_str()))
+    }
+
+    /// Decide a write of `category` in `group`.
+    pub fn decide(
+        &self,
+        key: &str,
+        content: &str,
+        category: &MemoryCategory,
+        group: Option<&str>,
+    ) -> WriteDecision {
+        if let Some(reason) = self.refusal(key, content, group) {
+            return WriteDecision::Deny(reason);
+        }
+        let name = category.to_string().to_ascii_lowercase();
+        if self.auto_store.contains(&name) {
+            WriteDecision::Allow
+        } else if self.confirm.contains(&name) {
+            WriteDecision::Confirm
+        } else {
+            WriteDecision::Deny(format!("category '{name}' may not be stored"))
+        }
+    }
+}
+
+/// Group of the conversation the current task runs in.
+pub fn current_group() -> Option<String> {
+    super::snowclaw_ext::current_context().and_then(|c| c.group_id)
+}
+
+// ── Pending writes ───────────────────────────────────────────────
+
+/// A write waiting for the owner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingWrite {
+    pub id: u64,
+    pub key: String,
+    pub content: String,
+    pub category: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Group the agent was in when it asked
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// Unix seconds
+    pub created_at: i64,
+    /// Approved and about to be stored
+    #[serde(default)]
+    pub approved: bool,
+}
+
+impl PendingWrite {
+    pub fn category(&self) -> MemoryCategory {
+        match self.category.as_str() {
+            "core" => MemoryCategory::Core,
+            "daily" => MemoryCategory::Daily,
+            "conversation" => MemoryCategory::Conversation,
+            other => MemoryCategory::Custom(other.to_string()),
+        }
+    }
+
+    fn is_write(&self, key: &str, content: &str, category: &MemoryCategory) -> bool {
+        self.key == key && self.content == content && self.category == category.to_string()
+    }
+}
+
+/// Persisted pending writes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingWrites {
+    #[serde(default)]
+    pub next_id: u64,
+    #[serde(default)]
+    pub writes: Vec<PendingWrite>,
+}
+
+impl PendingWrites {
+    pub fn path(workspace_dir: &Path) -> PathBuf {
+        workspace_dir.join("state").join(PENDING_FILE)
+    }
+
+    /// Load pending writes; empty when the file does not exist yet.
+    pub fn load(workspace_dir: &Path) -> Result<Self> {
+        let path = Self::path(workspace_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&raw).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, workspace_dir: &Path) -> Result<()> {
+        let path = Self::path(workspace_dir);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Queue a write; the same write asked for again keeps its id.
+    pub fn queue(
+        &mut self,
+        key: &str,
+        content: &str,
+        category: &MemoryCategory,
+        session_id: Option<&str>,
+        group_id: Option<&str>,
+        now: i64,
+    ) -> u64 {
+        if let Some(existing) = self
+            .writes
+            .iter()
+            .find(|w| !w.approved && w.is_write(key, content, category))
+        {
+            return existing.id;
+        }
+        self.next_id += 1;
+        self.writes.push(PendingWrite {
+            id: self.next_id,
+            key: key.to_string(),
+            content: content.to_string(),
+            category: category.to_string(),
+            session_id: session_id.map(str::to_string),
+            group_id: group_id.map(str::to_string),
+            created_at: now,
+            approved: false,
+        });
+        self.next_id
+    }
+
+    /// Consume the approval of this write, if the owner gave one.
+    pub fn take_approved(&mut self, key: &str, content: &str, category: &MemoryCategory) -> bool {
+        let Some(index) = self
+            .writes
+            .iter()
+            .position(|w| w.approved && w.is_write(key, content, category))
+        else {
+            return false;
+        };
+        self.writes.remove(index);
+        true
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<PendingWrite> {
+        let index = self.writes.iter().position(|w| w.id == id)?;
+        Some(self.writes.remove(index))
+    }
+
+    /// Drop writes not decided on within `max_age` seconds; returns them.
+    pub fn expire(&mut self, now: i64, max_age: i64) -> Vec<PendingWrite> {
+        let (expired, kept) = std::mem::take(&mut self.writes)
+            .into_iter()
+            .partition(|w| !w.approved && now.saturating_sub(w.created_at) > max_age);
+        self.writes = kept;
+        expired
+    }
+}
+
+/// Writes waiting for the owner, oldest first.
+pub fn pending(workspace_dir: &Path) -> Result<Vec<PendingWrite>> {
+    Ok(PendingWrites::load(workspace_dir)?
+        .writes
+        .into_iter()
+        .filter(|w| !w.approved)
+        .collect())
+}
+
+/// Approve pending write `id` and store it through `memory`. `None` when
+/// there is no such write.
+pub async fn approve(
+    memory: &dyn Memory,
+    workspace_dir: &Path,
+    id: u64,
+) -> Result<Option<PendingWrite>> {
+    let mut state = PendingWrites::load(workspace_dir)?;
+    let Some(write) = state.writes.iter_mut().find(|w| w.id == id) else {
+        return Ok(None);
+    };
+    write.approved = true;
+    let write = write.clone();
+    state.save(workspace_dir)?;
+
+    let stored = memory
+        .store(
+            &write.key,
+            &write.content,
+            write.category(),
+            write.session_id.as_deref(),
+        )
+        .await;
+    // A policy-wrapped backend consumed the approval; a bare one did not
+    let mut state = PendingWrites::load(workspace_dir)?;
+    match stored {
+        Ok(()) => {
+            if state.remove(id).is_some() {
+                state.save(workspace_dir)?;
+            }
+            Ok(Some(write))
+        }
+        Err(e) => {
+            if let Some(write) = state.writes.iter_mut().find(|w| w.id == id) {
+                write.approved = false;
+                state.save(workspace_dir)?;
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Drop pending write `id`. `None` when there is no such write.
+pub fn reject(workspace_dir: &Path, id: u64) -> Result<Option<PendingWrite>> {
+    let mut state = PendingWrites::load(workspace_dir)?;
+    let removed = state.remove(id);
+    if removed.is_some() {
+        state.save(workspace_dir)?;
+    }
+    Ok(removed)
+}
+
+// ── Enforcing wrapper ────────────────────────────────────────────
+
+/// A memory backend behind the write policy.
+pub struct PolicyMemory {
+    inner: Box<dyn Memory>,
+    policy: WritePolicy,
+    workspace_dir: PathBuf,
+    /// Serializes read-modify-write of the pending file.
+    pending_lock: Mutex<()>,
+}
+
+impl PolicyMemory {
+    pub fn new(inner: Box<dyn Memory>, policy: WritePolicy, workspace_dir: &Path) -> Self {
+        Self {
+            inner,
+            policy,
+            workspace_dir: workspace_dir.to_path_buf(),
+            pending_lock: Mutex::new(()),
+        }
+    }
+
+    /// Check one write; `Ok` when it may go to the backend now.
+    fn check(
+        &self,
+        key: &str,
+        content: &str,
+        category: &MemoryCategory,
+        session_id: Option<&str>,
+    ) -> Result<()> {
+        let group = current_group();
+        match self.policy.decide(key, content, category, group.as_deref()) {
+            WriteDecision::Allow => Ok(()),
+            WriteDecision::Deny(reason) => {
+                tracing::info!("🧠 Memory write '{key}' refused: {reason}");
+                Err(WritePolicyError::Denied {
+                    key: key.to_string(),
+                    reason,
+                }
+                .into())
+            }
+            WriteDecision::Confirm => {
+                let _guard = self.pending_lock.lock();
+                let mut state = PendingWrites::load(&self.workspace_dir)?;
+                let now = chrono::Utc::now().timestamp();
+                for expired in state.expire(now, self.policy.pending_secs) {
+                    tracing::info!(
+                        "🧠 Pending memory write #{} '{}' expired",
+                        expired.id,
+                        expired.key
+                    );
+                }
+                if state.take_approved(key, content, category) {
+                    state.save(&self.workspace_dir)?;
+                    return Ok(());
+                }
+                let id = state.queue(key, content, category, session_id, group.as_deref(), now);
+                state.save(&self.workspace_dir)?;
+                tracing::info!("🧠 Memory write '{key}' waits for owner approval (#{id})");
+                Err(WritePolicyError::Pending {
+                    key: key.to_string(),
+                    id,
+                }
+                .into())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Memory for PolicyMemory {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn store(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        session_id: Option<&str>,
+    ) -> Result<()> {
+        self.check(key, content, &category, session_id)?;
+        self.inner.store(key, content, category, session_id).await
+    }
+
+    async fn recall(
+        &self,
+        query: &str,
+        limit: usize,
+        session_id: Option<&str>,
+    ) -> Result<Vec<MemoryEntry>> {
+        self.inner.recall(query, limit, session_id).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<MemoryEntry>> {
+        self.inner.get(key).await
+    }
+
+    async fn list(
+        &self,
+        category: Option<&MemoryCategory>,
+        session_id: Option<&str>,
+    ) -> Result<Vec<MemoryEntry>> {
+        self.inner.list(category, session_id).await
+    }
+
+    async fn forget(&self, key: &str) -> Result<bool> {
+        self.inner.forget(key).await
+    }
+
+    async fn count(&self) -> Result<usize> {
+        self.inner.count().await
+    }
+
+    async fn health_check(&self) -> bool {
+        self.inner.health_check().await
+    }
+
+    fn supports_transactions(&self) -> bool {
+        self.inner.supports_transactions()
+    }
+
+    /// Refused stores fail the whole transaction before anything is
+    /// applied. Stores held for approval are left out and reported as
+    /// unchanged.
+    async fn transaction(&self, ops: &[MemoryOp]) -> Result<Vec<bool>> {
+        let mut allowed = Vec::with_capacity(ops.len());
+        let mut held = Vec::with_capacity(ops.len());
+        for op in ops {
+            let pass = match op {
+                MemoryOp::Store(entry) => match self.check(
+                    &entry.key,
+                    &entry.content,
+                    &entry.category,
+                    entry.session_id.as_deref(),
+                ) {
+                    Ok(()) => true,
+                    Err(e) => match e.downcast_ref::<WritePolicyError>() {
+                        Some(WritePolicyError::Pending { .. }) => false,
+                        _ => return Err(e),
+                    },
+                },
+                MemoryOp::Forget(_) => true,
+            };
+            if pass {
+                allowed.push(op.clone());
+            }
+            held.push(!pass);
+        }
+        let mut applied = self.inner.transaction(&allowed).await?.into_iter();
+        Ok(held
+            .into_iter()
+            .map(|held| !held && applied.next().unwrap_or(false))
+            .collect())
+    }
+
+    async fn reindex(
+        &self,
+        progress_callback: Option<Box<dyn Fn(usize, usize) + Send + Sync>>,
+    ) -> Result<usize> {
+        self.inner.reindex(progress_callback).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::snowclaw_ext::{scope, RecallContext};
+    use crate::memory::{NewMemory, NoneMemory, SqliteMemory};
+
+    fn config() -> MemoryWritePolicyConfig {
+        MemoryWritePolicyConfig {
+            enabled: true,
+            confirm: vec!["people".into()],
+            banned_patterns: vec![r"(?i)password\s*[:=]".into()],
+            no_memory_groups: vec!["#therapy".into()],
+            ..MemoryWritePolicyConfig::default()
+        }
+    }
+
+    fn policy_memory(dir: &Path) -> PolicyMemory {
+        let policy = WritePolicy::from_config(&config()).unwrap().unwrap();
+        PolicyMemory::new(Box::new(SqliteMemory::new(dir).unwrap()), policy, dir)
+    }
+
+    #[test]
+    fn decisions_follow_the_config() {
+        assert!(
+            WritePolicy::from_config(&MemoryWritePolicyConfig::default())
+                .unwrap()
+                .is_none()
+        );
+        let policy = WritePolicy::from_config(&config()).unwrap().unwrap();
+        let people = MemoryCategory::Custom("people".into());
+
+        assert_eq!(
+            policy.decide("k", "likes tea", &MemoryCategory::Core, None),
+            WriteDecision::Allow
+        );
+        assert_eq!(
+            policy.decide("k", "alice likes tea", &people, None),
+            WriteDecision::Confirm
+        );
+        assert!(matches!(
+            policy.decide("k", "x", &MemoryCategory::Custom("secrets".into()), None),
+            WriteDecision::Deny(_)
+        ));
+        assert!(matches!(
+            policy.decide("k", "Password: hunter2", &MemoryCategory::Core, None),
+            WriteDecision::Deny(_)
+        ));
+        assert!(matches!(
+            policy.decide("k", "likes tea", &MemoryCategory::Core, Some("therapy")),
+            WriteDecision::Deny(_)
+        ));
+
+        let bad = MemoryWritePolicyConfig {
+            banned_patterns: vec!["(".into()],
+            ..config()
+        };
+        assert!(WritePolicy::from_config(&bad).is_err());
+    }
+
+    #[tokio::test]
+    async fn stores_are_enforced_in_scope() {
+        let dir = tempfile::tempdir().unwrap();
+        let mem = policy_memory(dir.path());
+
+        mem.store("tea", "likes tea", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+        let in_group = scope(
+            Some(RecallContext::group("nostr", "therapy")),
+            mem.store("mood", "feels low", MemoryCategory::Core, None),
+        )
+        .await;
+        let err = in_group.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<WritePolicyError>(),
+            Some(WritePolicyError::Denied { .. })
+        ));
+        assert!(mem.get("mood").await.unwrap().is_none());
+        assert!(mem.get("tea").await.unwrap().is_some());
+
+        // A refused store fails the batch before anything is written
+        let batch = [
+            NewMemory::new("a", "fine", MemoryCategory::Core),
+            NewMemory::new("b", "password=1", MemoryCategory::Core),
+        ];
+        assert!(mem.store_batch(&batch).await.is_err());
+        assert!(mem.get("a").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn confirm_categories_wait_for_the_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let mem = policy_memory(dir.path());
+        let people = MemoryCategory::Custom("people".into());
+
+        let err = mem
+            .store("alice", "alice likes tea", people.clone(), None)
+            .await
+            .unwrap_err();
+        let Some(WritePolicyError::Pending { id, .. }) = err.downcast_ref::<WritePolicyError>()
+        else {
+            panic!("expected a pending write, got {err}");
+        };
+        let id = *id;
+        // Asking again keeps the same pending write
+        assert!(mem
+            .store("alice", "alice likes tea", people.clone(), None)
+            .await
+            .is_err());
+        assert_eq!(pending(dir.path()).unwrap().len(), 1);
+        assert!(mem.get("alice").await.unwrap().is_none());
+
+        let approved = approve(&mem, dir.path(), id).await.unwrap().unwrap();
+        assert_eq!(approved.key, "alice");
+        assert!(mem.get("alice").await.unwrap().is_some());
+        assert!(pending(dir.path()).unwrap().is_empty());
+        assert!(approve(&mem, dir.path(), id).await.unwrap().is_none());
+
+        // Held stores are skipped in a transaction
+        let applied = mem
+            .transaction(&[
+                MemoryOp::Store(NewMemory::new("bob", "bob likes coffee", people)),
+                MemoryOp::Store(NewMemory::new("tea", "likes tea", MemoryCategory::Core)),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(applied, vec![false, true]);
+        let queued = pending(dir.path()).unwrap();
+        assert_eq!(queued.len(), 1);
+        assert!(reject(dir.path(), queued[0].id).unwrap().is_some());
+        assert!(pending(dir.path()).unwrap().is_empty());
+
+        // Approving through a bare backend also clears the write
+        let mut state = PendingWrites::default();
+        let id = state.queue("k", "v", &MemoryCategory::Core, None, None, 0);
+        state.save(dir.path()).unwrap();
+        approve(&NoneMemory::new(), dir.path(), id).await.unwrap();
+        assert!(PendingWrites::load(dir.path()).unwrap().writes.is_empty());
+    }
+
+    #[test]
+    fn undecided_writes_expire() {
+        let mut state = PendingWrites::default();
+        state.queue("old", "v", &MemoryCategory::Core, None, None, 100);
+        state.queue("new", "v", &MemoryCategory::Core, None, None, 5000);
+        let expired = state.expire(5000, 3600);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].key, "old");
+        assert_eq!(state.writes.len(), 1);
+    }
+}
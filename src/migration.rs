@@ -86,12 +86,15 @@ struct JsonMergeStats {
     duplicate_items_skipped: usize,
 }
 
-pub async fn handle_command(command: crate::MigrateCommands, config: &Config) -> Result<()> {
+pub async fn handle_command(
+    command: crate::MigrateCommands,
+    config: &Config,
+    dry_run: bool,
+) -> Result<()> {
     match command {
         crate::MigrateCommands::Openclaw {
             source,
             source_config,
-            dry_run,
             no_memory,
             no_config,
         } => {
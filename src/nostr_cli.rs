@@ -1,5 +1,6 @@
 use anyhow::Result;
 use clap::Subcommand;
+use nostr_core::Signer;
 use nostr_sdk::prelude::*;
 
 use crate::config::Config;
@@ -44,6 +45,23 @@ pub enum NostrCommands {
     },
     /// Interactive onboarding: generate key, set profile, join groups
     Onboard,
+    /// Ask relays to delete the agent's own events (NIP-09) and purge local copies
+    Delete {
+        /// Event IDs (hex, note1... or nevent1...)
+        #[clap(required = true)]
+        ids: Vec<String>,
+        /// Group the events were posted in (adds its h tag for NIP-29 relays)
+        #[clap(long)]
+        group: Option<String>,
+        /// Reason shown to clients
+        #[clap(long)]
+        reason: Option<String>,
+    },
+    /// Send and read encrypted DMs with the configured identity (NIP-17)
+    Dm {
+        #[clap(subcommand)]
+        action: NostrDmAction,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -62,6 +80,9 @@ pub enum NostrConfigAction {
         /// Number of context history messages
         #[clap(long)]
         context_history: Option<usize>,
+        /// Mute for a while (e.g. 2h, 30m, 1d); the respond mode applies again afterwards
+        #[clap(long)]
+        mute: Option<String>,
     },
     /// Get current dynamic config
     Get {
@@ -71,6 +92,26 @@ pub enum NostrConfigAction {
     },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum NostrDmAction {
+    /// Send a NIP-17 gift-wrapped DM
+    Send {
+        /// Recipient (npub1... or hex pubkey)
+        recipient: String,
+        /// Message text
+        text: String,
+    },
+    /// Fetch DMs from the relays, decrypt them and print the conversations
+    Fetch {
+        /// Only messages since a Unix timestamp or duration ago (e.g. 2h, 1d; default 1d)
+        #[clap(long)]
+        since: Option<String>,
+        /// Only the conversation with this npub or hex pubkey
+        #[clap(long)]
+        with: Option<String>,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 pub enum NostrMemoryAction {
     /// Show memory for an npub (hex or npub1...)
@@ -92,12 +133,19 @@ pub enum NostrMemoryAction {
     },
     /// List all known contacts
     List,
+    /// Show who replies to and mentions whom in a group
+    Graph {
+        /// Group ID
+        group: String,
+    },
 }
 
-pub async fn handle_command(cmd: NostrCommands, config: &Config) -> Result<()> {
+/// Handle `snowclaw nostr <subcommand>`. With `dry_run`, `delete` only
+/// reports what it would publish and purge.
+pub async fn handle_command(cmd: NostrCommands, config: &Config, dry_run: bool) -> Result<()> {
     match cmd {
         NostrCommands::Keygen => cmd_keygen(config),
-        NostrCommands::Whoami => cmd_whoami(config),
+        NostrCommands::Whoami => cmd_whoami(config).await,
         NostrCommands::Import { nsec } => cmd_import(nsec, config),
         NostrCommands::Relays => cmd_relays(config),
         NostrCommands::Config { action } => cmd_config(action, config).await,
@@ -106,23 +154,23 @@ pub async fn handle_command(cmd: NostrCommands, config: &Config) -> Result<()> {
             cmd_profile(name, about, picture, nip05, config).await
         }
         NostrCommands::Onboard => cmd_onboard(config).await,
+        NostrCommands::Delete { ids, group, reason } => {
+            cmd_delete(ids, group, reason, config, dry_run).await
+        }
+        NostrCommands::Dm { action } => cmd_dm(action, config).await,
     }
 }
 
-async fn cmd_config(action: NostrConfigAction, config: &Config) -> Result<()> {
+/// Open a listener-less channel with the configured identity, for commands
+/// that publish on the agent's behalf.
+async fn open_channel(config: &Config) -> Result<crate::channels::nostr::NostrChannel> {
     let nostr_cfg = config
         .channels_config
         .nostr
         .as_ref()
         .ok_or_else(|| anyhow::anyhow!("Nostr not configured"))?;
 
-    let nsec_str = nostr_cfg
-        .nsec
-        .clone()
-        .or_else(|| std::env::var("SNOWCLAW_NSEC").ok())
-        .ok_or_else(|| anyhow::anyhow!("No nsec configured"))?;
-
-    let keys = Keys::parse(&nsec_str)?;
+    let signer = config_signer(config).await?;
 
     let owner = nostr_cfg
         .owner
@@ -131,7 +179,7 @@ async fn cmd_config(action: NostrConfigAction, config: &Config) -> Result<()> {
 
     let channel_config = crate::channels::nostr::NostrChannelConfig {
         relays: nostr_cfg.relays.clone(),
-        keys: keys.clone(),
+        signer,
         groups: nostr_cfg.groups.clone(),
         listen_dms: false,
         allowed_pubkeys: vec![],
@@ -145,7 +193,16 @@ async fn cmd_config(action: NostrConfigAction, config: &Config) -> Result<()> {
         index_interval_minutes: 30,
     };
 
-    let channel = crate::channels::nostr::NostrChannel::new(channel_config).await?;
+    crate::channels::nostr::NostrChannel::new(channel_config).await
+}
+
+async fn cmd_config(action: NostrConfigAction, config: &Config) -> Result<()> {
+    let nostr_cfg = config
+        .channels_config
+        .nostr
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Nostr not configured"))?;
+    let channel = open_channel(config).await?;
 
     match action {
         NostrConfigAction::Set {
@@ -153,6 +210,7 @@ async fn cmd_config(action: NostrConfigAction, config: &Config) -> Result<()> {
             global,
             respond_mode,
             context_history,
+            mute,
         } => {
             let d_tag = if global {
                 "snowclaw:config:global".to_string()
@@ -162,8 +220,26 @@ async fn cmd_config(action: NostrConfigAction, config: &Config) -> Result<()> {
                 anyhow::bail!("Specify --global or --group <name>");
             };
 
+            let muted_until = match mute.as_deref() {
+                Some(raw) => {
+                    let duration = crate::channels::nostr_commands::parse_duration(raw)
+                        .filter(|d| !d.is_zero() && *d <= crate::channels::nostr_commands::MAX_MUTE)
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("Invalid --mute duration `{raw}` (1s to 30d, e.g. 2h)")
+                        })?;
+                    Some(chrono::Utc::now().timestamp().unsigned_abs() + duration.as_secs())
+                }
+                None => None,
+            };
+
             let event_id = channel
-                .publish_config_event(&d_tag, respond_mode.as_deref(), context_history)
+                .publish_config_event(
+                    &d_tag,
+                    respond_mode.as_deref(),
+                    context_history,
+                    None,
+                    muted_until,
+                )
                 .await?;
 
             println!("✅ Published config event: {event_id}");
@@ -173,6 +249,9 @@ async fn cmd_config(action: NostrConfigAction, config: &Config) -> Result<()> {
             if let Some(n) = context_history {
                 println!("   context_history: {n}");
             }
+            if let (Some(raw), Some(until)) = (&mute, muted_until) {
+                println!("   muted_until: {until} ({raw})");
+            }
         }
         NostrConfigAction::Get { group } => {
             let scope = if let Some(ref g) = group {
@@ -196,6 +275,244 @@ async fn cmd_config(action: NostrConfigAction, config: &Config) -> Result<()> {
     Ok(())
 }
 
+/// Publish a deletion request for the agent's own events and purge them locally.
+async fn cmd_delete(
+    ids: Vec<String>,
+    group: Option<String>,
+    reason: Option<String>,
+    config: &Config,
+    dry_run: bool,
+) -> Result<()> {
+    let ids = crate::channels::nostr_deletion::parse_event_ids(&ids.join(" "))
+        .map_err(|e| anyhow::anyhow!(e))?;
+    if dry_run {
+        println!(
+            "[dry-run] Would publish a kind 5 deletion request for {} event(s)",
+            ids.len()
+        );
+        for id in &ids {
+            println!("   {id}");
+        }
+        if let Some(group) = &group {
+            println!("   group: {group}");
+        }
+        if let Some(reason) = &reason {
+            println!("   reason: {reason}");
+        }
+        println!("   and purge their indexed messages, links, DMs and cached events");
+        return Ok(());
+    }
+    let channel = open_channel(config).await?;
+    let (event_id, report) = channel
+        .request_deletion(&ids, group.as_deref(), reason.as_deref())
+        .await?;
+
+    println!("🗑️  Deletion request published: {event_id}");
+    println!("   events: {}", ids.len());
+    println!("   indexed messages purged: {}", report.indexed_messages);
+    println!("   shared links purged: {}", report.links);
+    println!("   DM messages purged: {}", report.dm_messages);
+    println!("   cached events purged: {}", report.cached_events);
+    Ok(())
+}
+
+/// The configured signer: the NIP-46 remote signer when one is set, else
+/// the nsec.
+async fn config_signer(config: &Config) -> Result<Signer> {
+    let nostr_cfg = config
+        .channels_config
+        .nostr
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("No [channels_config.nostr] in config"))?;
+    let persist_dir = config
+        .config_path
+        .parent()
+        .unwrap_or(std::path::Path::new("."));
+    crate::channels::snowclaw_channels::nostr_signer(nostr_cfg, persist_dir)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("No nsec or remote signer configured"))
+}
+
+/// Connect a plain client with the configured identity and relays.
+async fn connect_client(config: &Config) -> Result<(Client, Signer)> {
+    let nostr_cfg = config
+        .channels_config
+        .nostr
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("No [channels_config.nostr] in config"))?;
+    let signer = config_signer(config).await?;
+
+    let client =
+        crate::channels::snowclaw_channels::nostr_client(signer.clone(), &nostr_cfg.relays).await?;
+    client.connect().await;
+    Ok((client, signer))
+}
+
+/// One decrypted DM, in either direction.
+struct DmLine {
+    peer: PublicKey,
+    outgoing: bool,
+    created_at: u64,
+    protocol: &'static str,
+    content: String,
+}
+
+/// Send or fetch DMs, for end-to-end checks of the DM path.
+async fn cmd_dm(action: NostrDmAction, config: &Config) -> Result<()> {
+    let (client, signer) = connect_client(config).await?;
+    let result = match action {
+        NostrDmAction::Send { recipient, text } => cmd_dm_send(&client, &recipient, &text).await,
+        NostrDmAction::Fetch { since, with } => {
+            cmd_dm_fetch(&client, &signer, since.as_deref(), with.as_deref()).await
+        }
+    };
+    client.disconnect().await;
+    result
+}
+
+async fn cmd_dm_send(client: &Client, recipient: &str, text: &str) -> Result<()> {
+    let recipient = PublicKey::parse(&resolve_to_hex(recipient)?)?;
+    let output = client
+        .send_private_msg(recipient, text, [crate::channels::nostr::agent_tag()])
+        .await?;
+
+    println!("✉️  NIP-17 DM sent to {}", recipient.to_bech32()?);
+    println!("   gift wrap: {}", output.val);
+    for relay in &output.success {
+        println!("   ✅ {relay}");
+    }
+    for (relay, err) in &output.failed {
+        println!("   ❌ {relay}: {err}");
+    }
+    if output.success.is_empty() {
+        anyhow::bail!("No relay accepted the DM");
+    }
+    Ok(())
+}
+
+async fn cmd_dm_fetch(
+    client: &Client,
+    signer: &Signer,
+    since: Option<&str>,
+    with: Option<&str>,
+) -> Result<()> {
+    let now = Timestamp::now().as_secs();
+    let since = match since {
+        Some(raw) => parse_since(raw, now)?,
+        None => now.saturating_sub(24 * 60 * 60),
+    };
+    let with = with.map(resolve_to_hex).transpose()?;
+    let me = signer.public_key();
+
+    // Gift wraps carry a randomized created_at up to two days in the past,
+    // so look back further and filter on the rumor's own timestamp.
+    let wrap_since = Timestamp::from(since.saturating_sub(2 * 24 * 60 * 60));
+    let inbound = Filter::new()
+        .kinds(vec![Kind::GiftWrap, Kind::EncryptedDirectMessage])
+        .pubkey(me)
+        .since(wrap_since);
+    let outbound = Filter::new()
+        .kind(Kind::EncryptedDirectMessage)
+        .author(me)
+        .since(Timestamp::from(since));
+    let timeout = std::time::Duration::from_secs(10);
+    let mut events: Vec<Event> = client.fetch_events(inbound, timeout).await?.into_iter().collect();
+    events.extend(client.fetch_events(outbound, timeout).await?);
+
+    let mut lines = Vec::new();
+    let mut undecryptable = 0usize;
+    for event in &events {
+        let line = if event.kind == Kind::GiftWrap {
+            match client.unwrap_gift_wrap(event).await {
+                Ok(UnwrappedGift { sender, rumor }) if rumor.kind == Kind::PrivateDirectMessage => {
+                    let outgoing = sender == me;
+                    let peer = if outgoing {
+                        rumor.tags.public_keys().next().copied()
+                    } else {
+                        Some(sender)
+                    };
+                    peer.map(|peer| DmLine {
+                        peer,
+                        outgoing,
+                        created_at: rumor.created_at.as_secs(),
+                        protocol: "nip17",
+                        content: rumor.content,
+                    })
+                }
+                Ok(_) => None,
+                Err(e) => {
+                    undecryptable += 1;
+                    tracing::debug!("Failed to unwrap gift wrap {}: {e}", event.id);
+                    None
+                }
+            }
+        } else {
+            let outgoing = event.pubkey == me;
+            let peer = if outgoing {
+                event.tags.public_keys().next().copied()
+            } else {
+                Some(event.pubkey)
+            };
+            match peer {
+                Some(peer) => match signer.nip04_decrypt(&peer, &event.content).await {
+                    Ok(content) => Some(DmLine {
+                        peer,
+                        outgoing,
+                        created_at: event.created_at.as_secs(),
+                        protocol: "nip04",
+                        content,
+                    }),
+                    Err(e) => {
+                        undecryptable += 1;
+                        tracing::debug!("Failed to decrypt NIP-04 DM {}: {e}", event.id);
+                        None
+                    }
+                },
+                None => None,
+            }
+        };
+        if let Some(line) = line {
+            if line.created_at >= since && with.as_ref().is_none_or(|hex| *hex == line.peer.to_hex()) {
+                lines.push(line);
+            }
+        }
+    }
+
+    if lines.is_empty() {
+        println!("No DMs since {since}.");
+    }
+    let mut conversations: std::collections::BTreeMap<String, Vec<DmLine>> =
+        std::collections::BTreeMap::new();
+    for line in lines {
+        conversations.entry(line.peer.to_bech32()?).or_default().push(line);
+    }
+    for (peer, mut lines) in conversations {
+        lines.sort_by_key(|l| l.created_at);
+        println!("💬 {peer} ({} messages)", lines.len());
+        for l in &lines {
+            let time = chrono::DateTime::from_timestamp(i64::try_from(l.created_at).unwrap_or(0), 0)
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| l.created_at.to_string());
+            let arrow = if l.outgoing { "→" } else { "←" };
+            println!("   {time} {arrow} [{}] {}", l.protocol, l.content);
+        }
+    }
+    if undecryptable > 0 {
+        println!("⚠️  {undecryptable} DM event(s) could not be decrypted with this key");
+    }
+    Ok(())
+}
+
+/// Parse `--since`: a Unix timestamp, or a duration (e.g. 2h) before `now`.
+fn parse_since(raw: &str, now: u64) -> Result<u64> {
+    if let Ok(ts) = raw.parse::<u64>() {
+        return Ok(ts);
+    }
+    crate::channels::nostr_commands::parse_duration(raw)
+        .map(|d| now.saturating_sub(d.as_secs()))
+        .ok_or_else(|| anyhow::anyhow!("Invalid --since `{raw}` (Unix timestamp or duration, e.g. 2h)"))
+}
+
 fn cmd_keygen(config: &Config) -> Result<()> {
     let keys = Keys::generate();
     let nsec = keys.secret_key().to_bech32()?;
@@ -226,19 +543,30 @@ fn cmd_keygen(config: &Config) -> Result<()> {
     Ok(())
 }
 
-fn cmd_whoami(config: &Config) -> Result<()> {
-    let nsec_str = get_nsec_from_config(config);
-
-    match nsec_str {
-        Some(nsec_str) => {
-            let keys = Keys::parse(&nsec_str)
-                .map_err(|e| anyhow::anyhow!("Invalid nsec in config: {e}"))?;
-            let npub = keys.public_key().to_bech32()?;
-            let hex_pubkey = keys.public_key().to_hex();
+async fn cmd_whoami(config: &Config) -> Result<()> {
+    let signer = match config.channels_config.nostr.as_ref() {
+        Some(nostr_cfg) => {
+            let persist_dir = config
+                .config_path
+                .parent()
+                .unwrap_or(std::path::Path::new("."));
+            crate::channels::snowclaw_channels::nostr_signer(nostr_cfg, persist_dir).await?
+        }
+        None => get_nsec_from_config(config)
+            .map(|nsec| Keys::parse(&nsec).map(Signer::Local))
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid nsec in config: {e}"))?,
+    };
 
+    match signer {
+        Some(signer) => {
+            let pubkey = signer.public_key();
             println!("🔑 Nostr identity:\n");
-            println!("  npub: {npub}");
-            println!("  hex:  {hex_pubkey}");
+            println!("  npub: {}", pubkey.to_bech32()?);
+            println!("  hex:  {}", pubkey.to_hex());
+            if signer.is_remote() {
+                println!("  key:  held by the NIP-46 remote signer");
+            }
         }
         None => {
             println!("No Nostr identity configured.");
@@ -396,8 +724,62 @@ async fn cmd_memory(action: NostrMemoryAction, config: &Config) -> Result<()> {
                 }
             }
         }
+        NostrMemoryAction::Graph { group } => cmd_memory_graph(&group, persist_dir)?,
+    }
+
+    Ok(())
+}
+
+fn cmd_memory_graph(group: &str, persist_dir: &std::path::Path) -> Result<()> {
+    use crate::memory::social;
+
+    let db_path = persist_dir.join("social.db");
+    if !db_path.exists() {
+        println!("No social memory yet ({} not found).", db_path.display());
+        return Ok(());
+    }
+    let conn = crate::security::db_encryption::open(&db_path)?;
+    social::create_social_tables(&conn)?;
+
+    let edges = social::group_interactions(&conn, group)?;
+    if edges.is_empty() {
+        println!("No replies or mentions recorded in #{group} yet.");
+        return Ok(());
+    }
+
+    println!("🕸️  Interactions in #{group} ({} edges):", edges.len());
+    for e in &edges {
+        println!(
+            "  {} → {}  {} ×{}  (last {})",
+            social::display_name_or_short(&conn, &e.from_hex),
+            social::display_name_or_short(&conn, &e.to_hex),
+            e.kind.as_str(),
+            e.count,
+            e.last_at
+        );
     }
 
+    let mut people: Vec<&str> = edges.iter().map(|e| e.from_hex.as_str()).collect();
+    people.sort_unstable();
+    people.dedup();
+    let mut insights = Vec::new();
+    for hex in people {
+        let peers = social::top_collaborators(&conn, group, hex, social::COLLABORATION_MIN_INTERACTIONS, 3)?;
+        if !peers.is_empty() {
+            let names: Vec<String> = peers
+                .iter()
+                .map(|(peer, n)| format!("{} ({n})", social::display_name_or_short(&conn, peer)))
+                .collect();
+            insights.push(format!("{} frequently collaborates with {}", social::display_name_or_short(&conn, hex), names.join(", ")));
+        }
+    }
+    if !insights.is_empty() {
+        println!();
+        println!("   Insights:");
+        for line in insights {
+            println!("     - {line}");
+        }
+    }
     Ok(())
 }
 
@@ -427,18 +809,10 @@ async fn cmd_profile(
         .as_ref()
         .ok_or_else(|| anyhow::anyhow!("No [channels_config.nostr] in config"))?;
 
-    let nsec_str = nostr_cfg
-        .nsec
-        .clone()
-        .or_else(|| std::env::var("SNOWCLAW_NSEC").ok())
-        .ok_or_else(|| anyhow::anyhow!("No nsec configured"))?;
-
-    let keys = nostr_sdk::Keys::parse(&nsec_str)?;
-    let client = nostr_sdk::Client::new(keys.clone());
-
-    for relay in &nostr_cfg.relays {
-        client.add_relay(relay.as_str()).await?;
-    }
+    let signer = config_signer(config).await?;
+    let npub = signer.public_key().to_bech32()?;
+    let client =
+        crate::channels::snowclaw_channels::nostr_client(signer, &nostr_cfg.relays).await?;
     client.connect().await;
 
     // Build metadata JSON
@@ -470,7 +844,6 @@ async fn cmd_profile(
 
     let builder = nostr_sdk::EventBuilder::metadata(&metadata).tags(tags);
     let output = client.send_event_builder(builder).await?;
-    let npub = keys.public_key().to_bech32()?;
 
     println!("✅ Profile published!");
     println!("   npub: {npub}");
@@ -497,12 +870,12 @@ async fn cmd_onboard(config: &Config) -> Result<()> {
         .channels_config
         .nostr
         .as_ref()
-        .and_then(|n| n.nsec.as_ref())
-        .is_some()
-        || std::env::var("SNOWCLAW_NSEC").is_ok();
+        .is_some_and(|n| n.nsec.is_some() || n.signer.bunker_url.is_some())
+        || std::env::var("SNOWCLAW_NSEC").is_ok()
+        || std::env::var("SNOWCLAW_BUNKER_URL").is_ok();
 
     if has_key {
-        cmd_whoami(config)?;
+        cmd_whoami(config).await?;
         println!();
     } else {
         println!("No Nostr key found. Generating one...");
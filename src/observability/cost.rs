@@ -4,9 +4,8 @@
 //! calculating costs based on model pricing configuration.
 
 use super::traits::{Observer, ObserverEvent, ObserverMetric};
-use crate::config::schema::ModelPricing;
-use crate::cost::{CostTracker, TokenUsage};
-use std::collections::HashMap;
+use crate::cost::pricing::Pricing;
+use crate::cost::CostTracker;
 use std::sync::Arc;
 
 /// Observer that records token usage to a CostTracker.
@@ -14,67 +13,13 @@ use std::sync::Arc;
 /// Listens for `LlmResponse` events and calculates costs using model pricing.
 pub struct CostObserver {
     tracker: Arc<CostTracker>,
-    prices: HashMap<String, ModelPricing>,
-    /// Default pricing for unknown models (USD per 1M tokens)
-    default_input_price: f64,
-    default_output_price: f64,
+    pricing: Pricing,
 }
 
 impl CostObserver {
-    /// Create a new cost observer with the given tracker and pricing config.
-    pub fn new(tracker: Arc<CostTracker>, prices: HashMap<String, ModelPricing>) -> Self {
-        Self {
-            tracker,
-            prices,
-            // Conservative defaults for unknown models
-            default_input_price: 3.0,
-            default_output_price: 15.0,
-        }
-    }
-
-    /// Look up pricing for a model, trying various name formats.
-    fn get_pricing(&self, provider: &str, model: &str) -> (f64, f64) {
-        // Try exact match first: "provider/model"
-        let full_name = format!("{provider}/{model}");
-        if let Some(pricing) = self.prices.get(&full_name) {
-            return (pricing.input, pricing.output);
-        }
-
-        // Try just the model name
-        if let Some(pricing) = self.prices.get(model) {
-            return (pricing.input, pricing.output);
-        }
-
-        // Try model family matching (e.g., "claude-sonnet-4" matches any claude-sonnet-4-*)
-        for (key, pricing) in &self.prices {
-            // Strip provider prefix if present
-            let key_model = key.split('/').next_back().unwrap_or(key);
-
-            // Check if model starts with the key (family match)
-            if model.starts_with(key_model) || key_model.starts_with(model) {
-                return (pricing.input, pricing.output);
-            }
-
-            // Check for common model name patterns
-            // e.g., "claude-3-5-sonnet-20241022" should match "claude-3.5-sonnet"
-            let normalized_model = model.replace('-', ".");
-            let normalized_key = key_model.replace('-', ".");
-            if normalized_model.contains(&normalized_key)
-                || normalized_key.contains(&normalized_model)
-            {
-                return (pricing.input, pricing.output);
-            }
-        }
-
-        // Fall back to defaults
-        tracing::debug!(
-            "No pricing found for {}/{}, using defaults (${}/{} per 1M tokens)",
-            provider,
-            model,
-            self.default_input_price,
-            self.default_output_price
-        );
-        (self.default_input_price, self.default_output_price)
+    /// Create a new cost observer with the given tracker and pricing.
+    pub fn new(tracker: Arc<CostTracker>, pricing: Pricing) -> Self {
+        Self { tracker, pricing }
     }
 }
 
@@ -97,12 +42,24 @@ impl Observer for CostObserver {
                 return;
             }
 
-            let (input_price, output_price) = self.get_pricing(provider, model);
             let full_model_name = format!("{provider}/{model}");
-
-            let usage = TokenUsage::new(full_model_name, input, output, input_price, output_price);
-
-            if let Err(e) = self.tracker.record_usage(usage) {
+            let usage = self.pricing.rates(provider, model).usage(
+                full_model_name,
+                input,
+                output,
+                None,
+                None,
+            );
+
+            let result = match crate::cost::attribution::current() {
+                Some(attribution) => self.tracker.record_usage_attributed(
+                    usage,
+                    attribution,
+                    crate::cost::attribution::take_tool_latency(),
+                ),
+                None => self.tracker.record_usage(usage),
+            };
+            if let Err(e) = result {
                 tracing::warn!("Failed to record cost usage: {e}");
             }
         }
@@ -124,7 +81,8 @@ impl Observer for CostObserver {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::schema::CostConfig;
+    use crate::config::schema::{CostConfig, ModelPricing};
+    use std::collections::HashMap;
     use std::time::Duration;
     use tempfile::TempDir;
 
@@ -138,6 +96,16 @@ mod tests {
         (tmp, tracker)
     }
 
+    fn pricing(prices: HashMap<String, ModelPricing>) -> Pricing {
+        Pricing::new(
+            &CostConfig {
+                prices,
+                ..Default::default()
+            },
+            None,
+        )
+    }
+
     #[test]
     fn cost_observer_records_llm_response_usage() {
         let (_tmp, tracker) = create_test_tracker();
@@ -150,7 +118,7 @@ mod tests {
             },
         );
 
-        let observer = CostObserver::new(tracker.clone(), prices);
+        let observer = CostObserver::new(tracker.clone(), pricing(prices));
 
         observer.record_event(&ObserverEvent::LlmResponse {
             provider: "anthropic".into(),
@@ -171,7 +139,7 @@ mod tests {
     #[test]
     fn cost_observer_ignores_failed_responses() {
         let (_tmp, tracker) = create_test_tracker();
-        let observer = CostObserver::new(tracker.clone(), HashMap::new());
+        let observer = CostObserver::new(tracker.clone(), pricing(HashMap::new()));
 
         observer.record_event(&ObserverEvent::LlmResponse {
             provider: "anthropic".into(),
@@ -190,7 +158,7 @@ mod tests {
     #[test]
     fn cost_observer_ignores_zero_token_responses() {
         let (_tmp, tracker) = create_test_tracker();
-        let observer = CostObserver::new(tracker.clone(), HashMap::new());
+        let observer = CostObserver::new(tracker.clone(), pricing(HashMap::new()));
 
         observer.record_event(&ObserverEvent::LlmResponse {
             provider: "anthropic".into(),
@@ -209,7 +177,7 @@ mod tests {
     #[test]
     fn cost_observer_uses_default_pricing_for_unknown_models() {
         let (_tmp, tracker) = create_test_tracker();
-        let observer = CostObserver::new(tracker.clone(), HashMap::new());
+        let observer = CostObserver::new(tracker.clone(), pricing(HashMap::new()));
 
         observer.record_event(&ObserverEvent::LlmResponse {
             provider: "unknown".into(),
@@ -239,7 +207,7 @@ mod tests {
             },
         );
 
-        let observer = CostObserver::new(tracker.clone(), prices);
+        let observer = CostObserver::new(tracker.clone(), pricing(prices));
 
         // Model name with version suffix should still match
         observer.record_event(&ObserverEvent::LlmResponse {
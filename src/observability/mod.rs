@@ -24,6 +24,7 @@ pub use verbose::VerboseObserver;
 
 use crate::config::schema::CostConfig;
 use crate::config::ObservabilityConfig;
+use crate::cost::pricing::Pricing;
 use crate::cost::CostTracker;
 use std::sync::Arc;
 
@@ -45,7 +46,7 @@ pub fn create_observer_with_cost_tracking(
 
     match cost_tracker {
         Some(tracker) if cost_config.enabled => {
-            let cost_observer = CostObserver::new(tracker, cost_config.prices.clone());
+            let cost_observer = CostObserver::new(tracker, Pricing::from_config(cost_config));
             Box::new(MultiObserver::new(vec![
                 base_observer,
                 Box::new(cost_observer),
@@ -278,6 +278,7 @@ pub async fn run_wizard_with_migration(
         multimodal: crate::config::MultimodalConfig::default(),
         web_fetch: web_fetch_config,
         web_search: web_search_config,
+        tool_cache: crate::config::ToolCacheConfig::default(),
         proxy: crate::config::ProxyConfig::default(),
         identity: identity_config,
         cost: crate::config::CostConfig::default(),
@@ -292,6 +293,13 @@ pub async fn run_wizard_with_migration(
         agents_ipc: crate::config::AgentsIpcConfig::default(),
         mcp: crate::config::schema::McpConfig::default(),
         contextvm: None,
+        personas: crate::config::PersonasConfig::default(),
+        execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+        model_profiles: crate::config::ModelProfilesConfig::default(),
+        model_router: crate::config::ModelRouterConfig::default(),
+        stats: crate::config::StatsConfig::default(),
+        update: crate::config::UpdateConfig::default(),
+        determinism: crate::config::DeterminismConfig::default(),
         model_support_vision: None,
         wasm: crate::config::WasmConfig::default(),
     };
@@ -508,6 +516,7 @@ fn memory_config_defaults_for_backend(backend: &str) -> MemoryConfig {
             0
         },
         chunk_max_tokens: 512,
+        chunk_overlap_tokens: crate::config::snowclaw_schema::default_chunk_overlap_tokens(),
         response_cache_enabled: false,
         response_cache_ttl_minutes: 60,
         response_cache_max_entries: 5_000,
@@ -525,6 +534,7 @@ fn memory_config_defaults_for_backend(backend: &str) -> MemoryConfig {
         index_interval_minutes: 30,
         nomen_socket_path: None,
         collective: crate::config::CollectiveMemoryConfig::default(),
+        write_policy: crate::config::MemoryWritePolicyConfig::default(),
     }
 }
 
@@ -803,6 +813,7 @@ async fn run_quick_setup_with_home(
         multimodal: crate::config::MultimodalConfig::default(),
         web_fetch: crate::config::WebFetchConfig::default(),
         web_search: crate::config::WebSearchConfig::default(),
+        tool_cache: crate::config::ToolCacheConfig::default(),
         proxy: crate::config::ProxyConfig::default(),
         identity: crate::config::IdentityConfig::default(),
         cost: crate::config::CostConfig::default(),
@@ -817,6 +828,13 @@ async fn run_quick_setup_with_home(
         agents_ipc: crate::config::AgentsIpcConfig::default(),
         mcp: crate::config::schema::McpConfig::default(),
         contextvm: None,
+        personas: crate::config::PersonasConfig::default(),
+        execution_policies: crate::config::ExecutionPoliciesConfig::default(),
+        model_profiles: crate::config::ModelProfilesConfig::default(),
+        model_router: crate::config::ModelRouterConfig::default(),
+        stats: crate::config::StatsConfig::default(),
+        update: crate::config::UpdateConfig::default(),
+        determinism: crate::config::DeterminismConfig::default(),
         model_support_vision: None,
         wasm: crate::config::WasmConfig::default(),
     };
@@ -3961,7 +3979,10 @@ fn setup_tool_mode() -> Result<(ComposioConfig, SecretsConfig)> {
         .default(true)
         .interact()?;
 
-    let secrets_config = SecretsConfig { encrypt };
+    let secrets_config = SecretsConfig {
+        encrypt,
+        ..SecretsConfig::default()
+    };
 
     if encrypt {
         println!(
@@ -6221,9 +6242,11 @@ fn setup_channels() -> Result<ChannelsConfig> {
 
                 config.nostr = Some(NostrConfig {
                     nsec: Some(private_key.trim().to_string()),
+                    signer: crate::config::NostrSignerConfig::default(),
                     relays: relays.clone(),
                     allowed_pubkeys,
                     owner: None,
+                    owners: Vec::new(),
                     groups: vec![],
                     respond_mode: "mention_only".into(),
                     group_respond_mode: std::collections::HashMap::new(),
@@ -6231,6 +6254,33 @@ fn setup_channels() -> Result<ChannelsConfig> {
                     listen_dms: true,
                     context_history: 10,
                     extra_kinds: vec![],
+                    digest: crate::config::NostrDigestConfig::default(),
+                    banner: crate::config::NostrBannerConfig::default(),
+                    key_filter: crate::config::KeyFilterConfig::default(),
+                    index_redaction: crate::config::NostrIndexRedactionConfig::default(),
+                    onboarding: crate::config::NostrOnboardingConfig::default(),
+                    quota: crate::config::NostrQuotaConfig::default(),
+                    answer_cache: crate::config::NostrAnswerCacheConfig::default(),
+                    pipeline: crate::config::NostrPipelineConfig::default(),
+                    proxy: crate::config::NostrProxyConfig::default(),
+                    relay_trust: crate::config::NostrRelayTrustConfig::default(),
+                    publish: crate::config::NostrPublishConfig::default(),
+                    reconnect: crate::config::NostrReconnectConfig::default(),
+                    catchup: crate::config::NostrCatchupConfig::default(),
+                    dm_sessions: crate::config::NostrDmSessionConfig::default(),
+                    dm_presence: crate::config::NostrDmPresenceConfig::default(),
+                    link_index: crate::config::NostrLinkIndexConfig::default(),
+                    reply_moderation: crate::config::NostrReplyModerationConfig::default(),
+                    threading: crate::config::NostrThreadingConfig::default(),
+                    split: crate::config::NostrSplitConfig::default(),
+                    cost_alerts: crate::config::NostrCostAlertConfig::default(),
+                    nip05: crate::config::NostrNip05Config::default(),
+                    proactive: crate::config::NostrProactiveConfig::default(),
+                    backfill: crate::config::NostrBackfillConfig::default(),
+                    contact_pruning: crate::config::NostrContactPruningConfig::default(),
+                    status_feed: crate::config::NostrStatusFeedConfig::default(),
+                    subscriptions: crate::config::NostrSubscriptionsConfig::default(),
+                    context_template: None,
                 });
 
                 println!(
@@ -0,0 +1,120 @@
+//! CLI subcommands for the Nostr outbox.
+//!
+//! Provides `snowclaw outbox status`, reading the spools of events that were
+//! signed but not yet accepted by any relay: `outbox.db` next to
+//! `config.toml` (channel) and `nostr_sqlite/outbox.db` in the workspace
+//! (memory backend).
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use clap::Subcommand;
+
+use crate::channels::nostr_outbox::{self, OutboxStatus};
+use crate::config::Config;
+
+#[derive(Subcommand, Debug)]
+pub enum OutboxCommands {
+    /// Show queued events and retry state
+    Status {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn handle_command(cmd: OutboxCommands, config: &Config) -> Result<()> {
+    match cmd {
+        OutboxCommands::Status { json } => {
+            let config_dir = config.config_path.parent().unwrap_or(Path::new("."));
+            let spools: [(&str, PathBuf); 2] = [
+                ("channel", config_dir.join(nostr_outbox::OUTBOX_FILE)),
+                (
+                    "memory",
+                    config
+                        .workspace_dir
+                        .join("nostr_sqlite")
+                        .join(nostr_outbox::OUTBOX_FILE),
+                ),
+            ];
+            let mut rows = Vec::with_capacity(spools.len());
+            for (label, path) in spools {
+                let status = nostr_outbox::read_status(&path)?;
+                rows.push((label, path, status));
+            }
+            let now = chrono::Utc::now().timestamp();
+
+            if json {
+                let view: Vec<_> = rows
+                    .iter()
+                    .map(|(label, path, status)| {
+                        let status = status.clone().unwrap_or_default();
+                        let kinds: serde_json::Map<_, _> = status
+                            .kinds
+                            .iter()
+                            .map(|(kind, count)| (kind.to_string(), serde_json::json!(count)))
+                            .collect();
+                        serde_json::json!({
+                            "outbox": label,
+                            "path": path.display().to_string(),
+                            "pending": status.pending,
+                            "oldest_queued_at": status.oldest_queued_at,
+                            "next_attempt_at": status.next_attempt_at,
+                            "last_error": status.last_error,
+                            "kinds": kinds,
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&view)?);
+                return Ok(());
+            }
+
+            for (index, (label, path, status)) in rows.iter().enumerate() {
+                if index > 0 {
+                    println!();
+                }
+                println!("{label} outbox: {}", path.display());
+                match status {
+                    None => println!("  not created yet"),
+                    Some(status) if status.pending == 0 => println!("  empty"),
+                    Some(status) => print_status(status, now),
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_status(status: &OutboxStatus, now: i64) {
+    println!("  pending:    {}", status.pending);
+    if let Some(oldest) = status.oldest_queued_at {
+        println!("  oldest:     {} ago", format_secs(now - oldest));
+    }
+    if let Some(next) = status.next_attempt_at {
+        if next > now {
+            println!("  next retry: in {}", format_secs(next - now));
+        } else {
+            println!("  next retry: when a relay is connected");
+        }
+    }
+    if let Some(error) = &status.last_error {
+        println!("  last error: {error}");
+    }
+    let kinds: Vec<String> = status
+        .kinds
+        .iter()
+        .map(|(kind, count)| format!("{kind}×{count}"))
+        .collect();
+    println!("  kinds:      {}", kinds.join(", "));
+}
+
+fn format_secs(secs: i64) -> String {
+    let secs = secs.max(0);
+    if secs < 60 {
+        format!("{secs}s")
+    } else if secs < 3600 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
@@ -0,0 +1,175 @@
+//! CLI subcommands for model pricing.
+//!
+//! Provides `snowclaw pricing list`, `pricing set`, and `pricing remove`,
+//! editing `pricing.toml` next to `config.toml`. Running agents pick up
+//! edits without a restart.
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+
+use crate::config::{Config, UnknownModelPricing};
+use crate::cost::pricing::{self, ModelRates, PricingFile};
+
+#[derive(Subcommand, Debug)]
+pub enum PricingCommands {
+    /// List model prices from pricing.toml and [cost.prices]
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Set a model's prices in pricing.toml (USD per 1M tokens)
+    Set {
+        /// Model as `provider/model` (e.g. anthropic/claude-sonnet-4)
+        model: String,
+        /// Input price per 1M tokens
+        #[arg(long)]
+        input: f64,
+        /// Output price per 1M tokens
+        #[arg(long)]
+        output: f64,
+        /// Prompt-cache read price per 1M tokens (default: input price)
+        #[arg(long)]
+        cache_read: Option<f64>,
+        /// Prompt-cache write price per 1M tokens (default: input price)
+        #[arg(long)]
+        cache_write: Option<f64>,
+    },
+    /// Remove a model from pricing.toml
+    Remove {
+        /// Model as written in pricing.toml
+        model: String,
+    },
+}
+
+pub fn handle_command(cmd: PricingCommands, config: &Config, dry_run: bool) -> Result<()> {
+    let path = pricing::pricing_file_path(&config.config_path);
+    match cmd {
+        PricingCommands::List { json } => {
+            let file = PricingFile::load(&path)?;
+            let mut rows: Vec<(&str, ModelRates, &str)> = file
+                .models
+                .iter()
+                .map(|(model, rates)| (model.as_str(), *rates, "pricing.toml"))
+                .collect();
+            let mut configured: Vec<_> = config
+                .cost
+                .prices
+                .iter()
+                .filter(|(model, _)| !file.models.contains_key(*model))
+                .collect();
+            configured.sort_by(|a, b| a.0.cmp(b.0));
+            rows.extend(configured.into_iter().map(|(model, price)| {
+                let rates = ModelRates {
+                    input: price.input,
+                    output: price.output,
+                    cache_read: None,
+                    cache_write: None,
+                };
+                (model.as_str(), rates, "config")
+            }));
+            let unknown = match config.cost.unknown_model_pricing {
+                UnknownModelPricing::Conservative => "conservative",
+                UnknownModelPricing::Zero => "zero",
+            };
+
+            if json {
+                let models: Vec<_> = rows
+                    .iter()
+                    .map(|(model, rates, source)| {
+                        serde_json::json!({
+                            "model": model,
+                            "input": rates.input,
+                            "output": rates.output,
+                            "cache_read": rates.cache_read,
+                            "cache_write": rates.cache_write,
+                            "source": source,
+                        })
+                    })
+                    .collect();
+                let view = serde_json::json!({
+                    "pricing_file": path.display().to_string(),
+                    "unknown_model_pricing": unknown,
+                    "models": models,
+                });
+                println!("{}", serde_json::to_string_pretty(&view)?);
+                return Ok(());
+            }
+
+            println!("Pricing file: {}", path.display());
+            println!("Unknown models: {unknown}");
+            if rows.is_empty() {
+                println!("No model prices configured.");
+                return Ok(());
+            }
+            println!();
+            println!(
+                "{:<44} {:>9} {:>9} {:>11} {:>11}  SOURCE",
+                "MODEL", "INPUT", "OUTPUT", "CACHE_READ", "CACHE_WRITE"
+            );
+            let cache =
+                |rate: Option<f64>| rate.map_or_else(|| "-".to_string(), |r| format!("{r}"));
+            for (model, rates, source) in rows {
+                println!(
+                    "{model:<44} {:>9} {:>9} {:>11} {:>11}  {source}",
+                    rates.input,
+                    rates.output,
+                    cache(rates.cache_read),
+                    cache(rates.cache_write)
+                );
+            }
+            Ok(())
+        }
+        PricingCommands::Set {
+            model,
+            input,
+            output,
+            cache_read,
+            cache_write,
+        } => {
+            let model = model.trim();
+            if model.is_empty() {
+                bail!("Model name must not be empty");
+            }
+            for rate in [Some(input), Some(output), cache_read, cache_write]
+                .into_iter()
+                .flatten()
+            {
+                if !rate.is_finite() || rate < 0.0 {
+                    bail!("Prices must be non-negative numbers, got {rate}");
+                }
+            }
+            let mut file = PricingFile::load(&path)?;
+            file.models.insert(
+                model.to_string(),
+                ModelRates {
+                    input,
+                    output,
+                    cache_read,
+                    cache_write,
+                },
+            );
+            file.save(&path)?;
+            println!("Set pricing for {model} in {}", path.display());
+            Ok(())
+        }
+        PricingCommands::Remove { model } => {
+            let mut file = PricingFile::load(&path)?;
+            let Some(rates) = file.models.remove(model.trim()) else {
+                bail!("No pricing for {model} in {}", path.display());
+            };
+            if dry_run {
+                println!(
+                    "[dry-run] Would remove pricing for {model} (input ${}, output ${} per 1M tokens) from {}",
+                    rates.input,
+                    rates.output,
+                    path.display()
+                );
+                return Ok(());
+            }
+            file.save(&path)?;
+            println!("Removed pricing for {model} from {}", path.display());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Encryption at rest for the agent's SQLite databases (SQLCipher).
+//!
+//! With `[secrets] encrypt_databases = true`, the memory database
+//! (`memory/brain.db`), the Nostr seen-events/DM index (`seen_events.db`)
+//! and the social database (`social.db`) are opened with a SQLCipher key.
+//! The key is derived from the secret store key (`.secret_key`), so no extra
+//! secret has to be managed. This needs a build with the `sqlcipher` feature.
+//!
+//! Existing plaintext databases are converted in place with
+//! `snowclaw security encrypt-databases` (stop the daemon first).
+
+use crate::config::Config;
+use crate::security::SecretStore;
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Header every plaintext SQLite file starts with.
+const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+
+/// Purpose label for the key derived from the secret store.
+const KEY_PURPOSE: &str = "sqlcipher";
+
+/// Hex-encoded raw SQLCipher key, set once at startup by [`init`].
+static DATABASE_KEY: OnceLock<String> = OnceLock::new();
+
+/// Databases covered by `encrypt_databases`.
+pub fn database_paths(config: &Config) -> Vec<PathBuf> {
+    let config_dir = config
+        .config_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+    vec![
+        config.workspace_dir.join("memory").join("brain.db"),
+        config_dir.join("seen_events.db"),
+        config_dir.join("social.db"),
+        config_dir.join("outbox.db"),
+    ]
+}
+
+/// Derive the database key when `encrypt_databases` is on, and warn about
+/// covered databases that are still plaintext.
+pub fn init(config: &Config) -> Result<()> {
+    if !config.secrets.encrypt_databases {
+        return Ok(());
+    }
+    let key = database_key(config)?;
+    let _ = DATABASE_KEY.set(key);
+
+    for path in database_paths(config) {
+        if path.exists() && is_plaintext(&path)? {
+            tracing::warn!(
+                "{} is not encrypted; run `snowclaw security encrypt-databases` with the daemon stopped",
+                path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Key the connection with the startup key (no-op without encryption) and
+/// check that the database can be read with it.
+pub fn apply_key(conn: &Connection) -> Result<()> {
+    apply_key_with(conn, DATABASE_KEY.get().map(String::as_str))
+}
+
+/// Whether a database key was derived at startup.
+pub fn has_key() -> bool {
+    DATABASE_KEY.get().is_some()
+}
+
+/// Open `path` read-write, keyed when encryption is on.
+pub fn open(path: &Path) -> Result<Connection> {
+    let conn = Connection::open(path)
+        .with_context(|| format!("Failed to open database {}", path.display()))?;
+    apply_key(&conn).with_context(|| format!("Failed to unlock database {}", path.display()))?;
+    Ok(conn)
+}
+
+/// Whether `path` is a plaintext SQLite database (empty files count as
+/// plaintext: SQLite initializes them on first write).
+pub fn is_plaintext(path: &Path) -> Result<bool> {
+    let mut header = Vec::with_capacity(SQLITE_MAGIC.len());
+    std::fs::File::open(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .take(SQLITE_MAGIC.len() as u64)
+        .read_to_end(&mut header)?;
+    Ok(header.is_empty() || header == SQLITE_MAGIC)
+}
+
+/// Encrypt every covered plaintext database in place. Returns each path
+/// with whether it was converted (`false`: missing or already encrypted).
+pub fn encrypt_databases(config: &Config) -> Result<Vec<(PathBuf, bool)>> {
+    let key = database_key(config)?;
+    database_paths(config)
+        .into_iter()
+        .map(|path| {
+            let converted = encrypt_database(&path, &key)?;
+            Ok((path, converted))
+        })
+        .collect()
+}
+
+/// Copy a plaintext database into a SQLCipher-encrypted file and swap it
+/// in. Returns `false` when the file is missing or already encrypted.
+pub fn encrypt_database(path: &Path, key: &str) -> Result<bool> {
+    if !path.exists() || !is_plaintext(path)? {
+        return Ok(false);
+    }
+    let encrypted = path.with_extension("db.encrypting");
+    if encrypted.exists() {
+        std::fs::remove_file(&encrypted)?;
+    }
+
+    let conn = Connection::open(path)
+        .with_context(|| format!("Failed to open database {}", path.display()))?;
+    // Fold the WAL into the main file so the export sees every row.
+    conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")?;
+    conn.execute(
+        "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+        rusqlite::params![encrypted.to_string_lossy(), raw_key(key)],
+    )
+    .context("Failed to create the encrypted copy")?;
+    conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+        .context("sqlcipher_export failed")?;
+    conn.execute_batch("DETACH DATABASE encrypted;")?;
+    drop(conn);
+
+    let check = Connection::open(&encrypted)?;
+    apply_key_with(&check, Some(key)).context("Encrypted copy does not open with the key")?;
+    drop(check);
+
+    std::fs::rename(&encrypted, path)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = path.as_os_str().to_owned();
+        sidecar.push(suffix);
+        let _ = std::fs::remove_file(PathBuf::from(sidecar));
+    }
+    Ok(true)
+}
+
+fn database_key(config: &Config) -> Result<String> {
+    if !cfg!(feature = "sqlcipher") {
+        anyhow::bail!(
+            "secrets.encrypt_databases needs a build with the `sqlcipher` feature \
+             (cargo build --features sqlcipher)"
+        );
+    }
+    let config_dir = config
+        .config_path
+        .parent()
+        .context("Config path must have a parent directory")?;
+    SecretStore::new(config_dir, true).derive_key(KEY_PURPOSE)
+}
+
+/// SQLCipher raw-key literal: the key bytes are used as-is, skipping the
+/// passphrase KDF.
+fn raw_key(key: &str) -> String {
+    format!("x'{key}'")
+}
+
+fn apply_key_with(conn: &Connection, key: Option<&str>) -> Result<()> {
+    let Some(key) = key else {
+        return Ok(());
+    };
+    conn.pragma_update(None, "key", raw_key(key))?;
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))
+        .context(
+            "Database key rejected: wrong key, or a plaintext database \
+             (run `snowclaw security encrypt-databases`)",
+        )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn detects_plaintext_databases() {
+        let dir = TempDir::new().unwrap();
+        let db = dir.path().join("plain.db");
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch("CREATE TABLE t (x INTEGER);").unwrap();
+        drop(conn);
+        assert!(is_plaintext(&db).unwrap());
+
+        let empty = dir.path().join("empty.db");
+        std::fs::write(&empty, b"").unwrap();
+        assert!(is_plaintext(&empty).unwrap());
+
+        let noise = dir.path().join("noise.db");
+        std::fs::write(&noise, [0x5a_u8; 64]).unwrap();
+        assert!(!is_plaintext(&noise).unwrap());
+    }
+
+    #[test]
+    fn missing_key_is_a_no_op() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply_key_with(&conn, None).unwrap();
+        conn.execute_batch("CREATE TABLE t (x INTEGER);").unwrap();
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[test]
+    fn encrypts_plaintext_database_in_place() {
+        let dir = TempDir::new().unwrap();
+        let db = dir.path().join("brain.db");
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch("CREATE TABLE t (x TEXT); INSERT INTO t VALUES ('kept');")
+            .unwrap();
+        drop(conn);
+        let key = "ab".repeat(32);
+
+        assert!(encrypt_database(&db, &key).unwrap());
+        assert!(!is_plaintext(&db).unwrap());
+        assert!(!encrypt_database(&db, &key).unwrap());
+
+        let conn = Connection::open(&db).unwrap();
+        apply_key_with(&conn, Some(&key)).unwrap();
+        let value: String = conn.query_row("SELECT x FROM t", [], |r| r.get(0)).unwrap();
+        assert_eq!(value, "kept");
+
+        let conn = Connection::open(&db).unwrap();
+        assert!(apply_key_with(&conn, Some(&"cd".repeat(32))).is_err());
+    }
+}
@@ -0,0 +1,411 @@
+//! Per-conversation tool execution policies (`[execution_policies]`).
+//!
+//! Channel handlers select a policy for each message from its persona, room
+//! and channel, hide the tools it does not allow, and run the tool loop
+//! inside [`scope`]. The tool dispatcher checks every call against
+//! [`current`] before running it: the tool must be allowed, its arguments
+//! must not match a denied pattern, and path arguments must stay inside the
+//! allowed directories. Calls are cut off at the policy's timeout. Blocked
+//! and timed-out calls are written to the audit log as policy violations.
+//!
+//! Tools that run other tools on the model's behalf ([`DISPATCHER_TOOLS`])
+//! are denied under any policy that restricts tools, and the ones that spawn
+//! tasks carry the policy into them with [`scope`].
+
+use crate::config::{ExecutionPoliciesConfig, ExecutionPolicyConfig};
+use crate::security::audit::{AuditEvent, AuditEventType, AuditLogger};
+use regex::Regex;
+use std::future::Future;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Argument names whose string values are filesystem paths.
+const PATH_ARGUMENTS: &[&str] = &[
+    "path",
+    "paths",
+    "file_path",
+    "directory",
+    "dir",
+    "cwd",
+    "workdir",
+    "working_dir",
+];
+
+/// Tools that run other tools or agent loops. A policy that restricts tools
+/// denies them, since their inner calls would otherwise dodge the tool list.
+pub const DISPATCHER_TOOLS: &[&str] = &["bg_run", "delegate", "subagent_spawn"];
+
+/// Select the policy for a conversation: by persona, then room, then
+/// channel, then the default.
+pub fn select<'a>(
+    config: &'a ExecutionPoliciesConfig,
+    channel: &str,
+    room: &str,
+    persona: Option<&str>,
+) -> Option<(&'a str, &'a ExecutionPolicyConfig)> {
+    let name = persona
+        .and_then(|persona| config.personas.get(persona))
+        .or_else(|| {
+            room.strip_prefix('#')
+                .and_then(|group| config.rooms.get(group))
+        })
+        .or_else(|| config.rooms.get(room))
+        .or_else(|| config.channels.get(channel))
+        .or(config.default.as_ref())?;
+    match config.profiles.get_key_value(name) {
+        Some((name, policy)) => Some((name.as_str(), policy)),
+        None => {
+            tracing::warn!("Execution policy `{name}` is mapped but not defined");
+            None
+        }
+    }
+}
+
+/// A policy ready to check tool calls.
+pub struct ExecutionPolicy {
+    name: String,
+    allowed_tools: Vec<String>,
+    denied_tools: Vec<String>,
+    denied_arguments: Vec<Regex>,
+    workspace_dir: PathBuf,
+    allowed_paths: Vec<PathBuf>,
+    timeout: Option<Duration>,
+    channel: String,
+    room: String,
+    sender: Option<String>,
+    audit: Option<AuditLogger>,
+}
+
+impl ExecutionPolicy {
+    /// Compile `config`. A policy with a pattern that does not compile
+    /// denies every tool rather than dropping the pattern.
+    pub fn new(name: &str, config: &ExecutionPolicyConfig, workspace_dir: &Path) -> Self {
+        let mut denied_tools = config.denied_tools.clone();
+        let mut denied_arguments = Vec::with_capacity(config.denied_argument_patterns.len());
+        for pattern in &config.denied_argument_patterns {
+            match Regex::new(pattern) {
+                Ok(regex) => denied_arguments.push(regex),
+                Err(err) => {
+                    tracing::warn!(
+                        "Execution policy `{name}`: invalid argument pattern `{pattern}` ({err}); denying all tools"
+                    );
+                    denied_tools.push("*".to_string());
+                }
+            }
+        }
+        Self {
+            name: name.to_string(),
+            allowed_tools: config.allowed_tools.clone(),
+            denied_tools,
+            denied_arguments,
+            workspace_dir: workspace_dir.to_path_buf(),
+            allowed_paths: config
+                .allowed_paths
+                .iter()
+                .map(|path| resolve_path(workspace_dir, path))
+                .collect(),
+            timeout: config.timeout_secs.map(Duration::from_secs),
+            channel: String::new(),
+            room: String::new(),
+            sender: None,
+            audit: None,
+        }
+    }
+
+    /// Attribute violations to `sender` in `room` of `channel` and write
+    /// them to `audit`.
+    pub fn with_audit(
+        mut self,
+        channel: &str,
+        room: &str,
+        sender: &str,
+        audit: Option<AuditLogger>,
+    ) -> Self {
+        self.channel = channel.to_string();
+        self.room = room.to_string();
+        self.sender = Some(sender.to_string());
+        self.audit = audit;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Longest a single call may run.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Whether the policy limits which tools may run at all.
+    pub fn restricts_tools(&self) -> bool {
+        self.allowed_tools.iter().any(|rule| rule != "*") || !self.denied_tools.is_empty()
+    }
+
+    pub fn allows_tool(&self, tool: &str) -> bool {
+        if self.restricts_tools()
+            && DISPATCHER_TOOLS
+                .iter()
+                .any(|dispatcher| dispatcher.eq_ignore_ascii_case(tool))
+        {
+            return false;
+        }
+        let matches = |rule: &String| rule == "*" || rule.eq_ignore_ascii_case(tool);
+        (self.allowed_tools.is_empty() || self.allowed_tools.iter().any(matches))
+            && !self.denied_tools.iter().any(matches)
+    }
+
+    /// Extend `excluded` with every tool this policy does not allow, so the
+    /// model is not offered them.
+    pub fn restrict_tools<'a>(
+        &self,
+        tool_names: impl IntoIterator<Item = &'a str>,
+        excluded: &mut Vec<String>,
+    ) {
+        for name in tool_names {
+            if !self.allows_tool(name) && !excluded.iter().any(|t| t == name) {
+                excluded.push(name.to_string());
+            }
+        }
+    }
+
+    /// Why a call of `tool` with `args` is not allowed, if it is not.
+    pub fn check(&self, tool: &str, args: &serde_json::Value) -> Result<(), String> {
+        if !self.allows_tool(tool) {
+            return Err(format!("tool `{tool}` is not allowed here"));
+        }
+        let serialized = args.to_string();
+        if let Some(pattern) = self
+            .denied_arguments
+            .iter()
+            .find(|pattern| pattern.is_match(&serialized))
+        {
+            return Err(format!("arguments match denied pattern `{pattern}`"));
+        }
+        if self.allowed_paths.is_empty() {
+            return Ok(());
+        }
+        for raw in path_arguments(args) {
+            let path = resolve_path(&self.workspace_dir, raw);
+            if !self
+                .allowed_paths
+                .iter()
+                .any(|allowed| path.starts_with(allowed))
+            {
+                return Err(format!("path `{raw}` is outside the allowed directories"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a blocked call to the audit log.
+    pub fn record_violation(&self, tool: &str, reason: &str) {
+        tracing::warn!(
+            policy = self.name.as_str(),
+            tool,
+            "Tool call blocked by execution policy: {reason}"
+        );
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let mut event = AuditEvent::new(AuditEventType::PolicyViolation)
+            .with_actor(self.channel.clone(), self.sender.clone(), None)
+            .with_action(
+                format!("{tool} in {} (execution policy `{}`)", self.room, self.name),
+                "policy".to_string(),
+                false,
+                false,
+            )
+            .with_result(false, None, 0, Some(reason.to_string()));
+        event.security.policy_violation = true;
+        if let Err(err) = audit.log(&event) {
+            tracing::warn!("Failed to write policy violation to the audit log: {err}");
+        }
+    }
+}
+
+fn path_arguments(args: &serde_json::Value) -> Vec<&str> {
+    PATH_ARGUMENTS
+        .iter()
+        .filter_map(|key| args.get(key))
+        .flat_map(|value| match value {
+            serde_json::Value::String(path) => vec![path.as_str()],
+            serde_json::Value::Array(items) => items.iter().filter_map(|v| v.as_str()).collect(),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+/// Absolute, lexically normalized form of `raw` (`~` expanded, relative
+/// paths taken from `workspace_dir`). Symlinks are not followed.
+fn resolve_path(workspace_dir: &Path, raw: &str) -> PathBuf {
+    let expanded = shellexpand::tilde(raw.trim());
+    let path = Path::new(expanded.as_ref());
+    let joined = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        workspace_dir.join(path)
+    };
+    let mut normalized = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+tokio::task_local! {
+    static CURRENT_POLICY: Option<Arc<ExecutionPolicy>>;
+}
+
+/// Run `fut` with `policy` applied to the tool calls it makes.
+pub async fn scope<F: Future>(policy: Option<Arc<ExecutionPolicy>>, fut: F) -> F::Output {
+    CURRENT_POLICY.scope(policy, fut).await
+}
+
+/// Policy of the current task, if it runs inside [`scope`] with one.
+pub fn current() -> Option<Arc<ExecutionPolicy>> {
+    CURRENT_POLICY.try_with(Clone::clone).ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policies() -> ExecutionPoliciesConfig {
+        let mut config = ExecutionPoliciesConfig::default();
+        for name in ["public", "owner", "reviewer", "fallback"] {
+            config
+                .profiles
+                .insert(name.into(), ExecutionPolicyConfig::default());
+        }
+        config.rooms.insert("dev".into(), "public".into());
+        config.rooms.insert("abcd".into(), "owner".into());
+        config.personas.insert("reviewer".into(), "reviewer".into());
+        config.channels.insert("nostr".into(), "public".into());
+        config.default = Some("fallback".into());
+        config
+    }
+
+    #[test]
+    fn select_prefers_persona_then_room_then_channel() {
+        let config = policies();
+        let name = |channel, room, persona| select(&config, channel, room, persona).map(|p| p.0);
+        assert_eq!(name("nostr", "#dev", Some("reviewer")), Some("reviewer"));
+        assert_eq!(name("nostr", "#dev", None), Some("public"));
+        assert_eq!(name("nostr", "abcd", None), Some("owner"));
+        assert_eq!(name("nostr", "#other", None), Some("public"));
+        assert_eq!(name("telegram", "123", None), Some("fallback"));
+        assert!(select(&ExecutionPoliciesConfig::default(), "nostr", "#dev", None).is_none());
+    }
+
+    #[test]
+    fn check_enforces_tools_arguments_and_paths() {
+        let workspace = Path::new("/srv/agent/workspace");
+        let policy = ExecutionPolicy::new(
+            "public",
+            &ExecutionPolicyConfig {
+                allowed_tools: vec!["file_read".into(), "web_fetch".into(), "shell".into()],
+                denied_tools: vec!["shell".into()],
+                denied_argument_patterns: vec![r"(?i)\.env\b".into()],
+                allowed_paths: vec!["public".into()],
+                timeout_secs: Some(5),
+            },
+            workspace,
+        );
+
+        assert!(policy
+            .check("file_read", &json!({"path": "public/a.md"}))
+            .is_ok());
+        assert!(policy
+            .check("web_fetch", &json!({"url": "https://x"}))
+            .is_ok());
+        assert!(policy
+            .check("shell", &json!({"command": "ls"}))
+            .unwrap_err()
+            .contains("not allowed"));
+        assert!(policy
+            .check("memory_store", &json!({}))
+            .unwrap_err()
+            .contains("not allowed"));
+        assert!(policy
+            .check("file_read", &json!({"path": "public/.env"}))
+            .unwrap_err()
+            .contains("denied pattern"));
+        for escape in ["public/../secrets.txt", "/etc/passwd", "notes.md"] {
+            assert!(
+                policy
+                    .check("file_read", &json!({"path": escape}))
+                    .unwrap_err()
+                    .contains("outside"),
+                "{escape}"
+            );
+        }
+        assert_eq!(policy.timeout(), Some(Duration::from_secs(5)));
+
+        let mut excluded = vec!["shell".to_string()];
+        policy.restrict_tools(["file_read", "shell", "git_operations"], &mut excluded);
+        assert_eq!(excluded, ["shell", "git_operations"]);
+    }
+
+    #[test]
+    fn invalid_patterns_deny_every_tool() {
+        let policy = ExecutionPolicy::new(
+            "broken",
+            &ExecutionPolicyConfig {
+                denied_argument_patterns: vec!["(".into()],
+                ..ExecutionPolicyConfig::default()
+            },
+            Path::new("/tmp"),
+        );
+        assert!(!policy.allows_tool("file_read"));
+    }
+
+    #[test]
+    fn restricting_tools_denies_dispatchers() {
+        let restricted = ExecutionPolicy::new(
+            "public",
+            &ExecutionPolicyConfig {
+                allowed_tools: vec!["file_read".into(), "bg_run".into()],
+                ..ExecutionPolicyConfig::default()
+            },
+            Path::new("/tmp"),
+        );
+        for dispatcher in DISPATCHER_TOOLS {
+            assert!(!restricted.allows_tool(dispatcher), "{dispatcher}");
+        }
+
+        let open = ExecutionPolicy::new(
+            "paths_only",
+            &ExecutionPolicyConfig {
+                allowed_paths: vec!["public".into()],
+                ..ExecutionPolicyConfig::default()
+            },
+            Path::new("/tmp"),
+        );
+        assert!(open.allows_tool("bg_run"));
+    }
+
+    #[tokio::test]
+    async fn current_is_scoped_to_task() {
+        assert!(current().is_none());
+        let policy = Arc::new(ExecutionPolicy::new(
+            "public",
+            &ExecutionPolicyConfig::default(),
+            Path::new("/tmp"),
+        ));
+        let seen = scope(Some(policy), async {
+            current().map(|p| p.name().to_string())
+        })
+        .await;
+        assert_eq!(seen.as_deref(), Some("public"));
+        assert!(current().is_none());
+    }
+}
@@ -22,6 +22,7 @@ pub mod audit;
 #[cfg(feature = "sandbox-bubblewrap")]
 pub mod bubblewrap;
 pub mod canary_guard;
+pub mod db_encryption;
 pub mod detect;
 pub mod docker;
 pub mod file_link_guard;
@@ -29,6 +30,7 @@ pub mod file_link_guard;
 // Prompt injection defense (contributed from RustyClaw, MIT licensed)
 pub mod domain_matcher;
 pub mod estop;
+pub mod execution_policy;
 #[cfg(target_os = "linux")]
 pub mod firejail;
 #[cfg(feature = "sandbox-landlock")]
@@ -120,6 +120,20 @@ impl SecretStore {
         }
     }
 
+    /// Derive a hex-encoded 256-bit key for `purpose` (e.g. database
+    /// encryption) from the store key, creating the key file if needed.
+    /// Different purposes get unrelated keys.
+    pub fn derive_key(&self, purpose: &str) -> Result<String> {
+        use sha2::{Digest, Sha256};
+        let key = self.load_or_create_key()?;
+        let mut hasher = Sha256::new();
+        hasher.update(b"snowclaw-derived-key\0");
+        hasher.update(purpose.as_bytes());
+        hasher.update([0]);
+        hasher.update(&key);
+        Ok(hex_encode(&hasher.finalize()))
+    }
+
     /// Check if a value uses the legacy `enc:` format that should be migrated.
     pub fn needs_migration(value: &str) -> bool {
         value.starts_with("enc:")
@@ -361,6 +375,19 @@ mod tests {
         assert!(!SecretStore::is_encrypted(""));
     }
 
+    #[test]
+    fn derived_keys_are_stable_and_purpose_specific() {
+        let tmp = TempDir::new().unwrap();
+        let store = SecretStore::new(tmp.path(), true);
+        let key = store.derive_key("sqlcipher").unwrap();
+        assert_eq!(key.len(), 64);
+        assert_eq!(key, store.derive_key("sqlcipher").unwrap());
+        assert_ne!(key, store.derive_key("other").unwrap());
+
+        let other_store = SecretStore::new(TempDir::new().unwrap().path(), true);
+        assert_ne!(key, other_store.derive_key("sqlcipher").unwrap());
+    }
+
     #[tokio::test]
     async fn key_file_created_on_first_encrypt() {
         let tmp = TempDir::new().unwrap();
@@ -93,6 +93,7 @@ pub fn handle_command(
     command: &crate::ServiceCommands,
     config: &Config,
     init_system: InitSystem,
+    dry_run: bool,
 ) -> Result<()> {
     match command {
         crate::ServiceCommands::Install => install(config, init_system),
@@ -100,6 +101,7 @@ pub fn handle_command(
         crate::ServiceCommands::Stop => stop(config, init_system),
         crate::ServiceCommands::Restart => restart(config, init_system),
         crate::ServiceCommands::Status => status(config, init_system),
+        crate::ServiceCommands::Uninstall if dry_run => uninstall_dry_run(config, init_system),
         crate::ServiceCommands::Uninstall => uninstall(config, init_system),
     }
 }
@@ -118,11 +120,28 @@ fn install(config: &Config, init_system: InitSystem) -> Result<()> {
 }
 
 fn start(config: &Config, init_system: InitSystem) -> Result<()> {
+    start_unit(config, init_system)?;
+    println!("✅ Service started");
+    Ok(())
+}
+
+fn stop(config: &Config, init_system: InitSystem) -> Result<()> {
+    stop_unit(config, init_system)?;
+    println!("✅ Service stopped");
+    Ok(())
+}
+
+fn restart(config: &Config, init_system: InitSystem) -> Result<()> {
+    restart_unit(config, init_system)?;
+    println!("✅ Service restarted");
+    Ok(())
+}
+
+fn start_unit(config: &Config, init_system: InitSystem) -> Result<()> {
     if cfg!(target_os = "macos") {
         let plist = macos_service_file()?;
         run_checked(Command::new("launchctl").arg("load").arg("-w").arg(&plist))?;
         run_checked(Command::new("launchctl").arg("start").arg(SERVICE_LABEL))?;
-        println!("✅ Service started");
         Ok(())
     } else if cfg!(target_os = "linux") {
         let resolved = init_system.resolve()?;
@@ -130,7 +149,6 @@ fn start(config: &Config, init_system: InitSystem) -> Result<()> {
     } else if cfg!(target_os = "windows") {
         let _ = config;
         run_checked(Command::new("schtasks").args(["/Run", "/TN", windows_task_name()]))?;
-        println!("✅ Service started");
         Ok(())
     } else {
         let _ = config;
@@ -149,11 +167,10 @@ fn start_linux(init_system: InitSystem) -> Result<()> {
         }
         InitSystem::Auto => unreachable!("Auto should be resolved before this point"),
     }
-    println!("✅ Service started");
     Ok(())
 }
 
-fn stop(config: &Config, init_system: InitSystem) -> Result<()> {
+fn stop_unit(config: &Config, init_system: InitSystem) -> Result<()> {
     if cfg!(target_os = "macos") {
         let plist = macos_service_file()?;
         let _ = run_checked(Command::new("launchctl").arg("stop").arg(SERVICE_LABEL));
@@ -163,7 +180,6 @@ fn stop(config: &Config, init_system: InitSystem) -> Result<()> {
                 .arg("-w")
                 .arg(&plist),
         );
-        println!("✅ Service stopped");
         Ok(())
     } else if cfg!(target_os = "linux") {
         let resolved = init_system.resolve()?;
@@ -172,7 +188,6 @@ fn stop(config: &Config, init_system: InitSystem) -> Result<()> {
         let _ = config;
         let task_name = windows_task_name();
         let _ = run_checked(Command::new("schtasks").args(["/End", "/TN", task_name]));
-        println!("✅ Service stopped");
         Ok(())
     } else {
         let _ = config;
@@ -191,15 +206,13 @@ fn stop_linux(init_system: InitSystem) -> Result<()> {
         }
         InitSystem::Auto => unreachable!("Auto should be resolved before this point"),
     }
-    println!("✅ Service stopped");
     Ok(())
 }
 
-fn restart(config: &Config, init_system: InitSystem) -> Result<()> {
+fn restart_unit(config: &Config, init_system: InitSystem) -> Result<()> {
     if cfg!(target_os = "macos") {
-        stop(config, init_system)?;
-        start(config, init_system)?;
-        println!("✅ Service restarted");
+        stop_unit(config, init_system)?;
+        start_unit(config, init_system)?;
         return Ok(());
     }
 
@@ -209,15 +222,24 @@ fn restart(config: &Config, init_system: InitSystem) -> Result<()> {
     }
 
     if cfg!(target_os = "windows") {
-        stop(config, init_system)?;
-        start(config, init_system)?;
-        println!("✅ Service restarted");
+        stop_unit(config, init_system)?;
+        start_unit(config, init_system)?;
         return Ok(());
     }
 
     anyhow::bail!("Service management is supported on macOS and Linux only")
 }
 
+/// Restart the daemon through the detected service manager.
+///
+/// Used by the remote `control.update` action once a new binary is
+/// installed, or rolled back, so it logs instead of printing.
+pub fn restart_service(config: &Config) -> Result<()> {
+    restart_unit(config, InitSystem::Auto)?;
+    tracing::info!("Service restart requested");
+    Ok(())
+}
+
 fn restart_linux(init_system: InitSystem) -> Result<()> {
     match init_system {
         InitSystem::Systemd => {
@@ -229,7 +251,6 @@ fn restart_linux(init_system: InitSystem) -> Result<()> {
         }
         InitSystem::Auto => unreachable!("Auto should be resolved before this point"),
     }
-    println!("✅ Service restarted");
     Ok(())
 }
 
@@ -343,6 +364,63 @@ fn uninstall(config: &Config, init_system: InitSystem) -> Result<()> {
     anyhow::bail!("Service management is supported on macOS and Linux only")
 }
 
+/// Report what `service uninstall` would stop and delete.
+fn uninstall_dry_run(config: &Config, init_system: InitSystem) -> Result<()> {
+    let (files, commands): (Vec<PathBuf>, Vec<String>) = if cfg!(target_os = "macos") {
+        let plist = macos_service_file()?;
+        let unload = format!("launchctl unload -w {}", plist.display());
+        (
+            vec![plist],
+            vec![format!("launchctl stop {SERVICE_LABEL}"), unload],
+        )
+    } else if cfg!(target_os = "linux") {
+        match init_system.resolve()? {
+            InitSystem::Systemd => (
+                vec![linux_service_file(config)?],
+                vec![
+                    "systemctl --user stop zeroclaw.service".into(),
+                    "systemctl --user daemon-reload".into(),
+                ],
+            ),
+            InitSystem::Openrc => (
+                vec![PathBuf::from("/etc/init.d/zeroclaw")],
+                vec![
+                    "rc-service zeroclaw stop".into(),
+                    "rc-update del zeroclaw default".into(),
+                ],
+            ),
+            InitSystem::Auto => unreachable!("Auto should be resolved before this point"),
+        }
+    } else if cfg!(target_os = "windows") {
+        let wrapper = config
+            .config_path
+            .parent()
+            .map_or_else(|| PathBuf::from("."), PathBuf::from)
+            .join("logs")
+            .join("zeroclaw-daemon.cmd");
+        let task_name = windows_task_name();
+        (
+            vec![wrapper],
+            vec![
+                format!("schtasks /End /TN {task_name}"),
+                format!("schtasks /Delete /TN {task_name} /F"),
+            ],
+        )
+    } else {
+        anyhow::bail!("Service management is supported on macOS and Linux only");
+    };
+
+    println!("[dry-run] Would uninstall the service:");
+    for command in commands {
+        println!("  run    {command}");
+    }
+    for file in files {
+        let note = if file.exists() { "" } else { " (not present)" };
+        println!("  delete {}{note}", file.display());
+    }
+    Ok(())
+}
+
 fn uninstall_linux(config: &Config, init_system: InitSystem) -> Result<()> {
     match init_system {
         InitSystem::Systemd => {
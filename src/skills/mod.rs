@@ -2179,9 +2179,26 @@ fn fetch_url_blocking(url: &str, auth_token: Option<&str>) -> Result<Vec<u8>> {
 
 // ─── Handle command ───────────────────────────────────────────────────────────
 
+/// Files under `dir`, not following symlinks.
+fn count_files(dir: &Path) -> usize {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => count_files(&entry.path()),
+            _ => 1,
+        })
+        .sum()
+}
+
 /// Handle the `skills` CLI command
 #[allow(clippy::too_many_lines)]
-pub fn handle_command(command: crate::SkillCommands, config: &crate::config::Config) -> Result<()> {
+pub fn handle_command(
+    command: crate::SkillCommands,
+    config: &crate::config::Config,
+    dry_run: bool,
+) -> Result<()> {
     let workspace_dir = &config.workspace_dir;
     match command {
         crate::SkillCommands::New { name, template } => {
@@ -2497,6 +2514,15 @@ pub fn handle_command(command: crate::SkillCommands, config: &crate::config::Con
                 anyhow::bail!("Skill not found: {name}");
             }
 
+            if dry_run {
+                println!(
+                    "[dry-run] Would remove skill '{name}' ({} files in {})",
+                    count_files(&skill_path),
+                    skill_path.display()
+                );
+                return Ok(());
+            }
+
             std::fs::remove_dir_all(&skill_path)?;
             println!(
                 "  {} Skill '{}' removed.",
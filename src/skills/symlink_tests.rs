@@ -152,6 +152,7 @@ mod tests {
                     source: "outside_skill".to_string(),
                 },
                 &config,
+                false,
             );
             assert!(
                 blocked.is_err(),
@@ -164,6 +165,7 @@ mod tests {
                     source: "outside_skill".to_string(),
                 },
                 &config,
+                false,
             );
             assert!(
                 allowed.is_ok(),
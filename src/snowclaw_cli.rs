@@ -4,29 +4,108 @@
 //! Snowclaw-only CLI subcommand logic lives here.
 
 use crate::config::Config;
+use crate::memory::engagement;
 use crate::stats;
 use anyhow::Result;
 
 /// Handle the `stats` CLI subcommand.
+#[allow(clippy::too_many_arguments)]
 pub fn handle_stats(
     config: &Config,
     date: Option<String>,
     period: Option<String>,
     room: Option<String>,
+    tz: Option<String>,
+    by: Option<stats::StatsGroup>,
     json: bool,
+    format: stats::StatsFormat,
     live: bool,
 ) -> Result<()> {
+    let format = if json {
+        stats::StatsFormat::Json
+    } else {
+        format
+    };
+    let calendar = stats::StatsCalendar::from_config(&config.stats, tz.as_deref())?;
     if live {
-        return stats::tui::run(&config.workspace_dir);
+        let filter = stats::build_filter(date.as_deref(), period.as_deref(), room, &calendar)?;
+        return stats::tui::run(&config.workspace_dir, &calendar, &filter, period.as_deref());
+    }
+    if format == stats::StatsFormat::Csv && by.is_some() {
+        anyhow::bail!("--by has no effect on CSV output, which is always per day and room");
     }
     let jsonl_path = stats::costs_jsonl_path(&config.workspace_dir);
     let records = stats::read_records(&jsonl_path)?;
-    let filter = stats::build_filter(date.as_deref(), period.as_deref(), room)?;
-    let result = stats::aggregate(&records, &filter);
-    if json {
-        stats::print_stats_json(&result)?;
+    let filter = stats::build_filter(date.as_deref(), period.as_deref(), room, &calendar)?;
+    let mut result = stats::aggregate(&records, &filter);
+    if let Some(quota) = config
+        .channels_config
+        .nostr
+        .as_ref()
+        .map(|ns| &ns.quota)
+        .filter(|q| q.enabled)
+    {
+        // Quota usage is per UTC day, so it only applies to reports ending today.
+        let today = chrono::Utc::now().date_naive();
+        if filter.end_date == calendar.today() {
+            let persist_dir = config
+                .config_path
+                .parent()
+                .unwrap_or(std::path::Path::new("."));
+            result.contact_quotas =
+                crate::channels::nostr_quota::usage_rows(quota, persist_dir, &records, today)?;
+        }
+    }
+    match format {
+        stats::StatsFormat::Text => stats::print_stats(&result, by),
+        stats::StatsFormat::Json => stats::print_stats_json(&result, by)?,
+        stats::StatsFormat::Csv => stats::print_stats_csv(&result),
+    }
+    Ok(())
+}
+
+/// Handle `stats --engagement`: group activity and reply rate from the
+/// message index in `social.db`. Without `--date` or `--period` the current
+/// week is shown.
+pub fn handle_engagement(
+    config: &Config,
+    date: Option<String>,
+    period: Option<String>,
+    room: Option<String>,
+    tz: Option<String>,
+    json: bool,
+    format: stats::StatsFormat,
+) -> Result<()> {
+    let calendar = stats::StatsCalendar::from_config(&config.stats, tz.as_deref())?;
+    let period = period.or_else(|| date.is_none().then(|| "week".to_string()));
+    let filter = stats::build_filter(date.as_deref(), period.as_deref(), room, &calendar)?;
+
+    let db_path = config
+        .config_path
+        .parent()
+        .unwrap_or(std::path::Path::new("."))
+        .join("social.db");
+    let groups = if db_path.exists() {
+        let conn = crate::security::db_encryption::open(&db_path)?;
+        crate::memory::social::create_social_tables(&conn)?;
+        crate::memory::message_index::create_message_tables(&conn)?;
+        engagement::create_engagement_tables(&conn)?;
+        engagement::group_engagement(&conn, &stats::engagement_filter(&filter))?
+    } else {
+        Vec::new()
+    };
+
+    let format = if json {
+        stats::StatsFormat::Json
     } else {
-        stats::print_stats(&result);
+        format
+    };
+    match format {
+        stats::StatsFormat::Text => stats::print_engagement(&groups, &filter),
+        stats::StatsFormat::Json => stats::print_engagement_json(&groups, &filter)?,
+        stats::StatsFormat::Csv => {
+            anyhow::bail!("--engagement supports text and JSON output only")
+        }
     }
     Ok(())
 }
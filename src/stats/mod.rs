@@ -1,9 +1,12 @@
 pub mod tui;
 
-use crate::cost::types::{CostRecord, TokenBreakdown};
+use crate::config::StatsConfig;
+use crate::cost::types::{latest_records, CostRecord, TokenBreakdown, ToolLatency};
+use crate::memory::engagement::{self, EngagementFilter, GroupEngagement};
 use anyhow::{Context, Result};
-use chrono::{Datelike, NaiveDate, Utc};
-use std::collections::HashMap;
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
@@ -13,21 +16,157 @@ pub struct StatsFilter {
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
     pub room: Option<String>,
+    /// Zone whose local dates `start_date`/`end_date` are.
+    pub tz: Tz,
+}
+
+/// Time zone and week layout used to resolve stats periods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatsCalendar {
+    pub tz: Tz,
+    pub week_start: Weekday,
+}
+
+impl Default for StatsCalendar {
+    fn default() -> Self {
+        Self {
+            tz: Tz::UTC,
+            week_start: Weekday::Mon,
+        }
+    }
+}
+
+impl StatsCalendar {
+    /// Calendar from `[stats]`; `tz` (the `--tz` flag) overrides the
+    /// configured timezone.
+    pub fn from_config(config: &StatsConfig, tz: Option<&str>) -> Result<Self> {
+        let tz = match tz.or(config.timezone.as_deref()) {
+            Some(name) => name
+                .parse::<Tz>()
+                .map_err(|_| anyhow::anyhow!("Invalid IANA timezone: {name}"))?,
+            None => Tz::UTC,
+        };
+        Ok(Self {
+            tz,
+            week_start: config.week_start.weekday(),
+        })
+    }
+
+    /// Local date of `at`.
+    pub fn date_of(&self, at: DateTime<Utc>) -> NaiveDate {
+        at.with_timezone(&self.tz).date_naive()
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.date_of(Utc::now())
+    }
+
+    /// First day of the week containing `date`.
+    pub fn week_of(&self, date: NaiveDate) -> NaiveDate {
+        let offset = (date.weekday().num_days_from_monday() + 7
+            - self.week_start.num_days_from_monday())
+            % 7;
+        date - chrono::Duration::days(i64::from(offset))
+    }
 }
 
 /// Aggregated stats result for display.
 pub struct StatsResult {
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
+    /// Zone the dates are local to.
+    pub tz: Tz,
     pub total_input: u64,
     pub total_output: u64,
     pub total_cache_read: u64,
     pub total_cache_write: u64,
     pub total_cost: f64,
     pub request_count: usize,
+    /// Streamed responses cancelled before completion (billed up to the
+    /// cancel).
+    pub cancelled_count: usize,
     pub by_channel_room: Vec<ChannelRoomRow>,
+    pub by_persona: Vec<ChannelRoomRow>,
+    pub by_route: Vec<ChannelRoomRow>,
+    /// Full `provider/model` names.
+    pub by_model: Vec<ChannelRoomRow>,
+    /// Provider prefix of the model name.
+    pub by_provider: Vec<ChannelRoomRow>,
+    /// Tool call latency, slowest in total first.
+    pub by_tool: Vec<ToolLatencyRow>,
+    /// Requests answered from a response cache instead of the LLM.
+    pub cache_hits: CacheHitStats,
     pub breakdown: Option<BreakdownResult>,
     pub records: Vec<CostRecord>,
+    /// Today's per-contact quota consumption (filled by the caller when
+    /// Nostr quotas are enabled).
+    pub contact_quotas: Vec<ContactQuotaRow>,
+}
+
+/// Cache-hit accounting: records with message type `cache_hit`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CacheHitStats {
+    pub exact: usize,
+    /// Hits on a paraphrase (embedding similarity).
+    pub semantic: usize,
+    /// Hits × average cost of an LLM-answered user message in the period.
+    pub estimated_savings: f64,
+}
+
+impl CacheHitStats {
+    pub fn total(&self) -> usize {
+        self.exact + self.semantic
+    }
+}
+
+/// Breakdown selected with `snowclaw stats --by`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum StatsGroup {
+    /// Channel/room, persona and route sections
+    Room,
+    Model,
+    Provider,
+    /// Tool call latency
+    Tool,
+}
+
+/// Output of `snowclaw stats --format`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum StatsFormat {
+    #[default]
+    Text,
+    Json,
+    /// One row per day and channel/room
+    Csv,
+}
+
+/// Whether the breakdowns of `group` are shown; `None` shows all.
+fn shows(by: Option<StatsGroup>, group: StatsGroup) -> bool {
+    by.is_none_or(|by| by == group)
+}
+
+/// Provider of a recorded `provider/model` name.
+pub fn provider_of(model: &str) -> &str {
+    model
+        .split_once('/')
+        .map_or("unknown", |(provider, _)| provider)
+}
+
+/// Latency of one tool's calls over the period.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ToolLatencyRow {
+    pub tool: String,
+    pub calls: u32,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    pub cache_hits: u32,
+    pub cache_misses: u32,
+}
+
+impl ToolLatencyRow {
+    pub fn avg_ms(&self) -> u64 {
+        self.total_ms / u64::from(self.calls.max(1))
+    }
 }
 
 pub struct ChannelRoomRow {
@@ -38,6 +177,20 @@ pub struct ChannelRoomRow {
     pub cost: f64,
 }
 
+/// Quota consumption of one contact for the current UTC day.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ContactQuotaRow {
+    pub contact: String,
+    /// Quota tag the limits come from, if any.
+    pub tag: Option<String>,
+    pub requests: u32,
+    /// `None` = unlimited.
+    pub request_limit: Option<u32>,
+    pub cost: f64,
+    /// `None` = unlimited.
+    pub cost_limit: Option<f64>,
+}
+
 pub struct BreakdownResult {
     pub categories: Vec<(String, u64, f64)>, // (name, bytes, percent)
 }
@@ -47,7 +200,8 @@ pub fn costs_jsonl_path(workspace_dir: &Path) -> PathBuf {
     workspace_dir.join("state").join("costs.jsonl")
 }
 
-/// Read all cost records from the JSONL file.
+/// Read all cost records from the JSONL file, keeping only the latest
+/// record of each streamed response.
 pub fn read_records(path: &Path) -> Result<Vec<CostRecord>> {
     if !path.exists() {
         return Ok(Vec::new());
@@ -69,16 +223,18 @@ pub fn read_records(path: &Path) -> Result<Vec<CostRecord>> {
         }
     }
 
-    Ok(records)
+    Ok(latest_records(records))
 }
 
-/// Build a StatsFilter from CLI args.
+/// Build a StatsFilter from CLI args. Dates are local to `calendar.tz`;
+/// a week is the calendar week starting on `calendar.week_start`.
 pub fn build_filter(
     date: Option<&str>,
     period: Option<&str>,
     room: Option<String>,
+    calendar: &StatsCalendar,
 ) -> Result<StatsFilter> {
-    let today = Utc::now().date_naive();
+    let today = calendar.today();
 
     let (start_date, end_date) = match (date, period) {
         (Some(d), _) => {
@@ -86,8 +242,8 @@ pub fn build_filter(
                 .context("Invalid date format, expected YYYY-MM-DD")?;
             match period.unwrap_or("day") {
                 "week" => {
-                    let end = parsed + chrono::Duration::days(6);
-                    (parsed, end)
+                    let start = calendar.week_of(parsed);
+                    (start, start + chrono::Duration::days(6))
                 }
                 "month" => {
                     let end = if parsed.month() == 12 {
@@ -105,10 +261,7 @@ pub fn build_filter(
                 _ => (parsed, parsed),
             }
         }
-        (None, Some("week")) => {
-            let start = today - chrono::Duration::days(6);
-            (start, today)
-        }
+        (None, Some("week")) => (calendar.week_of(today), today),
         (None, Some("month")) => {
             let start = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap_or(today);
             (start, today)
@@ -120,15 +273,18 @@ pub fn build_filter(
         start_date,
         end_date,
         room,
+        tz: calendar.tz,
     })
 }
 
-/// Aggregate records according to the filter.
+/// Aggregate records according to the filter. Records are bucketed by
+/// their local date in `filter.tz`, so ranges stay whole days across DST
+/// changes.
 pub fn aggregate(records: &[CostRecord], filter: &StatsFilter) -> StatsResult {
     let mut filtered: Vec<&CostRecord> = records
         .iter()
         .filter(|r| {
-            let date = r.usage.timestamp.naive_utc().date();
+            let date = r.usage.timestamp.with_timezone(&filter.tz).date_naive();
             date >= filter.start_date && date <= filter.end_date
         })
         .filter(|r| {
@@ -150,6 +306,17 @@ pub fn aggregate(records: &[CostRecord], filter: &StatsFilter) -> StatsResult {
 
     // channel/room -> aggregated row
     let mut channel_room_map: HashMap<String, (usize, u64, u64, f64)> = HashMap::new();
+    // persona -> aggregated row (only records handled by a persona)
+    let mut persona_map: HashMap<String, (usize, u64, u64, f64)> = HashMap::new();
+    // model route -> aggregated row (only records routed by `[model_router]`)
+    let mut route_map: HashMap<String, (usize, u64, u64, f64)> = HashMap::new();
+    let mut model_map: HashMap<String, (usize, u64, u64, f64)> = HashMap::new();
+    let mut provider_map: HashMap<String, (usize, u64, u64, f64)> = HashMap::new();
+    let mut tool_map: HashMap<String, ToolLatency> = HashMap::new();
+
+    let mut cache_hits = CacheHitStats::default();
+    let mut cancelled_count = 0usize;
+    let (mut llm_messages, mut llm_message_cost) = (0usize, 0.0f64);
 
     // Breakdown aggregation
     let mut agg_breakdown = TokenBreakdown::default();
@@ -161,6 +328,7 @@ pub fn aggregate(records: &[CostRecord], filter: &StatsFilter) -> StatsResult {
         total_cache_read += r.usage.cache_read_tokens.unwrap_or(0);
         total_cache_write += r.usage.cache_write_tokens.unwrap_or(0);
         total_cost += r.usage.cost_usd;
+        cancelled_count += usize::from(r.cancelled);
 
         let channel = r.channel.as_deref().unwrap_or("unknown");
         let room = r.room.as_deref().unwrap_or("default");
@@ -171,6 +339,47 @@ pub fn aggregate(records: &[CostRecord], filter: &StatsFilter) -> StatsResult {
         entry.2 += r.usage.output_tokens;
         entry.3 += r.usage.cost_usd;
 
+        for (map, key) in [
+            (&mut model_map, r.usage.model.as_str()),
+            (&mut provider_map, provider_of(&r.usage.model)),
+        ] {
+            let entry = map.entry(key.to_string()).or_insert((0, 0, 0, 0.0));
+            entry.0 += 1;
+            entry.1 += r.usage.input_tokens;
+            entry.2 += r.usage.output_tokens;
+            entry.3 += r.usage.cost_usd;
+        }
+
+        if let Some(ref persona) = r.persona {
+            let entry = persona_map.entry(persona.clone()).or_insert((0, 0, 0, 0.0));
+            entry.0 += 1;
+            entry.1 += r.usage.input_tokens;
+            entry.2 += r.usage.output_tokens;
+            entry.3 += r.usage.cost_usd;
+        }
+
+        match r.message_type.as_deref() {
+            Some("cache_hit") if r.usage.model == "cache/semantic" => cache_hits.semantic += 1,
+            Some("cache_hit") => cache_hits.exact += 1,
+            Some("user_message") => {
+                llm_messages += 1;
+                llm_message_cost += r.usage.cost_usd;
+            }
+            _ => {}
+        }
+
+        for (tool, latency) in &r.tool_latency {
+            tool_map.entry(tool.clone()).or_default().merge(latency);
+        }
+
+        if let Some(ref route) = r.route {
+            let entry = route_map.entry(route.clone()).or_insert((0, 0, 0, 0.0));
+            entry.0 += 1;
+            entry.1 += r.usage.input_tokens;
+            entry.2 += r.usage.output_tokens;
+            entry.3 += r.usage.cost_usd;
+        }
+
         if let Some(ref bd) = r.breakdown {
             has_breakdown = true;
             agg_breakdown.tooling += bd.tooling;
@@ -190,21 +399,27 @@ pub fn aggregate(records: &[CostRecord], filter: &StatsFilter) -> StatsResult {
         }
     }
 
-    let mut by_channel_room: Vec<ChannelRoomRow> = channel_room_map
+    let by_channel_room = rows_by_cost(channel_room_map);
+    let by_persona = rows_by_cost(persona_map);
+    let by_route = rows_by_cost(route_map);
+    let by_model = rows_by_cost(model_map);
+    let by_provider = rows_by_cost(provider_map);
+    let mut by_tool: Vec<ToolLatencyRow> = tool_map
         .into_iter()
-        .map(|(label, (requests, input, output, cost))| ChannelRoomRow {
-            label,
-            requests,
-            input_tokens: input,
-            output_tokens: output,
-            cost,
+        .map(|(tool, latency)| ToolLatencyRow {
+            tool,
+            calls: latency.calls,
+            total_ms: latency.total_ms,
+            max_ms: latency.max_ms,
+            cache_hits: latency.cache_hits,
+            cache_misses: latency.cache_misses,
         })
         .collect();
-    by_channel_room.sort_by(|a, b| {
-        b.cost
-            .partial_cmp(&a.cost)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
+    by_tool.sort_by(|a, b| b.total_ms.cmp(&a.total_ms).then(a.tool.cmp(&b.tool)));
+    if llm_messages > 0 {
+        cache_hits.estimated_savings =
+            cache_hits.total() as f64 * llm_message_cost / llm_messages as f64;
+    }
 
     let breakdown = if has_breakdown {
         let request_count = filtered.len().max(1) as u64;
@@ -217,18 +432,46 @@ pub fn aggregate(records: &[CostRecord], filter: &StatsFilter) -> StatsResult {
     StatsResult {
         start_date: filter.start_date,
         end_date: filter.end_date,
+        tz: filter.tz,
         total_input,
         total_output,
         total_cache_read,
         total_cache_write,
         total_cost,
         request_count: filtered.len(),
+        cancelled_count,
         by_channel_room,
+        by_persona,
+        by_route,
+        by_model,
+        by_provider,
+        by_tool,
+        cache_hits,
         breakdown,
         records: filtered.into_iter().cloned().collect(),
+        contact_quotas: Vec::new(),
     }
 }
 
+fn rows_by_cost(map: HashMap<String, (usize, u64, u64, f64)>) -> Vec<ChannelRoomRow> {
+    let mut rows: Vec<ChannelRoomRow> = map
+        .into_iter()
+        .map(|(label, (requests, input, output, cost))| ChannelRoomRow {
+            label,
+            requests,
+            input_tokens: input,
+            output_tokens: output,
+            cost,
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.cost
+            .partial_cmp(&a.cost)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    rows
+}
+
 fn breakdown_categories(bd: &TokenBreakdown, request_count: u64) -> Vec<(String, u64, f64)> {
     let items = [
         ("identity", bd.identity),
@@ -264,8 +507,9 @@ fn breakdown_categories(bd: &TokenBreakdown, request_count: u64) -> Vec<(String,
     result
 }
 
-/// Format and print stats to stdout.
-pub fn print_stats(result: &StatsResult) {
+/// Format and print stats to stdout. `by` limits the breakdown sections
+/// to one grouping.
+pub fn print_stats(result: &StatsResult, by: Option<StatsGroup>) {
     let date_label = if result.start_date == result.end_date {
         result.start_date.format("%Y-%m-%d").to_string()
     } else {
@@ -295,33 +539,62 @@ pub fn print_stats(result: &StatsResult) {
         "Cost: ${:.2} (estimated)    Requests: {}",
         result.total_cost, result.request_count
     );
+    if result.cancelled_count > 0 {
+        println!(
+            "Cancelled streams: {} (billed up to the cancel)",
+            result.cancelled_count
+        );
+    }
     println!();
 
-    if !result.by_channel_room.is_empty() {
-        println!("By Channel/Room:");
-        // Calculate column widths
-        let max_label = result
-            .by_channel_room
-            .iter()
-            .map(|r| r.label.len())
-            .max()
-            .unwrap_or(10)
-            .max(10);
+    if shows(by, StatsGroup::Room) {
+        if !result.by_channel_room.is_empty() {
+            println!("By Channel/Room:");
+            print_rows(&result.by_channel_room);
+        }
 
-        for row in &result.by_channel_room {
-            println!(
-                "  {:<width$} {:>4} req  {:>8} in  {:>8} out  ${:.2}",
-                row.label,
-                row.requests,
-                fmt_num(row.input_tokens),
-                fmt_num(row.output_tokens),
-                row.cost,
-                width = max_label,
-            );
+        if !result.by_persona.is_empty() {
+            println!("By Persona:");
+            print_rows(&result.by_persona);
+        }
+
+        if !result.by_route.is_empty() {
+            println!("By Route:");
+            print_rows(&result.by_route);
         }
+    }
+
+    if shows(by, StatsGroup::Model) && !result.by_model.is_empty() {
+        println!("By Model:");
+        print_rows(&result.by_model);
+    }
+
+    if shows(by, StatsGroup::Provider) && !result.by_provider.is_empty() {
+        println!("By Provider:");
+        print_rows(&result.by_provider);
+    }
+
+    if shows(by, StatsGroup::Tool) && !result.by_tool.is_empty() {
+        println!("By Tool (latency):");
+        print_tool_rows(&result.by_tool);
+    }
+
+    if result.cache_hits.total() > 0 {
+        println!(
+            "Cache Hits: {} ({} exact / {} similar)    Saved: ~${:.2} (estimated)",
+            result.cache_hits.total(),
+            result.cache_hits.exact,
+            result.cache_hits.semantic,
+            result.cache_hits.estimated_savings
+        );
         println!();
     }
 
+    if !result.contact_quotas.is_empty() {
+        println!("Contact Quotas (today):");
+        print_quota_rows(&result.contact_quotas);
+    }
+
     if let Some(ref bd) = result.breakdown {
         println!("By Category (avg per request):");
         for (name, avg_bytes, pct) in &bd.categories {
@@ -336,10 +609,82 @@ pub fn print_stats(result: &StatsResult) {
     }
 }
 
-/// Print stats as JSON.
-pub fn print_stats_json(result: &StatsResult) -> Result<()> {
+fn print_rows(rows: &[ChannelRoomRow]) {
+    // Calculate column widths
+    let max_label = rows
+        .iter()
+        .map(|r| r.label.len())
+        .max()
+        .unwrap_or(10)
+        .max(10);
+
+    for row in rows {
+        println!(
+            "  {:<width$} {:>4} req  {:>8} in  {:>8} out  ${:.2}",
+            row.label,
+            row.requests,
+            fmt_num(row.input_tokens),
+            fmt_num(row.output_tokens),
+            row.cost,
+            width = max_label,
+        );
+    }
+    println!();
+}
+
+fn print_tool_rows(rows: &[ToolLatencyRow]) {
+    let width = rows
+        .iter()
+        .map(|r| r.tool.len())
+        .max()
+        .unwrap_or(10)
+        .max(10);
+    for row in rows {
+        let lookups = row.cache_hits + row.cache_misses;
+        let cache = if lookups > 0 {
+            format!("  cache {}/{lookups} hits", row.cache_hits)
+        } else {
+            String::new()
+        };
+        println!(
+            "  {:<width$} {:>5} calls  {:>9} ms total  {:>7} ms avg  {:>7} ms max{cache}",
+            row.tool,
+            row.calls,
+            fmt_num(row.total_ms),
+            fmt_num(row.avg_ms()),
+            fmt_num(row.max_ms),
+        );
+    }
+    println!();
+}
+
+fn print_quota_rows(rows: &[ContactQuotaRow]) {
+    let limit = |used: String, limit: Option<String>| match limit {
+        Some(limit) => format!("{used}/{limit}"),
+        None => format!("{used}/∞"),
+    };
+    for row in rows {
+        println!(
+            "  {:<20} {:>9} req  {:>13}  {}",
+            row.contact,
+            limit(
+                row.requests.to_string(),
+                row.request_limit.map(|l| l.to_string())
+            ),
+            limit(
+                format!("${:.2}", row.cost),
+                row.cost_limit.map(|l| format!("${l:.2}"))
+            ),
+            row.tag.as_deref().unwrap_or("default"),
+        );
+    }
+    println!();
+}
+
+/// Print stats as JSON. `by` limits the breakdowns like [`print_stats`].
+pub fn print_stats_json(result: &StatsResult, by: Option<StatsGroup>) -> Result<()> {
     #[derive(serde::Serialize)]
-    struct JsonOutput {
+    struct JsonOutput<'a> {
         start_date: String,
         end_date: String,
         total_input_tokens: u64,
@@ -348,9 +693,25 @@ pub fn print_stats_json(result: &StatsResult) -> Result<()> {
         total_cache_write_tokens: u64,
         total_cost_usd: f64,
         request_count: usize,
-        by_channel_room: Vec<JsonChannelRoom>,
+        cancelled_count: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        by_channel_room: Option<Vec<JsonChannelRoom>>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        by_persona: Vec<JsonChannelRoom>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        by_route: Vec<JsonChannelRoom>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        by_model: Vec<JsonChannelRoom>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        by_provider: Vec<JsonChannelRoom>,
+        #[serde(skip_serializing_if = "<[_]>::is_empty")]
+        by_tool: &'a [ToolLatencyRow],
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_hits: Option<JsonCacheHits>,
         #[serde(skip_serializing_if = "Option::is_none")]
         breakdown: Option<Vec<JsonCategory>>,
+        #[serde(skip_serializing_if = "<[_]>::is_empty")]
+        contact_quotas: &'a [ContactQuotaRow],
     }
 
     #[derive(serde::Serialize)]
@@ -362,6 +723,35 @@ pub fn print_stats_json(result: &StatsResult) -> Result<()> {
         cost_usd: f64,
     }
 
+    fn to_json_row(r: &ChannelRoomRow) -> JsonChannelRoom {
+        JsonChannelRoom {
+            label: r.label.clone(),
+            requests: r.requests,
+            input_tokens: r.input_tokens,
+            output_tokens: r.output_tokens,
+            cost_usd: r.cost,
+        }
+    }
+
+    fn json_rows(
+        by: Option<StatsGroup>,
+        group: StatsGroup,
+        rows: &[ChannelRoomRow],
+    ) -> Vec<JsonChannelRoom> {
+        if shows(by, group) {
+            rows.iter().map(to_json_row).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    struct JsonCacheHits {
+        exact: usize,
+        semantic: usize,
+        estimated_savings_usd: f64,
+    }
+
     #[derive(serde::Serialize)]
     struct JsonCategory {
         name: String,
@@ -378,17 +768,23 @@ pub fn print_stats_json(result: &StatsResult) -> Result<()> {
         total_cache_write_tokens: result.total_cache_write,
         total_cost_usd: result.total_cost,
         request_count: result.request_count,
-        by_channel_room: result
-            .by_channel_room
-            .iter()
-            .map(|r| JsonChannelRoom {
-                label: r.label.clone(),
-                requests: r.requests,
-                input_tokens: r.input_tokens,
-                output_tokens: r.output_tokens,
-                cost_usd: r.cost,
-            })
-            .collect(),
+        cancelled_count: result.cancelled_count,
+        by_channel_room: shows(by, StatsGroup::Room)
+            .then(|| result.by_channel_room.iter().map(to_json_row).collect()),
+        by_persona: json_rows(by, StatsGroup::Room, &result.by_persona),
+        by_route: json_rows(by, StatsGroup::Room, &result.by_route),
+        by_model: json_rows(by, StatsGroup::Model, &result.by_model),
+        by_provider: json_rows(by, StatsGroup::Provider, &result.by_provider),
+        by_tool: if shows(by, StatsGroup::Tool) {
+            &result.by_tool
+        } else {
+            &[]
+        },
+        cache_hits: (result.cache_hits.total() > 0).then(|| JsonCacheHits {
+            exact: result.cache_hits.exact,
+            semantic: result.cache_hits.semantic,
+            estimated_savings_usd: result.cache_hits.estimated_savings,
+        }),
         breakdown: result.breakdown.as_ref().map(|bd| {
             bd.categories
                 .iter()
@@ -399,12 +795,236 @@ pub fn print_stats_json(result: &StatsResult) -> Result<()> {
                 })
                 .collect()
         }),
+        contact_quotas: &result.contact_quotas,
+    };
+
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// Unix-second range of the filter's local dates, for engagement queries.
+pub fn engagement_filter(filter: &StatsFilter) -> EngagementFilter {
+    let midnight = |date: NaiveDate| {
+        date.and_hms_opt(0, 0, 0)
+            .and_then(|t| filter.tz.from_local_datetime(&t).earliest())
+            .map_or_else(
+                || {
+                    date.and_hms_opt(0, 0, 0)
+                        .unwrap_or_default()
+                        .and_utc()
+                        .timestamp()
+                },
+                |t| t.timestamp(),
+            )
     };
+    EngagementFilter {
+        since: midnight(filter.start_date),
+        until: midnight(filter.end_date + chrono::Duration::days(1)) - 1,
+        group: filter
+            .room
+            .as_deref()
+            .map(|room| room.trim_start_matches('#').to_string()),
+        tz: filter.tz,
+    }
+}
+
+/// Shades of the heatmap cells, from no messages to the busiest hour.
+const HEAT_SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// Hour-of-day × weekday heatmap of a group: an hour ruler, then one line
+/// per weekday with a shaded cell per hour relative to the busiest hour.
+pub fn heatmap_lines(group: &GroupEngagement) -> Vec<String> {
+    let max = group.heatmap.iter().flatten().copied().max().unwrap_or(0);
+    let mut lines = vec![format!("      {:<6}{:<6}{:<6}{:<6}", "0", "6", "12", "18")];
+    for (index, day) in group.heatmap.iter().enumerate() {
+        let cells: String = day
+            .iter()
+            .map(|&count| {
+                if count == 0 || max == 0 {
+                    HEAT_SHADES[0]
+                } else {
+                    // Any activity gets at least the lightest shade.
+                    #[allow(clippy::cast_possible_truncation)]
+                    let level = (count * 4).div_ceil(max) as usize;
+                    HEAT_SHADES[level.clamp(1, 4)]
+                }
+            })
+            .collect();
+        let total: u64 = day.iter().sum();
+        lines.push(format!(
+            "  {} |{cells}| {total}",
+            engagement::weekday(index)
+        ));
+    }
+    lines
+}
+
+/// Print group activity and engagement for `snowclaw stats --engagement`.
+pub fn print_engagement(groups: &[GroupEngagement], filter: &StatsFilter) {
+    let date_label = if filter.start_date == filter.end_date {
+        filter.start_date.format("%Y-%m-%d").to_string()
+    } else {
+        format!(
+            "{} to {}",
+            filter.start_date.format("%Y-%m-%d"),
+            filter.end_date.format("%Y-%m-%d")
+        )
+    };
+
+    println!("Snowclaw Group Engagement — {date_label} ({})", filter.tz);
+    println!();
+
+    if groups.is_empty() {
+        println!("No group activity for this period.");
+        return;
+    }
 
+    for group in groups {
+        let ratio = group
+            .engagement_ratio()
+            .map_or_else(|| "n/a".to_string(), |r| format!("{:.0}%", r * 100.0));
+        println!(
+            "#{}: {} indexed messages    Answered: {}/{} seen ({ratio})",
+            group.group_id,
+            fmt_num(group.messages),
+            fmt_num(group.answered),
+            fmt_num(group.seen),
+        );
+        if group.messages > 0 {
+            for line in heatmap_lines(group) {
+                println!("{line}");
+            }
+            let peaks: Vec<String> = group
+                .peak_hours(3)
+                .iter()
+                .map(|hour| format!("{hour:02}:00"))
+                .collect();
+            println!("  Peak hours: {}", peaks.join(", "));
+            let daily: Vec<String> = group
+                .daily
+                .iter()
+                .map(|(date, count)| format!("{} {count}", date.format("%m-%d")))
+                .collect();
+            println!("  Daily: {}", daily.join(", "));
+        }
+        if !group.top_contributors.is_empty() {
+            println!("  Top contributors:");
+            for contributor in &group.top_contributors {
+                println!(
+                    "    {:<20} {:>6}",
+                    contributor.name,
+                    fmt_num(contributor.messages)
+                );
+            }
+        }
+        println!();
+    }
+}
+
+/// Print group activity and engagement as JSON.
+pub fn print_engagement_json(groups: &[GroupEngagement], filter: &StatsFilter) -> Result<()> {
+    let groups: Vec<serde_json::Value> = groups
+        .iter()
+        .map(|group| {
+            let mut value = serde_json::to_value(group)?;
+            value["engagement_ratio"] = serde_json::json!(group.engagement_ratio());
+            value["peak_hours"] = serde_json::json!(group.peak_hours(3));
+            Ok(value)
+        })
+        .collect::<Result<_, serde_json::Error>>()?;
+    let output = serde_json::json!({
+        "start_date": filter.start_date.format("%Y-%m-%d").to_string(),
+        "end_date": filter.end_date.format("%Y-%m-%d").to_string(),
+        "timezone": filter.tz.name(),
+        "groups": groups,
+    });
     println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
 }
 
+/// Usage of one channel/room on one local day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyRoomRow {
+    pub date: NaiveDate,
+    pub channel: String,
+    pub room: String,
+    pub requests: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub cost: f64,
+}
+
+/// The result's records summed per local day and channel/room, by date
+/// and then label.
+pub fn daily_room_rows(result: &StatsResult) -> Vec<DailyRoomRow> {
+    let mut rows: BTreeMap<(NaiveDate, &str, &str), DailyRoomRow> = BTreeMap::new();
+    for r in &result.records {
+        let date = r.usage.timestamp.with_timezone(&result.tz).date_naive();
+        let channel = r.channel.as_deref().unwrap_or("unknown");
+        let room = r.room.as_deref().unwrap_or("default");
+        let row = rows
+            .entry((date, channel, room))
+            .or_insert_with(|| DailyRoomRow {
+                date,
+                channel: channel.to_string(),
+                room: room.to_string(),
+                requests: 0,
+                input_tokens: 0,
+                output_tokens: 0,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                cost: 0.0,
+            });
+        row.requests += 1;
+        row.input_tokens += r.usage.input_tokens;
+        row.output_tokens += r.usage.output_tokens;
+        row.cache_read_tokens += r.usage.cache_read_tokens.unwrap_or(0);
+        row.cache_write_tokens += r.usage.cache_write_tokens.unwrap_or(0);
+        row.cost += r.usage.cost_usd;
+    }
+    rows.into_values().collect()
+}
+
+/// Stats as CSV with a header line and one line per [`daily_room_rows`]
+/// row. Days and rooms without requests have no line.
+pub fn stats_csv(result: &StatsResult) -> String {
+    let mut out = String::from(
+        "date,channel,room,requests,input_tokens,output_tokens,\
+         cache_read_tokens,cache_write_tokens,cost_usd\n",
+    );
+    for row in daily_room_rows(result) {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{:.6}\n",
+            row.date.format("%Y-%m-%d"),
+            csv_field(&row.channel),
+            csv_field(&row.room),
+            row.requests,
+            row.input_tokens,
+            row.output_tokens,
+            row.cache_read_tokens,
+            row.cache_write_tokens,
+            row.cost,
+        ));
+    }
+    out
+}
+
+/// Print stats as CSV, see [`stats_csv`].
+pub fn print_stats_csv(result: &StatsResult) {
+    print!("{}", stats_csv(result));
+}
+
+/// A CSV field, quoted when it holds a comma, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 fn fmt_num(n: u64) -> String {
     if n == 0 {
         return "0".to_string();
@@ -434,7 +1054,7 @@ mod tests {
 
     #[test]
     fn build_filter_defaults_to_today() {
-        let filter = build_filter(None, None, None).unwrap();
+        let filter = build_filter(None, None, None, &StatsCalendar::default()).unwrap();
         let today = Utc::now().date_naive();
         assert_eq!(filter.start_date, today);
         assert_eq!(filter.end_date, today);
@@ -442,7 +1062,8 @@ mod tests {
 
     #[test]
     fn build_filter_specific_date() {
-        let filter = build_filter(Some("2026-01-15"), None, None).unwrap();
+        let filter =
+            build_filter(Some("2026-01-15"), None, None, &StatsCalendar::default()).unwrap();
         assert_eq!(
             filter.start_date,
             NaiveDate::from_ymd_opt(2026, 1, 15).unwrap()
@@ -451,17 +1072,289 @@ mod tests {
 
     #[test]
     fn build_filter_week_period() {
-        let filter = build_filter(None, Some("week"), None).unwrap();
+        let filter = build_filter(None, Some("week"), None, &StatsCalendar::default()).unwrap();
         let today = Utc::now().date_naive();
         assert_eq!(filter.end_date, today);
-        assert_eq!(filter.start_date, today - chrono::Duration::days(6));
+        assert_eq!(filter.start_date.weekday(), Weekday::Mon);
+        assert!(today - filter.start_date < chrono::Duration::days(7));
+    }
+
+    #[test]
+    fn build_filter_week_follows_week_start() {
+        let calendar = StatsCalendar {
+            tz: Tz::UTC,
+            week_start: Weekday::Sun,
+        };
+        // 2026-03-11 is a Wednesday.
+        let filter = build_filter(Some("2026-03-11"), Some("week"), None, &calendar).unwrap();
+        assert_eq!(
+            filter.start_date,
+            NaiveDate::from_ymd_opt(2026, 3, 8).unwrap()
+        );
+        assert_eq!(
+            filter.end_date,
+            NaiveDate::from_ymd_opt(2026, 3, 14).unwrap()
+        );
+    }
+
+    #[test]
+    fn aggregate_uses_local_month_across_dst() {
+        use chrono::TimeZone;
+        let record = |at: DateTime<Utc>| {
+            let mut usage = crate::cost::types::TokenUsage::new("m", 1, 1, 0.0, 0.0);
+            usage.timestamp = at;
+            CostRecord::new("s", usage)
+        };
+        // New York switches to EDT on 2026-03-08.
+        let records = [
+            // Feb 28, 23:30 EST
+            record(Utc.with_ymd_and_hms(2026, 3, 1, 4, 30, 0).unwrap()),
+            // Mar 1, 00:30 EST
+            record(Utc.with_ymd_and_hms(2026, 3, 1, 5, 30, 0).unwrap()),
+            // Mar 31, 23:30 EDT
+            record(Utc.with_ymd_and_hms(2026, 4, 1, 3, 30, 0).unwrap()),
+            // Apr 1, 00:30 EDT
+            record(Utc.with_ymd_and_hms(2026, 4, 1, 4, 30, 0).unwrap()),
+        ];
+        let calendar =
+            StatsCalendar::from_config(&StatsConfig::default(), Some("America/New_York")).unwrap();
+        let filter = build_filter(Some("2026-03-15"), Some("month"), None, &calendar).unwrap();
+        assert_eq!(
+            filter.start_date,
+            NaiveDate::from_ymd_opt(2026, 3, 1).unwrap()
+        );
+        assert_eq!(
+            filter.end_date,
+            NaiveDate::from_ymd_opt(2026, 3, 31).unwrap()
+        );
+        assert_eq!(aggregate(&records, &filter).request_count, 2);
     }
 
     #[test]
     fn aggregate_empty_records() {
-        let filter = build_filter(None, None, None).unwrap();
+        let filter = build_filter(None, None, None, &StatsCalendar::default()).unwrap();
         let result = aggregate(&[], &filter);
         assert_eq!(result.request_count, 0);
         assert_eq!(result.total_cost, 0.0);
     }
+
+    #[test]
+    fn aggregate_groups_by_persona() {
+        let usage = |cost: f64| {
+            let mut usage = crate::cost::types::TokenUsage::new("m", 100, 50, 0.0, 0.0);
+            usage.cost_usd = cost;
+            usage
+        };
+        let mut reviewer = CostRecord::with_context(
+            "s",
+            usage(0.5),
+            Some("nostr".into()),
+            Some("#dev".into()),
+            None,
+        );
+        reviewer.persona = Some("reviewer".into());
+        let mut reviewer_dm = CostRecord::new("s", usage(0.25));
+        reviewer_dm.persona = Some("reviewer".into());
+        let untagged = CostRecord::new("s", usage(1.0));
+
+        let filter = build_filter(None, None, None, &StatsCalendar::default()).unwrap();
+        let result = aggregate(&[reviewer, reviewer_dm, untagged], &filter);
+        assert_eq!(result.request_count, 3);
+        assert_eq!(result.by_persona.len(), 1);
+        assert_eq!(result.by_persona[0].label, "reviewer");
+        assert_eq!(result.by_persona[0].requests, 2);
+        assert!((result.by_persona[0].cost - 0.75).abs() < f64::EPSILON);
+        assert!(result.by_route.is_empty());
+    }
+
+    #[test]
+    fn aggregate_groups_by_route() {
+        let mut ack = CostRecord::new(
+            "s",
+            crate::cost::types::TokenUsage::new("m", 10, 5, 0.0, 0.0),
+        );
+        ack.route = Some("ack -> fast".into());
+        let mut complex = CostRecord::new(
+            "s",
+            crate::cost::types::TokenUsage::new("m", 900, 400, 0.0, 0.0),
+        );
+        complex.route = Some("complex -> reasoning".into());
+        let mut complex_again = complex.clone();
+        complex_again.id = "other".into();
+
+        let filter = build_filter(None, None, None, &StatsCalendar::default()).unwrap();
+        let result = aggregate(&[ack, complex, complex_again], &filter);
+        assert_eq!(result.by_route.len(), 2);
+        let complex = result
+            .by_route
+            .iter()
+            .find(|row| row.label == "complex -> reasoning")
+            .unwrap();
+        assert_eq!(complex.requests, 2);
+        assert_eq!(complex.input_tokens, 1800);
+    }
+
+    #[test]
+    fn aggregate_groups_by_model_and_provider() {
+        let record = |model: &str, cost: f64| {
+            let mut usage = crate::cost::types::TokenUsage::new(model, 10, 5, 0.0, 0.0);
+            usage.cost_usd = cost;
+            CostRecord::new("s", usage)
+        };
+        let records = [
+            record("openrouter/anthropic/claude-sonnet-4", 0.5),
+            record("openrouter/openai/gpt-5", 0.25),
+            record("anthropic/claude-sonnet-4", 1.0),
+        ];
+
+        let filter = build_filter(None, None, None, &StatsCalendar::default()).unwrap();
+        let result = aggregate(&records, &filter);
+        assert_eq!(result.by_model.len(), 3);
+        assert_eq!(result.by_model[0].label, "anthropic/claude-sonnet-4");
+        assert_eq!(result.by_provider.len(), 2);
+        assert_eq!(result.by_provider[0].label, "anthropic");
+        assert_eq!(result.by_provider[1].label, "openrouter");
+        assert_eq!(result.by_provider[1].requests, 2);
+        assert!((result.by_provider[1].cost - 0.75).abs() < f64::EPSILON);
+        assert_eq!(provider_of("bare-model"), "unknown");
+    }
+
+    #[test]
+    fn aggregate_sums_tool_latency() {
+        let record = |calls: &[(&str, u64)]| {
+            let mut record = CostRecord::new(
+                "s",
+                crate::cost::types::TokenUsage::new("m", 10, 5, 0.0, 0.0),
+            );
+            for (tool, ms) in calls {
+                record
+                    .tool_latency
+                    .entry((*tool).to_string())
+                    .or_default()
+                    .record(std::time::Duration::from_millis(*ms));
+            }
+            record
+        };
+        let mut cached = record(&[("web_fetch", 800)]);
+        let web_fetch = cached.tool_latency.get_mut("web_fetch").unwrap();
+        web_fetch.record_cache(false);
+        web_fetch.record_cache(true);
+        let records = [
+            record(&[("web_fetch", 1200), ("file_read", 10)]),
+            cached,
+            record(&[]),
+        ];
+
+        let filter = build_filter(None, None, None, &StatsCalendar::default()).unwrap();
+        let result = aggregate(&records, &filter);
+        assert_eq!(result.by_tool.len(), 2);
+        let web_fetch = &result.by_tool[0];
+        assert_eq!(web_fetch.tool, "web_fetch");
+        assert_eq!((web_fetch.calls, web_fetch.total_ms), (2, 2000));
+        assert_eq!((web_fetch.avg_ms(), web_fetch.max_ms), (1000, 1200));
+        assert_eq!((web_fetch.cache_hits, web_fetch.cache_misses), (1, 1));
+        assert_eq!(result.by_tool[1].tool, "file_read");
+    }
+
+    #[test]
+    fn csv_has_a_row_per_day_and_room() {
+        use chrono::TimeZone;
+        let record = |day: u32, room: &str, cost: f64| {
+            let mut usage = crate::cost::types::TokenUsage::new("m", 100, 50, 0.0, 0.0);
+            usage.timestamp = Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap();
+            usage.cost_usd = cost;
+            CostRecord::with_context("s", usage, Some("nostr".into()), Some(room.into()), None)
+        };
+        let records = [
+            record(3, "#dev", 0.5),
+            record(2, "#ops, east", 0.25),
+            record(2, "#dev", 0.125),
+            record(2, "#dev", 0.125),
+        ];
+
+        let calendar = StatsCalendar::default();
+        let filter = build_filter(Some("2026-03-01"), Some("month"), None, &calendar).unwrap();
+        let csv = stats_csv(&aggregate(&records, &filter));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "date,channel,room,requests,input_tokens,output_tokens,\
+                 cache_read_tokens,cache_write_tokens,cost_usd",
+                "2026-03-02,nostr,#dev,2,200,100,0,0,0.250000",
+                "2026-03-02,nostr,\"#ops, east\",1,100,50,0,0,0.250000",
+                "2026-03-03,nostr,#dev,1,100,50,0,0,0.500000",
+            ]
+        );
+
+        let filter = StatsFilter {
+            room: Some("#dev".into()),
+            ..filter
+        };
+        assert_eq!(stats_csv(&aggregate(&records, &filter)).lines().count(), 3);
+    }
+
+    #[test]
+    fn aggregate_counts_cache_hits_and_savings() {
+        let record = |model: &str, cost: f64, message_type: &str| {
+            let mut usage = crate::cost::types::TokenUsage::new(model, 0, 0, 0.0, 0.0);
+            usage.cost_usd = cost;
+            CostRecord::with_context("s", usage, None, None, Some(message_type.into()))
+        };
+        let records = [
+            record("m", 0.25, "user_message"),
+            record("m", 0.75, "user_message"),
+            record("cache/answer", 0.0, "cache_hit"),
+            record("cache/semantic", 0.0, "cache_hit"),
+            record("cache/semantic", 0.0, "cache_hit"),
+        ];
+
+        let filter = build_filter(None, None, None, &StatsCalendar::default()).unwrap();
+        let result = aggregate(&records, &filter);
+        assert_eq!(result.cache_hits.exact, 1);
+        assert_eq!(result.cache_hits.semantic, 2);
+        assert!((result.cache_hits.estimated_savings - 1.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn engagement_filter_spans_local_days() {
+        let tz: Tz = "Europe/Berlin".parse().unwrap();
+        let filter = StatsFilter {
+            start_date: NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2026, 3, 3).unwrap(),
+            room: Some("#dev".into()),
+            tz,
+        };
+        let range = engagement_filter(&filter);
+        // 2026-03-01 23:00 UTC to 2026-03-03 22:59:59 UTC
+        assert_eq!(range.since, 1_772_406_000);
+        assert_eq!(range.until, 1_772_578_799);
+        assert_eq!(range.group.as_deref(), Some("dev"));
+    }
+
+    #[test]
+    fn heatmap_shades_relative_to_the_busiest_hour() {
+        let mut heatmap = [[0; 24]; 7];
+        heatmap[0][9] = 12;
+        heatmap[0][10] = 1;
+        heatmap[6][23] = 6;
+        let group = GroupEngagement {
+            group_id: "dev".into(),
+            messages: 19,
+            heatmap,
+            daily: BTreeMap::new(),
+            top_contributors: Vec::new(),
+            seen: 0,
+            answered: 0,
+        };
+        let lines = heatmap_lines(&group);
+        assert_eq!(lines.len(), 8);
+        assert!(lines[0].starts_with("      0     6     12    18"));
+        assert_eq!(
+            lines[1],
+            format!("  Mon |{}█░{}| 13", " ".repeat(9), " ".repeat(13))
+        );
+        assert_eq!(lines[7], format!("  Sun |{}▒| 6", " ".repeat(23)));
+        assert_eq!(lines[2], format!("  Tue |{}| 0", " ".repeat(24)));
+    }
 }
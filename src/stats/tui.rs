@@ -1,6 +1,11 @@
-use super::{aggregate, build_filter, costs_jsonl_path, read_records, StatsResult};
+use super::{
+    aggregate, build_filter, costs_jsonl_path, read_records, StatsCalendar, StatsFilter,
+    StatsResult,
+};
+use crate::cost::types::CostRecord;
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{Months, NaiveDate};
+use chrono_tz::Tz;
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -10,16 +15,95 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table},
     Frame, Terminal,
 };
 use std::io::stdout;
 use std::path::Path;
 use std::time::{Duration, SystemTime};
 
-/// Run the live TUI dashboard.
-pub fn run(workspace_dir: &Path) -> Result<()> {
+/// Aggregation period the dashboard steps through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    fn parse(period: Option<&str>) -> Self {
+        match period {
+            Some("week") => Self::Week,
+            Some("month") => Self::Month,
+            _ => Self::Day,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Day => Self::Week,
+            Self::Week => Self::Month,
+            Self::Month => Self::Day,
+        }
+    }
+
+    /// `date` moved `steps` periods forward (or back when negative).
+    fn shift(self, date: NaiveDate, steps: i32) -> NaiveDate {
+        let months = Months::new(steps.unsigned_abs());
+        let shifted = match self {
+            Self::Day => date.checked_add_signed(chrono::Duration::days(i64::from(steps))),
+            Self::Week => date.checked_add_signed(chrono::Duration::weeks(i64::from(steps))),
+            Self::Month if steps < 0 => date.checked_sub_months(months),
+            Self::Month => date.checked_add_months(months),
+        };
+        shifted.unwrap_or(date)
+    }
+}
+
+/// Interactive filters: the period containing `anchor`, and one room or all.
+struct Filters {
+    anchor: NaiveDate,
+    period: Period,
+    room: Option<String>,
+    /// Highlighted line of the open room picker (0 = all rooms).
+    picker: Option<usize>,
+}
+
+impl Filters {
+    fn stats_filter(&self, calendar: &StatsCalendar) -> Result<StatsFilter> {
+        let anchor = self.anchor.format("%Y-%m-%d").to_string();
+        build_filter(
+            Some(&anchor),
+            Some(self.period.as_str()),
+            self.room.clone(),
+            calendar,
+        )
+    }
+}
+
+/// Run the live TUI dashboard, starting with `filter` (the `--date`,
+/// `--period` and `--room` flags); `period` is the flag as given.
+pub fn run(
+    workspace_dir: &Path,
+    calendar: &StatsCalendar,
+    filter: &StatsFilter,
+    period: Option<&str>,
+) -> Result<()> {
     let jsonl_path = costs_jsonl_path(workspace_dir);
+    let filters = Filters {
+        anchor: filter.end_date,
+        period: Period::parse(period),
+        room: filter.room.clone(),
+        picker: None,
+    };
 
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
@@ -27,7 +111,7 @@ pub fn run(workspace_dir: &Path) -> Result<()> {
     let backend = ratatui::backend::CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
 
-    let result = run_loop(&mut terminal, &jsonl_path);
+    let result = run_loop(&mut terminal, &jsonl_path, calendar, filters);
 
     disable_raw_mode()?;
     stdout().execute(LeaveAlternateScreen)?;
@@ -38,21 +122,27 @@ pub fn run(workspace_dir: &Path) -> Result<()> {
 fn run_loop(
     terminal: &mut Terminal<ratatui::backend::CrosstermBackend<std::io::Stdout>>,
     jsonl_path: &Path,
+    calendar: &StatsCalendar,
+    mut filters: Filters,
 ) -> Result<()> {
     let mut last_mtime = file_mtime(jsonl_path);
     let mut records = read_records(jsonl_path).unwrap_or_default();
-    let mut stats = compute_today_stats(&records);
+    let mut rooms = room_names(&records);
+    let mut stats = aggregate(&records, &filters.stats_filter(calendar)?);
     let poll_interval = Duration::from_secs(1);
 
     loop {
-        terminal.draw(|frame| draw(frame, &stats, &records))?;
+        terminal.draw(|frame| draw(frame, &stats, &filters, &rooms, calendar))?;
 
+        let mut changed = false;
         if event::poll(poll_interval)? {
             if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press
-                    && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
-                {
-                    return Ok(());
+                if key.kind == KeyEventKind::Press {
+                    match handle_key(&mut filters, &rooms, key.code, calendar) {
+                        KeyOutcome::Quit => return Ok(()),
+                        KeyOutcome::Refilter => changed = true,
+                        KeyOutcome::Redraw => {}
+                    }
                 }
             }
         }
@@ -62,21 +152,94 @@ fn run_loop(
         if new_mtime != last_mtime {
             last_mtime = new_mtime;
             records = read_records(jsonl_path).unwrap_or_default();
-            stats = compute_today_stats(&records);
+            rooms = room_names(&records);
+            changed = true;
+        }
+        if changed {
+            stats = aggregate(&records, &filters.stats_filter(calendar)?);
+        }
+    }
+}
+
+enum KeyOutcome {
+    Quit,
+    /// The filters changed; aggregate again.
+    Refilter,
+    Redraw,
+}
+
+/// Apply a key press: `←`/`→` step through periods, `p` cycles day, week
+/// and month, `t` returns to today and `r` opens the room picker, where
+/// `↑`/`↓` and `Enter` choose a room.
+fn handle_key(
+    filters: &mut Filters,
+    rooms: &[String],
+    code: KeyCode,
+    calendar: &StatsCalendar,
+) -> KeyOutcome {
+    if let Some(selected) = filters.picker {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                filters.picker = Some(selected.saturating_sub(1));
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                filters.picker = Some((selected + 1).min(rooms.len()));
+            }
+            KeyCode::Enter => {
+                filters.room = selected.checked_sub(1).and_then(|i| rooms.get(i)).cloned();
+                filters.picker = None;
+                return KeyOutcome::Refilter;
+            }
+            KeyCode::Esc | KeyCode::Char('r') => filters.picker = None,
+            KeyCode::Char('q') => return KeyOutcome::Quit,
+            _ => {}
+        }
+        return KeyOutcome::Redraw;
+    }
+
+    match code {
+        KeyCode::Char('q') | KeyCode::Esc => return KeyOutcome::Quit,
+        KeyCode::Left | KeyCode::Char('h') => {
+            filters.anchor = filters.period.shift(filters.anchor, -1);
+        }
+        KeyCode::Right | KeyCode::Char('l') => {
+            filters.anchor = filters.period.shift(filters.anchor, 1);
+        }
+        KeyCode::Char('p') => filters.period = filters.period.next(),
+        KeyCode::Char('t') => filters.anchor = calendar.today(),
+        KeyCode::Char('r') => {
+            let current = filters
+                .room
+                .as_ref()
+                .and_then(|room| rooms.iter().position(|r| r == room))
+                .map_or(0, |i| i + 1);
+            filters.picker = Some(current);
+            return KeyOutcome::Redraw;
         }
+        _ => return KeyOutcome::Redraw,
     }
+    KeyOutcome::Refilter
 }
 
-fn compute_today_stats(records: &[crate::cost::types::CostRecord]) -> StatsResult {
-    let filter = build_filter(None, None, None).unwrap();
-    aggregate(records, &filter)
+/// Distinct rooms of `records`, sorted.
+fn room_names(records: &[CostRecord]) -> Vec<String> {
+    let mut rooms: Vec<String> = records.iter().filter_map(|r| r.room.clone()).collect();
+    rooms.sort();
+    rooms.dedup();
+    rooms
 }
 
 fn file_mtime(path: &Path) -> Option<SystemTime> {
     std::fs::metadata(path).ok()?.modified().ok()
 }
 
-fn draw(frame: &mut Frame, stats: &StatsResult, records: &[crate::cost::types::CostRecord]) {
+fn draw(
+    frame: &mut Frame,
+    stats: &StatsResult,
+    filters: &Filters,
+    rooms: &[String],
+    calendar: &StatsCalendar,
+) {
     let area = frame.area();
 
     // Main vertical layout
@@ -90,16 +253,43 @@ fn draw(frame: &mut Frame, stats: &StatsResult, records: &[crate::cost::types::C
         ])
         .split(area);
 
-    draw_header(frame, chunks[0], stats);
-    draw_recent_requests(frame, chunks[1], records);
+    draw_header(frame, chunks[0], stats, filters, calendar);
+    draw_recent_requests(frame, chunks[1], &stats.records, calendar.tz);
     draw_bottom_section(frame, chunks[2], stats);
-    draw_footer(frame, chunks[3], stats, records);
+    draw_footer(frame, chunks[3], stats);
+    if let Some(selected) = filters.picker {
+        draw_room_picker(frame, area, rooms, selected);
+    }
 }
 
-fn draw_header(frame: &mut Frame, area: Rect, stats: &StatsResult) {
-    let today = Utc::now().format("%Y-%m-%d");
+fn draw_header(
+    frame: &mut Frame,
+    area: Rect,
+    stats: &StatsResult,
+    filters: &Filters,
+    calendar: &StatsCalendar,
+) {
+    let range = if stats.start_date == stats.end_date {
+        stats.start_date.format("%Y-%m-%d").to_string()
+    } else {
+        format!(
+            "{} to {}",
+            stats.start_date.format("%Y-%m-%d"),
+            stats.end_date.format("%Y-%m-%d")
+        )
+    };
+    let room = filters
+        .room
+        .as_deref()
+        .map(|room| format!(" · {room}"))
+        .unwrap_or_default();
+    let label = if stats.start_date == stats.end_date && stats.end_date == calendar.today() {
+        "Today"
+    } else {
+        "Total"
+    };
     let header_text = format!(
-        " Today: {} in / {} out / ${:.2}    Requests: {}    Cache: {} read / {} write",
+        " {label}: {} in / {} out / ${:.2}    Requests: {}    Cache: {} read / {} write",
         fmt_num(stats.total_input),
         fmt_num(stats.total_output),
         stats.total_cost,
@@ -109,7 +299,7 @@ fn draw_header(frame: &mut Frame, area: Rect, stats: &StatsResult) {
     );
 
     let block = Block::default()
-        .title(format!(" Snowclaw Token Monitor — {today} "))
+        .title(format!(" Snowclaw Token Monitor — {range}{room} "))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan));
 
@@ -122,12 +312,9 @@ fn draw_header(frame: &mut Frame, area: Rect, stats: &StatsResult) {
     frame.render_widget(paragraph, area);
 }
 
-fn draw_recent_requests(frame: &mut Frame, area: Rect, records: &[crate::cost::types::CostRecord]) {
-    let today = Utc::now().date_naive();
-    let mut today_records: Vec<&crate::cost::types::CostRecord> = records
-        .iter()
-        .filter(|r| r.usage.timestamp.naive_utc().date() == today)
-        .collect();
+/// `records` are the filtered ones, as aggregated into the stats.
+fn draw_recent_requests(frame: &mut Frame, area: Rect, records: &[CostRecord], tz: Tz) {
+    let mut today_records: Vec<&CostRecord> = records.iter().collect();
     today_records.sort_by(|a, b| b.usage.timestamp.cmp(&a.usage.timestamp));
     today_records.truncate(20);
 
@@ -151,7 +338,7 @@ fn draw_recent_requests(frame: &mut Frame, area: Rect, records: &[crate::cost::t
             let time = r
                 .usage
                 .timestamp
-                .with_timezone(&chrono::Local)
+                .with_timezone(&tz)
                 .format("%H:%M")
                 .to_string();
             let channel = r.channel.as_deref().unwrap_or("?");
@@ -243,7 +430,7 @@ fn draw_breakdown(frame: &mut Frame, area: Rect, stats: &StatsResult) {
 
 fn draw_rooms(frame: &mut Frame, area: Rect, stats: &StatsResult) {
     let block = Block::default()
-        .title(" By Room (today) ")
+        .title(" By Room ")
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::DarkGray));
 
@@ -271,17 +458,8 @@ fn draw_rooms(frame: &mut Frame, area: Rect, stats: &StatsResult) {
     frame.render_widget(paragraph, area);
 }
 
-fn draw_footer(
-    frame: &mut Frame,
-    area: Rect,
-    stats: &StatsResult,
-    records: &[crate::cost::types::CostRecord],
-) {
-    let today = Utc::now().date_naive();
-    let today_records: Vec<&crate::cost::types::CostRecord> = records
-        .iter()
-        .filter(|r| r.usage.timestamp.naive_utc().date() == today)
-        .collect();
+fn draw_footer(frame: &mut Frame, area: Rect, stats: &StatsResult) {
+    let today_records = &stats.records;
 
     // Rate calc: requests per hour
     let rate = if today_records.len() >= 2 {
@@ -313,7 +491,8 @@ fn draw_footer(
     };
 
     let footer_text = format!(
-        " Rate: {:.1} req/hr   Cache hit: {:.0}%   Press 'q' to quit",
+        " Rate: {:.1} req/hr   Cache hit: {:.0}%   ←/→ period  p day/week/month  t today  \
+         r room  q quit",
         rate, cache_ratio
     );
 
@@ -330,6 +509,53 @@ fn draw_footer(
     frame.render_widget(paragraph, area);
 }
 
+/// Popup listing "All rooms" and every room; `selected` is highlighted.
+fn draw_room_picker(frame: &mut Frame, area: Rect, rooms: &[String], selected: usize) {
+    let height = (rooms.len() as u16 + 3)
+        .min(area.height.saturating_sub(2))
+        .max(3);
+    let width = rooms
+        .iter()
+        .map(|r| r.chars().count() as u16 + 6)
+        .max()
+        .unwrap_or(0)
+        .clamp(24, area.width.saturating_sub(4).max(24));
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width: width.min(area.width),
+        height,
+    };
+
+    // Keep the highlighted line in view.
+    let visible = usize::from(height.saturating_sub(2));
+    let skip = (selected + 1).saturating_sub(visible);
+    let lines: Vec<Line> = std::iter::once("All rooms")
+        .chain(rooms.iter().map(String::as_str))
+        .enumerate()
+        .skip(skip)
+        .take(visible)
+        .map(|(i, name)| {
+            let style = if i == selected {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::from(Span::styled(format!(" {name} "), style))
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(" Room (Enter selects, Esc closes) ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
 fn cost_color(cost: f64) -> Color {
     if cost > 0.10 {
         Color::Red
@@ -1,18 +1,25 @@
 //! CLI subcommands for Nostr task management.
 //!
-//! Provides `snowclaw task create`, `task list`, `task status`, and `task update`
-//! using the local task store (nostr_tasks.json in workspace).
-//! Tasks use sequential SNOW-N IDs (e.g. SNOW-1, SNOW-2).
+//! Provides `snowclaw tasks add`, `list`, `status`, `update` and `done` on
+//! the task board in `social.db` (see [`crate::memory::task_board`]). A
+//! running Nostr channel publishes the changes as kind 1621 and 1630-1637
+//! events. Tasks use sequential SNOW-N IDs (e.g. SNOW-1, SNOW-2).
+
+use std::path::Path;
 
 use anyhow::{bail, Result};
 use clap::Subcommand;
+use rusqlite::Connection;
 
 use crate::config::Config;
+use crate::memory::social;
+use crate::memory::task_board::{self, NewTask, TaskChange, TaskFilter};
 
 #[derive(Subcommand, Debug)]
 pub enum TaskCommands {
-    /// Create a new task
-    Create {
+    /// Add a new task
+    #[command(alias = "create")]
+    Add {
         /// Task title
         title: String,
         /// Task description
@@ -21,289 +28,248 @@ pub enum TaskCommands {
         /// Initial status (default: draft)
         #[arg(short, long, default_value = "draft")]
         status: String,
+        /// Assignee: npub, hex pubkey or contact name (repeatable)
+        #[arg(short, long = "assignee")]
+        assignees: Vec<String>,
+        /// Due date (YYYY-MM-DD)
+        #[arg(long)]
+        due: Option<String>,
+        /// Group the task belongs to
+        #[arg(short, long)]
+        group: Option<String>,
     },
-    /// List tasks
+    /// List open tasks
     List {
         /// Filter by status
         #[arg(short, long)]
         status: Option<String>,
+        /// Filter by group
+        #[arg(short, long)]
+        group: Option<String>,
+        /// Filter by assignee
+        #[arg(short, long)]
+        assignee: Option<String>,
+        /// Include done, cancelled and failed tasks
+        #[arg(long)]
+        all: bool,
     },
     /// Show task status and history
+    #[command(alias = "show")]
     Status {
         /// Task ID (e.g. SNOW-1 or just 1)
         id: String,
     },
-    /// Update a task's status
+    /// Update a task's status, assignees or due date
     Update {
         /// Task ID (e.g. SNOW-1 or just 1)
         id: String,
         /// New status: draft, queued, executing, blocked, review, done, failed, cancelled
         #[arg(short, long)]
-        status: String,
+        status: Option<String>,
+        /// Optional detail/note
+        #[arg(short, long)]
+        detail: Option<String>,
+        /// Replace the assignees (repeatable)
+        #[arg(short, long = "assignee")]
+        assignees: Vec<String>,
+        /// New due date (YYYY-MM-DD, or "none" to clear it)
+        #[arg(long)]
+        due: Option<String>,
+    },
+    /// Mark a task done
+    Done {
+        /// Task ID (e.g. SNOW-1 or just 1)
+        id: String,
         /// Optional detail/note
         #[arg(short, long)]
         detail: Option<String>,
     },
 }
 
-/// Resolve the task store path from config.
-fn task_store_path(config: &Config) -> std::path::PathBuf {
-    config.workspace_dir.join("nostr_tasks.json")
+fn open_board(config: &Config) -> Result<Connection> {
+    let config_dir = config.config_path.parent().unwrap_or(Path::new("."));
+    task_board::open(config_dir, &config.workspace_dir)
 }
 
-/// Load tasks from the JSON store.
-fn load_tasks(config: &Config) -> Result<Vec<serde_json::Value>> {
-    let path = task_store_path(config);
-    if !path.exists() {
-        return Ok(Vec::new());
+fn resolve_assignees(conn: &Connection, inputs: &[String]) -> Result<Vec<String>> {
+    let mut hexes = Vec::new();
+    for input in inputs {
+        let hex = task_board::resolve_assignee(conn, input)?;
+        if !hexes.contains(&hex) {
+            hexes.push(hex);
+        }
     }
-    let data = std::fs::read_to_string(&path)?;
-    let tasks: Vec<serde_json::Value> = serde_json::from_str(&data)?;
-    Ok(tasks)
+    Ok(hexes)
 }
 
-/// Save tasks to the JSON store.
-fn save_tasks(config: &Config, tasks: &[serde_json::Value]) -> Result<()> {
-    let path = task_store_path(config);
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
+fn parse_due_arg(due: Option<&str>) -> Result<Option<Option<i64>>> {
+    match due.map(str::trim) {
+        None => Ok(None),
+        Some("" | "none") => Ok(Some(None)),
+        Some(due) => Ok(Some(Some(task_board::parse_due(due)?))),
     }
-    let json = serde_json::to_string_pretty(tasks)?;
-    std::fs::write(&path, json)?;
-    Ok(())
 }
 
-/// Map status string to Nostr event kind.
-pub fn status_to_kind(status: &str) -> Result<u16> {
-    match status {
-        "queued" => Ok(1630),
-        "done" => Ok(1631),
-        "cancelled" => Ok(1632),
-        "draft" => Ok(1633),
-        "executing" => Ok(1634),
-        "blocked" => Ok(1635),
-        "review" => Ok(1636),
-        "failed" => Ok(1637),
-        other => bail!("Unknown status: {other}. Valid: draft, queued, executing, blocked, review, done, failed, cancelled"),
+fn task_number(id: &str) -> Result<u64> {
+    match task_board::parse_task_number(id) {
+        Some(number) => Ok(number),
+        None => bail!("Invalid task id: {id} (expected SNOW-N or N)"),
     }
 }
 
-/// Extract the numeric part from a SNOW-N task ID.
-fn parse_snow_number(id: &str) -> Option<u64> {
-    id.strip_prefix("SNOW-").and_then(|n| n.parse::<u64>().ok())
+fn format_time(ts: i64) -> String {
+    chrono::DateTime::from_timestamp(ts, 0).map_or_else(
+        || "?".into(),
+        |t| t.format("%Y-%m-%d %H:%M UTC").to_string(),
+    )
 }
 
-/// Derive the next SNOW-N number from existing tasks.
-fn next_task_number(tasks: &[serde_json::Value]) -> u64 {
-    let max = tasks
-        .iter()
-        .filter_map(|t| t.get("id").and_then(|v| v.as_str()))
-        .filter_map(parse_snow_number)
-        .max()
-        .unwrap_or(0);
-    max + 1
-}
-
-/// Normalize a user-provided task ID to SNOW-N format.
-/// Accepts "SNOW-1", "snow-1", or just "1".
-fn normalize_task_id(input: &str) -> String {
-    let trimmed = input.trim();
-    if let Ok(n) = trimmed.parse::<u64>() {
-        return format!("SNOW-{n}");
-    }
-    if let Some(n) = trimmed
-        .to_uppercase()
-        .strip_prefix("SNOW-")
-        .and_then(|s| s.parse::<u64>().ok())
-    {
-        return format!("SNOW-{n}");
+fn apply_change(conn: &Connection, id: &str, change: &TaskChange) -> Result<()> {
+    let number = task_number(id)?;
+    match task_board::update_task(conn, number, change, chrono::Utc::now().timestamp())? {
+        Some(task) => {
+            println!("Updated {}", task_board::format_task_line(conn, &task));
+            Ok(())
+        }
+        None => bail!("Task not found: SNOW-{number}"),
     }
-    // Legacy: support old task-{timestamp} IDs
-    trimmed.to_string()
-}
-
-/// Find a task by normalized ID.
-fn find_task<'a>(tasks: &'a [serde_json::Value], id: &str) -> Option<&'a serde_json::Value> {
-    let normalized = normalize_task_id(id);
-    tasks
-        .iter()
-        .find(|t| t.get("id").and_then(|v| v.as_str()) == Some(&normalized))
-}
-
-/// Find a task by normalized ID (mutable).
-fn find_task_mut<'a>(
-    tasks: &'a mut [serde_json::Value],
-    id: &str,
-) -> Option<&'a mut serde_json::Value> {
-    let normalized = normalize_task_id(id);
-    tasks
-        .iter_mut()
-        .find(|t| t.get("id").and_then(|v| v.as_str()) == Some(&normalized))
 }
 
 pub fn handle_command(cmd: TaskCommands, config: &Config) -> Result<()> {
+    let conn = open_board(config)?;
     match cmd {
-        TaskCommands::Create {
+        TaskCommands::Add {
             title,
             description,
             status,
+            assignees,
+            due,
+            group,
         } => {
-            let _kind = status_to_kind(&status)?;
-
-            let mut tasks = load_tasks(config)?;
-            let number = next_task_number(&tasks);
-            let task_id = format!("SNOW-{number}");
-            let now = chrono::Utc::now().to_rfc3339();
-
-            let task = serde_json::json!({
-                "id": task_id,
-                "title": title,
-                "description": description.unwrap_or_default(),
-                "status": status,
-                "created_at": now,
-                "updated_at": now,
-                "kind": 1621,
-                "status_history": [{
-                    "status": status,
-                    "kind": _kind,
-                    "timestamp": now,
-                }]
-            });
-
-            tasks.push(task);
-            save_tasks(config, &tasks)?;
-
-            println!("Created {task_id}: {title}");
+            let task = NewTask {
+                title,
+                description: description.unwrap_or_default(),
+                status: Some(status),
+                group_id: group.map(|g| g.trim_start_matches('#').to_string()),
+                assignees: resolve_assignees(&conn, &assignees)?,
+                due_at: parse_due_arg(due.as_deref())?.flatten(),
+            };
+            let task = task_board::add_task(&conn, &task, chrono::Utc::now().timestamp())?;
+            println!("Created {}", task_board::format_task_line(&conn, &task));
             Ok(())
         }
 
-        TaskCommands::List { status } => {
-            let tasks = load_tasks(config)?;
-            let filtered: Vec<&serde_json::Value> = if let Some(ref s) = status {
-                tasks
-                    .iter()
-                    .filter(|t| t.get("status").and_then(|v| v.as_str()) == Some(s))
-                    .collect()
-            } else {
-                tasks.iter().collect()
+        TaskCommands::List {
+            status,
+            group,
+            assignee,
+            all,
+        } => {
+            let filter = TaskFilter {
+                status,
+                group_id: group.map(|g| g.trim_start_matches('#').to_string()),
+                assignee: assignee
+                    .map(|a| task_board::resolve_assignee(&conn, &a))
+                    .transpose()?,
+                include_closed: all,
             };
-
-            if filtered.is_empty() {
+            let tasks = task_board::list_tasks(&conn, &filter)?;
+            if tasks.is_empty() {
                 println!("No tasks found.");
                 return Ok(());
             }
 
-            println!("{} task(s):", filtered.len());
-            for task in &filtered {
-                let id = task.get("id").and_then(|v| v.as_str()).unwrap_or("?");
-                let title = task.get("title").and_then(|v| v.as_str()).unwrap_or("?");
-                let st = task.get("status").and_then(|v| v.as_str()).unwrap_or("?");
-                let updated = task
-                    .get("updated_at")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("?");
-                println!("  [{st}] {id}: {title} (updated: {updated})");
+            println!("{} task(s):", tasks.len());
+            for task in &tasks {
+                println!("  {}", task_board::format_task_line(&conn, task));
             }
-
             Ok(())
         }
 
         TaskCommands::Status { id } => {
-            let tasks = load_tasks(config)?;
-            let task = find_task(&tasks, &id);
-
-            match task {
-                Some(task) => {
-                    let task_id = task.get("id").and_then(|v| v.as_str()).unwrap_or("?");
-                    let title = task.get("title").and_then(|v| v.as_str()).unwrap_or("?");
-                    let status = task.get("status").and_then(|v| v.as_str()).unwrap_or("?");
-                    let desc = task
-                        .get("description")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("");
-                    let created = task
-                        .get("created_at")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("?");
-                    let updated = task
-                        .get("updated_at")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("?");
+            let number = task_number(&id)?;
+            let Some(task) = task_board::get_task(&conn, number)? else {
+                println!("Task not found: SNOW-{number}");
+                return Ok(());
+            };
 
-                    println!("Task: {task_id}");
-                    println!("  Title:   {title}");
-                    println!("  Status:  {status}");
-                    if !desc.is_empty() {
-                        println!("  Desc:    {desc}");
-                    }
-                    println!("  Created: {created}");
-                    println!("  Updated: {updated}");
+            println!("Task: {}", task.id());
+            println!("  Title:     {}", task.title);
+            println!("  Status:    {}", task.status);
+            if !task.description.is_empty() {
+                println!("  Desc:      {}", task.description);
+            }
+            if let Some(group) = &task.group_id {
+                println!("  Group:     #{group}");
+            }
+            if !task.assignees.is_empty() {
+                let names: Vec<String> = task
+                    .assignees
+                    .iter()
+                    .map(|hex| social::display_name_or_short(&conn, hex))
+                    .collect();
+                println!("  Assignees: {}", names.join(", "));
+            }
+            if let Some(due) = task.due_at {
+                println!("  Due:       {}", task_board::format_due(due));
+            }
+            println!("  Created:   {}", format_time(task.created_at));
+            println!("  Updated:   {}", format_time(task.updated_at));
+            println!(
+                "  Event:     {}",
+                task.event_id.as_deref().unwrap_or("(not yet published)")
+            );
 
-                    if let Some(history) = task.get("status_history").and_then(|v| v.as_array()) {
-                        if !history.is_empty() {
-                            println!("  History:");
-                            for entry in history {
-                                let st =
-                                    entry.get("status").and_then(|v| v.as_str()).unwrap_or("?");
-                                let ts = entry
-                                    .get("timestamp")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("?");
-                                let detail =
-                                    entry.get("detail").and_then(|v| v.as_str()).unwrap_or("");
-                                if detail.is_empty() {
-                                    println!("    {ts}: {st}");
-                                } else {
-                                    println!("    {ts}: {st} — {detail}");
-                                }
-                            }
-                        }
+            let history = task_board::task_history(&conn, number)?;
+            if !history.is_empty() {
+                println!("  History:");
+                for update in history {
+                    let by = update
+                        .author
+                        .as_deref()
+                        .map(|hex| format!(" by {}", social::display_name_or_short(&conn, hex)))
+                        .unwrap_or_default();
+                    let when = format_time(update.created_at);
+                    if update.detail.is_empty() {
+                        println!("    {when}: {}{by}", update.status);
+                    } else {
+                        println!("    {when}: {}{by} — {}", update.status, update.detail);
                     }
-                    Ok(())
-                }
-                None => {
-                    let normalized = normalize_task_id(&id);
-                    println!("Task not found: {normalized}");
-                    Ok(())
                 }
             }
+            Ok(())
         }
 
-        TaskCommands::Update { id, status, detail } => {
-            let kind = status_to_kind(&status)?;
-            let mut tasks = load_tasks(config)?;
-            let now = chrono::Utc::now().to_rfc3339();
-
-            let task = find_task_mut(&mut tasks, &id);
-
-            match task {
-                Some(task) => {
-                    let task_id = task
-                        .get("id")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("?")
-                        .to_string();
-                    task["status"] = serde_json::json!(status);
-                    task["updated_at"] = serde_json::json!(now);
-                    if let Some(history) = task.get_mut("status_history") {
-                        if let Some(arr) = history.as_array_mut() {
-                            arr.push(serde_json::json!({
-                                "status": status,
-                                "kind": kind,
-                                "detail": detail.as_deref().unwrap_or(""),
-                                "timestamp": now,
-                            }));
-                        }
-                    }
-                    save_tasks(config, &tasks)?;
-                    println!("Updated {task_id} to {status}");
-                    Ok(())
-                }
-                None => {
-                    let normalized = normalize_task_id(&id);
-                    bail!("Task not found: {normalized}")
-                }
+        TaskCommands::Update {
+            id,
+            status,
+            detail,
+            assignees,
+            due,
+        } => {
+            let change = TaskChange {
+                status,
+                detail: detail.unwrap_or_default(),
+                assignees: if assignees.is_empty() {
+                    None
+                } else {
+                    Some(resolve_assignees(&conn, &assignees)?)
+                },
+                due_at: parse_due_arg(due.as_deref())?,
+            };
+            if change.status.is_none() && change.assignees.is_none() && change.due_at.is_none() {
+                bail!("Nothing to update: pass --status, --assignee or --due");
             }
+            apply_change(&conn, &id, &change)
+        }
+
+        TaskCommands::Done { id, detail } => {
+            let change = TaskChange {
+                status: Some("done".into()),
+                detail: detail.unwrap_or_default(),
+                ..TaskChange::default()
+            };
+            apply_change(&conn, &id, &change)
         }
     }
 }
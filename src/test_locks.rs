@@ -2,3 +2,6 @@ use parking_lot::{const_mutex, Mutex};
 
 // Serialize tests that mutate process-global plugin runtime state.
 pub(crate) static PLUGIN_RUNTIME_LOCK: Mutex<()> = const_mutex(());
+
+// Serialize tests that toggle the process-global seed mode.
+pub(crate) static DETERMINISM_LOCK: Mutex<()> = const_mutex(());
@@ -85,10 +85,10 @@ impl AgentLessonTool {
             let _ = std::fs::create_dir_all(parent);
         }
 
-        let conn = match Connection::open(db_path) {
+        let conn = match crate::security::db_encryption::open(db_path) {
             Ok(c) => c,
             Err(e) => {
-                warn!("Failed to open social.db for agent lessons: {e}");
+                warn!("Failed to open social.db for agent lessons: {e:#}");
                 return None;
             }
         };
@@ -64,6 +64,10 @@ impl Tool for ApplyPatchTool {
         Self::schema()
     }
 
+    fn is_parallel_safe(&self) -> bool {
+        false
+    }
+
     async fn execute(&self, args: serde_json::Value) -> Result<ToolResult> {
         let patch = args
             .get("patch")
@@ -30,6 +30,7 @@ use tokio::sync::Mutex;
 use tokio::time::{timeout, Duration};
 
 use super::traits::{Tool, ToolResult};
+use crate::security::execution_policy;
 
 /// Hard timeout for background tool execution (seconds).
 const BG_TOOL_TIMEOUT_SECS: u64 = 600;
@@ -312,6 +313,23 @@ impl Tool for BgRunTool {
             });
         }
 
+        // The dispatcher only checked `bg_run` itself; hold the inner call to
+        // the same execution policy and keep it applied in the spawned task.
+        let policy = execution_policy::current();
+        if let Some(policy) = policy.as_deref() {
+            if let Err(reason) = policy.check(tool_name, &arguments) {
+                policy.record_violation(tool_name, &reason);
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!(
+                        "Blocked by execution policy `{}`: {reason}",
+                        policy.name()
+                    )),
+                });
+            }
+        }
+
         // Enforce concurrent job limit to prevent resource exhaustion
         let running_count = self.job_store.running_count().await;
         if running_count >= MAX_CONCURRENT_JOBS {
@@ -349,9 +367,12 @@ impl Tool for BgRunTool {
 
         // Spawn background execution
         tokio::spawn(async move {
-            let result = timeout(
-                Duration::from_secs(BG_TOOL_TIMEOUT_SECS),
-                tool.execute(arguments),
+            let result = execution_policy::scope(
+                policy,
+                timeout(
+                    Duration::from_secs(BG_TOOL_TIMEOUT_SECS),
+                    tool.execute(arguments),
+                ),
             )
             .await;
 
@@ -657,6 +678,63 @@ mod tests {
         assert!(drained2.is_empty());
     }
 
+    struct PathTool;
+
+    #[async_trait]
+    impl Tool for PathTool {
+        fn name(&self) -> &str {
+            "file_read"
+        }
+
+        fn description(&self) -> &str {
+            "reads a path"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        async fn execute(&self, _args: serde_json::Value) -> anyhow::Result<ToolResult> {
+            Ok(ToolResult {
+                success: true,
+                output: "read".to_string(),
+                error: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn execution_policy_applies_to_the_inner_call() {
+        let store = BgJobStore::new();
+        let tools: Vec<Arc<dyn Tool>> = vec![Arc::new(PathTool)];
+        let tool = BgRunTool::new(store.clone(), Arc::new(tools));
+        let policy = execution_policy::ExecutionPolicy::new(
+            "public",
+            &crate::config::ExecutionPolicyConfig {
+                allowed_paths: vec!["public".into()],
+                ..crate::config::ExecutionPolicyConfig::default()
+            },
+            std::path::Path::new("/srv/agent/workspace"),
+        );
+
+        let result = execution_policy::scope(Some(Arc::new(policy)), async {
+            tool.execute(serde_json::json!({
+                "tool": "file_read",
+                "arguments": {"path": "/etc/passwd"}
+            }))
+            .await
+        })
+        .await
+        .unwrap();
+
+        assert!(!result.success);
+        assert!(result
+            .error
+            .unwrap()
+            .contains("outside the allowed directories"));
+        assert_eq!(store.running_count().await, 0);
+    }
+
     #[test]
     fn format_bg_result() {
         let job = BgJob {
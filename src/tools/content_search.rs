@@ -97,6 +97,10 @@ impl Tool for ContentSearchTool {
         })
     }
 
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         // --- Parse parameters ---
         let pattern = args
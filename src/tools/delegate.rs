@@ -849,6 +849,14 @@ impl Tool for ToolArcRef {
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         self.inner.execute(args).await
     }
+
+    fn is_parallel_safe(&self) -> bool {
+        self.inner.is_parallel_safe()
+    }
+
+    fn is_idempotent(&self) -> bool {
+        self.inner.is_idempotent()
+    }
 }
 
 struct NoopObserver;
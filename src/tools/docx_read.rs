@@ -103,6 +103,10 @@ impl Tool for DocxReadTool {
         })
     }
 
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let path = args
             .get("path")
@@ -219,7 +219,7 @@ impl Tool for FileEditTool {
             "properties": {
                 "path": {
                     "type": "string",
-                    "description": "Path to the file. Relative paths resolve from workspace; outside paths require policy allowlist."
+                    "description": "Path to the file. Relative paths resolve from workspace; outside paths require policy allowlist. Paths starting with $ZEROCLAW_ARTIFACT_DIR/ go to this conversation's scratch directory, meant for intermediate files."
                 },
                 "old_string": {
                     "type": "string",
@@ -234,12 +234,27 @@ impl Tool for FileEditTool {
         })
     }
 
+    fn is_parallel_safe(&self) -> bool {
+        false
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         // ── 1. Extract parameters ──────────────────────────────────
         let path = args
             .get("path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
+        let path = match crate::agent::artifacts::expand_path(path, &self.security.workspace_dir) {
+            Ok(path) => path,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(e.to_string()),
+                });
+            }
+        };
+        let path = path.as_str();
 
         let old_string = args
             .get("old_string")
@@ -55,7 +55,7 @@ impl Tool for FileReadTool {
             "properties": {
                 "path": {
                     "type": "string",
-                    "description": "Path to the file. Relative paths resolve from workspace; outside paths require policy allowlist."
+                    "description": "Path to the file. Relative paths resolve from workspace; outside paths require policy allowlist. Paths starting with $ZEROCLAW_ARTIFACT_DIR/ go to this conversation's scratch directory, meant for intermediate files."
                 },
                 "offset": {
                     "type": "integer",
@@ -70,11 +70,26 @@ impl Tool for FileReadTool {
         })
     }
 
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let path = args
             .get("path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
+        let path = match crate::agent::artifacts::expand_path(path, &self.security.workspace_dir) {
+            Ok(path) => path,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(e.to_string()),
+                });
+            }
+        };
+        let path = path.as_str();
 
         if self.security.is_rate_limited() {
             return Ok(ToolResult {
@@ -49,7 +49,7 @@ impl Tool for FileWriteTool {
             "properties": {
                 "path": {
                     "type": "string",
-                    "description": "Path to the file. Relative paths resolve from workspace; outside paths require policy allowlist."
+                    "description": "Path to the file. Relative paths resolve from workspace; outside paths require policy allowlist. Paths starting with $ZEROCLAW_ARTIFACT_DIR/ go to this conversation's scratch directory, meant for intermediate files."
                 },
                 "content": {
                     "type": "string",
@@ -60,11 +60,26 @@ impl Tool for FileWriteTool {
         })
     }
 
+    fn is_parallel_safe(&self) -> bool {
+        false
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let path = args
             .get("path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
+        let path = match crate::agent::artifacts::expand_path(path, &self.security.workspace_dir) {
+            Ok(path) => path,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(e.to_string()),
+                });
+            }
+        };
+        let path = path.as_str();
 
         let content = args
             .get("content")
@@ -294,6 +309,30 @@ mod tests {
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
+    #[tokio::test]
+    async fn file_write_resolves_the_artifact_dir() {
+        let dir = std::env::temp_dir().join("zeroclaw_test_file_write_artifacts");
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+
+        let tool = FileWriteTool::new(test_security(dir.clone()));
+        let artifact_dir = dir.join("artifacts/conv");
+        let result = crate::agent::artifacts::scope(
+            Some(artifact_dir.clone()),
+            tool.execute(json!({"path": "$ZEROCLAW_ARTIFACT_DIR/draft.md", "content": "draft"})),
+        )
+        .await
+        .unwrap();
+        assert!(result.success, "{:?}", result.error);
+
+        let content = tokio::fs::read_to_string(artifact_dir.join("draft.md"))
+            .await
+            .unwrap();
+        assert_eq!(content, "draft");
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
     #[tokio::test]
     async fn file_write_creates_parent_dirs() {
         let dir = std::env::temp_dir().join("zeroclaw_test_file_write_nested");
@@ -480,6 +480,10 @@ impl Tool for GitOperationsTool {
         })
     }
 
+    fn is_parallel_safe(&self) -> bool {
+        false
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let operation = match args.get("operation").and_then(|v| v.as_str()) {
             Some(op) => op,
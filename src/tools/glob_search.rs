@@ -42,6 +42,10 @@ impl Tool for GlobSearchTool {
         })
     }
 
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let pattern = args
             .get("pattern")
@@ -0,0 +1,234 @@
+use super::social_search::SocialSearchTool;
+use super::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use chrono_tz::Tz;
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::memory::engagement::{self, EngagementFilter};
+
+/// Default look-back window in days.
+const DEFAULT_DAYS: u32 = 14;
+
+/// Longest look-back window in days.
+const MAX_DAYS: u32 = 90;
+
+/// Group activity and engagement from social.db.
+///
+/// Gives the agent the busiest hours and days of its Nostr groups, how busy
+/// the current hour usually is, top contributors and its own reply rate, so
+/// it can judge when a proactive post is likely to be read. Opens social.db
+/// read-only; the counters are written by the Nostr channel.
+pub struct GroupActivityTool {
+    conn: Option<Arc<Mutex<Connection>>>,
+}
+
+impl GroupActivityTool {
+    /// Create a new GroupActivityTool reading `social.db` in the given directory.
+    pub fn new(config_dir: &Path) -> Self {
+        let conn = SocialSearchTool::open_readonly(&config_dir.join("social.db"));
+        Self { conn }
+    }
+}
+
+#[async_trait]
+impl Tool for GroupActivityTool {
+    fn name(&self) -> &str {
+        "group_activity"
+    }
+
+    fn description(&self) -> &str {
+        "Activity of your Nostr groups: busiest hours and days, how busy the \
+         current hour usually is, top contributors and how many messages you \
+         answered. Consult it before posting proactively to pick a good time."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "group": {
+                    "type": "string",
+                    "description": "Group id (default: all groups)"
+                },
+                "days": {
+                    "type": "integer",
+                    "description": "Days to look back (default: 14, max: 90)"
+                },
+                "timezone": {
+                    "type": "string",
+                    "description": "IANA timezone for hours and days (default: UTC)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let group = args
+            .get("group")
+            .and_then(|v| v.as_str())
+            .map(|g| g.trim().trim_start_matches('#').to_string())
+            .filter(|g| !g.is_empty());
+
+        #[allow(clippy::cast_possible_truncation)]
+        let days = args
+            .get("days")
+            .and_then(serde_json::Value::as_u64)
+            .map_or(DEFAULT_DAYS, |d| d.clamp(1, u64::from(MAX_DAYS)) as u32);
+
+        let tz = match args.get("timezone").and_then(|v| v.as_str()) {
+            Some(name) => match name.parse::<Tz>() {
+                Ok(tz) => tz,
+                Err(_) => {
+                    return Ok(ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some(format!("Invalid IANA timezone: {name}")),
+                    });
+                }
+            },
+            None => Tz::UTC,
+        };
+
+        let Some(ref conn) = self.conn else {
+            return Ok(ToolResult {
+                success: true,
+                output: "Social database not available (no social.db found).".into(),
+                error: None,
+            });
+        };
+
+        let now = chrono::Utc::now();
+        let filter = EngagementFilter {
+            since: now.timestamp() - i64::from(days) * 86_400,
+            until: now.timestamp(),
+            group,
+            tz,
+        };
+        let groups = {
+            let db = conn.lock();
+            match engagement::group_engagement(&db, &filter) {
+                Ok(groups) => groups,
+                Err(e) => {
+                    return Ok(ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some(format!("Group activity lookup failed: {e}")),
+                    });
+                }
+            }
+        };
+
+        if groups.is_empty() {
+            return Ok(ToolResult {
+                success: true,
+                output: format!("No group activity in the last {days} day(s)."),
+                error: None,
+            });
+        }
+
+        let now = now.with_timezone(&tz);
+        let output = groups
+            .iter()
+            .map(|group| engagement::summary(group, now, days))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::message_index::{self, IndexableMessage};
+    use crate::memory::social;
+
+    fn test_conn() -> Arc<Mutex<Connection>> {
+        let conn = Connection::open_in_memory().unwrap();
+        social::create_social_tables(&conn).unwrap();
+        message_index::create_message_tables(&conn).unwrap();
+        engagement::create_engagement_tables(&conn).unwrap();
+        Arc::new(Mutex::new(conn))
+    }
+
+    fn seed(conn: &Arc<Mutex<Connection>>, group: &str, messages: usize) {
+        let db = conn.lock();
+        let now = chrono::Utc::now().timestamp();
+        for i in 0..messages {
+            message_index::index_message(
+                &db,
+                &IndexableMessage {
+                    event_id: format!("{group}{i}"),
+                    sender_hex: "aabbccdd".into(),
+                    group_id: Some(group.into()),
+                    content: format!("a message long enough to be indexed {i}"),
+                    created_at: now - 3600,
+                    kind: 9,
+                },
+            )
+            .unwrap();
+            engagement::record_seen(&db, group, now - 3600).unwrap();
+        }
+        engagement::record_answered(&db, group, now - 3600).unwrap();
+    }
+
+    #[tokio::test]
+    async fn no_db_returns_not_available() {
+        let tool = GroupActivityTool { conn: None };
+        let result = tool.execute(json!({})).await.unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("not available"));
+    }
+
+    #[tokio::test]
+    async fn empty_db_reports_no_activity() {
+        let tool = GroupActivityTool {
+            conn: Some(test_conn()),
+        };
+        let result = tool.execute(json!({"days": 7})).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "No group activity in the last 7 day(s).");
+    }
+
+    #[tokio::test]
+    async fn summarizes_groups_most_active_first() {
+        let conn = test_conn();
+        seed(&conn, "ops", 1);
+        seed(&conn, "dev", 4);
+
+        let tool = GroupActivityTool { conn: Some(conn) };
+        let result = tool.execute(json!({})).await.unwrap();
+        assert!(result.success);
+        assert!(result
+            .output
+            .starts_with("#dev: 4 indexed message(s) in the last 14 day(s)"));
+        assert!(result
+            .output
+            .contains("you answered 1 of 4 message(s) seen (25%)"));
+        assert!(result.output.contains("#ops: 1"));
+
+        let result = tool.execute(json!({"group": "#ops"})).await.unwrap();
+        assert!(result.output.starts_with("#ops:"));
+        assert!(!result.output.contains("#dev"));
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_timezone() {
+        let tool = GroupActivityTool {
+            conn: Some(test_conn()),
+        };
+        let result = tool
+            .execute(json!({"timezone": "Mars/Olympus"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Mars/Olympus"));
+    }
+}
@@ -41,6 +41,10 @@ impl Tool for MemoryForgetTool {
         })
     }
 
+    fn is_parallel_safe(&self) -> bool {
+        false
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let key = args
             .get("key")
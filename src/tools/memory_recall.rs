@@ -1,4 +1,5 @@
 use super::traits::{Tool, ToolResult};
+use crate::memory::collective::ARCHIVE_QUERY_PREFIX;
 use crate::memory::Memory;
 use async_trait::async_trait;
 use serde_json::json;
@@ -37,6 +38,10 @@ impl Tool for MemoryRecallTool {
                 "limit": {
                     "type": "integer",
                     "description": "Max results to return (default: 5)"
+                },
+                "include_archived": {
+                    "type": "boolean",
+                    "description": "Also search archived memories that have not been used in months and restore matches (default: false)"
                 }
             },
             "required": ["query"]
@@ -55,7 +60,18 @@ impl Tool for MemoryRecallTool {
             .and_then(serde_json::Value::as_u64)
             .map_or(5, |v| v as usize);
 
-        match self.memory.recall(query, limit, None).await {
+        let include_archived = args
+            .get("include_archived")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        // Only the collective backend has an archive tier.
+        let query = if include_archived && self.memory.name() == "collective" {
+            format!("{ARCHIVE_QUERY_PREFIX}{query}")
+        } else {
+            query.to_string()
+        };
+
+        match self.memory.recall(&query, limit, None).await {
             Ok(entries) if entries.is_empty() => Ok(ToolResult {
                 success: true,
                 output: "No memories found matching that query.".into(),
@@ -55,6 +55,10 @@ impl Tool for MemoryStoreTool {
         })
     }
 
+    fn is_parallel_safe(&self) -> bool {
+        false
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let key = args
             .get("key")
@@ -44,6 +44,7 @@ pub mod file_read;
 pub mod file_write;
 pub mod git_operations;
 pub mod glob_search;
+pub mod group_activity;
 #[cfg(feature = "hardware")]
 pub mod hardware_board_info;
 #[cfg(feature = "hardware")]
@@ -70,6 +71,7 @@ pub mod process;
 pub mod proxy_config;
 pub mod pushover;
 pub mod quota_tools;
+pub mod result_cache;
 pub mod schedule;
 pub mod schema;
 pub mod screenshot;
@@ -119,6 +121,7 @@ pub use file_read::FileReadTool;
 pub use file_write::FileWriteTool;
 pub use git_operations::GitOperationsTool;
 pub use glob_search::GlobSearchTool;
+pub use group_activity::GroupActivityTool;
 #[cfg(feature = "hardware")]
 pub use hardware_board_info::HardwareBoardInfoTool;
 #[cfg(feature = "hardware")]
@@ -202,6 +205,14 @@ impl Tool for ArcDelegatingTool {
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         self.inner.execute(args).await
     }
+
+    fn is_parallel_safe(&self) -> bool {
+        self.inner.is_parallel_safe()
+    }
+
+    fn is_idempotent(&self) -> bool {
+        self.inner.is_idempotent()
+    }
 }
 
 fn boxed_registry_from_arcs(tools: Vec<Arc<dyn Tool>>) -> Vec<Box<dyn Tool>> {
@@ -795,6 +806,9 @@ pub fn all_tools_with_runtime(
         }
     }
 
+    // Answer repeated idempotent calls from `[tool_cache]` when enabled.
+    let tool_arcs = result_cache::with_result_cache(tool_arcs, &root_config.tool_cache);
+
     // Attach background execution wrappers to the finalized registry.
     // This ensures `bg_run` / `bg_status` are available anywhere the
     // runtime tool graph is used.
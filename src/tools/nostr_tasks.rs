@@ -1,83 +1,215 @@
 //! Nostr task management tool for the agent.
 //!
-//! Exposes task creation, status updates, and listing as an agent-callable tool
-//! via the Nostr event protocol (kind 1621 for tasks, 1630-1637 for status).
-//! Tasks use sequential SNOW-N IDs (e.g. SNOW-1, SNOW-2).
+//! Exposes the task board (see [`crate::memory::task_board`]) as an
+//! agent-callable tool so tasks can be managed conversationally: create,
+//! update, close, list and show tasks with assignees, due dates and a group.
+//! Changes are stored in `social.db` and published by the Nostr channel
+//! (kind 1621 for tasks, 1630-1637 for status). Tasks use sequential SNOW-N
+//! IDs (e.g. SNOW-1, SNOW-2).
 
 use async_trait::async_trait;
+use parking_lot::Mutex;
+use rusqlite::Connection;
 use serde_json::json;
-use std::path::{Path, PathBuf};
+use std::fmt::Write as _;
+use std::path::Path;
 use std::sync::Arc;
+use tracing::warn;
 
 use super::traits::{Tool, ToolResult};
+use crate::memory::social;
+use crate::memory::task_board::{self, NewTask, TaskChange, TaskFilter};
+use crate::security::policy::ToolOperation;
 use crate::security::SecurityPolicy;
 
 /// Tool for managing Nostr-native tasks.
-///
-/// Supports creating tasks (kind 1621), updating task status (kinds 1630-1637),
-/// and listing tasks from the local task store.
 pub struct NostrTaskTool {
     security: Arc<SecurityPolicy>,
-    workspace_dir: PathBuf,
+    conn: Option<Arc<Mutex<Connection>>>,
 }
 
 impl NostrTaskTool {
-    pub fn new(security: Arc<SecurityPolicy>, workspace_dir: &Path) -> Self {
-        Self {
-            security,
-            workspace_dir: workspace_dir.to_path_buf(),
+    /// Open the board in `social.db` under `config_dir`, importing a
+    /// `nostr_tasks.json` left in `workspace_dir`.
+    pub fn new(security: Arc<SecurityPolicy>, workspace_dir: &Path, config_dir: &Path) -> Self {
+        let conn = match task_board::open(config_dir, workspace_dir) {
+            Ok(conn) => Some(Arc::new(Mutex::new(conn))),
+            Err(e) => {
+                warn!("Task board unavailable: {e:#}");
+                None
+            }
+        };
+        Self { security, conn }
+    }
+
+    fn failure(error: impl Into<String>) -> ToolResult {
+        ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(error.into()),
         }
     }
 
-    /// Load tasks from the local JSON store.
-    fn load_tasks(&self) -> anyhow::Result<Vec<serde_json::Value>> {
-        let path = self.workspace_dir.join("nostr_tasks.json");
-        if !path.exists() {
-            return Ok(Vec::new());
+    fn success(output: String) -> ToolResult {
+        ToolResult {
+            success: true,
+            output,
+            error: None,
         }
-        let data = std::fs::read_to_string(&path)?;
-        let tasks: Vec<serde_json::Value> = serde_json::from_str(&data)?;
-        Ok(tasks)
     }
 
-    /// Save tasks to the local JSON store.
-    fn save_tasks(&self, tasks: &[serde_json::Value]) -> anyhow::Result<()> {
-        let path = self.workspace_dir.join("nostr_tasks.json");
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
+    /// `assignees` as an array or comma-separated string, resolved to hex.
+    fn assignees(
+        conn: &Connection,
+        args: &serde_json::Value,
+    ) -> anyhow::Result<Option<Vec<String>>> {
+        let names: Vec<String> = match args.get("assignees") {
+            None | Some(serde_json::Value::Null) => return Ok(None),
+            Some(serde_json::Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(str::to_string)
+                .collect(),
+            Some(v) => v
+                .as_str()
+                .unwrap_or_default()
+                .split(',')
+                .map(str::to_string)
+                .collect(),
+        };
+        let mut hexes = Vec::new();
+        for name in names.iter().filter(|n| !n.trim().is_empty()) {
+            let hex = task_board::resolve_assignee(conn, name)?;
+            if !hexes.contains(&hex) {
+                hexes.push(hex);
+            }
         }
-        let json = serde_json::to_string_pretty(tasks)?;
-        std::fs::write(&path, json)?;
-        Ok(())
+        Ok(Some(hexes))
     }
 
-    /// Derive the next SNOW-N number from existing tasks.
-    fn next_task_number(&self, tasks: &[serde_json::Value]) -> u64 {
-        let max = tasks
-            .iter()
-            .filter_map(|t| t.get("id").and_then(|v| v.as_str()))
-            .filter_map(|id| id.strip_prefix("SNOW-"))
-            .filter_map(|n| n.parse::<u64>().ok())
-            .max()
-            .unwrap_or(0);
-        max + 1
+    /// `due` as a date; `none` or an empty string clears it.
+    fn due(args: &serde_json::Value) -> anyhow::Result<Option<Option<i64>>> {
+        match args.get("due").and_then(|v| v.as_str()).map(str::trim) {
+            None => Ok(None),
+            Some("" | "none") => Ok(Some(None)),
+            Some(due) => Ok(Some(Some(task_board::parse_due(due)?))),
+        }
     }
 
-    /// Normalize a task ID to SNOW-N format.
-    fn normalize_task_id(input: &str) -> String {
-        let trimmed = input.trim();
-        if let Ok(n) = trimmed.parse::<u64>() {
-            return format!("SNOW-{n}");
+    fn str_arg<'a>(args: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+        args.get(key)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    }
+
+    fn create(&self, conn: &Connection, args: &serde_json::Value) -> anyhow::Result<ToolResult> {
+        let Some(title) = Self::str_arg(args, "title") else {
+            return Ok(Self::failure("title is required for create"));
+        };
+        let task = NewTask {
+            title: title.to_string(),
+            description: Self::str_arg(args, "description")
+                .unwrap_or_default()
+                .to_string(),
+            status: Self::str_arg(args, "status").map(str::to_string),
+            group_id: Self::str_arg(args, "group").map(|g| g.trim_start_matches('#').to_string()),
+            assignees: Self::assignees(conn, args)?.unwrap_or_default(),
+            due_at: Self::due(args)?.flatten(),
+        };
+        let task = task_board::add_task(conn, &task, chrono::Utc::now().timestamp())?;
+        Ok(Self::success(format!(
+            "Created {}. It will be published to the Nostr relays as a kind 1621 event.",
+            task_board::format_task_line(conn, &task)
+        )))
+    }
+
+    fn update(
+        &self,
+        conn: &Connection,
+        args: &serde_json::Value,
+        status: Option<&str>,
+    ) -> anyhow::Result<ToolResult> {
+        let Some(raw_id) = Self::str_arg(args, "task_id") else {
+            return Ok(Self::failure("task_id is required"));
+        };
+        let Some(number) = task_board::parse_task_number(raw_id) else {
+            return Ok(Self::failure(format!("Invalid task id: {raw_id}")));
+        };
+        let change = TaskChange {
+            status: status
+                .or_else(|| Self::str_arg(args, "status"))
+                .map(str::to_string),
+            detail: Self::str_arg(args, "detail")
+                .unwrap_or_default()
+                .to_string(),
+            assignees: Self::assignees(conn, args)?,
+            due_at: Self::due(args)?,
+        };
+        if change.status.is_none() && change.assignees.is_none() && change.due_at.is_none() {
+            return Ok(Self::failure(
+                "Nothing to update: give a status, assignees or due date",
+            ));
         }
-        if let Some(n) = trimmed
-            .to_uppercase()
-            .strip_prefix("SNOW-")
-            .and_then(|s| s.parse::<u64>().ok())
-        {
-            return format!("SNOW-{n}");
+        match task_board::update_task(conn, number, &change, chrono::Utc::now().timestamp())? {
+            Some(task) => Ok(Self::success(format!(
+                "Updated {}",
+                task_board::format_task_line(conn, &task)
+            ))),
+            None => Ok(Self::failure(format!("Task not found: SNOW-{number}"))),
         }
-        // Legacy: support old task-{timestamp} IDs
-        trimmed.to_string()
+    }
+
+    fn list(&self, conn: &Connection, args: &serde_json::Value) -> anyhow::Result<ToolResult> {
+        let filter = TaskFilter {
+            status: Self::str_arg(args, "filter_status").map(str::to_string),
+            group_id: Self::str_arg(args, "filter_group")
+                .map(|g| g.trim_start_matches('#').to_string()),
+            assignee: Self::str_arg(args, "filter_assignee")
+                .map(|a| task_board::resolve_assignee(conn, a))
+                .transpose()?,
+            include_closed: args
+                .get("include_closed")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        };
+        let tasks = task_board::list_tasks(conn, &filter)?;
+        if tasks.is_empty() {
+            return Ok(Self::success("No tasks found.".into()));
+        }
+        let mut output = format!("{} task(s):\n", tasks.len());
+        for task in &tasks {
+            let _ = writeln!(output, "  {}", task_board::format_task_line(conn, task));
+        }
+        Ok(Self::success(output))
+    }
+
+    fn show(&self, conn: &Connection, args: &serde_json::Value) -> anyhow::Result<ToolResult> {
+        let Some(number) = Self::str_arg(args, "task_id").and_then(task_board::parse_task_number)
+        else {
+            return Ok(Self::failure("task_id is required for show"));
+        };
+        let Some(task) = task_board::get_task(conn, number)? else {
+            return Ok(Self::failure(format!("Task not found: SNOW-{number}")));
+        };
+        let mut output = task_board::format_task_line(conn, &task);
+        if !task.description.is_empty() {
+            let _ = write!(output, "\n{}", task.description);
+        }
+        for update in task_board::task_history(conn, number)? {
+            let when = chrono::DateTime::from_timestamp(update.created_at, 0)
+                .map_or_else(String::new, |t| t.format("%Y-%m-%d %H:%M").to_string());
+            let by = update
+                .author
+                .as_deref()
+                .map(|hex| format!(" by {}", social::display_name_or_short(conn, hex)))
+                .unwrap_or_default();
+            let _ = write!(output, "\n  {when}: {}{by}", update.status);
+            if !update.detail.is_empty() {
+                let _ = write!(output, " — {}", update.detail);
+            }
+        }
+        Ok(Self::success(output))
     }
 }
 
@@ -88,9 +220,12 @@ impl Tool for NostrTaskTool {
     }
 
     fn description(&self) -> &str {
-        "Manage Nostr-native tasks. Actions: create (new task), update (change status), list (show tasks). \
-         Task IDs use SNOW-N format (e.g. SNOW-1). \
-         Task statuses: draft, queued, executing, blocked, review, done, failed, cancelled."
+        "Manage the shared Nostr task board. Actions: create (new task), update (change status, \
+         assignees or due date), done (close a task), list (open tasks; filters optional), \
+         show (task details and history). Task IDs use SNOW-N format (e.g. SNOW-1). \
+         Task statuses: draft, queued, executing, blocked, review, done, failed, cancelled. \
+         Assignees are npubs, hex pubkeys or contact names; due dates are YYYY-MM-DD. \
+         In a group conversation, pass the group id so the task is tagged with it."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -99,7 +234,7 @@ impl Tool for NostrTaskTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["create", "update", "list"],
+                    "enum": ["create", "update", "done", "list", "show"],
                     "description": "Action to perform"
                 },
                 "title": {
@@ -112,200 +247,85 @@ impl Tool for NostrTaskTool {
                 },
                 "task_id": {
                     "type": "string",
-                    "description": "Task ID, e.g. SNOW-1 (for update)"
+                    "description": "Task ID, e.g. SNOW-1 (for update, done, show)"
                 },
                 "status": {
                     "type": "string",
                     "enum": ["draft", "queued", "executing", "blocked", "review", "done", "failed", "cancelled"],
-                    "description": "New status (for update)"
+                    "description": "Initial status (for create, default draft) or new status (for update)"
                 },
                 "detail": {
                     "type": "string",
-                    "description": "Status detail/note (for update)"
+                    "description": "Status detail/note (for update, done)"
+                },
+                "assignees": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Assignees as npubs, hex pubkeys or contact names (for create, update; replaces the current list)"
+                },
+                "due": {
+                    "type": "string",
+                    "description": "Due date YYYY-MM-DD (for create, update; 'none' clears it)"
+                },
+                "group": {
+                    "type": "string",
+                    "description": "Group id the task belongs to (for create)"
                 },
                 "filter_status": {
                     "type": "string",
                     "description": "Filter by status (for list)"
+                },
+                "filter_group": {
+                    "type": "string",
+                    "description": "Filter by group id (for list)"
+                },
+                "filter_assignee": {
+                    "type": "string",
+                    "description": "Filter by assignee (for list)"
+                },
+                "include_closed": {
+                    "type": "boolean",
+                    "description": "Also list done, cancelled and failed tasks (for list)"
                 }
             },
             "required": ["action"]
         })
     }
 
+    fn is_parallel_safe(&self) -> bool {
+        false
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("");
-
-        match action {
-            "create" => {
-                let title = args
-                    .get("title")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("Untitled task");
-                let description = args
-                    .get("description")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
-
-                let mut tasks = self.load_tasks()?;
-                let number = self.next_task_number(&tasks);
-                let task_id = format!("SNOW-{number}");
-                let now = chrono::Utc::now().to_rfc3339();
-
-                let task = json!({
-                    "id": task_id,
-                    "title": title,
-                    "description": description,
-                    "status": "draft",
-                    "created_at": now,
-                    "updated_at": now,
-                    "kind": 1621,
-                    "status_history": [{
-                        "status": "draft",
-                        "kind": 1633,
-                        "timestamp": now,
-                    }]
-                });
-
-                tasks.push(task);
-                self.save_tasks(&tasks)?;
-
-                Ok(ToolResult {
-                    success: true,
-                    output: format!("Created {task_id}: {title}"),
-                    error: None,
-                })
+        if matches!(action, "create" | "update" | "done") {
+            if let Err(error) = self
+                .security
+                .enforce_tool_operation(ToolOperation::Act, "nostr_task")
+            {
+                return Ok(Self::failure(error));
             }
-
-            "update" => {
-                let raw_id = match args.get("task_id").and_then(|v| v.as_str()) {
-                    Some(id) => id,
-                    None => {
-                        return Ok(ToolResult {
-                            success: false,
-                            output: String::new(),
-                            error: Some("task_id is required for update".into()),
-                        })
-                    }
-                };
-                let task_id = Self::normalize_task_id(raw_id);
-
-                let new_status = match args.get("status").and_then(|v| v.as_str()) {
-                    Some(s) => s,
-                    None => {
-                        return Ok(ToolResult {
-                            success: false,
-                            output: String::new(),
-                            error: Some("status is required for update".into()),
-                        })
-                    }
-                };
-
-                let detail = args.get("detail").and_then(|v| v.as_str()).unwrap_or("");
-
-                let status_kind = match new_status {
-                    "queued" => 1630,
-                    "done" => 1631,
-                    "cancelled" => 1632,
-                    "draft" => 1633,
-                    "executing" => 1634,
-                    "blocked" => 1635,
-                    "review" => 1636,
-                    "failed" => 1637,
-                    _ => {
-                        return Ok(ToolResult {
-                            success: false,
-                            output: String::new(),
-                            error: Some(format!("Unknown status: {new_status}")),
-                        })
-                    }
-                };
-
-                let mut tasks = self.load_tasks()?;
-                let now = chrono::Utc::now().to_rfc3339();
-
-                let task = tasks
-                    .iter_mut()
-                    .find(|t| t.get("id").and_then(|v| v.as_str()) == Some(&task_id));
-
-                match task {
-                    Some(task) => {
-                        task["status"] = json!(new_status);
-                        task["updated_at"] = json!(now);
-                        if let Some(history) = task.get_mut("status_history") {
-                            if let Some(arr) = history.as_array_mut() {
-                                arr.push(json!({
-                                    "status": new_status,
-                                    "kind": status_kind,
-                                    "detail": detail,
-                                    "timestamp": now,
-                                }));
-                            }
-                        }
-                        self.save_tasks(&tasks)?;
-                        Ok(ToolResult {
-                            success: true,
-                            output: format!("Updated {task_id} to {new_status}"),
-                            error: None,
-                        })
-                    }
-                    None => Ok(ToolResult {
-                        success: false,
-                        output: String::new(),
-                        error: Some(format!("Task not found: {task_id}")),
-                    }),
-                }
-            }
-
-            "list" => {
-                let filter_status = args.get("filter_status").and_then(|v| v.as_str());
-
-                let tasks = self.load_tasks()?;
-                let filtered: Vec<&serde_json::Value> = if let Some(status) = filter_status {
-                    tasks
-                        .iter()
-                        .filter(|t| t.get("status").and_then(|v| v.as_str()) == Some(status))
-                        .collect()
-                } else {
-                    tasks.iter().collect()
-                };
-
-                if filtered.is_empty() {
-                    return Ok(ToolResult {
-                        success: true,
-                        output: "No tasks found.".into(),
-                        error: None,
-                    });
-                }
-
-                let mut output = format!("{} task(s):\n", filtered.len());
-                for task in &filtered {
-                    let id = task.get("id").and_then(|v| v.as_str()).unwrap_or("?");
-                    let title = task.get("title").and_then(|v| v.as_str()).unwrap_or("?");
-                    let status = task.get("status").and_then(|v| v.as_str()).unwrap_or("?");
-                    let updated = task
-                        .get("updated_at")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("?");
-                    output.push_str(&format!(
-                        "  [{status}] {id}: {title} (updated: {updated})\n"
-                    ));
-                }
-
-                Ok(ToolResult {
-                    success: true,
-                    output,
-                    error: None,
-                })
-            }
-
-            _ => Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!(
-                    "Unknown action: {action}. Use create, update, or list."
-                )),
-            }),
         }
+        let Some(conn) = &self.conn else {
+            return Ok(Self::failure(
+                "Task board not available — social.db could not be opened.",
+            ));
+        };
+        let conn = conn.lock();
+
+        let result = match action {
+            "create" => self.create(&conn, &args),
+            "update" => self.update(&conn, &args, None),
+            "done" => self.update(&conn, &args, Some("done")),
+            "list" => self.list(&conn, &args),
+            "show" => self.show(&conn, &args),
+            _ => {
+                return Ok(Self::failure(format!(
+                    "Unknown action: {action}. Use create, update, done, list, or show."
+                )))
+            }
+        };
+        Ok(result.unwrap_or_else(|e| Self::failure(format!("{e:#}"))))
     }
 }
 
@@ -317,7 +337,7 @@ mod tests {
     fn setup() -> (NostrTaskTool, TempDir) {
         let tmp = TempDir::new().unwrap();
         let security = Arc::new(SecurityPolicy::default());
-        let tool = NostrTaskTool::new(security, tmp.path());
+        let tool = NostrTaskTool::new(security, tmp.path(), tmp.path());
         (tool, tmp)
     }
 
@@ -362,6 +382,39 @@ mod tests {
         assert!(result.output.contains("SNOW-2"));
     }
 
+    #[tokio::test]
+    async fn create_with_group_assignee_and_due_date() {
+        let (tool, _tmp) = setup();
+        let hex = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+        let result = tool
+            .execute(json!({
+                "action": "create",
+                "title": "Ship it",
+                "group": "#dev",
+                "assignees": [hex],
+                "due": "2026-03-02"
+            }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("#dev"));
+        assert!(result.output.contains("due 2026-03-02"));
+
+        let mine = tool
+            .execute(json!({"action": "list", "filter_assignee": hex, "filter_group": "dev"}))
+            .await
+            .unwrap();
+        assert!(mine.output.contains("Ship it"));
+
+        let bad = tool
+            .execute(json!({"action": "create", "title": "X", "assignees": ["nobody"]}))
+            .await
+            .unwrap();
+        assert!(!bad.success);
+        assert!(bad.error.unwrap().contains("Unknown assignee"));
+    }
+
     #[tokio::test]
     async fn update_status() {
         let (tool, _tmp) = setup();
@@ -376,6 +429,12 @@ mod tests {
             .unwrap();
         assert!(result.success);
         assert!(result.output.contains("executing"));
+
+        let nothing = tool
+            .execute(json!({"action": "update", "task_id": "SNOW-1"}))
+            .await
+            .unwrap();
+        assert!(!nothing.success);
     }
 
     #[tokio::test]
@@ -394,6 +453,35 @@ mod tests {
         assert!(result.output.contains("SNOW-1"));
     }
 
+    #[tokio::test]
+    async fn done_closes_and_hides_the_task() {
+        let (tool, _tmp) = setup();
+
+        tool.execute(json!({"action": "create", "title": "Finish me"}))
+            .await
+            .unwrap();
+        let result = tool
+            .execute(json!({"action": "done", "task_id": "SNOW-1", "detail": "shipped"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("[done]"));
+
+        let open = tool.execute(json!({"action": "list"})).await.unwrap();
+        assert!(open.output.contains("No tasks found"));
+        let all = tool
+            .execute(json!({"action": "list", "include_closed": true}))
+            .await
+            .unwrap();
+        assert!(all.output.contains("Finish me"));
+
+        let shown = tool
+            .execute(json!({"action": "show", "task_id": "1"}))
+            .await
+            .unwrap();
+        assert!(shown.output.contains("done — shipped"));
+    }
+
     #[tokio::test]
     async fn update_missing_task() {
         let (tool, _tmp) = setup();
@@ -421,4 +509,18 @@ mod tests {
         assert!(result.success);
         assert!(result.output.contains("No tasks found"));
     }
+
+    #[tokio::test]
+    async fn legacy_json_tasks_are_imported() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(
+            tmp.path().join("nostr_tasks.json"),
+            json!([{"id": "SNOW-5", "title": "Old task", "status": "queued"}]).to_string(),
+        )
+        .unwrap();
+        let tool = NostrTaskTool::new(Arc::new(SecurityPolicy::default()), tmp.path(), tmp.path());
+
+        let result = tool.execute(json!({"action": "list"})).await.unwrap();
+        assert!(result.output.contains("[queued] SNOW-5: Old task"));
+    }
 }
@@ -59,6 +59,10 @@ impl Tool for PdfReadTool {
         })
     }
 
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let path = args
             .get("path")
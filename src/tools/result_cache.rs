@@ -0,0 +1,335 @@
+//! Tool result cache (`[tool_cache]`).
+//!
+//! [`with_result_cache`] wraps every tool of a registry. Successful results
+//! of idempotent tools are kept per tool, keyed by the call arguments, and
+//! returned for identical calls until they expire. Running a tool that is
+//! not parallel-safe clears the cache, since it may have changed what the
+//! cached reads saw. Lookups are reported to cost attribution, so
+//! `snowclaw stats --by tool` shows hits and misses next to tool latency.
+
+use super::traits::{Tool, ToolResult};
+use crate::config::ToolCacheConfig;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+struct CachedResult {
+    result: ToolResult,
+    stored_at: Instant,
+    /// Insertion order, for evicting the oldest result.
+    seq: u64,
+}
+
+/// Results shared by all tools of one registry, by tool name and then by
+/// serialized arguments.
+struct ToolResultCache {
+    config: ToolCacheConfig,
+    entries: Mutex<HashMap<String, HashMap<String, CachedResult>>>,
+    next_seq: AtomicU64,
+}
+
+impl ToolResultCache {
+    fn new(config: ToolCacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+            next_seq: AtomicU64::new(0),
+        }
+    }
+
+    /// TTL and capacity for `tool`, or `None` when its results are not cached.
+    fn policy(&self, tool: &str) -> Option<(Duration, usize)> {
+        let policy = self.config.tools.get(tool);
+        if policy.is_some_and(|p| p.bypass) {
+            return None;
+        }
+        let ttl_secs = policy
+            .and_then(|p| p.ttl_secs)
+            .unwrap_or(self.config.ttl_secs);
+        let max_entries = policy
+            .and_then(|p| p.max_entries)
+            .unwrap_or(self.config.max_entries);
+        (ttl_secs > 0 && max_entries > 0).then(|| (Duration::from_secs(ttl_secs), max_entries))
+    }
+
+    fn get(&self, tool: &str, key: &str, ttl: Duration, now: Instant) -> Option<ToolResult> {
+        let mut entries = self.entries.lock();
+        let results = entries.get_mut(tool)?;
+        let cached = results.get(key)?;
+        if now.saturating_duration_since(cached.stored_at) < ttl {
+            return Some(cached.result.clone());
+        }
+        results.remove(key);
+        None
+    }
+
+    fn put(&self, tool: &str, key: String, result: ToolResult, max_entries: usize) {
+        let mut entries = self.entries.lock();
+        let results = entries.entry(tool.to_string()).or_default();
+        results.remove(&key);
+        while results.len() >= max_entries {
+            let Some(oldest) = results
+                .iter()
+                .min_by_key(|(_, cached)| cached.seq)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            results.remove(&oldest);
+        }
+        results.insert(
+            key,
+            CachedResult {
+                result,
+                stored_at: Instant::now(),
+                seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+            },
+        );
+    }
+
+    fn clear(&self) {
+        self.entries.lock().clear();
+    }
+}
+
+struct CachedTool {
+    inner: Arc<dyn Tool>,
+    cache: Arc<ToolResultCache>,
+}
+
+#[async_trait]
+impl Tool for CachedTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        self.inner.parameters_schema()
+    }
+
+    fn is_parallel_safe(&self) -> bool {
+        self.inner.is_parallel_safe()
+    }
+
+    fn is_idempotent(&self) -> bool {
+        self.inner.is_idempotent()
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let name = self.inner.name();
+        let policy = self
+            .inner
+            .is_idempotent()
+            .then(|| self.cache.policy(name))
+            .flatten();
+        let Some((ttl, max_entries)) = policy else {
+            let result = self.inner.execute(args).await;
+            if !self.inner.is_parallel_safe() {
+                self.cache.clear();
+            }
+            return result;
+        };
+
+        let key = args.to_string();
+        if let Some(result) = self.cache.get(name, &key, ttl, Instant::now()) {
+            crate::cost::attribution::record_tool_cache(name, true);
+            return Ok(result);
+        }
+        crate::cost::attribution::record_tool_cache(name, false);
+        let result = self.inner.execute(args).await?;
+        if result.success {
+            self.cache.put(name, key, result.clone(), max_entries);
+        }
+        Ok(result)
+    }
+}
+
+/// Wrap `tools` in a shared result cache when `[tool_cache]` is enabled.
+pub fn with_result_cache(
+    tools: Vec<Arc<dyn Tool>>,
+    config: &ToolCacheConfig,
+) -> Vec<Arc<dyn Tool>> {
+    if !config.enabled {
+        return tools;
+    }
+    let cache = Arc::new(ToolResultCache::new(config.clone()));
+    tools
+        .into_iter()
+        .map(|inner| {
+            Arc::new(CachedTool {
+                inner,
+                cache: Arc::clone(&cache),
+            }) as Arc<dyn Tool>
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::ToolCachePolicy;
+    use std::sync::atomic::AtomicUsize;
+
+    struct CountingTool {
+        name: &'static str,
+        idempotent: bool,
+        parallel_safe: bool,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Tool for CountingTool {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn description(&self) -> &str {
+            "counts executions"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        fn is_parallel_safe(&self) -> bool {
+            self.parallel_safe
+        }
+
+        fn is_idempotent(&self) -> bool {
+            self.idempotent
+        }
+
+        async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(ToolResult {
+                success: args["fail"].as_bool() != Some(true),
+                output: format!("{} #{n}", self.name),
+                error: None,
+            })
+        }
+    }
+
+    fn enabled() -> ToolCacheConfig {
+        ToolCacheConfig {
+            enabled: true,
+            ..ToolCacheConfig::default()
+        }
+    }
+
+    fn registry(config: &ToolCacheConfig) -> (Vec<Arc<dyn Tool>>, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let tool = |name, idempotent, parallel_safe| -> Arc<dyn Tool> {
+            Arc::new(CountingTool {
+                name,
+                idempotent,
+                parallel_safe,
+                calls: Arc::clone(&calls),
+            })
+        };
+        let tools = vec![
+            tool("web_search", true, true),
+            tool("http_request", false, true),
+            tool("shell", false, false),
+        ];
+        (with_result_cache(tools, config), calls)
+    }
+
+    async fn run(tools: &[Arc<dyn Tool>], index: usize, args: serde_json::Value) -> String {
+        tools[index].execute(args).await.unwrap().output
+    }
+
+    #[tokio::test]
+    async fn repeated_idempotent_calls_are_cached() {
+        let (tools, calls) = registry(&enabled());
+        let query = serde_json::json!({"query": "rust"});
+
+        assert_eq!(run(&tools, 0, query.clone()).await, "web_search #1");
+        assert_eq!(run(&tools, 0, query.clone()).await, "web_search #1");
+        assert_eq!(
+            run(&tools, 0, serde_json::json!({"query": "go"})).await,
+            "web_search #2"
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        run(&tools, 1, query.clone()).await;
+        run(&tools, 1, query).await;
+        assert_eq!(
+            calls.load(Ordering::SeqCst),
+            4,
+            "non-idempotent tools always run"
+        );
+    }
+
+    #[tokio::test]
+    async fn failures_are_not_cached_and_stateful_tools_clear_the_cache() {
+        let (tools, calls) = registry(&enabled());
+        let failing = serde_json::json!({"query": "x", "fail": true});
+        run(&tools, 0, failing.clone()).await;
+        run(&tools, 0, failing).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let query = serde_json::json!({"query": "rust"});
+        run(&tools, 0, query.clone()).await;
+        run(&tools, 2, serde_json::json!({"command": "touch x"})).await;
+        assert_eq!(run(&tools, 0, query).await, "web_search #5");
+    }
+
+    #[tokio::test]
+    async fn bypassed_tools_and_disabled_cache_run_every_call() {
+        let mut config = enabled();
+        config.tools.insert(
+            "web_search".into(),
+            ToolCachePolicy {
+                bypass: true,
+                ..ToolCachePolicy::default()
+            },
+        );
+        for config in [config, ToolCacheConfig::default()] {
+            let (tools, calls) = registry(&config);
+            let query = serde_json::json!({"query": "rust"});
+            run(&tools, 0, query.clone()).await;
+            run(&tools, 0, query).await;
+            assert_eq!(calls.load(Ordering::SeqCst), 2);
+        }
+    }
+
+    #[test]
+    fn entries_expire_and_evict_the_oldest() {
+        let mut config = enabled();
+        config.tools.insert(
+            "web_fetch".into(),
+            ToolCachePolicy {
+                ttl_secs: Some(10),
+                max_entries: Some(2),
+                ..ToolCachePolicy::default()
+            },
+        );
+        let cache = ToolResultCache::new(config);
+        let (ttl, max_entries) = cache.policy("web_fetch").unwrap();
+        assert_eq!((ttl, max_entries), (Duration::from_secs(10), 2));
+        assert_eq!(
+            cache.policy("web_search"),
+            Some((Duration::from_secs(300), 64))
+        );
+
+        let result = |output: &str| ToolResult {
+            success: true,
+            output: output.into(),
+            error: None,
+        };
+        for key in ["a", "b", "c"] {
+            cache.put("web_fetch", key.into(), result(key), max_entries);
+        }
+        let now = Instant::now();
+        assert!(cache.get("web_fetch", "a", ttl, now).is_none());
+        assert_eq!(cache.get("web_fetch", "c", ttl, now).unwrap().output, "c");
+        assert!(cache.get("web_fetch", "c", ttl, now + ttl).is_none());
+    }
+}
@@ -166,6 +166,10 @@ impl Tool for ShellTool {
         })
     }
 
+    fn is_parallel_safe(&self) -> bool {
+        false
+    }
+
     #[allow(clippy::incompatible_msrv)]
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let command = extract_command_argument(&args)
@@ -233,6 +237,9 @@ impl Tool for ShellTool {
                 cmd.env(&var, val);
             }
         }
+        if let Some(dir) = crate::agent::artifacts::current_dir() {
+            cmd.env(crate::agent::artifacts::ARTIFACT_DIR_ENV, dir);
+        }
 
         let result =
             tokio::time::timeout(Duration::from_secs(SHELL_TIMEOUT_SECS), cmd.output()).await;
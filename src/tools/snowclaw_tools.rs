@@ -4,7 +4,7 @@
 //! the Snowclaw fork are registered here.
 
 use crate::security::SecurityPolicy;
-use crate::tools::{AgentLessonTool, NostrTaskTool, SocialSearchTool, Tool};
+use crate::tools::{AgentLessonTool, GroupActivityTool, NostrTaskTool, SocialSearchTool, Tool};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -18,7 +18,9 @@ pub(crate) fn register_snowclaw_tools(
     tools.push(Arc::new(NostrTaskTool::new(
         security.clone(),
         workspace_dir,
+        config_dir,
     )));
     tools.push(Arc::new(SocialSearchTool::new(config_dir)));
     tools.push(Arc::new(AgentLessonTool::new(config_dir)));
+    tools.push(Arc::new(GroupActivityTool::new(config_dir)));
 }
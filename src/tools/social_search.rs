@@ -30,7 +30,9 @@ impl SocialSearchTool {
         Self { conn }
     }
 
-    fn open_readonly(db_path: &PathBuf) -> Option<Arc<Mutex<Connection>>> {
+    /// Open `social.db` read-only, unlocking it when encrypted. `None` when
+    /// the file is missing or can't be opened.
+    pub(crate) fn open_readonly(db_path: &PathBuf) -> Option<Arc<Mutex<Connection>>> {
         if !db_path.exists() {
             return None;
         }
@@ -46,6 +48,11 @@ impl SocialSearchTool {
             }
         };
 
+        if let Err(e) = crate::security::db_encryption::apply_key(&conn) {
+            warn!("Failed to unlock social.db for search: {e}");
+            return None;
+        }
+
         if let Err(e) = conn.execute_batch("PRAGMA busy_timeout = 3000;") {
             warn!("Failed to set social.db pragmas: {e}");
         }
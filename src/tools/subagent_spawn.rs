@@ -12,6 +12,7 @@ use super::traits::{Tool, ToolResult};
 use crate::config::{DelegateAgentConfig, SubAgentsConfig};
 use crate::observability::traits::{Observer, ObserverEvent, ObserverMetric};
 use crate::providers::{self, ChatMessage, Provider};
+use crate::security::execution_policy;
 use crate::security::policy::ToolOperation;
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
@@ -349,8 +350,11 @@ impl Tool for SubAgentSpawnTool {
         let registry = self.registry.clone();
         let sid = session_id.clone();
         let mut bg_load_lease = load_lease;
+        // Task-locals do not cross `tokio::spawn`; keep the conversation's
+        // execution policy applied to the sub-agent's tool calls.
+        let policy = execution_policy::current();
 
-        let handle = tokio::spawn(async move {
+        let task = async move {
             let result = if is_agentic {
                 run_agentic_background(
                     &agent_name_owned,
@@ -386,7 +390,8 @@ impl Tool for SubAgentSpawnTool {
                     bg_load_lease.mark_failure();
                 }
             }
-        });
+        };
+        let handle = tokio::spawn(execution_policy::scope(policy, task));
 
         // Store the handle for cancellation
         self.registry.set_handle(&session_id, handle);
@@ -495,6 +500,14 @@ impl Tool for ToolArcRef {
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         self.inner.execute(args).await
     }
+
+    fn is_parallel_safe(&self) -> bool {
+        self.inner.is_parallel_safe()
+    }
+
+    fn is_idempotent(&self) -> bool {
+        self.inner.is_idempotent()
+    }
 }
 
 struct NoopObserver;
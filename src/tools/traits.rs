@@ -32,6 +32,21 @@ pub trait Tool: Send + Sync {
     /// Execute the tool with given arguments
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult>;
 
+    /// Whether calls may overlap with other calls from the same LLM turn.
+    /// Tools that change shared state (files, repositories, memory, shell)
+    /// return `false`: they run after every earlier call of the turn and
+    /// before every later one.
+    fn is_parallel_safe(&self) -> bool {
+        true
+    }
+
+    /// Whether repeating a call with the same arguments returns the same
+    /// result and has no side effects. Only idempotent tools are answered
+    /// from the tool result cache (`[tool_cache]`).
+    fn is_idempotent(&self) -> bool {
+        false
+    }
+
     /// Get the full spec for LLM registration
     fn spec(&self) -> ToolSpec {
         ToolSpec {
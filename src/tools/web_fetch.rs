@@ -134,7 +134,7 @@ impl WebFetchTool {
 
     /// Strips noisy structural HTML elements (nav, scripts, footers, etc.) before text
     /// extraction to reduce boilerplate in the LLM output.
-    fn strip_noise_elements(html: &str) -> anyhow::Result<String> {
+    pub(crate) fn strip_noise_elements(html: &str) -> anyhow::Result<String> {
         // Rust regex does not support backreferences, so run one pass per tag.
         // OnceLock stores Result<_, String> so that a compile failure is surfaced as an
         // error rather than a panic. String is used instead of anyhow::Error because it
@@ -429,6 +429,10 @@ impl Tool for WebFetchTool {
         })
     }
 
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let url = args
             .get("url")
@@ -0,0 +1,103 @@
+//! `snowclaw top`: live dashboard of the running agent.
+//!
+//! Polls the daemon's local API (`GET /api/nostr/live`) and sends owner
+//! commands (`POST /api/nostr/command`) for the dashboard's keybindings, so
+//! it works against any running daemon without touching its databases.
+
+pub mod tui;
+
+use anyhow::{bail, Context, Result};
+use std::time::Duration;
+
+use crate::channels::nostr_live::LiveView;
+use crate::config::Config;
+
+/// Environment variable holding the gateway bearer token.
+pub const TOKEN_ENV: &str = "SNOWCLAW_GATEWAY_TOKEN";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Blocking client for the daemon's local API.
+pub struct LiveClient {
+    base_url: String,
+    token: Option<String>,
+    http: reqwest::blocking::Client,
+}
+
+impl LiveClient {
+    pub fn new(base_url: &str, token: Option<String>) -> Result<Self> {
+        let http = reqwest::blocking::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+            http,
+        })
+    }
+
+    fn authorized(
+        &self,
+        request: reqwest::blocking::RequestBuilder,
+    ) -> reqwest::blocking::RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Current live view of the Nostr channel.
+    pub fn live(&self) -> Result<LiveView> {
+        let response = self
+            .authorized(self.http.get(format!("{}/api/nostr/live", self.base_url)))
+            .send()
+            .with_context(|| format!("Daemon not reachable at {}", self.base_url))?;
+        Ok(api_result(response)?.json()?)
+    }
+
+    /// Run an owner command such as `/mute #dev 1h`; returns the reply text.
+    pub fn command(&self, text: &str) -> Result<String> {
+        let response = self
+            .authorized(
+                self.http
+                    .post(format!("{}/api/nostr/command", self.base_url)),
+            )
+            .json(&serde_json::json!({ "command": text }))
+            .send()
+            .with_context(|| format!("Daemon not reachable at {}", self.base_url))?;
+        let body: serde_json::Value = api_result(response)?.json()?;
+        Ok(body["reply"].as_str().unwrap_or_default().to_string())
+    }
+}
+
+/// Turn an error status into its `{"error": …}` message.
+fn api_result(response: reqwest::blocking::Response) -> Result<reqwest::blocking::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body: serde_json::Value = response.json().unwrap_or_default();
+    match body["error"].as_str() {
+        Some(error) => bail!("{error} ({status})"),
+        None => bail!("Daemon returned {status}"),
+    }
+}
+
+/// Gateway URL from `[gateway]`; a wildcard bind is reached on loopback.
+pub fn default_url(config: &Config) -> String {
+    let host = match config.gateway.host.as_str() {
+        "0.0.0.0" | "::" | "[::]" => "127.0.0.1",
+        host => host,
+    };
+    format!("http://{host}:{}", config.gateway.port)
+}
+
+/// Handle `snowclaw top`.
+pub fn run(config: &Config, url: Option<String>, token: Option<String>) -> Result<()> {
+    let url = url.unwrap_or_else(|| default_url(config));
+    let token = token.or_else(|| std::env::var(TOKEN_ENV).ok());
+    let client = LiveClient::new(&url, token)?;
+    // Fail before taking over the terminal when the daemon is not there.
+    client.live()?;
+    tui::run(&client)
+}
@@ -0,0 +1,460 @@
+use super::LiveClient;
+use crate::channels::nostr_live::LiveView;
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Gauge, Paragraph, Row, Table, Wrap},
+    Frame, Terminal,
+};
+use std::io::stdout;
+use std::time::{Duration, Instant};
+
+/// How often the live view is fetched.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Mute length for the `m` key.
+const MUTE_KEY_DURATION: &str = "1h";
+
+struct App {
+    view: LiveView,
+    /// Last fetch error; the previous view stays on screen.
+    error: Option<String>,
+    /// Index into the status groups.
+    selected: usize,
+    /// Command being typed after `:`.
+    prompt: Option<String>,
+    /// Reply to the last command.
+    notice: Option<String>,
+}
+
+impl App {
+    fn selected_group(&self) -> Option<&str> {
+        let groups = &self.view.status.as_ref()?.groups;
+        groups
+            .get(self.selected.min(groups.len().saturating_sub(1)))
+            .map(|g| g.name.as_str())
+    }
+
+    fn group_count(&self) -> usize {
+        self.view.status.as_ref().map_or(0, |s| s.groups.len())
+    }
+}
+
+/// Run the dashboard until `q`.
+pub fn run(client: &LiveClient) -> Result<()> {
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+
+    let backend = ratatui::backend::CrosstermBackend::new(stdout());
+    let mut terminal = Terminal::new(backend)?;
+
+    let result = run_loop(&mut terminal, client);
+
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+
+    result
+}
+
+fn run_loop(
+    terminal: &mut Terminal<ratatui::backend::CrosstermBackend<std::io::Stdout>>,
+    client: &LiveClient,
+) -> Result<()> {
+    let mut app = App {
+        view: LiveView::default(),
+        error: None,
+        selected: 0,
+        prompt: None,
+        notice: None,
+    };
+    let mut last_poll: Option<Instant> = None;
+
+    loop {
+        if last_poll.is_none_or(|at| at.elapsed() >= POLL_INTERVAL) {
+            match client.live() {
+                Ok(view) => {
+                    app.view = view;
+                    app.error = None;
+                }
+                Err(e) => app.error = Some(e.to_string()),
+            }
+            last_poll = Some(Instant::now());
+        }
+
+        terminal.draw(|frame| draw(frame, &app))?;
+
+        if !event::poll(Duration::from_millis(200))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        if let Some(prompt) = app.prompt.as_mut() {
+            match key.code {
+                KeyCode::Esc => app.prompt = None,
+                KeyCode::Enter => {
+                    let text = app.prompt.take().unwrap_or_default();
+                    app.notice = Some(run_command(client, &text));
+                    last_poll = None;
+                }
+                KeyCode::Backspace => {
+                    prompt.pop();
+                }
+                KeyCode::Char(c) => prompt.push(c),
+                _ => {}
+            }
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Up | KeyCode::Char('k') => app.selected = app.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                app.selected = (app.selected + 1).min(app.group_count().saturating_sub(1));
+            }
+            KeyCode::Char(':') => app.prompt = Some("/".to_string()),
+            KeyCode::Char(c) => {
+                if let Some(text) = app.selected_group().and_then(|g| group_command(c, g)) {
+                    app.notice = Some(run_command(client, &text));
+                    last_poll = None;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Owner command bound to `key` for the selected group.
+fn group_command(key: char, group: &str) -> Option<String> {
+    match key {
+        'm' => Some(format!("/mute #{group} {MUTE_KEY_DURATION}")),
+        'u' => Some(format!("/unmute #{group}")),
+        's' => Some(format!("/stop #{group}")),
+        'r' => Some(format!("/resume #{group}")),
+        'c' => Some(format!("/catchup #{group}")),
+        _ => None,
+    }
+}
+
+fn run_command(client: &LiveClient, text: &str) -> String {
+    match client.command(text) {
+        Ok(reply) => format!("{text}: {}", reply.replace('\n', " · ")),
+        Err(e) => format!("{text}: {e}"),
+    }
+}
+
+fn draw(frame: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Header
+            Constraint::Min(8),    // Groups, relays and messages
+            Constraint::Length(8), // Pending replies and security flags
+            Constraint::Length(3), // Budget
+            Constraint::Length(3), // Footer
+        ])
+        .split(frame.area());
+
+    draw_header(frame, chunks[0], app);
+
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(chunks[1]);
+    let left = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(middle[0]);
+    draw_groups(frame, left[0], app);
+    draw_relays(frame, left[1], &app.view);
+    draw_messages(frame, middle[1], app);
+
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(chunks[2]);
+    draw_pending(frame, bottom[0], &app.view);
+    draw_flags(frame, bottom[1], &app.view);
+
+    draw_budget(frame, chunks[3], &app.view);
+    draw_footer(frame, chunks[4], app);
+}
+
+fn panel(title: &str) -> Block<'_> {
+    Block::default()
+        .title(format!(" {title} "))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray))
+}
+
+fn draw_header(frame: &mut Frame, area: Rect, app: &App) {
+    let now = chrono::Utc::now().timestamp().unsigned_abs();
+    let line = match (&app.view.status, &app.error) {
+        (_, Some(error)) => Line::from(Span::styled(
+            format!(" {error}"),
+            Style::default().fg(Color::Red),
+        )),
+        (Some(status), None) => {
+            let connected = status
+                .relays
+                .iter()
+                .filter(|r| r.status == "connected")
+                .count();
+            Line::from(format!(
+                " Relays: {connected}/{}    Outbox: {}    Running actions: {}    Updated {}s ago",
+                status.relays.len(),
+                status.outbox_pending,
+                status.running_actions,
+                now.saturating_sub(status.updated_at)
+            ))
+        }
+        (None, None) => Line::from(" Waiting for the Nostr channel…"),
+    };
+    let version = app
+        .view
+        .status
+        .as_ref()
+        .map_or(String::new(), |s| format!(" {}", s.version));
+    let block = Block::default()
+        .title(format!(" Snowclaw{version} — top "))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    frame.render_widget(Paragraph::new(line).block(block), area);
+}
+
+fn draw_groups(frame: &mut Frame, area: Rect, app: &App) {
+    let selected = app.selected_group();
+    let rows: Vec<Row> = app
+        .view
+        .status
+        .iter()
+        .flat_map(|s| &s.groups)
+        .map(|g| {
+            let mute = g
+                .muted_for_secs
+                .map(|secs| {
+                    crate::channels::nostr_commands::format_duration(Duration::from_secs(secs))
+                })
+                .unwrap_or_default();
+            let mode_color = match g.mode.as_str() {
+                "none" => Color::Red,
+                "all" => Color::Green,
+                _ => Color::White,
+            };
+            let mut row = Row::new(vec![
+                Cell::from(format!("#{}", g.name)),
+                Cell::from(g.mode.clone()).style(Style::default().fg(mode_color)),
+                Cell::from(mute).style(Style::default().fg(Color::Yellow)),
+            ]);
+            if Some(g.name.as_str()) == selected {
+                row = row.style(Style::default().add_modifier(Modifier::REVERSED));
+            }
+            row
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(12),
+            Constraint::Length(8),
+            Constraint::Length(8),
+        ],
+    )
+    .header(
+        Row::new(vec!["Group", "Mode", "Muted"]).style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+    )
+    .block(panel("Groups"));
+    frame.render_widget(table, area);
+}
+
+fn draw_relays(frame: &mut Frame, area: Rect, view: &LiveView) {
+    let lines: Vec<Line> = view
+        .status
+        .iter()
+        .flat_map(|s| &s.relays)
+        .map(|r| {
+            let color = if r.status == "connected" {
+                Color::Green
+            } else {
+                Color::Red
+            };
+            Line::from(vec![
+                Span::styled("● ", Style::default().fg(color)),
+                Span::raw(format!("{} ({})", r.url, r.status)),
+            ])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines).block(panel("Relays")), area);
+}
+
+fn draw_messages(frame: &mut Frame, area: Rect, app: &App) {
+    let group = app.selected_group();
+    let title = group.map_or("Recent messages".to_string(), |g| {
+        format!("Recent messages — #{g}")
+    });
+    let messages = group
+        .and_then(|g| app.view.messages.get(g))
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    // Newest at the bottom, as many as fit
+    let visible = usize::from(area.height.saturating_sub(2));
+    let lines: Vec<Line> = messages
+        .iter()
+        .skip(messages.len().saturating_sub(visible))
+        .map(|m| {
+            let sender_style = if m.is_owner {
+                Style::default().fg(Color::Magenta)
+            } else {
+                Style::default().fg(Color::Cyan)
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", clock(m.created_at)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(format!("{}: ", m.sender), sender_style),
+                Span::raw(m.content.replace('\n', " ")),
+            ])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines).block(panel(&title)), area);
+}
+
+fn draw_pending(frame: &mut Frame, area: Rect, view: &LiveView) {
+    let now = chrono::Utc::now().timestamp().unsigned_abs();
+    let lines: Vec<Line> = view
+        .pending_replies
+        .iter()
+        .map(|p| {
+            let target = if p.target.starts_with('#') {
+                p.target.clone()
+            } else {
+                format!("DM {:.12}", p.target)
+            };
+            Line::from(format!(
+                " {target} ← {} ({}s)",
+                p.sender,
+                now.saturating_sub(p.since)
+            ))
+        })
+        .collect();
+    let title = format!("Pending replies ({})", lines.len());
+    frame.render_widget(Paragraph::new(lines).block(panel(&title)), area);
+}
+
+fn draw_flags(frame: &mut Frame, area: Rect, view: &LiveView) {
+    let lines: Vec<Line> = view
+        .security_flags
+        .iter()
+        .rev()
+        .map(|f| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", clock(f.at)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(f.description.clone(), Style::default().fg(Color::Red)),
+            ])
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .block(panel("Security flags")),
+        area,
+    );
+}
+
+fn draw_budget(frame: &mut Frame, area: Rect, view: &LiveView) {
+    let block = panel("Budget today");
+    let Some(budget) = view.status.as_ref().and_then(|s| s.budget) else {
+        frame.render_widget(Paragraph::new(" Cost tracking disabled").block(block), area);
+        return;
+    };
+    let spent = (budget.daily_limit - budget.daily).max(0.0);
+    let ratio = if budget.daily_limit > 0.0 {
+        (spent / budget.daily_limit).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let color = if ratio > 0.9 {
+        Color::Red
+    } else if ratio > 0.7 {
+        Color::Yellow
+    } else {
+        Color::Green
+    };
+    let gauge = Gauge::default()
+        .block(block)
+        .gauge_style(Style::default().fg(color))
+        .ratio(ratio)
+        .label(format!(
+            "${spent:.2} of ${:.2} today · ${:.2} of ${:.2} left this month",
+            budget.daily_limit, budget.monthly, budget.monthly_limit
+        ));
+    frame.render_widget(gauge, area);
+}
+
+fn draw_footer(frame: &mut Frame, area: Rect, app: &App) {
+    let line = match (&app.prompt, &app.notice) {
+        (Some(prompt), _) => Line::from(vec![
+            Span::styled(" command: ", Style::default().fg(Color::Yellow)),
+            Span::raw(format!("{prompt}▏")),
+        ]),
+        (None, Some(notice)) => Line::from(Span::styled(
+            format!(" {notice}"),
+            Style::default().fg(Color::White),
+        )),
+        (None, None) => Line::from(Span::styled(
+            " ↑/↓ group   m mute 1h   u unmute   s stop   r resume   c catch-up   : command   q quit",
+            Style::default().fg(Color::Gray),
+        )),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(Paragraph::new(line).block(block), area);
+}
+
+/// Local `HH:MM` of a Unix timestamp.
+fn clock(ts: u64) -> String {
+    i64::try_from(ts)
+        .ok()
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+        .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_map_to_owner_commands_for_the_selected_group() {
+        assert_eq!(group_command('m', "dev").as_deref(), Some("/mute #dev 1h"));
+        assert_eq!(group_command('r', "dev").as_deref(), Some("/resume #dev"));
+        assert_eq!(group_command('x', "dev"), None);
+        for key in ['m', 'u', 's', 'r', 'c'] {
+            let text = group_command(key, "dev").unwrap();
+            assert!(
+                matches!(crate::channels::nostr_commands::parse(&text), Some(Ok(_))),
+                "{text}"
+            );
+        }
+    }
+}
@@ -1,8 +1,12 @@
 //! Self-update functionality for ZeroClaw.
 //!
-//! Downloads and installs the latest release from GitHub.
+//! Downloads and installs the latest release from GitHub. Remote updates
+//! (`control.update`) install a pinned release whose `SHA256SUMS` is signed
+//! by a pinned minisign key, and are rolled back when the restarted daemon
+//! fails its health check.
 
 use anyhow::{bail, Context, Result};
+use base64::Engine;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -12,6 +16,8 @@ use std::process::Command;
 const GITHUB_REPO: &str = "zeroclaw-labs/zeroclaw";
 const GITHUB_API_RELEASES: &str =
     "https://api.github.com/repos/zeroclaw-labs/zeroclaw/releases/latest";
+const GITHUB_API_RELEASE_TAGS: &str =
+    "https://api.github.com/repos/zeroclaw-labs/zeroclaw/releases/tags";
 
 /// Release information from GitHub API
 #[derive(Debug, serde::Deserialize)]
@@ -76,13 +82,22 @@ fn get_archive_name(target: &str) -> String {
 
 /// Fetch the latest release information from GitHub
 async fn fetch_latest_release() -> Result<Release> {
+    fetch_release(GITHUB_API_RELEASES).await
+}
+
+/// Fetch release information for a specific tag from GitHub
+async fn fetch_release_by_tag(tag: &str) -> Result<Release> {
+    fetch_release(&format!("{GITHUB_API_RELEASE_TAGS}/{tag}")).await
+}
+
+async fn fetch_release(url: &str) -> Result<Release> {
     let client = reqwest::Client::builder()
         .user_agent(format!("zeroclaw/{}", current_version()))
         .build()
         .context("Failed to create HTTP client")?;
 
     let response = client
-        .get(GITHUB_API_RELEASES)
+        .get(url)
         .send()
         .await
         .context("Failed to fetch release information from GitHub")?;
@@ -116,8 +131,15 @@ fn find_asset_for_platform(release: &Release) -> Result<&Asset> {
         })
 }
 
-/// Download and extract the binary from the release archive
-async fn download_binary(asset: &Asset, temp_dir: &Path) -> Result<PathBuf> {
+/// Download and extract the binary from the release archive.
+///
+/// When `expected_sha256` is set, the archive digest must match it before
+/// anything is extracted.
+async fn download_binary(
+    asset: &Asset,
+    temp_dir: &Path,
+    expected_sha256: Option<&str>,
+) -> Result<PathBuf> {
     let client = reqwest::Client::builder()
         .user_agent(format!("zeroclaw/{}", current_version()))
         .build()
@@ -141,6 +163,10 @@ async fn download_binary(asset: &Asset, temp_dir: &Path) -> Result<PathBuf> {
         .await
         .context("Failed to read download content")?;
 
+    if let Some(expected) = expected_sha256 {
+        verify_sha256(&archive_bytes, expected)?;
+    }
+
     fs::write(&archive_path, &archive_bytes).context("Failed to write archive to temp file")?;
 
     tracing::info!("Extracting {}...", asset.name);
@@ -175,6 +201,42 @@ async fn download_binary(asset: &Asset, temp_dir: &Path) -> Result<PathBuf> {
     Ok(binary_path)
 }
 
+/// Check that `bytes` hash to the pinned hex SHA-256 digest.
+fn verify_sha256(bytes: &[u8], expected: &str) -> Result<()> {
+    use sha2::{Digest, Sha256};
+
+    let actual = hex::encode(Sha256::digest(bytes));
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        bail!("Archive SHA-256 mismatch: expected {expected}, got {actual}");
+    }
+    Ok(())
+}
+
+/// Run `<binary> --version` and require it to succeed and report `version`.
+fn health_check_binary(binary: &Path, version: &str) -> Result<()> {
+    let output = Command::new(binary)
+        .arg("--version")
+        .output()
+        .with_context(|| format!("Failed to execute {}", binary.display()))?;
+    if !output.status.success() {
+        bail!(
+            "Health check failed for {}: exit status {}",
+            binary.display(),
+            output.status
+        );
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = version.trim_start_matches('v');
+    if !stdout.contains(expected) {
+        bail!(
+            "Health check failed for {}: expected version {expected}, got {}",
+            binary.display(),
+            stdout.trim()
+        );
+    }
+    Ok(())
+}
+
 /// Extract a tar.gz archive
 fn extract_tar_gz(archive_path: &Path, dest_dir: &Path) -> Result<()> {
     let output = Command::new("tar")
@@ -428,7 +490,7 @@ pub async fn self_update(force: bool, check_only: bool) -> Result<()> {
     let temp_dir = tempfile::tempdir().context("Failed to create temp directory")?;
 
     // Download and extract
-    let new_binary = download_binary(asset, temp_dir.path()).await?;
+    let new_binary = download_binary(asset, temp_dir.path(), None).await?;
 
     println!("Installing update...");
 
@@ -443,10 +505,464 @@ pub async fn self_update(force: bool, check_only: bool) -> Result<()> {
     Ok(())
 }
 
+/// Release asset listing the SHA-256 digest of every archive.
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+/// Detached minisign signature of [`CHECKSUMS_ASSET`].
+const CHECKSUMS_SIGNATURE_ASSET: &str = "SHA256SUMS.minisig";
+
+/// Minisign public key for release signatures, built into the binary from
+/// `SNOWCLAW_RELEASE_SIGNING_KEY`. `[update] signing_key` takes precedence.
+const RELEASE_SIGNING_KEY: Option<&str> = option_env!("SNOWCLAW_RELEASE_SIGNING_KEY");
+
+/// Marker in the config directory for an update awaiting its post-restart
+/// health check.
+const PENDING_UPDATE_FILE: &str = "update-pending.json";
+
+/// How long the restarted daemon must stay healthy to keep an update.
+const UPDATE_HEALTH_WINDOW: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// An update installed by `control.update` that the restarted daemon has
+/// not confirmed yet.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct PendingUpdate {
+    tag: String,
+    binary: PathBuf,
+    /// Copy of the binary the update replaced.
+    previous_binary: PathBuf,
+    /// Daemon starts on the new binary so far.
+    boots: u32,
+}
+
+fn pending_update_path(config: &crate::config::Config) -> PathBuf {
+    config
+        .config_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(PENDING_UPDATE_FILE)
+}
+
+/// Where the binary replaced by `control.update` is kept, next to it.
+fn previous_binary_path(current_exe: &Path) -> Result<PathBuf> {
+    let parent = current_exe
+        .parent()
+        .context("Current executable has no parent directory")?;
+    let binary_name = current_exe
+        .file_name()
+        .context("Current executable path is missing a file name")?
+        .to_string_lossy()
+        .into_owned();
+    Ok(parent.join(format!(".{binary_name}.previous")))
+}
+
+/// Digest listed for `name` in a `SHA256SUMS` file (`<hex>  <name>`, or
+/// `<hex> *<name>` for binary mode; entries may carry a directory prefix).
+fn listed_sha256<'a>(sums: &'a str, name: &str) -> Option<&'a str> {
+    sums.lines().find_map(|line| {
+        let (digest, file) = line.trim().split_once(char::is_whitespace)?;
+        let file = file.trim_start().trim_start_matches('*');
+        (file.rsplit('/').next() == Some(name)).then_some(digest)
+    })
+}
+
+/// Pinned minisign key that release checksums must be signed with.
+fn release_signing_key(config: &crate::config::Config) -> Result<&str> {
+    config
+        .update
+        .signing_key
+        .as_deref()
+        .or(RELEASE_SIGNING_KEY)
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .context(
+            "No release signing key pinned (set [update] signing_key or build with \
+             SNOWCLAW_RELEASE_SIGNING_KEY); refusing to install an unsigned release",
+        )
+}
+
+/// Download a release asset as text.
+async fn fetch_release_asset_text(release: &Release, name: &str) -> Result<String> {
+    let asset = release
+        .assets
+        .iter()
+        .find(|a| a.name == name)
+        .with_context(|| format!("Release {} has no {name}", release.tag_name))?;
+    let client = reqwest::Client::builder()
+        .user_agent(format!("zeroclaw/{}", current_version()))
+        .build()
+        .context("Failed to create HTTP client")?;
+    let response = client
+        .get(&asset.browser_download_url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to download {name}"))?;
+    if !response.status().is_success() {
+        bail!("{name} download failed with status: {}", response.status());
+    }
+    response
+        .text()
+        .await
+        .with_context(|| format!("Failed to read {name}"))
+}
+
+/// Decode a base64 line of a minisign key or signature file, which must be
+/// `len` bytes long.
+fn decode_minisign_line(line: &str, len: usize, what: &str) -> Result<Vec<u8>> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(line.trim())
+        .with_context(|| format!("Minisign {what} is not valid base64"))?;
+    if bytes.len() != len {
+        bail!("Minisign {what} has the wrong length");
+    }
+    Ok(bytes)
+}
+
+fn verify_ed25519(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, public_key)
+        .verify(message, signature)
+        .is_ok()
+}
+
+/// Verify a detached minisign signature (`.minisig` file contents) of
+/// `data` against `public_key` (the base64 line of `minisign.pub`),
+/// including the signature over its trusted comment.
+fn verify_minisign(public_key: &str, signature: &str, data: &[u8]) -> Result<()> {
+    // Key: algorithm, key ID, Ed25519 key. Signature: algorithm (`Ed`, or
+    // `ED` over the BLAKE2b-512 digest of the data), key ID, signature.
+    let key = decode_minisign_line(public_key, 42, "public key")?;
+    if &key[..2] != b"Ed" {
+        bail!("Minisign public key is not an Ed25519 key");
+    }
+
+    let mut lines = signature.lines().map(str::trim_end);
+    let sig_line = lines
+        .find(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+        .context("Minisign signature is empty")?;
+    let sig = decode_minisign_line(sig_line, 74, "signature")?;
+    let trusted_comment = lines
+        .next()
+        .and_then(|line| line.strip_prefix("trusted comment: "))
+        .context("Minisign signature has no trusted comment")?;
+    let global_sig = decode_minisign_line(
+        lines
+            .next()
+            .context("Minisign signature has no global signature")?,
+        64,
+        "global signature",
+    )?;
+
+    if sig[2..10] != key[2..10] {
+        bail!("Signature was made with a different key than the pinned one");
+    }
+    let valid = match &sig[..2] {
+        b"Ed" => verify_ed25519(&key[10..], data, &sig[10..]),
+        b"ED" => {
+            use blake2::Digest;
+            let digest = blake2::Blake2b512::digest(data);
+            verify_ed25519(&key[10..], &digest, &sig[10..])
+        }
+        _ => bail!("Unsupported minisign signature algorithm"),
+    };
+    if !valid {
+        bail!("Signature does not match the signed file");
+    }
+    let mut global = sig[10..].to_vec();
+    global.extend_from_slice(trusted_comment.as_bytes());
+    if !verify_ed25519(&key[10..], &global, &global_sig) {
+        bail!("Trusted comment signature is invalid");
+    }
+    Ok(())
+}
+
+/// Check the pinned digest against the release's published `SHA256SUMS`,
+/// after verifying its detached signature with the pinned `signing_key`.
+async fn verify_release_checksum(
+    release: &Release,
+    asset: &Asset,
+    pinned: &str,
+    signing_key: &str,
+) -> Result<()> {
+    let text = fetch_release_asset_text(release, CHECKSUMS_ASSET).await?;
+    let signature = fetch_release_asset_text(release, CHECKSUMS_SIGNATURE_ASSET).await?;
+    verify_minisign(signing_key, &signature, text.as_bytes())
+        .with_context(|| format!("{CHECKSUMS_SIGNATURE_ASSET} verification failed"))?;
+    let listed = listed_sha256(&text, &asset.name)
+        .with_context(|| format!("{CHECKSUMS_ASSET} does not list {}", asset.name))?;
+    if !listed.eq_ignore_ascii_case(pinned.trim()) {
+        bail!(
+            "Pinned sha256 does not match the release {CHECKSUMS_ASSET} entry for {}",
+            asset.name
+        );
+    }
+    Ok(())
+}
+
+/// Install a pinned release requested remotely (`control.update`).
+///
+/// The release's `SHA256SUMS` must carry a valid minisign signature by the
+/// pinned release key, and the pinned `sha256` must match both its entry
+/// and the downloaded archive; without a pinned key nothing is installed. The extracted binary is health-checked,
+/// swapped in, and health-checked again; if the second check fails the
+/// previous binary is restored. On success the previous binary is kept
+/// next to the new one and a pending marker is left for
+/// [`watch_pending_update`] to confirm or roll back after the restart,
+/// which is up to the caller.
+pub async fn apply_pinned_update(
+    config: &crate::config::Config,
+    version: &str,
+    sha256: &str,
+) -> Result<String> {
+    let version = version.trim();
+    if version.is_empty() {
+        bail!("Pinned version is required");
+    }
+    if sha256.trim().len() != 64 || !sha256.trim().chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("Pinned sha256 must be a 64-character hex digest");
+    }
+
+    let tag = if version.starts_with('v') {
+        version.to_string()
+    } else {
+        format!("v{version}")
+    };
+    if tag.trim_start_matches('v') == current_version() {
+        bail!("Already running {tag}");
+    }
+    let signing_key = release_signing_key(config)?;
+
+    let current_exe = get_current_exe()?;
+    let release = fetch_release_by_tag(&tag).await?;
+    let asset = find_asset_for_platform(&release)?;
+    verify_release_checksum(&release, asset, sha256, signing_key).await?;
+
+    let temp_dir = tempfile::tempdir().context("Failed to create temp directory")?;
+    let new_binary = download_binary(asset, temp_dir.path(), Some(sha256)).await?;
+    health_check_binary(&new_binary, &tag)?;
+
+    // Keep the running binary so the update can be rolled back, now or after
+    // the restart.
+    let previous_binary = previous_binary_path(&current_exe)?;
+    fs::copy(&current_exe, &previous_binary).context("Failed to back up current binary")?;
+
+    replace_binary(&new_binary, &current_exe)?;
+    if let Err(err) = health_check_binary(&current_exe, &tag) {
+        tracing::warn!("Installed binary failed health check, rolling back: {err}");
+        replace_binary(&previous_binary, &current_exe)
+            .context("Rollback after failed health check also failed")?;
+        return Err(err.context("Update rolled back"));
+    }
+
+    let pending = PendingUpdate {
+        tag: tag.clone(),
+        binary: current_exe.clone(),
+        previous_binary,
+        boots: 0,
+    };
+    fs::write(
+        pending_update_path(config),
+        serde_json::to_vec_pretty(&pending)?,
+    )
+    .context("Failed to record the pending update")?;
+
+    tracing::info!("Installed {tag} at {}", current_exe.display());
+    Ok(format!(
+        "updated v{} -> {tag} at {}",
+        current_version(),
+        current_exe.display()
+    ))
+}
+
+/// Confirm or roll back an update left pending by `control.update`.
+///
+/// Called when the daemon starts. The first start on the new binary must
+/// stay free of failing health components for `UPDATE_HEALTH_WINDOW`; an
+/// unhealthy window, or a second start before confirmation (the first one
+/// died), restores the previous binary and restarts the service.
+pub fn watch_pending_update(config: &crate::config::Config) {
+    let path = pending_update_path(config);
+    let Ok(raw) = fs::read(&path) else {
+        return;
+    };
+    let mut pending: PendingUpdate = match serde_json::from_slice(&raw) {
+        Ok(pending) => pending,
+        Err(e) => {
+            tracing::warn!("Ignoring unreadable {}: {e}", path.display());
+            let _ = fs::remove_file(&path);
+            return;
+        }
+    };
+    if pending.tag.trim_start_matches('v') != current_version() {
+        tracing::warn!(
+            "Pending update to {} does not match the running v{}; dropping it",
+            pending.tag,
+            current_version()
+        );
+        let _ = fs::remove_file(&path);
+        return;
+    }
+
+    pending.boots += 1;
+    if pending.boots > 1 {
+        roll_back_update(
+            config,
+            &path,
+            &pending,
+            "the daemon restarted before the update was confirmed",
+        );
+        return;
+    }
+    if let Err(e) = serde_json::to_vec_pretty(&pending)
+        .map_err(anyhow::Error::from)
+        .and_then(|raw| fs::write(&path, raw).map_err(Into::into))
+    {
+        tracing::warn!("Failed to update {}: {e}", path.display());
+    }
+
+    let config = config.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(UPDATE_HEALTH_WINDOW).await;
+        let failing: Vec<String> = crate::health::snapshot()
+            .components
+            .into_iter()
+            .filter(|(_, health)| health.status == "error")
+            .map(|(name, _)| name)
+            .collect();
+        if failing.is_empty() {
+            let _ = fs::remove_file(&path);
+            tracing::info!("Update to {} passed its health check", pending.tag);
+        } else {
+            let reason = format!("failing components: {}", failing.join(", "));
+            roll_back_update(&config, &path, &pending, &reason);
+        }
+    });
+}
+
+fn roll_back_update(
+    config: &crate::config::Config,
+    marker: &Path,
+    pending: &PendingUpdate,
+    reason: &str,
+) {
+    tracing::error!("Rolling back the update to {}: {reason}", pending.tag);
+    if let Err(e) = replace_binary(&pending.previous_binary, &pending.binary) {
+        tracing::error!("Rollback of {} failed: {e:#}", pending.binary.display());
+        return;
+    }
+    let _ = fs::remove_file(marker);
+    if let Err(e) = crate::service::restart_service(config) {
+        tracing::error!("Service restart after rollback failed: {e:#}");
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn verify_sha256_accepts_matching_digest_case_insensitively() {
+        let digest = "2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824";
+        assert!(verify_sha256(b"hello", digest).is_ok());
+        assert!(verify_sha256(b"hello!", digest).is_err());
+    }
+
+    #[tokio::test]
+    async fn apply_pinned_update_rejects_malformed_pins() {
+        let config = crate::config::Config::default();
+        assert!(apply_pinned_update(&config, "", &"a".repeat(64))
+            .await
+            .is_err());
+        assert!(apply_pinned_update(&config, "v9.9.9", "deadbeef")
+            .await
+            .is_err());
+        assert!(apply_pinned_update(&config, "v9.9.9", &"z".repeat(64))
+            .await
+            .is_err());
+    }
+
+    /// Minisign public key and a signature of `data` by the same key.
+    fn minisign_fixture(data: &[u8], prehashed: bool) -> (String, String) {
+        use ring::signature::{Ed25519KeyPair, KeyPair};
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+        let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let key_id = [7u8; 8];
+
+        let mut public = b"Ed".to_vec();
+        public.extend_from_slice(&key_id);
+        public.extend_from_slice(pair.public_key().as_ref());
+
+        let sig = if prehashed {
+            use blake2::Digest;
+            pair.sign(&blake2::Blake2b512::digest(data))
+        } else {
+            pair.sign(data)
+        };
+        let mut line = if prehashed { b"ED" } else { b"Ed" }.to_vec();
+        line.extend_from_slice(&key_id);
+        line.extend_from_slice(sig.as_ref());
+        let comment = "timestamp:1767225600\tfile:SHA256SUMS";
+        let mut global = sig.as_ref().to_vec();
+        global.extend_from_slice(comment.as_bytes());
+        let minisig = format!(
+            "untrusted comment: signature from minisign secret key\n{}\ntrusted comment: {comment}\n{}\n",
+            b64.encode(line),
+            b64.encode(pair.sign(&global)),
+        );
+        (b64.encode(public), minisig)
+    }
+
+    #[test]
+    fn verify_minisign_accepts_legacy_and_prehashed_signatures() {
+        let sums = b"aaaa  zeroclaw-x86_64-unknown-linux-gnu.tar.gz\n";
+        for prehashed in [false, true] {
+            let (key, sig) = minisign_fixture(sums, prehashed);
+            assert!(verify_minisign(&key, &sig, sums).is_ok());
+            assert!(verify_minisign(&key, &sig, b"bbbb  tampered.tar.gz\n").is_err());
+        }
+    }
+
+    #[test]
+    fn verify_minisign_rejects_other_keys_and_edited_comments() {
+        let sums = b"aaaa  zeroclaw-x86_64-unknown-linux-gnu.tar.gz\n";
+        let (key, sig) = minisign_fixture(sums, true);
+        let (other_key, _) = minisign_fixture(sums, true);
+        assert!(verify_minisign(&other_key, &sig, sums).is_err());
+
+        let edited = sig.replace("timestamp:1767225600", "timestamp:1767225601");
+        assert!(verify_minisign(&key, &edited, sums).is_err());
+        assert!(verify_minisign(&key, "", sums).is_err());
+    }
+
+    #[tokio::test]
+    async fn apply_pinned_update_refuses_without_a_signing_key() {
+        if RELEASE_SIGNING_KEY.is_some() {
+            return;
+        }
+        let config = crate::config::Config::default();
+        let err = apply_pinned_update(&config, "v9.9.9", &"a".repeat(64))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No release signing key"));
+    }
+
+    #[test]
+    fn listed_sha256_matches_archive_names() {
+        let sums = "aaaa  zeroclaw-x86_64-unknown-linux-gnu.tar.gz\n\
+                    bbbb *artifacts/zeroclaw-aarch64-apple-darwin.tar.gz\n";
+        assert_eq!(
+            listed_sha256(sums, "zeroclaw-x86_64-unknown-linux-gnu.tar.gz"),
+            Some("aaaa")
+        );
+        assert_eq!(
+            listed_sha256(sums, "zeroclaw-aarch64-apple-darwin.tar.gz"),
+            Some("bbbb")
+        );
+        assert_eq!(
+            listed_sha256(sums, "zeroclaw-x86_64-pc-windows-msvc.zip"),
+            None
+        );
+    }
+
     #[test]
     fn archive_name_uses_zip_for_windows_and_targz_elsewhere() {
         assert_eq!(
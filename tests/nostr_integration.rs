@@ -65,6 +65,15 @@ async fn nostr_integration_group_messaging() {
         persist_dir: std::path::PathBuf::from("/tmp/snowclaw-test"),
         indexed_paths: Vec::new(),
         index_interval_minutes: 60,
+        digest: zeroclaw::config::NostrDigestConfig::default(),
+        workspace_dir: std::path::PathBuf::from("/tmp/snowclaw-test"),
+        key_filter: zeroclaw::config::KeyFilterConfig::default(),
+        onboarding: zeroclaw::config::NostrOnboardingConfig::default(),
+        quota: zeroclaw::config::NostrQuotaConfig::default(),
+        answer_cache: zeroclaw::config::NostrAnswerCacheConfig::default(),
+        pipeline: zeroclaw::config::NostrPipelineConfig::default(),
+        proxy: zeroclaw::config::NostrProxyConfig::default(),
+        cost: zeroclaw::config::CostConfig::default(),
     };
 
     let agent_channel = NostrChannel::new(agent_config)